@@ -1,4 +1,93 @@
-// racoon-cli - placeholder
-pub fn placeholder() {
-    println!("racoon-cli not yet implemented");
+//! Racoon command line interface
+//!
+//! Queries the running daemons through orchd's gRPC management interface
+//! (`management.grpc_port`).
+
+use racoon_common::Config;
+use racoon_orchd::grpc::ManagementClient;
+use racoon_orchd::grpc::proto::{Event, RecentEventsRequest, ResyncRequest};
+use std::fmt::Write;
+use std::net::SocketAddr;
+
+/// URL of orchd's management interface, if `config` has it serve one
+pub fn orchd_endpoint(config: &Config) -> Option<String> {
+    let port = config.management.grpc_port?;
+    Some(format!(
+        "http://{}",
+        SocketAddr::new(config.management.grpc_addr, port)
+    ))
+}
+
+/// Notifications orchd processed last, oldest first
+pub async fn recent_events(endpoint: String) -> anyhow::Result<Vec<Event>> {
+    let mut client = ManagementClient::connect(endpoint).await?;
+    let response = client.recent_events(RecentEventsRequest {}).await?;
+    Ok(response.into_inner().events)
+}
+
+/// Have orchd rebuild APPL_DB from CONFIG_DB, returning how many VLANs it
+/// rewrote
+pub async fn resync(endpoint: String) -> anyhow::Result<u64> {
+    let mut client = ManagementClient::connect(endpoint).await?;
+    let response = client.resync(ResyncRequest {}).await?;
+    Ok(response.into_inner().vlan_count)
+}
+
+/// One line per event: Unix time, operation, key and why it failed
+pub fn format_events(events: &[Event]) -> String {
+    let mut out = String::new();
+    for event in events {
+        let _ = write!(
+            out,
+            "{}.{:03} {:<4} {}",
+            event.ts / 1000,
+            event.ts % 1000,
+            event.operation,
+            event.key
+        );
+        if let Some(error) = &event.error {
+            let _ = write!(out, " failed: {}", error);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_events() {
+        let events = [
+            Event {
+                ts: 1_700_000_000_123,
+                operation: "SET".to_string(),
+                key: "VLAN|Vlan100".to_string(),
+                error: None,
+            },
+            Event {
+                ts: 1_700_000_000_456,
+                operation: "HSET".to_string(),
+                key: "VLAN_MEMBER|Vlan100|Ethernet0".to_string(),
+                error: Some("field deltas are not supported".to_string()),
+            },
+        ];
+        assert_eq!(
+            format_events(&events),
+            "1700000000.123 SET  VLAN|Vlan100\n\
+             1700000000.456 HSET VLAN_MEMBER|Vlan100|Ethernet0 failed: field deltas are not supported\n"
+        );
+    }
+
+    #[test]
+    fn test_orchd_endpoint_needs_grpc_port() {
+        let mut config = Config::default();
+        assert_eq!(orchd_endpoint(&config), None);
+        config.management.grpc_port = Some(50051);
+        assert_eq!(
+            orchd_endpoint(&config).as_deref(),
+            Some("http://127.0.0.1:50051")
+        );
+    }
 }
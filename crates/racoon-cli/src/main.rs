@@ -0,0 +1,63 @@
+//! Racoon command line interface
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use racoon_cli::{format_events, orchd_endpoint, recent_events, resync};
+use racoon_common::Config;
+
+#[derive(Parser)]
+#[command(name = "racoon-cli", about = "Query the running Racoon daemons")]
+struct Cli {
+    /// orchd management URL; defaults to management.grpc_port of the config
+    #[arg(long)]
+    orchd: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Notifications orchd processed last, oldest first
+    Events,
+    /// Commands for orchd
+    Orch {
+        #[command(subcommand)]
+        command: OrchCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum OrchCommand {
+    /// Rebuild APPL_DB from CONFIG_DB
+    Resync,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let endpoint = match cli.orchd {
+        Some(endpoint) => endpoint,
+        None => {
+            let config_path = std::env::var("RACOON_CONFIG")
+                .unwrap_or_else(|_| "/etc/racoon/racoon.toml".to_string());
+            let config = Config::load(&config_path)
+                .with_context(|| format!("failed to load {}", config_path))?;
+            orchd_endpoint(&config).context("management.grpc_port is not set")?
+        }
+    };
+
+    match cli.command {
+        Command::Events => {
+            let events = recent_events(endpoint).await?;
+            print!("{}", format_events(&events));
+        }
+        Command::Orch {
+            command: OrchCommand::Resync,
+        } => {
+            let vlan_count = resync(endpoint).await?;
+            println!("Resynced {} VLANs into APPL_DB", vlan_count);
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,18 @@
+use crate::constants::DB_TABLE_SEPARATOR;
 use crate::error::{RacoonError, Result};
+use crate::keys::TableKeys;
+use crate::migrate::{SCHEMA_VERSION, migrate, schema_version};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::Path;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Layout of the file; older files are migrated on load
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     pub platform: PlatformConfig,
     pub database: DatabaseConfig,
     pub logging: LoggingConfig,
@@ -12,6 +20,12 @@ pub struct Config {
     pub management: ManagementConfig,
     #[serde(default)]
     pub features: FeaturesConfig,
+    #[serde(default)]
+    pub vlan: VlanDefaultsConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub counters: CountersConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +34,13 @@ pub struct PlatformConfig {
     pub sai_library: String,
     #[serde(default = "default_config_db_path")]
     pub config_db_path: String,
+    /// Number of switch ASICs; above 1 each gets its own channel namespace
+    #[serde(default = "default_asic_count")]
+    pub asic_count: u32,
+    /// Platform details file (`config/platform/*.toml`) with the hardware
+    /// capabilities to enforce; without one no limits are applied
+    #[serde(default)]
+    pub details_path: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +51,52 @@ pub struct DatabaseConfig {
     pub port: u16,
     #[serde(default = "default_db_socket")]
     pub socket: String,
+    /// Whether to connect over `host`/`port` or the unix `socket`
+    #[serde(default)]
+    pub transport: DbTransport,
+    /// Seconds without any pub/sub message (heartbeats included) before
+    /// subscribers resubscribe; 0 disables the watchdog
+    #[serde(default = "default_pubsub_watchdog_secs")]
+    pub pubsub_watchdog_secs: u64,
+    /// Separator between table name and key in APPL_DB keys
+    #[serde(default = "default_table_separator")]
+    pub table_separator: String,
+    /// Namespace of all pub/sub channels (`ns` gives `ns:VLAN_TABLE`), so
+    /// several instances can share one server; unset keeps the plain names
+    #[serde(default)]
+    pub channel_prefix: Option<String>,
+}
+
+/// How the daemons reach the database server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbTransport {
+    #[default]
+    Tcp,
+    Unix,
+}
+
+/// Pub/sub watchdog window used when the config does not set one
+pub const DEFAULT_PUBSUB_WATCHDOG: Duration = Duration::from_secs(30);
+
+impl DatabaseConfig {
+    /// Connection URL for the configured transport
+    pub fn to_url(&self) -> String {
+        match self.transport {
+            DbTransport::Tcp => format!("redis://{}:{}", self.host, self.port),
+            DbTransport::Unix => format!("unix://{}", self.socket),
+        }
+    }
+
+    /// Pub/sub watchdog window, if enabled
+    pub fn pubsub_watchdog(&self) -> Option<Duration> {
+        (self.pubsub_watchdog_secs > 0).then(|| Duration::from_secs(self.pubsub_watchdog_secs))
+    }
+
+    /// Key layout for APPL_DB tables
+    pub fn table_keys(&self) -> TableKeys {
+        TableKeys::new(self.table_separator.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,11 +107,63 @@ pub struct LoggingConfig {
     pub format: String,
     #[serde(default = "default_log_output")]
     pub output: String,
+    /// OTLP collector receiving spans, e.g. `http://127.0.0.1:4317`;
+    /// needs the `otel` feature
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServicesConfig {
     pub enabled: Vec<String>,
+    /// Sync agents run by syncd and orchd; all of them when not set
+    #[serde(default)]
+    pub agents: Option<Vec<String>>,
+}
+
+/// Sync agents that can be selected in `services.agents`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncAgent {
+    Vlan,
+    Lag,
+    Fdb,
+}
+
+impl SyncAgent {
+    pub const ALL: [SyncAgent; 3] = [SyncAgent::Vlan, SyncAgent::Lag, SyncAgent::Fdb];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SyncAgent::Vlan => "vlan",
+            SyncAgent::Lag => "lag",
+            SyncAgent::Fdb => "fdb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|agent| agent.name() == name)
+    }
+}
+
+impl ServicesConfig {
+    /// Sync agents named in `agents`, or every agent if it is not set
+    ///
+    /// Unknown names are logged and ignored.
+    pub fn sync_agents(&self) -> Vec<SyncAgent> {
+        let Some(names) = &self.agents else {
+            return SyncAgent::ALL.to_vec();
+        };
+
+        let mut agents = Vec::new();
+        for name in names {
+            match SyncAgent::from_name(name) {
+                Some(agent) if !agents.contains(&agent) => agents.push(agent),
+                Some(_) => {}
+                None => tracing::warn!("Unknown sync agent '{}' in services.agents", name),
+            }
+        }
+        agents
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +172,15 @@ pub struct ManagementConfig {
     pub rest_api_port: u16,
     #[serde(default = "default_cli_socket")]
     pub cli_socket: String,
+    /// Port of orchd's gRPC management service; disabled when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc_port: Option<u16>,
+    /// Address the gRPC management service binds to
+    ///
+    /// Loopback by default: the service is unauthenticated and can change
+    /// the configuration.
+    #[serde(default = "default_grpc_addr")]
+    pub grpc_addr: IpAddr,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -61,6 +189,94 @@ pub struct FeaturesConfig {
     pub warm_boot: bool,
     #[serde(default)]
     pub fast_reboot: bool,
+    /// Remove programmed objects from SAI on a normal shutdown; leave off
+    /// to keep them for a warm boot
+    #[serde(default)]
+    pub cleanup_on_shutdown: bool,
+    /// Milliseconds over which port attribute changes are collected and
+    /// applied together; 0 sets each change right away
+    #[serde(default)]
+    pub port_batch_window_ms: u64,
+}
+
+impl FeaturesConfig {
+    /// Port attribute batch window, if batching is enabled
+    pub fn port_batch_window(&self) -> Option<Duration> {
+        (self.port_batch_window_ms > 0).then(|| Duration::from_millis(self.port_batch_window_ms))
+    }
+}
+
+/// Default VLAN membership for access ports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VlanDefaultsConfig {
+    /// VLAN that access ports join untagged when no explicit membership exists
+    #[serde(default)]
+    pub default_vlan: Option<u16>,
+    /// Ports that receive the default membership
+    #[serde(default)]
+    pub access_ports: Vec<String>,
+    /// Provision the default memberships on first boot; later boots keep
+    /// whatever membership APPL_DB holds
+    #[serde(default = "default_provision_on_first_boot")]
+    pub provision_on_first_boot: bool,
+}
+
+impl Default for VlanDefaultsConfig {
+    fn default() -> Self {
+        Self {
+            default_vlan: None,
+            access_ports: Vec::new(),
+            provision_on_first_boot: default_provision_on_first_boot(),
+        }
+    }
+}
+
+/// Append-only file of SAI creates, removes and attribute sets, kept
+/// apart from the tracing log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// File receiving one JSON object per line; no file is written without one
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Size at which the file is rotated to `<path>.1`
+    #[serde(default = "default_audit_max_bytes")]
+    pub max_bytes: u64,
+    /// Rotated files kept (`<path>.1` is the newest)
+    #[serde(default = "default_audit_keep")]
+    pub keep: u32,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_bytes: default_audit_max_bytes(),
+            keep: default_audit_keep(),
+        }
+    }
+}
+
+/// Polling of VLAN and LAG counters into COUNTERS_DB
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountersConfig {
+    /// Seconds between polls; 0 disables polling
+    #[serde(default = "default_counter_poll_secs")]
+    pub poll_interval_secs: u64,
+}
+
+impl Default for CountersConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: default_counter_poll_secs(),
+        }
+    }
+}
+
+impl CountersConfig {
+    /// Counter poll interval, if polling is enabled
+    pub fn poll_interval(&self) -> Option<Duration> {
+        (self.poll_interval_secs > 0).then(|| Duration::from_secs(self.poll_interval_secs))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,21 +295,92 @@ pub struct CapabilitiesConfig {
     pub max_ecmp_groups: u32,
 }
 
+impl CapabilitiesConfig {
+    /// Limits enforced by the sync agents
+    pub fn limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            max_vlans: Some(self.max_vlans),
+            max_fdb_entries: Some(self.max_fdb_entries),
+        }
+    }
+}
+
+/// Hardware table sizes checked before objects are created in SAI
+///
+/// `None` leaves a table unlimited, so SAI has the final word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_vlans: Option<u32>,
+    pub max_fdb_entries: Option<u32>,
+}
+
+impl ResourceLimits {
+    /// Fail if a VLAN can't be added to `tracked` existing ones
+    pub fn check_vlans(&self, tracked: usize) -> Result<()> {
+        check_limit("VLAN", tracked, self.max_vlans)
+    }
+
+    /// Fail if an FDB entry can't be added to `tracked` existing ones
+    pub fn check_fdb_entries(&self, tracked: usize) -> Result<()> {
+        check_limit("FDB entry", tracked, self.max_fdb_entries)
+    }
+}
+
+fn check_limit(resource: &str, tracked: usize, limit: Option<u32>) -> Result<()> {
+    match limit {
+        Some(limit) if tracked >= limit as usize => Err(RacoonError::ResourceExhausted {
+            resource: resource.to_string(),
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// `[platform]` section of a platform details file
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PlatformDetailsConfig {
+pub struct PlatformInfoConfig {
     pub name: String,
     pub asic_type: String,
     pub sai_library: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformDetailsConfig {
+    pub platform: PlatformInfoConfig,
     pub hardware: HardwareConfig,
     pub port_mapping: HashMap<String, (u32, u32)>,
     pub capabilities: CapabilitiesConfig,
 }
 
 // Default value functions
+fn default_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
 fn default_config_db_path() -> String {
     "/etc/racoon/config_db.json".to_string()
 }
 
+fn default_asic_count() -> u32 {
+    1
+}
+
+fn default_provision_on_first_boot() -> bool {
+    true
+}
+
+fn default_audit_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_audit_keep() -> u32 {
+    3
+}
+
+fn default_counter_poll_secs() -> u64 {
+    10
+}
+
 fn default_db_host() -> String {
     "127.0.0.1".to_string()
 }
@@ -106,6 +393,14 @@ fn default_db_socket() -> String {
     "/var/run/racoon/database.sock".to_string()
 }
 
+fn default_pubsub_watchdog_secs() -> u64 {
+    DEFAULT_PUBSUB_WATCHDOG.as_secs()
+}
+
+fn default_table_separator() -> String {
+    DB_TABLE_SEPARATOR.to_string()
+}
+
 fn default_log_level() -> String {
     "info".to_string()
 }
@@ -126,12 +421,41 @@ fn default_cli_socket() -> String {
     "/var/run/racoon/cli.sock".to_string()
 }
 
+fn default_grpc_addr() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+/// Config of a daemon started without a config file
+const DEFAULT_CONFIG: &str = r#"
+[platform]
+name = "virtual"
+sai_library = "/usr/lib/libsai.so"
+
+[database]
+
+[logging]
+
+[services]
+enabled = []
+
+[management]
+"#;
+
+/// Every setting at its default, with all sync agents enabled
+impl Default for Config {
+    fn default() -> Self {
+        toml::from_str(DEFAULT_CONFIG).expect("built-in default config is valid")
+    }
+}
+
 impl Config {
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| RacoonError::Config(format!("Failed to read config file: {}", e)))?;
 
-        let config: Config = toml::from_str(&content)?;
+        let value: toml::Value = toml::from_str(&content)?;
+        let value = migrate(schema_version(&value)?, SCHEMA_VERSION, value)?;
+        let config: Config = value.try_into()?;
         Ok(config)
     }
 
@@ -142,6 +466,42 @@ impl Config {
         let platform: PlatformDetailsConfig = toml::from_str(&content)?;
         Ok(platform)
     }
+
+    /// Settings that differ in `other`, as `section.setting` paths
+    pub fn changed_settings(&self, other: &Config) -> Vec<String> {
+        let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(other))
+        else {
+            return Vec::new();
+        };
+
+        let mut changed = Vec::new();
+        for (section, old_section) in &old {
+            let (Some(old_section), Some(new_section)) =
+                (old_section.as_object(), new[section].as_object())
+            else {
+                continue;
+            };
+            let mut settings: Vec<&String> = old_section.keys().chain(new_section.keys()).collect();
+            settings.sort();
+            settings.dedup();
+            changed.extend(
+                settings
+                    .into_iter()
+                    .filter(|setting| old_section.get(*setting) != new_section.get(*setting))
+                    .map(|setting| format!("{}.{}", section, setting)),
+            );
+        }
+        changed
+    }
+
+    /// Resource limits from the platform details file, if one is configured
+    pub fn resource_limits(&self) -> Result<ResourceLimits> {
+        match &self.platform.details_path {
+            Some(path) => Ok(Self::load_platform(path)?.capabilities.limits()),
+            None => Ok(ResourceLimits::default()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -166,8 +526,96 @@ mod tests {
         "#;
 
         let parsed: Config = toml::from_str(config).unwrap();
+        assert_eq!(parsed.platform.asic_count, 1);
         assert_eq!(parsed.database.port, 6379);
+        assert_eq!(parsed.database.transport, DbTransport::Tcp);
+        assert_eq!(
+            parsed.database.pubsub_watchdog(),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parsed.database.table_keys(), TableKeys::default());
         assert_eq!(parsed.logging.level, "info");
         assert_eq!(parsed.management.rest_api_port, 8080);
+        assert_eq!(parsed.management.grpc_addr, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert!(!parsed.features.cleanup_on_shutdown);
+        assert!(parsed.features.port_batch_window().is_none());
+        assert!(parsed.vlan.default_vlan.is_none());
+        assert!(parsed.vlan.access_ports.is_empty());
+        assert!(parsed.vlan.provision_on_first_boot);
+        assert!(parsed.database.channel_prefix.is_none());
+        assert_eq!(
+            parsed.counters.poll_interval(),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn test_changed_settings() {
+        let config = Config::default();
+        assert!(config.changed_settings(&config.clone()).is_empty());
+
+        let mut changed = config.clone();
+        changed.logging.level = "debug".to_string();
+        changed.database.port = 6380;
+        changed.management.grpc_port = Some(50051);
+        assert_eq!(
+            config.changed_settings(&changed),
+            ["database.port", "logging.level", "management.grpc_port"]
+        );
+    }
+
+    #[test]
+    fn test_database_url() {
+        let database = |transport: &str| -> DatabaseConfig {
+            toml::from_str(&format!(
+                r#"
+                host = "10.0.0.5"
+                port = 6380
+                socket = "/run/redis/redis.sock"
+                transport = "{}"
+                "#,
+                transport
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(database("tcp").to_url(), "redis://10.0.0.5:6380");
+        assert_eq!(database("unix").to_url(), "unix:///run/redis/redis.sock");
+        assert!(toml::from_str::<DatabaseConfig>(r#"transport = "udp""#).is_err());
+    }
+
+    #[test]
+    fn test_platform_limits() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../config/platform/virtual.toml"
+        );
+        let platform = Config::load_platform(path).unwrap();
+        assert_eq!(platform.platform.asic_type, "virtual");
+
+        let limits = platform.capabilities.limits();
+        assert_eq!(limits.max_vlans, Some(4096));
+        assert!(limits.check_fdb_entries(32767).is_ok());
+        assert!(matches!(
+            limits.check_fdb_entries(32768),
+            Err(RacoonError::ResourceExhausted { limit: 32768, .. })
+        ));
+        assert!(ResourceLimits::default().check_vlans(usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_sync_agents() {
+        let services = |agents: Option<&[&str]>| ServicesConfig {
+            enabled: vec!["database".to_string(), "syncd".to_string()],
+            agents: agents.map(|agents| agents.iter().map(|s| s.to_string()).collect()),
+        };
+
+        assert_eq!(
+            services(Some(&["vlan", "lag", "vlan", "bogus"])).sync_agents(),
+            vec![SyncAgent::Vlan, SyncAgent::Lag]
+        );
+        assert!(services(Some(&[])).sync_agents().is_empty());
+        // Configs written before agent selection run every agent
+        assert_eq!(services(None).sync_agents(), SyncAgent::ALL.to_vec());
     }
 }
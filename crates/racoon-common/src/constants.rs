@@ -14,6 +14,9 @@ pub const DB_TABLE_SEPARATOR: &str = ":";
 /// Port name prefix
 pub const PORT_PREFIX: &str = "Ethernet";
 
+/// Accepted abbreviations of [`PORT_PREFIX`]
+pub const PORT_PREFIX_ALIASES: [&str; 1] = ["Eth"];
+
 /// VLAN name prefix
 pub const VLAN_PREFIX: &str = "Vlan";
 
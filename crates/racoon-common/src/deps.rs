@@ -0,0 +1,180 @@
+//! Ordering of pending operations by declared dependencies
+//!
+//! SAI objects must be created after the objects they reference: a VLAN
+//! member needs its VLAN and bridge port, a route its next hop, a next hop
+//! its router interface. [`DependencyResolver`] orders a batch of pending
+//! operations so every operation follows its dependencies.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Result of [`DependencyResolver::resolve`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution<K> {
+    /// Operations in an order that satisfies every dependency
+    pub ordered: Vec<K>,
+    /// Operations waiting on a dependency that is neither pending nor
+    /// already satisfied, directly or through another deferred operation
+    pub deferred: Vec<K>,
+    /// Operations that are part of, or wait on, a dependency cycle
+    pub cyclic: Vec<K>,
+}
+
+/// Topological sort of pending operations
+#[derive(Debug, Clone)]
+pub struct DependencyResolver<K> {
+    pending: Vec<(K, Vec<K>)>,
+}
+
+impl<K: Clone + Eq + Hash> Default for DependencyResolver<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash> DependencyResolver<K> {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add an operation that must run after `dependencies`
+    pub fn add(&mut self, key: K, dependencies: Vec<K>) {
+        self.pending.push((key, dependencies));
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Order the pending operations
+    ///
+    /// Dependencies in `satisfied` (objects that already exist) need no
+    /// pending operation. Ties keep insertion order, so independent
+    /// operations run in the order they were added.
+    pub fn resolve(&self, satisfied: &HashSet<K>) -> Resolution<K> {
+        let pending: HashSet<&K> = self.pending.iter().map(|(key, _)| key).collect();
+
+        // Operations with a dependency that can never be met
+        let mut blocked: HashSet<&K> = self
+            .pending
+            .iter()
+            .filter(|(_, deps)| {
+                deps.iter()
+                    .any(|dep| !satisfied.contains(dep) && !pending.contains(dep))
+            })
+            .map(|(key, _)| key)
+            .collect();
+
+        // Kahn's algorithm over the dependencies that are still pending
+        let mut waiting: HashMap<&K, usize> = HashMap::new();
+        let mut dependents: HashMap<&K, Vec<&K>> = HashMap::new();
+        for (key, deps) in &self.pending {
+            let open: HashSet<&K> = deps
+                .iter()
+                .filter(|dep| !satisfied.contains(*dep) && pending.contains(dep))
+                .collect();
+            for dep in &open {
+                dependents.entry(*dep).or_default().push(key);
+            }
+            waiting.insert(key, open.len());
+        }
+
+        let mut ready: VecDeque<&K> = self
+            .pending
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| waiting[key] == 0)
+            .collect();
+        let mut ordered = Vec::new();
+        let mut visited: HashSet<&K> = HashSet::new();
+
+        while let Some(key) = ready.pop_front() {
+            if !visited.insert(key) {
+                continue;
+            }
+
+            let is_blocked = blocked.contains(key);
+            if !is_blocked {
+                ordered.push(key.clone());
+            }
+
+            for dependent in dependents.get(key).into_iter().flatten() {
+                if is_blocked {
+                    blocked.insert(dependent);
+                }
+                let count = waiting.get_mut(dependent).expect("dependent is pending");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push_back(dependent);
+                }
+            }
+        }
+
+        // Whatever was never released sits on a cycle or behind one
+        let (deferred, cyclic) = self
+            .pending
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| blocked.contains(key) || !visited.contains(key))
+            .cloned()
+            .partition(|key| visited.contains(key));
+
+        Resolution {
+            ordered,
+            deferred,
+            cyclic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain() {
+        // Added in reverse: route -> next hop -> router interface
+        let mut resolver = DependencyResolver::new();
+        resolver.add("route", vec!["nexthop"]);
+        resolver.add("nexthop", vec!["rif"]);
+        resolver.add("rif", vec!["vr"]);
+        resolver.add("vlan_member", vec!["vlan", "bridge_port"]);
+        resolver.add("vlan", vec![]);
+
+        let satisfied: HashSet<&str> = ["vr", "bridge_port"].into_iter().collect();
+        let resolution = resolver.resolve(&satisfied);
+
+        assert_eq!(
+            resolution.ordered,
+            vec!["rif", "vlan", "nexthop", "vlan_member", "route"]
+        );
+        assert!(resolution.deferred.is_empty());
+        assert!(resolution.cyclic.is_empty());
+
+        // Without the virtual router the whole chain waits
+        let resolution = resolver.resolve(&["bridge_port"].into_iter().collect());
+        assert_eq!(resolution.ordered, vec!["vlan", "vlan_member"]);
+        assert_eq!(resolution.deferred, vec!["route", "nexthop", "rif"]);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut resolver = DependencyResolver::new();
+        resolver.add("a", vec!["b"]);
+        resolver.add("b", vec!["c"]);
+        resolver.add("c", vec!["a"]);
+        resolver.add("d", vec!["c"]);
+        resolver.add("e", vec![]);
+
+        let resolution = resolver.resolve(&HashSet::new());
+        assert_eq!(resolution.ordered, vec!["e"]);
+        assert!(resolution.deferred.is_empty());
+        assert_eq!(resolution.cyclic, vec!["a", "b", "c", "d"]);
+    }
+}
@@ -5,9 +5,19 @@ pub enum RacoonError {
     #[error("SAI error: {0}")]
     Sai(String),
 
+    /// A SAI call returned a failure status
+    #[error("SAI error: {message}")]
+    SaiCall { code: i32, message: String },
+
     #[error("Database error: {0}")]
     Database(String),
 
+    #[error("Database client has been shut down")]
+    DbClientShutdown,
+
+    #[error("Key not found: {0}")]
+    KeyNotFound(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -23,12 +33,22 @@ pub enum RacoonError {
     #[error("Invalid VLAN ID: {0} (must be 1-4094)")]
     InvalidVlanId(u16),
 
+    #[error("Invalid VLAN name: {0}")]
+    InvalidVlanName(String),
+
+    /// An entry's `vlanid` disagrees with the VLAN named by its key
+    #[error("VLAN entry {name} has vlanid {vlanid}")]
+    VlanIdMismatch { name: String, vlanid: u16 },
+
     #[error("FDB entry not found: {0}")]
     FdbNotFound(String),
 
     #[error("LAG {0} not found")]
     LagNotFound(String),
 
+    #[error("Host interface name '{name}' for port 0x{port:x} is already in use")]
+    HostifNameConflict { name: String, port: u64 },
+
     #[error("Invalid MAC address: {0}")]
     InvalidMacAddress(String),
 
@@ -38,6 +58,10 @@ pub enum RacoonError {
     #[error("OID not found: {0}")]
     OidNotFound(String),
 
+    /// Programming one more object would exceed a hardware table size
+    #[error("{resource} limit reached: the platform supports at most {limit}")]
+    ResourceExhausted { resource: String, limit: u32 },
+
     #[error("Invalid attribute: {0}")]
     InvalidAttribute(String),
 
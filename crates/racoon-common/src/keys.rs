@@ -0,0 +1,145 @@
+//! APPL_DB key layout
+//!
+//! APPL_DB keys join a table name and an entry key with a separator
+//! (`VLAN_TABLE:Vlan100`). The separator defaults to [`DB_TABLE_SEPARATOR`]
+//! and can be set with `database.table_separator` to share a database with
+//! producers that use a different one. Producers and consumers must build
+//! and parse keys through the same [`TableKeys`].
+//!
+//! On multi-ASIC platforms each ASIC's keys are prefixed with its namespace
+//! (`asic1:VLAN_TABLE:Vlan100`), so the agents of different switches never
+//! read or remove each other's entries. [`TableKeys::scoped`] applies the
+//! same prefix to keys of other databases.
+
+use crate::constants::DB_TABLE_SEPARATOR;
+
+/// Separator between a namespace and the key it scopes
+const NAMESPACE_SEPARATOR: char = ':';
+
+/// Builds and parses `<table><separator><key>` keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableKeys {
+    separator: String,
+    /// Prefix of every key, e.g. `asic1`; `None` on single-ASIC platforms
+    namespace: Option<String>,
+}
+
+impl Default for TableKeys {
+    fn default() -> Self {
+        Self::new(DB_TABLE_SEPARATOR)
+    }
+}
+
+impl TableKeys {
+    pub fn new(separator: impl Into<String>) -> Self {
+        Self {
+            separator: separator.into(),
+            namespace: None,
+        }
+    }
+
+    /// Prefix every key with `namespace`
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// `key` prefixed with the namespace, if there is one
+    pub fn scoped(&self, key: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, key),
+            None => key.to_string(),
+        }
+    }
+
+    /// Full key of an entry in `table`
+    pub fn key(&self, table: &str, key: &str) -> String {
+        self.scoped(&format!("{}{}{}", table, self.separator, key))
+    }
+
+    /// `KEYS` pattern matching every entry of `table`
+    pub fn pattern(&self, table: &str) -> String {
+        self.key(table, "*")
+    }
+
+    /// Entry key of `full_key`, if it belongs to `table`
+    pub fn strip<'a>(&self, table: &str, full_key: &'a str) -> Option<&'a str> {
+        let full_key = match &self.namespace {
+            Some(namespace) => full_key
+                .strip_prefix(namespace.as_str())?
+                .strip_prefix(NAMESPACE_SEPARATOR)?,
+            None => full_key,
+        };
+        full_key
+            .strip_prefix(table)?
+            .strip_prefix(self.separator.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_separator() {
+        let keys = TableKeys::default();
+        assert_eq!(keys.key("VLAN_TABLE", "Vlan100"), "VLAN_TABLE:Vlan100");
+        assert_eq!(keys.pattern("VLAN_TABLE"), "VLAN_TABLE:*");
+        assert_eq!(
+            keys.strip("VLAN_TABLE", "VLAN_TABLE:Vlan100"),
+            Some("Vlan100")
+        );
+    }
+
+    #[test]
+    fn test_custom_separator_round_trip() {
+        let producer = TableKeys::new("|");
+        let consumer = TableKeys::new("|");
+
+        let key = producer.key("VLAN_MEMBER_TABLE", "Vlan100:Ethernet0");
+        assert_eq!(key, "VLAN_MEMBER_TABLE|Vlan100:Ethernet0");
+        assert_eq!(
+            consumer.strip("VLAN_MEMBER_TABLE", &key),
+            Some("Vlan100:Ethernet0")
+        );
+
+        // Keys of another table or separator are not matched
+        assert_eq!(consumer.strip("VLAN_TABLE", &key), None);
+        assert_eq!(consumer.strip("VLAN_TABLE", "VLAN_TABLE:Vlan100"), None);
+        assert_eq!(consumer.strip("VLAN", "VLAN_TABLE|Vlan100"), None);
+    }
+
+    #[test]
+    fn test_namespaced_keys() {
+        let keys = TableKeys::default().with_namespace("asic1");
+        assert_eq!(
+            keys.key("VLAN_TABLE", "Vlan100"),
+            "asic1:VLAN_TABLE:Vlan100"
+        );
+        assert_eq!(keys.pattern("VLAN_TABLE"), "asic1:VLAN_TABLE:*");
+        assert_eq!(
+            keys.scoped("VLAN_STATE:Vlan100"),
+            "asic1:VLAN_STATE:Vlan100"
+        );
+        assert_eq!(
+            keys.strip("VLAN_TABLE", "asic1:VLAN_TABLE:Vlan100"),
+            Some("Vlan100")
+        );
+
+        // Another switch's entries and unscoped ones are not matched
+        assert_eq!(keys.strip("VLAN_TABLE", "asic0:VLAN_TABLE:Vlan100"), None);
+        assert_eq!(keys.strip("VLAN_TABLE", "VLAN_TABLE:Vlan100"), None);
+        assert_eq!(
+            TableKeys::default().scoped("VLAN_STATE:Vlan100"),
+            "VLAN_STATE:Vlan100"
+        );
+    }
+}
@@ -1,7 +1,15 @@
 pub mod config;
 pub mod constants;
+pub mod deps;
 pub mod error;
+pub mod keys;
 pub mod logging;
+pub mod metrics;
+pub mod migrate;
+#[cfg(feature = "oui-vendors")]
+mod oui;
+pub mod reconcile;
+pub mod reload;
 pub mod types;
 
 pub use config::Config;
@@ -1,34 +1,178 @@
 use crate::config::LoggingConfig;
-use crate::error::Result;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+use crate::error::{RacoonError, Result};
+use std::sync::Arc;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, reload, util::SubscriberInitExt};
 
-pub fn init_logging(config: &LoggingConfig) -> Result<()> {
+/// Changes the level filter of an installed subscriber at runtime
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    reload: Arc<dyn Fn(EnvFilter) -> std::result::Result<(), reload::Error> + Send + Sync>,
+}
+
+impl LogLevelHandle {
+    pub fn new<S: 'static>(handle: reload::Handle<EnvFilter, S>) -> Self {
+        Self {
+            reload: Arc::new(move |filter| handle.reload(filter)),
+        }
+    }
+
+    /// Replace the filter with `level` (`debug`, `racoon_syncd=trace`, ...)
+    pub fn set_level(&self, level: &str) -> Result<()> {
+        let filter = EnvFilter::try_new(level)
+            .map_err(|e| RacoonError::Config(format!("invalid log level '{}': {}", level, e)))?;
+        (self.reload)(filter)
+            .map_err(|e| RacoonError::Internal(format!("cannot change log level: {}", e)))
+    }
+}
+
+/// Install the global subscriber
+///
+/// With the `otel` feature and `otlp_endpoint` set, spans are also exported
+/// to that OTLP collector; call [`shutdown_tracing`] before exiting so the
+/// last batch is sent. Exporting needs a running tokio runtime.
+pub fn init_logging(config: &LoggingConfig) -> Result<LogLevelHandle> {
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
+    let (env_filter, handle) = reload::Layer::new(env_filter);
+    #[cfg(feature = "otel")]
+    let otel = config
+        .otlp_endpoint
+        .as_deref()
+        .map(otel::export_layer)
+        .transpose()?;
+    #[cfg(not(feature = "otel"))]
+    let otel = Option::<tracing_subscriber::layer::Identity>::None;
 
     match config.format.as_str() {
         "json" => {
             tracing_subscriber::registry()
                 .with(env_filter)
+                .with(otel)
                 .with(tracing_subscriber::fmt::layer().json())
                 .init();
         }
         "pretty" => {
             tracing_subscriber::registry()
                 .with(env_filter)
+                .with(otel)
                 .with(tracing_subscriber::fmt::layer().pretty())
                 .init();
         }
         _ => {
             tracing_subscriber::registry()
                 .with(env_filter)
+                .with(otel)
                 .with(tracing_subscriber::fmt::layer())
                 .init();
         }
     }
 
     tracing::info!("Logging initialized with level: {}", config.level);
-    Ok(())
+    if let Some(endpoint) = &config.otlp_endpoint {
+        if cfg!(feature = "otel") {
+            tracing::info!("Exporting spans to {}", endpoint);
+        } else {
+            tracing::warn!(
+                "otlp_endpoint {} ignored: built without the otel feature",
+                endpoint
+            );
+        }
+    }
+    Ok(LogLevelHandle::new(handle))
+}
+
+/// Install the daemons' console subscriber logging at `level`
+pub fn init_console_logging(level: &str) -> LogLevelHandle {
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(level));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_thread_ids(true)
+                .with_level(true),
+        )
+        .init();
+    LogLevelHandle::new(handle)
+}
+
+/// Send spans still buffered for the OTLP collector
+pub fn shutdown_tracing() {
+    #[cfg(feature = "otel")]
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use crate::error::{RacoonError, Result};
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+    use tracing::Subscriber;
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Instrumentation scope of exported spans
+    const TRACER_NAME: &str = "racoon";
+
+    /// Layer exporting spans in batches to the OTLP collector at `endpoint`
+    ///
+    /// The provider is installed globally so [`super::shutdown_tracing`]
+    /// can flush it.
+    pub(super) fn export_layer<S>(endpoint: &str) -> Result<OpenTelemetryLayer<S, Tracer>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| {
+                RacoonError::Config(format!("cannot export spans to {}: {}", endpoint, e))
+            })?;
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .build();
+        let layer = layer(&provider);
+        opentelemetry::global::set_tracer_provider(provider);
+        Ok(layer)
+    }
+
+    /// Layer turning tracing spans into spans of `provider`
+    pub(super) fn layer<S>(provider: &TracerProvider) -> OpenTelemetryLayer<S, Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        #[test]
+        fn test_spans_exported() {
+            let exporter = InMemorySpanExporter::default();
+            let provider = TracerProvider::builder()
+                .with_simple_exporter(exporter.clone())
+                .build();
+            let subscriber = tracing_subscriber::registry().with(layer(&provider));
+
+            tracing::subscriber::with_default(subscriber, || {
+                let _notification = tracing::info_span!("handle_notification").entered();
+                let _call = tracing::debug_span!("create_vlan").entered();
+            });
+            provider.force_flush();
+
+            let spans = exporter.get_finished_spans().unwrap();
+            let names: Vec<&str> = spans.iter().map(|span| span.name.as_ref()).collect();
+            assert_eq!(names, ["create_vlan", "handle_notification"]);
+            // The SAI call is a child of the notification it serves
+            assert_eq!(spans[0].parent_span_id, spans[1].span_context.span_id());
+        }
+    }
 }
 
 #[macro_export]
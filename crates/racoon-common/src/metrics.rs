@@ -0,0 +1,275 @@
+//! Lightweight in-process metrics
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Longest prefix of an ignored payload kept for debugging
+pub const IGNORED_PAYLOAD_MAX_LEN: usize = 256;
+
+/// Processed notifications kept by an [`EventLog`] by default
+pub const EVENT_LOG_CAPACITY: usize = 256;
+
+/// Upper bounds (in milliseconds) of the latency histogram buckets
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [1, 5, 10, 50, 100, 500, 1000, 5000];
+
+/// Current wall-clock time as milliseconds since the Unix epoch
+///
+/// Used for the `ts` field carried in notification envelopes.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Fixed-bucket latency histogram safe to update from many tasks
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    /// One counter per bucket plus a final overflow bucket
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one latency sample
+    pub fn observe(&self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Number of samples recorded
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Point-in-time copy of the histogram
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let count = self.count();
+        let sum_us = self.sum_us.load(Ordering::Relaxed);
+
+        LatencySnapshot {
+            buckets: self
+                .buckets
+                .iter()
+                .map(|b| b.load(Ordering::Relaxed))
+                .collect(),
+            count,
+            mean_ms: if count == 0 {
+                0.0
+            } else {
+                sum_us as f64 / count as f64 / 1000.0
+            },
+        }
+    }
+}
+
+/// Serializable view of a [`LatencyHistogram`]
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySnapshot {
+    /// Sample counts per bucket of [`LATENCY_BUCKETS_MS`], then overflow
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub mean_ms: f64,
+}
+
+/// Counter of notifications dropped before they could be handled
+///
+/// Unparsable messages, unknown operations and missing keys are only logged
+/// by the handlers; the counter makes a misbehaving producer visible in stats.
+#[derive(Debug, Default)]
+pub struct IgnoredNotifications {
+    count: AtomicU64,
+    /// Last ignored payload, truncated to [`IGNORED_PAYLOAD_MAX_LEN`] bytes
+    last_payload: Mutex<Option<String>>,
+}
+
+impl IgnoredNotifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one ignored notification and keep its payload
+    pub fn record(&self, payload: &str) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+
+        let mut end = payload.len().min(IGNORED_PAYLOAD_MAX_LEN);
+        while !payload.is_char_boundary(end) {
+            end -= 1;
+        }
+        if let Ok(mut last) = self.last_payload.lock() {
+            *last = Some(payload[..end].to_string());
+        }
+    }
+
+    /// Number of ignored notifications
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Point-in-time copy of the counter
+    pub fn snapshot(&self) -> IgnoredSnapshot {
+        IgnoredSnapshot {
+            count: self.count(),
+            last_payload: self.last_payload.lock().ok().and_then(|last| last.clone()),
+        }
+    }
+}
+
+/// Serializable view of [`IgnoredNotifications`]
+#[derive(Debug, Clone, Serialize)]
+pub struct IgnoredSnapshot {
+    pub count: u64,
+    pub last_payload: Option<String>,
+}
+
+/// A processed notification kept by an [`EventLog`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcessedEvent {
+    /// Unix milliseconds when processing finished
+    pub ts: u64,
+    /// Operation as received (`SET`, `DEL`, `HSET`)
+    pub operation: String,
+    pub key: String,
+    /// Why processing failed, `None` on success
+    pub error: Option<String>,
+}
+
+/// The last processed notifications, for live debugging
+///
+/// Shows what an agent just did without enabling debug logs. Once full,
+/// each new event evicts the oldest.
+#[derive(Debug)]
+pub struct EventLog {
+    capacity: usize,
+    events: Mutex<VecDeque<ProcessedEvent>>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Keep the outcome of processing `operation` on `key`
+    pub fn record<T, E: Display>(
+        &self,
+        operation: &str,
+        key: &str,
+        result: &std::result::Result<T, E>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let event = ProcessedEvent {
+            ts: unix_millis(),
+            operation: operation.to_string(),
+            key: key.to_string(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+
+        // A panicking holder can't leave the queue in a state worth refusing
+        let mut events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Kept events, oldest first
+    pub fn recent(&self) -> Vec<ProcessedEvent> {
+        let events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        events.iter().cloned().collect()
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(EVENT_LOG_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histogram_buckets() {
+        let histogram = LatencyHistogram::new();
+        histogram.observe(Duration::from_micros(500));
+        histogram.observe(Duration::from_millis(42));
+        histogram.observe(Duration::from_secs(10));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(snapshot.buckets[0], 1);
+        assert_eq!(snapshot.buckets[3], 1);
+        assert_eq!(snapshot.buckets[LATENCY_BUCKETS_MS.len()], 1);
+    }
+
+    #[test]
+    fn test_ignored_payload_truncated() {
+        let ignored = IgnoredNotifications::new();
+        assert_eq!(ignored.snapshot().last_payload, None);
+
+        // Multi-byte characters straddling the limit are not split
+        let payload = "é".repeat(IGNORED_PAYLOAD_MAX_LEN);
+        ignored.record(&payload);
+        ignored.record("{");
+
+        let snapshot = ignored.snapshot();
+        assert_eq!(snapshot.count, 2);
+        assert_eq!(snapshot.last_payload.as_deref(), Some("{"));
+
+        ignored.record(&payload);
+        let last = ignored.snapshot().last_payload.unwrap();
+        assert_eq!(last.len(), IGNORED_PAYLOAD_MAX_LEN);
+        assert!(payload.starts_with(&last));
+    }
+
+    #[test]
+    fn test_event_log_evicts_oldest() {
+        let events = EventLog::new(3);
+        events.record("SET", "Vlan100", &Ok::<(), String>(()));
+        events.record("SET", "Vlan200", &Err::<(), _>("VLAN 200 already exists"));
+        events.record("DEL", "Vlan100", &Ok::<(), String>(()));
+
+        let recent = events.recent();
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[1].key, "Vlan200");
+        assert_eq!(recent[1].error.as_deref(), Some("VLAN 200 already exists"));
+
+        events.record("DEL", "Vlan200", &Ok::<(), String>(()));
+        let keys: Vec<(String, String)> = events
+            .recent()
+            .into_iter()
+            .map(|event| (event.operation, event.key))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("SET".to_string(), "Vlan200".to_string()),
+                ("DEL".to_string(), "Vlan100".to_string()),
+                ("DEL".to_string(), "Vlan200".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Config file schema migrations
+//!
+//! A config file records its layout in a top-level `schema_version`; files
+//! written before versioning have none and are version 1. [`Config::load`]
+//! upgrades older files to [`SCHEMA_VERSION`] with [`migrate`] before
+//! parsing them, one version at a time, so a schema change only needs a
+//! migration from the version before it.
+//!
+//! [`Config::load`]: crate::config::Config::load
+
+use crate::config::FeaturesConfig;
+use crate::error::{RacoonError, Result};
+use toml::Value;
+use tracing::info;
+
+/// Schema version of the config layout this build reads
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Version of a file without a `schema_version`
+const UNVERSIONED: u32 = 1;
+
+/// Upgrade from one version to the next
+type Migration = fn(Value) -> Result<Value>;
+
+/// Migration to version `i + 2` at index `i`
+const MIGRATIONS: [Migration; (SCHEMA_VERSION - UNVERSIONED) as usize] = [v1_to_v2];
+
+/// Schema version recorded in a config file
+pub fn schema_version(value: &Value) -> Result<u32> {
+    match value.get("schema_version") {
+        None => Ok(UNVERSIONED),
+        Some(Value::Integer(version)) => u32::try_from(*version)
+            .ok()
+            .filter(|version| *version >= UNVERSIONED)
+            .ok_or_else(|| RacoonError::Config(format!("Invalid schema_version {}", version))),
+        Some(other) => Err(RacoonError::Config(format!(
+            "schema_version must be an integer, not {}",
+            other
+        ))),
+    }
+}
+
+/// Upgrade a config file from schema version `from` to `to`
+///
+/// Versions are never downgraded; a file newer than this build is rejected
+/// rather than misread.
+pub fn migrate(from: u32, to: u32, mut value: Value) -> Result<Value> {
+    if from > to {
+        return Err(RacoonError::Config(format!(
+            "Config schema version {} is newer than the supported {}",
+            from, to
+        )));
+    }
+    if to > SCHEMA_VERSION || from < UNVERSIONED {
+        return Err(RacoonError::Config(format!(
+            "No migration from config schema version {} to {}",
+            from, to
+        )));
+    }
+
+    for version in from..to {
+        value = MIGRATIONS[(version - UNVERSIONED) as usize](value)?;
+        info!(
+            "Migrated config from schema version {} to {}",
+            version,
+            version + 1
+        );
+    }
+    if let Value::Table(table) = &mut value {
+        table.insert("schema_version".to_string(), Value::Integer(to.into()));
+    }
+    Ok(value)
+}
+
+/// Version 2 makes the `features` table explicit
+fn v1_to_v2(mut value: Value) -> Result<Value> {
+    let Value::Table(table) = &mut value else {
+        return Err(RacoonError::Config("Config is not a table".to_string()));
+    };
+    if !table.contains_key("features") {
+        let features = Value::try_from(FeaturesConfig::default())
+            .map_err(|e| RacoonError::Config(e.to_string()))?;
+        table.insert("features".to_string(), features);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    const V1_CONFIG: &str = r#"
+        [platform]
+        name = "test"
+        sai_library = "/usr/lib/libsai.so"
+
+        [database]
+
+        [logging]
+
+        [services]
+        enabled = ["syncd"]
+
+        [management]
+    "#;
+
+    #[test]
+    fn test_v1_config_migrated() {
+        let value: Value = toml::from_str(V1_CONFIG).unwrap();
+        assert_eq!(schema_version(&value).unwrap(), 1);
+
+        let migrated = migrate(1, SCHEMA_VERSION, value).unwrap();
+        assert_eq!(schema_version(&migrated).unwrap(), 2);
+        assert_eq!(
+            migrated["features"]["cleanup_on_shutdown"],
+            Value::Boolean(false)
+        );
+
+        let config: Config = migrated.try_into().unwrap();
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+        assert!(!config.features.warm_boot);
+        assert!(!config.features.fast_reboot);
+    }
+
+    #[test]
+    fn test_newer_version_rejected() {
+        let value: Value = toml::from_str(V1_CONFIG).unwrap();
+        assert!(migrate(SCHEMA_VERSION + 1, SCHEMA_VERSION, value.clone()).is_err());
+        assert!(migrate(1, SCHEMA_VERSION + 1, value).is_err());
+
+        let value: Value = toml::from_str("schema_version = 0").unwrap();
+        assert!(schema_version(&value).is_err());
+    }
+}
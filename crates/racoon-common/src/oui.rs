@@ -0,0 +1,61 @@
+//! Vendor names of common OUIs
+//!
+//! A short table of vendors seen on switch ports, for reading FDB dumps.
+//! It is not the IEEE registry; unknown prefixes have no name.
+
+use crate::types::MacAddress;
+
+/// OUI to vendor name, sorted by OUI for binary search
+const OUI_VENDORS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0c], "Cisco"),
+    ([0x00, 0x02, 0xc9], "Mellanox"),
+    ([0x00, 0x05, 0x85], "Juniper"),
+    ([0x00, 0x0c, 0x29], "VMware"),
+    ([0x00, 0x10, 0x18], "Broadcom"),
+    ([0x00, 0x15, 0x5d], "Microsoft"),
+    ([0x00, 0x16, 0x3e], "Xen"),
+    ([0x00, 0x1b, 0x21], "Intel"),
+    ([0x00, 0x1c, 0x73], "Arista"),
+    ([0x00, 0x25, 0x90], "Supermicro"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x00, 0xe0, 0x4c], "Realtek"),
+    ([0x08, 0x00, 0x27], "VirtualBox"),
+    ([0x0c, 0xc4, 0x7a], "Supermicro"),
+    ([0x3c, 0xfd, 0xfe], "Intel"),
+    ([0xb8, 0x27, 0xeb], "Raspberry Pi"),
+];
+
+impl MacAddress {
+    /// Vendor of the address's OUI, if it is in the embedded table
+    ///
+    /// Locally administered and multicast addresses carry no OUI and have
+    /// no vendor.
+    pub fn vendor_name(&self) -> Option<&'static str> {
+        if !self.is_universal_unicast() {
+            return None;
+        }
+        let oui = self.oui();
+        OUI_VENDORS
+            .binary_search_by_key(&oui, |(prefix, _)| *prefix)
+            .ok()
+            .map(|index| OUI_VENDORS[index].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_name() {
+        assert!(OUI_VENDORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        let mac: MacAddress = "00:1c:73:aa:bb:cc".parse().unwrap();
+        assert_eq!(mac.vendor_name(), Some("Arista"));
+        let mac: MacAddress = "00:1c:74:aa:bb:cc".parse().unwrap();
+        assert_eq!(mac.vendor_name(), None);
+        // Same prefix with the locally administered bit set
+        let mac: MacAddress = "02:1c:73:aa:bb:cc".parse().unwrap();
+        assert_eq!(mac.vendor_name(), None);
+    }
+}
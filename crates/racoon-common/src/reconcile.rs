@@ -0,0 +1,79 @@
+//! Diffing of current against desired state
+//!
+//! Declarative pushes hand over a whole table; [`diff`] reduces it to the
+//! minimal set of creates, updates and deletes against what is applied.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Operations that turn the current state into the desired one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Changes<K> {
+    /// Keys only in the desired state
+    pub create: Vec<K>,
+    /// Keys in both states whose values differ
+    pub update: Vec<K>,
+    /// Keys only in the current state
+    pub delete: Vec<K>,
+}
+
+impl<K> Changes<K> {
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty() && self.delete.is_empty()
+    }
+
+    /// Total number of operations
+    pub fn len(&self) -> usize {
+        self.create.len() + self.update.len() + self.delete.len()
+    }
+}
+
+/// Diff `current` against `desired`, each list sorted by key
+pub fn diff<K, V>(current: &HashMap<K, V>, desired: &HashMap<K, V>) -> Changes<K>
+where
+    K: Clone + Eq + Hash + Ord,
+    V: PartialEq,
+{
+    let mut create = Vec::new();
+    let mut update = Vec::new();
+    for (key, value) in desired {
+        match current.get(key) {
+            None => create.push(key.clone()),
+            Some(existing) if existing != value => update.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    let mut delete: Vec<K> = current
+        .keys()
+        .filter(|key| !desired.contains_key(*key))
+        .cloned()
+        .collect();
+
+    create.sort();
+    update.sort();
+    delete.sort();
+    Changes {
+        create,
+        update,
+        delete,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let current: HashMap<&str, u32> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        let desired: HashMap<&str, u32> = [("a", 1), ("b", 20), ("d", 4)].into_iter().collect();
+
+        let changes = diff(&current, &desired);
+        assert_eq!(changes.create, vec!["d"]);
+        assert_eq!(changes.update, vec!["b"]);
+        assert_eq!(changes.delete, vec!["c"]);
+        assert_eq!(changes.len(), 3);
+
+        assert!(diff(&desired, &desired).is_empty());
+    }
+}
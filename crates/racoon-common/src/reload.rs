@@ -0,0 +1,134 @@
+//! Configuration reload on SIGHUP
+//!
+//! Daemons share their [`Config`] as a [`SharedConfig`] and re-read the file
+//! with [`reload_config`] when signalled. The log level is applied here;
+//! each daemon names the other settings it applies at runtime, and changes
+//! to any remaining setting are only logged as needing a restart.
+
+use crate::config::Config;
+use crate::error::Result;
+use crate::logging::LogLevelHandle;
+use arc_swap::ArcSwap;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Config shared by a daemon's tasks and replaced on reload
+pub type SharedConfig = Arc<ArcSwap<Config>>;
+
+/// Setting applied by [`reload_config`] itself
+pub const LOG_LEVEL_SETTING: &str = "logging.level";
+
+/// Settings a reload changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigReload {
+    /// Changed settings now in effect
+    pub applied: Vec<String>,
+    /// Changed settings that take effect on the next restart
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigReload {
+    /// Whether `setting` changed and is now in effect
+    pub fn applied(&self, setting: &str) -> bool {
+        self.applied.iter().any(|applied| applied == setting)
+    }
+}
+
+/// Re-read the config at `path` into `config`
+///
+/// `hot` lists the settings the daemon applies at runtime besides the log
+/// level. A file that can't be loaded, or names an invalid log level,
+/// leaves the current config in place.
+pub fn reload_config<P: AsRef<Path>>(
+    path: P,
+    config: &SharedConfig,
+    log_level: &LogLevelHandle,
+    hot: &[&str],
+) -> Result<ConfigReload> {
+    let new = Config::load(path)?;
+    let mut reload = ConfigReload::default();
+    for setting in config.load().changed_settings(&new) {
+        if setting == LOG_LEVEL_SETTING || hot.contains(&setting.as_str()) {
+            reload.applied.push(setting);
+        } else {
+            reload.restart_required.push(setting);
+        }
+    }
+
+    if reload.applied(LOG_LEVEL_SETTING) {
+        log_level.set_level(&new.logging.level)?;
+    }
+    config.store(Arc::new(new));
+
+    for setting in &reload.applied {
+        info!("Reloaded {}", setting);
+    }
+    for setting in &reload.restart_required {
+        warn!("{} changed; restart to apply it", setting);
+    }
+    if reload.applied.is_empty() && reload.restart_required.is_empty() {
+        info!("Config reloaded, nothing changed");
+    }
+    Ok(reload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::Level;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::{EnvFilter, reload};
+
+    fn write_config(path: &Path, level: &str, port: u16) {
+        let content = format!(
+            r#"
+            [platform]
+            name = "test"
+            sai_library = "/usr/lib/libsai.so"
+
+            [database]
+            port = {}
+
+            [logging]
+            level = "{}"
+
+            [services]
+            enabled = ["vlan"]
+
+            [management]
+            "#,
+            port, level
+        );
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_reload_applies_log_level() {
+        let path = std::env::temp_dir().join(format!("racoon-reload-{}.toml", std::process::id()));
+        write_config(&path, "info", 6379);
+        let config: SharedConfig = Arc::new(ArcSwap::from_pointee(Config::load(&path).unwrap()));
+
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let log_level = LogLevelHandle::new(handle);
+        let subscriber = tracing_subscriber::registry().with(filter);
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(!tracing::enabled!(Level::DEBUG));
+
+            write_config(&path, "debug", 6380);
+            let reload = reload_config(&path, &config, &log_level, &[]).unwrap();
+            assert_eq!(reload.applied, [LOG_LEVEL_SETTING]);
+            assert_eq!(reload.restart_required, ["database.port"]);
+            assert!(tracing::enabled!(Level::DEBUG));
+            assert_eq!(config.load().database.port, 6380);
+
+            // An invalid level keeps the running filter and config
+            write_config(&path, "debug=verbose", 6380);
+            assert!(reload_config(&path, &config, &log_level, &[]).is_err());
+            assert!(tracing::enabled!(Level::DEBUG));
+            assert_eq!(config.load().logging.level, "debug");
+        });
+        std::fs::remove_file(&path).unwrap();
+    }
+}
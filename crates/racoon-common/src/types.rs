@@ -1,3 +1,5 @@
+use crate::constants::{MAX_VLAN_ID, MIN_VLAN_ID, PORT_PREFIX, PORT_PREFIX_ALIASES};
+use crate::error::RacoonError;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
@@ -14,6 +16,17 @@ impl MacAddress {
     pub fn as_bytes(&self) -> &[u8; 6] {
         &self.0
     }
+
+    /// Organizationally unique identifier: the first three bytes
+    pub fn oui(&self) -> [u8; 3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    /// Whether the address is globally unique and unicast, i.e. its OUI
+    /// identifies the vendor
+    pub fn is_universal_unicast(&self) -> bool {
+        self.0[0] & 0x03 == 0
+    }
 }
 
 impl FromStr for MacAddress {
@@ -61,6 +74,50 @@ impl VlanId {
     pub fn get(&self) -> u16 {
         self.0
     }
+
+    /// VLAN id of a VLAN name (`Vlan100`); a bare number is accepted too
+    ///
+    /// Fails with [`RacoonError::InvalidVlanName`] when no number can be
+    /// read from the name and [`RacoonError::InvalidVlanId`] when the
+    /// number is outside 1-4094.
+    pub fn parse_from_name(name: &str) -> crate::Result<Self> {
+        let id = name
+            .strip_prefix("Vlan")
+            .unwrap_or(name)
+            .parse::<u16>()
+            .map_err(|_| RacoonError::InvalidVlanName(name.to_string()))?;
+        Self::new(id).ok_or(RacoonError::InvalidVlanId(id))
+    }
+
+    /// First and last VLAN id of a range name (`Vlan100-200`)
+    ///
+    /// Both ends must be valid VLAN ids and the first must not exceed the
+    /// last; a single VLAN name is not a range.
+    pub fn parse_range_from_name(name: &str) -> crate::Result<(Self, Self)> {
+        let (start, end) = name
+            .strip_prefix("Vlan")
+            .unwrap_or(name)
+            .split_once('-')
+            .ok_or_else(|| RacoonError::InvalidVlanName(name.to_string()))?;
+        let start = Self::parse_from_name(start)?;
+        let end = Self::parse_from_name(end)?;
+        if start.get() > end.get() {
+            return Err(RacoonError::InvalidVlanName(name.to_string()));
+        }
+        Ok((start, end))
+    }
+
+    /// VLAN ids from `start` to `end` inclusive, clamped to 1-4094
+    ///
+    /// Yields nothing when `start > end`.
+    pub fn range(start: u16, end: u16) -> impl Iterator<Item = VlanId> {
+        (start.max(MIN_VLAN_ID)..=end.min(MAX_VLAN_ID)).map(Self)
+    }
+
+    /// Every valid VLAN id
+    pub fn all() -> impl Iterator<Item = VlanId> {
+        Self::range(MIN_VLAN_ID, MAX_VLAN_ID)
+    }
 }
 
 impl fmt::Display for VlanId {
@@ -69,14 +126,6 @@ impl fmt::Display for VlanId {
     }
 }
 
-/// VLAN tagging mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum VlanTaggingMode {
-    Untagged,
-    Tagged,
-    Priority,
-}
-
 /// Port operational status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PortOperStatus {
@@ -93,6 +142,144 @@ pub enum PortAdminStatus {
     Down,
 }
 
+impl PortAdminStatus {
+    /// CONFIG_DB spelling ("up" or "down")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+}
+
+impl FromStr for PortAdminStatus {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "up" | "enabled" => Ok(Self::Up),
+            "down" | "disabled" => Ok(Self::Down),
+            _ => Err("admin status must be up, down, enabled or disabled"),
+        }
+    }
+}
+
+impl fmt::Display for PortAdminStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Serde adapter for optional `admin_status` fields
+///
+/// Use with `#[serde(default, with = "racoon_common::admin_status")]`.
+/// The database stores the status as a lowercase string. Parsing is
+/// case-insensitive and also accepts "enabled"/"disabled"; anything else is
+/// rejected when the entry is loaded.
+pub mod admin_status {
+    use super::PortAdminStatus;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(
+        status: &Option<PortAdminStatus>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match status {
+            Some(status) => serializer.serialize_str(status.as_str()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PortAdminStatus>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| {
+                s.parse()
+                    .map_err(|_| D::Error::custom(format!("invalid admin_status '{}'", s)))
+            })
+            .transpose()
+    }
+}
+
+/// Front-panel port name (`Ethernet<n>`)
+///
+/// Parsing is case-insensitive and accepts the aliases in
+/// [`PORT_PREFIX_ALIASES`], so `ethernet0` and `Eth0` both become
+/// `Ethernet0`. Names must end in a port number.
+///
+/// [`PORT_PREFIX_ALIASES`]: crate::constants::PORT_PREFIX_ALIASES
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PortName(u32);
+
+impl PortName {
+    pub fn new(index: u32) -> Self {
+        Self(index)
+    }
+
+    /// Port number (`Ethernet8` -> 8)
+    pub fn index(&self) -> u32 {
+        self.0
+    }
+
+    /// Canonical spelling of `name` if it is a front-panel port
+    ///
+    /// Other interface names (e.g. `PortChannel1`) are returned unchanged.
+    pub fn normalize(name: &str) -> String {
+        name.parse::<Self>()
+            .map(|port| port.to_string())
+            .unwrap_or_else(|_| name.to_string())
+    }
+}
+
+impl FromStr for PortName {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_ascii_lowercase();
+
+        // Try the longest prefix first so "Ethernet" is not read as "Eth"
+        let mut prefixes: Vec<&str> = std::iter::once(PORT_PREFIX)
+            .chain(PORT_PREFIX_ALIASES)
+            .collect();
+        prefixes.sort_by_key(|prefix| std::cmp::Reverse(prefix.len()));
+
+        let index = prefixes
+            .iter()
+            .find_map(|prefix| lower.strip_prefix(&prefix.to_ascii_lowercase()))
+            .ok_or("port name must start with Ethernet or Eth")?;
+        if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("port name must end in a port number");
+        }
+
+        index
+            .parse()
+            .map(Self)
+            .map_err(|_| "port number out of range")
+    }
+}
+
+impl TryFrom<String> for PortName {
+    type Error = &'static str;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PortName> for String {
+    fn from(port: PortName) -> Self {
+        port.to_string()
+    }
+}
+
+impl fmt::Display for PortName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", PORT_PREFIX, self.0)
+    }
+}
+
 /// FDB entry type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FdbEntryType {
@@ -133,9 +320,212 @@ impl PortSpeed {
     }
 }
 
+/// Define an enum stored in CONFIG_DB under a fixed spelling
+///
+/// Parsing is case-insensitive; serialization uses the listed spelling.
+macro_rules! config_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $spelling:literal,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub enum $name {
+            $($variant,)+
+        }
+
+        impl $name {
+            /// CONFIG_DB spelling
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $spelling,)+
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(
+                    if s.eq_ignore_ascii_case($spelling) {
+                        return Ok(Self::$variant);
+                    }
+                )+
+                Err(format!(
+                    "invalid {} '{}' (expected one of: {})",
+                    stringify!($name),
+                    s,
+                    [$($spelling),+].join(", ")
+                ))
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = String;
+
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.as_str().to_string()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+config_enum! {
+    /// How a VLAN member sends frames of its VLAN
+    ///
+    /// `priority_tagged` frames carry an 802.1Q header with VLAN ID 0: the
+    /// port behaves as an untagged access port but keeps the 802.1p priority
+    /// bits, for end stations that need QoS marking without trunking.
+    pub enum VlanTaggingMode {
+        Untagged => "untagged",
+        Tagged => "tagged",
+        Priority => "priority_tagged",
+    }
+}
+
+config_enum! {
+    /// Forward error correction mode of a port
+    pub enum FecMode {
+        None => "none",
+        Rs => "rs",
+        Fc => "fc",
+    }
+}
+
+config_enum! {
+    /// Media type a port is configured for
+    pub enum PortInterfaceType {
+        None => "none",
+        Cr => "CR",
+        Cr2 => "CR2",
+        Cr4 => "CR4",
+        Sr => "SR",
+        Sr2 => "SR2",
+        Sr4 => "SR4",
+        Lr => "LR",
+        Lr4 => "LR4",
+        Kr => "KR",
+        Kr4 => "KR4",
+    }
+}
+
+config_enum! {
+    /// What the switch does with frames matching an FDB entry
+    ///
+    /// `drop` blackholes a MAC; `trap` sends its frames to the CPU only.
+    pub enum PacketAction {
+        Forward => "forward",
+        Drop => "drop",
+        Trap => "trap",
+        Copy => "copy",
+    }
+}
+
+/// Link bring-up settings of a port (CONFIG_DB `PORT` fields)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortLinkConfig {
+    /// Auto-negotiation, stored as "on" or "off"
+    #[serde(default, with = "on_off", skip_serializing_if = "Option::is_none")]
+    pub autoneg: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fec: Option<FecMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface_type: Option<PortInterfaceType>,
+}
+
+/// Serde adapter for optional "on"/"off" flags
+mod on_off {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<bool>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(true) => serializer.serialize_str("on"),
+            Some(false) => serializer.serialize_str("off"),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<bool>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| match s.to_ascii_lowercase().as_str() {
+                "on" => Ok(true),
+                "off" => Ok(false),
+                _ => Err(D::Error::custom(format!("expected on or off, got '{}'", s))),
+            })
+            .transpose()
+    }
+}
+
 /// SAI Object ID (opaque 64-bit identifier)
 pub type SaiOid = u64;
 
+/// Serde adapter storing a [`SaiOid`] as a `0x`-prefixed hex string
+///
+/// Use with `#[serde(with = "racoon_common::hex_oid")]`. This is the
+/// format of OIDs in ASIC_DB keys and values.
+pub mod hex_oid {
+    use super::SaiOid;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(oid: &SaiOid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{:x}", oid))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SaiOid, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .and_then(|hex| SaiOid::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| D::Error::custom(format!("invalid OID '{}'", s)))
+    }
+
+    /// Optional OID; pair with `#[serde(default)]` so a missing field is `None`
+    pub mod option {
+        use super::SaiOid;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            oid: &Option<SaiOid>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match oid {
+                Some(oid) => super::serialize(oid, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<SaiOid>, D::Error> {
+            #[derive(Deserialize)]
+            struct Hex(#[serde(with = "super")] SaiOid);
+
+            Ok(Option::<Hex>::deserialize(deserializer)?.map(|Hex(oid)| oid))
+        }
+    }
+}
+
 /// Database table names
 pub mod db_tables {
     pub const CONFIG_DB: &str = "CONFIG_DB";
@@ -149,6 +539,45 @@ pub mod db_tables {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_vlan_id_from_name() {
+        assert_eq!(VlanId::parse_from_name("Vlan100").unwrap().get(), 100);
+        assert_eq!(VlanId::parse_from_name("100").unwrap().get(), 100);
+        assert!(matches!(
+            VlanId::parse_from_name("VlanXYZ"),
+            Err(RacoonError::InvalidVlanName(name)) if name == "VlanXYZ"
+        ));
+        assert!(matches!(
+            VlanId::parse_from_name("Vlan5000"),
+            Err(RacoonError::InvalidVlanId(5000))
+        ));
+        assert!(matches!(
+            VlanId::parse_from_name("Vlan70000"),
+            Err(RacoonError::InvalidVlanName(_))
+        ));
+    }
+
+    #[test]
+    fn test_port_link_config() {
+        let link: PortLinkConfig =
+            serde_json::from_str(r#"{"autoneg": "ON", "fec": "RS", "interface_type": "cr4"}"#)
+                .unwrap();
+        assert_eq!(link.autoneg, Some(true));
+        assert_eq!(link.fec, Some(FecMode::Rs));
+        assert_eq!(link.interface_type, Some(PortInterfaceType::Cr4));
+        assert_eq!(
+            serde_json::to_string(&link).unwrap(),
+            r#"{"autoneg":"on","fec":"rs","interface_type":"CR4"}"#
+        );
+
+        assert_eq!(
+            serde_json::from_str::<PortLinkConfig>("{}").unwrap(),
+            PortLinkConfig::default()
+        );
+        assert!(serde_json::from_str::<PortLinkConfig>(r#"{"fec": "base-r"}"#).is_err());
+        assert!(serde_json::from_str::<PortLinkConfig>(r#"{"autoneg": "yes"}"#).is_err());
+    }
+
     #[test]
     fn test_mac_address() {
         let mac = "00:11:22:33:44:55".parse::<MacAddress>().unwrap();
@@ -158,6 +587,26 @@ mod tests {
         assert_eq!(mac, mac2);
     }
 
+    #[test]
+    fn test_mac_oui() {
+        let mac = "3c:fd:fe:01:02:03".parse::<MacAddress>().unwrap();
+        assert_eq!(mac.oui(), [0x3c, 0xfd, 0xfe]);
+        assert!(mac.is_universal_unicast());
+
+        assert!(
+            !"02:00:00:00:00:01"
+                .parse::<MacAddress>()
+                .unwrap()
+                .is_universal_unicast()
+        );
+        assert!(
+            !"01:00:5e:00:00:01"
+                .parse::<MacAddress>()
+                .unwrap()
+                .is_universal_unicast()
+        );
+    }
+
     #[test]
     fn test_vlan_id() {
         assert!(VlanId::new(0).is_none());
@@ -166,6 +615,99 @@ mod tests {
         assert!(VlanId::new(4095).is_none());
     }
 
+    #[test]
+    fn test_vlan_id_range() {
+        let ids: Vec<u16> = VlanId::range(100, 103).map(|id| id.get()).collect();
+        assert_eq!(ids, vec![100, 101, 102, 103]);
+
+        // Endpoints outside 1-4094 are clamped
+        let ids: Vec<u16> = VlanId::range(4090, u16::MAX).map(|id| id.get()).collect();
+        assert_eq!(ids, vec![4090, 4091, 4092, 4093, 4094]);
+        assert_eq!(VlanId::range(0, 2).next(), VlanId::new(1));
+
+        assert_eq!(VlanId::range(200, 100).count(), 0);
+        assert_eq!(VlanId::range(4095, 5000).count(), 0);
+        assert_eq!(VlanId::all().count(), 4094);
+    }
+
+    #[test]
+    fn test_parse_range_from_name() {
+        let (start, end) = VlanId::parse_range_from_name("Vlan100-200").unwrap();
+        assert_eq!((start.get(), end.get()), (100, 200));
+        assert!(VlanId::parse_range_from_name("Vlan5-5").is_ok());
+
+        assert!(matches!(
+            VlanId::parse_range_from_name("Vlan100"),
+            Err(RacoonError::InvalidVlanName(_))
+        ));
+        assert!(matches!(
+            VlanId::parse_range_from_name("Vlan200-100"),
+            Err(RacoonError::InvalidVlanName(_))
+        ));
+        assert!(matches!(
+            VlanId::parse_range_from_name("Vlan4000-4095"),
+            Err(RacoonError::InvalidVlanId(4095))
+        ));
+    }
+
+    #[test]
+    fn test_port_admin_status() {
+        assert_eq!("up".parse(), Ok(PortAdminStatus::Up));
+        assert_eq!("Enabled".parse(), Ok(PortAdminStatus::Up));
+        assert_eq!("DOWN".parse(), Ok(PortAdminStatus::Down));
+        assert_eq!("disabled".parse(), Ok(PortAdminStatus::Down));
+        assert!("on".parse::<PortAdminStatus>().is_err());
+        assert_eq!(PortAdminStatus::Down.to_string(), "down");
+    }
+
+    #[test]
+    fn test_port_name_aliases() {
+        let canonical = PortName::new(0);
+        for name in [
+            "Ethernet0",
+            "ethernet0",
+            "ETHERNET0",
+            "Eth0",
+            "eth0",
+            " Ethernet0 ",
+        ] {
+            assert_eq!(name.parse(), Ok(canonical), "{}", name);
+        }
+        assert_eq!(
+            "Eth48".parse::<PortName>().unwrap().to_string(),
+            "Ethernet48"
+        );
+
+        // Non-port interfaces pass through normalization untouched
+        assert_eq!(PortName::normalize("eth4"), "Ethernet4");
+        assert_eq!(PortName::normalize("PortChannel1"), "PortChannel1");
+
+        let json = serde_json::to_string(&PortName::new(8)).unwrap();
+        assert_eq!(json, r#""Ethernet8""#);
+        assert_eq!(
+            serde_json::from_str::<PortName>(r#""eth8""#)
+                .unwrap()
+                .index(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_port_name_requires_number() {
+        for name in [
+            "Ethernet",
+            "Eth",
+            "Ethernetx",
+            "Ethernet1a",
+            "Ethernet-1",
+            "Port1",
+            "",
+        ] {
+            assert!(name.parse::<PortName>().is_err(), "{}", name);
+        }
+        assert!(serde_json::from_str::<PortName>(r#""Ethernet""#).is_err());
+    }
+
     #[test]
     fn test_port_speed() {
         let speed = PortSpeed::from_mbps(100000).unwrap();
@@ -4,4 +4,4 @@
 
 pub mod schema;
 
-pub use schema::{Database, DbError, DbResult};
+pub use schema::{Counters, Database, DbError, DbResult, home_db, tables};
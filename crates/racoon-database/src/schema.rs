@@ -7,8 +7,10 @@
 //! - STATE_DB: Runtime state
 //! - COUNTERS_DB: Statistics and counters
 
+use racoon_common::{PacketAction, PortAdminStatus, PortLinkConfig, VlanTaggingMode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Database identifiers (Valkey database numbers)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -54,25 +56,49 @@ pub mod tables {
 
     // STATE_DB tables
     pub const PORT_STATE: &str = "PORT_STATE";
+    pub const PORT_SERDES_STATE: &str = "PORT_SERDES_STATE";
     pub const VLAN_STATE: &str = "VLAN_STATE";
+    pub const BOOT_STATE: &str = "BOOT_STATE";
 
     // COUNTERS_DB tables
     pub const COUNTERS: &str = "COUNTERS";
     pub const RATES: &str = "RATES";
 }
 
+/// Database a table lives in
+///
+/// Tables outside the schema map to APPL_DB, the default database (0) of
+/// SONiC tooling.
+pub fn home_db(table: &str) -> Database {
+    use tables::*;
+
+    match table {
+        VLAN | VLAN_MEMBER | PORT | LAG | LAG_MEMBER | INTERFACE => Database::Config,
+        VLAN_TABLE | VLAN_MEMBER_TABLE | PORT_TABLE | LAG_TABLE | LAG_MEMBER_TABLE | FDB_TABLE => {
+            Database::Appl
+        }
+        ASIC_STATE => Database::Asic,
+        PORT_STATE | PORT_SERDES_STATE | VLAN_STATE | BOOT_STATE => Database::State,
+        COUNTERS | RATES => Database::Counters,
+        _ => Database::Appl,
+    }
+}
+
 /// VLAN configuration entry (CONFIG_DB)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VlanConfig {
     pub vlanid: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Programming order on sync; higher values go first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
 }
 
 /// VLAN member configuration entry (CONFIG_DB)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VlanMemberConfig {
-    pub tagging_mode: String, // "tagged" or "untagged"
+    pub tagging_mode: VlanTaggingMode, // "tagged", "untagged" or "priority_tagged"
 }
 
 /// Port configuration entry (CONFIG_DB)
@@ -82,12 +108,19 @@ pub struct PortConfig {
     pub speed: Option<String>, // "10000", "25000", "40000", "100000"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mtu: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub admin_status: Option<String>, // "up" or "down"
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub alias: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Auto-negotiation, FEC and interface type
+    #[serde(flatten)]
+    pub link: PortLinkConfig,
 }
 
 /// LAG configuration entry (CONFIG_DB)
@@ -95,8 +128,15 @@ pub struct PortConfig {
 pub struct LagConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mtu: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub admin_status: Option<String>,
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
+    /// Members that must be oper up for the LAG to forward
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_links: Option<u32>,
 }
 
 /// FDB entry (APPL_DB)
@@ -105,6 +145,16 @@ pub struct FdbEntry {
     pub port: String,
     #[serde(rename = "type")]
     pub entry_type: String, // "static" or "dynamic"
+    /// "forward", "drop", "trap" or "copy"; unset forwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packet_action: Option<PacketAction>,
+}
+
+impl FdbEntry {
+    /// Action to program, forwarding when none is configured
+    pub fn packet_action(&self) -> PacketAction {
+        self.packet_action.unwrap_or(PacketAction::Forward)
+    }
 }
 
 /// Port state (STATE_DB)
@@ -118,12 +168,33 @@ pub struct PortState {
 }
 
 /// Counter entry (COUNTERS_DB)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Counters {
     #[serde(flatten)]
     pub values: HashMap<String, u64>,
 }
 
+impl Counters {
+    /// Per-second rate of each counter since `prev`, taken `elapsed` earlier
+    ///
+    /// Counters missing from either snapshot are skipped. A counter lower
+    /// than in `prev` was reset (e.g. the object was recreated) and rates 0.
+    pub fn diff(&self, prev: &Counters, elapsed: Duration) -> HashMap<String, f64> {
+        let secs = elapsed.as_secs_f64();
+        self.values
+            .iter()
+            .filter_map(|(field, &value)| {
+                let prev = *prev.values.get(field)?;
+                let rate = match value.checked_sub(prev) {
+                    Some(delta) if secs > 0.0 => delta as f64 / secs,
+                    _ => 0.0,
+                };
+                Some((field.clone(), rate))
+            })
+            .collect()
+    }
+}
+
 /// Key format helpers following SONiC conventions
 pub mod keys {
     use racoon_common::VlanId;
@@ -191,3 +262,163 @@ impl From<redis::RedisError> for DbError {
         DbError::Operation(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_home_db_of_known_tables() {
+        let expected = [
+            (tables::VLAN, Database::Config),
+            (tables::VLAN_MEMBER, Database::Config),
+            (tables::PORT, Database::Config),
+            (tables::LAG, Database::Config),
+            (tables::LAG_MEMBER, Database::Config),
+            (tables::INTERFACE, Database::Config),
+            (tables::VLAN_TABLE, Database::Appl),
+            (tables::VLAN_MEMBER_TABLE, Database::Appl),
+            (tables::PORT_TABLE, Database::Appl),
+            (tables::LAG_TABLE, Database::Appl),
+            (tables::LAG_MEMBER_TABLE, Database::Appl),
+            (tables::FDB_TABLE, Database::Appl),
+            (tables::ASIC_STATE, Database::Asic),
+            (tables::PORT_STATE, Database::State),
+            (tables::PORT_SERDES_STATE, Database::State),
+            (tables::VLAN_STATE, Database::State),
+            (tables::BOOT_STATE, Database::State),
+            (tables::COUNTERS, Database::Counters),
+            (tables::RATES, Database::Counters),
+        ];
+        for (table, db) in expected {
+            assert_eq!(home_db(table), db, "{}", table);
+        }
+    }
+
+    fn counters(values: &[(&str, u64)]) -> Counters {
+        Counters {
+            values: values.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_counters_diff() {
+        let prev = counters(&[("in_octets", 1000), ("in_packets", 10)]);
+        let now = counters(&[("in_octets", 3000), ("in_packets", 30)]);
+
+        let rates = now.diff(&prev, Duration::from_secs(2));
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates["in_octets"], 1000.0);
+        assert_eq!(rates["in_packets"], 10.0);
+    }
+
+    #[test]
+    fn test_counters_diff_skips_missing_fields() {
+        let prev = counters(&[("in_octets", 1000), ("out_octets", 500)]);
+        let now = counters(&[("in_octets", 2000), ("in_packets", 30)]);
+
+        let rates = now.diff(&prev, Duration::from_secs(1));
+        assert_eq!(rates.len(), 1);
+        assert_eq!(rates["in_octets"], 1000.0);
+    }
+
+    #[test]
+    fn test_counters_diff_after_reset() {
+        let prev = counters(&[("in_octets", 5000), ("in_packets", 50)]);
+        let now = counters(&[("in_octets", 100), ("in_packets", 60)]);
+
+        let rates = now.diff(&prev, Duration::from_secs(1));
+        assert_eq!(rates["in_octets"], 0.0);
+        assert_eq!(rates["in_packets"], 10.0);
+    }
+
+    #[test]
+    fn test_home_db_of_unknown_table() {
+        assert_eq!(home_db("ROUTE_TABLE"), Database::Appl);
+        assert_eq!(home_db("vlan"), Database::Appl);
+    }
+
+    fn port_admin_status(value: &str) -> Result<Option<PortAdminStatus>, serde_json::Error> {
+        let json = format!(r#"{{"admin_status": "{}"}}"#, value);
+        serde_json::from_str::<PortConfig>(&json).map(|port| port.admin_status)
+    }
+
+    #[test]
+    fn test_admin_status_accepted_values() {
+        assert_eq!(port_admin_status("up").unwrap(), Some(PortAdminStatus::Up));
+        assert_eq!(
+            port_admin_status("down").unwrap(),
+            Some(PortAdminStatus::Down)
+        );
+        assert_eq!(
+            port_admin_status("enabled").unwrap(),
+            Some(PortAdminStatus::Up)
+        );
+        assert_eq!(
+            port_admin_status("Disabled").unwrap(),
+            Some(PortAdminStatus::Down)
+        );
+
+        let lag: LagConfig = serde_json::from_str(r#"{"admin_status": "UP"}"#).unwrap();
+        assert_eq!(lag.admin_status, Some(PortAdminStatus::Up));
+    }
+
+    #[test]
+    fn test_admin_status_rejected_value() {
+        assert!(port_admin_status("shutdown").is_err());
+        assert!(serde_json::from_str::<LagConfig>(r#"{"admin_status": "on"}"#).is_err());
+    }
+
+    #[test]
+    fn test_port_link_fields() {
+        let port: PortConfig =
+            serde_json::from_str(r#"{"speed": "100000", "autoneg": "off", "fec": "rs"}"#).unwrap();
+        assert_eq!(port.speed.as_deref(), Some("100000"));
+        assert_eq!(port.link.autoneg, Some(false));
+        assert_eq!(port.link.fec, Some(racoon_common::FecMode::Rs));
+        assert_eq!(port.link.interface_type, None);
+    }
+
+    #[test]
+    fn test_fdb_packet_action() {
+        let entry: FdbEntry =
+            serde_json::from_str(r#"{"port": "Ethernet0", "type": "static"}"#).unwrap();
+        assert_eq!(entry.packet_action(), PacketAction::Forward);
+
+        let blackhole: FdbEntry = serde_json::from_str(
+            r#"{"port": "Ethernet0", "type": "static", "packet_action": "drop"}"#,
+        )
+        .unwrap();
+        assert_eq!(blackhole.packet_action(), PacketAction::Drop);
+    }
+
+    #[test]
+    fn test_vlan_member_tagging_modes() {
+        let member: VlanMemberConfig =
+            serde_json::from_str(r#"{"tagging_mode": "priority_tagged"}"#).unwrap();
+        assert_eq!(member.tagging_mode, VlanTaggingMode::Priority);
+        assert_eq!(
+            serde_json::to_string(&member).unwrap(),
+            r#"{"tagging_mode":"priority_tagged"}"#
+        );
+        assert!(
+            serde_json::from_str::<VlanMemberConfig>(r#"{"tagging_mode": "priority"}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_admin_status_missing_and_serialized() {
+        let port: PortConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(port.admin_status, None);
+
+        let lag = LagConfig {
+            mtu: None,
+            admin_status: Some(PortAdminStatus::Down),
+            min_links: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&lag).unwrap(),
+            r#"{"admin_status":"down"}"#
+        );
+    }
+}
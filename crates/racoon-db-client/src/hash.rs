@@ -0,0 +1,83 @@
+//! Deserialization of hash entries into typed structs
+//!
+//! SONiC stores table entries as hashes whose field values are all strings
+//! (`vlanid` = `"100"`). [`from_hash`] feeds the fields to a struct's
+//! `Deserialize` impl, parsing each string into the type the field asks for.
+
+use serde::de::value::{Error, MapDeserializer};
+use serde::de::{DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::collections::HashMap;
+
+/// Deserialize hash fields into `T`
+pub fn from_hash<T: DeserializeOwned>(fields: HashMap<String, String>) -> Result<T, Error> {
+    let fields = fields
+        .into_iter()
+        .map(|(name, value)| (name, FieldDeserializer(value)));
+    T::deserialize(MapDeserializer::new(fields))
+}
+
+/// A single string field value, parsed on demand
+struct FieldDeserializer(String);
+
+macro_rules! parse_field {
+    ($($method:ident => $visit:ident,)+) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let value = self.0.parse().map_err(|_| {
+                    serde::de::Error::custom(format!("invalid value '{}'", self.0))
+                })?;
+                visitor.$visit(value)
+            }
+        )+
+    };
+}
+
+impl<'de> Deserializer<'de> for FieldDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    parse_field! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        IntoDeserializer::<Error>::into_deserializer(self.0)
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for FieldDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
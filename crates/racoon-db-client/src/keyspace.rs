@@ -0,0 +1,252 @@
+//! Table change streams from keyspace notifications
+//!
+//! The server publishes `__keyspace@<db>__:<key>` with the command name
+//! whenever a key changes. [`DbSubscriberClient::stream_table`] subscribes
+//! to those for one table and turns them into [`TableEvent`]s, so
+//! telemetry collectors can follow e.g. ASIC_DB without decoding pub/sub
+//! messages or relying on producers to publish.
+
+use crate::scan::{self, ScanThrottle};
+use crate::{Database, DbSubscriberClient};
+use futures::{Stream, StreamExt};
+use racoon_common::{RacoonError, Result};
+use std::collections::HashSet;
+use tracing::{debug, info};
+
+/// Keyspace event classes a table stream needs: keyspace channel (`K`),
+/// generic commands like DEL (`g`), strings (`$`), hashes (`h`) and
+/// expiry (`x`)
+const KEYSPACE_EVENT_CLASSES: &str = "Kg$hx";
+
+/// A change to a key of a streamed table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableEvent {
+    /// The key was written for the first time
+    Added(String),
+    /// An existing key was written
+    Updated(String),
+    /// The key was removed or expired
+    Deleted(String),
+}
+
+impl TableEvent {
+    /// Full key the event is about
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Added(key) | Self::Updated(key) | Self::Deleted(key) => key,
+        }
+    }
+}
+
+/// Prefix of keyspace notification channels of `db`, before the key
+fn keyspace_channel_prefix(db: Database) -> String {
+    format!("__keyspace@{}__:", db as i64)
+}
+
+/// Channel pattern of keyspace notifications for keys starting with `prefix`
+fn keyspace_pattern(db: Database, prefix: &str) -> String {
+    format!("{}{}*", keyspace_channel_prefix(db), prefix)
+}
+
+/// Event for `command` on `key`, updating the set of `known` keys
+fn classify(known: &mut HashSet<String>, key: &str, command: &str) -> TableEvent {
+    match command {
+        "del" | "expired" | "evicted" | "rename_from" => {
+            known.remove(key);
+            TableEvent::Deleted(key.to_string())
+        }
+        _ if known.insert(key.to_string()) => TableEvent::Added(key.to_string()),
+        _ => TableEvent::Updated(key.to_string()),
+    }
+}
+
+/// `current` notify-keyspace-events flags with `classes` added
+fn merge_event_classes(current: &str, classes: &str) -> String {
+    let mut flags = current.to_string();
+    for class in classes.chars() {
+        // `A` is an alias for every class except the keyspace/keyevent ones
+        let covered = flags.contains(class) || (class != 'K' && flags.contains('A'));
+        if !covered {
+            flags.push(class);
+        }
+    }
+    flags
+}
+
+impl DbSubscriberClient {
+    /// Stream changes to keys of `db` starting with `table_prefix`
+    ///
+    /// Turns on the keyspace notifications the stream needs if the server
+    /// has them off. Keys that exist when the stream starts are known, so
+    /// their first write is an update. A key created while the stream
+    /// starts may therefore be reported as an update instead of an add.
+    pub async fn stream_table(
+        &self,
+        db: Database,
+        table_prefix: &str,
+    ) -> Result<impl Stream<Item = TableEvent> + Send + 'static> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| RacoonError::Database(e.to_string()))?;
+        self.enable_keyspace_events(&mut conn).await?;
+
+        // Subscribe before listing the table, so no change falls between
+        let pattern = keyspace_pattern(db, table_prefix);
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| RacoonError::Database(e.to_string()))?;
+        pubsub
+            .psubscribe(&pattern)
+            .await
+            .map_err(|e| RacoonError::Database(e.to_string()))?;
+
+        let _: () = redis::cmd("SELECT")
+            .arg(db as i64)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| RacoonError::Database(e.to_string()))?;
+        let table_pattern = format!("{}*", table_prefix);
+        let keys = scan::scan_batches(&ScanThrottle::default(), |cursor, count| {
+            let mut conn = conn.clone();
+            let table_pattern = &table_pattern;
+            async move {
+                redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(table_pattern)
+                    .arg("COUNT")
+                    .arg(count)
+                    .query_async::<(u64, Vec<String>)>(&mut conn)
+                    .await
+                    .map_err(|e| RacoonError::Database(e.to_string()))
+            }
+        })
+        .await?;
+        let mut known: HashSet<String> = keys.into_iter().collect();
+        info!(
+            "Streaming {} ({} existing keys) via {}",
+            table_prefix,
+            known.len(),
+            pattern
+        );
+
+        let channel_prefix = keyspace_channel_prefix(db).len();
+        Ok(pubsub.into_on_message().filter_map(move |msg| {
+            let event = msg.get_payload::<String>().ok().and_then(|command| {
+                let key = msg.get_channel_name().get(channel_prefix..)?;
+                debug!("Keyspace event {} on {}", command, key);
+                Some(classify(&mut known, key, &command))
+            });
+            async move { event }
+        }))
+    }
+
+    /// Add the event classes table streams need to notify-keyspace-events
+    async fn enable_keyspace_events(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+    ) -> Result<()> {
+        let (_, current): (String, String) = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("notify-keyspace-events")
+            .query_async(conn)
+            .await
+            .map_err(|e| RacoonError::Database(e.to_string()))?;
+
+        let flags = merge_event_classes(&current, KEYSPACE_EVENT_CLASSES);
+        if flags != current {
+            let _: () = redis::cmd("CONFIG")
+                .arg("SET")
+                .arg("notify-keyspace-events")
+                .arg(&flags)
+                .query_async(conn)
+                .await
+                .map_err(|e| RacoonError::Database(e.to_string()))?;
+            info!("Enabled keyspace notifications: {}", flags);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_classify_events() {
+        let mut known = HashSet::from(["ASIC_STATE:a".to_string()]);
+
+        assert_eq!(
+            classify(&mut known, "ASIC_STATE:a", "hset"),
+            TableEvent::Updated("ASIC_STATE:a".to_string())
+        );
+        assert_eq!(
+            classify(&mut known, "ASIC_STATE:b", "set"),
+            TableEvent::Added("ASIC_STATE:b".to_string())
+        );
+        assert_eq!(
+            classify(&mut known, "ASIC_STATE:b", "del"),
+            TableEvent::Deleted("ASIC_STATE:b".to_string())
+        );
+        // Written again after its deletion
+        assert_eq!(
+            classify(&mut known, "ASIC_STATE:b", "hset"),
+            TableEvent::Added("ASIC_STATE:b".to_string())
+        );
+
+        assert_eq!(
+            keyspace_pattern(Database::Asic, "ASIC_STATE:"),
+            "__keyspace@1__:ASIC_STATE:*"
+        );
+        assert_eq!(merge_event_classes("", KEYSPACE_EVENT_CLASSES), "Kg$hx");
+        assert_eq!(merge_event_classes("AE", KEYSPACE_EVENT_CLASSES), "AEK");
+    }
+
+    #[tokio::test]
+    async fn test_stream_table_yields_writes() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+        let subscriber = DbSubscriberClient::new(&server.url()).unwrap();
+
+        let stream = subscriber
+            .stream_table(Database::Asic, "ASIC_STATE:")
+            .await
+            .unwrap();
+        let mut stream = std::pin::pin!(stream);
+        let mut next = async || {
+            tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("no event within 5s")
+                .expect("stream ended")
+        };
+
+        let key = "ASIC_STATE:SAI_OBJECT_TYPE_VLAN:oid:0x26000000000001";
+        let fields = [("SAI_VLAN_ATTR_VLAN_ID".to_string(), "100".to_string())]
+            .into_iter()
+            .collect();
+        client
+            .hset_multiple(Database::Asic, key, &fields)
+            .await
+            .unwrap();
+        assert_eq!(next().await, TableEvent::Added(key.to_string()));
+
+        client
+            .hset_multiple(Database::Asic, key, &fields)
+            .await
+            .unwrap();
+        assert_eq!(next().await, TableEvent::Updated(key.to_string()));
+
+        // Keys outside the table are not streamed
+        client
+            .hset_multiple(Database::Asic, "OTHER:x", &fields)
+            .await
+            .unwrap();
+        client.del(Database::Asic, key).await.unwrap();
+        assert_eq!(next().await, TableEvent::Deleted(key.to_string()));
+    }
+}
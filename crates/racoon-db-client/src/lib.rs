@@ -3,29 +3,166 @@
 //! Provides async interface to Valkey database with pub/sub support
 
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use racoon_common::Result;
 use redis::{AsyncCommands, Client, aio::ConnectionManager};
-use serde::{Serialize, de::DeserializeOwned};
+use serde::de::value::StrDeserializer;
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{debug, info};
-
-/// Database identifiers
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Database {
-    Config = 4,
-    Appl = 0,
-    Asic = 1,
-    State = 6,
-    Counters = 2,
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::{OwnedRwLockReadGuard, RwLock};
+use tracing::{debug, info, warn};
+
+mod hash;
+pub mod keyspace;
+mod scan;
+pub mod state_table;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod vlan_member;
+
+pub use keyspace::TableEvent;
+pub use racoon_database::{Counters, Database, home_db, tables};
+pub use scan::ScanThrottle;
+pub use state_table::{ConsumerStateTable, KeyOpFields, ProducerStateTable};
+
+/// Operation carried by a notification envelope
+///
+/// Producers spell creation `SET` or `CREATE` and removal `DEL` or `DELETE`;
+/// both spellings deserialize to the same variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operation {
+    #[serde(rename = "SET", alias = "CREATE")]
+    Set,
+    #[serde(rename = "DEL", alias = "DELETE")]
+    Del,
+    /// A single hash field changed; `field` and `value` carry the delta
+    #[serde(rename = "HSET")]
+    Hset,
+}
+
+impl Operation {
+    /// Parse an operation string, logging and rejecting unknown ones
+    pub fn parse(operation: &str) -> Option<Self> {
+        let deserializer: StrDeserializer<'_, serde::de::value::Error> =
+            operation.into_deserializer();
+        match Self::deserialize(deserializer) {
+            Ok(operation) => Some(operation),
+            Err(_) => {
+                warn!("Unknown operation: {}", operation);
+                None
+            }
+        }
+    }
+}
+
+/// Notification envelope published on table channels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    #[serde(alias = "op")]
+    pub operation: Operation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table: Option<String>,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    /// Producer timestamp in Unix milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ts: Option<u64>,
+}
+
+impl Notification {
+    /// Build a field-level delta for one changed field of an entry
+    pub fn field_delta(
+        table: &str,
+        key: &str,
+        field: &str,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        Self {
+            operation: Operation::Hset,
+            table: Some(table.to_string()),
+            key: key.to_string(),
+            data: None,
+            field: Some(field.to_string()),
+            value: Some(value.into()),
+            ts: Some(racoon_common::metrics::unix_millis()),
+        }
+    }
+}
+
+/// Connection checked out for a single operation
+///
+/// Holds a share of the client's in-flight lock so that
+/// [`DbClient::shutdown`] can wait for the operation to finish.
+struct PooledConnection {
+    conn: ConnectionManager,
+    _in_flight: OwnedRwLockReadGuard<()>,
+}
+
+impl Deref for PooledConnection {
+    type Target = ConnectionManager;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+/// Field [`DbClient::set`] adds to JSON objects when a source is configured
+pub const SOURCE_FIELD: &str = "_source";
+
+/// `value` with [`SOURCE_FIELD`] set to `source`
+///
+/// Only JSON objects are tagged; other values have nowhere to carry it.
+fn tag_source(mut value: serde_json::Value, source: &str) -> serde_json::Value {
+    if let serde_json::Value::Object(fields) = &mut value {
+        fields.insert(SOURCE_FIELD.to_string(), source.into());
+    }
+    value
+}
+
+/// Separator between a channel prefix and the channel name
+const CHANNEL_PREFIX_SEPARATOR: char = ':';
+
+/// Name of `channel` on the server under `prefix`
+fn prefixed_channel(prefix: Option<&str>, channel: &str) -> String {
+    match prefix {
+        Some(prefix) => format!("{}{}{}", prefix, CHANNEL_PREFIX_SEPARATOR, channel),
+        None => channel.to_string(),
+    }
+}
+
+/// `channel` received from the server with `prefix` removed
+fn unprefixed_channel<'a>(prefix: Option<&str>, channel: &'a str) -> &'a str {
+    prefix
+        .and_then(|prefix| channel.strip_prefix(prefix))
+        .and_then(|rest| rest.strip_prefix(CHANNEL_PREFIX_SEPARATOR))
+        .unwrap_or(channel)
 }
 
 /// Database client with connection pooling
 pub struct DbClient {
     client: Client,
     connections: Arc<RwLock<HashMap<Database, ConnectionManager>>>,
+    in_flight: Arc<RwLock<()>>,
+    closed: AtomicBool,
+    scan_throttle: ScanThrottle,
+    source: Option<String>,
+    channel_prefix: Option<String>,
 }
 
 impl DbClient {
@@ -38,11 +175,93 @@ impl DbClient {
         Ok(Self {
             client,
             connections: Arc::new(RwLock::new(HashMap::new())),
+            in_flight: Arc::new(RwLock::new(())),
+            closed: AtomicBool::new(false),
+            scan_throttle: ScanThrottle::default(),
+            source: None,
+            channel_prefix: None,
         })
     }
 
+    /// Pace [`scan`](Self::scan) with an inter-batch delay and adaptive COUNT
+    pub fn with_scan_throttle(mut self, scan_throttle: ScanThrottle) -> Self {
+        self.scan_throttle = scan_throttle;
+        self
+    }
+
+    /// Tag values written by [`set`](Self::set) with the writing service
+    ///
+    /// Object values get a [`SOURCE_FIELD`] naming `source`, so a shared
+    /// database shows who wrote each key. Off by default, as it adds a field
+    /// to the stored schema; readers that don't deny unknown fields ignore it.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Publish on channels under `prefix` (`prefix:VLAN_TABLE`)
+    ///
+    /// Callers keep using the plain channel names; pair with
+    /// [`DbSubscriberClient::with_channel_prefix`] on the receiving side.
+    pub fn with_channel_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.channel_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Subscriber client connecting like this client
+    ///
+    /// Pub/sub needs a connection of its own, but it shares this client's
+    /// URL, connection options and channel prefix, so both paths reach the
+    /// same server the same way.
+    pub fn subscriber(&self) -> DbSubscriberClient {
+        DbSubscriberClient {
+            client: self.client.clone(),
+            watchdog: None,
+            channel_prefix: self.channel_prefix.clone(),
+        }
+    }
+
+    /// Name `channel` is published under on the server
+    pub fn channel(&self, channel: &str) -> String {
+        prefixed_channel(self.channel_prefix.as_deref(), channel)
+    }
+
+    /// Close all connections
+    ///
+    /// Waits for in-flight operations to complete, then drops every
+    /// connection manager. The client is unusable afterwards: all further
+    /// operations fail with [`RacoonError::DbClientShutdown`].
+    ///
+    /// [`RacoonError::DbClientShutdown`]: racoon_common::RacoonError::DbClientShutdown
+    pub async fn shutdown(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        // Operations hold a read guard for their whole duration
+        let _drained = self.in_flight.write().await;
+
+        let mut connections = self.connections.write().await;
+        let count = connections.len();
+        connections.clear();
+        info!("Database client shut down ({} connections closed)", count);
+    }
+
     /// Get connection for specific database
-    async fn get_connection(&self, db: Database) -> Result<ConnectionManager> {
+    async fn get_connection(&self, db: Database) -> Result<PooledConnection> {
+        let in_flight = self.in_flight.clone().read_owned().await;
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(racoon_common::RacoonError::DbClientShutdown);
+        }
+
+        let conn = self.connection_manager(db).await?;
+        Ok(PooledConnection {
+            conn,
+            _in_flight: in_flight,
+        })
+    }
+
+    async fn connection_manager(&self, db: Database) -> Result<ConnectionManager> {
         // Check if we already have a connection
         {
             let connections = self.connections.read().await;
@@ -72,8 +291,15 @@ impl DbClient {
     }
 
     /// Set a value in the database
+    ///
+    /// A value that can't be serialized fails with
+    /// [`RacoonError::Serialization`] before the database is contacted.
+    /// Objects are tagged with the client's source, if one is configured
+    /// with [`with_source`](Self::with_source).
+    ///
+    /// [`RacoonError::Serialization`]: racoon_common::RacoonError::Serialization
     pub async fn set<T: Serialize>(&self, db: Database, key: &str, value: &T) -> Result<()> {
-        let json = serde_json::to_string(value)?;
+        let json = self.encode(key, value)?;
 
         let mut conn = self.get_connection(db).await?;
         let _: () = conn
@@ -85,6 +311,55 @@ impl DbClient {
         Ok(())
     }
 
+    /// Set several values in one round-trip (pipelined SET)
+    pub async fn set_many<T: Serialize>(
+        &self,
+        db: Database,
+        entries: &[(String, T)],
+    ) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipe = redis::pipe();
+        for (key, value) in entries {
+            pipe.set(key, self.encode(key, value)?).ignore();
+        }
+
+        let mut conn = self.get_connection(db).await?;
+        let _: () = pipe
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        debug!(
+            "SET {} keys in {:?}: {}",
+            entries.len(),
+            db,
+            std::any::type_name::<T>()
+        );
+        Ok(())
+    }
+
+    /// JSON stored for `value`, tagged with the client's source
+    fn encode<T: Serialize>(&self, key: &str, value: &T) -> Result<String> {
+        match &self.source {
+            Some(source) => {
+                serde_json::to_value(value).map(|value| tag_source(value, source).to_string())
+            }
+            None => serde_json::to_string(value),
+        }
+        .map_err(|e| {
+            debug!(
+                "Failed to serialize {} for {}: {}",
+                std::any::type_name::<T>(),
+                key,
+                e
+            );
+            racoon_common::RacoonError::Serialization(e)
+        })
+    }
+
     /// Get a value from the database
     pub async fn get<T: DeserializeOwned>(&self, db: Database, key: &str) -> Result<T> {
         let mut conn = self.get_connection(db).await?;
@@ -99,6 +374,60 @@ impl DbClient {
         Ok(value)
     }
 
+    /// Get a value stored either as a JSON string or as a hash
+    ///
+    /// Racoon writes JSON strings while SONiC producers write hashes of
+    /// string fields; the key's type decides how it is decoded. Hash fields
+    /// are parsed into the types of `T`'s fields (`"100"` into a `u16`).
+    pub async fn get_auto<T: DeserializeOwned>(&self, db: Database, key: &str) -> Result<T> {
+        match self.key_type(db, key).await?.as_str() {
+            "hash" => {
+                let fields = self.hgetall(db, key).await?;
+                hash::from_hash(fields).map_err(|e| {
+                    racoon_common::RacoonError::Database(format!(
+                        "cannot decode hash {}: {}",
+                        key, e
+                    ))
+                })
+            }
+            "none" => Err(racoon_common::RacoonError::KeyNotFound(key.to_string())),
+            _ => self.get(db, key).await,
+        }
+    }
+
+    /// Get multiple values in one round-trip (MGET)
+    ///
+    /// Results follow the order of `keys`; missing keys yield `None`.
+    pub async fn get_many<T: DeserializeOwned>(
+        &self,
+        db: Database,
+        keys: &[String],
+    ) -> Result<Vec<Option<T>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.get_connection(db).await?;
+        let values: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        let entries = values
+            .into_iter()
+            .map(|value| value.map(|json| serde_json::from_str(&json)).transpose())
+            .collect::<std::result::Result<Vec<Option<T>>, _>>()?;
+
+        debug!(
+            "MGET {} keys from {:?}: {}",
+            keys.len(),
+            db,
+            std::any::type_name::<T>()
+        );
+        Ok(entries)
+    }
+
     /// Delete a key from the database
     pub async fn del(&self, db: Database, key: &str) -> Result<()> {
         let mut conn = self.get_connection(db).await?;
@@ -111,6 +440,104 @@ impl DbClient {
         Ok(())
     }
 
+    /// Delete several keys in one round-trip
+    pub async fn del_many(&self, db: Database, keys: &[String]) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.get_connection(db).await?;
+        let _: () = conn
+            .del(keys)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        debug!("DEL {} keys from {:?}", keys.len(), db);
+        Ok(())
+    }
+
+    /// Delete all keys matching a pattern, returning how many were deleted
+    ///
+    /// Keys are found with [`scan`](Self::scan) and deleted in batches.
+    /// Patterns without a literal prefix, such as `*`, would empty the
+    /// whole database and are refused unless `force` is set.
+    pub async fn del_pattern(&self, db: Database, pattern: &str, force: bool) -> Result<u64> {
+        if !force && is_broad_pattern(pattern) {
+            return Err(racoon_common::RacoonError::Database(format!(
+                "refusing to delete keys matching '{}' without force",
+                pattern
+            )));
+        }
+
+        let keys = self.scan(db, pattern).await?;
+        let mut deleted = 0;
+        for batch in keys.chunks(DEL_BATCH_SIZE) {
+            let mut conn = self.get_connection(db).await?;
+            let count: u64 = conn
+                .del(batch)
+                .await
+                .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+            deleted += count;
+        }
+
+        debug!("DEL {} from {:?}: {} keys", pattern, db, deleted);
+        Ok(deleted)
+    }
+
+    /// Atomically move `from` to `to`, replacing any existing `to` (RENAME)
+    ///
+    /// Fails with [`RacoonError::KeyNotFound`] when `from` does not exist.
+    ///
+    /// [`RacoonError::KeyNotFound`]: racoon_common::RacoonError::KeyNotFound
+    pub async fn rename(&self, db: Database, from: &str, to: &str) -> Result<()> {
+        let mut conn = self.get_connection(db).await?;
+        let _: () = conn
+            .rename(from, to)
+            .await
+            .map_err(|e| rename_error(e, from))?;
+
+        debug!("RENAME {} -> {} in {:?}", from, to, db);
+        Ok(())
+    }
+
+    /// Atomically move `from` to `to` unless `to` exists (RENAMENX)
+    ///
+    /// Returns `false` when `to` already exists and nothing was moved.
+    pub async fn rename_nx(&self, db: Database, from: &str, to: &str) -> Result<bool> {
+        let mut conn = self.get_connection(db).await?;
+        let renamed: bool = conn
+            .rename_nx(from, to)
+            .await
+            .map_err(|e| rename_error(e, from))?;
+
+        debug!("RENAMENX {} -> {} in {:?}: {}", from, to, db, renamed);
+        Ok(renamed)
+    }
+
+    /// Copy `from` to `to`, replacing any existing `to` (COPY ... REPLACE)
+    ///
+    /// Fails with [`RacoonError::KeyNotFound`] when `from` does not exist.
+    ///
+    /// [`RacoonError::KeyNotFound`]: racoon_common::RacoonError::KeyNotFound
+    pub async fn copy(&self, db: Database, from: &str, to: &str) -> Result<()> {
+        let mut conn = self.get_connection(db).await?;
+        let copied: bool = redis::cmd("COPY")
+            .arg(from)
+            .arg(to)
+            .arg("REPLACE")
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        // With REPLACE, COPY only reports 0 when the source is missing
+        if !copied {
+            return Err(racoon_common::RacoonError::KeyNotFound(from.to_string()));
+        }
+
+        debug!("COPY {} -> {} in {:?}", from, to, db);
+        Ok(())
+    }
+
     /// Check if key exists
     pub async fn exists(&self, db: Database, key: &str) -> Result<bool> {
         let mut conn = self.get_connection(db).await?;
@@ -122,6 +549,47 @@ impl DbClient {
         Ok(exists)
     }
 
+    /// Check several keys in one round-trip (pipelined EXISTS)
+    ///
+    /// Results follow the order of `keys`.
+    pub async fn exists_many(&self, db: Database, keys: &[String]) -> Result<Vec<bool>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("EXISTS").arg(key);
+        }
+
+        let mut conn = self.get_connection(db).await?;
+        let exists: Vec<bool> = pipe
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        debug!("EXISTS {} keys in {:?}", keys.len(), db);
+        Ok(exists)
+    }
+
+    /// Number of `keys` that exist (multi-key EXISTS)
+    ///
+    /// A key listed twice is counted twice.
+    pub async fn count_existing(&self, db: Database, keys: &[String]) -> Result<usize> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let mut conn = self.get_connection(db).await?;
+        let count: usize = redis::cmd("EXISTS")
+            .arg(keys)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        Ok(count)
+    }
+
     /// Get all keys matching a pattern
     pub async fn keys(&self, db: Database, pattern: &str) -> Result<Vec<String>> {
         let mut conn = self.get_connection(db).await?;
@@ -133,6 +601,30 @@ impl DbClient {
         Ok(keys)
     }
 
+    /// Get keys matching a pattern without blocking the server
+    ///
+    /// Iterates with SCAN, paced by the client's [`ScanThrottle`]. Keys
+    /// added or removed during the scan may or may not be returned.
+    pub async fn scan(&self, db: Database, pattern: &str) -> Result<Vec<String>> {
+        let keys = scan::scan_batches(&self.scan_throttle, |cursor, count| async move {
+            // Checked out per batch so shutdown isn't held up by the delay
+            let mut conn = self.get_connection(db).await?;
+            redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(count)
+                .query_async::<(u64, Vec<String>)>(&mut *conn)
+                .await
+                .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))
+        })
+        .await?;
+
+        debug!("SCAN {} in {:?}: {} keys", pattern, db, keys.len());
+        Ok(keys)
+    }
+
     /// Set multiple hash fields
     pub async fn hset_multiple(
         &self,
@@ -152,6 +644,22 @@ impl DbClient {
         Ok(())
     }
 
+    /// Add `delta` to an integer hash field and return the new value
+    ///
+    /// A missing field starts from 0 and a negative `delta` decrements. The
+    /// update is atomic on the server, so concurrent writers don't lose
+    /// increments the way a read-modify-write would.
+    pub async fn hincrby(&self, db: Database, key: &str, field: &str, delta: i64) -> Result<i64> {
+        let mut conn = self.get_connection(db).await?;
+        let value: i64 = conn
+            .hincr(key, field, delta)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        debug!("HINCRBY {} {} {} in {:?}: {}", key, field, delta, db, value);
+        Ok(value)
+    }
+
     /// Get all hash fields
     pub async fn hgetall(&self, db: Database, key: &str) -> Result<HashMap<String, String>> {
         let mut conn = self.get_connection(db).await?;
@@ -163,17 +671,153 @@ impl DbClient {
         Ok(fields)
     }
 
+    /// Get the Redis type of a key ("string", "hash", "none", ...)
+    pub async fn key_type(&self, db: Database, key: &str) -> Result<String> {
+        let mut conn = self.get_connection(db).await?;
+        let key_type: String = redis::cmd("TYPE")
+            .arg(key)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        Ok(key_type)
+    }
+
+    /// Export keys matching `pattern` to a JSON object
+    ///
+    /// Each key maps to `{"type": "string", "value": "<raw value>"}` or
+    /// `{"type": "hash", "value": {<field>: <value>}}`. Values are kept as
+    /// stored so [`import_db`](Self::import_db) restores them exactly. Keys
+    /// of other types are skipped.
+    pub async fn export_db(&self, db: Database, pattern: &str) -> Result<serde_json::Value> {
+        let mut export = serde_json::Map::new();
+
+        for key in self.keys(db, pattern).await? {
+            let entry = match self.key_type(db, &key).await?.as_str() {
+                "string" => {
+                    let mut conn = self.get_connection(db).await?;
+                    let value: Option<String> = conn
+                        .get(&key)
+                        .await
+                        .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+                    match value {
+                        Some(value) => serde_json::json!({ "type": "string", "value": value }),
+                        None => continue,
+                    }
+                }
+                "hash" => {
+                    let fields = self.hgetall(db, &key).await?;
+                    serde_json::json!({ "type": "hash", "value": fields })
+                }
+                other => {
+                    debug!("Skipping {} of type {} in export", key, other);
+                    continue;
+                }
+            };
+            export.insert(key, entry);
+        }
+
+        debug!("Exported {} keys from {:?}", export.len(), db);
+        Ok(serde_json::Value::Object(export))
+    }
+
+    /// Write back keys produced by [`export_db`](Self::export_db)
+    ///
+    /// Existing keys are replaced. Returns the number of keys written.
+    pub async fn import_db(&self, db: Database, export: &serde_json::Value) -> Result<usize> {
+        let invalid = |key: &str| {
+            racoon_common::RacoonError::Database(format!("invalid export entry {}", key))
+        };
+
+        let entries = export.as_object().ok_or_else(|| invalid("(root)"))?;
+
+        for (key, entry) in entries {
+            match (entry["type"].as_str(), &entry["value"]) {
+                (Some("string"), serde_json::Value::String(value)) => {
+                    let mut conn = self.get_connection(db).await?;
+                    let _: () = conn
+                        .set(key, value)
+                        .await
+                        .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+                }
+                (Some("hash"), serde_json::Value::Object(fields)) => {
+                    let fields = fields
+                        .iter()
+                        .map(|(field, value)| {
+                            value
+                                .as_str()
+                                .map(|value| (field.clone(), value.to_string()))
+                                .ok_or_else(|| invalid(key))
+                        })
+                        .collect::<Result<HashMap<String, String>>>()?;
+
+                    // Drop fields that are not part of the export
+                    self.del(db, key).await?;
+                    self.hset_multiple(db, key, &fields).await?;
+                }
+                _ => return Err(invalid(key)),
+            }
+        }
+
+        debug!("Imported {} keys into {:?}", entries.len(), db);
+        Ok(entries.len())
+    }
+
     /// Publish a message to a channel
     pub async fn publish(&self, channel: &str, message: &str) -> Result<()> {
+        let channel = self.channel(channel);
         let mut conn = self.get_connection(Database::Appl).await?;
         let _: () = conn
-            .publish(channel, message)
+            .publish(&channel, message)
             .await
             .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
 
         debug!("PUBLISH to {}: {}", channel, message);
         Ok(())
     }
+
+    /// Publish a typed notification envelope
+    pub async fn publish_notification(
+        &self,
+        channel: &str,
+        notification: &Notification,
+    ) -> Result<()> {
+        let message = serde_json::to_string(notification)?;
+        self.publish(channel, &message).await
+    }
+
+    /// Publish a single changed field on the table's channel
+    ///
+    /// Only the notification is sent; the caller is responsible for having
+    /// stored the new value.
+    pub async fn publish_field_delta(
+        &self,
+        table: &str,
+        key: &str,
+        field: &str,
+        value: impl Into<serde_json::Value>,
+    ) -> Result<()> {
+        let notification = Notification::field_delta(table, key, field, value);
+        self.publish_notification(table, &notification).await
+    }
+}
+
+/// Keys per DEL command in [`DbClient::del_pattern`]
+const DEL_BATCH_SIZE: usize = 500;
+
+/// Whether a glob pattern starts with a wildcard, so it can match keys of
+/// every table
+fn is_broad_pattern(pattern: &str) -> bool {
+    pattern.is_empty() || pattern.starts_with(['*', '?', '['])
+}
+
+/// Map a RENAME/RENAMENX failure, reporting a missing source key clearly
+fn rename_error(e: redis::RedisError, from: &str) -> racoon_common::RacoonError {
+    if e.to_string().to_ascii_lowercase().contains("no such key") {
+        racoon_common::RacoonError::KeyNotFound(from.to_string())
+    } else {
+        racoon_common::RacoonError::Database(e.to_string())
+    }
 }
 
 /// Subscriber trait for database pub/sub
@@ -183,6 +827,9 @@ pub trait DbSubscriber: Send + Sync {
     async fn on_message(&self, channel: String, message: String);
 
     /// Handle subscription confirmation
+    ///
+    /// Called for each channel the server confirmed on (re)connect, before
+    /// messages of the connection are delivered.
     async fn on_subscribe(&self, channel: String) {
         info!("Subscribed to channel: {}", channel);
     }
@@ -191,11 +838,29 @@ pub trait DbSubscriber: Send + Sync {
     async fn on_unsubscribe(&self, channel: String) {
         info!("Unsubscribed from channel: {}", channel);
     }
+
+    /// Handle a resubscription after the watchdog found the channel silent
+    ///
+    /// Messages published while the connection was wedged are lost, so
+    /// subscribers should reload their tables here. It is called once the
+    /// new connection is subscribed: anything published during the reload
+    /// is delivered afterwards.
+    async fn on_resubscribe(&self) {
+        info!("Resubscribed after pub/sub watchdog timeout");
+    }
 }
 
+/// Channel the subscriber watchdog publishes keepalives on
+pub const HEARTBEAT_CHANNEL: &str = "RACOON_HEARTBEAT";
+
+/// How often channels that failed to subscribe are retried
+pub const CHANNEL_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Database subscriber client
 pub struct DbSubscriberClient {
     client: Client,
+    watchdog: Option<Duration>,
+    channel_prefix: Option<String>,
 }
 
 impl DbSubscriberClient {
@@ -204,7 +869,32 @@ impl DbSubscriberClient {
         let client =
             Client::open(url).map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            watchdog: None,
+            channel_prefix: None,
+        })
+    }
+
+    /// Resubscribe when nothing arrives for `window`
+    ///
+    /// A heartbeat is published on [`HEARTBEAT_CHANNEL`] several times per
+    /// window, so silence means the pub/sub connection stopped delivering.
+    /// The subscription is then torn down and re-established, and
+    /// [`DbSubscriber::on_resubscribe`] lets the subscriber resync anything
+    /// it missed.
+    pub fn with_watchdog(mut self, window: Duration) -> Self {
+        self.watchdog = Some(window);
+        self
+    }
+
+    /// Subscribe to channels under `prefix` (`prefix:VLAN_TABLE`)
+    ///
+    /// Channels are still passed and delivered by their plain names;
+    /// channels of other prefixes, or none, are not received.
+    pub fn with_channel_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.channel_prefix = Some(prefix.into());
+        self
     }
 
     /// Subscribe to channels and process messages
@@ -213,46 +903,758 @@ impl DbSubscriberClient {
         channels: Vec<String>,
         subscriber: Arc<S>,
     ) -> Result<()> {
-        let mut pubsub = self
-            .client
-            .get_async_pubsub()
-            .await
-            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+        let prefix = self.channel_prefix.clone();
+        let heartbeat_channel = prefixed_channel(prefix.as_deref(), HEARTBEAT_CHANNEL);
+        let _heartbeat = self.watchdog.map(|window| {
+            let client = self.client.clone();
+            let channel = heartbeat_channel.clone();
+            AbortOnDrop(tokio::spawn(publish_heartbeats(
+                client,
+                channel,
+                window / 3,
+            )))
+        });
+        let server_channels: Vec<String> = channels
+            .iter()
+            .map(|channel| prefixed_channel(prefix.as_deref(), channel))
+            .collect();
 
-        // Subscribe to all channels
-        for channel in &channels {
-            pubsub
-                .subscribe(channel)
+        let connect = || async {
+            let pubsub = self
+                .client
+                .get_async_pubsub()
                 .await
                 .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
-            info!("Subscribing to channel: {}", channel);
+
+            let (mut sink, stream) = pubsub.split();
+
+            // A channel that fails to subscribe doesn't hold up the others;
+            // it is retried in the background for as long as `stream` lives
+            let failed = subscribe_each(&mut sink, &server_channels).await;
+            for (channel, _) in channels
+                .iter()
+                .zip(&server_channels)
+                .filter(|(_, server_channel)| !failed.contains(server_channel))
+            {
+                subscriber.on_subscribe(channel.clone()).await;
+            }
+            if self.watchdog.is_some() {
+                sink.subscribe(&heartbeat_channel)
+                    .await
+                    .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+            }
+            let retry = (!failed.is_empty()).then(|| {
+                AbortOnDrop(tokio::spawn(retry_channels(
+                    sink,
+                    failed,
+                    CHANNEL_RETRY_INTERVAL,
+                )))
+            });
+
+            let prefix = prefix.clone();
+            Ok(stream.map(move |msg| {
+                let _retry = &retry;
+                (
+                    unprefixed_channel(prefix.as_deref(), msg.get_channel_name()).to_string(),
+                    msg.get_payload_bytes().to_vec(),
+                )
+            }))
+        };
+
+        run_subscription(connect, subscriber.clone(), self.watchdog).await
+    }
+}
+
+/// Sending half of a pub/sub connection
+#[async_trait]
+trait ChannelSink: Send + 'static {
+    async fn subscribe_channel(&mut self, channel: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl ChannelSink for redis::aio::PubSubSink {
+    async fn subscribe_channel(&mut self, channel: &str) -> Result<()> {
+        self.subscribe(channel)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))
+    }
+}
+
+/// Subscribe to each channel independently, returning the ones that failed
+async fn subscribe_each<K: ChannelSink>(sink: &mut K, channels: &[String]) -> Vec<String> {
+    let mut failed = Vec::new();
+    for channel in channels {
+        match sink.subscribe_channel(channel).await {
+            Ok(()) => info!("Subscribing to channel: {}", channel),
+            Err(e) => {
+                warn!("Failed to subscribe to channel {}: {}", channel, e);
+                failed.push(channel.clone());
+            }
         }
+    }
+    failed
+}
+
+/// Retry `failed` channels every `period` until all are subscribed
+async fn retry_channels<K: ChannelSink>(mut sink: K, mut failed: Vec<String>, period: Duration) {
+    while !failed.is_empty() {
+        tokio::time::sleep(period).await;
+        failed = subscribe_each(&mut sink, &failed).await;
+    }
+}
+
+/// Longest prefix of a dropped payload included in the warning
+const DROPPED_PAYLOAD_PREVIEW_LEN: usize = 64;
 
-        // Process messages
+/// Payload of a message as text, or `None` if it is not valid UTF-8
+///
+/// Binary payloads from a misbehaving publisher are logged and skipped
+/// rather than ending the subscription.
+fn decode_payload(channel: &str, payload: Vec<u8>) -> Option<String> {
+    match String::from_utf8(payload) {
+        Ok(payload) => Some(payload),
+        Err(e) => {
+            let bytes = e.as_bytes();
+            let preview = &bytes[..bytes.len().min(DROPPED_PAYLOAD_PREVIEW_LEN)];
+            warn!(
+                "Dropping non-UTF-8 message on {} ({} bytes): {}",
+                channel,
+                bytes.len(),
+                String::from_utf8_lossy(preview)
+            );
+            None
+        }
+    }
+}
+
+/// Process messages from `connect`, reconnecting when the watchdog trips
+///
+/// `connect` returns once its channels are subscribed.
+async fn run_subscription<S, F, Fut, M>(
+    mut connect: F,
+    subscriber: Arc<S>,
+    watchdog: Option<Duration>,
+) -> Result<()>
+where
+    S: DbSubscriber,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<M>>,
+    M: Stream<Item = (String, Vec<u8>)>,
+{
+    let mut reconnected = false;
+    loop {
+        let messages = connect().await?;
+        let mut messages = std::pin::pin!(messages);
+
+        // Resync only now that the new connection is subscribed, so changes
+        // published while resyncing are queued on it rather than lost
+        if reconnected {
+            subscriber.on_resubscribe().await;
+        }
+
+        // Process messages until the stream ends or goes silent
         loop {
-            let msg = pubsub.on_message().next().await.ok_or_else(|| {
+            let next = match watchdog {
+                Some(window) => match tokio::time::timeout(window, messages.next()).await {
+                    Ok(next) => next,
+                    Err(_) => break,
+                },
+                None => messages.next().await,
+            };
+            let (channel, payload) = next.ok_or_else(|| {
                 racoon_common::RacoonError::Database("Subscription closed".into())
             })?;
 
-            let channel = msg.get_channel_name().to_string();
-            let payload: String = msg
-                .get_payload()
-                .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+            if channel == HEARTBEAT_CHANNEL {
+                continue;
+            }
+            if let Some(payload) = decode_payload(&channel, payload) {
+                subscriber.on_message(channel, payload).await;
+            }
+        }
+
+        warn!(
+            "No messages (including heartbeats) received for {:?}, resubscribing",
+            watchdog.unwrap_or_default()
+        );
+        reconnected = true;
+    }
+}
 
-            subscriber.on_message(channel, payload).await;
+/// Publish a keepalive on `channel`, the server name of
+/// [`HEARTBEAT_CHANNEL`], every `period`
+async fn publish_heartbeats(client: Client, channel: String, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    let mut conn = None;
+    loop {
+        interval.tick().await;
+        if conn.is_none() {
+            conn = client.get_multiplexed_async_connection().await.ok();
+        }
+        let Some(c) = conn.as_mut() else {
+            continue;
+        };
+        let result: redis::RedisResult<()> = c.publish(&channel, "").await;
+        if let Err(e) = result {
+            debug!("Failed to publish heartbeat: {}", e);
+            conn = None;
         }
     }
 }
 
+/// Aborts a background task when dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_field_delta_envelope() {
+        let delta = Notification::field_delta("VLAN_TABLE", "Vlan100", "description", "uplink");
+        let json = serde_json::to_value(&delta).unwrap();
+
+        assert_eq!(json["operation"], "HSET");
+        assert_eq!(json["key"], "Vlan100");
+        assert_eq!(json["field"], "description");
+        assert_eq!(json["value"], "uplink");
+        assert!(json.get("data").is_none());
+
+        let parsed: Notification =
+            serde_json::from_str(r#"{"op":"HSET","key":"Vlan1","field":"vlanid","value":1}"#)
+                .unwrap();
+        assert_eq!(parsed.operation, Operation::Hset);
+    }
+
+    #[test]
+    fn test_operation_spellings() {
+        assert_eq!(Operation::parse("SET"), Some(Operation::Set));
+        assert_eq!(Operation::parse("CREATE"), Some(Operation::Set));
+        assert_eq!(Operation::parse("DEL"), Some(Operation::Del));
+        assert_eq!(Operation::parse("DELETE"), Some(Operation::Del));
+        assert_eq!(Operation::parse("HSET"), Some(Operation::Hset));
+        assert_eq!(Operation::parse("UPSERT"), None);
+        assert_eq!(Operation::parse("set"), None);
+
+        // Envelopes accept the aliases too, and serialize canonically
+        let parsed: Notification =
+            serde_json::from_str(r#"{"operation":"DELETE","key":"Vlan1"}"#).unwrap();
+        assert_eq!(parsed.operation, Operation::Del);
+        assert_eq!(serde_json::to_value(parsed).unwrap()["operation"], "DEL");
+        assert!(serde_json::from_str::<Notification>(r#"{"operation":"X","key":"a"}"#).is_err());
+    }
+
+    #[derive(Default)]
+    struct RecordingSubscriber {
+        messages: std::sync::Mutex<Vec<String>>,
+        resubscribes: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl DbSubscriber for RecordingSubscriber {
+        async fn on_message(&self, _channel: String, message: String) {
+            self.messages.lock().unwrap().push(message);
+        }
+
+        async fn on_resubscribe(&self) {
+            self.resubscribes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_watchdog_resubscribes_on_silence() {
+        let subscriber = Arc::new(RecordingSubscriber::default());
+        let mut connects = 0;
+
+        // First connection delivers one message and then goes silent, the
+        // second is silent from the start; the third fails so the loop
+        // returns and the test can inspect it
+        let connect = || {
+            connects += 1;
+            let attempt = connects;
+            async move {
+                let messages = match attempt {
+                    1 => vec![
+                        (HEARTBEAT_CHANNEL.to_string(), Vec::new()),
+                        ("VLAN_TABLE".to_string(), b"{}".to_vec()),
+                    ],
+                    2 => Vec::new(),
+                    _ => return Err(racoon_common::RacoonError::Database("refused".into())),
+                };
+                Ok(futures::stream::iter(messages).chain(futures::stream::pending()))
+            }
+        };
+
+        let result = run_subscription(connect, subscriber.clone(), Some(Duration::from_secs(30)));
+        assert!(result.await.is_err());
+
+        // Only the connection that came up resyncs
+        assert_eq!(connects, 3);
+        assert_eq!(subscriber.resubscribes.load(Ordering::SeqCst), 1);
+        // Heartbeats are not forwarded to the subscriber
+        assert_eq!(*subscriber.messages.lock().unwrap(), vec!["{}"]);
+    }
+
+    type MessageSender = futures::channel::mpsc::UnboundedSender<(String, Vec<u8>)>;
+
+    /// Subscriber whose resync races a producer publishing a change
+    #[derive(Default)]
+    struct ResyncingSubscriber {
+        messages: std::sync::Mutex<Vec<String>>,
+        /// Delivers to the most recently subscribed connection
+        connection: Arc<std::sync::Mutex<Option<MessageSender>>>,
+    }
+
+    #[async_trait]
+    impl DbSubscriber for ResyncingSubscriber {
+        async fn on_message(&self, _channel: String, message: String) {
+            self.messages.lock().unwrap().push(message);
+        }
+
+        async fn on_resubscribe(&self) {
+            // Dropping the sender afterwards ends the stream and the test
+            if let Some(tx) = self.connection.lock().unwrap().take() {
+                tx.unbounded_send(("VLAN_TABLE".to_string(), b"during resync".to_vec()))
+                    .unwrap();
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_publish_during_resync_delivered() {
+        let subscriber = Arc::new(ResyncingSubscriber::default());
+        let mut connects = 0;
+        let connect = || {
+            connects += 1;
+            let attempt = connects;
+            let connection = subscriber.connection.clone();
+            async move {
+                if attempt > 2 {
+                    return Err(racoon_common::RacoonError::Database("refused".into()));
+                }
+                let (tx, rx) = futures::channel::mpsc::unbounded();
+                *connection.lock().unwrap() = Some(tx);
+                Ok(rx)
+            }
+        };
+
+        // The first connection is silent, so the watchdog reconnects
+        let result = run_subscription(connect, subscriber.clone(), Some(Duration::from_secs(30)));
+        assert!(matches!(
+            result.await,
+            Err(racoon_common::RacoonError::Database(e)) if e == "Subscription closed"
+        ));
+        assert_eq!(connects, 2);
+        assert_eq!(*subscriber.messages.lock().unwrap(), vec!["during resync"]);
+    }
+
     #[tokio::test]
-    #[ignore] // Requires running Valkey/Redis instance
-    async fn test_db_client() {
+    async fn test_invalid_utf8_payload_skipped() {
+        let subscriber = Arc::new(RecordingSubscriber::default());
+
+        // The stream ends after the last message, so the loop then returns
+        let connect = || async {
+            Ok(futures::stream::iter(vec![
+                ("VLAN_TABLE".to_string(), br#"{"key":"Vlan100"}"#.to_vec()),
+                ("VLAN_TABLE".to_string(), vec![0xff, 0xfe, 0x00, 0x80]),
+                ("VLAN_TABLE".to_string(), br#"{"key":"Vlan200"}"#.to_vec()),
+            ]))
+        };
+
+        let result = run_subscription(connect, subscriber.clone(), None).await;
+        assert!(matches!(
+            result,
+            Err(racoon_common::RacoonError::Database(e)) if e == "Subscription closed"
+        ));
+        assert_eq!(
+            *subscriber.messages.lock().unwrap(),
+            vec![r#"{"key":"Vlan100"}"#, r#"{"key":"Vlan200"}"#]
+        );
+    }
+
+    /// In-memory pub/sub: publishing reaches only subscribed channels, and
+    /// subscribing to a channel in `refused` fails
+    #[derive(Clone, Default)]
+    struct MockPubSub {
+        subscribed: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+        refused: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    }
+
+    impl MockPubSub {
+        fn publish(
+            &self,
+            tx: &futures::channel::mpsc::UnboundedSender<(String, Vec<u8>)>,
+            channel: &str,
+        ) {
+            if self.subscribed.lock().unwrap().contains(channel) {
+                tx.unbounded_send((channel.to_string(), channel.as_bytes().to_vec()))
+                    .unwrap();
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChannelSink for MockPubSub {
+        async fn subscribe_channel(&mut self, channel: &str) -> Result<()> {
+            if self.refused.lock().unwrap().contains(channel) {
+                return Err(racoon_common::RacoonError::Database("refused".into()));
+            }
+            self.subscribed.lock().unwrap().insert(channel.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_channel_does_not_block_others() {
+        let subscriber = Arc::new(RecordingSubscriber::default());
+        let pubsub = MockPubSub::default();
+        pubsub
+            .refused
+            .lock()
+            .unwrap()
+            .insert("BAD_TABLE".to_string());
+        let channels: Vec<String> = ["VLAN_TABLE", "BAD_TABLE", "PORT_TABLE"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let mut rx = Some(rx);
+        let failed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let connect = || {
+            let messages = rx.take();
+            let mut sink = pubsub.clone();
+            let failed = failed.clone();
+            let channels = channels.clone();
+            async move {
+                let messages = messages
+                    .ok_or_else(|| racoon_common::RacoonError::Database("refused".into()))?;
+                *failed.lock().unwrap() = subscribe_each(&mut sink, &channels).await;
+                Ok(messages)
+            }
+        };
+        let subscription = run_subscription(connect, subscriber.clone(), None);
+        let publisher = async {
+            tokio::task::yield_now().await;
+            for channel in &channels {
+                pubsub.publish(&tx, channel);
+            }
+            tx.close_channel();
+        };
+        let (result, ()) = tokio::join!(subscription, publisher);
+        assert!(result.is_err());
+
+        let failed = failed.lock().unwrap().clone();
+        assert_eq!(failed, vec!["BAD_TABLE"]);
+        assert_eq!(
+            *subscriber.messages.lock().unwrap(),
+            vec!["VLAN_TABLE", "PORT_TABLE"]
+        );
+
+        // The failed channel is retried until it goes through
+        let retry = tokio::spawn(retry_channels(
+            pubsub.clone(),
+            failed,
+            CHANNEL_RETRY_INTERVAL,
+        ));
+        tokio::time::sleep(CHANNEL_RETRY_INTERVAL * 2).await;
+        assert!(!pubsub.subscribed.lock().unwrap().contains("BAD_TABLE"));
+        pubsub.refused.lock().unwrap().clear();
+        retry.await.unwrap();
+        assert!(pubsub.subscribed.lock().unwrap().contains("BAD_TABLE"));
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct VlanRecord {
+        vlanid: u16,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        learning: Option<bool>,
+    }
+
+    #[test]
+    fn test_hash_fields_decode_to_struct() {
+        let fields: HashMap<String, String> = [
+            ("vlanid", "100"),
+            ("description", "200"),
+            ("learning", "false"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let record: VlanRecord = hash::from_hash(fields).unwrap();
+        assert_eq!(
+            record,
+            VlanRecord {
+                vlanid: 100,
+                // String fields stay strings even when they look numeric
+                description: Some("200".to_string()),
+                learning: Some(false),
+            }
+        );
+
+        let bad: HashMap<String, String> = [("vlanid".to_string(), "abc".to_string())].into();
+        assert!(hash::from_hash::<VlanRecord>(bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_auto_string_and_hash() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+
+        client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100, "description": "uplink"}),
+            )
+            .await
+            .unwrap();
+        let fields: HashMap<String, String> = [
+            ("vlanid".to_string(), "100".to_string()),
+            ("description".to_string(), "uplink".to_string()),
+        ]
+        .into();
+        client
+            .hset_multiple(Database::Appl, "VLAN_TABLE:Vlan200", &fields)
+            .await
+            .unwrap();
+
+        let from_string: VlanRecord = client
+            .get_auto(Database::Appl, "VLAN_TABLE:Vlan100")
+            .await
+            .unwrap();
+        let from_hash: VlanRecord = client
+            .get_auto(Database::Appl, "VLAN_TABLE:Vlan200")
+            .await
+            .unwrap();
+        assert_eq!(from_string, from_hash);
+        assert_eq!(from_hash.description.as_deref(), Some("uplink"));
+
+        assert!(matches!(
+            client
+                .get_auto::<VlanRecord>(Database::Appl, "VLAN_TABLE:Vlan300")
+                .await,
+            Err(racoon_common::RacoonError::KeyNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_operations_fail_after_shutdown() {
+        // Connections are opened lazily, so no server is needed
+        let client = DbClient::new("redis://127.0.0.1:6379").await.unwrap();
+        client.shutdown().await;
+        client.shutdown().await;
+
+        let err = client
+            .get::<String>(Database::Config, "test_key")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, racoon_common::RacoonError::DbClientShutdown));
+        assert!(matches!(
+            client.publish("VLAN_TABLE", "{}").await,
+            Err(racoon_common::RacoonError::DbClientShutdown)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_del_pattern_refuses_broad_pattern() {
+        // The guard runs before a connection is needed
+        let client = DbClient::new("redis://127.0.0.1:6379").await.unwrap();
+
+        for pattern in ["*", "", "?LAN_TABLE:*", "[V]LAN_TABLE:*"] {
+            let err = client
+                .del_pattern(Database::Appl, pattern, false)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, racoon_common::RacoonError::Database(_)));
+        }
+        assert!(!is_broad_pattern("VLAN_TABLE:*"));
+    }
+
+    #[tokio::test]
+    async fn test_del_pattern() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+
+        for key in [
+            "VLAN_TABLE:Vlan100",
+            "VLAN_TABLE:Vlan200",
+            "VLAN_MEMBER_TABLE:Vlan100:Ethernet0",
+        ] {
+            client.set(Database::Appl, key, &key).await.unwrap();
+        }
+
+        assert_eq!(
+            client
+                .del_pattern(Database::Appl, "VLAN_TABLE:*", false)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            client.keys(Database::Appl, "*").await.unwrap(),
+            vec!["VLAN_MEMBER_TABLE:Vlan100:Ethernet0"]
+        );
+
+        assert_eq!(
+            client.del_pattern(Database::Appl, "*", true).await.unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_serialization_error() {
+        // Serialization fails before a connection is needed
         let client = DbClient::new("redis://127.0.0.1:6379").await.unwrap();
 
+        // JSON object keys must be strings
+        let value: HashMap<(u16, u16), u32> = [((100, 0), 1)].into_iter().collect();
+        let err = client
+            .set(Database::Config, "VLAN|Vlan100", &value)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, racoon_common::RacoonError::Serialization(_)));
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct SourcedEntry {
+        vlanid: u16,
+    }
+
+    #[test]
+    fn test_source_tag_ignored_by_readers() {
+        let tagged = tag_source(
+            serde_json::to_value(SourcedEntry { vlanid: 100 }).unwrap(),
+            "vlansyncd",
+        );
+        assert_eq!(tagged[SOURCE_FIELD], "vlansyncd");
+        let entry: SourcedEntry = serde_json::from_value(tagged).unwrap();
+        assert_eq!(entry, SourcedEntry { vlanid: 100 });
+
+        // Values that aren't objects are stored as they are
+        assert_eq!(tag_source("up".into(), "vlansyncd"), "up");
+    }
+
+    #[test]
+    fn test_channel_prefix() {
+        assert_eq!(prefixed_channel(None, "VLAN_TABLE"), "VLAN_TABLE");
+        assert_eq!(prefixed_channel(Some("ns"), "VLAN_TABLE"), "ns:VLAN_TABLE");
+        assert_eq!(
+            unprefixed_channel(Some("ns"), "ns:VLAN_TABLE"),
+            "VLAN_TABLE"
+        );
+        // Only a whole prefix segment is removed
+        assert_eq!(
+            unprefixed_channel(Some("ns"), "nsx:VLAN_TABLE"),
+            "nsx:VLAN_TABLE"
+        );
+        assert_eq!(unprefixed_channel(None, "ns:VLAN_TABLE"), "ns:VLAN_TABLE");
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_shares_connection_settings() {
+        let client = DbClient::new("redis://10.0.0.5:6380/2")
+            .await
+            .unwrap()
+            .with_channel_prefix("asic1");
+        let subscriber = client.subscriber();
+
+        assert_eq!(
+            format!("{:?}", subscriber.client.get_connection_info()),
+            format!("{:?}", client.client.get_connection_info())
+        );
+        assert_eq!(subscriber.channel_prefix.as_deref(), Some("asic1"));
+        assert!(subscriber.watchdog.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prefixed_channels_isolated() {
+        let server = crate::test_server_or_skip!();
+        let plain = server.client().await;
+        let prefixed = server.client().await.with_channel_prefix("ns");
+        let subscriber = Arc::new(RecordingSubscriber::default());
+
+        let subscriber_client = DbSubscriberClient::new(&server.url())
+            .unwrap()
+            .with_channel_prefix("ns");
+        let subscription = {
+            let subscriber = subscriber.clone();
+            AbortOnDrop(tokio::spawn(async move {
+                let _ = subscriber_client
+                    .subscribe(vec!["VLAN_TABLE".to_string()], subscriber)
+                    .await;
+            }))
+        };
+
+        // Publish until the subscription is up and a message got through
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while subscriber.messages.lock().unwrap().is_empty() {
+                plain.publish("VLAN_TABLE", "plain").await.unwrap();
+                prefixed.publish("VLAN_TABLE", "prefixed").await.unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("no prefixed message within 5s");
+        drop(subscription);
+
+        let messages = subscriber.messages.lock().unwrap();
+        assert!(messages.iter().all(|message| message == "prefixed"));
+    }
+
+    #[tokio::test]
+    async fn test_set_with_source() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await.with_source("vlansyncd");
+
+        let entry = SourcedEntry { vlanid: 100 };
+        client
+            .set(Database::Appl, "VLAN_TABLE:Vlan100", &entry)
+            .await
+            .unwrap();
+        let raw: serde_json::Value = client
+            .get(Database::Appl, "VLAN_TABLE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(raw[SOURCE_FIELD], "vlansyncd");
+        let read: SourcedEntry = client
+            .get(Database::Appl, "VLAN_TABLE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(read, entry);
+
+        // Untagged values written by other clients still read back
+        server
+            .client()
+            .await
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan200",
+                &SourcedEntry { vlanid: 200 },
+            )
+            .await
+            .unwrap();
+        let raw: serde_json::Value = client
+            .get(Database::Appl, "VLAN_TABLE:Vlan200")
+            .await
+            .unwrap();
+        assert!(raw.get(SOURCE_FIELD).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_db_client() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+
         // Test set/get
         client
             .set(Database::Config, "test_key", &"test_value")
@@ -265,4 +1667,293 @@ mod tests {
         client.del(Database::Config, "test_key").await.unwrap();
         assert!(!client.exists(Database::Config, "test_key").await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_scan() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await.with_scan_throttle(ScanThrottle {
+            count: 10,
+            delay: Duration::from_millis(1),
+            ..ScanThrottle::default()
+        });
+
+        for vlan in 1..=50 {
+            client
+                .set(Database::Appl, &format!("VLAN_TABLE:Vlan{}", vlan), &vlan)
+                .await
+                .unwrap();
+        }
+        client
+            .set(Database::Appl, "PORT_TABLE:Ethernet0", &0)
+            .await
+            .unwrap();
+
+        let mut keys = client.scan(Database::Appl, "VLAN_TABLE:*").await.unwrap();
+        keys.sort();
+        let mut expected = client.keys(Database::Appl, "VLAN_TABLE:*").await.unwrap();
+        expected.sort();
+        assert_eq!(keys.len(), 50);
+        assert_eq!(keys, expected);
+    }
+
+    #[tokio::test]
+    #[ignore] // Only run when valkey-server or redis-server is on PATH
+    async fn test_hincrby() {
+        let server = crate::testing::TestServer::start().expect("no valkey-server or redis-server");
+        let client = server.client().await;
+        let key = "COUNTERS:Vlan100";
+
+        assert_eq!(
+            client
+                .hincrby(Database::Counters, key, "refs", 3)
+                .await
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            client
+                .hincrby(Database::Counters, key, "refs", 2)
+                .await
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            client
+                .hincrby(Database::Counters, key, "refs", -4)
+                .await
+                .unwrap(),
+            1
+        );
+
+        let fields = client.hgetall(Database::Counters, key).await.unwrap();
+        assert_eq!(fields["refs"], "1");
+    }
+
+    #[tokio::test]
+    async fn test_set_many() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await.with_source("syncd");
+
+        let entries: Vec<(String, serde_json::Value)> = [100, 200]
+            .iter()
+            .map(|vlanid| {
+                (
+                    format!("VLAN_TABLE:Vlan{}", vlanid),
+                    serde_json::json!({ "vlanid": vlanid }),
+                )
+            })
+            .collect();
+        client.set_many(Database::Appl, &entries).await.unwrap();
+
+        let values: Vec<Option<serde_json::Value>> = client
+            .get_many(
+                Database::Appl,
+                &entries
+                    .iter()
+                    .map(|(key, _)| key.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(values[0].as_ref().unwrap()["vlanid"], 100);
+        assert_eq!(values[1].as_ref().unwrap()["vlanid"], 200);
+        assert_eq!(values[1].as_ref().unwrap()[SOURCE_FIELD], "syncd");
+    }
+
+    #[tokio::test]
+    #[ignore] // Only run when valkey-server or redis-server is on PATH
+    async fn test_exists_many() {
+        let server = crate::testing::TestServer::start().expect("no valkey-server or redis-server");
+        let client = server.client().await;
+
+        for vlan in ["Vlan100", "Vlan300"] {
+            client
+                .set(Database::Config, &format!("VLAN|{}", vlan), &vlan)
+                .await
+                .unwrap();
+        }
+
+        let keys: Vec<String> = [
+            "VLAN|Vlan300",
+            "VLAN|Vlan200",
+            "VLAN|Vlan100",
+            "VLAN|Vlan400",
+        ]
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+        assert_eq!(
+            client.exists_many(Database::Config, &keys).await.unwrap(),
+            vec![true, false, true, false]
+        );
+        assert_eq!(
+            client
+                .count_existing(Database::Config, &keys)
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(
+            client
+                .exists_many(Database::Config, &[])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_copy() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+
+        client
+            .set(Database::Config, "VLAN|Vlan100", &"uplink")
+            .await
+            .unwrap();
+        client
+            .rename(Database::Config, "VLAN|Vlan100", "VLAN|Vlan200")
+            .await
+            .unwrap();
+        assert!(
+            !client
+                .exists(Database::Config, "VLAN|Vlan100")
+                .await
+                .unwrap()
+        );
+        let value: String = client.get(Database::Config, "VLAN|Vlan200").await.unwrap();
+        assert_eq!(value, "uplink");
+
+        client
+            .copy(Database::Config, "VLAN|Vlan200", "VLAN|Vlan300")
+            .await
+            .unwrap();
+        let value: String = client.get(Database::Config, "VLAN|Vlan300").await.unwrap();
+        assert_eq!(value, "uplink");
+        assert!(
+            client
+                .exists(Database::Config, "VLAN|Vlan200")
+                .await
+                .unwrap()
+        );
+
+        // RENAMENX leaves an existing destination alone
+        assert!(
+            !client
+                .rename_nx(Database::Config, "VLAN|Vlan200", "VLAN|Vlan300")
+                .await
+                .unwrap()
+        );
+
+        // A missing source is reported as such
+        assert!(matches!(
+            client
+                .rename(Database::Config, "VLAN|Vlan100", "VLAN|Vlan400")
+                .await,
+            Err(racoon_common::RacoonError::KeyNotFound(_))
+        ));
+        assert!(matches!(
+            client
+                .copy(Database::Config, "VLAN|Vlan100", "VLAN|Vlan400")
+                .await,
+            Err(racoon_common::RacoonError::KeyNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+
+        client
+            .set(
+                Database::Config,
+                "VLAN|Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        client
+            .set(
+                Database::Config,
+                "VLAN|Vlan200",
+                &serde_json::json!({"vlanid": 200}),
+            )
+            .await
+            .unwrap();
+        let fields: HashMap<String, String> = [
+            ("mtu".to_string(), "9100".to_string()),
+            ("admin_status".to_string(), "up".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        client
+            .hset_multiple(Database::Config, "PORT|Ethernet0", &fields)
+            .await
+            .unwrap();
+
+        let export = client.export_db(Database::Config, "*").await.unwrap();
+        assert_eq!(export.as_object().unwrap().len(), 3);
+        assert_eq!(export["PORT|Ethernet0"]["type"], "hash");
+
+        // Wipe, change, then restore from the export
+        client.del(Database::Config, "VLAN|Vlan100").await.unwrap();
+        client
+            .set(
+                Database::Config,
+                "VLAN|Vlan200",
+                &serde_json::json!({"vlanid": 201}),
+            )
+            .await
+            .unwrap();
+        client
+            .hset_multiple(
+                Database::Config,
+                "PORT|Ethernet0",
+                &[("speed".to_string(), "100000".to_string())]
+                    .into_iter()
+                    .collect(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            client.import_db(Database::Config, &export).await.unwrap(),
+            3
+        );
+        assert_eq!(
+            client.export_db(Database::Config, "*").await.unwrap(),
+            export
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_many() {
+        let server = crate::test_server_or_skip!();
+        let client = server.client().await;
+
+        client
+            .set(Database::Config, "test_mget_a", &"a")
+            .await
+            .unwrap();
+        client
+            .set(Database::Config, "test_mget_c", &"c")
+            .await
+            .unwrap();
+        client.del(Database::Config, "test_mget_b").await.unwrap();
+
+        let keys = vec![
+            "test_mget_a".to_string(),
+            "test_mget_b".to_string(),
+            "test_mget_c".to_string(),
+        ];
+        let values: Vec<Option<String>> = client.get_many(Database::Config, &keys).await.unwrap();
+        assert_eq!(
+            values,
+            vec![Some("a".to_string()), None, Some("c".to_string())]
+        );
+
+        client.del(Database::Config, "test_mget_a").await.unwrap();
+        client.del(Database::Config, "test_mget_c").await.unwrap();
+    }
 }
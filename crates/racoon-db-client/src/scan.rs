@@ -0,0 +1,140 @@
+//! Throttled SCAN iteration
+//!
+//! A full-keyspace SCAN at a high COUNT can still monopolize a shared
+//! server. [`ScanThrottle`] pauses between batches and adapts COUNT to the
+//! observed batch latency, so a large cold-boot reconciliation leaves room
+//! for other clients.
+
+use racoon_common::Result;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// Pacing of [`DbClient::scan`](crate::DbClient::scan)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanThrottle {
+    /// COUNT hint of the first batch
+    pub count: usize,
+    /// Lower bound when COUNT backs off
+    pub min_count: usize,
+    /// Upper bound when COUNT grows back
+    pub max_count: usize,
+    /// Pause between batches
+    pub delay: Duration,
+    /// Batches slower than this halve COUNT; batches faster than half of it
+    /// double COUNT again
+    pub target_latency: Duration,
+}
+
+impl Default for ScanThrottle {
+    fn default() -> Self {
+        Self {
+            count: 100,
+            min_count: 10,
+            max_count: 1000,
+            delay: Duration::ZERO,
+            target_latency: Duration::from_millis(10),
+        }
+    }
+}
+
+impl ScanThrottle {
+    /// COUNT for the next batch given the latency of the last one
+    pub fn next_count(&self, count: usize, latency: Duration) -> usize {
+        if latency > self.target_latency {
+            (count / 2).max(self.min_count)
+        } else if latency < self.target_latency / 2 {
+            (count * 2).min(self.max_count)
+        } else {
+            count
+        }
+    }
+}
+
+/// Drive a SCAN cursor to completion
+///
+/// `fetch(cursor, count)` runs one SCAN call and returns the next cursor
+/// with the batch of keys; iteration ends when the cursor returns to 0.
+/// SCAN may return a key more than once, so duplicates are dropped.
+pub(crate) async fn scan_batches<F, Fut>(
+    throttle: &ScanThrottle,
+    mut fetch: F,
+) -> Result<Vec<String>>
+where
+    F: FnMut(u64, usize) -> Fut,
+    Fut: Future<Output = Result<(u64, Vec<String>)>>,
+{
+    let mut keys = Vec::new();
+    let mut seen = HashSet::new();
+    let mut cursor = 0;
+    let mut count = throttle.count.clamp(throttle.min_count, throttle.max_count);
+
+    loop {
+        let started = Instant::now();
+        let (next, batch) = fetch(cursor, count).await?;
+        let latency = started.elapsed();
+        keys.extend(batch.into_iter().filter(|key| seen.insert(key.clone())));
+
+        if next == 0 {
+            return Ok(keys);
+        }
+        cursor = next;
+
+        let next_count = throttle.next_count(count, latency);
+        if next_count != count {
+            debug!(
+                "SCAN batch took {:?}, COUNT {} -> {}",
+                latency, count, next_count
+            );
+            count = next_count;
+        }
+        if !throttle.delay.is_zero() {
+            tokio::time::sleep(throttle.delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_adapts_to_latency() {
+        let throttle = ScanThrottle::default();
+        assert_eq!(throttle.next_count(100, Duration::from_millis(50)), 50);
+        assert_eq!(throttle.next_count(15, Duration::from_millis(50)), 10);
+        assert_eq!(throttle.next_count(100, Duration::from_millis(7)), 100);
+        assert_eq!(throttle.next_count(100, Duration::from_millis(1)), 200);
+        assert_eq!(throttle.next_count(800, Duration::from_millis(1)), 1000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_delay_between_batches() {
+        let throttle = ScanThrottle {
+            delay: Duration::from_millis(20),
+            ..ScanThrottle::default()
+        };
+
+        let mut calls: Vec<(Instant, u64)> = Vec::new();
+        let keys = scan_batches(&throttle, |cursor, _count| {
+            calls.push((Instant::now(), cursor));
+            let next = if cursor < 3 { cursor + 1 } else { 0 };
+            // The last batch repeats a key, as SCAN may
+            let mut batch = vec![format!("key{}", cursor)];
+            if next == 0 {
+                batch.push("key0".to_string());
+            }
+            async move { Ok((next, batch)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(keys, vec!["key0", "key1", "key2", "key3"]);
+        let cursors: Vec<u64> = calls.iter().map(|(_, cursor)| *cursor).collect();
+        assert_eq!(cursors, vec![0, 1, 2, 3]);
+        for pair in calls.windows(2) {
+            assert!(pair[1].0 - pair[0].0 >= throttle.delay);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! SONiC producer/consumer state tables
+//!
+//! Standard SONiC daemons do not exchange JSON envelopes. A
+//! `ProducerStateTable` stages each change in a temporary hash
+//! (`_VLAN_TABLE:Vlan100`), adds the key to `VLAN_TABLE_KEY_SET` and marks
+//! deletions in `VLAN_TABLE_DEL_SET`, then publishes on
+//! `VLAN_TABLE_CHANNEL@<db>`. A `ConsumerStateTable` pops the key set and
+//! applies the staged changes to the real table. Both sides run as Lua
+//! scripts so a batch is applied atomically, matching swss-common.
+
+use crate::{Database, DbClient, Operation};
+use racoon_common::Result;
+use racoon_common::keys::TableKeys;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Message published on the table channel; consumers only need a wakeup
+const WAKEUP_MESSAGE: &str = "G";
+
+/// Prefix of the hash a change is staged in before it is consumed
+const STAGING_PREFIX: &str = "_";
+
+/// Field staged for a `Set` without fields, since redis drops empty hashes
+const EMPTY_FIELD: &str = "NULL";
+
+/// KEYS: channel, key set, staging hash. ARGV: message, key, placeholder,
+/// field/value pairs
+const SET_SCRIPT: &str = r#"
+local added = redis.call('SADD', KEYS[2], ARGV[2])
+if #ARGV == 3 then
+    redis.call('HSET', KEYS[3], ARGV[3], ARGV[3])
+end
+for i = 4, #ARGV, 2 do
+    redis.call('HSET', KEYS[3], ARGV[i], ARGV[i + 1])
+end
+if added > 0 then
+    redis.call('PUBLISH', KEYS[1], ARGV[1])
+end
+"#;
+
+/// KEYS: channel, key set, staging hash, del set. ARGV: message, key
+const DEL_SCRIPT: &str = r#"
+local added = redis.call('SADD', KEYS[2], ARGV[2])
+redis.call('SADD', KEYS[4], ARGV[2])
+redis.call('DEL', KEYS[3])
+if added > 0 then
+    redis.call('PUBLISH', KEYS[1], ARGV[1])
+end
+"#;
+
+/// KEYS: key set, del set. ARGV: batch size, table prefix, staging prefix
+///
+/// Returns `{key, op, {field, value, ...}}` per change. A key deleted and
+/// set again before it was popped yields a DEL followed by a SET.
+const POP_SCRIPT: &str = r#"
+local ret = {}
+local keys = redis.call('SPOP', KEYS[1], ARGV[1])
+for _, key in ipairs(keys) do
+    local table_key = ARGV[2] .. key
+    local staging_key = ARGV[3] .. table_key
+    if redis.call('SREM', KEYS[2], key) == 1 then
+        redis.call('DEL', table_key)
+        table.insert(ret, {key, 'DEL', {}})
+    end
+    local fields = redis.call('HGETALL', staging_key)
+    if #fields > 0 then
+        for i = 1, #fields, 2 do
+            redis.call('HSET', table_key, fields[i], fields[i + 1])
+        end
+        redis.call('DEL', staging_key)
+        table.insert(ret, {key, 'SET', fields})
+    end
+end
+return ret
+"#;
+
+/// A change popped from a state table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyOpFields {
+    /// Entry key within the table (`Vlan100`)
+    pub key: String,
+    /// `Set` or `Del`
+    pub operation: Operation,
+    /// Fields written by a `Set`; empty for a `Del`
+    pub fields: HashMap<String, String>,
+}
+
+/// Key names shared by the producer and consumer of a table
+#[derive(Debug, Clone)]
+struct StateTableKeys {
+    db: Database,
+    table: String,
+    table_keys: TableKeys,
+}
+
+impl StateTableKeys {
+    fn channel(&self) -> String {
+        format!("{}_CHANNEL@{}", self.table, self.db as i64)
+    }
+
+    fn key_set(&self) -> String {
+        self.table_keys.scoped(&format!("{}_KEY_SET", self.table))
+    }
+
+    fn del_set(&self) -> String {
+        self.table_keys.scoped(&format!("{}_DEL_SET", self.table))
+    }
+
+    /// `VLAN_TABLE:`, prepended to an entry key to form the table key
+    fn table_prefix(&self) -> String {
+        self.table_keys.key(&self.table, "")
+    }
+
+    fn staging_key(&self, key: &str) -> String {
+        format!(
+            "{}{}",
+            STAGING_PREFIX,
+            self.table_keys.key(&self.table, key)
+        )
+    }
+}
+
+/// Writes changes to a table the way swss-common's `ProducerStateTable` does
+pub struct ProducerStateTable {
+    db_client: Arc<DbClient>,
+    keys: StateTableKeys,
+}
+
+impl ProducerStateTable {
+    pub fn new(db_client: Arc<DbClient>, db: Database, table: impl Into<String>) -> Self {
+        Self {
+            db_client,
+            keys: StateTableKeys {
+                db,
+                table: table.into(),
+                table_keys: TableKeys::default(),
+            },
+        }
+    }
+
+    /// Override the table key layout
+    pub fn with_table_keys(mut self, table_keys: TableKeys) -> Self {
+        self.keys.table_keys = table_keys;
+        self
+    }
+
+    /// Channel consumers of the table listen on
+    pub fn channel(&self) -> String {
+        self.keys.channel()
+    }
+
+    /// Stage `fields` of `key` for consumers
+    ///
+    /// An empty `fields` still produces a `Set`; the entry is stored with a
+    /// `NULL` placeholder field, as SONiC does for field-less entries.
+    pub async fn set(&self, key: &str, fields: &HashMap<String, String>) -> Result<()> {
+        let script = redis::Script::new(SET_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(self.keys.channel())
+            .key(self.keys.key_set())
+            .key(self.keys.staging_key(key))
+            .arg(WAKEUP_MESSAGE)
+            .arg(key)
+            .arg(EMPTY_FIELD);
+        for (field, value) in fields {
+            invocation.arg(field).arg(value);
+        }
+
+        let mut conn = self.db_client.get_connection(self.keys.db).await?;
+        let _: () = invocation
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        debug!("Produced SET {} on {}", key, self.keys.table);
+        Ok(())
+    }
+
+    /// Stage the deletion of `key` for consumers
+    pub async fn del(&self, key: &str) -> Result<()> {
+        let mut conn = self.db_client.get_connection(self.keys.db).await?;
+        let _: () = redis::Script::new(DEL_SCRIPT)
+            .key(self.keys.channel())
+            .key(self.keys.key_set())
+            .key(self.keys.staging_key(key))
+            .key(self.keys.del_set())
+            .arg(WAKEUP_MESSAGE)
+            .arg(key)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        debug!("Produced DEL {} on {}", key, self.keys.table);
+        Ok(())
+    }
+}
+
+/// Pops changes staged by a [`ProducerStateTable`] or a SONiC producer
+pub struct ConsumerStateTable {
+    db_client: Arc<DbClient>,
+    keys: StateTableKeys,
+}
+
+impl ConsumerStateTable {
+    pub fn new(db_client: Arc<DbClient>, db: Database, table: impl Into<String>) -> Self {
+        Self {
+            db_client,
+            keys: StateTableKeys {
+                db,
+                table: table.into(),
+                table_keys: TableKeys::default(),
+            },
+        }
+    }
+
+    /// Override the table key layout
+    pub fn with_table_keys(mut self, table_keys: TableKeys) -> Self {
+        self.keys.table_keys = table_keys;
+        self
+    }
+
+    /// Channel producers publish on; a message means changes are pending
+    ///
+    /// The name is the raw SONiC one, without the client's channel prefix.
+    pub fn channel(&self) -> String {
+        self.keys.channel()
+    }
+
+    /// Pop up to `count` pending keys and apply them to the table
+    ///
+    /// Returns the changes in the order they must be handled.
+    pub async fn get_batch(&self, count: usize) -> Result<Vec<KeyOpFields>> {
+        let mut conn = self.db_client.get_connection(self.keys.db).await?;
+        let popped: Vec<(String, String, Vec<String>)> = redis::Script::new(POP_SCRIPT)
+            .key(self.keys.key_set())
+            .key(self.keys.del_set())
+            .arg(count)
+            .arg(self.keys.table_prefix())
+            .arg(STAGING_PREFIX)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| racoon_common::RacoonError::Database(e.to_string()))?;
+
+        let batch: Vec<KeyOpFields> = popped
+            .into_iter()
+            .filter_map(|(key, operation, fields)| {
+                Some(KeyOpFields {
+                    key,
+                    operation: Operation::parse(&operation)?,
+                    fields: fields
+                        .chunks_exact(2)
+                        .filter(|pair| pair[0] != EMPTY_FIELD)
+                        .map(|pair| (pair[0].clone(), pair[1].clone()))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        debug!("Consumed {} changes from {}", batch.len(), self.keys.table);
+        Ok(batch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_sonic_key_names() {
+        let keys = StateTableKeys {
+            db: Database::Appl,
+            table: "VLAN_TABLE".to_string(),
+            table_keys: TableKeys::default(),
+        };
+        assert_eq!(keys.channel(), "VLAN_TABLE_CHANNEL@0");
+        assert_eq!(keys.key_set(), "VLAN_TABLE_KEY_SET");
+        assert_eq!(keys.del_set(), "VLAN_TABLE_DEL_SET");
+        assert_eq!(keys.table_prefix(), "VLAN_TABLE:");
+        assert_eq!(keys.staging_key("Vlan100"), "_VLAN_TABLE:Vlan100");
+
+        let scoped = StateTableKeys {
+            table_keys: TableKeys::default().with_namespace("asic0"),
+            ..keys
+        };
+        assert_eq!(scoped.channel(), "VLAN_TABLE_CHANNEL@0");
+        assert_eq!(scoped.key_set(), "asic0:VLAN_TABLE_KEY_SET");
+        assert_eq!(scoped.del_set(), "asic0:VLAN_TABLE_DEL_SET");
+    }
+
+    #[tokio::test]
+    async fn test_set_without_fields_is_consumed() {
+        let server = crate::test_server_or_skip!();
+        let client = Arc::new(server.client().await);
+        let producer = ProducerStateTable::new(client.clone(), Database::Appl, "VLAN_TABLE");
+        let consumer = ConsumerStateTable::new(client.clone(), Database::Appl, "VLAN_TABLE");
+
+        producer.set("Vlan100", &HashMap::new()).await.unwrap();
+        let batch = consumer.get_batch(128).await.unwrap();
+        assert_eq!(
+            batch,
+            vec![KeyOpFields {
+                key: "Vlan100".to_string(),
+                operation: Operation::Set,
+                fields: HashMap::new(),
+            }]
+        );
+        assert!(
+            client
+                .exists(Database::Appl, "VLAN_TABLE:Vlan100")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_and_del_round_trip() {
+        let server = crate::test_server_or_skip!();
+        let client = Arc::new(server.client().await);
+        let producer = ProducerStateTable::new(client.clone(), Database::Appl, "VLAN_TABLE");
+        let consumer = ConsumerStateTable::new(client.clone(), Database::Appl, "VLAN_TABLE");
+        assert_eq!(producer.channel(), consumer.channel());
+
+        let vlan = fields(&[("vlanid", "100"), ("admin_status", "up")]);
+        producer.set("Vlan100", &vlan).await.unwrap();
+
+        let batch = consumer.get_batch(128).await.unwrap();
+        assert_eq!(
+            batch,
+            vec![KeyOpFields {
+                key: "Vlan100".to_string(),
+                operation: Operation::Set,
+                fields: vlan.clone(),
+            }]
+        );
+        assert_eq!(
+            client
+                .hgetall(Database::Appl, "VLAN_TABLE:Vlan100")
+                .await
+                .unwrap(),
+            vlan
+        );
+        assert!(
+            !client
+                .exists(Database::Appl, "_VLAN_TABLE:Vlan100")
+                .await
+                .unwrap()
+        );
+        assert!(consumer.get_batch(128).await.unwrap().is_empty());
+
+        producer.del("Vlan100").await.unwrap();
+        let batch = consumer.get_batch(128).await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].operation, Operation::Del);
+        assert!(batch[0].fields.is_empty());
+        assert!(
+            !client
+                .exists(Database::Appl, "VLAN_TABLE:Vlan100")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_del_then_set_replaces_entry_and_batches_are_bounded() {
+        let server = crate::test_server_or_skip!();
+        let client = Arc::new(server.client().await);
+        let producer = ProducerStateTable::new(client.clone(), Database::Appl, "VLAN_TABLE");
+        let consumer = ConsumerStateTable::new(client.clone(), Database::Appl, "VLAN_TABLE");
+
+        producer
+            .set("Vlan100", &fields(&[("vlanid", "100"), ("mtu", "9100")]))
+            .await
+            .unwrap();
+        consumer.get_batch(128).await.unwrap();
+
+        // Deleted and set again before it was popped: the old fields go
+        let replaced = fields(&[("vlanid", "100"), ("description", "servers")]);
+        producer.del("Vlan100").await.unwrap();
+        producer.set("Vlan100", &replaced).await.unwrap();
+        let operations: Vec<Operation> = consumer
+            .get_batch(128)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|change| change.operation)
+            .collect();
+        assert_eq!(operations, vec![Operation::Del, Operation::Set]);
+        assert_eq!(
+            client
+                .hgetall(Database::Appl, "VLAN_TABLE:Vlan100")
+                .await
+                .unwrap(),
+            replaced
+        );
+
+        // Keys beyond the batch size wait for the next pop
+        for vlan in ["Vlan200", "Vlan300"] {
+            producer
+                .set(vlan, &fields(&[("vlanid", &vlan[4..])]))
+                .await
+                .unwrap();
+        }
+        assert_eq!(consumer.get_batch(1).await.unwrap().len(), 1);
+        assert_eq!(consumer.get_batch(1).await.unwrap().len(), 1);
+        assert!(consumer.get_batch(1).await.unwrap().is_empty());
+    }
+}
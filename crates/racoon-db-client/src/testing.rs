@@ -0,0 +1,110 @@
+//! Ephemeral database server for tests
+//!
+//! [`TestServer::start`] launches `valkey-server` or `redis-server` from PATH
+//! on a free port with a scratch directory, and stops it on drop. When
+//! neither binary is installed it returns `None` so the calling test can
+//! skip instead of failing.
+
+use crate::DbClient;
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Server binaries tried in order
+const SERVER_BINARIES: [&str; 2] = ["valkey-server", "redis-server"];
+
+/// How long to wait for the server to accept connections
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A database server owned by a single test
+pub struct TestServer {
+    child: Child,
+    port: u16,
+    dir: PathBuf,
+}
+
+impl TestServer {
+    /// Start a server, or return `None` if no server binary is on PATH
+    ///
+    /// Panics if a binary exists but the server fails to come up.
+    pub fn start() -> Option<Self> {
+        let binary = SERVER_BINARIES.iter().find_map(|name| find_in_path(name))?;
+
+        // Reserve a free port; the server binds it right after we release it
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("failed to reserve a port")
+            .port();
+
+        let dir = std::env::temp_dir().join(format!("racoon-test-{}-{}", std::process::id(), port));
+        std::fs::create_dir_all(&dir).expect("failed to create server directory");
+
+        let child = Command::new(&binary)
+            .args(["--port", &port.to_string()])
+            .args(["--bind", "127.0.0.1"])
+            .arg("--dir")
+            .arg(&dir)
+            .args(["--save", "", "--appendonly", "no"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|e| panic!("failed to launch {}: {}", binary.display(), e));
+
+        let server = Self { child, port, dir };
+        server.wait_ready();
+        Some(server)
+    }
+
+    /// Connection URL of the server
+    pub fn url(&self) -> String {
+        format!("redis://127.0.0.1:{}", self.port)
+    }
+
+    /// Database client connected to the server
+    pub async fn client(&self) -> DbClient {
+        DbClient::new(&self.url())
+            .await
+            .expect("failed to create client")
+    }
+
+    fn wait_ready(&self) {
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while TcpStream::connect(("127.0.0.1", self.port)).is_err() {
+            assert!(
+                Instant::now() < deadline,
+                "test server did not start on port {}",
+                self.port
+            );
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Start a [`TestServer`], or return from the test when none is available
+#[macro_export]
+macro_rules! test_server_or_skip {
+    () => {
+        match $crate::testing::TestServer::start() {
+            Some(server) => server,
+            None => {
+                eprintln!("skipping: no valkey-server or redis-server on PATH");
+                return;
+            }
+        }
+    };
+}
@@ -0,0 +1,182 @@
+//! In-place VLAN member reconfiguration
+//!
+//! Moving a port between a VLAN's tagged and untagged sets as a delete and
+//! an add makes syncd remove the SAI member and create it again, dropping
+//! traffic in between. [`DbClient::reassign_vlan_member`] changes the
+//! tagging mode of the member entry and announces only that field, so
+//! syncd changes the existing member in place.
+
+use crate::{Database, DbClient, Notification, tables};
+use racoon_common::keys::TableKeys;
+use racoon_common::{RacoonError, Result, VlanTaggingMode};
+use tracing::debug;
+
+/// Field of a VLAN member entry holding its tagging mode
+const TAGGING_MODE_FIELD: &str = "tagging_mode";
+
+/// KEYS: member key. ARGV: channel, notification, then field and
+/// JSON-encoded string value pairs
+///
+/// Rewrites only the changed fields in the stored JSON text, appending those
+/// the entry lacks, so the other fields keep their exact encoding and order.
+/// Returns 0 without writing or publishing when the member doesn't exist.
+const REASSIGN_SCRIPT: &str = r#"
+local entry = redis.call('GET', KEYS[1])
+if not entry then
+    return 0
+end
+for i = 3, #ARGV, 2 do
+    local name = '"' .. ARGV[i] .. '"'
+    local pattern = name:gsub('%p', '%%%0') .. '%s*:%s*"[^"]*"'
+    local field = name .. ':' .. ARGV[i + 1]
+    local replacement = field:gsub('%%', '%%%%')
+    local patched, count = entry:gsub(pattern, replacement, 1)
+    if count == 0 then
+        if entry:match('^%s*{%s*}%s*$') then
+            patched = '{' .. field .. '}'
+        else
+            patched = entry:gsub('}%s*$', ',' .. replacement .. '}', 1)
+        end
+    end
+    entry = patched
+end
+redis.call('SET', KEYS[1], entry)
+redis.call('PUBLISH', ARGV[1], ARGV[2])
+return 1
+"#;
+
+impl DbClient {
+    /// Change the tagging mode of an existing VLAN member
+    ///
+    /// Sets the tagging mode in the `VLAN_MEMBER_TABLE` entry of `port` in
+    /// `vlan`, keyed through `table_keys`, and publishes a `tagging_mode`
+    /// field delta in one transaction, so subscribers never see the entry
+    /// without its notification. The entry's other fields are kept. A
+    /// member that doesn't exist fails with [`RacoonError::KeyNotFound`].
+    pub async fn reassign_vlan_member(
+        &self,
+        db: Database,
+        table_keys: &TableKeys,
+        vlan: &str,
+        port: &str,
+        new_mode: VlanTaggingMode,
+    ) -> Result<()> {
+        let member = format!("{}:{}", vlan, port);
+        let key = table_keys.key(tables::VLAN_MEMBER_TABLE, &member);
+
+        let mut changed = vec![(TAGGING_MODE_FIELD, serde_json::to_string(&new_mode)?)];
+        if let Some(source) = &self.source {
+            changed.push((crate::SOURCE_FIELD, serde_json::to_string(source)?));
+        }
+        let notification = Notification::field_delta(
+            tables::VLAN_MEMBER_TABLE,
+            &member,
+            TAGGING_MODE_FIELD,
+            new_mode.as_str(),
+        );
+        let message = serde_json::to_string(&notification)?;
+
+        let mut conn = self.get_connection(db).await?;
+        let script = redis::Script::new(REASSIGN_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(&key)
+            .arg(self.channel(tables::VLAN_MEMBER_TABLE))
+            .arg(message);
+        for (field, value) in changed {
+            invocation.arg(field).arg(value);
+        }
+        let updated: bool = invocation
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| RacoonError::Database(e.to_string()))?;
+        if !updated {
+            return Err(RacoonError::KeyNotFound(key));
+        }
+
+        debug!("Reassigned VLAN member {} to {}", member, new_mode);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Operation;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    #[tokio::test]
+    #[ignore] // Only run when valkey-server or redis-server is on PATH
+    async fn test_reassign_is_one_update_and_one_event() {
+        let server = crate::testing::TestServer::start().expect("no valkey-server or redis-server");
+        let client = server.client().await;
+        let mut pubsub = redis::Client::open(server.url())
+            .unwrap()
+            .get_async_pubsub()
+            .await
+            .unwrap();
+        pubsub.subscribe(tables::VLAN_MEMBER_TABLE).await.unwrap();
+
+        let keys = TableKeys::default().with_namespace("asic1");
+        let key = "asic1:VLAN_MEMBER_TABLE:Vlan100:Ethernet0";
+        let stored = r#"{"tagging_mode": "untagged","_source":"orchd","vlan_id":100,"ports":[]}"#;
+        redis::cmd("SET")
+            .arg(key)
+            .arg(stored)
+            .query_async::<()>(&mut *client.get_connection(Database::Appl).await.unwrap())
+            .await
+            .unwrap();
+        client
+            .reassign_vlan_member(
+                Database::Appl,
+                &keys,
+                "Vlan100",
+                "Ethernet0",
+                VlanTaggingMode::Tagged,
+            )
+            .await
+            .unwrap();
+
+        // Only the tagging mode changes; the other fields keep their encoding
+        let entry: String = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut *client.get_connection(Database::Appl).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            entry,
+            r#"{"tagging_mode":"tagged","_source":"orchd","vlan_id":100,"ports":[]}"#
+        );
+
+        let mut messages = pubsub.into_on_message();
+        let message = tokio::time::timeout(Duration::from_secs(5), messages.next())
+            .await
+            .expect("no event within 5s")
+            .unwrap();
+        let notification: Notification =
+            serde_json::from_str(&message.get_payload::<String>().unwrap()).unwrap();
+        assert_eq!(notification.operation, Operation::Hset);
+        assert_eq!(notification.key, "Vlan100:Ethernet0");
+        assert_eq!(notification.field.as_deref(), Some("tagging_mode"));
+        assert_eq!(notification.value, Some("tagged".into()));
+
+        // A missing member is neither written nor announced
+        let err = client
+            .reassign_vlan_member(
+                Database::Appl,
+                &keys,
+                "Vlan100",
+                "Ethernet4",
+                VlanTaggingMode::Tagged,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RacoonError::KeyNotFound(_)));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), messages.next())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,4 +1,100 @@
-// racoon-mgmtd - placeholder
-pub fn placeholder() {
-    println!("racoon-mgmtd not yet implemented");
+//! Racoon Management Daemon
+//!
+//! REST interface on `management.rest_api_port`, answered from orchd's gRPC
+//! management interface:
+//!
+//! - `GET /events`: the notifications orchd processed last, oldest first,
+//!   as JSON objects with `ts`, `operation`, `key` and `error`
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use racoon_common::metrics::ProcessedEvent;
+use racoon_orchd::grpc::ManagementClient;
+use racoon_orchd::grpc::proto::RecentEventsRequest;
+use tonic::transport::Channel;
+use tracing::warn;
+
+/// REST routes, answered by the orchd behind `orchd`
+pub fn router(orchd: ManagementClient<Channel>) -> Router {
+    Router::new()
+        .route("/events", get(events))
+        .with_state(orchd)
+}
+
+async fn events(
+    State(mut orchd): State<ManagementClient<Channel>>,
+) -> Result<Json<Vec<ProcessedEvent>>, (StatusCode, String)> {
+    let response = orchd
+        .recent_events(RecentEventsRequest {})
+        .await
+        .map_err(|status| {
+            warn!("Failed to fetch events from orchd: {}", status);
+            (StatusCode::BAD_GATEWAY, status.message().to_string())
+        })?;
+    let events = response
+        .into_inner()
+        .events
+        .into_iter()
+        .map(|event| ProcessedEvent {
+            ts: event.ts,
+            operation: event.operation,
+            key: event.key,
+            error: event.error,
+        })
+        .collect();
+    Ok(Json(events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use racoon_db_client::DbClient;
+    use racoon_orchd::VlanOrch;
+    use racoon_orchd::grpc::ManagementService;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_events_served_from_orchd() {
+        // DbClient connects lazily; a rejected field delta never uses it
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        let vlan_orch = Arc::new(VlanOrch::new(db_client.clone()));
+        let message = serde_json::json!({
+            "operation": "HSET",
+            "key": "VLAN_MEMBER|Vlan100|Ethernet0",
+        });
+        vlan_orch
+            .handle_notification("CONFIG_DB:VLAN_MEMBER", &message.to_string())
+            .await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = ManagementService::new(vlan_orch, db_client).into_server();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        let orchd = ManagementClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+
+        let response = router(orchd)
+            .oneshot(Request::get("/events").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let events: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(events.as_array().unwrap().len(), 1);
+        assert_eq!(events[0]["operation"], "HSET");
+        assert_eq!(events[0]["key"], "VLAN_MEMBER|Vlan100|Ethernet0");
+        assert!(events[0]["error"].is_string());
+    }
 }
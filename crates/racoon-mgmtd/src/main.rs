@@ -0,0 +1,56 @@
+//! Racoon Management Daemon
+//!
+//! Serves the REST interface on loopback; it is unauthenticated, like
+//! orchd's gRPC interface it answers from.
+
+use anyhow::{Context, Result};
+use racoon_common::Config;
+use racoon_common::logging::{init_logging, shutdown_tracing};
+use racoon_orchd::grpc::ManagementClient;
+use std::net::{Ipv4Addr, SocketAddr};
+use tonic::transport::Endpoint;
+use tracing::{info, warn};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Load configuration; built-in defaults apply when the file is unavailable
+    let config_path =
+        std::env::var("RACOON_CONFIG").unwrap_or_else(|_| "/etc/racoon/racoon.toml".to_string());
+    let (config, load_error) = match Config::load(&config_path) {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+
+    let _log_level = init_logging(&config.logging)?;
+
+    info!("Starting Racoon Management Daemon (mgmtd)");
+    if let Some(e) = load_error {
+        warn!("Failed to load config from {}: {}", config_path, e);
+    }
+
+    // orchd is dialed on the first request, so either may start first
+    let grpc_port = config
+        .management
+        .grpc_port
+        .context("management.grpc_port is not set; mgmtd needs orchd's gRPC interface")?;
+    let orchd_addr = SocketAddr::new(config.management.grpc_addr, grpc_port);
+    let orchd = ManagementClient::new(
+        Endpoint::from_shared(format!("http://{}", orchd_addr))?.connect_lazy(),
+    );
+
+    let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), config.management.rest_api_port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Serving REST interface on {}", addr);
+
+    let result = axum::serve(listener, racoon_mgmtd::router(orchd))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Received shutdown signal");
+        })
+        .await;
+
+    shutdown_tracing();
+    result?;
+    info!("mgmtd stopped");
+    Ok(())
+}
@@ -0,0 +1,22 @@
+fn main() {
+    // The management service is only generated with the `grpc` feature
+    #[cfg(feature = "grpc")]
+    compile_management_proto();
+}
+
+#[cfg(feature = "grpc")]
+fn compile_management_proto() {
+    println!("cargo:rerun-if-changed=proto/mgmt.proto");
+
+    // Use a bundled protoc unless the environment provides one
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("bundled protoc is available");
+        // SAFETY: build scripts are single-threaded
+        unsafe { std::env::set_var("PROTOC", protoc) };
+    }
+
+    // The client is used by racoon-cli and racoon-mgmtd
+    tonic_build::configure()
+        .compile_protos(&["proto/mgmt.proto"], &["proto"])
+        .expect("failed to compile proto/mgmt.proto");
+}
@@ -0,0 +1,350 @@
+//! gRPC management service
+//!
+//! Exposes the VLAN orchestrator to SDN controllers. Reads come from the
+//! orchestrator's tracked state; `CreateVlan` and `DeleteVlan` only write
+//! CONFIG_DB and notify the VLAN config channel, so the change reaches
+//! APPL_DB and syncd through the same path as any other config writer.
+//! `RecentEvents` returns [`VlanOrch::recent_events`] for racoon-cli's
+//! `events` command and racoon-mgmtd's `/events`.
+//! `Resync` rebuilds APPL_DB from CONFIG_DB with [`VlanOrch::resync_all`]
+//! for racoon-cli's `orch resync` command.
+
+use crate::agent_channels;
+use crate::vlan_orch::{VlanConfig, VlanOrch};
+use racoon_common::config::SyncAgent;
+use racoon_common::metrics::{ProcessedEvent, unix_millis};
+use racoon_common::{RacoonError, VlanId};
+use racoon_db_client::{DbClient, Notification, Operation, home_db, tables};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+/// Code generated from `proto/mgmt.proto`
+pub mod proto {
+    tonic::include_proto!("racoon.mgmt.v1");
+}
+
+pub use proto::management_client::ManagementClient;
+pub use proto::management_server::{Management, ManagementServer};
+use proto::{
+    CreateVlanRequest, CreateVlanResponse, DeleteVlanRequest, DeleteVlanResponse, Event,
+    GetStatsRequest, GetStatsResponse, ListVlansRequest, ListVlansResponse, RecentEventsRequest,
+    RecentEventsResponse, ResyncRequest, ResyncResponse, Vlan,
+};
+
+/// Channel orchd listens on for VLAN config changes
+fn vlan_config_channel() -> &'static str {
+    agent_channels(SyncAgent::Vlan)
+        .iter()
+        .find(|channel| channel.rsplit(':').next() == Some(tables::VLAN))
+        .expect("the VLAN agent subscribes to VLAN config changes")
+}
+
+/// [`Management`] implementation backed by a [`VlanOrch`]
+pub struct ManagementService {
+    vlan_orch: Arc<VlanOrch>,
+    db_client: Arc<DbClient>,
+}
+
+impl ManagementService {
+    pub fn new(vlan_orch: Arc<VlanOrch>, db_client: Arc<DbClient>) -> Self {
+        Self {
+            vlan_orch,
+            db_client,
+        }
+    }
+
+    /// Wrap the service for a tonic server
+    pub fn into_server(self) -> ManagementServer<Self> {
+        ManagementServer::new(self)
+    }
+
+    /// Notify orchd that a CONFIG_DB VLAN entry changed
+    async fn notify(&self, operation: Operation, config_key: &str) -> Result<(), Status> {
+        let notification = Notification {
+            operation,
+            table: Some(tables::VLAN.to_string()),
+            key: config_key.to_string(),
+            data: None,
+            field: None,
+            value: None,
+            ts: Some(unix_millis()),
+        };
+        self.db_client
+            .publish_notification(vlan_config_channel(), &notification)
+            .await
+            .map_err(to_status)
+    }
+}
+
+/// CONFIG_DB key and VLAN id of a requested VLAN, if the id is valid
+fn vlan_config_key(vlan_id: u32) -> Option<(String, VlanId)> {
+    let vlan_id = VlanId::new(u16::try_from(vlan_id).ok()?)?;
+    Some((format!("{}|Vlan{}", tables::VLAN, vlan_id.get()), vlan_id))
+}
+
+fn invalid_vlan_id(vlan_id: u32) -> Status {
+    Status::invalid_argument(format!("invalid VLAN ID: {}", vlan_id))
+}
+
+impl From<ProcessedEvent> for Event {
+    fn from(event: ProcessedEvent) -> Self {
+        Self {
+            ts: event.ts,
+            operation: event.operation,
+            key: event.key,
+            error: event.error,
+        }
+    }
+}
+
+fn to_status(e: RacoonError) -> Status {
+    match e {
+        RacoonError::InvalidVlanId(_) | RacoonError::InvalidVlanName(_) => {
+            Status::invalid_argument(e.to_string())
+        }
+        RacoonError::VlanNotFound(_) | RacoonError::KeyNotFound(_) => {
+            Status::not_found(e.to_string())
+        }
+        e => Status::internal(e.to_string()),
+    }
+}
+
+#[tonic::async_trait]
+impl Management for ManagementService {
+    async fn list_vlans(
+        &self,
+        _request: Request<ListVlansRequest>,
+    ) -> Result<Response<ListVlansResponse>, Status> {
+        let vlans = self
+            .vlan_orch
+            .vlans()
+            .into_iter()
+            .map(|(vlan_id, entry)| Vlan {
+                vlan_id: vlan_id.get().into(),
+                description: entry.description,
+            })
+            .collect();
+        Ok(Response::new(ListVlansResponse { vlans }))
+    }
+
+    async fn get_stats(
+        &self,
+        _request: Request<GetStatsRequest>,
+    ) -> Result<Response<GetStatsResponse>, Status> {
+        let stats = self.vlan_orch.stats();
+        Ok(Response::new(GetStatsResponse {
+            vlan_count: stats.vlan_count as u64,
+            ignored_notifications: stats.ignored.count,
+        }))
+    }
+
+    async fn create_vlan(
+        &self,
+        request: Request<CreateVlanRequest>,
+    ) -> Result<Response<CreateVlanResponse>, Status> {
+        let request = request.into_inner();
+        let (config_key, vlan_id) =
+            vlan_config_key(request.vlan_id).ok_or_else(|| invalid_vlan_id(request.vlan_id))?;
+
+        let config = VlanConfig {
+            vlanid: vlan_id.get(),
+            description: request.description,
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        self.db_client
+            .set(home_db(tables::VLAN), &config_key, &config)
+            .await
+            .map_err(to_status)?;
+        self.notify(Operation::Set, &config_key).await?;
+
+        info!("gRPC: wrote {} to CONFIG_DB", config_key);
+        Ok(Response::new(CreateVlanResponse {}))
+    }
+
+    async fn delete_vlan(
+        &self,
+        request: Request<DeleteVlanRequest>,
+    ) -> Result<Response<DeleteVlanResponse>, Status> {
+        let requested = request.into_inner().vlan_id;
+        let (config_key, vlan_id) =
+            vlan_config_key(requested).ok_or_else(|| invalid_vlan_id(requested))?;
+
+        let exists = self
+            .db_client
+            .exists(home_db(tables::VLAN), &config_key)
+            .await
+            .map_err(to_status)?;
+        if !exists {
+            return Err(to_status(RacoonError::VlanNotFound(vlan_id.get())));
+        }
+
+        self.db_client
+            .del(home_db(tables::VLAN), &config_key)
+            .await
+            .map_err(to_status)?;
+        self.notify(Operation::Del, &config_key).await?;
+
+        info!("gRPC: removed {} from CONFIG_DB", config_key);
+        Ok(Response::new(DeleteVlanResponse {}))
+    }
+
+    async fn recent_events(
+        &self,
+        _request: Request<RecentEventsRequest>,
+    ) -> Result<Response<RecentEventsResponse>, Status> {
+        let events = self
+            .vlan_orch
+            .recent_events()
+            .into_iter()
+            .map(Event::from)
+            .collect();
+        Ok(Response::new(RecentEventsResponse { events }))
+    }
+
+    async fn resync(
+        &self,
+        _request: Request<ResyncRequest>,
+    ) -> Result<Response<ResyncResponse>, Status> {
+        let vlan_count = self.vlan_orch.resync_all().await.map_err(to_status)?;
+
+        info!("gRPC: resynced {} VLANs into APPL_DB", vlan_count);
+        Ok(Response::new(ResyncResponse {
+            vlan_count: vlan_count as u64,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_db_client::Database;
+    use tonic::Code;
+
+    #[test]
+    fn test_vlan_config_channel_subscribed() {
+        // Writes are announced where the VLAN agent listens
+        assert_eq!(vlan_config_channel(), "CONFIG_DB:VLAN");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_vlan_rejected() {
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        let service = ManagementService::new(Arc::new(VlanOrch::new(db_client.clone())), db_client);
+
+        for vlan_id in [0, 4095, 70000] {
+            let status = service
+                .create_vlan(Request::new(CreateVlanRequest {
+                    vlan_id,
+                    description: None,
+                }))
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+        }
+
+        let stats = service
+            .get_stats(Request::new(GetStatsRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stats.vlan_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_served() {
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        let vlan_orch = Arc::new(VlanOrch::new(db_client.clone()));
+        let service = ManagementService::new(vlan_orch.clone(), db_client);
+
+        // Field deltas are rejected without touching the DB
+        let message = serde_json::json!({
+            "operation": "HSET",
+            "key": "VLAN_MEMBER|Vlan100|Ethernet0",
+        });
+        vlan_orch
+            .handle_notification("CONFIG_DB:VLAN_MEMBER", &message.to_string())
+            .await;
+
+        let events = service
+            .recent_events(Request::new(RecentEventsRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .events;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].operation, "HSET");
+        assert_eq!(events[0].key, "VLAN_MEMBER|Vlan100|Ethernet0");
+        assert!(events[0].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_create_list_delete() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = Arc::new(VlanOrch::new(db_client.clone()));
+        let service = ManagementService::new(vlan_orch.clone(), db_client.clone());
+        let notify = |operation: &str| {
+            serde_json::json!({"operation": operation, "key": "VLAN|Vlan200"}).to_string()
+        };
+
+        service
+            .create_vlan(Request::new(CreateVlanRequest {
+                vlan_id: 200,
+                description: Some("controller".to_string()),
+            }))
+            .await
+            .unwrap();
+        let config: VlanConfig = db_client
+            .get(Database::Config, "VLAN|Vlan200")
+            .await
+            .unwrap();
+        assert_eq!(config.vlanid, 200);
+
+        // The subscriber would deliver this; orchd then programs APPL_DB
+        vlan_orch
+            .handle_notification(vlan_config_channel(), &notify("SET"))
+            .await;
+        let vlans = service
+            .list_vlans(Request::new(ListVlansRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .vlans;
+        assert_eq!(
+            vlans,
+            vec![Vlan {
+                vlan_id: 200,
+                description: Some("controller".to_string()),
+            }]
+        );
+
+        service
+            .delete_vlan(Request::new(DeleteVlanRequest { vlan_id: 200 }))
+            .await
+            .unwrap();
+        assert!(
+            !db_client
+                .exists(Database::Config, "VLAN|Vlan200")
+                .await
+                .unwrap()
+        );
+        vlan_orch
+            .handle_notification(vlan_config_channel(), &notify("DEL"))
+            .await;
+        let stats = service
+            .get_stats(Request::new(GetStatsRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(stats.vlan_count, 0);
+
+        let status = service
+            .delete_vlan(Request::new(DeleteVlanRequest { vlan_id: 200 }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+}
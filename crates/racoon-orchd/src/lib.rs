@@ -2,6 +2,44 @@
 //!
 //! Translates configuration from CONFIG_DB to application-level database entries
 
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod vlan_orch;
 
 pub use vlan_orch::{VlanOrch, VlanOrchSubscriber};
+
+use racoon_common::config::SyncAgent;
+
+/// CONFIG_DB channels consumed by each agent
+///
+/// Agents without an implementation in orchd have no channels.
+pub fn agent_channels(agent: SyncAgent) -> &'static [&'static str] {
+    match agent {
+        SyncAgent::Vlan => &["CONFIG_DB:VLAN", "CONFIG_DB:VLAN_MEMBER"],
+        SyncAgent::Lag | SyncAgent::Fdb => &[],
+    }
+}
+
+/// Channels to subscribe to for the enabled agents
+pub fn subscription_channels(agents: &[SyncAgent]) -> Vec<String> {
+    agents
+        .iter()
+        .flat_map(|agent| agent_channels(*agent))
+        .map(|channel| channel.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_channels_follow_enabled_agents() {
+        let channels = subscription_channels(&[SyncAgent::Vlan, SyncAgent::Lag]);
+        assert!(channels.iter().all(|c| !c.contains("FDB")));
+        assert!(channels.contains(&"CONFIG_DB:VLAN".to_string()));
+
+        let channels = subscription_channels(&[SyncAgent::Lag, SyncAgent::Fdb]);
+        assert!(channels.iter().all(|c| !c.contains("VLAN")));
+    }
+}
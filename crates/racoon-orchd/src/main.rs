@@ -3,53 +3,162 @@
 //! Translates configuration from CONFIG_DB to application-level entries
 
 use anyhow::Result;
-use racoon_db_client::{DbClient, DbSubscriberClient};
-use racoon_orchd::{VlanOrch, VlanOrchSubscriber};
+use arc_swap::ArcSwap;
+use racoon_common::Config;
+use racoon_common::config::SyncAgent;
+use racoon_common::logging::{LogLevelHandle, init_logging, shutdown_tracing};
+use racoon_common::reload::{SharedConfig, reload_config};
+use racoon_db_client::DbClient;
+use racoon_orchd::{VlanOrch, VlanOrchSubscriber, agent_channels, subscription_channels};
 use std::sync::Arc;
-use tracing::{error, info};
+use tokio::signal::unix::{SignalKind, signal};
+use tracing::{error, info, warn};
+
+/// Settings applied on SIGHUP besides the log level; both resubscribe
+const HOT_SETTINGS: [&str; 2] = ["services.agents", "database.pubsub_watchdog_secs"];
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .with_thread_ids(true)
-        .with_level(true)
-        .init();
+    // Load configuration; built-in defaults apply when the file is unavailable
+    let config_path =
+        std::env::var("RACOON_CONFIG").unwrap_or_else(|_| "/etc/racoon/racoon.toml".to_string());
+    let (config, load_error) = match Config::load(&config_path) {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+
+    // Initialize tracing as the [logging] section asks
+    let log_level = init_logging(&config.logging)?;
 
     info!("Starting Racoon Orchestration Daemon (orchd)");
+    if let Some(e) = load_error {
+        warn!("Failed to load config from {}: {}", config_path, e);
+    }
+    let config: SharedConfig = Arc::new(ArcSwap::from_pointee(config));
+    let mut hangup = signal(SignalKind::hangup())?;
+    let startup_config = config.load_full();
 
-    // Get database URL from environment or use default
+    // Database URL from the environment, then the config
     let db_url =
-        std::env::var("RACOON_DB_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        std::env::var("RACOON_DB_URL").unwrap_or_else(|_| startup_config.database.to_url());
     info!("Connecting to database: {}", db_url);
 
     // Create database client
-    let db_client = Arc::new(DbClient::new(&db_url).await?);
+    // Channel namespace shared by publisher and subscribers
+    let channel_prefix = startup_config.database.channel_prefix.clone();
+    let mut db_client = DbClient::new(&db_url).await?;
+    if let Some(prefix) = &channel_prefix {
+        info!("Using pub/sub channel prefix: {}", prefix);
+        db_client = db_client.with_channel_prefix(prefix);
+    }
+    let db_client = Arc::new(db_client);
     info!("Database client connected");
 
+    // Select sync agents; all of them run when none is configured
+    let agents = startup_config.services.sync_agents();
+    for agent in &agents {
+        if agent_channels(*agent).is_empty() {
+            warn!("{} agent is not available in orchd, skipping", agent.name());
+        }
+    }
+
     // Create VLAN orchestration agent
-    let vlan_orch = Arc::new(VlanOrch::new(db_client.clone()));
+    let vlan_orch = Arc::new(
+        VlanOrch::new(db_client.clone())
+            .with_vlan_defaults(startup_config.vlan.clone())
+            .with_table_keys(startup_config.database.table_keys()),
+    );
 
-    // Start VLAN orchestration (load existing VLANs)
-    vlan_orch.start().await?;
-    info!("VLAN orchestration agent started");
+    if agents.contains(&SyncAgent::Vlan) {
+        // Start VLAN orchestration (load existing VLANs)
+        vlan_orch.start().await?;
+        info!("VLAN orchestration agent started");
+    }
 
-    // Create subscriber for CONFIG_DB changes
-    let subscriber_client = DbSubscriberClient::new(&db_url)?;
     let vlan_subscriber = Arc::new(VlanOrchSubscriber::new(vlan_orch.clone()));
 
-    info!("Subscribing to CONFIG_DB VLAN channel");
+    // Serve the gRPC management interface when a port is configured
+    #[cfg(feature = "grpc")]
+    if let Some(port) = startup_config.management.grpc_port {
+        let addr = std::net::SocketAddr::new(startup_config.management.grpc_addr, port);
+        let service =
+            racoon_orchd::grpc::ManagementService::new(vlan_orch.clone(), db_client.clone());
+        info!("Serving gRPC management interface on {}", addr);
+        tokio::spawn(async move {
+            if let Err(e) = tonic::transport::Server::builder()
+                .add_service(service.into_server())
+                .serve(addr)
+                .await
+            {
+                error!("gRPC management interface failed: {}", e);
+            }
+        });
+    }
+
+    // Process configuration changes of the enabled agents until ctrl-c;
+    // a SIGHUP changing the agents or the watchdog resubscribes
+    let result = 'run: loop {
+        let subscription = subscribe(&db_client, config.load_full(), vlan_subscriber.clone());
+        tokio::pin!(subscription);
+        loop {
+            tokio::select! {
+                result = &mut subscription => break 'run result,
+                _ = tokio::signal::ctrl_c() => {
+                    info!("Received shutdown signal");
+                    break 'run Ok(());
+                }
+                _ = hangup.recv() => {
+                    info!("Received SIGHUP, reloading {}", config_path);
+                    if reload(&config_path, &config, &log_level) {
+                        // Changes made between the old subscription and the
+                        // new one would be missed
+                        vlan_subscriber.resync_on_subscribe();
+                        break;
+                    }
+                }
+            }
+        }
+    };
 
-    // Subscribe to VLAN configuration changes
-    // This will block and process messages
-    if let Err(e) = subscriber_client
-        .subscribe(vec!["CONFIG_DB:VLAN".to_string()], vlan_subscriber)
-        .await
-    {
+    db_client.shutdown().await;
+    shutdown_tracing();
+
+    if let Err(e) = result {
         error!("Subscription error: {}", e);
         return Err(e.into());
     }
 
+    info!("orchd stopped");
     Ok(())
 }
+
+/// Re-read the config, returning whether the subscriptions must be renewed
+fn reload(path: &str, config: &SharedConfig, log_level: &LogLevelHandle) -> bool {
+    match reload_config(path, config, log_level, &HOT_SETTINGS) {
+        Ok(reload) => HOT_SETTINGS.iter().any(|setting| reload.applied(setting)),
+        Err(e) => {
+            error!("Failed to reload config, keeping the running one: {}", e);
+            false
+        }
+    }
+}
+
+/// Subscribe to the CONFIG_DB channels of the agents `config` enables
+async fn subscribe(
+    db_client: &DbClient,
+    config: Arc<Config>,
+    subscriber: Arc<VlanOrchSubscriber>,
+) -> racoon_common::Result<()> {
+    let channels = subscription_channels(&config.services.sync_agents());
+    let mut subscriber_client = db_client.subscriber();
+    if let Some(window) = config.database.pubsub_watchdog() {
+        subscriber_client = subscriber_client.with_watchdog(window);
+    }
+
+    info!("Subscribing to CONFIG_DB channels: {:?}", channels);
+    if channels.is_empty() {
+        std::future::pending().await
+    } else {
+        subscriber_client.subscribe(channels, subscriber).await
+    }
+}
@@ -4,11 +4,19 @@
 
 use async_trait::async_trait;
 use dashmap::DashMap;
-use racoon_common::{Result, VlanId};
-use racoon_db_client::{Database, DbClient, DbSubscriber};
+use racoon_common::config::VlanDefaultsConfig;
+use racoon_common::keys::TableKeys;
+use racoon_common::metrics::{
+    EventLog, IgnoredNotifications, IgnoredSnapshot, ProcessedEvent, unix_millis,
+};
+use racoon_common::reconcile::{Changes, diff};
+use racoon_common::{PortAdminStatus, PortName, Result, VlanId, VlanTaggingMode};
+use racoon_db_client::{DbClient, DbSubscriber, Operation, home_db, tables};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{debug, error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, error, info, instrument, warn};
 
 /// VLAN configuration from CONFIG_DB
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,14 +24,148 @@ pub struct VlanConfig {
     pub vlanid: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Admin status of the VLAN's L3 interface; `None` leaves it up
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
+    /// MTU of the VLAN's L3 interface; `None` keeps the platform default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+    /// Programming order on sync; higher values go first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+}
+
+/// Config shared by a range of VLANs (`VLAN|Vlan100-200`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VlanRangeConfig {
+    /// Description of each VLAN; `{vlanid}` is replaced by its VLAN id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Admin status of the VLANs' L3 interfaces; `None` leaves them up
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
+    /// MTU of the VLANs' L3 interfaces; `None` keeps the platform default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+}
+
+impl VlanRangeConfig {
+    /// Config of one VLAN of the range
+    pub fn expand(&self, vlan_id: VlanId) -> VlanConfig {
+        VlanConfig {
+            vlanid: vlan_id.get(),
+            description: self
+                .description
+                .as_ref()
+                .map(|template| template.replace("{vlanid}", &vlan_id.to_string())),
+            admin_status: self.admin_status,
+            mtu: self.mtu,
+            priority: None,
+        }
+    }
+}
+
+/// Whether a CONFIG_DB VLAN name is a range (`Vlan100-200`)
+fn is_vlan_range(vlan_name: &str) -> bool {
+    vlan_name.contains('-')
+}
+
+/// VLAN member configuration from CONFIG_DB (`VLAN_MEMBER|Vlan100|Ethernet0`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VlanMemberConfig {
+    /// `tagged`, `untagged` or `priority_tagged`; anything else is rejected
+    pub tagging_mode: VlanTaggingMode,
+}
+
+/// Priority of VLANs that don't set one
+pub const DEFAULT_VLAN_PRIORITY: i32 = 0;
+
+/// Order VLAN configs for programming, highest priority first
+///
+/// The sort is stable, so VLANs with equal priority keep their input order.
+pub fn order_by_priority(vlans: &mut [(String, VlanConfig)]) {
+    vlans.sort_by_key(|(_, config)| {
+        std::cmp::Reverse(config.priority.unwrap_or(DEFAULT_VLAN_PRIORITY))
+    });
 }
 
 /// VLAN entry for APPL_DB
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VlanEntry {
     pub vlanid: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Admin status of the VLAN's L3 interface; `None` leaves it up
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
+    /// MTU of the VLAN's L3 interface; `None` keeps the platform default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+}
+
+/// VLAN member entry for APPL_DB
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VlanMemberEntry {
+    pub tagging_mode: VlanTaggingMode,
+}
+
+/// Build the default untagged memberships for access ports
+///
+/// Returns `(APPL_DB key, entry)` pairs keyed by canonical port name. Ports
+/// listed in `explicit_ports` (canonical names) already have a configured
+/// membership and are left out.
+pub fn default_vlan_members(
+    defaults: &VlanDefaultsConfig,
+    explicit_ports: &HashSet<String>,
+    table_keys: &TableKeys,
+) -> Vec<(String, VlanMemberEntry)> {
+    let Some(vlan_id) = defaults.default_vlan else {
+        return Vec::new();
+    };
+
+    defaults
+        .access_ports
+        .iter()
+        .map(|port| PortName::normalize(port))
+        .filter(|port| !explicit_ports.contains(port))
+        .map(|port| {
+            (
+                table_keys.key(
+                    tables::VLAN_MEMBER_TABLE,
+                    &format!("Vlan{}:{}", vlan_id, port),
+                ),
+                VlanMemberEntry {
+                    tagging_mode: VlanTaggingMode::Untagged,
+                },
+            )
+        })
+        .collect()
+}
+
+/// BOOT_STATE key recording the access ports default provisioning handled
+pub const DEFAULT_PROVISIONED_KEY: &str = "default_provisioned";
+
+/// Access ports default provisioning has handled
+/// (STATE_DB `BOOT_STATE:default_provisioned`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvisionedDefaults {
+    pub ts: u64,
+    /// Canonical names of the ports assigned to the default VLAN, or left
+    /// out for their explicit membership
+    #[serde(default)]
+    pub ports: Vec<String>,
 }
 
 /// VLAN Orchestration Agent
@@ -31,6 +173,20 @@ pub struct VlanOrch {
     db_client: Arc<DbClient>,
     /// Track VLANs we've processed
     vlans: DashMap<VlanId, VlanEntry>,
+    /// VLANs each range key expanded to, without individually configured ones
+    ranges: DashMap<String, Vec<VlanId>>,
+    /// Default access-port membership settings
+    vlan_defaults: VlanDefaultsConfig,
+    /// Ports currently holding a default membership (port -> APPL_DB key)
+    default_members: DashMap<String, String>,
+    /// APPL_DB key layout
+    table_keys: TableKeys,
+    /// Notifications dropped before handling
+    ignored: IgnoredNotifications,
+    /// Outcome of the last processed notifications
+    events: EventLog,
+    /// Held by a full resync; notifications wait for it to finish
+    sync_lock: tokio::sync::Mutex<()>,
 }
 
 impl VlanOrch {
@@ -39,9 +195,35 @@ impl VlanOrch {
         Self {
             db_client,
             vlans: DashMap::new(),
+            ranges: DashMap::new(),
+            vlan_defaults: VlanDefaultsConfig::default(),
+            default_members: DashMap::new(),
+            table_keys: TableKeys::default(),
+            ignored: IgnoredNotifications::new(),
+            events: EventLog::default(),
+            sync_lock: tokio::sync::Mutex::new(()),
         }
     }
 
+    /// Configure default VLAN membership for access ports
+    pub fn with_vlan_defaults(mut self, vlan_defaults: VlanDefaultsConfig) -> Self {
+        self.vlan_defaults = vlan_defaults;
+        self
+    }
+
+    /// Override the APPL_DB key layout
+    pub fn with_table_keys(mut self, table_keys: TableKeys) -> Self {
+        self.table_keys = table_keys;
+        self
+    }
+
+    /// Keep the last `capacity` processed notifications for
+    /// [`recent_events`](Self::recent_events)
+    pub fn with_event_capacity(mut self, capacity: usize) -> Self {
+        self.events = EventLog::new(capacity);
+        self
+    }
+
     /// Start the orchestration agent
     pub async fn start(&self) -> Result<()> {
         info!("Starting VLAN orchestration agent");
@@ -49,22 +231,51 @@ impl VlanOrch {
         // Load existing VLANs from CONFIG_DB
         self.sync_vlans().await?;
 
+        // Assign access ports without explicit membership to the default VLAN,
+        // once per port: later boots keep what APPL_DB already holds
+        self.provision_first_boot().await?;
+
         info!("VLAN orchestration agent started");
         Ok(())
     }
 
     /// Sync all VLANs from CONFIG_DB to APPL_DB
-    async fn sync_vlans(&self) -> Result<()> {
+    pub async fn sync_vlans(&self) -> Result<()> {
         info!("Syncing VLANs from CONFIG_DB");
 
-        let keys = self.db_client.keys(Database::Config, "VLAN|Vlan*").await?;
+        let (range_keys, keys): (Vec<String>, Vec<String>) = self
+            .db_client
+            .scan(home_db(tables::VLAN), "VLAN|Vlan*")
+            .await?
+            .into_iter()
+            .partition(|key| is_vlan_range(key));
+        let configs: Vec<Option<VlanConfig>> = self
+            .db_client
+            .get_many(home_db(tables::VLAN), &keys)
+            .await?;
 
-        for key in keys {
-            if let Some(vlan_name) = key.strip_prefix("VLAN|") {
-                match self.process_vlan_config(vlan_name).await {
-                    Ok(_) => debug!("Synced VLAN: {}", vlan_name),
-                    Err(e) => warn!("Failed to sync VLAN {}: {}", vlan_name, e),
-                }
+        let mut vlans: Vec<(String, VlanConfig)> = keys
+            .iter()
+            .zip(configs)
+            .filter_map(|(key, config)| {
+                let vlan_name = key.strip_prefix("VLAN|")?;
+                Some((vlan_name.to_string(), config?))
+            })
+            .collect();
+        order_by_priority(&mut vlans);
+
+        for (vlan_name, config) in vlans {
+            match self.apply_vlan_config(&vlan_name, config).await {
+                Ok(_) => debug!("Synced VLAN: {}", vlan_name),
+                Err(e) => warn!("Failed to sync VLAN {}: {}", vlan_name, e),
+            }
+        }
+
+        // Ranges go last, around the individually configured VLANs
+        for key in &range_keys {
+            let range_name = key.strip_prefix("VLAN|").unwrap_or(key);
+            if let Err(e) = self.process_range_config(range_name).await {
+                warn!("Failed to sync VLAN range {}: {}", range_name, e);
             }
         }
 
@@ -72,13 +283,512 @@ impl VlanOrch {
         Ok(())
     }
 
+    /// Rebuild APPL_DB from CONFIG_DB, e.g. after APPL_DB was wiped
+    ///
+    /// Forgets the tracked VLANs, then rewrites and notifies every VLAN,
+    /// VLAN member and default membership the config defines. Entries
+    /// whose config is gone are left alone, and so are default memberships
+    /// removed since provisioning recorded their port. Notifications, and
+    /// other resyncs, wait until it finishes. Returns the number of VLANs
+    /// synced.
+    pub async fn resync_all(&self) -> Result<usize> {
+        let _sync = self.sync_lock.lock().await;
+        info!("Resyncing APPL_DB from CONFIG_DB");
+
+        // Default memberships still held are rewritten like any other entry
+        let held: HashSet<String> = self
+            .default_members
+            .iter()
+            .map(|member| member.key().clone())
+            .collect();
+        self.vlans.clear();
+        self.ranges.clear();
+        self.default_members.clear();
+        self.sync_vlans().await?;
+
+        let member_keys = self
+            .db_client
+            .scan(home_db(tables::VLAN_MEMBER), "VLAN_MEMBER|*")
+            .await?;
+        for key in &member_keys {
+            let Some((vlan_name, port)) = key
+                .strip_prefix("VLAN_MEMBER|")
+                .and_then(|member| member.split_once('|'))
+            else {
+                warn!("Malformed VLAN member key: {}", key);
+                continue;
+            };
+            if let Err(e) = self.process_member_config(vlan_name, port).await {
+                warn!("Failed to resync VLAN member {}: {}", key, e);
+            }
+        }
+
+        if self.vlan_defaults.provision_on_first_boot {
+            let mut provisioned = self.provisioned_ports().await?.unwrap_or_default();
+            let handled: HashSet<String> = provisioned.difference(&held).cloned().collect();
+            if let Some(ports) = self.provision_default_members(&handled).await? {
+                provisioned.extend(ports);
+                self.record_provisioned(provisioned).await?;
+            }
+        }
+
+        info!(
+            "Resynced {} VLANs and {} VLAN members",
+            self.vlans.len(),
+            member_keys.len()
+        );
+        Ok(self.vlans.len())
+    }
+
+    /// Converge APPL_DB on a complete desired VLAN table
+    ///
+    /// `desired` maps VLAN names to their config. It is diffed against the
+    /// tracked VLANs, and only created, changed and removed VLANs are
+    /// written and notified. VLANs expanded from a range are part of the
+    /// diff; afterwards the desired table owns them, so ranges no longer
+    /// track them. Notifications and resyncs wait until it finishes.
+    /// Returns the changes that were applied.
+    pub async fn apply_desired(
+        &self,
+        desired: HashMap<String, VlanConfig>,
+    ) -> Result<Changes<String>> {
+        let _sync = self.sync_lock.lock().await;
+        let current: HashMap<String, VlanEntry> = self
+            .vlans
+            .iter()
+            .map(|vlan| (format!("Vlan{}", vlan.key().get()), vlan.value().clone()))
+            .collect();
+        let wanted: HashMap<String, VlanEntry> = desired
+            .iter()
+            .map(|(vlan_name, config)| {
+                let entry = VlanEntry {
+                    vlanid: config.vlanid,
+                    description: config.description.clone(),
+                    admin_status: config.admin_status,
+                    mtu: config.mtu,
+                };
+                (vlan_name.clone(), entry)
+            })
+            .collect();
+        let changes = diff(&current, &wanted);
+
+        for vlan_name in &changes.delete {
+            self.delete_vlan(vlan_name).await?;
+        }
+
+        let mut desired = desired;
+        let mut vlans: Vec<(String, VlanConfig)> = changes
+            .create
+            .iter()
+            .chain(&changes.update)
+            .filter_map(|vlan_name| Some((vlan_name.clone(), desired.remove(vlan_name)?)))
+            .collect();
+        order_by_priority(&mut vlans);
+        for (vlan_name, config) in vlans {
+            self.apply_vlan_config(&vlan_name, config).await?;
+        }
+
+        // Deleted VLANs are gone and kept ones are now configured as
+        // desired, so a later range change must not delete either
+        self.ranges.clear();
+
+        info!(
+            "Applied desired VLAN table: {} created, {} updated, {} deleted",
+            changes.create.len(),
+            changes.update.len(),
+            changes.delete.len()
+        );
+        Ok(changes)
+    }
+
+    /// Run default provisioning for the access ports it hasn't handled yet
+    ///
+    /// The first boot with provisioning enabled assigns every access port;
+    /// later boots only assign ports added to the config since, and re-track
+    /// the default memberships that still exist so explicit configuration
+    /// can release them. The ports handled are recorded in STATE_DB once
+    /// provisioning has run.
+    async fn provision_first_boot(&self) -> Result<()> {
+        let mut handled = HashSet::new();
+        if let Some(ports) = self.provisioned_ports().await? {
+            handled.extend(ports);
+            self.recover_default_members().await?;
+        }
+
+        if !self.vlan_defaults.provision_on_first_boot {
+            info!("Default provisioning disabled");
+            return Ok(());
+        }
+        let ports = match self.provision_default_members(&handled).await? {
+            Some(ports) if !ports.is_empty() => ports,
+            _ => {
+                debug!("No access ports to provision");
+                return Ok(());
+            }
+        };
+
+        handled.extend(ports);
+        self.record_provisioned(handled).await
+    }
+
+    /// Ports default provisioning has handled, if it ever ran
+    async fn provisioned_ports(&self) -> Result<Option<HashSet<String>>> {
+        let state_db = home_db(tables::BOOT_STATE);
+        let record_key = self
+            .table_keys
+            .key(tables::BOOT_STATE, DEFAULT_PROVISIONED_KEY);
+        if !self.db_client.exists(state_db, &record_key).await? {
+            return Ok(None);
+        }
+        let record: ProvisionedDefaults = self.db_client.get(state_db, &record_key).await?;
+        Ok(Some(record.ports.into_iter().collect()))
+    }
+
+    /// Record the ports default provisioning has handled in STATE_DB
+    async fn record_provisioned(&self, ports: HashSet<String>) -> Result<()> {
+        let mut ports: Vec<String> = ports.into_iter().collect();
+        ports.sort();
+        let record = ProvisionedDefaults {
+            ts: unix_millis(),
+            ports,
+        };
+        self.db_client
+            .set(
+                home_db(tables::BOOT_STATE),
+                &self
+                    .table_keys
+                    .key(tables::BOOT_STATE, DEFAULT_PROVISIONED_KEY),
+                &record,
+            )
+            .await
+    }
+
+    /// Track the default memberships a previous boot left in APPL_DB
+    async fn recover_default_members(&self) -> Result<()> {
+        let members = default_vlan_members(&self.vlan_defaults, &HashSet::new(), &self.table_keys);
+        let keys: Vec<String> = members.into_iter().map(|(key, _)| key).collect();
+        let present = self
+            .db_client
+            .exists_many(home_db(tables::VLAN_MEMBER_TABLE), &keys)
+            .await?;
+
+        for (appl_key, _) in keys
+            .into_iter()
+            .zip(present)
+            .filter(|(_, present)| *present)
+        {
+            let port = appl_key.rsplit(':').next().unwrap_or(&appl_key).to_string();
+            self.default_members.insert(port, appl_key);
+        }
+
+        debug!(
+            "Tracking {} default VLAN memberships",
+            self.default_members.len()
+        );
+        Ok(())
+    }
+
+    /// Create APPL_DB memberships for access ports on the default VLAN
+    ///
+    /// Ports in `handled` (canonical names) are skipped. Returns the other
+    /// access ports, whether assigned or left to their explicit membership,
+    /// or `None` when there is no default VLAN to provision.
+    async fn provision_default_members(
+        &self,
+        handled: &HashSet<String>,
+    ) -> Result<Option<Vec<String>>> {
+        let Some(default_vlan) = self.vlan_defaults.default_vlan else {
+            return Ok(None);
+        };
+
+        let vlan_id = VlanId::new(default_vlan)
+            .ok_or(racoon_common::RacoonError::InvalidVlanId(default_vlan))?;
+
+        if !self.vlans.contains_key(&vlan_id) {
+            warn!(
+                "Default VLAN {} is not configured, skipping default memberships",
+                default_vlan
+            );
+            return Ok(None);
+        }
+
+        // Ports with an explicit VLAN_MEMBER entry keep their own configuration
+        let mut skipped: HashSet<String> = self
+            .db_client
+            .keys(home_db(tables::VLAN_MEMBER), "VLAN_MEMBER|*")
+            .await?
+            .iter()
+            .filter_map(|key| key.rsplit('|').next().map(PortName::normalize))
+            .collect();
+        skipped.extend(handled.iter().cloned());
+
+        let members = default_vlan_members(&self.vlan_defaults, &skipped, &self.table_keys);
+        let assigned = members.len();
+        for (appl_key, entry) in members {
+            let member = self
+                .table_keys
+                .strip(tables::VLAN_MEMBER_TABLE, &appl_key)
+                .unwrap_or(&appl_key);
+            let port = member.rsplit(':').next().unwrap_or(member);
+
+            self.db_client
+                .set(home_db(tables::VLAN_MEMBER_TABLE), &appl_key, &entry)
+                .await?;
+            self.default_members
+                .insert(port.to_string(), appl_key.clone());
+
+            let notification = serde_json::json!({
+                "operation": "SET",
+                "ts": unix_millis(),
+                "table": tables::VLAN_MEMBER_TABLE,
+                "key": member,
+                "data": entry
+            });
+            self.db_client
+                .publish(tables::VLAN_MEMBER_TABLE, &notification.to_string())
+                .await?;
+        }
+
+        info!(
+            "Assigned {} access ports to default VLAN {}",
+            assigned, default_vlan
+        );
+        let ports = self
+            .vlan_defaults
+            .access_ports
+            .iter()
+            .map(|port| PortName::normalize(port))
+            .filter(|port| !handled.contains(port))
+            .collect();
+        Ok(Some(ports))
+    }
+
+    /// Drop a port's default membership once it is configured explicitly
+    async fn release_default_member(&self, port: &str) -> Result<()> {
+        let Some((_, appl_key)) = self.default_members.remove(&PortName::normalize(port)) else {
+            return Ok(());
+        };
+
+        self.db_client
+            .del(home_db(tables::VLAN_MEMBER_TABLE), &appl_key)
+            .await?;
+
+        info!("Removed default VLAN membership for {}", port);
+
+        let notification = serde_json::json!({
+            "operation": "DEL",
+            "ts": unix_millis(),
+            "table": tables::VLAN_MEMBER_TABLE,
+            "key": self
+                .table_keys
+                .strip(tables::VLAN_MEMBER_TABLE, &appl_key)
+                .unwrap_or(&appl_key)
+        });
+
+        self.db_client
+            .publish(tables::VLAN_MEMBER_TABLE, &notification.to_string())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Write the APPL_DB entry for a VLAN member config and notify syncd
+    async fn process_member_config(&self, vlan_name: &str, port: &str) -> Result<()> {
+        let config_key = format!("VLAN_MEMBER|{}|{}", vlan_name, port);
+        let config: VlanMemberConfig = self
+            .db_client
+            .get(home_db(tables::VLAN_MEMBER), &config_key)
+            .await?;
+
+        let member = format!("{}:{}", vlan_name, PortName::normalize(port));
+        let entry = VlanMemberEntry {
+            tagging_mode: config.tagging_mode,
+        };
+        self.db_client
+            .set(
+                home_db(tables::VLAN_MEMBER_TABLE),
+                &self.table_keys.key(tables::VLAN_MEMBER_TABLE, &member),
+                &entry,
+            )
+            .await?;
+
+        info!(
+            "Processed VLAN member {} ({}) -> APPL_DB",
+            member, entry.tagging_mode
+        );
+
+        let notification = serde_json::json!({
+            "operation": "SET",
+            "ts": unix_millis(),
+            "table": tables::VLAN_MEMBER_TABLE,
+            "key": member,
+            "data": entry
+        });
+        self.db_client
+            .publish(tables::VLAN_MEMBER_TABLE, &notification.to_string())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove the APPL_DB entry of a deleted VLAN member
+    async fn delete_member(&self, vlan_name: &str, port: &str) -> Result<()> {
+        let member = format!("{}:{}", vlan_name, PortName::normalize(port));
+        self.db_client
+            .del(
+                home_db(tables::VLAN_MEMBER_TABLE),
+                &self.table_keys.key(tables::VLAN_MEMBER_TABLE, &member),
+            )
+            .await?;
+
+        info!("Deleted VLAN member {} from APPL_DB", member);
+
+        let notification = serde_json::json!({
+            "operation": "DEL",
+            "ts": unix_millis(),
+            "table": tables::VLAN_MEMBER_TABLE,
+            "key": member
+        });
+        self.db_client
+            .publish(tables::VLAN_MEMBER_TABLE, &notification.to_string())
+            .await?;
+
+        Ok(())
+    }
+
     /// Process VLAN configuration and create APPL_DB entry
     async fn process_vlan_config(&self, vlan_name: &str) -> Result<()> {
         let config_key = format!("VLAN|{}", vlan_name);
 
         // Get VLAN config from CONFIG_DB
-        let config: VlanConfig = self.db_client.get(Database::Config, &config_key).await?;
+        let config: VlanConfig = self
+            .db_client
+            .get(home_db(tables::VLAN), &config_key)
+            .await?;
+
+        // The individual config wins over any range covering the VLAN
+        if let Some(vlan_id) = VlanId::new(config.vlanid) {
+            for mut range in self.ranges.iter_mut() {
+                range.retain(|member| *member != vlan_id);
+            }
+        }
+
+        self.apply_vlan_config(vlan_name, config).await
+    }
 
+    /// Expand a VLAN range config (`Vlan100-200`) into APPL_DB entries
+    ///
+    /// VLANs with an individual config of their own are left to it. VLANs
+    /// an earlier version of the range covered and this one doesn't are
+    /// deleted.
+    async fn process_range_config(&self, range_name: &str) -> Result<()> {
+        let (start, end) = VlanId::parse_range_from_name(range_name)?;
+        let config: VlanRangeConfig = self
+            .db_client
+            .get(home_db(tables::VLAN), &format!("VLAN|{}", range_name))
+            .await?;
+
+        let vlan_ids: Vec<VlanId> = VlanId::range(start.get(), end.get()).collect();
+        let config_keys: Vec<String> = vlan_ids
+            .iter()
+            .map(|vlan_id| format!("VLAN|Vlan{}", vlan_id))
+            .collect();
+        let individual = self
+            .db_client
+            .exists_many(home_db(tables::VLAN), &config_keys)
+            .await?;
+
+        let mut members = Vec::new();
+        for (vlan_id, individual) in vlan_ids.into_iter().zip(individual) {
+            if individual {
+                debug!(
+                    "Vlan{} is configured individually, skipping it in {}",
+                    vlan_id, range_name
+                );
+                continue;
+            }
+            self.apply_vlan_config(&format!("Vlan{}", vlan_id), config.expand(vlan_id))
+                .await?;
+            members.push(vlan_id);
+        }
+
+        let count = members.len();
+        let previous = self.ranges.insert(range_name.to_string(), members.clone());
+        for vlan_id in previous.unwrap_or_default() {
+            if !members.contains(&vlan_id) && !self.in_range(vlan_id) {
+                self.delete_vlan(&format!("Vlan{}", vlan_id)).await?;
+            }
+        }
+
+        info!("Expanded VLAN range {} into {} VLANs", range_name, count);
+        Ok(())
+    }
+
+    /// Delete the VLANs a range key expanded to
+    ///
+    /// VLANs another range still covers are kept.
+    async fn delete_range(&self, range_name: &str) -> Result<()> {
+        let Some((_, members)) = self.ranges.remove(range_name) else {
+            warn!("VLAN range {} not found in tracking", range_name);
+            return Ok(());
+        };
+
+        let mut deleted = 0;
+        for vlan_id in members {
+            if self.in_range(vlan_id) {
+                continue;
+            }
+            self.delete_vlan(&format!("Vlan{}", vlan_id)).await?;
+            deleted += 1;
+        }
+
+        info!("Deleted VLAN range {} ({} VLANs)", range_name, deleted);
+        Ok(())
+    }
+
+    /// Delete the individual config of a VLAN
+    ///
+    /// A VLAN that a range also covers falls back to the range's config
+    /// instead of being deleted.
+    async fn delete_vlan_config(&self, vlan_name: &str) -> Result<()> {
+        let vlan_id = VlanId::parse_from_name(vlan_name)?;
+        let covering = self.covering_ranges(vlan_id);
+        if covering.is_empty() {
+            return self.delete_vlan(vlan_name).await;
+        }
+
+        for range_name in covering {
+            info!("{} falls back to VLAN range {}", vlan_name, range_name);
+            self.process_range_config(&range_name).await?;
+        }
+        Ok(())
+    }
+
+    /// Tracked ranges whose span includes `vlan_id`, even where an
+    /// individual config overrides them
+    fn covering_ranges(&self, vlan_id: VlanId) -> Vec<String> {
+        let mut ranges: Vec<String> = self
+            .ranges
+            .iter()
+            .map(|range| range.key().clone())
+            .filter(|range_name| {
+                VlanId::parse_range_from_name(range_name)
+                    .is_ok_and(|(start, end)| (start.get()..=end.get()).contains(&vlan_id.get()))
+            })
+            .collect();
+        ranges.sort();
+        ranges
+    }
+
+    /// Whether a tracked range covers `vlan_id`
+    fn in_range(&self, vlan_id: VlanId) -> bool {
+        self.ranges
+            .iter()
+            .any(|range| range.value().contains(&vlan_id))
+    }
+
+    /// Write the APPL_DB entry for a VLAN config and notify syncd
+    async fn apply_vlan_config(&self, vlan_name: &str, config: VlanConfig) -> Result<()> {
         let vlan_id = VlanId::new(config.vlanid)
             .ok_or(racoon_common::RacoonError::InvalidVlanId(config.vlanid))?;
 
@@ -86,11 +796,13 @@ impl VlanOrch {
         let vlan_entry = VlanEntry {
             vlanid: config.vlanid,
             description: config.description.clone(),
+            admin_status: config.admin_status,
+            mtu: config.mtu,
         };
 
-        let appl_key = format!("VLAN_TABLE:{}", vlan_name);
+        let appl_key = self.table_keys.key(tables::VLAN_TABLE, vlan_name);
         self.db_client
-            .set(Database::Appl, &appl_key, &vlan_entry)
+            .set(home_db(tables::VLAN_TABLE), &appl_key, &vlan_entry)
             .await?;
 
         // Track the VLAN
@@ -104,13 +816,14 @@ impl VlanOrch {
         // Publish notification
         let notification = serde_json::json!({
             "operation": "SET",
-            "table": "VLAN_TABLE",
+            "ts": unix_millis(),
+            "table": tables::VLAN_TABLE,
             "key": vlan_name,
             "data": vlan_entry
         });
 
         self.db_client
-            .publish("VLAN_TABLE", &notification.to_string())
+            .publish(tables::VLAN_TABLE, &notification.to_string())
             .await?;
 
         Ok(())
@@ -118,17 +831,13 @@ impl VlanOrch {
 
     /// Handle VLAN deletion
     async fn delete_vlan(&self, vlan_name: &str) -> Result<()> {
-        // Parse VLAN ID from name (Vlan100 -> 100)
-        let vlan_id_str = vlan_name.strip_prefix("Vlan").unwrap_or(vlan_name);
-        let vlan_id_num = vlan_id_str
-            .parse::<u16>()
-            .map_err(|_| racoon_common::RacoonError::InvalidVlanId(0))?;
-        let vlan_id = VlanId::new(vlan_id_num)
-            .ok_or(racoon_common::RacoonError::InvalidVlanId(vlan_id_num))?;
+        let vlan_id = VlanId::parse_from_name(vlan_name)?;
 
         // Remove from APPL_DB
-        let appl_key = format!("VLAN_TABLE:{}", vlan_name);
-        self.db_client.del(Database::Appl, &appl_key).await?;
+        let appl_key = self.table_keys.key(tables::VLAN_TABLE, vlan_name);
+        self.db_client
+            .del(home_db(tables::VLAN_TABLE), &appl_key)
+            .await?;
 
         // Remove from tracking
         self.vlans.remove(&vlan_id);
@@ -138,76 +847,177 @@ impl VlanOrch {
         // Publish deletion notification
         let notification = serde_json::json!({
             "operation": "DEL",
-            "table": "VLAN_TABLE",
+            "ts": unix_millis(),
+            "table": tables::VLAN_TABLE,
             "key": vlan_name
         });
 
         self.db_client
-            .publish("VLAN_TABLE", &notification.to_string())
+            .publish(tables::VLAN_TABLE, &notification.to_string())
             .await?;
 
         Ok(())
     }
 
     /// Handle database notification
+    #[instrument(skip(self, message))]
     pub async fn handle_notification(&self, channel: &str, message: &str) {
         debug!("Received notification on {}: {}", channel, message);
+        let _sync = self.sync_lock.lock().await;
 
         // Parse notification
         let notification: serde_json::Value = match serde_json::from_str(message) {
             Ok(v) => v,
             Err(e) => {
                 error!("Failed to parse notification: {}", e);
+                self.ignored.record(message);
                 return;
             }
         };
 
-        let operation = notification["operation"].as_str().unwrap_or("");
-        let key = notification["key"].as_str().unwrap_or("");
+        let operation_name = notification["operation"].as_str().unwrap_or("");
+        let Some(operation) = Operation::parse(operation_name) else {
+            self.ignored.record(message);
+            return;
+        };
+        let Some(key) = notification["key"].as_str() else {
+            warn!("Notification on {} without key", channel);
+            self.ignored.record(message);
+            return;
+        };
+
+        if let Some(member) = key.strip_prefix("VLAN_MEMBER|") {
+            let Some((vlan_name, port)) = member.split_once('|') else {
+                warn!("Malformed VLAN member key: {}", key);
+                return;
+            };
 
-        match operation {
-            "SET" | "CREATE" => {
-                if let Some(vlan_name) = key.strip_prefix("VLAN|")
-                    && let Err(e) = self.process_vlan_config(vlan_name).await
-                {
+            let result = match operation {
+                Operation::Set => {
+                    // An explicit membership replaces the port's default membership
+                    let released = self.release_default_member(port).await;
+                    if let Err(e) = &released {
+                        error!("Failed to release default membership of {}: {}", port, e);
+                    }
+                    let result = self.process_member_config(vlan_name, port).await;
+                    if let Err(e) = &result {
+                        error!("Failed to process VLAN member {}: {}", member, e);
+                    }
+                    released.and(result)
+                }
+                Operation::Del => {
+                    let result = self.delete_member(vlan_name, port).await;
+                    if let Err(e) = &result {
+                        error!("Failed to delete VLAN member {}: {}", member, e);
+                    }
+                    result
+                }
+                Operation::Hset => {
+                    warn!("Field deltas are not supported for {}", key);
+                    Err(unsupported_delta())
+                }
+            };
+            self.events.record(operation_name, key, &result);
+            return;
+        }
+
+        let result = match (operation, key.strip_prefix("VLAN|")) {
+            (Operation::Set, Some(range_name)) if is_vlan_range(range_name) => {
+                let result = self.process_range_config(range_name).await;
+                if let Err(e) = &result {
+                    error!("Failed to process VLAN range {}: {}", range_name, e);
+                }
+                result
+            }
+            (Operation::Del, Some(range_name)) if is_vlan_range(range_name) => {
+                let result = self.delete_range(range_name).await;
+                if let Err(e) = &result {
+                    error!("Failed to delete VLAN range {}: {}", range_name, e);
+                }
+                result
+            }
+            (Operation::Set, Some(vlan_name)) => {
+                let result = self.process_vlan_config(vlan_name).await;
+                if let Err(e) = &result {
                     error!("Failed to process VLAN {}: {}", vlan_name, e);
                 }
+                result
             }
-            "DEL" | "DELETE" => {
-                if let Some(vlan_name) = key.strip_prefix("VLAN|")
-                    && let Err(e) = self.delete_vlan(vlan_name).await
-                {
+            (Operation::Del, Some(vlan_name)) => {
+                let result = self.delete_vlan_config(vlan_name).await;
+                if let Err(e) = &result {
                     error!("Failed to delete VLAN {}: {}", vlan_name, e);
                 }
+                result
             }
-            _ => {
-                warn!("Unknown operation: {}", operation);
+            (Operation::Set | Operation::Del, None) => Ok(()),
+            (Operation::Hset, _) => {
+                warn!("Field deltas are not supported for {}", key);
+                Err(unsupported_delta())
             }
-        }
+        };
+        self.events.record(operation_name, key, &result);
+    }
+
+    /// Outcome of the last processed notifications, oldest first
+    pub fn recent_events(&self) -> Vec<ProcessedEvent> {
+        self.events.recent()
+    }
+
+    /// VLANs programmed into APPL_DB, by VLAN id
+    pub fn vlans(&self) -> Vec<(VlanId, VlanEntry)> {
+        let mut vlans: Vec<(VlanId, VlanEntry)> = self
+            .vlans
+            .iter()
+            .map(|vlan| (*vlan.key(), vlan.value().clone()))
+            .collect();
+        vlans.sort_by_key(|(vlan_id, _)| vlan_id.get());
+        vlans
     }
 
     /// Get statistics
     pub fn stats(&self) -> VlanOrchStats {
         VlanOrchStats {
             vlan_count: self.vlans.len(),
+            ignored: self.ignored.snapshot(),
         }
     }
 }
 
+/// Outcome recorded for field deltas, which orchd does not handle
+fn unsupported_delta() -> racoon_common::RacoonError {
+    racoon_common::RacoonError::Internal("field deltas are not supported".to_string())
+}
+
 /// VLAN orchestration statistics
 #[derive(Debug, Clone, Serialize)]
 pub struct VlanOrchStats {
     pub vlan_count: usize,
+    pub ignored: IgnoredSnapshot,
 }
 
 /// Database subscriber implementation for VlanOrch
 pub struct VlanOrchSubscriber {
     vlan_orch: Arc<VlanOrch>,
+    /// Resync everything once the next subscription is established
+    resync_pending: AtomicBool,
 }
 
 impl VlanOrchSubscriber {
     pub fn new(vlan_orch: Arc<VlanOrch>) -> Self {
-        Self { vlan_orch }
+        Self {
+            vlan_orch,
+            resync_pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Resync all VLANs once the next subscription is established
+    ///
+    /// For when the subscription is replaced, e.g. on a config reload:
+    /// changes made without a subscription are picked up by the resync, and
+    /// changes made during it are delivered on the new subscription.
+    pub fn resync_on_subscribe(&self) {
+        self.resync_pending.store(true, Ordering::SeqCst);
     }
 }
 
@@ -219,23 +1029,133 @@ impl DbSubscriber for VlanOrchSubscriber {
 
     async fn on_subscribe(&self, channel: String) {
         info!("VlanOrch subscribed to channel: {}", channel);
+
+        // Every channel is subscribed by the time the first is confirmed
+        if self.resync_pending.swap(false, Ordering::SeqCst) {
+            match self.vlan_orch.resync_all().await {
+                Ok(count) => info!("Resynced {} VLANs after resubscribing", count),
+                Err(e) => warn!("Failed to resync VLANs after resubscribing: {}", e),
+            }
+        }
+    }
+
+    async fn on_resubscribe(&self) {
+        // Notifications may have been lost while the subscription was wedged
+        if let Err(e) = self.vlan_orch.sync_vlans().await {
+            warn!("VlanOrch resync after resubscribe failed: {}", e);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use racoon_db_client::Database;
+
+    #[test]
+    fn test_default_vlan_members() {
+        // Alias spellings in the config resolve to the canonical port name
+        let defaults = VlanDefaultsConfig {
+            default_vlan: Some(1),
+            access_ports: vec![
+                "Ethernet0".to_string(),
+                "eth4".to_string(),
+                "Eth8".to_string(),
+            ],
+            ..Default::default()
+        };
+        let explicit: HashSet<String> = ["Ethernet4".to_string()].into_iter().collect();
+
+        let members = default_vlan_members(&defaults, &explicit, &TableKeys::default());
+        let keys: Vec<&str> = members.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "VLAN_MEMBER_TABLE:Vlan1:Ethernet0",
+                "VLAN_MEMBER_TABLE:Vlan1:Ethernet8"
+            ]
+        );
+        assert!(
+            members
+                .iter()
+                .all(|(_, e)| e.tagging_mode == VlanTaggingMode::Untagged)
+        );
+
+        // A custom separator only changes the table/key boundary
+        let keys = TableKeys::new("|");
+        let members = default_vlan_members(&defaults, &explicit, &keys);
+        assert_eq!(members[0].0, "VLAN_MEMBER_TABLE|Vlan1:Ethernet0");
+        assert_eq!(
+            keys.strip("VLAN_MEMBER_TABLE", &members[0].0),
+            Some("Vlan1:Ethernet0")
+        );
+
+        // No default VLAN configured means no memberships
+        let none = default_vlan_members(
+            &VlanDefaultsConfig::default(),
+            &HashSet::new(),
+            &TableKeys::default(),
+        );
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_order_by_priority() {
+        let vlan = |vlanid, priority| VlanConfig {
+            vlanid,
+            description: None,
+            admin_status: None,
+            mtu: None,
+            priority,
+        };
+        let mut vlans = vec![
+            ("Vlan200".to_string(), vlan(200, None)),
+            ("Vlan300".to_string(), vlan(300, Some(-5))),
+            ("Vlan10".to_string(), vlan(10, Some(100))),
+            ("Vlan201".to_string(), vlan(201, None)),
+        ];
+
+        order_by_priority(&mut vlans);
+
+        let names: Vec<&str> = vlans.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Vlan10", "Vlan200", "Vlan201", "Vlan300"]);
+    }
 
     #[tokio::test]
-    #[ignore] // Requires running database
-    async fn test_vlan_orch() {
+    async fn test_malformed_notifications_counted() {
+        // DbClient connects lazily; ignored notifications never reach it
         let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        let vlan_orch = VlanOrch::new(db_client);
+
+        vlan_orch.handle_notification("VLAN", "{truncated").await;
+        vlan_orch
+            .handle_notification("VLAN", r#"{"operation": "PATCH", "key": "VLAN|Vlan100"}"#)
+            .await;
+        vlan_orch
+            .handle_notification("VLAN", r#"{"operation": "DEL", "key": 100}"#)
+            .await;
+
+        let stats = vlan_orch.stats();
+        assert_eq!(stats.ignored.count, 3);
+        assert_eq!(
+            stats.ignored.last_payload.as_deref(),
+            Some(r#"{"operation": "DEL", "key": 100}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vlan_orch() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
         let vlan_orch = VlanOrch::new(db_client.clone());
 
         // Create test VLAN in CONFIG_DB
         let config = VlanConfig {
             vlanid: 100,
             description: Some("Test VLAN".to_string()),
+            admin_status: None,
+            mtu: None,
+            priority: None,
         };
 
         db_client
@@ -255,4 +1175,479 @@ mod tests {
         assert_eq!(entry.vlanid, 100);
         assert_eq!(entry.description, Some("Test VLAN".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_priority_tagged_member() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = VlanOrch::new(db_client.clone());
+
+        db_client
+            .set(
+                Database::Config,
+                "VLAN_MEMBER|Vlan100|eth0",
+                &serde_json::json!({"tagging_mode": "priority_tagged"}),
+            )
+            .await
+            .unwrap();
+        let notify = |operation: &str| {
+            serde_json::json!({"operation": operation, "key": "VLAN_MEMBER|Vlan100|eth0"})
+                .to_string()
+        };
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("SET"))
+            .await;
+
+        let entry: VlanMemberEntry = db_client
+            .get(Database::Appl, "VLAN_MEMBER_TABLE:Vlan100:Ethernet0")
+            .await
+            .unwrap();
+        assert_eq!(entry.tagging_mode, VlanTaggingMode::Priority);
+
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("DEL"))
+            .await;
+        assert!(
+            db_client
+                .get::<VlanMemberEntry>(Database::Appl, "VLAN_MEMBER_TABLE:Vlan100:Ethernet0")
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_member_tagging_mode_validated() {
+        let config: VlanMemberConfig =
+            serde_json::from_str(r#"{"tagging_mode": "priority_tagged"}"#).unwrap();
+        assert_eq!(config.tagging_mode, VlanTaggingMode::Priority);
+        assert!(serde_json::from_str::<VlanMemberConfig>(r#"{"tagging_mode": "trunk"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vlan_range_expanded_and_deleted() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = VlanOrch::new(db_client.clone());
+        let notify = |operation: &str, key: &str| {
+            serde_json::json!({"operation": operation, "key": key}).to_string()
+        };
+
+        // Vlan101 is configured on its own as well
+        let individual = VlanConfig {
+            vlanid: 101,
+            description: Some("uplink".to_string()),
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        db_client
+            .set(Database::Config, "VLAN|Vlan101", &individual)
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("SET", "VLAN|Vlan101"))
+            .await;
+        db_client
+            .set(
+                Database::Config,
+                "VLAN|Vlan100-103",
+                &serde_json::json!({"description": "servers {vlanid}"}),
+            )
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("SET", "VLAN|Vlan100-103"))
+            .await;
+
+        let description = |vlanid: u16| {
+            let db_client = db_client.clone();
+            async move {
+                db_client
+                    .get::<VlanEntry>(Database::Appl, &format!("VLAN_TABLE:Vlan{}", vlanid))
+                    .await
+                    .ok()
+                    .and_then(|entry| entry.description)
+            }
+        };
+        assert_eq!(description(100).await.as_deref(), Some("servers 100"));
+        assert_eq!(description(101).await.as_deref(), Some("uplink"));
+        assert_eq!(description(103).await.as_deref(), Some("servers 103"));
+        assert_eq!(vlan_orch.stats().vlan_count, 4);
+
+        // Without its own config Vlan101 falls back to the range
+        db_client
+            .del(Database::Config, "VLAN|Vlan101")
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("DEL", "VLAN|Vlan101"))
+            .await;
+        assert_eq!(description(101).await.as_deref(), Some("servers 101"));
+        assert_eq!(vlan_orch.stats().vlan_count, 4);
+
+        db_client
+            .set(Database::Config, "VLAN|Vlan101", &individual)
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("SET", "VLAN|Vlan101"))
+            .await;
+        assert_eq!(description(101).await.as_deref(), Some("uplink"));
+
+        db_client
+            .del(Database::Config, "VLAN|Vlan100-103")
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("DEL", "VLAN|Vlan100-103"))
+            .await;
+
+        let keys = db_client
+            .keys(Database::Appl, "VLAN_TABLE:*")
+            .await
+            .unwrap();
+        assert_eq!(keys, vec!["VLAN_TABLE:Vlan101"]);
+        assert_eq!(vlan_orch.stats().vlan_count, 1);
+        assert!(
+            vlan_orch
+                .recent_events()
+                .iter()
+                .all(|event| event.error.is_none())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_desired() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = VlanOrch::new(db_client.clone());
+
+        let vlan = |vlanid| VlanConfig {
+            vlanid,
+            description: None,
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        for vlanid in [100, 200] {
+            db_client
+                .set(
+                    Database::Config,
+                    &format!("VLAN|Vlan{}", vlanid),
+                    &vlan(vlanid),
+                )
+                .await
+                .unwrap();
+        }
+        vlan_orch.sync_vlans().await.unwrap();
+
+        // Keep Vlan100 unchanged, drop Vlan200, add Vlan300
+        let desired: HashMap<String, VlanConfig> = [
+            ("Vlan100".to_string(), vlan(100)),
+            ("Vlan300".to_string(), vlan(300)),
+        ]
+        .into_iter()
+        .collect();
+        let changes = vlan_orch.apply_desired(desired.clone()).await.unwrap();
+
+        assert_eq!(changes.create, vec!["Vlan300"]);
+        assert!(changes.update.is_empty());
+        assert_eq!(changes.delete, vec!["Vlan200"]);
+
+        let keys = db_client
+            .keys(Database::Appl, "VLAN_TABLE:*")
+            .await
+            .unwrap();
+        let mut keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["VLAN_TABLE:Vlan100", "VLAN_TABLE:Vlan300"]);
+
+        // Applying the same table again is a no-op
+        assert!(vlan_orch.apply_desired(desired).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_apply_desired_takes_over_range_vlans() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = VlanOrch::new(db_client.clone());
+        let notify = |operation: &str, key: &str| {
+            serde_json::json!({"operation": operation, "key": key}).to_string()
+        };
+        db_client
+            .set(
+                Database::Config,
+                "VLAN|Vlan100-101",
+                &serde_json::json!({"description": "servers {vlanid}"}),
+            )
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("SET", "VLAN|Vlan100-101"))
+            .await;
+
+        // Vlan100 stays, Vlan101 goes
+        let desired: HashMap<String, VlanConfig> = [(
+            "Vlan100".to_string(),
+            VlanConfig {
+                vlanid: 100,
+                description: Some("servers 100".to_string()),
+                admin_status: None,
+                mtu: None,
+                priority: None,
+            },
+        )]
+        .into_iter()
+        .collect();
+        let changes = vlan_orch.apply_desired(desired).await.unwrap();
+        assert_eq!(changes.delete, vec!["Vlan101"]);
+        assert!(vlan_orch.ranges.is_empty());
+
+        // Removing the range no longer deletes the VLAN the table kept
+        db_client
+            .del(Database::Config, "VLAN|Vlan100-101")
+            .await
+            .unwrap();
+        vlan_orch
+            .handle_notification("CONFIG_DB", &notify("DEL", "VLAN|Vlan100-101"))
+            .await;
+        assert!(
+            db_client
+                .exists(Database::Appl, "VLAN_TABLE:Vlan100")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_defaults_provisioned_on_first_boot_only() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let defaults = VlanDefaultsConfig {
+            default_vlan: Some(1),
+            access_ports: vec!["Ethernet0".to_string(), "Ethernet4".to_string()],
+            ..Default::default()
+        };
+        let config = VlanConfig {
+            vlanid: 1,
+            description: None,
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        db_client
+            .set(Database::Config, "VLAN|Vlan1", &config)
+            .await
+            .unwrap();
+
+        let record_key = "BOOT_STATE:default_provisioned";
+
+        // Nothing is recorded while provisioning is disabled
+        let disabled = VlanOrch::new(db_client.clone()).with_vlan_defaults(VlanDefaultsConfig {
+            provision_on_first_boot: false,
+            ..defaults.clone()
+        });
+        disabled.start().await.unwrap();
+        assert!(!db_client.exists(Database::State, record_key).await.unwrap());
+
+        let first = VlanOrch::new(db_client.clone()).with_vlan_defaults(defaults.clone());
+        first.start().await.unwrap();
+        for port in ["Ethernet0", "Ethernet4"] {
+            let key = format!("VLAN_MEMBER_TABLE:Vlan1:{}", port);
+            assert!(db_client.exists(Database::Appl, &key).await.unwrap());
+        }
+        let record: ProvisionedDefaults = db_client.get(Database::State, record_key).await.unwrap();
+        assert_eq!(record.ports, ["Ethernet0", "Ethernet4"]);
+
+        // A membership removed after first boot stays removed
+        db_client
+            .del(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet4")
+            .await
+            .unwrap();
+
+        let second = VlanOrch::new(db_client.clone()).with_vlan_defaults(defaults.clone());
+        second.start().await.unwrap();
+        assert!(
+            !db_client
+                .exists(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet4")
+                .await
+                .unwrap()
+        );
+        let tracked: Vec<String> = second
+            .default_members
+            .iter()
+            .map(|member| member.key().clone())
+            .collect();
+        assert_eq!(tracked, vec!["Ethernet0"]);
+
+        // An access port added to the config later is still provisioned
+        let mut added = defaults;
+        added.access_ports.push("Ethernet8".to_string());
+        let third = VlanOrch::new(db_client.clone()).with_vlan_defaults(added);
+        third.start().await.unwrap();
+        assert!(
+            db_client
+                .exists(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet8")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !db_client
+                .exists(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet4")
+                .await
+                .unwrap()
+        );
+        let record: ProvisionedDefaults = db_client.get(Database::State, record_key).await.unwrap();
+        assert_eq!(record.ports, ["Ethernet0", "Ethernet4", "Ethernet8"]);
+    }
+
+    #[tokio::test]
+    async fn test_resync_repopulates_wiped_appl_db() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = VlanOrch::new(db_client.clone());
+
+        let config = VlanConfig {
+            vlanid: 100,
+            description: Some("servers".to_string()),
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        db_client
+            .set(Database::Config, "VLAN|Vlan100", &config)
+            .await
+            .unwrap();
+        db_client
+            .set(
+                Database::Config,
+                "VLAN_MEMBER|Vlan100|Ethernet0",
+                &serde_json::json!({"tagging_mode": "tagged"}),
+            )
+            .await
+            .unwrap();
+        vlan_orch.start().await.unwrap();
+
+        db_client
+            .del_pattern(Database::Appl, "*", true)
+            .await
+            .unwrap();
+        assert_eq!(vlan_orch.resync_all().await.unwrap(), 1);
+
+        let entry: VlanEntry = db_client
+            .get(Database::Appl, "VLAN_TABLE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(entry.description.as_deref(), Some("servers"));
+        let member: VlanMemberEntry = db_client
+            .get(Database::Appl, "VLAN_MEMBER_TABLE:Vlan100:Ethernet0")
+            .await
+            .unwrap();
+        assert_eq!(member.tagging_mode, VlanTaggingMode::Tagged);
+    }
+
+    #[tokio::test]
+    async fn test_resync_keeps_removed_default_memberships_removed() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let defaults = VlanDefaultsConfig {
+            default_vlan: Some(1),
+            access_ports: vec!["Ethernet0".to_string(), "Ethernet4".to_string()],
+            ..Default::default()
+        };
+        let config = VlanConfig {
+            vlanid: 1,
+            description: None,
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        db_client
+            .set(Database::Config, "VLAN|Vlan1", &config)
+            .await
+            .unwrap();
+        VlanOrch::new(db_client.clone())
+            .with_vlan_defaults(defaults.clone())
+            .start()
+            .await
+            .unwrap();
+
+        // Ethernet4's default membership is removed after provisioning
+        db_client
+            .del(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet4")
+            .await
+            .unwrap();
+        let vlan_orch = VlanOrch::new(db_client.clone()).with_vlan_defaults(defaults);
+        vlan_orch.start().await.unwrap();
+
+        db_client
+            .del_pattern(Database::Appl, "*", true)
+            .await
+            .unwrap();
+        vlan_orch.resync_all().await.unwrap();
+
+        assert!(
+            db_client
+                .exists(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet0")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !db_client
+                .exists(Database::Appl, "VLAN_MEMBER_TABLE:Vlan1:Ethernet4")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resync_deferred_until_subscribed() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_orch = Arc::new(VlanOrch::new(db_client.clone()));
+        vlan_orch.start().await.unwrap();
+        let subscriber = VlanOrchSubscriber::new(vlan_orch.clone());
+
+        // Added while no subscription was active
+        let config = VlanConfig {
+            vlanid: 200,
+            description: None,
+            admin_status: None,
+            mtu: None,
+            priority: None,
+        };
+        db_client
+            .set(Database::Config, "VLAN|Vlan200", &config)
+            .await
+            .unwrap();
+
+        subscriber.resync_on_subscribe();
+        assert!(
+            !db_client
+                .exists(Database::Appl, "VLAN_TABLE:Vlan200")
+                .await
+                .unwrap()
+        );
+
+        subscriber.on_subscribe("VLAN".to_string()).await;
+        assert!(
+            db_client
+                .exists(Database::Appl, "VLAN_TABLE:Vlan200")
+                .await
+                .unwrap()
+        );
+
+        // The resync runs once, not for every channel
+        db_client
+            .del(Database::Appl, "VLAN_TABLE:Vlan200")
+            .await
+            .unwrap();
+        subscriber.on_subscribe("VLAN_MEMBER".to_string()).await;
+        assert!(
+            !db_client
+                .exists(Database::Appl, "VLAN_TABLE:Vlan200")
+                .await
+                .unwrap()
+        );
+    }
 }
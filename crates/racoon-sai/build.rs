@@ -1,5 +1,52 @@
+use bindgen::callbacks::{EnumVariantValue, ParseCallbacks};
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Attribute ids and names by attribute enum (`sai_vlan_attr_t`)
+type AttrTables = BTreeMap<String, BTreeSet<(u32, String)>>;
+
+/// Collects the variants of every SAI attribute enum as bindgen parses
+/// them, skipping range markers (`*_START`, `*_END`)
+#[derive(Debug, Default)]
+struct AttrNameCollector {
+    tables: Arc<Mutex<AttrTables>>,
+}
+
+impl ParseCallbacks for AttrNameCollector {
+    fn enum_variant_name(
+        &self,
+        enum_name: Option<&str>,
+        original_variant_name: &str,
+        variant_value: EnumVariantValue,
+    ) -> Option<String> {
+        // C enums are spelled `enum _sai_vlan_attr_t`
+        let attr_type = enum_name?
+            .trim_start_matches("enum ")
+            .trim_start_matches('_');
+        if !attr_type.starts_with("sai_")
+            || !attr_type.ends_with("_attr_t")
+            || original_variant_name.ends_with("_START")
+            || original_variant_name.ends_with("_END")
+        {
+            return None;
+        }
+        let id = match variant_value {
+            EnumVariantValue::Signed(id) => u32::try_from(id).ok()?,
+            EnumVariantValue::Unsigned(id) => u32::try_from(id).ok()?,
+            EnumVariantValue::Boolean(_) => return None,
+        };
+        self.tables
+            .lock()
+            .expect("attribute name collector poisoned")
+            .entry(attr_type.to_string())
+            .or_default()
+            .insert((id, original_variant_name.to_string()));
+        // Variants keep their names
+        None
+    }
+}
 
 fn main() {
     println!("cargo:rerun-if-changed=../../sai/SAI/inc");
@@ -12,6 +59,9 @@ fn main() {
         println!("cargo:rustc-link-search=native={}", lib_path);
     }
 
+    let attr_names = AttrNameCollector::default();
+    let attr_tables = attr_names.tables.clone();
+
     // Generate bindings for SAI headers
     // Note: We include individual headers instead of sai.h to avoid experimental dependencies
     let bindings = bindgen::Builder::default()
@@ -24,7 +74,17 @@ fn main() {
         .header(format!("{}/saivlan.h", sai_include_path))
         .header(format!("{}/saifdb.h", sai_include_path))
         .header(format!("{}/sailag.h", sai_include_path))
+        .header(format!("{}/saistp.h", sai_include_path))
+        .header(format!("{}/saiqueue.h", sai_include_path))
+        .header(format!("{}/saischedulergroup.h", sai_include_path))
+        .header(format!("{}/saihash.h", sai_include_path))
+        .header(format!("{}/sairouterinterface.h", sai_include_path))
         .header(format!("{}/saibridge.h", sai_include_path))
+        .header(format!("{}/saihostif.h", sai_include_path))
+        // Object enumeration (sai_get_object_key)
+        .header(format!("{}/saiobject.h", sai_include_path))
+        // VXLAN tunnels
+        .header(format!("{}/saitunnel.h", sai_include_path))
         // Include directory
         .clang_arg(format!("-I{}", sai_include_path))
         // Generate comments from headers
@@ -45,6 +105,8 @@ fn main() {
         .prepend_enum_name(false)
         // Translate macro constants
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
+        // Record attribute names for the id -> name table
+        .parse_callbacks(Box::new(attr_names))
         // Use libc types
         .ctypes_prefix("libc")
         .generate()
@@ -54,6 +116,29 @@ fn main() {
     bindings
         .write_to_file(out_path.join("sai_bindings.rs"))
         .expect("Couldn't write bindings");
+    let attr_tables = attr_tables
+        .lock()
+        .expect("attribute name collector poisoned");
+    write_attr_names(&attr_tables, &out_path.join("sai_attr_names.rs"));
 
     println!("cargo:rustc-link-lib=dylib=sai");
 }
+
+/// Generate the attribute id -> name table from the collected enums
+fn write_attr_names(tables: &AttrTables, out: &Path) {
+    let mut code = String::from(
+        "/// Attribute ids and names of a SAI attribute enum, e.g. `sai_vlan_attr_t`\n\
+         pub fn attr_names(attr_type: &str) -> &'static [(u32, &'static str)] {\n    \
+         match attr_type {\n",
+    );
+    for (attr_type, names) in tables {
+        code.push_str(&format!("        {:?} => &[\n", attr_type));
+        for (id, name) in names {
+            code.push_str(&format!("            ({}, {:?}),\n", id, name));
+        }
+        code.push_str("        ],\n");
+    }
+    code.push_str("        _ => &[],\n    }\n}\n");
+
+    std::fs::write(out, code).expect("Couldn't write attribute names");
+}
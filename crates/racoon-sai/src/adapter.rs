@@ -1,10 +1,16 @@
 use crate::bindings::*;
 use crate::constants::*;
+use crate::oid::SaiGetObjectKeyFn;
 use crate::status::SaiStatus;
+use crate::switch::SaiQueryAttributeCapabilityFn;
 use libloading::{Library, Symbol};
 use racoon_common::{RacoonError, Result};
-use std::os::raw::c_void;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_void};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::{info, warn};
 
 type SaiApiQueryFn =
@@ -17,11 +23,117 @@ type SaiApiInitializeFn = unsafe extern "C" fn(
 
 type SaiApiUninitializeFn = unsafe extern "C" fn() -> sai_status_t;
 
+/// `sai_query_api_version`; the version is a `sai_api_version_t` (`u64`)
+type SaiQueryApiVersionFn = unsafe extern "C" fn(version: *mut u64) -> sai_status_t;
+
+/// What was loaded as the SAI library
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaiLibraryInfo {
+    /// Path the library was loaded from
+    pub path: String,
+    /// `path` with symlinks resolved, if it exists on disk
+    pub real_path: Option<PathBuf>,
+    /// SAI API version the library was built against, if it reports one
+    pub api_version: Option<SaiApiVersion>,
+}
+
+impl SaiLibraryInfo {
+    /// Describe the library at `path`, asking it for its version when it
+    /// exports `sai_query_api_version`
+    fn query(path: &str, query_api_version: Option<SaiQueryApiVersionFn>) -> Self {
+        let api_version = query_api_version.and_then(|query| {
+            let mut version = 0u64;
+            let status = SaiStatus::from(unsafe { query(&mut version) });
+            match status.to_result() {
+                Ok(()) => Some(SaiApiVersion(version)),
+                Err(e) => {
+                    warn!("sai_query_api_version failed: {}", e);
+                    None
+                }
+            }
+        });
+
+        Self {
+            path: path.to_string(),
+            real_path: std::fs::canonicalize(path).ok(),
+            api_version,
+        }
+    }
+}
+
+/// SAI API version, encoded as `major * 10000 + minor * 100 + revision`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaiApiVersion(pub u64);
+
+impl SaiApiVersion {
+    pub fn major(&self) -> u64 {
+        self.0 / 10000
+    }
+
+    pub fn minor(&self) -> u64 {
+        self.0 / 100 % 100
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.0 % 100
+    }
+}
+
+impl fmt::Display for SaiApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major(), self.minor(), self.revision())
+    }
+}
+
+/// How the SAI library brings up the switch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SaiBootType {
+    /// Reset the hardware and start from an empty switch
+    #[default]
+    Cold,
+    /// Restore the state saved by a warm shutdown, keeping traffic forwarding
+    Warm,
+}
+
+impl SaiBootType {
+    /// Value of the `SAI_BOOT_TYPE` profile key
+    fn profile_value(&self) -> &'static CStr {
+        match self {
+            Self::Cold => c"0",
+            Self::Warm => c"1",
+        }
+    }
+}
+
+/// Profile key the library reads the boot type from (`SAI_KEY_BOOT_TYPE`)
+const BOOT_TYPE_PROFILE_KEY: &CStr = c"SAI_BOOT_TYPE";
+
+/// Whether the profile reports a warm boot; set before `sai_api_initialize`
+static WARM_BOOT: AtomicBool = AtomicBool::new(false);
+
+/// `profile_get_value` of the service method table
+///
+/// Answers the boot type; every other key is left to the library default.
+unsafe extern "C" fn profile_get_value(_profile_id: u32, variable: *const c_char) -> *const c_char {
+    if variable.is_null() || unsafe { CStr::from_ptr(variable) } != BOOT_TYPE_PROFILE_KEY {
+        return std::ptr::null();
+    }
+    let boot_type = if WARM_BOOT.load(Ordering::SeqCst) {
+        SaiBootType::Warm
+    } else {
+        SaiBootType::Cold
+    };
+    boot_type.profile_value().as_ptr()
+}
+
 /// SAI Adapter - manages dynamic loading and interaction with vendor SAI libraries
 pub struct SaiAdapter {
     _library: Library,
     _api_query: Symbol<'static, SaiApiQueryFn>,
     api_uninitialize: Symbol<'static, SaiApiUninitializeFn>,
+    query_attribute_capability: Option<SaiQueryAttributeCapabilityFn>,
+    get_object_key: Option<SaiGetObjectKeyFn>,
+    library_info: SaiLibraryInfo,
 
     // Cached API table pointers
     switch_api: *const sai_switch_api_t,
@@ -30,6 +142,14 @@ pub struct SaiAdapter {
     fdb_api: *const sai_fdb_api_t,
     lag_api: *const sai_lag_api_t,
     bridge_api: *const sai_bridge_api_t,
+    /// Optional api tables are `None` when the library doesn't implement them
+    hostif_api: Option<*const sai_hostif_api_t>,
+    stp_api: Option<*const sai_stp_api_t>,
+    queue_api: Option<*const sai_queue_api_t>,
+    scheduler_group_api: Option<*const sai_scheduler_group_api_t>,
+    hash_api: Option<*const sai_hash_api_t>,
+    router_interface_api: Option<*const sai_router_interface_api_t>,
+    tunnel_api: Option<*const sai_tunnel_api_t>,
 }
 
 unsafe impl Send for SaiAdapter {}
@@ -38,7 +158,18 @@ unsafe impl Sync for SaiAdapter {}
 impl SaiAdapter {
     /// Load a SAI library from the specified path
     pub fn load(library_path: &str) -> Result<Arc<Self>> {
-        info!("Loading SAI library from: {}", library_path);
+        Self::load_with_boot_type(library_path, SaiBootType::Cold)
+    }
+
+    /// Load a SAI library, initializing it for a cold or warm boot
+    ///
+    /// The boot type is reported through the `SAI_BOOT_TYPE` profile key,
+    /// which the library consults when the switch is created.
+    pub fn load_with_boot_type(library_path: &str, boot_type: SaiBootType) -> Result<Arc<Self>> {
+        info!(
+            "Loading SAI library from: {} ({:?} boot)",
+            library_path, boot_type
+        );
 
         // Load the shared library
         let library = unsafe {
@@ -68,22 +199,76 @@ impl SaiAdapter {
             })?
         };
 
+        // Optional; older libraries don't export it
+        let query_attribute_capability: Option<SaiQueryAttributeCapabilityFn> = unsafe {
+            match library.get::<SaiQueryAttributeCapabilityFn>(b"sai_query_attribute_capability\0")
+            {
+                Ok(symbol) => Some(*symbol),
+                Err(e) => {
+                    warn!("sai_query_attribute_capability not available: {}", e);
+                    None
+                }
+            }
+        };
+
+        // Optional; lets objects whose OID was lost be found again
+        let get_object_key: Option<SaiGetObjectKeyFn> = unsafe {
+            match library.get::<SaiGetObjectKeyFn>(b"sai_get_object_key\0") {
+                Ok(symbol) => Some(*symbol),
+                Err(e) => {
+                    warn!("sai_get_object_key not available: {}", e);
+                    None
+                }
+            }
+        };
+
+        // Optional; added in SAI 1.10
+        let query_api_version: Option<SaiQueryApiVersionFn> = unsafe {
+            library
+                .get::<SaiQueryApiVersionFn>(b"sai_query_api_version\0")
+                .ok()
+                .map(|symbol| *symbol)
+        };
+
         // Initialize SAI
+        WARM_BOOT.store(boot_type == SaiBootType::Warm, Ordering::SeqCst);
         let status = unsafe {
-            let service_table: sai_service_method_table_t = std::mem::zeroed();
+            let mut service_table: sai_service_method_table_t = std::mem::zeroed();
+            service_table.profile_get_value = Some(profile_get_value);
             api_initialize(0, &service_table)
         };
 
         SaiStatus::from(status).to_result()?;
         info!("SAI library initialized successfully");
 
+        let library_info = SaiLibraryInfo::query(library_path, query_api_version);
+        info!(
+            path = %library_info.path,
+            real_path = ?library_info.real_path,
+            api_version = %library_info
+                .api_version
+                .map_or_else(|| "unknown".to_string(), |v| v.to_string()),
+            "SAI library loaded"
+        );
+
         // Query all API tables
-        let switch_api = Self::query_api(&api_query, SAI_API_SWITCH)?;
-        let port_api = Self::query_api(&api_query, SAI_API_PORT)?;
-        let vlan_api = Self::query_api(&api_query, SAI_API_VLAN)?;
-        let fdb_api = Self::query_api(&api_query, SAI_API_FDB)?;
-        let lag_api = Self::query_api(&api_query, SAI_API_LAG)?;
-        let bridge_api = Self::query_api(&api_query, SAI_API_BRIDGE)?;
+        let switch_api = Self::query_api(*api_query, SAI_API_SWITCH)?;
+        let port_api = Self::query_api(*api_query, SAI_API_PORT)?;
+        let vlan_api = Self::query_api(*api_query, SAI_API_VLAN)?;
+        let fdb_api = Self::query_api(*api_query, SAI_API_FDB)?;
+        let lag_api = Self::query_api(*api_query, SAI_API_LAG)?;
+        let bridge_api = Self::query_api(*api_query, SAI_API_BRIDGE)?;
+
+        // Features beyond VLAN bridging; a library without them still loads
+        let hostif_api = Self::query_optional_api(*api_query, SAI_API_HOSTIF, "host interface");
+        let stp_api = Self::query_optional_api(*api_query, SAI_API_STP, "STP");
+        let queue_api = Self::query_optional_api(*api_query, SAI_API_QUEUE, "queue");
+        let scheduler_group_api =
+            Self::query_optional_api(*api_query, SAI_API_SCHEDULER_GROUP, "scheduler group");
+        let hash_api = Self::query_optional_api(*api_query, SAI_API_HASH, "hash");
+        let router_interface_api =
+            Self::query_optional_api(*api_query, SAI_API_ROUTER_INTERFACE, "router interface");
+        let tunnel_api = Self::query_optional_api(*api_query, SAI_API_TUNNEL, "tunnel");
 
         // Leak the symbols to get 'static lifetime
         #[allow(clippy::missing_transmute_annotations)]
@@ -95,17 +280,27 @@ impl SaiAdapter {
             _library: library,
             _api_query: api_query,
             api_uninitialize,
+            query_attribute_capability,
+            get_object_key,
+            library_info,
             switch_api,
             port_api,
             vlan_api,
             fdb_api,
             lag_api,
             bridge_api,
+            hostif_api,
+            stp_api,
+            queue_api,
+            scheduler_group_api,
+            hash_api,
+            router_interface_api,
+            tunnel_api,
         }))
     }
 
     /// Query a specific SAI API table
-    fn query_api<T>(api_query: &Symbol<SaiApiQueryFn>, api_type: sai_api_t) -> Result<*const T> {
+    fn query_api<T>(api_query: SaiApiQueryFn, api_type: sai_api_t) -> Result<*const T> {
         let mut api_ptr: *const c_void = std::ptr::null();
 
         let status = unsafe { api_query(api_type, &mut api_ptr as *mut *const c_void) };
@@ -119,11 +314,41 @@ impl SaiAdapter {
         Ok(api_ptr as *const T)
     }
 
+    /// Query an API table the library may not implement
+    fn query_optional_api<T>(
+        api_query: SaiApiQueryFn,
+        api_type: sai_api_t,
+        name: &str,
+    ) -> Option<*const T> {
+        match Self::query_api(api_query, api_type) {
+            Ok(api) => Some(api),
+            Err(e) => {
+                warn!("SAI {} API not available: {}", name, e);
+                None
+            }
+        }
+    }
+
     /// Get the Switch API table
     pub fn get_switch_api(&self) -> &sai_switch_api_t {
         unsafe { &*self.switch_api }
     }
 
+    /// Get `sai_query_attribute_capability`, if the library exports it
+    pub fn query_attribute_capability_fn(&self) -> Option<SaiQueryAttributeCapabilityFn> {
+        self.query_attribute_capability
+    }
+
+    /// Get `sai_get_object_key`, if the library exports it
+    pub fn get_object_key_fn(&self) -> Option<SaiGetObjectKeyFn> {
+        self.get_object_key
+    }
+
+    /// Path and version of the loaded library
+    pub fn library_info(&self) -> &SaiLibraryInfo {
+        &self.library_info
+    }
+
     /// Get the Port API table
     pub fn get_port_api(&self) -> &sai_port_api_t {
         unsafe { &*self.port_api }
@@ -148,6 +373,79 @@ impl SaiAdapter {
     pub fn get_bridge_api(&self) -> &sai_bridge_api_t {
         unsafe { &*self.bridge_api }
     }
+
+    /// Get the Host Interface API table, if the library implements it
+    pub fn get_hostif_api(&self) -> Option<&sai_hostif_api_t> {
+        self.hostif_api.map(|api| unsafe { &*api })
+    }
+
+    /// Get the STP API table, if the library implements it
+    pub fn get_stp_api(&self) -> Option<&sai_stp_api_t> {
+        self.stp_api.map(|api| unsafe { &*api })
+    }
+
+    /// Get the Queue API table, if the library implements it
+    pub fn get_queue_api(&self) -> Option<&sai_queue_api_t> {
+        self.queue_api.map(|api| unsafe { &*api })
+    }
+
+    /// Get the Scheduler Group API table, if the library implements it
+    pub fn get_scheduler_group_api(&self) -> Option<&sai_scheduler_group_api_t> {
+        self.scheduler_group_api.map(|api| unsafe { &*api })
+    }
+
+    /// Get the Hash API table, if the library implements it
+    pub fn get_hash_api(&self) -> Option<&sai_hash_api_t> {
+        self.hash_api.map(|api| unsafe { &*api })
+    }
+
+    /// Get the Router Interface API table, if the library implements it
+    pub fn get_router_interface_api(&self) -> Option<&sai_router_interface_api_t> {
+        self.router_interface_api.map(|api| unsafe { &*api })
+    }
+
+    /// Get the Tunnel API table, if the library implements it
+    pub fn get_tunnel_api(&self) -> Option<&sai_tunnel_api_t> {
+        self.tunnel_api.map(|api| unsafe { &*api })
+    }
+}
+
+#[cfg(feature = "diag")]
+impl SaiAdapter {
+    /// Raw attribute access across all loaded api tables
+    pub fn raw_attributes(&self) -> crate::diag::RawAttributes {
+        crate::diag::RawAttributes::new(
+            self.switch_api,
+            self.port_api,
+            self.vlan_api,
+            self.lag_api,
+            self.bridge_api,
+            self.hostif_api,
+        )
+    }
+
+    /// Read any attribute by numeric id, returning the raw value union
+    ///
+    /// Debugging escape hatch for attributes without a typed wrapper.
+    pub fn get_raw_attribute(
+        &self,
+        object_type: crate::SaiObjectType,
+        oid: racoon_common::SaiOid,
+        attr_id: u32,
+    ) -> Result<u64> {
+        self.raw_attributes().get(object_type, oid, attr_id)
+    }
+
+    /// Write any attribute by numeric id as a raw 64-bit value
+    pub fn set_raw_attribute(
+        &self,
+        object_type: crate::SaiObjectType,
+        oid: racoon_common::SaiOid,
+        attr_id: u32,
+        value: u64,
+    ) -> Result<()> {
+        self.raw_attributes().set(object_type, oid, attr_id, value)
+    }
 }
 
 impl Drop for SaiAdapter {
@@ -166,6 +464,72 @@ impl Drop for SaiAdapter {
 mod tests {
     use super::*;
 
+    unsafe extern "C" fn query_api_without_hostif(
+        api: sai_api_t,
+        api_method_table: *mut *const c_void,
+    ) -> sai_status_t {
+        if api == SAI_API_HOSTIF {
+            return SAI_STATUS_NOT_IMPLEMENTED as sai_status_t;
+        }
+        static TABLE: u8 = 0;
+        unsafe { *api_method_table = &TABLE as *const u8 as *const c_void };
+        SAI_STATUS_SUCCESS as sai_status_t
+    }
+
+    #[test]
+    fn test_missing_optional_api_tolerated() {
+        let vlan_api: Option<*const sai_vlan_api_t> =
+            SaiAdapter::query_optional_api(query_api_without_hostif, SAI_API_VLAN, "VLAN");
+        assert!(vlan_api.is_some());
+        let hostif_api: Option<*const sai_hostif_api_t> = SaiAdapter::query_optional_api(
+            query_api_without_hostif,
+            SAI_API_HOSTIF,
+            "host interface",
+        );
+        assert!(hostif_api.is_none());
+        assert!(
+            SaiAdapter::query_api::<sai_hostif_api_t>(query_api_without_hostif, SAI_API_HOSTIF)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_profile_reports_boot_type() {
+        let value = |key: &CStr| {
+            let value = unsafe { profile_get_value(0, key.as_ptr()) };
+            (!value.is_null()).then(|| unsafe { CStr::from_ptr(value) }.to_owned())
+        };
+
+        WARM_BOOT.store(true, Ordering::SeqCst);
+        assert_eq!(value(BOOT_TYPE_PROFILE_KEY).as_deref(), Some(c"1"));
+        WARM_BOOT.store(false, Ordering::SeqCst);
+        assert_eq!(value(BOOT_TYPE_PROFILE_KEY).as_deref(), Some(c"0"));
+        assert_eq!(value(c"SAI_INIT_CONFIG_FILE"), None);
+    }
+
+    unsafe extern "C" fn stub_query_api_version(version: *mut u64) -> sai_status_t {
+        unsafe { *version = 11401 };
+        SAI_STATUS_SUCCESS as sai_status_t
+    }
+
+    unsafe extern "C" fn failing_query_api_version(_version: *mut u64) -> sai_status_t {
+        SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+    }
+
+    #[test]
+    fn test_library_info_from_version_symbol() {
+        let info = SaiLibraryInfo::query("/nonexistent/libsai.so", Some(stub_query_api_version));
+        assert_eq!(info.path, "/nonexistent/libsai.so");
+        assert_eq!(info.real_path, None);
+        assert_eq!(info.api_version, Some(SaiApiVersion(11401)));
+        assert_eq!(info.api_version.unwrap().to_string(), "1.14.1");
+
+        let info = SaiLibraryInfo::query("/", Some(failing_query_api_version));
+        assert_eq!(info.real_path, Some(PathBuf::from("/")));
+        assert_eq!(info.api_version, None);
+        assert_eq!(SaiLibraryInfo::query("/", None).api_version, None);
+    }
+
     #[test]
     #[ignore] // Only run when SAI library is available
     fn test_load_sai_library() {
@@ -0,0 +1,162 @@
+//! ASIC_DB attribute encoding
+//!
+//! SONiC's syncd stores each SAI object in ASIC_DB as a hash at
+//! `ASIC_STATE:<object type>:oid:0x...`. Fields are SAI attribute names and
+//! values use the sairedis string form: OIDs as `oid:0x...`, enum values by
+//! constant name, integers in decimal. [`AsicAttributeSerializer`] produces
+//! the same layout so sairedis consumers can read what Racoon programs.
+
+use crate::bindings::*;
+use crate::enums::VlanTaggingMode;
+use crate::types::{SaiAttribute, SaiAttributeValue, SaiObjectType};
+use racoon_common::{RacoonError, Result, SaiOid};
+use std::collections::HashMap;
+
+/// Decodes an enum attribute's `s32` to its SAI constant name
+type EnumName = fn(i32) -> Option<&'static str>;
+
+/// Field name and enum decoder of a known attribute
+struct AttrSpec {
+    object_type: SaiObjectType,
+    id: u32,
+    name: &'static str,
+    enum_name: Option<EnumName>,
+}
+
+const ATTRS: &[AttrSpec] = &[
+    AttrSpec {
+        object_type: SaiObjectType::Vlan,
+        id: SAI_VLAN_ATTR_VLAN_ID,
+        name: "SAI_VLAN_ATTR_VLAN_ID",
+        enum_name: None,
+    },
+    AttrSpec {
+        object_type: SaiObjectType::VlanMember,
+        id: SAI_VLAN_MEMBER_ATTR_VLAN_ID,
+        name: "SAI_VLAN_MEMBER_ATTR_VLAN_ID",
+        enum_name: None,
+    },
+    AttrSpec {
+        object_type: SaiObjectType::VlanMember,
+        id: SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID,
+        name: "SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID",
+        enum_name: None,
+    },
+    AttrSpec {
+        object_type: SaiObjectType::VlanMember,
+        id: SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+        name: "SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE",
+        enum_name: Some(|value| VlanTaggingMode::from_sai(value).map(VlanTaggingMode::sai_name)),
+    },
+];
+
+/// Maps SAI attributes to ASIC_DB hash fields
+pub struct AsicAttributeSerializer;
+
+impl AsicAttributeSerializer {
+    /// ASIC_DB key of an object
+    pub fn key(object_type: SaiObjectType, oid: SaiOid) -> String {
+        format!("ASIC_STATE:{}:{}", object_type.sai_name(), Self::oid(oid))
+    }
+
+    /// sairedis form of an OID
+    pub fn oid(oid: SaiOid) -> String {
+        format!("oid:0x{:x}", oid)
+    }
+
+    /// Field name of an attribute, if the serializer knows it
+    pub fn field_name(object_type: SaiObjectType, attr_id: u32) -> Option<&'static str> {
+        Self::spec(object_type, attr_id).map(|spec| spec.name)
+    }
+
+    /// ASIC_DB fields for an object's attributes
+    ///
+    /// Fails with [`RacoonError::InvalidAttribute`] on an attribute without
+    /// a known name or an enum value outside its SAI enum.
+    pub fn serialize(
+        object_type: SaiObjectType,
+        attrs: &[SaiAttribute],
+    ) -> Result<HashMap<String, String>> {
+        attrs
+            .iter()
+            .map(|attr| {
+                let spec = Self::spec(object_type, attr.id).ok_or_else(|| {
+                    RacoonError::InvalidAttribute(format!(
+                        "no ASIC_DB name for {} attribute {}",
+                        object_type, attr.id
+                    ))
+                })?;
+                Ok((spec.name.to_string(), Self::value(spec, &attr.value)?))
+            })
+            .collect()
+    }
+
+    fn spec(object_type: SaiObjectType, attr_id: u32) -> Option<&'static AttrSpec> {
+        ATTRS
+            .iter()
+            .find(|spec| spec.object_type == object_type && spec.id == attr_id)
+    }
+
+    fn value(spec: &AttrSpec, value: &SaiAttributeValue) -> Result<String> {
+        let invalid = || {
+            RacoonError::InvalidAttribute(format!("invalid value {:?} for {}", value, spec.name))
+        };
+
+        Ok(match (value, spec.enum_name) {
+            (SaiAttributeValue::I32(v), Some(enum_name)) => {
+                enum_name(*v).ok_or_else(invalid)?.to_string()
+            }
+            (_, Some(_)) => return Err(invalid()),
+            (value, None) => value.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vlan::VlanApi;
+
+    #[test]
+    fn test_vlan_member_fields() {
+        let attrs =
+            VlanApi::member_attributes(0x26000000000001, 0x3a000000000004, VlanTaggingMode::Tagged);
+        let fields = AsicAttributeSerializer::serialize(SaiObjectType::VlanMember, &attrs).unwrap();
+
+        let expected: HashMap<String, String> = [
+            ("SAI_VLAN_MEMBER_ATTR_VLAN_ID", "oid:0x26000000000001"),
+            (
+                "SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID",
+                "oid:0x3a000000000004",
+            ),
+            (
+                "SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE",
+                "SAI_VLAN_TAGGING_MODE_TAGGED",
+            ),
+        ]
+        .into_iter()
+        .map(|(field, value)| (field.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(fields, expected);
+
+        assert_eq!(
+            AsicAttributeSerializer::key(SaiObjectType::VlanMember, 0x27000000000613),
+            "ASIC_STATE:SAI_OBJECT_TYPE_VLAN_MEMBER:oid:0x27000000000613"
+        );
+    }
+
+    #[test]
+    fn test_unknown_attribute_rejected() {
+        let attrs = [SaiAttribute::new_u32(
+            SAI_VLAN_ATTR_MAX_LEARNED_ADDRESSES,
+            10,
+        )];
+        assert!(AsicAttributeSerializer::serialize(SaiObjectType::Vlan, &attrs).is_err());
+
+        let attrs = [SaiAttribute::new_i32(
+            SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+            42,
+        )];
+        assert!(AsicAttributeSerializer::serialize(SaiObjectType::VlanMember, &attrs).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! SAI attribute names
+//!
+//! Id -> name tables per attribute enum, generated by `build.rs` from the
+//! bindings, so every attribute the headers define has its symbolic name.
+
+use crate::types::SaiObjectType;
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/sai_attr_names.rs"));
+}
+
+/// Symbolic name of an attribute, e.g. `SAI_VLAN_ATTR_VLAN_ID`
+pub fn attr_name(object_type: SaiObjectType, attr_id: u32) -> Option<&'static str> {
+    let object = object_type
+        .sai_name()
+        .strip_prefix("SAI_OBJECT_TYPE_")?
+        .to_lowercase();
+    generated::attr_names(&format!("sai_{}_attr_t", object))
+        .iter()
+        .find(|(id, _)| *id == attr_id)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::*;
+
+    #[test]
+    fn test_attr_names_per_object_type() {
+        assert_eq!(
+            attr_name(SaiObjectType::Vlan, SAI_VLAN_ATTR_VLAN_ID),
+            Some("SAI_VLAN_ATTR_VLAN_ID")
+        );
+        assert_eq!(
+            attr_name(
+                SaiObjectType::VlanMember,
+                SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID
+            ),
+            Some("SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID")
+        );
+        // Ids are scoped to their object type
+        assert_eq!(
+            attr_name(SaiObjectType::Port, SAI_PORT_ATTR_ADMIN_STATE),
+            Some("SAI_PORT_ATTR_ADMIN_STATE")
+        );
+        assert_eq!(attr_name(SaiObjectType::RouteEntry, 0), None);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::bindings::*;
+use crate::constants::*;
+use crate::enums::BridgePortType;
+use crate::oid::{NULL_OID, get_object_list, non_null};
+use crate::status::SaiStatus;
+use crate::types::SaiAttribute;
+use racoon_common::{Result, SaiOid};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tracing::instrument;
+
+pub struct BridgeApi {
+    api_table: *const sai_bridge_api_t,
+}
+
+unsafe impl Send for BridgeApi {}
+unsafe impl Sync for BridgeApi {}
+
+impl BridgeApi {
+    pub fn new(api_table: *const sai_bridge_api_t) -> Self {
+        Self { api_table }
+    }
+
+    /// Create a bridge port for a port or LAG
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_bridge_port(&self, switch_id: SaiOid, port_id: SaiOid) -> Result<SaiOid> {
+        let mut bridge_port_oid: SaiOid = NULL_OID;
+
+        let attrs = [
+            SaiAttribute::new_i32(SAI_BRIDGE_PORT_ATTR_TYPE, BridgePortType::Port.to_sai()),
+            SaiAttribute::new_oid(SAI_BRIDGE_PORT_ATTR_PORT_ID, port_id),
+            SaiAttribute::new_bool(SAI_BRIDGE_PORT_ATTR_ADMIN_STATE, true),
+        ];
+
+        let c_attrs: Vec<sai_attribute_t> = attrs
+            .iter()
+            .map(|attr| unsafe { attr.to_c_attribute() })
+            .collect();
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_bridge_port {
+                create_fn(
+                    &mut bridge_port_oid,
+                    switch_id,
+                    c_attrs.len() as u32,
+                    c_attrs.as_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(bridge_port_oid, "create_bridge_port")
+    }
+
+    /// Remove a bridge port
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_bridge_port(&self, bridge_port_oid: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_bridge_port {
+                remove_fn(bridge_port_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Bridge ports of a bridge (`SAI_BRIDGE_ATTR_PORT_LIST`)
+    pub fn get_bridge_ports(&self, bridge_oid: SaiOid) -> Result<Vec<SaiOid>> {
+        let get_fn = unsafe { (*self.api_table).get_bridge_attribute };
+        get_object_list(get_fn, bridge_oid, SAI_BRIDGE_ATTR_PORT_LIST)
+    }
+
+    /// Port or LAG a bridge port bridges (`SAI_BRIDGE_PORT_ATTR_PORT_ID`)
+    pub fn get_bridge_port_port(&self, bridge_port_oid: SaiOid) -> Result<SaiOid> {
+        let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
+        c_attr.id = SAI_BRIDGE_PORT_ATTR_PORT_ID;
+
+        let status = match unsafe { (*self.api_table).get_bridge_port_attribute } {
+            Some(get_fn) => unsafe { get_fn(bridge_port_oid, 1, &mut c_attr) },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(unsafe { c_attr.value.oid }, "get_bridge_port_port")
+    }
+}
+
+/// Bridge ports shared between VLAN members
+///
+/// A port that is a member of several VLANs has a single bridge port. It is
+/// created for the first membership and removed with the last one. The
+/// table stays locked while SAI creates or removes a bridge port, so a
+/// concurrent user of the same port waits for it instead of missing it.
+pub struct BridgePortTable {
+    /// Bridge port and reference count of each port
+    bridge_ports: Mutex<HashMap<SaiOid, (SaiOid, usize)>>,
+}
+
+impl BridgePortTable {
+    pub fn new() -> Self {
+        Self {
+            bridge_ports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<SaiOid, (SaiOid, usize)>> {
+        // Entries change only after SAI succeeded, so a panicking holder
+        // leaves them consistent
+        self.bridge_ports
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the bridge port of `port_id`, creating it on first use
+    pub fn acquire(&self, api: &BridgeApi, switch_id: SaiOid, port_id: SaiOid) -> Result<SaiOid> {
+        let mut bridge_ports = self.lock();
+        if let Some((bridge_port, refs)) = bridge_ports.get_mut(&port_id) {
+            *refs += 1;
+            return Ok(*bridge_port);
+        }
+
+        let bridge_port = api.create_bridge_port(switch_id, port_id)?;
+        bridge_ports.insert(port_id, (bridge_port, 1));
+        Ok(bridge_port)
+    }
+
+    /// Drop a reference, removing the bridge port with the last one
+    ///
+    /// Releasing a port without a bridge port does nothing. If SAI fails
+    /// to remove the bridge port, the reference is kept.
+    pub fn release(&self, api: &BridgeApi, port_id: SaiOid) -> Result<()> {
+        let mut bridge_ports = self.lock();
+        match bridge_ports.get_mut(&port_id) {
+            Some((_, refs)) if *refs > 1 => *refs -= 1,
+            Some((bridge_port, _)) => {
+                api.remove_bridge_port(*bridge_port)?;
+                bridge_ports.remove(&port_id);
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Bridge port of `port_id`, if one exists
+    pub fn get(&self, port_id: SaiOid) -> Option<SaiOid> {
+        self.lock()
+            .get(&port_id)
+            .map(|(bridge_port, _)| *bridge_port)
+    }
+}
+
+impl Default for BridgePortTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use racoon_common::VlanId;
+
+    #[test]
+    fn test_bridge_port_shared_between_vlan_members() {
+        let bridge_api = mock::bridge_api();
+        let vlan_api = mock::vlan_api();
+        let table = BridgePortTable::new();
+        let port = mock::add_port(&[1], 25_000);
+
+        let vlan100 = vlan_api.create_vlan(0, VlanId::new(100).unwrap()).unwrap();
+        let vlan200 = vlan_api.create_vlan(0, VlanId::new(200).unwrap()).unwrap();
+
+        let bp1 = table.acquire(&bridge_api, 0, port).unwrap();
+        let bp2 = table.acquire(&bridge_api, 0, port).unwrap();
+        assert_eq!(bp1, bp2);
+
+        let m1 = vlan_api
+            .create_vlan_member(0, vlan100, bp1, crate::VlanTaggingMode::Tagged)
+            .unwrap();
+        let m2 = vlan_api
+            .create_vlan_member(0, vlan200, bp2, crate::VlanTaggingMode::Tagged)
+            .unwrap();
+
+        vlan_api.remove_vlan_member(m1).unwrap();
+        table.release(&bridge_api, port).unwrap();
+        assert_eq!(table.get(port), Some(bp1));
+
+        vlan_api.remove_vlan_member(m2).unwrap();
+        table.release(&bridge_api, port).unwrap();
+        assert_eq!(table.get(port), None);
+
+        let bridge_calls: Vec<&str> = mock::take_calls()
+            .iter()
+            .map(|c| c.function)
+            .filter(|f| f.contains("bridge_port"))
+            .collect();
+        assert_eq!(
+            bridge_calls,
+            vec!["create_bridge_port", "remove_bridge_port"]
+        );
+    }
+
+    #[test]
+    fn test_concurrent_acquire_shares_one_bridge_port() {
+        let table = std::sync::Arc::new(BridgePortTable::new());
+        let port = 0x1000000000001;
+
+        // The mock keeps its state per thread, so each thread brings its own
+        let bridge_ports: Vec<SaiOid> = (0..8)
+            .map(|_| {
+                let table = table.clone();
+                std::thread::spawn(move || table.acquire(&mock::bridge_api(), 0, port).unwrap())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert!(bridge_ports.iter().all(|bp| *bp == bridge_ports[0]));
+        assert_eq!(table.get(port), Some(bridge_ports[0]));
+    }
+
+    #[test]
+    fn test_default_bridge_lists_bridge_ports_and_their_ports() {
+        let bridge_api = mock::bridge_api();
+        let port = mock::add_port(&[1], 25_000);
+        let bridge_port = mock::add_bridge_port(port);
+
+        let bridge = mock::switch_api().get_default_1q_bridge(0).unwrap();
+        let bridge_ports = bridge_api.get_bridge_ports(bridge).unwrap();
+        assert!(bridge_ports.contains(&bridge_port));
+        assert_eq!(bridge_api.get_bridge_port_port(bridge_port).unwrap(), port);
+    }
+}
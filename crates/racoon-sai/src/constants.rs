@@ -30,6 +30,10 @@ pub const SAI_STATUS_INVALID_OBJECT_ID: sai_status_t = -19;
 pub const SAI_STATUS_INVALID_NV_STORAGE: sai_status_t = -20;
 pub const SAI_STATUS_NV_STORAGE_FULL: sai_status_t = -21;
 pub const SAI_STATUS_INVALID_ATTRIBUTE_0: sai_status_t = -0x10000;
+pub const SAI_STATUS_ATTR_NOT_IMPLEMENTED_0: sai_status_t = -0x40000;
+pub const SAI_STATUS_ATTR_NOT_IMPLEMENTED_MAX: sai_status_t = -0x4ffff;
+pub const SAI_STATUS_ATTR_NOT_SUPPORTED_0: sai_status_t = -0x50000;
+pub const SAI_STATUS_ATTR_NOT_SUPPORTED_MAX: sai_status_t = -0x5ffff;
 
 // SAI API type and enum values (from sai.h)
 // The sai_api_t enum is defined in sai.h which includes experimental dependencies.
@@ -43,7 +47,14 @@ pub const SAI_API_SWITCH: sai_api_t = 1;
 pub const SAI_API_PORT: sai_api_t = 2;
 pub const SAI_API_FDB: sai_api_t = 3;
 pub const SAI_API_VLAN: sai_api_t = 4;
+pub const SAI_API_ROUTER_INTERFACE: sai_api_t = 9;
+pub const SAI_API_HOSTIF: sai_api_t = 12;
+pub const SAI_API_STP: sai_api_t = 15;
 pub const SAI_API_LAG: sai_api_t = 16;
+pub const SAI_API_QUEUE: sai_api_t = 20;
+pub const SAI_API_SCHEDULER_GROUP: sai_api_t = 22;
+pub const SAI_API_HASH: sai_api_t = 24;
+pub const SAI_API_TUNNEL: sai_api_t = 26;
 pub const SAI_API_BRIDGE: sai_api_t = 33;
 
 // Service method table function pointer types (from sai.h)
@@ -0,0 +1,216 @@
+//! Raw attribute access for debugging
+//!
+//! An escape hatch for attributes without a typed wrapper: any attribute of
+//! an OID-keyed object can be read or written by numeric id. Values are the
+//! raw 64-bit `sai_attribute_value_t` union, so list and struct attributes
+//! are out of reach. Only built with the `diag` feature, and every call is
+//! logged at warn level so its use shows up in the field.
+
+use crate::bindings::*;
+use crate::constants::*;
+use crate::status::SaiStatus;
+use crate::types::SaiObjectType;
+use racoon_common::{RacoonError, Result, SaiOid};
+use tracing::warn;
+
+type GetFn =
+    Option<unsafe extern "C" fn(sai_object_id_t, u32, *mut sai_attribute_t) -> sai_status_t>;
+type SetFn = Option<unsafe extern "C" fn(sai_object_id_t, *const sai_attribute_t) -> sai_status_t>;
+
+/// Routes raw attribute calls to the api table of an object type
+pub struct RawAttributes {
+    switch_api: *const sai_switch_api_t,
+    port_api: *const sai_port_api_t,
+    vlan_api: *const sai_vlan_api_t,
+    lag_api: *const sai_lag_api_t,
+    bridge_api: *const sai_bridge_api_t,
+    /// `None` when the library has no host interface api
+    hostif_api: Option<*const sai_hostif_api_t>,
+}
+
+unsafe impl Send for RawAttributes {}
+unsafe impl Sync for RawAttributes {}
+
+impl RawAttributes {
+    pub fn new(
+        switch_api: *const sai_switch_api_t,
+        port_api: *const sai_port_api_t,
+        vlan_api: *const sai_vlan_api_t,
+        lag_api: *const sai_lag_api_t,
+        bridge_api: *const sai_bridge_api_t,
+        hostif_api: Option<*const sai_hostif_api_t>,
+    ) -> Self {
+        Self {
+            switch_api,
+            port_api,
+            vlan_api,
+            lag_api,
+            bridge_api,
+            hostif_api,
+        }
+    }
+
+    /// Read the raw value of `attr_id` on `oid`
+    pub fn get(&self, object_type: SaiObjectType, oid: SaiOid, attr_id: u32) -> Result<u64> {
+        warn!(
+            "DIAG: raw read of attribute {} on {:?} 0x{:x}",
+            attr_id, object_type, oid
+        );
+        let (get_fn, _) = self.functions(object_type)?;
+
+        let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
+        c_attr.id = attr_id;
+        let status = match get_fn {
+            Some(get_fn) => unsafe { get_fn(oid, 1, &mut c_attr) },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        SaiStatus::from(status).to_result()?;
+        Ok(unsafe { c_attr.value.u64_ })
+    }
+
+    /// Write a raw value to `attr_id` on `oid`
+    pub fn set(
+        &self,
+        object_type: SaiObjectType,
+        oid: SaiOid,
+        attr_id: u32,
+        value: u64,
+    ) -> Result<()> {
+        warn!(
+            "DIAG: raw write of attribute {} = 0x{:x} on {:?} 0x{:x}",
+            attr_id, value, object_type, oid
+        );
+        let (_, set_fn) = self.functions(object_type)?;
+
+        let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
+        c_attr.id = attr_id;
+        c_attr.value.u64_ = value;
+        let status = match set_fn {
+            Some(set_fn) => unsafe { set_fn(oid, &c_attr) },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    fn hostif_api(&self) -> Result<*const sai_hostif_api_t> {
+        self.hostif_api
+            .ok_or_else(|| RacoonError::Sai("host interface API not available".to_string()))
+    }
+
+    fn functions(&self, object_type: SaiObjectType) -> Result<(GetFn, SetFn)> {
+        unsafe {
+            Ok(match object_type {
+                SaiObjectType::Switch => {
+                    let api = &*self.switch_api;
+                    (api.get_switch_attribute, api.set_switch_attribute)
+                }
+                SaiObjectType::Port => {
+                    let api = &*self.port_api;
+                    (api.get_port_attribute, api.set_port_attribute)
+                }
+                SaiObjectType::Vlan => {
+                    let api = &*self.vlan_api;
+                    (api.get_vlan_attribute, api.set_vlan_attribute)
+                }
+                SaiObjectType::VlanMember => {
+                    let api = &*self.vlan_api;
+                    (api.get_vlan_member_attribute, api.set_vlan_member_attribute)
+                }
+                SaiObjectType::Lag => {
+                    let api = &*self.lag_api;
+                    (api.get_lag_attribute, api.set_lag_attribute)
+                }
+                SaiObjectType::LagMember => {
+                    let api = &*self.lag_api;
+                    (api.get_lag_member_attribute, api.set_lag_member_attribute)
+                }
+                SaiObjectType::BridgePort => {
+                    let api = &*self.bridge_api;
+                    (api.get_bridge_port_attribute, api.set_bridge_port_attribute)
+                }
+                SaiObjectType::Hostif => {
+                    let api = &*self.hostif_api()?;
+                    (api.get_hostif_attribute, api.set_hostif_attribute)
+                }
+                SaiObjectType::HostifTrapGroup => {
+                    let api = &*self.hostif_api()?;
+                    (
+                        api.get_hostif_trap_group_attribute,
+                        api.set_hostif_trap_group_attribute,
+                    )
+                }
+                SaiObjectType::HostifTrap => {
+                    let api = &*self.hostif_api()?;
+                    (api.get_hostif_trap_attribute, api.set_hostif_trap_attribute)
+                }
+                other => {
+                    return Err(RacoonError::InvalidAttribute(format!(
+                        "raw attribute access is not supported for {:?}",
+                        other
+                    )));
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    fn raw_attributes() -> RawAttributes {
+        RawAttributes::new(
+            Box::leak(Box::new(mock::switch_api_table())),
+            Box::leak(Box::new(mock::port_api_table())),
+            Box::leak(Box::new(mock::vlan_api_table())),
+            Box::leak(Box::new(mock::lag_api_table())),
+            Box::leak(Box::new(mock::bridge_api_table())),
+            Some(Box::leak(Box::new(mock::hostif_api_table())) as *const _),
+        )
+    }
+
+    #[test]
+    fn test_vlan_attribute_routed_to_vlan_api() {
+        let raw = raw_attributes();
+        let vlan = mock::vlan_api().with_switch_id(0x21000000000000);
+        let vlan_oid = vlan
+            .create(racoon_common::VlanId::new(100).unwrap())
+            .unwrap();
+        mock::take_calls();
+
+        let value = raw
+            .get(
+                SaiObjectType::Vlan,
+                vlan_oid,
+                SAI_VLAN_ATTR_MAX_LEARNED_ADDRESSES,
+            )
+            .unwrap();
+        assert_eq!(
+            value,
+            mock::attr_value(vlan_oid, SAI_VLAN_ATTR_MAX_LEARNED_ADDRESSES)
+        );
+
+        raw.set(
+            SaiObjectType::Vlan,
+            vlan_oid,
+            SAI_VLAN_ATTR_MAX_LEARNED_ADDRESSES,
+            512,
+        )
+        .unwrap();
+        let calls = mock::take_calls();
+        let functions: Vec<&str> = calls.iter().map(|call| call.function).collect();
+        assert_eq!(functions, vec!["get_vlan_attribute", "set_vlan_attribute"]);
+        assert_eq!(
+            calls[1].attrs,
+            vec![(SAI_VLAN_ATTR_MAX_LEARNED_ADDRESSES, 512)]
+        );
+
+        assert!(matches!(
+            raw.get(SaiObjectType::RouteEntry, 1, 0),
+            Err(RacoonError::InvalidAttribute(_))
+        ));
+    }
+}
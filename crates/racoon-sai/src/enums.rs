@@ -0,0 +1,354 @@
+//! Typed wrappers for SAI enum attribute values
+//!
+//! SAI passes enum attributes as `s32`. These enums mirror the C values so
+//! callers never build attribute payloads from raw constants, and values read
+//! back from the ASIC are checked with `from_sai`.
+
+use crate::bindings::*;
+
+/// Define a `repr(i32)` enum mirroring a SAI enum, with `to_sai`/`from_sai`
+macro_rules! sai_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident = $value:ident,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(i32)]
+        pub enum $name {
+            $($variant = $value as i32,)+
+        }
+
+        impl $name {
+            /// Value carried in the attribute's `s32` field
+            pub fn to_sai(self) -> i32 {
+                self as i32
+            }
+
+            /// Name of the SAI constant, as sairedis writes it to ASIC_DB
+            pub fn sai_name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($value),)+
+                }
+            }
+
+            /// Convert a SAI value, rejecting values this wrapper does not know
+            pub fn from_sai(value: i32) -> Option<Self> {
+                match value {
+                    $(x if x == $value as i32 => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+sai_enum! {
+    /// `sai_vlan_tagging_mode_t`
+    pub enum VlanTaggingMode {
+        Untagged = SAI_VLAN_TAGGING_MODE_UNTAGGED,
+        Tagged = SAI_VLAN_TAGGING_MODE_TAGGED,
+        Priority = SAI_VLAN_TAGGING_MODE_PRIORITY_TAGGED,
+    }
+}
+
+impl From<racoon_common::VlanTaggingMode> for VlanTaggingMode {
+    fn from(mode: racoon_common::VlanTaggingMode) -> Self {
+        match mode {
+            racoon_common::VlanTaggingMode::Untagged => Self::Untagged,
+            racoon_common::VlanTaggingMode::Tagged => Self::Tagged,
+            racoon_common::VlanTaggingMode::Priority => Self::Priority,
+        }
+    }
+}
+
+sai_enum! {
+    /// `sai_packet_action_t`
+    pub enum PacketAction {
+        Drop = SAI_PACKET_ACTION_DROP,
+        Forward = SAI_PACKET_ACTION_FORWARD,
+        Copy = SAI_PACKET_ACTION_COPY,
+        CopyCancel = SAI_PACKET_ACTION_COPY_CANCEL,
+        Trap = SAI_PACKET_ACTION_TRAP,
+        Log = SAI_PACKET_ACTION_LOG,
+        Deny = SAI_PACKET_ACTION_DENY,
+        Transit = SAI_PACKET_ACTION_TRANSIT,
+    }
+}
+
+impl From<racoon_common::PacketAction> for PacketAction {
+    fn from(action: racoon_common::PacketAction) -> Self {
+        match action {
+            racoon_common::PacketAction::Forward => Self::Forward,
+            racoon_common::PacketAction::Drop => Self::Drop,
+            racoon_common::PacketAction::Trap => Self::Trap,
+            racoon_common::PacketAction::Copy => Self::Copy,
+        }
+    }
+}
+
+sai_enum! {
+    /// `sai_acl_stage_t` stages an ACL can be bound at
+    pub enum AclStage {
+        Ingress = SAI_ACL_STAGE_INGRESS,
+        Egress = SAI_ACL_STAGE_EGRESS,
+    }
+}
+
+sai_enum! {
+    /// `sai_fdb_entry_type_t`
+    pub enum FdbEntryType {
+        Dynamic = SAI_FDB_ENTRY_TYPE_DYNAMIC,
+        Static = SAI_FDB_ENTRY_TYPE_STATIC,
+    }
+}
+
+sai_enum! {
+    /// `sai_fdb_flush_entry_type_t`
+    pub enum FdbFlushEntryType {
+        Dynamic = SAI_FDB_FLUSH_ENTRY_TYPE_DYNAMIC,
+        Static = SAI_FDB_FLUSH_ENTRY_TYPE_STATIC,
+        All = SAI_FDB_FLUSH_ENTRY_TYPE_ALL,
+    }
+}
+
+sai_enum! {
+    /// `sai_bridge_port_type_t`
+    pub enum BridgePortType {
+        Port = SAI_BRIDGE_PORT_TYPE_PORT,
+        SubPort = SAI_BRIDGE_PORT_TYPE_SUB_PORT,
+        Router1Q = SAI_BRIDGE_PORT_TYPE_1Q_ROUTER,
+        Router1D = SAI_BRIDGE_PORT_TYPE_1D_ROUTER,
+        Tunnel = SAI_BRIDGE_PORT_TYPE_TUNNEL,
+    }
+}
+
+sai_enum! {
+    /// `sai_tunnel_map_type_t` directions between VNIs and VLANs; decap
+    /// maps are `VniToVlan`, encap maps `VlanToVni`
+    pub enum TunnelMapType {
+        VniToVlan = SAI_TUNNEL_MAP_TYPE_VNI_TO_VLAN_ID,
+        VlanToVni = SAI_TUNNEL_MAP_TYPE_VLAN_ID_TO_VNI,
+    }
+}
+
+sai_enum! {
+    /// `sai_port_link_training_rx_status_t`
+    pub enum PortLinkTrainingRxStatus {
+        NotTrained = SAI_PORT_LINK_TRAINING_RX_STATUS_NOT_TRAINED,
+        Trained = SAI_PORT_LINK_TRAINING_RX_STATUS_TRAINED,
+    }
+}
+
+sai_enum! {
+    /// `sai_port_link_training_failure_status_t`
+    pub enum PortLinkTrainingFailureStatus {
+        NoError = SAI_PORT_LINK_TRAINING_FAILURE_STATUS_NO_ERROR,
+        FrameLockError = SAI_PORT_LINK_TRAINING_FAILURE_STATUS_FRAME_LOCK_ERROR,
+        SnrLowerThreshold = SAI_PORT_LINK_TRAINING_FAILURE_STATUS_SNR_LOWER_THRESHOLD,
+        TimeOut = SAI_PORT_LINK_TRAINING_FAILURE_STATUS_TIME_OUT,
+    }
+}
+
+sai_enum! {
+    /// `sai_hostif_trap_type_t` (control protocols trapped to the CPU)
+    pub enum HostifTrapType {
+        Stp = SAI_HOSTIF_TRAP_TYPE_STP,
+        Lacp = SAI_HOSTIF_TRAP_TYPE_LACP,
+        Eapol = SAI_HOSTIF_TRAP_TYPE_EAPOL,
+        Lldp = SAI_HOSTIF_TRAP_TYPE_LLDP,
+        Pvrst = SAI_HOSTIF_TRAP_TYPE_PVRST,
+    }
+}
+
+sai_enum! {
+    /// `sai_switch_oper_status_t`
+    pub enum SwitchOperStatus {
+        Unknown = SAI_SWITCH_OPER_STATUS_UNKNOWN,
+        Up = SAI_SWITCH_OPER_STATUS_UP,
+        Down = SAI_SWITCH_OPER_STATUS_DOWN,
+        Failed = SAI_SWITCH_OPER_STATUS_FAILED,
+    }
+}
+
+impl SwitchOperStatus {
+    /// STATE_DB spelling
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+sai_enum! {
+    /// `sai_port_fec_mode_t`
+    pub enum FecMode {
+        None = SAI_PORT_FEC_MODE_NONE,
+        Rs = SAI_PORT_FEC_MODE_RS,
+        Fc = SAI_PORT_FEC_MODE_FC,
+    }
+}
+
+impl From<racoon_common::FecMode> for FecMode {
+    fn from(mode: racoon_common::FecMode) -> Self {
+        match mode {
+            racoon_common::FecMode::None => Self::None,
+            racoon_common::FecMode::Rs => Self::Rs,
+            racoon_common::FecMode::Fc => Self::Fc,
+        }
+    }
+}
+
+sai_enum! {
+    /// `sai_port_interface_type_t`
+    pub enum PortInterfaceType {
+        None = SAI_PORT_INTERFACE_TYPE_NONE,
+        Cr = SAI_PORT_INTERFACE_TYPE_CR,
+        Cr2 = SAI_PORT_INTERFACE_TYPE_CR2,
+        Cr4 = SAI_PORT_INTERFACE_TYPE_CR4,
+        Sr = SAI_PORT_INTERFACE_TYPE_SR,
+        Sr2 = SAI_PORT_INTERFACE_TYPE_SR2,
+        Sr4 = SAI_PORT_INTERFACE_TYPE_SR4,
+        Lr = SAI_PORT_INTERFACE_TYPE_LR,
+        Lr4 = SAI_PORT_INTERFACE_TYPE_LR4,
+        Kr = SAI_PORT_INTERFACE_TYPE_KR,
+        Kr4 = SAI_PORT_INTERFACE_TYPE_KR4,
+    }
+}
+
+impl From<racoon_common::PortInterfaceType> for PortInterfaceType {
+    fn from(interface_type: racoon_common::PortInterfaceType) -> Self {
+        use racoon_common::PortInterfaceType as Config;
+        match interface_type {
+            Config::None => Self::None,
+            Config::Cr => Self::Cr,
+            Config::Cr2 => Self::Cr2,
+            Config::Cr4 => Self::Cr4,
+            Config::Sr => Self::Sr,
+            Config::Sr2 => Self::Sr2,
+            Config::Sr4 => Self::Sr4,
+            Config::Lr => Self::Lr,
+            Config::Lr4 => Self::Lr4,
+            Config::Kr => Self::Kr,
+            Config::Kr4 => Self::Kr4,
+        }
+    }
+}
+
+sai_enum! {
+    /// `sai_native_hash_field_t` packet fields an ECMP or LAG hash covers
+    pub enum HashField {
+        SrcIp = SAI_NATIVE_HASH_FIELD_SRC_IP,
+        DstIp = SAI_NATIVE_HASH_FIELD_DST_IP,
+        InnerSrcIp = SAI_NATIVE_HASH_FIELD_INNER_SRC_IP,
+        InnerDstIp = SAI_NATIVE_HASH_FIELD_INNER_DST_IP,
+        VlanId = SAI_NATIVE_HASH_FIELD_VLAN_ID,
+        IpProtocol = SAI_NATIVE_HASH_FIELD_IP_PROTOCOL,
+        EtherType = SAI_NATIVE_HASH_FIELD_ETHERTYPE,
+        L4SrcPort = SAI_NATIVE_HASH_FIELD_L4_SRC_PORT,
+        L4DstPort = SAI_NATIVE_HASH_FIELD_L4_DST_PORT,
+        SrcMac = SAI_NATIVE_HASH_FIELD_SRC_MAC,
+        DstMac = SAI_NATIVE_HASH_FIELD_DST_MAC,
+        InPort = SAI_NATIVE_HASH_FIELD_IN_PORT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for mode in [
+            VlanTaggingMode::Untagged,
+            VlanTaggingMode::Tagged,
+            VlanTaggingMode::Priority,
+        ] {
+            assert_eq!(VlanTaggingMode::from_sai(mode.to_sai()), Some(mode));
+        }
+
+        for action in [
+            PacketAction::Drop,
+            PacketAction::Forward,
+            PacketAction::Copy,
+            PacketAction::CopyCancel,
+            PacketAction::Trap,
+            PacketAction::Log,
+            PacketAction::Deny,
+            PacketAction::Transit,
+        ] {
+            assert_eq!(PacketAction::from_sai(action.to_sai()), Some(action));
+        }
+
+        for entry_type in [FdbEntryType::Dynamic, FdbEntryType::Static] {
+            assert_eq!(
+                FdbEntryType::from_sai(entry_type.to_sai()),
+                Some(entry_type)
+            );
+        }
+
+        for flush_type in [
+            FdbFlushEntryType::Dynamic,
+            FdbFlushEntryType::Static,
+            FdbFlushEntryType::All,
+        ] {
+            assert_eq!(
+                FdbFlushEntryType::from_sai(flush_type.to_sai()),
+                Some(flush_type)
+            );
+        }
+
+        for port_type in [
+            BridgePortType::Port,
+            BridgePortType::SubPort,
+            BridgePortType::Router1Q,
+            BridgePortType::Router1D,
+            BridgePortType::Tunnel,
+        ] {
+            assert_eq!(
+                BridgePortType::from_sai(port_type.to_sai()),
+                Some(port_type)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tagging_mode_from_sai() {
+        assert_eq!(
+            VlanTaggingMode::from_sai(SAI_VLAN_TAGGING_MODE_UNTAGGED as i32),
+            Some(VlanTaggingMode::Untagged)
+        );
+        assert_eq!(
+            VlanTaggingMode::from_sai(SAI_VLAN_TAGGING_MODE_TAGGED as i32),
+            Some(VlanTaggingMode::Tagged)
+        );
+        assert_eq!(
+            VlanTaggingMode::from_sai(SAI_VLAN_TAGGING_MODE_PRIORITY_TAGGED as i32),
+            Some(VlanTaggingMode::Priority)
+        );
+        assert_eq!(VlanTaggingMode::from_sai(3), None);
+    }
+
+    #[test]
+    fn test_matches_sai_values() {
+        assert_eq!(
+            VlanTaggingMode::Tagged.to_sai(),
+            SAI_VLAN_TAGGING_MODE_TAGGED as i32
+        );
+        assert_eq!(
+            PacketAction::Forward.to_sai(),
+            SAI_PACKET_ACTION_FORWARD as i32
+        );
+        assert_eq!(
+            VlanTaggingMode::from(racoon_common::VlanTaggingMode::Priority).to_sai(),
+            SAI_VLAN_TAGGING_MODE_PRIORITY_TAGGED as i32
+        );
+        assert_eq!(VlanTaggingMode::from_sai(-1), None);
+        assert_eq!(PacketAction::from_sai(1000), None);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::bindings::*;
 use crate::constants::*;
+pub use crate::enums::{FdbEntryType, FdbFlushEntryType, PacketAction};
 use crate::status::SaiStatus;
 use crate::types::SaiAttribute;
 use racoon_common::{MacAddress, Result, SaiOid};
+use tracing::instrument;
 
 pub struct FdbApi {
     api_table: *const sai_fdb_api_t,
@@ -16,7 +18,24 @@ impl FdbApi {
         Self { api_table }
     }
 
+    /// Attributes an FDB entry is created with
+    pub fn entry_attributes(
+        bridge_port_id: SaiOid,
+        entry_type: FdbEntryType,
+        packet_action: PacketAction,
+    ) -> Vec<SaiAttribute> {
+        vec![
+            SaiAttribute::new_i32(SAI_FDB_ENTRY_ATTR_TYPE, entry_type.to_sai()),
+            SaiAttribute::new_oid(SAI_FDB_ENTRY_ATTR_BRIDGE_PORT_ID, bridge_port_id),
+            SaiAttribute::new_i32(SAI_FDB_ENTRY_ATTR_PACKET_ACTION, packet_action.to_sai()),
+        ]
+    }
+
     /// Create an FDB entry
+    ///
+    /// Ordinary entries use [`PacketAction::Forward`]; `Drop` blackholes the
+    /// MAC and `Trap` sends its frames to the CPU.
+    #[instrument(level = "debug", skip(self))]
     pub fn create_fdb_entry(
         &self,
         switch_id: SaiOid,
@@ -24,20 +43,14 @@ impl FdbApi {
         bv_id: SaiOid,
         bridge_port_id: SaiOid,
         entry_type: FdbEntryType,
+        packet_action: PacketAction,
     ) -> Result<()> {
         let mut fdb_entry: sai_fdb_entry_t = unsafe { std::mem::zeroed() };
         fdb_entry.switch_id = switch_id;
         fdb_entry.mac_address.copy_from_slice(mac.as_bytes());
         fdb_entry.bv_id = bv_id;
 
-        let attrs = [
-            SaiAttribute::new_i32(SAI_FDB_ENTRY_ATTR_TYPE, entry_type as i32),
-            SaiAttribute::new_oid(SAI_FDB_ENTRY_ATTR_BRIDGE_PORT_ID, bridge_port_id),
-            SaiAttribute::new_i32(
-                SAI_FDB_ENTRY_ATTR_PACKET_ACTION,
-                SAI_PACKET_ACTION_FORWARD as i32,
-            ),
-        ];
+        let attrs = Self::entry_attributes(bridge_port_id, entry_type, packet_action);
 
         let c_attrs: Vec<sai_attribute_t> = attrs
             .iter()
@@ -57,6 +70,7 @@ impl FdbApi {
     }
 
     /// Remove an FDB entry
+    #[instrument(level = "debug", skip(self))]
     pub fn remove_fdb_entry(
         &self,
         switch_id: SaiOid,
@@ -81,6 +95,7 @@ impl FdbApi {
     }
 
     /// Flush FDB entries
+    #[instrument(level = "debug", skip(self))]
     pub fn flush_fdb_entries(&self, switch_id: SaiOid, attributes: &[SaiAttribute]) -> Result<()> {
         let c_attrs: Vec<sai_attribute_t> = attributes
             .iter()
@@ -100,8 +115,38 @@ impl FdbApi {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum FdbEntryType {
-    Dynamic = SAI_FDB_ENTRY_TYPE_DYNAMIC as isize,
-    Static = SAI_FDB_ENTRY_TYPE_STATIC as isize,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SaiAttributeValue;
+
+    fn packet_action(attrs: &[SaiAttribute]) -> Option<i32> {
+        attrs.iter().find_map(|attr| match attr.value {
+            SaiAttributeValue::I32(v) if attr.id == SAI_FDB_ENTRY_ATTR_PACKET_ACTION => Some(v),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_blackhole_entry_drops() {
+        let blackhole = FdbApi::entry_attributes(
+            0x3a000000000001,
+            FdbEntryType::Static,
+            racoon_common::PacketAction::Drop.into(),
+        );
+        assert_eq!(
+            packet_action(&blackhole),
+            Some(SAI_PACKET_ACTION_DROP as i32)
+        );
+
+        let ordinary = FdbApi::entry_attributes(
+            0x3a000000000001,
+            FdbEntryType::Dynamic,
+            PacketAction::Forward,
+        );
+        assert_eq!(
+            packet_action(&ordinary),
+            Some(SAI_PACKET_ACTION_FORWARD as i32)
+        );
+    }
 }
@@ -0,0 +1,155 @@
+use crate::bindings::*;
+use crate::constants::*;
+pub use crate::enums::HashField;
+use crate::oid::{NULL_OID, non_null};
+use crate::status::SaiStatus;
+use crate::types::SaiAttribute;
+use crate::vlan::stored_switch_id;
+use racoon_common::{Result, SaiOid};
+use tracing::instrument;
+
+/// Hash objects selecting the packet fields ECMP and LAG hashing use
+///
+/// A hash takes effect once assigned to the switch with
+/// [`SwitchApi::set_ecmp_hash`](crate::SwitchApi::set_ecmp_hash) or
+/// [`SwitchApi::set_lag_hash`](crate::SwitchApi::set_lag_hash).
+pub struct HashApi {
+    api_table: *const sai_hash_api_t,
+    switch_id: Option<SaiOid>,
+}
+
+unsafe impl Send for HashApi {}
+unsafe impl Sync for HashApi {}
+
+impl HashApi {
+    pub fn new(api_table: *const sai_hash_api_t) -> Self {
+        Self {
+            api_table,
+            switch_id: None,
+        }
+    }
+
+    /// Store the switch used by [`create`](Self::create)
+    pub fn with_switch_id(mut self, switch_id: SaiOid) -> Self {
+        self.switch_id = Some(switch_id);
+        self
+    }
+
+    /// `SAI_HASH_ATTR_NATIVE_HASH_FIELD_LIST` covering `fields`
+    fn field_list(fields: &[HashField]) -> SaiAttribute {
+        SaiAttribute::new_s32_list(
+            SAI_HASH_ATTR_NATIVE_HASH_FIELD_LIST,
+            fields.iter().map(|field| field.to_sai()).collect(),
+        )
+    }
+
+    /// Create a hash over `fields` on the stored switch
+    pub fn create(&self, fields: &[HashField]) -> Result<SaiOid> {
+        self.create_hash(stored_switch_id(self.switch_id)?, fields)
+    }
+
+    /// Create a hash over `fields`
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_hash(&self, switch_id: SaiOid, fields: &[HashField]) -> Result<SaiOid> {
+        let mut hash_oid: SaiOid = NULL_OID;
+        let attr = Self::field_list(fields);
+        let c_attr = unsafe { attr.to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_hash {
+                create_fn(&mut hash_oid, switch_id, 1, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(hash_oid, "create_hash")
+    }
+
+    /// Replace the fields a hash covers
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_fields(&self, hash_oid: SaiOid, fields: &[HashField]) -> Result<()> {
+        let attr = Self::field_list(fields);
+        let c_attr = unsafe { attr.to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(set_fn) = api.set_hash_attribute {
+                set_fn(hash_oid, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Remove a hash; it must no longer be assigned to the switch
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_hash(&self, hash_oid: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_hash {
+                remove_fn(hash_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::types::SaiObjectType;
+
+    #[test]
+    fn test_hash_assigned_to_switch() {
+        let switch_id = 0x21000000000000;
+        let api = mock::hash_api().with_switch_id(switch_id);
+        let five_tuple = [
+            HashField::SrcIp,
+            HashField::DstIp,
+            HashField::IpProtocol,
+            HashField::L4SrcPort,
+            HashField::L4DstPort,
+        ];
+
+        let hash_oid = api.create(&five_tuple).unwrap();
+        assert_eq!(SaiObjectType::from_oid(hash_oid), Some(SaiObjectType::Hash));
+        assert_eq!(
+            mock::hash_fields(hash_oid),
+            five_tuple.map(HashField::to_sai)
+        );
+        mock::take_calls();
+
+        mock::switch_api()
+            .set_ecmp_hash(switch_id, hash_oid)
+            .unwrap();
+        let calls = mock::take_calls();
+        assert!(calls.iter().all(|call| call.oid == switch_id));
+        assert_eq!(
+            calls
+                .iter()
+                .flat_map(|call| &call.attrs)
+                .collect::<Vec<_>>(),
+            [
+                &(SAI_SWITCH_ATTR_ECMP_HASH_IPV4, hash_oid),
+                &(SAI_SWITCH_ATTR_ECMP_HASH_IPV4_IN_IPV4, hash_oid),
+                &(SAI_SWITCH_ATTR_ECMP_HASH_IPV6, hash_oid),
+            ]
+        );
+
+        api.set_fields(hash_oid, &[HashField::SrcMac, HashField::DstMac])
+            .unwrap();
+        assert_eq!(
+            mock::hash_fields(hash_oid),
+            [HashField::SrcMac.to_sai(), HashField::DstMac.to_sai()]
+        );
+    }
+}
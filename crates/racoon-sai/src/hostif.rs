@@ -0,0 +1,327 @@
+use crate::bindings::*;
+use crate::constants::*;
+pub use crate::enums::{HostifTrapType, PacketAction};
+use crate::oid::{NULL_OID, non_null};
+use crate::status::SaiStatus;
+use crate::types::SaiAttribute;
+use racoon_common::{RacoonError, Result, SaiOid};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::{instrument, warn};
+
+/// A netdev host interface created through [`HostifApi::create_hostif`]
+#[derive(Debug, Clone, Copy)]
+struct Netdev {
+    port: SaiOid,
+    hostif: SaiOid,
+}
+
+pub struct HostifApi {
+    api_table: *const sai_hostif_api_t,
+    /// Created netdevs by Linux interface name
+    netdevs: Mutex<HashMap<String, Netdev>>,
+}
+
+unsafe impl Send for HostifApi {}
+unsafe impl Sync for HostifApi {}
+
+impl HostifApi {
+    pub fn new(api_table: *const sai_hostif_api_t) -> Self {
+        Self {
+            api_table,
+            netdevs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a netdev host interface named `name` for `port_id`
+    ///
+    /// Creating the same name for the same port again returns the existing
+    /// host interface. A name already used by another port, or one SAI
+    /// reports as existing, fails with [`RacoonError::HostifNameConflict`].
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_hostif(&self, switch_id: SaiOid, port_id: SaiOid, name: &str) -> Result<SaiOid> {
+        if name.is_empty() || name.len() >= SAI_HOSTIF_NAME_SIZE as usize {
+            return Err(RacoonError::InvalidAttribute(format!(
+                "host interface name '{}' must be 1-{} bytes",
+                name,
+                SAI_HOSTIF_NAME_SIZE - 1
+            )));
+        }
+
+        let mut netdevs = self.netdevs.lock().unwrap();
+        if let Some(netdev) = netdevs.get(name) {
+            if netdev.port == port_id {
+                return Ok(netdev.hostif);
+            }
+            warn!(
+                "Host interface {} for port 0x{:x} is already used by port 0x{:x}",
+                name, port_id, netdev.port
+            );
+            return Err(RacoonError::HostifNameConflict {
+                name: name.to_string(),
+                port: port_id,
+            });
+        }
+
+        let mut hostif_oid: SaiOid = NULL_OID;
+
+        let attrs = [
+            SaiAttribute::new_i32(SAI_HOSTIF_ATTR_TYPE, SAI_HOSTIF_TYPE_NETDEV as i32),
+            SaiAttribute::new_oid(SAI_HOSTIF_ATTR_OBJ_ID, port_id),
+            SaiAttribute::new_chardata(SAI_HOSTIF_ATTR_NAME, name),
+        ];
+
+        let c_attrs: Vec<sai_attribute_t> = attrs
+            .iter()
+            .map(|attr| unsafe { attr.to_c_attribute() })
+            .collect();
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_hostif {
+                create_fn(
+                    &mut hostif_oid,
+                    switch_id,
+                    c_attrs.len() as u32,
+                    c_attrs.as_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        // Created outside this wrapper, e.g. before a restart
+        if SaiStatus::from(status) == SaiStatus::ITEM_ALREADY_EXISTS {
+            return Err(RacoonError::HostifNameConflict {
+                name: name.to_string(),
+                port: port_id,
+            });
+        }
+        SaiStatus::from(status).to_result()?;
+        non_null(hostif_oid, "create_hostif")?;
+
+        netdevs.insert(
+            name.to_string(),
+            Netdev {
+                port: port_id,
+                hostif: hostif_oid,
+            },
+        );
+        Ok(hostif_oid)
+    }
+
+    /// Remove a host interface, freeing its netdev name
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_hostif(&self, hostif_oid: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_hostif {
+                remove_fn(hostif_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        self.netdevs
+            .lock()
+            .unwrap()
+            .retain(|_, netdev| netdev.hostif != hostif_oid);
+        Ok(())
+    }
+
+    /// Create a trap group delivering trapped packets to CPU queue `queue`
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_trap_group(&self, switch_id: SaiOid, queue: u32) -> Result<SaiOid> {
+        let mut group_oid: SaiOid = NULL_OID;
+
+        let attrs = [
+            SaiAttribute::new_bool(SAI_HOSTIF_TRAP_GROUP_ATTR_ADMIN_STATE, true),
+            SaiAttribute::new_u32(SAI_HOSTIF_TRAP_GROUP_ATTR_QUEUE, queue),
+        ];
+
+        let c_attrs: Vec<sai_attribute_t> = attrs
+            .iter()
+            .map(|attr| unsafe { attr.to_c_attribute() })
+            .collect();
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_hostif_trap_group {
+                create_fn(
+                    &mut group_oid,
+                    switch_id,
+                    c_attrs.len() as u32,
+                    c_attrs.as_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(group_oid, "create_hostif_trap_group")
+    }
+
+    /// Remove a trap group
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_trap_group(&self, group_oid: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_hostif_trap_group {
+                remove_fn(group_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Create a trap for a control protocol
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_trap(
+        &self,
+        switch_id: SaiOid,
+        trap_type: HostifTrapType,
+        packet_action: PacketAction,
+        trap_group: SaiOid,
+    ) -> Result<SaiOid> {
+        let mut trap_oid: SaiOid = NULL_OID;
+
+        let attrs = trap_attributes(trap_type, packet_action, trap_group);
+
+        let c_attrs: Vec<sai_attribute_t> = attrs
+            .iter()
+            .map(|attr| unsafe { attr.to_c_attribute() })
+            .collect();
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_hostif_trap {
+                create_fn(
+                    &mut trap_oid,
+                    switch_id,
+                    c_attrs.len() as u32,
+                    c_attrs.as_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(trap_oid, "create_hostif_trap")
+    }
+
+    /// Remove a trap
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_trap(&self, trap_oid: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_hostif_trap {
+                remove_fn(trap_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+}
+
+/// Attributes of a trap
+pub fn trap_attributes(
+    trap_type: HostifTrapType,
+    packet_action: PacketAction,
+    trap_group: SaiOid,
+) -> [SaiAttribute; 3] {
+    [
+        SaiAttribute::new_i32(SAI_HOSTIF_TRAP_ATTR_TRAP_TYPE, trap_type.to_sai()),
+        SaiAttribute::new_i32(SAI_HOSTIF_TRAP_ATTR_PACKET_ACTION, packet_action.to_sai()),
+        SaiAttribute::new_oid(SAI_HOSTIF_TRAP_ATTR_TRAP_GROUP, trap_group),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::types::SaiObjectType;
+
+    #[test]
+    fn test_lacp_trap() {
+        let api = mock::hostif_api();
+
+        let group = api.create_trap_group(0, 7).unwrap();
+        let trap = api
+            .create_trap(0, HostifTrapType::Lacp, PacketAction::Trap, group)
+            .unwrap();
+        assert_eq!(
+            SaiObjectType::from_oid(trap),
+            Some(SaiObjectType::HostifTrap)
+        );
+
+        let calls = mock::take_calls();
+        assert_eq!(calls[0].function, "create_hostif_trap_group");
+        assert_eq!(
+            calls[0].attrs,
+            vec![
+                (SAI_HOSTIF_TRAP_GROUP_ATTR_ADMIN_STATE, 1),
+                (SAI_HOSTIF_TRAP_GROUP_ATTR_QUEUE, 7)
+            ]
+        );
+        assert_eq!(calls[1].function, "create_hostif_trap");
+        assert_eq!(
+            calls[1].attrs,
+            vec![
+                (
+                    SAI_HOSTIF_TRAP_ATTR_TRAP_TYPE,
+                    SAI_HOSTIF_TRAP_TYPE_LACP as u64
+                ),
+                (
+                    SAI_HOSTIF_TRAP_ATTR_PACKET_ACTION,
+                    SAI_PACKET_ACTION_TRAP as u64
+                ),
+                (SAI_HOSTIF_TRAP_ATTR_TRAP_GROUP, group)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hostif_name_conflict() {
+        let api = mock::hostif_api();
+
+        let hostif = api.create_hostif(0, 0x1000000000001, "Ethernet0").unwrap();
+        assert_eq!(SaiObjectType::from_oid(hostif), Some(SaiObjectType::Hostif));
+        // Same port and name reuses the host interface
+        assert_eq!(
+            api.create_hostif(0, 0x1000000000001, "Ethernet0").unwrap(),
+            hostif
+        );
+
+        // Another port asking for the name is caught before calling SAI
+        mock::take_calls();
+        let err = api
+            .create_hostif(0, 0x1000000000002, "Ethernet0")
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            RacoonError::HostifNameConflict { name, port }
+                if name == "Ethernet0" && *port == 0x1000000000002
+        ));
+        assert!(err.to_string().contains("0x1000000000002"));
+        assert!(mock::take_calls().is_empty());
+
+        // A name SAI already knows about surfaces the same error
+        let fresh = mock::hostif_api();
+        assert!(matches!(
+            fresh.create_hostif(0, 0x1000000000002, "Ethernet0"),
+            Err(RacoonError::HostifNameConflict { .. })
+        ));
+
+        // Removing the host interface frees the name
+        api.remove_hostif(hostif).unwrap();
+        assert!(api.create_hostif(0, 0x1000000000002, "Ethernet0").is_ok());
+    }
+}
@@ -1,11 +1,16 @@
 use crate::bindings::*;
 use crate::constants::*;
+use crate::oid::{NULL_OID, get_object_list, non_null};
+use crate::port::PortApi;
 use crate::status::SaiStatus;
 use crate::types::SaiAttribute;
+use crate::vlan::stored_switch_id;
 use racoon_common::{Result, SaiOid};
+use tracing::instrument;
 
 pub struct LagApi {
     api_table: *const sai_lag_api_t,
+    switch_id: Option<SaiOid>,
 }
 
 unsafe impl Send for LagApi {}
@@ -13,12 +18,33 @@ unsafe impl Sync for LagApi {}
 
 impl LagApi {
     pub fn new(api_table: *const sai_lag_api_t) -> Self {
-        Self { api_table }
+        Self {
+            api_table,
+            switch_id: None,
+        }
+    }
+
+    /// Store the switch used by [`create`](Self::create) and
+    /// [`create_member`](Self::create_member)
+    pub fn with_switch_id(mut self, switch_id: SaiOid) -> Self {
+        self.switch_id = Some(switch_id);
+        self
+    }
+
+    /// Create a LAG on the stored switch
+    pub fn create(&self, attributes: &[SaiAttribute]) -> Result<SaiOid> {
+        self.create_lag(stored_switch_id(self.switch_id)?, attributes)
+    }
+
+    /// Create a LAG member on the stored switch
+    pub fn create_member(&self, lag_id: SaiOid, port_id: SaiOid) -> Result<SaiOid> {
+        self.create_lag_member(stored_switch_id(self.switch_id)?, lag_id, port_id)
     }
 
     /// Create a LAG (Link Aggregation Group / Port Channel)
+    #[instrument(level = "debug", skip(self))]
     pub fn create_lag(&self, switch_id: SaiOid, attributes: &[SaiAttribute]) -> Result<SaiOid> {
-        let mut lag_oid: SaiOid = 0;
+        let mut lag_oid: SaiOid = NULL_OID;
 
         let c_attrs: Vec<sai_attribute_t> = attributes
             .iter()
@@ -40,10 +66,11 @@ impl LagApi {
         };
 
         SaiStatus::from(status).to_result()?;
-        Ok(lag_oid)
+        non_null(lag_oid, "create_lag")
     }
 
     /// Remove a LAG
+    #[instrument(level = "debug", skip(self))]
     pub fn remove_lag(&self, lag_oid: SaiOid) -> Result<()> {
         let status = unsafe {
             let api = &*self.api_table;
@@ -57,14 +84,23 @@ impl LagApi {
         SaiStatus::from(status).to_result()
     }
 
+    /// LAG members of a LAG (`SAI_LAG_ATTR_PORT_LIST`)
+    ///
+    /// Empty for a LAG without members.
+    pub fn get_member_list(&self, lag_oid: SaiOid) -> Result<Vec<SaiOid>> {
+        let get_fn = unsafe { (*self.api_table).get_lag_attribute };
+        get_object_list(get_fn, lag_oid, SAI_LAG_ATTR_PORT_LIST)
+    }
+
     /// Create a LAG member (add port to LAG)
+    #[instrument(level = "debug", skip(self))]
     pub fn create_lag_member(
         &self,
         switch_id: SaiOid,
         lag_id: SaiOid,
         port_id: SaiOid,
     ) -> Result<SaiOid> {
-        let mut member_oid: SaiOid = 0;
+        let mut member_oid: SaiOid = NULL_OID;
 
         let attrs = [
             SaiAttribute::new_oid(SAI_LAG_MEMBER_ATTR_LAG_ID, lag_id),
@@ -91,10 +127,11 @@ impl LagApi {
         };
 
         SaiStatus::from(status).to_result()?;
-        Ok(member_oid)
+        non_null(member_oid, "create_lag_member")
     }
 
     /// Remove a LAG member
+    #[instrument(level = "debug", skip(self))]
     pub fn remove_lag_member(&self, member_oid: SaiOid) -> Result<()> {
         let status = unsafe {
             let api = &*self.api_table;
@@ -109,6 +146,7 @@ impl LagApi {
     }
 
     /// Set LAG attribute
+    #[instrument(level = "debug", skip(self))]
     pub fn set_attribute(&self, lag_oid: SaiOid, attribute: &SaiAttribute) -> Result<()> {
         let c_attr = unsafe { attribute.to_c_attribute() };
 
@@ -123,4 +161,142 @@ impl LagApi {
 
         SaiStatus::from(status).to_result()
     }
+
+    /// Set LAG member attribute
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_member_attribute(&self, member_oid: SaiOid, attribute: &SaiAttribute) -> Result<()> {
+        let c_attr = unsafe { attribute.to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(set_fn) = api.set_lag_member_attribute {
+                set_fn(member_oid, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Enable or disable forwarding through a LAG member
+    ///
+    /// A disabled member stays in the LAG but neither sends nor receives
+    /// traffic.
+    pub fn set_member_enabled(&self, member_oid: SaiOid, enabled: bool) -> Result<()> {
+        self.set_member_attribute(
+            member_oid,
+            &SaiAttribute::new_bool(SAI_LAG_MEMBER_ATTR_EGRESS_DISABLE, !enabled),
+        )?;
+        self.set_member_attribute(
+            member_oid,
+            &SaiAttribute::new_bool(SAI_LAG_MEMBER_ATTR_INGRESS_DISABLE, !enabled),
+        )
+    }
+
+    /// Get LAG statistics
+    ///
+    /// SAI has no LAG counters (`sai_lag_api_t` carries no stats functions),
+    /// so the LAG's counters are the sum of its member ports' counters.
+    pub fn get_stats(
+        &self,
+        port_api: &PortApi,
+        member_ports: &[SaiOid],
+        counter_ids: &[sai_port_stat_t],
+    ) -> Result<Vec<u64>> {
+        let mut counters = vec![0u64; counter_ids.len()];
+
+        for port_oid in member_ports {
+            let port_counters = port_api.get_stats(*port_oid, counter_ids)?;
+            for (total, value) in counters.iter_mut().zip(port_counters) {
+                *total = total.wrapping_add(value);
+            }
+        }
+
+        Ok(counters)
+    }
+
+    /// Clear LAG statistics by clearing each member port
+    pub fn clear_stats(
+        &self,
+        port_api: &PortApi,
+        member_ports: &[SaiOid],
+        counter_ids: &[sai_port_stat_t],
+    ) -> Result<()> {
+        for port_oid in member_ports {
+            port_api.clear_stats(*port_oid, counter_ids)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_lag_stats_sum_members() {
+        let lag_api = LagApi::new(Box::leak(Box::new(sai_lag_api_t::default())));
+        let port_api = mock::port_api();
+        let members = [mock::add_port(&[1], 25_000), mock::add_port(&[2], 25_000)];
+        let counter_ids = [SAI_PORT_STAT_IF_IN_OCTETS, SAI_PORT_STAT_IF_OUT_OCTETS];
+
+        let counters = lag_api
+            .get_stats(&port_api, &members, &counter_ids)
+            .unwrap();
+        let expected: Vec<u64> = counter_ids
+            .iter()
+            .map(|id| members.iter().map(|oid| mock::stat_value(*oid, *id)).sum())
+            .collect();
+        assert_eq!(counters, expected);
+
+        // No members means all-zero counters of the requested size
+        assert_eq!(
+            lag_api.get_stats(&port_api, &[], &counter_ids).unwrap(),
+            vec![0, 0]
+        );
+    }
+
+    #[test]
+    fn test_member_list() {
+        let lag_api = mock::lag_api().with_switch_id(0x21000000000000);
+        let lag = lag_api.create(&[]).unwrap();
+        assert!(lag_api.get_member_list(lag).unwrap().is_empty());
+
+        let members: Vec<SaiOid> = (1..=20)
+            .map(|port| lag_api.create_member(lag, 0x1000000000000 | port).unwrap())
+            .collect();
+        // More members than the first read makes room for
+        assert_eq!(lag_api.get_member_list(lag).unwrap(), members);
+
+        lag_api.remove_lag_member(members[0]).unwrap();
+        assert_eq!(lag_api.get_member_list(lag).unwrap(), members[1..]);
+    }
+
+    #[test]
+    fn test_member_enabled() {
+        let lag_api = mock::lag_api().with_switch_id(0x21000000000000);
+        let lag = lag_api.create(&[]).unwrap();
+        let member = lag_api.create_member(lag, 0x1000000000001).unwrap();
+        mock::take_calls();
+
+        lag_api.set_member_enabled(member, false).unwrap();
+        lag_api.set_member_enabled(member, true).unwrap();
+
+        let attrs: Vec<(u32, u64)> = mock::take_calls()
+            .into_iter()
+            .inspect(|call| assert_eq!(call.oid, member))
+            .flat_map(|call| call.attrs)
+            .collect();
+        assert_eq!(
+            attrs,
+            vec![
+                (SAI_LAG_MEMBER_ATTR_EGRESS_DISABLE, 1),
+                (SAI_LAG_MEMBER_ATTR_INGRESS_DISABLE, 1),
+                (SAI_LAG_MEMBER_ATTR_EGRESS_DISABLE, 0),
+                (SAI_LAG_MEMBER_ATTR_INGRESS_DISABLE, 0),
+            ]
+        );
+    }
 }
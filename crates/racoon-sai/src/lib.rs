@@ -1,18 +1,52 @@
 pub mod adapter;
+pub mod asic;
+pub mod attr_names;
 pub mod bindings;
+pub mod bridge;
 pub mod constants;
+#[cfg(feature = "diag")]
+pub mod diag;
+pub mod enums;
 pub mod fdb;
+pub mod hash;
+pub mod hostif;
 pub mod lag;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod oid;
 pub mod port;
+pub mod refcount;
+pub mod router_interface;
+pub mod scheduler_group;
 pub mod status;
+pub mod stp;
 pub mod switch;
+pub mod tunnel;
 pub mod types;
 pub mod vlan;
 
-pub use adapter::SaiAdapter;
-pub use status::SaiStatus;
-pub use types::{SaiAttribute, SaiObjectType};
-pub use vlan::VlanApi;
+pub use adapter::{SaiAdapter, SaiApiVersion, SaiBootType, SaiLibraryInfo};
+pub use asic::AsicAttributeSerializer;
+pub use bridge::{BridgeApi, BridgePortTable};
+#[cfg(feature = "diag")]
+pub use diag::RawAttributes;
+pub use enums::{
+    AclStage, BridgePortType, FdbEntryType, FdbFlushEntryType, FecMode, HashField, HostifTrapType,
+    PacketAction, PortInterfaceType, PortLinkTrainingFailureStatus, PortLinkTrainingRxStatus,
+    SwitchOperStatus, TunnelMapType, VlanTaggingMode,
+};
+pub use hash::HashApi;
+pub use hostif::HostifApi;
+pub use oid::{NULL_OID, OidAllocator, SequentialOidAllocator, non_null};
+pub use refcount::RefCounter;
+pub use router_interface::RouterInterfaceApi;
+pub use scheduler_group::SchedulerGroupApi;
+pub use status::{Retryability, SaiStatus, classify, classify_error};
+pub use stp::StpApi;
+pub use switch::{AttrCapability, SwitchApi};
+pub use tunnel::{MAX_VNI, TunnelApi, VniVlanMapping};
+pub use types::{NamedAttribute, SaiAttribute, SaiObjectType};
+pub use vlan::{VlanApi, VlanInfo};
 
 // Re-export bindings for convenient access
 pub use bindings::*;
@@ -0,0 +1,1509 @@
+//! In-process mock SAI
+//!
+//! Api tables backed by Rust functions, so the wrappers and the sync agents
+//! can be exercised without a vendor library. Created objects get OIDs from a
+//! [`SequentialOidAllocator`] and every call is recorded in a per-thread log
+//! that tests inspect with [`take_calls`].
+
+use crate::bindings::*;
+use crate::bridge::BridgeApi;
+use crate::constants::{
+    SAI_STATUS_ATTR_NOT_SUPPORTED_0, SAI_STATUS_BUFFER_OVERFLOW, SAI_STATUS_FAILURE,
+    SAI_STATUS_INVALID_PARAMETER, SAI_STATUS_ITEM_ALREADY_EXISTS, SAI_STATUS_ITEM_NOT_FOUND,
+    SAI_STATUS_NOT_IMPLEMENTED, SAI_STATUS_NOT_SUPPORTED,
+};
+use crate::fdb::FdbApi;
+use crate::hash::HashApi;
+use crate::hostif::HostifApi;
+use crate::lag::LagApi;
+use crate::oid::{OidAllocator, SequentialOidAllocator};
+use crate::port::PortApi;
+use crate::router_interface::RouterInterfaceApi;
+use crate::scheduler_group::SchedulerGroupApi;
+use crate::stp::StpApi;
+use crate::switch::SwitchApi;
+use crate::tunnel::TunnelApi;
+use crate::types::SaiObjectType;
+use crate::vlan::VlanApi;
+use once_cell::sync::Lazy;
+use racoon_common::SaiOid;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::ffi::CStr;
+
+static OIDS: Lazy<SequentialOidAllocator> = Lazy::new(SequentialOidAllocator::new);
+
+/// Default STP instance the mock switch reports
+static DEFAULT_STP: Lazy<SaiOid> = Lazy::new(|| OIDS.allocate(SaiObjectType::Stp));
+
+/// Default .1Q bridge the mock switch reports; every bridge port is in it
+static DEFAULT_1Q_BRIDGE: Lazy<SaiOid> = Lazy::new(|| OIDS.allocate(SaiObjectType::Bridge));
+
+/// A call recorded by the mock SAI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    /// SAI function name, e.g. `create_vlan`
+    pub function: &'static str,
+    /// Object created or acted on
+    pub oid: SaiOid,
+    /// Switch passed to create calls, 0 for other calls
+    pub switch_id: SaiOid,
+    /// Attribute ids with their raw 64-bit values
+    pub attrs: Vec<(u32, u64)>,
+}
+
+/// Lanes, speed, mirror sessions and scheduler groups of a mock port
+#[derive(Debug, Clone, Default)]
+struct MockPort {
+    lanes: Vec<u32>,
+    speed: u32,
+    /// Sessions bound by mirror session attribute id
+    mirror_sessions: HashMap<u32, Vec<SaiOid>>,
+    scheduler_groups: Vec<SaiOid>,
+}
+
+thread_local! {
+    static CALLS: RefCell<Vec<MockCall>> = const { RefCell::new(Vec::new()) };
+    static PORTS: RefCell<HashMap<SaiOid, MockPort>> = RefCell::new(HashMap::new());
+    /// Port creates left to succeed before one fails
+    static PORT_CREATES_BEFORE_FAILURE: Cell<Option<usize>> = const { Cell::new(None) };
+    /// Netdev names of host interfaces by OID
+    static HOSTIFS: RefCell<HashMap<SaiOid, String>> = RefCell::new(HashMap::new());
+    /// VLAN id of VLANs by OID
+    static VLANS: RefCell<HashMap<SaiOid, u16>> = RefCell::new(HashMap::new());
+    /// Members of each VLAN by OID, in creation order
+    static VLAN_MEMBER_LISTS: RefCell<HashMap<SaiOid, Vec<SaiOid>>> = RefCell::new(HashMap::new());
+    /// Tagging mode of VLAN members by OID
+    static VLAN_MEMBERS: RefCell<HashMap<SaiOid, i32>> = RefCell::new(HashMap::new());
+    /// LAG members of each LAG by OID, in creation order
+    static LAG_MEMBERS: RefCell<HashMap<SaiOid, Vec<SaiOid>>> = RefCell::new(HashMap::new());
+    /// Native fields of each hash by OID
+    static HASHES: RefCell<HashMap<SaiOid, Vec<i32>>> = RefCell::new(HashMap::new());
+    /// FDB entries by VLAN/bridge OID and MAC
+    static FDB_ENTRIES: RefCell<HashSet<(SaiOid, [u8; 6])>> = RefCell::new(HashSet::new());
+    /// Port of each bridge port by OID
+    static BRIDGE_PORTS: RefCell<HashMap<SaiOid, SaiOid>> = RefCell::new(HashMap::new());
+}
+
+/// Take all calls recorded on the current thread
+pub fn take_calls() -> Vec<MockCall> {
+    CALLS.with(|calls| std::mem::take(&mut *calls.borrow_mut()))
+}
+
+fn record(function: &'static str, oid: SaiOid, attrs: Vec<(u32, u64)>) {
+    record_call(function, oid, 0, attrs);
+}
+
+fn record_call(function: &'static str, oid: SaiOid, switch_id: SaiOid, attrs: Vec<(u32, u64)>) {
+    CALLS.with(|calls| {
+        calls.borrow_mut().push(MockCall {
+            function,
+            oid,
+            switch_id,
+            attrs,
+        })
+    });
+}
+
+fn success() -> sai_status_t {
+    SAI_STATUS_SUCCESS as sai_status_t
+}
+
+/// # Safety
+///
+/// `attr_list` must point to `attr_count` initialized attributes.
+unsafe fn read_attrs(attr_count: u32, attr_list: *const sai_attribute_t) -> Vec<(u32, u64)> {
+    (0..attr_count as usize)
+        .map(|i| unsafe {
+            let attr = &*attr_list.add(i);
+            (attr.id, attr.value.u64_)
+        })
+        .collect()
+}
+
+/// # Safety
+///
+/// `object_id` must be writable and `attr_list` must hold `attr_count` attributes.
+unsafe fn create(
+    function: &'static str,
+    object_type: SaiObjectType,
+    object_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    let oid = OIDS.allocate(object_type);
+    unsafe {
+        *object_id = oid;
+        record_call(function, oid, switch_id, read_attrs(attr_count, attr_list));
+    }
+    success()
+}
+
+unsafe extern "C" fn create_vlan(
+    vlan_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        let status = create(
+            "create_vlan",
+            SaiObjectType::Vlan,
+            vlan_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        );
+        if let Some((_, id)) = read_attrs(attr_count, attr_list)
+            .into_iter()
+            .find(|(id, _)| *id == SAI_VLAN_ATTR_VLAN_ID)
+        {
+            VLANS.with(|vlans| vlans.borrow_mut().insert(*vlan_id, id as u16));
+        }
+        status
+    }
+}
+
+unsafe extern "C" fn remove_vlan(vlan_id: sai_object_id_t) -> sai_status_t {
+    record("remove_vlan", vlan_id, Vec::new());
+    VLANS.with(|vlans| vlans.borrow_mut().remove(&vlan_id));
+    success()
+}
+
+/// Copy `objects` into object list attribute `attr`
+///
+/// # Safety
+///
+/// `attr.value.objlist` must describe a writable buffer.
+unsafe fn fill_object_list(attr: &mut sai_attribute_t, objects: &[SaiOid]) -> sai_status_t {
+    unsafe {
+        let capacity = attr.value.objlist.count as usize;
+        attr.value.objlist.count = objects.len() as u32;
+        if capacity < objects.len() {
+            return SAI_STATUS_BUFFER_OVERFLOW;
+        }
+        std::ptr::copy_nonoverlapping(objects.as_ptr(), attr.value.objlist.list, objects.len());
+    }
+    success()
+}
+
+unsafe extern "C" fn set_vlan_attribute(
+    vlan_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe { record("set_vlan_attribute", vlan_id, read_attrs(1, attr)) };
+    success()
+}
+
+unsafe extern "C" fn get_vlan_attribute(
+    vlan_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    record("get_vlan_attribute", vlan_id, vec![(attr_count, 0)]);
+    let mut status = success();
+    for i in 0..attr_count as usize {
+        unsafe {
+            let attr = &mut *attr_list.add(i);
+            match attr.id {
+                SAI_VLAN_ATTR_VLAN_ID => {
+                    if let Some(id) = VLANS.with(|vlans| vlans.borrow().get(&vlan_id).copied()) {
+                        attr.value.u16_ = id;
+                    }
+                }
+                SAI_VLAN_ATTR_MEMBER_LIST => {
+                    let members = VLAN_MEMBER_LISTS
+                        .with(|lists| lists.borrow().get(&vlan_id).cloned())
+                        .unwrap_or_default();
+                    if fill_object_list(attr, &members) != success() {
+                        status = SAI_STATUS_BUFFER_OVERFLOW;
+                    }
+                }
+                _ => attr.value.u64_ = attr_value(vlan_id, attr.id),
+            }
+        }
+    }
+    status
+}
+
+/// VLAN member creates: a member without a bridge port is rejected
+unsafe extern "C" fn create_vlan_member(
+    member_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        let attrs = read_attrs(attr_count, attr_list);
+        if attrs.contains(&(SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID, 0)) {
+            return SAI_STATUS_INVALID_PARAMETER;
+        }
+        let status = create(
+            "create_vlan_member",
+            SaiObjectType::VlanMember,
+            member_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        );
+        let tagging_mode = attrs
+            .iter()
+            .find(|(id, _)| *id == SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE)
+            .map_or(SAI_VLAN_TAGGING_MODE_UNTAGGED as i32, |(_, value)| {
+                *value as i32
+            });
+        VLAN_MEMBERS.with(|members| members.borrow_mut().insert(*member_id, tagging_mode));
+        if let Some((_, vlan)) = attrs
+            .iter()
+            .find(|(id, _)| *id == SAI_VLAN_MEMBER_ATTR_VLAN_ID)
+        {
+            VLAN_MEMBER_LISTS.with(|lists| {
+                lists
+                    .borrow_mut()
+                    .entry(*vlan)
+                    .or_default()
+                    .push(*member_id)
+            });
+        }
+        status
+    }
+}
+
+/// Bulk VLAN member create, one `create_vlan_member` per object
+unsafe extern "C" fn create_vlan_members(
+    switch_id: sai_object_id_t,
+    object_count: u32,
+    attr_count: *const u32,
+    attr_list: *mut *const sai_attribute_t,
+    _mode: sai_bulk_op_error_mode_t,
+    object_id: *mut sai_object_id_t,
+    object_statuses: *mut sai_status_t,
+) -> sai_status_t {
+    record("create_vlan_members", 0, vec![(object_count, 0)]);
+    let mut status = success();
+    for i in 0..object_count as usize {
+        unsafe {
+            // Keep the per-object creates out of the call log
+            let calls = take_calls();
+            let object_status = create_vlan_member(
+                object_id.add(i),
+                switch_id,
+                *attr_count.add(i),
+                *attr_list.add(i),
+            );
+            CALLS.with(|log| *log.borrow_mut() = calls);
+            *object_statuses.add(i) = object_status;
+            if object_status != success() {
+                status = SAI_STATUS_FAILURE;
+            }
+        }
+    }
+    status
+}
+
+unsafe extern "C" fn remove_vlan_member(member_id: sai_object_id_t) -> sai_status_t {
+    record("remove_vlan_member", member_id, Vec::new());
+    VLAN_MEMBERS.with(|members| members.borrow_mut().remove(&member_id));
+    VLAN_MEMBER_LISTS.with(|lists| {
+        for members in lists.borrow_mut().values_mut() {
+            members.retain(|member| *member != member_id);
+        }
+    });
+    success()
+}
+
+unsafe extern "C" fn set_vlan_member_attribute(
+    member_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        record("set_vlan_member_attribute", member_id, read_attrs(1, attr));
+        if (*attr).id == SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE {
+            let tagging_mode = (*attr).value.s32;
+            VLAN_MEMBERS.with(|members| members.borrow_mut().insert(member_id, tagging_mode));
+        }
+    }
+    success()
+}
+
+unsafe extern "C" fn get_vlan_member_attribute(
+    member_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    record(
+        "get_vlan_member_attribute",
+        member_id,
+        vec![(attr_count, 0)],
+    );
+    let Some(tagging_mode) = VLAN_MEMBERS.with(|members| members.borrow().get(&member_id).copied())
+    else {
+        return SAI_STATUS_ITEM_NOT_FOUND;
+    };
+    for i in 0..attr_count as usize {
+        unsafe {
+            let attr = &mut *attr_list.add(i);
+            if attr.id == SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE {
+                attr.value.s32 = tagging_mode;
+            }
+        }
+    }
+    success()
+}
+
+/// VLAN api table backed by the mock
+pub fn vlan_api_table() -> sai_vlan_api_t {
+    sai_vlan_api_t {
+        create_vlan: Some(create_vlan),
+        remove_vlan: Some(remove_vlan),
+        set_vlan_attribute: Some(set_vlan_attribute),
+        get_vlan_attribute: Some(get_vlan_attribute),
+        create_vlan_member: Some(create_vlan_member),
+        remove_vlan_member: Some(remove_vlan_member),
+        create_vlan_members: Some(create_vlan_members),
+        set_vlan_member_attribute: Some(set_vlan_member_attribute),
+        get_vlan_member_attribute: Some(get_vlan_member_attribute),
+        get_vlan_stats: Some(get_vlan_stats),
+        clear_vlan_stats: Some(clear_vlan_stats),
+        ..Default::default()
+    }
+}
+
+/// `VlanApi` wired to the mock table and object enumeration
+///
+/// The table is leaked so the returned wrapper can outlive the caller.
+pub fn vlan_api() -> VlanApi {
+    VlanApi::new(Box::leak(Box::new(vlan_api_table()))).with_object_key_query(Some(get_object_key))
+}
+
+/// Object enumeration; only VLANs are tracked
+unsafe extern "C" fn get_object_key(
+    switch_id: sai_object_id_t,
+    object_type: sai_object_type_t,
+    object_count: *mut u32,
+    object_list: *mut sai_object_key_t,
+) -> sai_status_t {
+    record(
+        "sai_get_object_key",
+        switch_id,
+        vec![(object_type as u32, 0)],
+    );
+    if object_type != SAI_OBJECT_TYPE_VLAN {
+        return SAI_STATUS_NOT_IMPLEMENTED;
+    }
+
+    let mut vlans: Vec<SaiOid> = VLANS.with(|vlans| vlans.borrow().keys().copied().collect());
+    vlans.sort();
+    unsafe {
+        let capacity = *object_count as usize;
+        *object_count = vlans.len() as u32;
+        if capacity < vlans.len() {
+            return SAI_STATUS_BUFFER_OVERFLOW;
+        }
+        for (i, vlan_oid) in vlans.into_iter().enumerate() {
+            (*object_list.add(i)).key.object_id = vlan_oid;
+        }
+    }
+    success()
+}
+
+/// Raw value the mock reports for attribute `attr_id` of VLAN `oid`
+///
+/// Deterministic, so tests can compute the expected result.
+pub fn attr_value(oid: SaiOid, attr_id: u32) -> u64 {
+    (oid & 0xffff) << 16 | attr_id as u64
+}
+
+/// Counter value the mock reports for `counter_id` on `oid`
+///
+/// Deterministic, so tests can compute the expected result.
+pub fn stat_value(oid: SaiOid, counter_id: u32) -> u64 {
+    (oid & 0xffff) * 1000 + counter_id as u64
+}
+
+/// # Safety
+///
+/// `counter_ids` and `counters` must both hold `count` elements.
+unsafe fn get_stats(
+    function: &'static str,
+    oid: sai_object_id_t,
+    count: u32,
+    counter_ids: *const sai_stat_id_t,
+    counters: *mut u64,
+) -> sai_status_t {
+    record(function, oid, vec![(count, 0)]);
+    for i in 0..count as usize {
+        unsafe { *counters.add(i) = stat_value(oid, *counter_ids.add(i)) };
+    }
+    success()
+}
+
+unsafe extern "C" fn get_vlan_stats(
+    vlan_id: sai_object_id_t,
+    count: u32,
+    counter_ids: *const sai_stat_id_t,
+    counters: *mut u64,
+) -> sai_status_t {
+    unsafe { get_stats("get_vlan_stats", vlan_id, count, counter_ids, counters) }
+}
+
+unsafe extern "C" fn clear_vlan_stats(
+    vlan_id: sai_object_id_t,
+    count: u32,
+    _counter_ids: *const sai_stat_id_t,
+) -> sai_status_t {
+    record("clear_vlan_stats", vlan_id, vec![(count, 0)]);
+    success()
+}
+
+/// Seed a port as if it had been discovered at switch init
+pub fn add_port(lanes: &[u32], speed: u32) -> SaiOid {
+    let oid = OIDS.allocate(SaiObjectType::Port);
+    PORTS.with(|ports| {
+        ports.borrow_mut().insert(
+            oid,
+            MockPort {
+                lanes: lanes.to_vec(),
+                speed,
+                ..Default::default()
+            },
+        )
+    });
+    oid
+}
+
+/// Fail the port create after the next `count` that succeed
+pub fn fail_port_create_after(count: usize) {
+    PORT_CREATES_BEFORE_FAILURE.with(|left| left.set(Some(count)));
+}
+
+unsafe extern "C" fn create_port(
+    port_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    let fail = PORT_CREATES_BEFORE_FAILURE.with(|left| match left.get() {
+        Some(0) => {
+            left.set(None);
+            true
+        }
+        Some(count) => {
+            left.set(Some(count - 1));
+            false
+        }
+        None => false,
+    });
+    if fail {
+        return SAI_STATUS_FAILURE;
+    }
+
+    let mut port = MockPort::default();
+    for i in 0..attr_count as usize {
+        let attr = unsafe { &*attr_list.add(i) };
+        match attr.id {
+            SAI_PORT_ATTR_HW_LANE_LIST => unsafe {
+                let list = attr.value.u32list;
+                port.lanes = std::slice::from_raw_parts(list.list, list.count as usize).to_vec();
+            },
+            SAI_PORT_ATTR_SPEED => port.speed = unsafe { attr.value.u32_ },
+            _ => {}
+        }
+    }
+
+    let status = unsafe {
+        create(
+            "create_port",
+            SaiObjectType::Port,
+            port_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    };
+    let oid = unsafe { *port_id };
+    PORTS.with(|ports| ports.borrow_mut().insert(oid, port));
+    status
+}
+
+unsafe extern "C" fn remove_port(port_id: sai_object_id_t) -> sai_status_t {
+    record("remove_port", port_id, Vec::new());
+    PORTS.with(|ports| ports.borrow_mut().remove(&port_id));
+    success()
+}
+
+unsafe extern "C" fn get_port_attribute(
+    port_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    record("get_port_attribute", port_id, vec![(attr_count, 0)]);
+    let Some(port) = PORTS.with(|ports| ports.borrow().get(&port_id).cloned()) else {
+        return SAI_STATUS_ITEM_NOT_FOUND;
+    };
+
+    for i in 0..attr_count as usize {
+        let attr = unsafe { &mut *attr_list.add(i) };
+        match attr.id {
+            SAI_PORT_ATTR_HW_LANE_LIST => unsafe {
+                let capacity = attr.value.u32list.count as usize;
+                attr.value.u32list.count = port.lanes.len() as u32;
+                if capacity < port.lanes.len() {
+                    return SAI_STATUS_BUFFER_OVERFLOW;
+                }
+                std::ptr::copy_nonoverlapping(
+                    port.lanes.as_ptr(),
+                    attr.value.u32list.list,
+                    port.lanes.len(),
+                );
+            },
+            SAI_PORT_ATTR_SPEED => attr.value.u32_ = port.speed,
+            SAI_PORT_ATTR_QOS_NUMBER_OF_SCHEDULER_GROUPS => {
+                attr.value.u32_ = port.scheduler_groups.len() as u32
+            }
+            SAI_PORT_ATTR_QOS_SCHEDULER_GROUP_LIST => unsafe {
+                let groups = &port.scheduler_groups;
+                let capacity = attr.value.objlist.count as usize;
+                attr.value.objlist.count = groups.len() as u32;
+                if capacity < groups.len() {
+                    return SAI_STATUS_BUFFER_OVERFLOW;
+                }
+                std::ptr::copy_nonoverlapping(
+                    groups.as_ptr(),
+                    attr.value.objlist.list,
+                    groups.len(),
+                );
+            },
+            // Link training always completes
+            SAI_PORT_ATTR_LINK_TRAINING_RX_STATUS => {
+                attr.value.s32 = SAI_PORT_LINK_TRAINING_RX_STATUS_TRAINED as i32
+            }
+            SAI_PORT_ATTR_LINK_TRAINING_FAILURE_STATUS => {
+                attr.value.s32 = SAI_PORT_LINK_TRAINING_FAILURE_STATUS_NO_ERROR as i32
+            }
+            // Every lane detects a signal
+            SAI_PORT_ATTR_RX_SIGNAL_DETECT => unsafe {
+                let list = &mut attr.value.portlanelatchstatuslist;
+                let capacity = list.count as usize;
+                list.count = port.lanes.len() as u32;
+                if capacity < port.lanes.len() {
+                    return SAI_STATUS_BUFFER_OVERFLOW;
+                }
+                for (j, lane) in port.lanes.iter().enumerate() {
+                    let status = &mut *list.list.add(j);
+                    status.lane = *lane;
+                    status.value.current_status = true;
+                }
+            },
+            SAI_PORT_ATTR_RX_LOCK_STATUS => {
+                return SAI_STATUS_ATTR_NOT_SUPPORTED_0 - i as sai_status_t;
+            }
+            _ => {}
+        }
+    }
+    success()
+}
+
+unsafe extern "C" fn set_port_attribute(
+    port_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    let attr = unsafe { &*attr };
+    if matches!(
+        attr.id,
+        SAI_PORT_ATTR_INGRESS_MIRROR_SESSION | SAI_PORT_ATTR_EGRESS_MIRROR_SESSION
+    ) {
+        let sessions = unsafe {
+            let list = attr.value.objlist;
+            if list.count == 0 {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(list.list, list.count as usize).to_vec()
+            }
+        };
+        PORTS.with(|ports| {
+            ports
+                .borrow_mut()
+                .entry(port_id)
+                .or_default()
+                .mirror_sessions
+                .insert(attr.id, sessions)
+        });
+    }
+    unsafe { record("set_port_attribute", port_id, read_attrs(1, attr)) };
+    success()
+}
+
+/// Mirror sessions bound to a mock port by `attr_id`
+pub fn mirror_sessions(port_id: SaiOid, attr_id: u32) -> Vec<SaiOid> {
+    PORTS.with(|ports| {
+        ports
+            .borrow()
+            .get(&port_id)
+            .and_then(|port| port.mirror_sessions.get(&attr_id))
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+unsafe extern "C" fn get_port_stats(
+    port_id: sai_object_id_t,
+    count: u32,
+    counter_ids: *const sai_stat_id_t,
+    counters: *mut u64,
+) -> sai_status_t {
+    unsafe { get_stats("get_port_stats", port_id, count, counter_ids, counters) }
+}
+
+/// Seed `count` scheduler groups on a mock port, as switch init would
+pub fn add_scheduler_groups(port_id: SaiOid, count: usize) -> Vec<SaiOid> {
+    let groups: Vec<SaiOid> = (0..count)
+        .map(|_| OIDS.allocate(SaiObjectType::SchedulerGroup))
+        .collect();
+    PORTS.with(|ports| {
+        ports
+            .borrow_mut()
+            .entry(port_id)
+            .or_default()
+            .scheduler_groups
+            .extend(&groups)
+    });
+    groups
+}
+
+/// Port api table backed by the mock
+pub fn port_api_table() -> sai_port_api_t {
+    sai_port_api_t {
+        create_port: Some(create_port),
+        remove_port: Some(remove_port),
+        set_port_attribute: Some(set_port_attribute),
+        get_port_attribute: Some(get_port_attribute),
+        get_port_stats: Some(get_port_stats),
+        ..Default::default()
+    }
+}
+
+/// `PortApi` wired to the mock table
+pub fn port_api() -> PortApi {
+    PortApi::new(Box::leak(Box::new(port_api_table())))
+}
+
+unsafe extern "C" fn create_bridge_port(
+    bridge_port_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    let status = unsafe {
+        create(
+            "create_bridge_port",
+            SaiObjectType::BridgePort,
+            bridge_port_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    };
+    if status == success() {
+        let port_id = unsafe { read_attrs(attr_count, attr_list) }
+            .into_iter()
+            .find_map(|(id, value)| (id == SAI_BRIDGE_PORT_ATTR_PORT_ID).then_some(value))
+            .unwrap_or_default();
+        let oid = unsafe { *bridge_port_id };
+        BRIDGE_PORTS.with(|bridge_ports| bridge_ports.borrow_mut().insert(oid, port_id));
+    }
+    status
+}
+
+unsafe extern "C" fn remove_bridge_port(bridge_port_id: sai_object_id_t) -> sai_status_t {
+    record("remove_bridge_port", bridge_port_id, Vec::new());
+    BRIDGE_PORTS.with(|bridge_ports| bridge_ports.borrow_mut().remove(&bridge_port_id));
+    success()
+}
+
+/// Seed a bridge port of `port_id` as if SAI had created it with the switch
+pub fn add_bridge_port(port_id: SaiOid) -> SaiOid {
+    let oid = OIDS.allocate(SaiObjectType::BridgePort);
+    BRIDGE_PORTS.with(|bridge_ports| bridge_ports.borrow_mut().insert(oid, port_id));
+    oid
+}
+
+/// Bridge reads: the default .1Q bridge lists every bridge port
+unsafe extern "C" fn get_bridge_attribute(
+    bridge_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    record("get_bridge_attribute", bridge_id, vec![(attr_count, 0)]);
+    if bridge_id != *DEFAULT_1Q_BRIDGE {
+        return SAI_STATUS_ITEM_NOT_FOUND;
+    }
+
+    let attrs = unsafe { std::slice::from_raw_parts_mut(attr_list, attr_count as usize) };
+    for attr in attrs {
+        if attr.id != SAI_BRIDGE_ATTR_PORT_LIST {
+            return SAI_STATUS_NOT_SUPPORTED;
+        }
+        let mut bridge_ports: Vec<SaiOid> =
+            BRIDGE_PORTS.with(|bridge_ports| bridge_ports.borrow().keys().copied().collect());
+        bridge_ports.sort();
+        let status = unsafe { fill_object_list(attr, &bridge_ports) };
+        if status != success() {
+            return status;
+        }
+    }
+    success()
+}
+
+/// Bridge port reads: only the bridged port
+unsafe extern "C" fn get_bridge_port_attribute(
+    bridge_port_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    record(
+        "get_bridge_port_attribute",
+        bridge_port_id,
+        vec![(attr_count, 0)],
+    );
+    let Some(port_id) =
+        BRIDGE_PORTS.with(|bridge_ports| bridge_ports.borrow().get(&bridge_port_id).copied())
+    else {
+        return SAI_STATUS_ITEM_NOT_FOUND;
+    };
+
+    let attrs = unsafe { std::slice::from_raw_parts_mut(attr_list, attr_count as usize) };
+    for attr in attrs {
+        if attr.id != SAI_BRIDGE_PORT_ATTR_PORT_ID {
+            return SAI_STATUS_NOT_SUPPORTED;
+        }
+        attr.value.oid = port_id;
+    }
+    success()
+}
+
+/// Bridge api table backed by the mock
+pub fn bridge_api_table() -> sai_bridge_api_t {
+    sai_bridge_api_t {
+        create_bridge_port: Some(create_bridge_port),
+        remove_bridge_port: Some(remove_bridge_port),
+        get_bridge_attribute: Some(get_bridge_attribute),
+        get_bridge_port_attribute: Some(get_bridge_port_attribute),
+        ..Default::default()
+    }
+}
+
+/// `BridgeApi` wired to the mock table
+pub fn bridge_api() -> BridgeApi {
+    BridgeApi::new(Box::leak(Box::new(bridge_api_table())))
+}
+
+unsafe extern "C" fn query_attribute_capability(
+    switch_id: sai_object_id_t,
+    object_type: sai_object_type_t,
+    attr_id: sai_attr_id_t,
+    attr_capability: *mut sai_attr_capability_t,
+) -> sai_status_t {
+    record(
+        "sai_query_attribute_capability",
+        switch_id,
+        vec![(attr_id, object_type as u64)],
+    );
+
+    // VLAN ids are fixed at creation; everything else is fully supported
+    let create_only = object_type == SAI_OBJECT_TYPE_VLAN && attr_id == SAI_VLAN_ATTR_VLAN_ID;
+    unsafe {
+        *attr_capability = sai_attr_capability_t {
+            create_implemented: true,
+            set_implemented: !create_only,
+            get_implemented: true,
+        };
+    }
+    success()
+}
+
+unsafe extern "C" fn create_hostif_trap_group(
+    group_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_hostif_trap_group",
+            SaiObjectType::HostifTrapGroup,
+            group_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn create_hostif_trap(
+    trap_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_hostif_trap",
+            SaiObjectType::HostifTrap,
+            trap_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+/// Netdev host interfaces; a name can only be created once
+unsafe extern "C" fn create_hostif(
+    hostif_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    let attrs = unsafe { std::slice::from_raw_parts(attr_list, attr_count as usize) };
+    let name = attrs
+        .iter()
+        .find(|attr| attr.id == SAI_HOSTIF_ATTR_NAME)
+        .map(|attr| unsafe { CStr::from_ptr(attr.value.chardata.as_ptr()) })
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if HOSTIFS.with(|hostifs| hostifs.borrow().values().any(|existing| *existing == name)) {
+        return SAI_STATUS_ITEM_ALREADY_EXISTS;
+    }
+
+    let status = unsafe {
+        create(
+            "create_hostif",
+            SaiObjectType::Hostif,
+            hostif_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    };
+    HOSTIFS.with(|hostifs| hostifs.borrow_mut().insert(unsafe { *hostif_id }, name));
+    status
+}
+
+unsafe extern "C" fn remove_hostif(hostif_id: sai_object_id_t) -> sai_status_t {
+    record("remove_hostif", hostif_id, Vec::new());
+    HOSTIFS.with(|hostifs| hostifs.borrow_mut().remove(&hostif_id));
+    success()
+}
+
+/// Host interface api table backed by the mock
+pub fn hostif_api_table() -> sai_hostif_api_t {
+    sai_hostif_api_t {
+        create_hostif: Some(create_hostif),
+        remove_hostif: Some(remove_hostif),
+        create_hostif_trap_group: Some(create_hostif_trap_group),
+        create_hostif_trap: Some(create_hostif_trap),
+        ..Default::default()
+    }
+}
+
+/// `HostifApi` wired to the mock table
+pub fn hostif_api() -> HostifApi {
+    HostifApi::new(Box::leak(Box::new(hostif_api_table())))
+}
+
+unsafe extern "C" fn create_lag(
+    lag_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        let status = create(
+            "create_lag",
+            SaiObjectType::Lag,
+            lag_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        );
+        LAG_MEMBERS.with(|lags| lags.borrow_mut().insert(*lag_id, Vec::new()));
+        status
+    }
+}
+
+unsafe extern "C" fn remove_lag(lag_id: sai_object_id_t) -> sai_status_t {
+    record("remove_lag", lag_id, Vec::new());
+    LAG_MEMBERS.with(|lags| lags.borrow_mut().remove(&lag_id));
+    success()
+}
+
+/// LAG reads: `SAI_LAG_ATTR_PORT_LIST` lists the members created so far
+unsafe extern "C" fn get_lag_attribute(
+    lag_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    record("get_lag_attribute", lag_id, vec![(attr_count, 0)]);
+    let Some(members) = LAG_MEMBERS.with(|lags| lags.borrow().get(&lag_id).cloned()) else {
+        return SAI_STATUS_ITEM_NOT_FOUND;
+    };
+
+    for i in 0..attr_count as usize {
+        let attr = unsafe { &mut *attr_list.add(i) };
+        if attr.id == SAI_LAG_ATTR_PORT_LIST {
+            let status = unsafe { fill_object_list(attr, &members) };
+            if status != success() {
+                return status;
+            }
+        }
+    }
+    success()
+}
+
+unsafe extern "C" fn create_lag_member(
+    member_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        let status = create(
+            "create_lag_member",
+            SaiObjectType::LagMember,
+            member_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        );
+        let lag_id = read_attrs(attr_count, attr_list)
+            .into_iter()
+            .find(|(id, _)| *id == SAI_LAG_MEMBER_ATTR_LAG_ID)
+            .map(|(_, lag_id)| lag_id);
+        if let Some(lag_id) = lag_id {
+            LAG_MEMBERS.with(|lags| {
+                lags.borrow_mut()
+                    .entry(lag_id)
+                    .or_default()
+                    .push(*member_id)
+            });
+        }
+        status
+    }
+}
+
+unsafe extern "C" fn remove_lag_member(member_id: sai_object_id_t) -> sai_status_t {
+    record("remove_lag_member", member_id, Vec::new());
+    LAG_MEMBERS.with(|lags| {
+        for members in lags.borrow_mut().values_mut() {
+            members.retain(|member| *member != member_id);
+        }
+    });
+    success()
+}
+
+unsafe extern "C" fn set_lag_member_attribute(
+    member_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe { record("set_lag_member_attribute", member_id, read_attrs(1, attr)) };
+    success()
+}
+
+/// LAG api table backed by the mock
+pub fn lag_api_table() -> sai_lag_api_t {
+    sai_lag_api_t {
+        create_lag: Some(create_lag),
+        remove_lag: Some(remove_lag),
+        get_lag_attribute: Some(get_lag_attribute),
+        create_lag_member: Some(create_lag_member),
+        remove_lag_member: Some(remove_lag_member),
+        set_lag_member_attribute: Some(set_lag_member_attribute),
+        ..Default::default()
+    }
+}
+
+/// `LagApi` wired to the mock table
+pub fn lag_api() -> LagApi {
+    LagApi::new(Box::leak(Box::new(lag_api_table())))
+}
+
+/// Whether an FDB entry for `mac` exists on `bv_id`
+pub fn has_fdb_entry(bv_id: SaiOid, mac: [u8; 6]) -> bool {
+    FDB_ENTRIES.with(|entries| entries.borrow().contains(&(bv_id, mac)))
+}
+
+unsafe extern "C" fn create_fdb_entry(
+    fdb_entry: *const sai_fdb_entry_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    let entry = unsafe { &*fdb_entry };
+    let key = (entry.bv_id, entry.mac_address);
+    if !FDB_ENTRIES.with(|entries| entries.borrow_mut().insert(key)) {
+        return SAI_STATUS_ITEM_ALREADY_EXISTS;
+    }
+    unsafe {
+        record_call(
+            "create_fdb_entry",
+            entry.bv_id,
+            entry.switch_id,
+            read_attrs(attr_count, attr_list),
+        )
+    };
+    success()
+}
+
+unsafe extern "C" fn remove_fdb_entry(fdb_entry: *const sai_fdb_entry_t) -> sai_status_t {
+    let entry = unsafe { &*fdb_entry };
+    let key = (entry.bv_id, entry.mac_address);
+    if !FDB_ENTRIES.with(|entries| entries.borrow_mut().remove(&key)) {
+        return SAI_STATUS_ITEM_NOT_FOUND;
+    }
+    record("remove_fdb_entry", entry.bv_id, Vec::new());
+    success()
+}
+
+/// FDB api table backed by the mock
+pub fn fdb_api_table() -> sai_fdb_api_t {
+    sai_fdb_api_t {
+        create_fdb_entry: Some(create_fdb_entry),
+        remove_fdb_entry: Some(remove_fdb_entry),
+        ..Default::default()
+    }
+}
+
+/// `FdbApi` wired to the mock table
+pub fn fdb_api() -> FdbApi {
+    FdbApi::new(Box::leak(Box::new(fdb_api_table())))
+}
+
+unsafe extern "C" fn create_stp(
+    stp_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_stp",
+            SaiObjectType::Stp,
+            stp_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn remove_stp(stp_id: sai_object_id_t) -> sai_status_t {
+    record("remove_stp", stp_id, Vec::new());
+    success()
+}
+
+/// STP api table backed by the mock
+pub fn stp_api_table() -> sai_stp_api_t {
+    sai_stp_api_t {
+        create_stp: Some(create_stp),
+        remove_stp: Some(remove_stp),
+        ..Default::default()
+    }
+}
+
+/// `StpApi` wired to the mock table
+pub fn stp_api() -> StpApi {
+    StpApi::new(Box::leak(Box::new(stp_api_table())))
+}
+
+unsafe extern "C" fn set_scheduler_group_attribute(
+    group_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        record(
+            "set_scheduler_group_attribute",
+            group_id,
+            read_attrs(1, attr),
+        )
+    };
+    success()
+}
+
+unsafe extern "C" fn set_queue_attribute(
+    queue_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe { record("set_queue_attribute", queue_id, read_attrs(1, attr)) };
+    success()
+}
+
+/// Scheduler group api table backed by the mock
+pub fn scheduler_group_api_table() -> sai_scheduler_group_api_t {
+    sai_scheduler_group_api_t {
+        set_scheduler_group_attribute: Some(set_scheduler_group_attribute),
+        ..Default::default()
+    }
+}
+
+/// Queue api table backed by the mock
+pub fn queue_api_table() -> sai_queue_api_t {
+    sai_queue_api_t {
+        set_queue_attribute: Some(set_queue_attribute),
+        ..Default::default()
+    }
+}
+
+/// `SchedulerGroupApi` wired to the mock tables
+pub fn scheduler_group_api() -> SchedulerGroupApi {
+    SchedulerGroupApi::new(
+        Box::leak(Box::new(scheduler_group_api_table())),
+        Box::leak(Box::new(queue_api_table())),
+    )
+}
+
+/// # Safety
+///
+/// `attr_list` must hold `attr_count` attributes.
+unsafe fn store_hash_fields(hash_id: SaiOid, attr_count: u32, attr_list: *const sai_attribute_t) {
+    for i in 0..attr_count as usize {
+        let attr = unsafe { &*attr_list.add(i) };
+        if attr.id == SAI_HASH_ATTR_NATIVE_HASH_FIELD_LIST {
+            let fields = unsafe {
+                let list = attr.value.s32list;
+                std::slice::from_raw_parts(list.list, list.count as usize).to_vec()
+            };
+            HASHES.with(|hashes| hashes.borrow_mut().insert(hash_id, fields));
+        }
+    }
+}
+
+unsafe extern "C" fn create_hash(
+    hash_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        let status = create(
+            "create_hash",
+            SaiObjectType::Hash,
+            hash_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        );
+        store_hash_fields(*hash_id, attr_count, attr_list);
+        status
+    }
+}
+
+unsafe extern "C" fn remove_hash(hash_id: sai_object_id_t) -> sai_status_t {
+    record("remove_hash", hash_id, Vec::new());
+    HASHES.with(|hashes| hashes.borrow_mut().remove(&hash_id));
+    success()
+}
+
+unsafe extern "C" fn set_hash_attribute(
+    hash_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        record("set_hash_attribute", hash_id, read_attrs(1, attr));
+        store_hash_fields(hash_id, 1, attr);
+    }
+    success()
+}
+
+/// Native fields of a mock hash, as SAI values
+pub fn hash_fields(hash_id: SaiOid) -> Vec<i32> {
+    HASHES.with(|hashes| hashes.borrow().get(&hash_id).cloned().unwrap_or_default())
+}
+
+/// Hash api table backed by the mock
+pub fn hash_api_table() -> sai_hash_api_t {
+    sai_hash_api_t {
+        create_hash: Some(create_hash),
+        remove_hash: Some(remove_hash),
+        set_hash_attribute: Some(set_hash_attribute),
+        ..Default::default()
+    }
+}
+
+/// `HashApi` wired to the mock table
+pub fn hash_api() -> HashApi {
+    HashApi::new(Box::leak(Box::new(hash_api_table())))
+}
+
+unsafe extern "C" fn create_router_interface(
+    rif_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_router_interface",
+            SaiObjectType::RouterInterface,
+            rif_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn remove_router_interface(rif_id: sai_object_id_t) -> sai_status_t {
+    record("remove_router_interface", rif_id, Vec::new());
+    success()
+}
+
+unsafe extern "C" fn set_router_interface_attribute(
+    rif_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        record(
+            "set_router_interface_attribute",
+            rif_id,
+            read_attrs(1, attr),
+        )
+    };
+    success()
+}
+
+/// Router interface api table backed by the mock
+pub fn router_interface_api_table() -> sai_router_interface_api_t {
+    sai_router_interface_api_t {
+        create_router_interface: Some(create_router_interface),
+        remove_router_interface: Some(remove_router_interface),
+        set_router_interface_attribute: Some(set_router_interface_attribute),
+        ..Default::default()
+    }
+}
+
+/// `RouterInterfaceApi` wired to the mock table
+pub fn router_interface_api() -> RouterInterfaceApi {
+    RouterInterfaceApi::new(Box::leak(Box::new(router_interface_api_table())))
+}
+
+unsafe extern "C" fn create_tunnel_map(
+    tunnel_map_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_tunnel_map",
+            SaiObjectType::TunnelMap,
+            tunnel_map_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn remove_tunnel_map(tunnel_map_id: sai_object_id_t) -> sai_status_t {
+    record("remove_tunnel_map", tunnel_map_id, Vec::new());
+    success()
+}
+
+unsafe extern "C" fn create_tunnel_map_entry(
+    tunnel_map_entry_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_tunnel_map_entry",
+            SaiObjectType::TunnelMapEntry,
+            tunnel_map_entry_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn remove_tunnel_map_entry(tunnel_map_entry_id: sai_object_id_t) -> sai_status_t {
+    record("remove_tunnel_map_entry", tunnel_map_entry_id, Vec::new());
+    success()
+}
+
+unsafe extern "C" fn create_tunnel(
+    tunnel_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_tunnel",
+            SaiObjectType::Tunnel,
+            tunnel_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn remove_tunnel(tunnel_id: sai_object_id_t) -> sai_status_t {
+    record("remove_tunnel", tunnel_id, Vec::new());
+    success()
+}
+
+unsafe extern "C" fn create_tunnel_term_table_entry(
+    tunnel_term_table_entry_id: *mut sai_object_id_t,
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe {
+        create(
+            "create_tunnel_term_table_entry",
+            SaiObjectType::TunnelTermTableEntry,
+            tunnel_term_table_entry_id,
+            switch_id,
+            attr_count,
+            attr_list,
+        )
+    }
+}
+
+unsafe extern "C" fn remove_tunnel_term_table_entry(
+    tunnel_term_table_entry_id: sai_object_id_t,
+) -> sai_status_t {
+    record(
+        "remove_tunnel_term_table_entry",
+        tunnel_term_table_entry_id,
+        Vec::new(),
+    );
+    success()
+}
+
+/// Tunnel api table backed by the mock
+pub fn tunnel_api_table() -> sai_tunnel_api_t {
+    sai_tunnel_api_t {
+        create_tunnel_map: Some(create_tunnel_map),
+        remove_tunnel_map: Some(remove_tunnel_map),
+        create_tunnel_map_entry: Some(create_tunnel_map_entry),
+        remove_tunnel_map_entry: Some(remove_tunnel_map_entry),
+        create_tunnel: Some(create_tunnel),
+        remove_tunnel: Some(remove_tunnel),
+        create_tunnel_term_table_entry: Some(create_tunnel_term_table_entry),
+        remove_tunnel_term_table_entry: Some(remove_tunnel_term_table_entry),
+        ..Default::default()
+    }
+}
+
+/// `TunnelApi` wired to the mock table
+pub fn tunnel_api() -> TunnelApi {
+    TunnelApi::new(Box::leak(Box::new(tunnel_api_table())))
+}
+
+unsafe extern "C" fn set_switch_attribute(
+    switch_id: sai_object_id_t,
+    attr: *const sai_attribute_t,
+) -> sai_status_t {
+    unsafe { record("set_switch_attribute", switch_id, read_attrs(1, attr)) };
+    success()
+}
+
+/// Switch reads: the switch is always oper up, has one default STP instance
+/// and .1Q bridge, and lists the seeded ports
+unsafe extern "C" fn get_switch_attribute(
+    switch_id: sai_object_id_t,
+    attr_count: u32,
+    attr_list: *mut sai_attribute_t,
+) -> sai_status_t {
+    let attrs = unsafe { std::slice::from_raw_parts_mut(attr_list, attr_count as usize) };
+    record(
+        "get_switch_attribute",
+        switch_id,
+        attrs.iter().map(|attr| (attr.id, 0)).collect(),
+    );
+
+    for attr in attrs {
+        match attr.id {
+            SAI_SWITCH_ATTR_OPER_STATUS => attr.value.s32 = SAI_SWITCH_OPER_STATUS_UP as i32,
+            SAI_SWITCH_ATTR_DEFAULT_STP_INST_ID => attr.value.oid = *DEFAULT_STP,
+            SAI_SWITCH_ATTR_DEFAULT_1Q_BRIDGE_ID => attr.value.oid = *DEFAULT_1Q_BRIDGE,
+            SAI_SWITCH_ATTR_PORT_LIST => {
+                let mut ports: Vec<SaiOid> =
+                    PORTS.with(|ports| ports.borrow().keys().copied().collect());
+                ports.sort();
+                let status = unsafe { fill_object_list(attr, &ports) };
+                if status != success() {
+                    return status;
+                }
+            }
+            _ => return SAI_STATUS_NOT_SUPPORTED,
+        }
+    }
+    success()
+}
+
+/// Switch api table backed by the mock
+pub fn switch_api_table() -> sai_switch_api_t {
+    sai_switch_api_t {
+        set_switch_attribute: Some(set_switch_attribute),
+        get_switch_attribute: Some(get_switch_attribute),
+        ..Default::default()
+    }
+}
+
+/// `SwitchApi` with the mock table and capability query
+pub fn switch_api() -> SwitchApi {
+    SwitchApi::new(Box::leak(Box::new(switch_api_table())))
+        .with_capability_query(Some(query_attribute_capability))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_common::VlanId;
+
+    #[test]
+    fn test_mock_vlan_create_uses_typed_oids() {
+        let api = vlan_api();
+        let vlan_oid = api.create_vlan(0, VlanId::new(100).unwrap()).unwrap();
+
+        assert_eq!(SaiObjectType::from_oid(vlan_oid), Some(SaiObjectType::Vlan));
+
+        let calls = take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "create_vlan");
+        assert_eq!(calls[0].oid, vlan_oid);
+        assert_eq!(calls[0].attrs, vec![(SAI_VLAN_ATTR_VLAN_ID, 100)]);
+    }
+
+    #[test]
+    fn test_mock_vlan_stats() {
+        let api = vlan_api();
+        let vlan_oid = api.create_vlan(0, VlanId::new(200).unwrap()).unwrap();
+        take_calls();
+
+        let counter_ids = [
+            SAI_VLAN_STAT_IN_OCTETS,
+            SAI_VLAN_STAT_IN_PACKETS,
+            SAI_VLAN_STAT_OUT_OCTETS,
+        ];
+        let counters = api.get_stats(vlan_oid, &counter_ids).unwrap();
+        assert_eq!(counters.len(), counter_ids.len());
+        for (value, id) in counters.iter().zip(counter_ids) {
+            assert_eq!(*value, stat_value(vlan_oid, id));
+        }
+
+        api.clear_stats(vlan_oid, &counter_ids).unwrap();
+        let functions: Vec<&str> = take_calls().iter().map(|c| c.function).collect();
+        assert_eq!(functions, vec!["get_vlan_stats", "clear_vlan_stats"]);
+
+        // A table without stats functions reports NOT_IMPLEMENTED
+        let bare = VlanApi::new(Box::leak(Box::new(sai_vlan_api_t::default())));
+        assert!(bare.get_stats(vlan_oid, &counter_ids).is_err());
+    }
+}
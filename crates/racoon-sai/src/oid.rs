@@ -0,0 +1,282 @@
+//! Synthetic SAI object ID allocation
+//!
+//! Real OIDs are handed out by the vendor SAI library. The mock SAI needs
+//! OIDs that look the same, so it uses an [`OidAllocator`] instead.
+//!
+//! syncd has no dry-run mode: it refuses to start without a vendor library,
+//! and the mock keeps its objects per thread for tests, so it can't stand in
+//! for one under syncd's multi-threaded runtime. A dry-run mode would take
+//! its OIDs from an allocator here.
+
+use crate::bindings::{
+    SAI_NULL_OBJECT_ID, sai_attribute_t, sai_object_id_t, sai_object_key_t, sai_object_type_t,
+    sai_status_t,
+};
+use crate::constants::{SAI_STATUS_BUFFER_OVERFLOW, SAI_STATUS_NOT_IMPLEMENTED};
+use crate::status::SaiStatus;
+use crate::types::{SaiAttribute, SaiObjectType};
+use racoon_common::{RacoonError, Result, SaiOid};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bit offset of the object type within an OID (sairedis layout)
+pub const OID_OBJECT_TYPE_SHIFT: u32 = 48;
+
+/// Mask for the per-type index portion of an OID
+pub const OID_INDEX_MASK: u64 = (1 << OID_OBJECT_TYPE_SHIFT) - 1;
+
+/// `SAI_NULL_OBJECT_ID` as a [`SaiOid`]: no object
+pub const NULL_OID: SaiOid = SAI_NULL_OBJECT_ID as SaiOid;
+
+/// Check the OID a successful create returned
+///
+/// A vendor SAI that reports success without writing the OID would leave
+/// it null; treating that as an object would corrupt later calls.
+pub fn non_null(oid: SaiOid, function: &str) -> Result<SaiOid> {
+    if oid == NULL_OID {
+        return Err(RacoonError::Sai(format!(
+            "{} returned success with a null object id",
+            function
+        )));
+    }
+    Ok(oid)
+}
+
+/// Objects an object list read makes room for before asking SAI for the size
+const INITIAL_LIST_CAPACITY: usize = 16;
+
+type GetFn =
+    Option<unsafe extern "C" fn(sai_object_id_t, u32, *mut sai_attribute_t) -> sai_status_t>;
+
+/// Read list attribute `attr_id` of `oid` through `get_fn`
+///
+/// `bind` points the attribute at the buffer and `count` reads back the
+/// number of entries SAI reported. SAI reports the required size when the
+/// buffer is too small; the read is then repeated with a buffer that large.
+/// The final status is returned with the list, so callers decide what an
+/// unsupported attribute means.
+pub(crate) fn get_list<T: Clone>(
+    get_fn: GetFn,
+    oid: SaiOid,
+    attr_id: u32,
+    capacity: usize,
+    fill: T,
+    bind: impl Fn(&mut sai_attribute_t, &mut [T]),
+    count: impl Fn(&sai_attribute_t) -> usize,
+) -> (SaiStatus, Vec<T>) {
+    let mut list = vec![fill.clone(); capacity];
+
+    loop {
+        let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
+        c_attr.id = attr_id;
+        bind(&mut c_attr, &mut list);
+
+        let status = match get_fn {
+            Some(get_fn) => unsafe { get_fn(oid, 1, &mut c_attr) },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        let reported = count(&c_attr);
+        if status == SAI_STATUS_BUFFER_OVERFLOW && reported > list.len() {
+            list.resize(reported, fill.clone());
+            continue;
+        }
+
+        let status = SaiStatus::from(status);
+        if status.is_success() {
+            list.truncate(reported);
+        }
+        return (status, list);
+    }
+}
+
+/// Read object list attribute `attr_id` of `oid` through `get_fn`
+pub(crate) fn get_object_list(get_fn: GetFn, oid: SaiOid, attr_id: u32) -> Result<Vec<SaiOid>> {
+    let (status, list) = get_list(
+        get_fn,
+        oid,
+        attr_id,
+        INITIAL_LIST_CAPACITY,
+        NULL_OID,
+        |c_attr, list| {
+            c_attr.value.objlist.count = list.len() as u32;
+            c_attr.value.objlist.list = list.as_mut_ptr();
+        },
+        |c_attr| unsafe { c_attr.value.objlist.count } as usize,
+    );
+    status.to_result()?;
+    Ok(list)
+}
+
+/// `sai_get_object_key`, exported by the SAI library itself rather than
+/// through an api table
+pub type SaiGetObjectKeyFn = unsafe extern "C" fn(
+    switch_id: sai_object_id_t,
+    object_type: sai_object_type_t,
+    object_count: *mut u32,
+    object_list: *mut sai_object_key_t,
+) -> sai_status_t;
+
+/// List the OIDs of every `object_type` object on `switch_id`
+///
+/// Like [`get_object_list`], the call is repeated with a larger buffer when
+/// SAI reports more objects than fit.
+pub(crate) fn get_object_keys(
+    get_fn: Option<SaiGetObjectKeyFn>,
+    switch_id: SaiOid,
+    object_type: SaiObjectType,
+) -> Result<Vec<SaiOid>> {
+    let mut keys: Vec<sai_object_key_t> =
+        vec![unsafe { std::mem::zeroed() }; INITIAL_LIST_CAPACITY];
+
+    loop {
+        let mut count = keys.len() as u32;
+        let status = match get_fn {
+            Some(get_fn) => unsafe {
+                get_fn(
+                    switch_id,
+                    object_type.to_sai(),
+                    &mut count,
+                    keys.as_mut_ptr(),
+                )
+            },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        if status == SAI_STATUS_BUFFER_OVERFLOW && count as usize > keys.len() {
+            keys.resize(count as usize, unsafe { std::mem::zeroed() });
+            continue;
+        }
+
+        SaiStatus::from(status).to_result()?;
+        keys.truncate(count as usize);
+        return Ok(keys
+            .iter()
+            .map(|key| unsafe { key.key.object_id })
+            .collect());
+    }
+}
+
+/// Read several attributes of `oid` in one `get_fn` call
+///
+/// Each of `attrs` names an attribute and, through its value, the type to
+/// decode it as; the values themselves are ignored. List attributes are
+/// read like [`get_object_list`] does, repeating the call when SAI reports
+/// a list larger than its buffer.
+pub(crate) fn get_attributes(
+    get_fn: GetFn,
+    oid: SaiOid,
+    attrs: &[SaiAttribute],
+) -> Result<Vec<SaiAttribute>> {
+    let mut attrs = attrs.to_vec();
+    for attr in &mut attrs {
+        if attr.list_capacity().is_some() {
+            attr.resize_list(INITIAL_LIST_CAPACITY);
+        }
+    }
+
+    loop {
+        let mut c_attrs: Vec<sai_attribute_t> = attrs
+            .iter_mut()
+            .map(|attr| unsafe { attr.c_attribute_for_read() })
+            .collect();
+
+        let status = match get_fn {
+            Some(get_fn) => unsafe { get_fn(oid, c_attrs.len() as u32, c_attrs.as_mut_ptr()) },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        if status == SAI_STATUS_BUFFER_OVERFLOW {
+            let mut grown = false;
+            for (attr, c_attr) in attrs.iter_mut().zip(&c_attrs) {
+                let reported = unsafe { attr.reported_list_len(c_attr) };
+                if let (Some(reported), Some(capacity)) = (reported, attr.list_capacity())
+                    && reported > capacity
+                {
+                    attr.resize_list(reported);
+                    grown = true;
+                }
+            }
+            if grown {
+                continue;
+            }
+        }
+
+        SaiStatus::from(status).to_result()?;
+        for (attr, c_attr) in attrs.iter_mut().zip(&c_attrs) {
+            unsafe { attr.read_c_attribute(c_attr) };
+        }
+        return Ok(attrs);
+    }
+}
+
+/// Source of object IDs for objects not created by a vendor SAI library
+pub trait OidAllocator: Send + Sync {
+    /// Allocate a new OID for the given object type
+    fn allocate(&self, object_type: SaiObjectType) -> SaiOid;
+}
+
+/// Default allocator: encodes the object type and a running counter
+///
+/// Generated OIDs decode back to their type with [`SaiObjectType::from_oid`].
+#[derive(Debug)]
+pub struct SequentialOidAllocator {
+    next: AtomicU64,
+}
+
+impl SequentialOidAllocator {
+    pub fn new() -> Self {
+        Self {
+            next: AtomicU64::new(1),
+        }
+    }
+}
+
+impl Default for SequentialOidAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OidAllocator for SequentialOidAllocator {
+    fn allocate(&self, object_type: SaiObjectType) -> SaiOid {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) & OID_INDEX_MASK;
+        ((object_type.to_sai() as u64) << OID_OBJECT_TYPE_SHIFT) | index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocated_oids_decode_to_type() {
+        let allocator = SequentialOidAllocator::new();
+
+        let types = [
+            SaiObjectType::Vlan,
+            SaiObjectType::VlanMember,
+            SaiObjectType::Lag,
+            SaiObjectType::Port,
+            SaiObjectType::Vlan,
+        ];
+        let oids: Vec<SaiOid> = types.iter().map(|t| allocator.allocate(*t)).collect();
+
+        for (oid, object_type) in oids.iter().zip(types.iter()) {
+            assert_ne!(*oid, 0);
+            assert_eq!(SaiObjectType::from_oid(*oid), Some(*object_type));
+        }
+
+        // Two VLANs never share an OID
+        assert_ne!(oids[0], oids[4]);
+    }
+
+    #[test]
+    fn test_switch_oid_layout() {
+        // syncd's placeholder switch id uses the same layout
+        assert_eq!(
+            SaiObjectType::from_oid(0x21000000000000),
+            Some(SaiObjectType::Switch)
+        );
+        assert_eq!(SaiObjectType::from_oid(0), None);
+    }
+}
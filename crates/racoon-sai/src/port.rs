@@ -1,8 +1,84 @@
 use crate::bindings::*;
 use crate::constants::*;
+pub use crate::enums::{
+    FecMode, PortInterfaceType, PortLinkTrainingFailureStatus, PortLinkTrainingRxStatus,
+};
+use crate::oid::{NULL_OID, get_list, get_object_list, non_null};
 use crate::status::SaiStatus;
-use crate::types::SaiAttribute;
-use racoon_common::{Result, SaiOid};
+use crate::types::{SaiAttribute, SaiAttributeValue};
+use racoon_common::{PortAdminStatus, PortLinkConfig, PortName, RacoonError, Result, SaiOid};
+use std::collections::HashMap;
+use tracing::{instrument, warn};
+
+/// Lanes a lane list read makes room for before asking SAI for the count
+const LANE_LIST_CAPACITY: usize = 8;
+
+/// Link training state of a port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkTrainingStatus {
+    /// The SAI does not report link training for the port
+    Unknown,
+    NotTrained,
+    Trained,
+    /// Training gave up for the given reason
+    Failed(PortLinkTrainingFailureStatus),
+}
+
+impl LinkTrainingStatus {
+    /// Name written to STATE_DB
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkTrainingStatus::Unknown => "unknown",
+            LinkTrainingStatus::NotTrained => "not_trained",
+            LinkTrainingStatus::Trained => "trained",
+            LinkTrainingStatus::Failed(_) => "failed",
+        }
+    }
+
+    /// Why training failed, as written to STATE_DB
+    pub fn failure(&self) -> Option<&'static str> {
+        match self {
+            LinkTrainingStatus::Failed(failure) => Some(match failure {
+                PortLinkTrainingFailureStatus::NoError => "no_error",
+                PortLinkTrainingFailureStatus::FrameLockError => "frame_lock_error",
+                PortLinkTrainingFailureStatus::SnrLowerThreshold => "snr_lower_threshold",
+                PortLinkTrainingFailureStatus::TimeOut => "timeout",
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Receive state of a port's serdes lanes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaneStatus {
+    /// The SAI does not report this state
+    Unknown,
+    /// Every lane is up
+    Up,
+    /// The listed lanes are down
+    Down(Vec<u32>),
+}
+
+impl LaneStatus {
+    /// Name written to STATE_DB
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LaneStatus::Unknown => "unknown",
+            LaneStatus::Up => "up",
+            LaneStatus::Down(_) => "down",
+        }
+    }
+}
+
+/// Serdes receive state of a port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdesStatus {
+    /// Signal detected on the lanes (`SAI_PORT_ATTR_RX_SIGNAL_DETECT`)
+    pub signal_detect: LaneStatus,
+    /// CDR locked on the lanes (`SAI_PORT_ATTR_RX_LOCK_STATUS`)
+    pub rx_lock: LaneStatus,
+}
 
 pub struct PortApi {
     api_table: *const sai_port_api_t,
@@ -16,7 +92,221 @@ impl PortApi {
         Self { api_table }
     }
 
+    /// Create a port
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_port(&self, switch_id: SaiOid, attributes: &[SaiAttribute]) -> Result<SaiOid> {
+        let mut port_oid: SaiOid = NULL_OID;
+
+        let c_attrs: Vec<sai_attribute_t> = attributes
+            .iter()
+            .map(|attr| unsafe { attr.to_c_attribute() })
+            .collect();
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_port {
+                create_fn(
+                    &mut port_oid,
+                    switch_id,
+                    c_attrs.len() as u32,
+                    c_attrs.as_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(port_oid, "create_port")
+    }
+
+    /// Remove a port
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_port(&self, port_id: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_port {
+                remove_fn(port_id)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Get the hardware lanes of a port
+    pub fn get_hw_lanes(&self, port_id: SaiOid) -> Result<Vec<u32>> {
+        let get_fn = unsafe { (*self.api_table).get_port_attribute };
+        let (status, lanes) = get_list(
+            get_fn,
+            port_id,
+            SAI_PORT_ATTR_HW_LANE_LIST,
+            LANE_LIST_CAPACITY,
+            0u32,
+            |c_attr, lanes| {
+                c_attr.value.u32list.count = lanes.len() as u32;
+                c_attr.value.u32list.list = lanes.as_mut_ptr();
+            },
+            |c_attr| unsafe { c_attr.value.u32list.count } as usize,
+        );
+        status.to_result()?;
+        Ok(lanes)
+    }
+
+    /// Split a port into sub-ports of `lanes_per_subport` lanes each
+    ///
+    /// The parent port is removed and one port per lane group is created,
+    /// each running at an equal share of the parent speed. Returns the new
+    /// OIDs in lane order. If a sub-port cannot be created, the ones
+    /// created are removed and the parent is recreated.
+    pub fn breakout(
+        &self,
+        switch_id: SaiOid,
+        port_id: SaiOid,
+        lanes_per_subport: usize,
+    ) -> Result<Vec<SaiOid>> {
+        self.try_breakout(switch_id, port_id, lanes_per_subport)
+            .map_err(|(e, _)| e)
+    }
+
+    /// [`breakout`](Self::breakout), also reporting how a failure left the
+    /// parent port
+    fn try_breakout(
+        &self,
+        switch_id: SaiOid,
+        port_id: SaiOid,
+        lanes_per_subport: usize,
+    ) -> std::result::Result<Vec<SaiOid>, (RacoonError, Rollback)> {
+        let unchanged = |e| (e, Rollback::Unchanged);
+        let lanes = self.get_hw_lanes(port_id).map_err(unchanged)?;
+        if lanes_per_subport == 0
+            || lanes_per_subport >= lanes.len()
+            || lanes.len() % lanes_per_subport != 0
+        {
+            return Err(unchanged(RacoonError::InvalidAttribute(format!(
+                "cannot split {} lanes into groups of {}",
+                lanes.len(),
+                lanes_per_subport
+            ))));
+        }
+
+        let speed = self.get_speed(port_id).map_err(unchanged)?;
+        let subport_count = lanes.len() / lanes_per_subport;
+        let subport_speed = speed / subport_count as u32;
+
+        self.remove_port(port_id).map_err(unchanged)?;
+
+        let mut subports = Vec::with_capacity(subport_count);
+        for group in lanes.chunks(lanes_per_subport) {
+            match self.create_lane_port(switch_id, group.to_vec(), subport_speed) {
+                Ok(subport) => subports.push(subport),
+                Err(e) => {
+                    let restored = self.restore(switch_id, &subports, &[(lanes.clone(), speed)]);
+                    return Err((e, Rollback::Restored(restored)));
+                }
+            }
+        }
+
+        Ok(subports)
+    }
+
+    /// Merge broken-out sub-ports back into a single port at `speed`
+    ///
+    /// If the sub-ports cannot all be removed or the merged port cannot be
+    /// created, the removed sub-ports are recreated.
+    pub fn unbreakout(&self, switch_id: SaiOid, subports: &[SaiOid], speed: u32) -> Result<SaiOid> {
+        self.try_unbreakout(switch_id, subports, speed)
+            .map_err(|(e, _)| e)
+    }
+
+    /// [`unbreakout`](Self::unbreakout), also reporting how a failure left
+    /// the sub-ports
+    fn try_unbreakout(
+        &self,
+        switch_id: SaiOid,
+        subports: &[SaiOid],
+        speed: u32,
+    ) -> std::result::Result<SaiOid, (RacoonError, Rollback)> {
+        let unchanged = |e| (e, Rollback::Unchanged);
+        let mut layout = Vec::with_capacity(subports.len());
+        for port_id in subports {
+            let lanes = self.get_hw_lanes(*port_id).map_err(unchanged)?;
+            let speed = self.get_speed(*port_id).map_err(unchanged)?;
+            layout.push((lanes, speed));
+        }
+
+        for (removed, port_id) in subports.iter().enumerate() {
+            if let Err(e) = self.remove_port(*port_id) {
+                if removed == 0 {
+                    return Err(unchanged(e));
+                }
+                // Ports not yet removed keep their OIDs
+                let mut restored = self.restore(switch_id, &[], &layout[..removed]);
+                restored.extend(subports[removed..].iter().copied().map(Some));
+                return Err((e, Rollback::Restored(restored)));
+            }
+        }
+
+        let lanes = layout.iter().flat_map(|(lanes, _)| lanes.clone()).collect();
+        self.create_lane_port(switch_id, lanes, speed).map_err(|e| {
+            let restored = self.restore(switch_id, &[], &layout);
+            (e, Rollback::Restored(restored))
+        })
+    }
+
+    /// Create a port on `lanes` running at `speed`
+    fn create_lane_port(&self, switch_id: SaiOid, lanes: Vec<u32>, speed: u32) -> Result<SaiOid> {
+        let attrs = [
+            SaiAttribute::new_u32_list(SAI_PORT_ATTR_HW_LANE_LIST, lanes),
+            SaiAttribute::new_u32(SAI_PORT_ATTR_SPEED, speed),
+        ];
+        self.create_port(switch_id, &attrs)
+    }
+
+    /// Undo a partial breakout or unbreakout: remove the ports `created`
+    /// so far, then recreate the removed ports from their lanes and speeds
+    ///
+    /// Returns the new OID of each removed port, `None` where it could not
+    /// be recreated.
+    fn restore(
+        &self,
+        switch_id: SaiOid,
+        created: &[SaiOid],
+        removed: &[(Vec<u32>, u32)],
+    ) -> Vec<Option<SaiOid>> {
+        for port_id in created {
+            if let Err(e) = self.remove_port(*port_id) {
+                warn!(
+                    "Failed to remove port 0x{:x} while rolling back: {}",
+                    port_id, e
+                );
+            }
+        }
+
+        removed
+            .iter()
+            .map(|(lanes, speed)| {
+                self.create_lane_port(switch_id, lanes.clone(), *speed)
+                    .inspect_err(|e| warn!("Failed to recreate port on lanes {:?}: {}", lanes, e))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Configured speed of a port in Mbps
+    fn get_speed(&self, port_id: SaiOid) -> Result<u32> {
+        match self.get_attribute(port_id, SAI_PORT_ATTR_SPEED)?.value {
+            SaiAttributeValue::U32(speed) if speed > 0 => Ok(speed),
+            value => Err(RacoonError::InvalidAttribute(format!(
+                "port 0x{:x} reports unknown speed {:?}",
+                port_id, value
+            ))),
+        }
+    }
+
     /// Set port attribute
+    #[instrument(level = "debug", skip(self))]
     pub fn set_attribute(&self, port_id: SaiOid, attribute: &SaiAttribute) -> Result<()> {
         let c_attr = unsafe { attribute.to_c_attribute() };
 
@@ -32,6 +322,93 @@ impl PortApi {
         SaiStatus::from(status).to_result()
     }
 
+    /// Set several port attributes in one pass
+    ///
+    /// Stops at the first attribute the SAI rejects.
+    pub fn set_attributes(&self, port_id: SaiOid, attributes: &[SaiAttribute]) -> Result<()> {
+        attributes
+            .iter()
+            .try_for_each(|attribute| self.set_attribute(port_id, attribute))
+    }
+
+    /// Bring the port administratively up or down
+    pub fn set_admin_status(&self, port_id: SaiOid, status: PortAdminStatus) -> Result<()> {
+        self.set_attribute(
+            port_id,
+            &SaiAttribute::new_bool(SAI_PORT_ATTR_ADMIN_STATE, status == PortAdminStatus::Up),
+        )
+    }
+
+    /// Mirror traffic received on the port to `sessions`
+    ///
+    /// Replaces the port's ingress sessions; an empty list stops mirroring.
+    pub fn set_ingress_mirror(&self, port_id: SaiOid, sessions: &[SaiOid]) -> Result<()> {
+        self.set_attribute(
+            port_id,
+            &SaiAttribute::new_oid_list(SAI_PORT_ATTR_INGRESS_MIRROR_SESSION, sessions.to_vec()),
+        )
+    }
+
+    /// Mirror traffic sent from the port to `sessions`
+    ///
+    /// Replaces the port's egress sessions; an empty list stops mirroring.
+    pub fn set_egress_mirror(&self, port_id: SaiOid, sessions: &[SaiOid]) -> Result<()> {
+        self.set_attribute(
+            port_id,
+            &SaiAttribute::new_oid_list(SAI_PORT_ATTR_EGRESS_MIRROR_SESSION, sessions.to_vec()),
+        )
+    }
+
+    /// Unbind all mirror sessions from the port in both directions
+    pub fn clear_mirror(&self, port_id: SaiOid) -> Result<()> {
+        self.set_ingress_mirror(port_id, &[])?;
+        self.set_egress_mirror(port_id, &[])
+    }
+
+    /// Enable or disable auto-negotiation
+    pub fn set_autoneg(&self, port_id: SaiOid, enabled: bool) -> Result<()> {
+        self.set_attribute(
+            port_id,
+            &SaiAttribute::new_bool(SAI_PORT_ATTR_AUTO_NEG_MODE, enabled),
+        )
+    }
+
+    /// Set the forward error correction mode
+    pub fn set_fec(&self, port_id: SaiOid, fec: FecMode) -> Result<()> {
+        self.set_attribute(
+            port_id,
+            &SaiAttribute::new_i32(SAI_PORT_ATTR_FEC_MODE, fec.to_sai()),
+        )
+    }
+
+    /// Set the media type the port's serdes are tuned for
+    pub fn set_interface_type(
+        &self,
+        port_id: SaiOid,
+        interface_type: PortInterfaceType,
+    ) -> Result<()> {
+        self.set_attribute(
+            port_id,
+            &SaiAttribute::new_i32(SAI_PORT_ATTR_INTERFACE_TYPE, interface_type.to_sai()),
+        )
+    }
+
+    /// Apply the link settings of a port's configuration
+    ///
+    /// Settings left unset in `link` are not touched.
+    pub fn apply_link_config(&self, port_id: SaiOid, link: &PortLinkConfig) -> Result<()> {
+        if let Some(interface_type) = link.interface_type {
+            self.set_interface_type(port_id, interface_type.into())?;
+        }
+        if let Some(fec) = link.fec {
+            self.set_fec(port_id, fec.into())?;
+        }
+        if let Some(autoneg) = link.autoneg {
+            self.set_autoneg(port_id, autoneg)?;
+        }
+        Ok(())
+    }
+
     /// Get port attribute
     pub fn get_attribute(&self, port_id: SaiOid, attr_id: u32) -> Result<SaiAttribute> {
         let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
@@ -52,6 +429,107 @@ impl PortApi {
         Ok(SaiAttribute::new_u32(attr_id, unsafe { c_attr.value.u32_ }))
     }
 
+    /// Number of scheduler groups of a port
+    /// (`SAI_PORT_ATTR_QOS_NUMBER_OF_SCHEDULER_GROUPS`)
+    pub fn get_scheduler_group_count(&self, port_id: SaiOid) -> Result<u32> {
+        let count = self.get_attribute(port_id, SAI_PORT_ATTR_QOS_NUMBER_OF_SCHEDULER_GROUPS)?;
+        match count.value {
+            SaiAttributeValue::U32(count) => Ok(count),
+            _ => Ok(0),
+        }
+    }
+
+    /// Link training state of a port
+    ///
+    /// A reported failure wins over the receive status, which SAIs leave at
+    /// not trained after giving up.
+    pub fn get_link_training_status(&self, port_id: SaiOid) -> Result<LinkTrainingStatus> {
+        let failure = self
+            .get_s32_if_supported(port_id, SAI_PORT_ATTR_LINK_TRAINING_FAILURE_STATUS)?
+            .and_then(PortLinkTrainingFailureStatus::from_sai);
+        if let Some(failure) = failure
+            && failure != PortLinkTrainingFailureStatus::NoError
+        {
+            return Ok(LinkTrainingStatus::Failed(failure));
+        }
+
+        let rx = self
+            .get_s32_if_supported(port_id, SAI_PORT_ATTR_LINK_TRAINING_RX_STATUS)?
+            .and_then(PortLinkTrainingRxStatus::from_sai);
+        Ok(match rx {
+            Some(PortLinkTrainingRxStatus::Trained) => LinkTrainingStatus::Trained,
+            Some(PortLinkTrainingRxStatus::NotTrained) => LinkTrainingStatus::NotTrained,
+            None => LinkTrainingStatus::Unknown,
+        })
+    }
+
+    /// Signal detect and CDR lock of each serdes lane of a port
+    pub fn get_serdes_status(&self, port_id: SaiOid) -> Result<SerdesStatus> {
+        Ok(SerdesStatus {
+            signal_detect: self.get_lane_status(port_id, SAI_PORT_ATTR_RX_SIGNAL_DETECT)?,
+            rx_lock: self.get_lane_status(port_id, SAI_PORT_ATTR_RX_LOCK_STATUS)?,
+        })
+    }
+
+    /// Read `s32` attribute `attr_id`, or `None` if the SAI does not support it
+    fn get_s32_if_supported(&self, port_id: SaiOid, attr_id: u32) -> Result<Option<i32>> {
+        let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
+        c_attr.id = attr_id;
+
+        let status = match unsafe { (*self.api_table).get_port_attribute } {
+            Some(get_fn) => unsafe { get_fn(port_id, 1, &mut c_attr) },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        let status = SaiStatus::from(status);
+        if status.is_unsupported() {
+            return Ok(None);
+        }
+        status.to_result()?;
+        Ok(Some(unsafe { c_attr.value.s32 }))
+    }
+
+    /// Read lane latch status list attribute `attr_id` of a port
+    ///
+    /// Only the current status of each lane counts; the latched `changed`
+    /// flag is left for the SAI to clear.
+    fn get_lane_status(&self, port_id: SaiOid, attr_id: u32) -> Result<LaneStatus> {
+        let get_fn = unsafe { (*self.api_table).get_port_attribute };
+        let (status, lanes) = get_list(
+            get_fn,
+            port_id,
+            attr_id,
+            LANE_LIST_CAPACITY,
+            unsafe { std::mem::zeroed::<sai_port_lane_latch_status_t>() },
+            |c_attr, lanes| {
+                c_attr.value.portlanelatchstatuslist.count = lanes.len() as u32;
+                c_attr.value.portlanelatchstatuslist.list = lanes.as_mut_ptr();
+            },
+            |c_attr| unsafe { c_attr.value.portlanelatchstatuslist.count } as usize,
+        );
+        if status.is_unsupported() {
+            return Ok(LaneStatus::Unknown);
+        }
+        status.to_result()?;
+
+        let down: Vec<u32> = lanes
+            .iter()
+            .filter(|lane| !lane.value.current_status)
+            .map(|lane| lane.lane)
+            .collect();
+        Ok(if down.is_empty() {
+            LaneStatus::Up
+        } else {
+            LaneStatus::Down(down)
+        })
+    }
+
+    /// Read object list attribute `attr_id` of a port
+    pub(crate) fn get_object_list(&self, port_id: SaiOid, attr_id: u32) -> Result<Vec<SaiOid>> {
+        let get_fn = unsafe { (*self.api_table).get_port_attribute };
+        get_object_list(get_fn, port_id, attr_id)
+    }
+
     /// Get port statistics
     pub fn get_stats(&self, port_id: SaiOid, counter_ids: &[sai_port_stat_t]) -> Result<Vec<u64>> {
         let mut counters = vec![0u64; counter_ids.len()];
@@ -88,3 +566,375 @@ impl PortApi {
         SaiStatus::from(status).to_result()
     }
 }
+
+/// Ports left by a breakout or unbreakout that failed part way
+#[derive(Debug)]
+enum Rollback {
+    /// Failed before any port was removed
+    Unchanged,
+    /// OID of each original port after the rollback, in order; `None`
+    /// where it could not be recreated
+    Restored(Vec<Option<SaiOid>>),
+}
+
+/// Front-panel port name to OID map
+///
+/// Tracks VLAN membership per port so that breakout refuses to remove a
+/// port that still carries traffic. Keys are [`PortName`]s, so alias
+/// spellings such as `eth0` resolve to the same port as `Ethernet0`.
+#[derive(Debug, Default)]
+pub struct PortMap {
+    ports: HashMap<PortName, SaiOid>,
+    vlan_members: HashMap<PortName, usize>,
+}
+
+impl PortMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: PortName, port_id: SaiOid) {
+        self.ports.insert(name, port_id);
+    }
+
+    pub fn get(&self, name: PortName) -> Option<SaiOid> {
+        self.ports.get(&name).copied()
+    }
+
+    /// Record that a port joined a VLAN
+    pub fn add_vlan_member(&mut self, name: PortName) {
+        *self.vlan_members.entry(name).or_insert(0) += 1;
+    }
+
+    /// Record that a port left a VLAN
+    pub fn remove_vlan_member(&mut self, name: PortName) {
+        if let Some(count) = self.vlan_members.get_mut(&name) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.vlan_members.remove(&name);
+            }
+        }
+    }
+
+    fn ensure_unused(&self, name: PortName) -> Result<()> {
+        match self.vlan_members.get(&name) {
+            Some(count) => Err(RacoonError::DependencyNotSatisfied(format!(
+                "{} still has {} VLAN member(s)",
+                name, count
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Break out a port and register the sub-ports under their lane names
+    ///
+    /// `Ethernet0` split into 4x1 lanes becomes `Ethernet0`..`Ethernet3`;
+    /// split into 2x2 lanes it becomes `Ethernet0` and `Ethernet2`.
+    pub fn breakout(
+        &mut self,
+        api: &PortApi,
+        switch_id: SaiOid,
+        name: PortName,
+        lanes_per_subport: usize,
+    ) -> Result<Vec<PortName>> {
+        let port_id = self
+            .get(name)
+            .ok_or_else(|| RacoonError::PortNotFound(name.to_string()))?;
+        self.ensure_unused(name)?;
+
+        let subports = match api.try_breakout(switch_id, port_id, lanes_per_subport) {
+            Ok(subports) => subports,
+            Err((e, rollback)) => {
+                self.apply_rollback(&[name], rollback);
+                return Err(e);
+            }
+        };
+
+        self.ports.remove(&name);
+        let names: Vec<PortName> = subports
+            .iter()
+            .enumerate()
+            .map(|(i, oid)| {
+                let subport = PortName::new(name.index() + (i * lanes_per_subport) as u32);
+                self.ports.insert(subport, *oid);
+                subport
+            })
+            .collect();
+
+        Ok(names)
+    }
+
+    /// Merge sub-ports back into one port registered under the first name
+    pub fn unbreakout(
+        &mut self,
+        api: &PortApi,
+        switch_id: SaiOid,
+        names: &[PortName],
+        speed: u32,
+    ) -> Result<SaiOid> {
+        let mut subports = Vec::with_capacity(names.len());
+        for name in names {
+            self.ensure_unused(*name)?;
+            subports.push(
+                self.get(*name)
+                    .ok_or_else(|| RacoonError::PortNotFound(name.to_string()))?,
+            );
+        }
+
+        let port_id = match api.try_unbreakout(switch_id, &subports, speed) {
+            Ok(port_id) => port_id,
+            Err((e, rollback)) => {
+                self.apply_rollback(names, rollback);
+                return Err(e);
+            }
+        };
+
+        for name in names {
+            self.ports.remove(name);
+        }
+        if let Some(first) = names.first() {
+            self.ports.insert(*first, port_id);
+        }
+
+        Ok(port_id)
+    }
+
+    /// Point `names` at the ports a failed breakout or unbreakout left
+    fn apply_rollback(&mut self, names: &[PortName], rollback: Rollback) {
+        let Rollback::Restored(restored) = rollback else {
+            return;
+        };
+        for (name, port_id) in names.iter().zip(restored) {
+            match port_id {
+                Some(port_id) => self.ports.insert(*name, port_id),
+                None => self.ports.remove(name),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    fn port(name: &str) -> PortName {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn test_breakout_and_unbreakout() {
+        let api = mock::port_api();
+        let parent = mock::add_port(&[1, 2, 3, 4], 100_000);
+
+        let mut ports = PortMap::new();
+        ports.insert(port("Ethernet0"), parent);
+
+        let names = ports.breakout(&api, 0, port("Ethernet0"), 1).unwrap();
+        let names: Vec<String> = names.iter().map(PortName::to_string).collect();
+        assert_eq!(
+            names,
+            vec!["Ethernet0", "Ethernet1", "Ethernet2", "Ethernet3"]
+        );
+
+        let calls = mock::take_calls();
+        let functions: Vec<&str> = calls.iter().map(|c| c.function).collect();
+        assert_eq!(
+            functions,
+            vec![
+                "get_port_attribute",
+                "get_port_attribute",
+                "remove_port",
+                "create_port",
+                "create_port",
+                "create_port",
+                "create_port"
+            ]
+        );
+        assert_eq!(calls[2].oid, parent);
+        assert_eq!(
+            api.get_hw_lanes(ports.get(port("eth2")).unwrap()).unwrap(),
+            vec![3]
+        );
+        // Each sub-port runs at a quarter of the parent speed
+        assert!(calls[3].attrs.contains(&(SAI_PORT_ATTR_SPEED, 25_000)));
+
+        let merged = ports
+            .unbreakout(
+                &api,
+                0,
+                &[
+                    port("Ethernet0"),
+                    port("Ethernet1"),
+                    port("Ethernet2"),
+                    port("Ethernet3"),
+                ],
+                100_000,
+            )
+            .unwrap();
+        assert_eq!(ports.get(port("Ethernet0")), Some(merged));
+        assert_eq!(ports.get(port("Ethernet1")), None);
+        assert_eq!(api.get_hw_lanes(merged).unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_failed_breakout_restores_parent() {
+        let api = mock::port_api();
+        let parent = mock::add_port(&[1, 2, 3, 4], 100_000);
+        let mut ports = PortMap::new();
+        ports.insert(port("Ethernet0"), parent);
+
+        // The third sub-port fails
+        mock::fail_port_create_after(2);
+        assert!(ports.breakout(&api, 0, port("Ethernet0"), 1).is_err());
+
+        let restored = ports.get(port("Ethernet0")).unwrap();
+        assert_ne!(restored, parent);
+        assert_eq!(api.get_hw_lanes(restored).unwrap(), vec![1, 2, 3, 4]);
+        assert_eq!(api.get_speed(restored).unwrap(), 100_000);
+        assert_eq!(ports.get(port("Ethernet1")), None);
+        let removed = mock::take_calls()
+            .iter()
+            .filter(|call| call.function == "remove_port")
+            .count();
+        // The parent and both sub-ports created
+        assert_eq!(removed, 3);
+    }
+
+    #[test]
+    fn test_failed_unbreakout_restores_subports() {
+        let api = mock::port_api();
+        let parent = mock::add_port(&[1, 2], 50_000);
+        let mut ports = PortMap::new();
+        ports.insert(port("Ethernet0"), parent);
+        let names = ports.breakout(&api, 0, port("Ethernet0"), 1).unwrap();
+
+        mock::fail_port_create_after(0);
+        assert!(ports.unbreakout(&api, 0, &names, 50_000).is_err());
+
+        for (name, lane) in names.iter().zip([1, 2]) {
+            let port_id = ports.get(*name).unwrap();
+            assert_eq!(api.get_hw_lanes(port_id).unwrap(), vec![lane]);
+            assert_eq!(api.get_speed(port_id).unwrap(), 25_000);
+        }
+    }
+
+    #[test]
+    fn test_breakout_rejects_unknown_speed() {
+        let api = mock::port_api();
+        let parent = mock::add_port(&[1, 2, 3, 4], 0);
+
+        let err = api.breakout(0, parent, 2).unwrap_err();
+        assert!(matches!(err, RacoonError::InvalidAttribute(_)));
+        assert!(
+            !mock::take_calls()
+                .iter()
+                .any(|call| call.function == "remove_port")
+        );
+    }
+
+    #[test]
+    fn test_link_attributes() {
+        let api = mock::port_api();
+        let port_oid = mock::add_port(&[1, 2, 3, 4], 100_000);
+
+        api.set_autoneg(port_oid, true).unwrap();
+        api.set_fec(port_oid, FecMode::Rs).unwrap();
+        api.set_interface_type(port_oid, PortInterfaceType::Cr4)
+            .unwrap();
+
+        let calls = mock::take_calls();
+        assert!(
+            calls
+                .iter()
+                .all(|call| call.function == "set_port_attribute" && call.oid == port_oid)
+        );
+        let attrs: Vec<(u32, u64)> = calls.into_iter().flat_map(|call| call.attrs).collect();
+        assert_eq!(
+            attrs,
+            vec![
+                (SAI_PORT_ATTR_AUTO_NEG_MODE, 1),
+                (SAI_PORT_ATTR_FEC_MODE, SAI_PORT_FEC_MODE_RS as u64),
+                (
+                    SAI_PORT_ATTR_INTERFACE_TYPE,
+                    SAI_PORT_INTERFACE_TYPE_CR4 as u64
+                ),
+            ]
+        );
+
+        // Only the configured settings are applied
+        let link = PortLinkConfig {
+            autoneg: None,
+            fec: Some(racoon_common::FecMode::Fc),
+            interface_type: None,
+        };
+        api.apply_link_config(port_oid, &link).unwrap();
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0].attrs,
+            vec![(SAI_PORT_ATTR_FEC_MODE, SAI_PORT_FEC_MODE_FC as u64)]
+        );
+    }
+
+    #[test]
+    fn test_link_training_done() {
+        let api = mock::port_api();
+        let port_oid = mock::add_port(&[1, 2, 3, 4], 100_000);
+
+        let status = api.get_link_training_status(port_oid).unwrap();
+        assert_eq!(status, LinkTrainingStatus::Trained);
+        assert_eq!(status.as_str(), "trained");
+        assert_eq!(status.failure(), None);
+
+        // The mock reports signal detect but not CDR lock
+        let serdes = api.get_serdes_status(port_oid).unwrap();
+        assert_eq!(serdes.signal_detect, LaneStatus::Up);
+        assert_eq!(serdes.rx_lock, LaneStatus::Unknown);
+        assert_eq!(serdes.rx_lock.as_str(), "unknown");
+
+        assert!(matches!(
+            api.get_link_training_status(0x1000000000099),
+            Err(RacoonError::SaiCall { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ingress_mirror_session() {
+        let api = mock::port_api();
+        let port_oid = mock::add_port(&[1], 25_000);
+        let sessions = [0x0e000000000001, 0x0e000000000002];
+
+        api.set_ingress_mirror(port_oid, &sessions).unwrap();
+        assert_eq!(
+            mock::mirror_sessions(port_oid, SAI_PORT_ATTR_INGRESS_MIRROR_SESSION),
+            sessions
+        );
+        assert!(mock::mirror_sessions(port_oid, SAI_PORT_ATTR_EGRESS_MIRROR_SESSION).is_empty());
+
+        api.clear_mirror(port_oid).unwrap();
+        assert!(mock::mirror_sessions(port_oid, SAI_PORT_ATTR_INGRESS_MIRROR_SESSION).is_empty());
+        let functions: Vec<&str> = mock::take_calls().iter().map(|c| c.function).collect();
+        assert_eq!(functions, vec!["set_port_attribute"; 3]);
+    }
+
+    #[test]
+    fn test_breakout_refused_with_vlan_members() {
+        let api = mock::port_api();
+        let parent = mock::add_port(&[1, 2, 3, 4], 100_000);
+
+        let mut ports = PortMap::new();
+        ports.insert(port("Ethernet0"), parent);
+        ports.add_vlan_member(port("Ethernet0"));
+
+        let err = ports.breakout(&api, 0, port("Ethernet0"), 2).unwrap_err();
+        assert!(matches!(err, RacoonError::DependencyNotSatisfied(_)));
+        assert!(mock::take_calls().is_empty());
+
+        ports.remove_vlan_member(port("ethernet0"));
+        assert_eq!(
+            ports.breakout(&api, 0, port("Ethernet0"), 2).unwrap().len(),
+            2
+        );
+    }
+}
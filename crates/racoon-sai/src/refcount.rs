@@ -0,0 +1,90 @@
+//! Reference counting for shared SAI objects
+//!
+//! Objects such as bridge ports are referenced by several higher-level
+//! objects. Removing one while still referenced fails with
+//! `SAI_STATUS_OBJECT_IN_USE`, so owners acquire a reference per user and
+//! only remove the object when the last reference is released.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// Reference counts keyed by object
+#[derive(Debug)]
+pub struct RefCounter<K> {
+    counts: Mutex<HashMap<K, usize>>,
+}
+
+impl<K: Eq + Hash + Copy> RefCounter<K> {
+    pub fn new() -> Self {
+        Self {
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add a reference; returns true if this is the first one
+    pub fn acquire(&self, key: K) -> bool {
+        let mut counts = self.counts.lock().unwrap();
+        let count = counts.entry(key).or_insert(0);
+        *count += 1;
+        *count == 1
+    }
+
+    /// Drop a reference; returns true if the object should now be removed
+    ///
+    /// Releasing an untracked object returns false.
+    pub fn release(&self, key: K) -> bool {
+        let mut counts = self.counts.lock().unwrap();
+        match counts.get_mut(&key) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                false
+            }
+            Some(_) => {
+                counts.remove(&key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Current number of references to an object
+    pub fn count(&self, key: K) -> usize {
+        self.counts.lock().unwrap().get(&key).copied().unwrap_or(0)
+    }
+}
+
+impl<K: Eq + Hash + Copy> Default for RefCounter<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_common::SaiOid;
+
+    #[test]
+    fn test_release_after_last_reference() {
+        let refs: RefCounter<SaiOid> = RefCounter::new();
+
+        assert!(refs.acquire(0x1000));
+        assert!(!refs.acquire(0x1000));
+        assert_eq!(refs.count(0x1000), 2);
+
+        // One reference remains, keep the object
+        assert!(!refs.release(0x1000));
+        assert_eq!(refs.count(0x1000), 1);
+
+        // Last reference gone, remove it
+        assert!(refs.release(0x1000));
+        assert_eq!(refs.count(0x1000), 0);
+    }
+
+    #[test]
+    fn test_release_untracked() {
+        let refs: RefCounter<SaiOid> = RefCounter::new();
+        assert!(!refs.release(0x2000));
+    }
+}
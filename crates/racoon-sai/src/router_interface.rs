@@ -0,0 +1,226 @@
+use crate::bindings::*;
+use crate::constants::*;
+use crate::oid::{NULL_OID, non_null};
+use crate::status::SaiStatus;
+use crate::switch::SwitchApi;
+use crate::types::{SaiAttribute, SaiObjectType};
+use racoon_common::{Result, SaiOid};
+use tracing::instrument;
+
+/// Router interfaces (RIFs) attaching L3 routing to ports and VLANs
+pub struct RouterInterfaceApi {
+    api_table: *const sai_router_interface_api_t,
+}
+
+unsafe impl Send for RouterInterfaceApi {}
+unsafe impl Sync for RouterInterfaceApi {}
+
+impl RouterInterfaceApi {
+    pub fn new(api_table: *const sai_router_interface_api_t) -> Self {
+        Self { api_table }
+    }
+
+    /// Create the L3 interface (SVI) of a VLAN in `virtual_router`
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_vlan_interface(
+        &self,
+        switch_id: SaiOid,
+        virtual_router: SaiOid,
+        vlan_oid: SaiOid,
+    ) -> Result<SaiOid> {
+        let mut rif_oid: SaiOid = NULL_OID;
+
+        let attrs = [
+            SaiAttribute::new_oid(SAI_ROUTER_INTERFACE_ATTR_VIRTUAL_ROUTER_ID, virtual_router),
+            SaiAttribute::new_i32(
+                SAI_ROUTER_INTERFACE_ATTR_TYPE,
+                SAI_ROUTER_INTERFACE_TYPE_VLAN as i32,
+            ),
+            SaiAttribute::new_oid(SAI_ROUTER_INTERFACE_ATTR_VLAN_ID, vlan_oid),
+        ];
+
+        let c_attrs: Vec<sai_attribute_t> = attrs
+            .iter()
+            .map(|attr| unsafe { attr.to_c_attribute() })
+            .collect();
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_router_interface {
+                create_fn(
+                    &mut rif_oid,
+                    switch_id,
+                    c_attrs.len() as u32,
+                    c_attrs.as_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(rif_oid, "create_router_interface")
+    }
+
+    /// Enable or disable IPv4 and IPv6 routing on a router interface
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_admin_state(&self, rif_oid: SaiOid, up: bool) -> Result<()> {
+        for attr_id in [
+            SAI_ROUTER_INTERFACE_ATTR_ADMIN_V4_STATE,
+            SAI_ROUTER_INTERFACE_ATTR_ADMIN_V6_STATE,
+        ] {
+            let c_attr = unsafe { SaiAttribute::new_bool(attr_id, up).to_c_attribute() };
+
+            let status = unsafe {
+                let api = &*self.api_table;
+                if let Some(set_fn) = api.set_router_interface_attribute {
+                    set_fn(rif_oid, &c_attr)
+                } else {
+                    SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+                }
+            };
+
+            SaiStatus::from(status).to_result()?;
+        }
+        Ok(())
+    }
+
+    /// Set the MTU of a router interface
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_mtu(&self, rif_oid: SaiOid, mtu: u32) -> Result<()> {
+        let c_attr =
+            unsafe { SaiAttribute::new_u32(SAI_ROUTER_INTERFACE_ATTR_MTU, mtu).to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(set_fn) = api.set_router_interface_attribute {
+                set_fn(rif_oid, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Set the MTU of a router interface unless the platform reports it as
+    /// not settable
+    ///
+    /// Returns false when the set was skipped. If the capability can't be
+    /// queried the set is attempted anyway.
+    pub fn set_mtu_if_supported(
+        &self,
+        switch_api: &SwitchApi,
+        switch_id: SaiOid,
+        rif_oid: SaiOid,
+        mtu: u32,
+    ) -> Result<bool> {
+        if !switch_api.is_settable(
+            switch_id,
+            SaiObjectType::RouterInterface,
+            SAI_ROUTER_INTERFACE_ATTR_MTU,
+        ) {
+            return Ok(false);
+        }
+        self.set_mtu(rif_oid, mtu)?;
+        Ok(true)
+    }
+
+    /// Remove a router interface
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_router_interface(&self, rif_oid: SaiOid) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_router_interface {
+                remove_fn(rif_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_admin_state_sets_both_families() {
+        let api = mock::router_interface_api();
+        let rif = api
+            .create_vlan_interface(0x21000000000000, 0x3000000000001, 0x26000000000001)
+            .unwrap();
+        assert_eq!(
+            SaiObjectType::from_oid(rif),
+            Some(SaiObjectType::RouterInterface)
+        );
+        mock::take_calls();
+
+        api.set_admin_state(rif, false).unwrap();
+        let calls = mock::take_calls();
+        assert!(calls.iter().all(|call| call.oid == rif));
+        assert_eq!(
+            calls
+                .iter()
+                .flat_map(|call| &call.attrs)
+                .collect::<Vec<_>>(),
+            [
+                &(SAI_ROUTER_INTERFACE_ATTR_ADMIN_V4_STATE, 0),
+                &(SAI_ROUTER_INTERFACE_ATTR_ADMIN_V6_STATE, 0),
+            ]
+        );
+    }
+
+    /// Capability query reporting every attribute as create-only
+    unsafe extern "C" fn query_create_only(
+        _switch_id: sai_object_id_t,
+        _object_type: sai_object_type_t,
+        _attr_id: sai_attr_id_t,
+        attr_capability: *mut sai_attr_capability_t,
+    ) -> sai_status_t {
+        unsafe {
+            *attr_capability = sai_attr_capability_t {
+                create_implemented: true,
+                set_implemented: false,
+                get_implemented: true,
+            };
+        }
+        SAI_STATUS_SUCCESS as sai_status_t
+    }
+
+    #[test]
+    fn test_mtu_set_only_when_settable() {
+        let api = mock::router_interface_api();
+        let rif = api
+            .create_vlan_interface(0x21000000000000, 0x3000000000001, 0x26000000000001)
+            .unwrap();
+        mock::take_calls();
+
+        let switch_api = mock::switch_api();
+        assert!(
+            api.set_mtu_if_supported(&switch_api, 0x21000000000000, rif, 9100)
+                .unwrap()
+        );
+        let calls = mock::take_calls();
+        let set = calls
+            .iter()
+            .find(|call| call.function == "set_router_interface_attribute")
+            .unwrap();
+        assert_eq!(set.attrs, [(SAI_ROUTER_INTERFACE_ATTR_MTU, 9100)]);
+
+        let create_only = SwitchApi::new(Box::leak(Box::new(mock::switch_api_table())))
+            .with_capability_query(Some(query_create_only));
+        assert!(
+            !api.set_mtu_if_supported(&create_only, 0x21000000000000, rif, 1500)
+                .unwrap()
+        );
+        assert!(
+            mock::take_calls()
+                .iter()
+                .all(|call| call.function != "set_router_interface_attribute")
+        );
+    }
+}
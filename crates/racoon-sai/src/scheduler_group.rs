@@ -0,0 +1,143 @@
+use crate::bindings::*;
+use crate::constants::*;
+use crate::port::PortApi;
+use crate::status::SaiStatus;
+use crate::types::SaiAttribute;
+use racoon_common::{Result, SaiOid};
+use tracing::{instrument, warn};
+
+/// Scheduler groups and queue scheduling
+///
+/// SAI creates each port's scheduler groups at switch init, forming a tree
+/// whose leaves are the port's queues. Groups are enumerated from the port
+/// and scheduling is programmed by attaching a scheduler profile to a
+/// group or a queue.
+pub struct SchedulerGroupApi {
+    api_table: *const sai_scheduler_group_api_t,
+    queue_api: *const sai_queue_api_t,
+}
+
+unsafe impl Send for SchedulerGroupApi {}
+unsafe impl Sync for SchedulerGroupApi {}
+
+impl SchedulerGroupApi {
+    pub fn new(
+        api_table: *const sai_scheduler_group_api_t,
+        queue_api: *const sai_queue_api_t,
+    ) -> Self {
+        Self {
+            api_table,
+            queue_api,
+        }
+    }
+
+    /// Scheduler groups of a port
+    ///
+    /// Empty for a port without scheduler groups.
+    pub fn get_port_groups(&self, port_api: &PortApi, port_id: SaiOid) -> Result<Vec<SaiOid>> {
+        let count = port_api.get_scheduler_group_count(port_id)?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let groups = port_api.get_object_list(port_id, SAI_PORT_ATTR_QOS_SCHEDULER_GROUP_LIST)?;
+        if groups.len() != count as usize {
+            warn!(
+                "Port 0x{:x} reports {} scheduler groups but lists {}",
+                port_id,
+                count,
+                groups.len()
+            );
+        }
+        Ok(groups)
+    }
+
+    /// Schedule a group with `scheduler_oid`; `NULL_OID` detaches it
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_group_scheduler(&self, group_oid: SaiOid, scheduler_oid: SaiOid) -> Result<()> {
+        let attr =
+            SaiAttribute::new_oid(SAI_SCHEDULER_GROUP_ATTR_SCHEDULER_PROFILE_ID, scheduler_oid);
+        let c_attr = unsafe { attr.to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(set_fn) = api.set_scheduler_group_attribute {
+                set_fn(group_oid, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
+    /// Schedule a queue with `scheduler_oid`; `NULL_OID` detaches it
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_queue_scheduler(&self, queue_oid: SaiOid, scheduler_oid: SaiOid) -> Result<()> {
+        let attr = SaiAttribute::new_oid(SAI_QUEUE_ATTR_SCHEDULER_PROFILE_ID, scheduler_oid);
+        let c_attr = unsafe { attr.to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.queue_api;
+            if let Some(set_fn) = api.set_queue_attribute {
+                set_fn(queue_oid, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::types::SaiObjectType;
+
+    #[test]
+    fn test_port_groups_enumerated() {
+        let api = mock::scheduler_group_api();
+        let port_api = mock::port_api();
+        let port = mock::add_port(&[1, 2, 3, 4], 100_000);
+        assert!(api.get_port_groups(&port_api, port).unwrap().is_empty());
+
+        // More groups than the first list read makes room for
+        let groups = mock::add_scheduler_groups(port, 20);
+        let listed = api.get_port_groups(&port_api, port).unwrap();
+        assert_eq!(listed, groups);
+        assert!(
+            listed
+                .iter()
+                .all(|oid| SaiObjectType::from_oid(*oid) == Some(SaiObjectType::SchedulerGroup))
+        );
+    }
+
+    #[test]
+    fn test_scheduler_attached_to_group_and_queue() {
+        let api = mock::scheduler_group_api();
+        let group = mock::add_scheduler_groups(mock::add_port(&[1], 25_000), 1)[0];
+        let queue = 0x15000000000001;
+        let scheduler = 0x16000000000001;
+        mock::take_calls();
+
+        api.set_group_scheduler(group, scheduler).unwrap();
+        api.set_queue_scheduler(queue, scheduler).unwrap();
+
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].function, "set_scheduler_group_attribute");
+        assert_eq!(calls[0].oid, group);
+        assert_eq!(
+            calls[0].attrs,
+            vec![(SAI_SCHEDULER_GROUP_ATTR_SCHEDULER_PROFILE_ID, scheduler)]
+        );
+        assert_eq!(calls[1].function, "set_queue_attribute");
+        assert_eq!(calls[1].oid, queue);
+        assert_eq!(
+            calls[1].attrs,
+            vec![(SAI_QUEUE_ATTR_SCHEDULER_PROFILE_ID, scheduler)]
+        );
+    }
+}
@@ -25,15 +25,64 @@ impl SaiStatus {
         !self.is_success()
     }
 
+    /// Whether the SAI does not implement the call or one of its attributes
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self.0,
+            SAI_STATUS_NOT_SUPPORTED
+                | SAI_STATUS_NOT_IMPLEMENTED
+                | SAI_STATUS_ATTR_NOT_IMPLEMENTED_MAX..=SAI_STATUS_ATTR_NOT_IMPLEMENTED_0
+                | SAI_STATUS_ATTR_NOT_SUPPORTED_MAX..=SAI_STATUS_ATTR_NOT_SUPPORTED_0
+        )
+    }
+
     pub fn to_result(self) -> Result<(), RacoonError> {
         if self.is_success() {
             Ok(())
         } else {
-            Err(RacoonError::Sai(self.to_string()))
+            Err(RacoonError::SaiCall {
+                code: self.0,
+                message: self.to_string(),
+            })
         }
     }
 }
 
+/// Whether a failed SAI call is worth retrying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retryability {
+    /// Momentary resource contention; the same call may succeed later
+    Transient,
+    /// The call itself is wrong and will fail again
+    Permanent,
+}
+
+/// Classify a SAI status for retrying
+///
+/// Resource exhaustion, objects still in use and an uninitialized switch
+/// can clear up on their own. Everything else, including the generic
+/// `FAILURE`, is treated as permanent so a bad attribute is not retried.
+pub fn classify(status: SaiStatus) -> Retryability {
+    match status.0 {
+        SAI_STATUS_INSUFFICIENT_RESOURCES
+        | SAI_STATUS_NO_MEMORY
+        | SAI_STATUS_TABLE_FULL
+        | SAI_STATUS_OBJECT_IN_USE
+        | SAI_STATUS_UNINITIALIZED => Retryability::Transient,
+        _ => Retryability::Permanent,
+    }
+}
+
+/// Classify an error returned by a SAI wrapper
+///
+/// Errors that did not come from a SAI status are permanent.
+pub fn classify_error(error: &RacoonError) -> Retryability {
+    match error {
+        RacoonError::SaiCall { code, .. } => classify(SaiStatus(*code)),
+        _ => Retryability::Permanent,
+    }
+}
+
 impl From<sai_status_t> for SaiStatus {
     fn from(status: sai_status_t) -> Self {
         SaiStatus(status)
@@ -84,6 +133,49 @@ mod tests {
         assert!(status.to_result().is_ok());
     }
 
+    #[test]
+    fn test_classify() {
+        for status in [
+            SAI_STATUS_INSUFFICIENT_RESOURCES,
+            SAI_STATUS_NO_MEMORY,
+            SAI_STATUS_TABLE_FULL,
+            SAI_STATUS_OBJECT_IN_USE,
+        ] {
+            assert_eq!(classify(SaiStatus(status)), Retryability::Transient);
+        }
+        for status in [
+            SAI_STATUS_FAILURE,
+            SAI_STATUS_INVALID_PARAMETER,
+            SAI_STATUS_INVALID_VLAN_ID,
+            SAI_STATUS_NOT_SUPPORTED,
+            SAI_STATUS_ITEM_ALREADY_EXISTS,
+        ] {
+            assert_eq!(classify(SaiStatus(status)), Retryability::Permanent);
+        }
+
+        // The status survives conversion to an error
+        let err = SaiStatus(SAI_STATUS_TABLE_FULL).to_result().unwrap_err();
+        assert_eq!(classify_error(&err), Retryability::Transient);
+        assert_eq!(
+            classify_error(&RacoonError::Sai("null API table".to_string())),
+            Retryability::Permanent
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        for status in [
+            SAI_STATUS_NOT_SUPPORTED,
+            SAI_STATUS_NOT_IMPLEMENTED,
+            SAI_STATUS_ATTR_NOT_IMPLEMENTED_0,
+            SAI_STATUS_ATTR_NOT_SUPPORTED_0 - 3,
+        ] {
+            assert!(SaiStatus(status).is_unsupported());
+        }
+        assert!(!SaiStatus::FAILURE.is_unsupported());
+        assert!(!SaiStatus(SAI_STATUS_INVALID_ATTRIBUTE_0).is_unsupported());
+    }
+
     #[test]
     fn test_status_error() {
         let status = SaiStatus::FAILURE;
@@ -0,0 +1,129 @@
+use crate::bindings::*;
+use crate::constants::*;
+use crate::oid::{NULL_OID, non_null};
+use crate::status::SaiStatus;
+use crate::vlan::stored_switch_id;
+use racoon_common::{RacoonError, Result, SaiOid};
+use tracing::instrument;
+
+/// Spanning tree instances
+///
+/// Every VLAN starts in the switch's default instance
+/// (`SAI_SWITCH_ATTR_DEFAULT_STP_INST_ID`), which the switch owns and which
+/// can't be removed. Additional instances are created here and assigned to
+/// VLANs with [`VlanApi::set_stp_instance`](crate::VlanApi::set_stp_instance).
+pub struct StpApi {
+    api_table: *const sai_stp_api_t,
+    switch_id: Option<SaiOid>,
+    default_instance: Option<SaiOid>,
+}
+
+unsafe impl Send for StpApi {}
+unsafe impl Sync for StpApi {}
+
+impl StpApi {
+    pub fn new(api_table: *const sai_stp_api_t) -> Self {
+        Self {
+            api_table,
+            switch_id: None,
+            default_instance: None,
+        }
+    }
+
+    /// Store the switch used by [`create`](Self::create)
+    pub fn with_switch_id(mut self, switch_id: SaiOid) -> Self {
+        self.switch_id = Some(switch_id);
+        self
+    }
+
+    /// Store the switch's default instance, so it is never removed
+    ///
+    /// Read it with
+    /// [`SwitchApi::get_default_stp_instance`](crate::SwitchApi::get_default_stp_instance).
+    pub fn with_default_instance(mut self, stp_oid: SaiOid) -> Self {
+        self.default_instance = Some(stp_oid);
+        self
+    }
+
+    /// The switch's default instance, if stored
+    pub fn default_instance(&self) -> Option<SaiOid> {
+        self.default_instance
+    }
+
+    /// Create an STP instance on the stored switch
+    pub fn create(&self) -> Result<SaiOid> {
+        self.create_stp(stored_switch_id(self.switch_id)?)
+    }
+
+    /// Create an STP instance
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_stp(&self, switch_id: SaiOid) -> Result<SaiOid> {
+        let mut stp_oid: SaiOid = NULL_OID;
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(create_fn) = api.create_stp {
+                create_fn(&mut stp_oid, switch_id, 0, std::ptr::null())
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        non_null(stp_oid, "create_stp")
+    }
+
+    /// Remove an STP instance
+    ///
+    /// VLANs using it must be moved to another instance first. The default
+    /// instance is refused without calling SAI.
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_stp(&self, stp_oid: SaiOid) -> Result<()> {
+        if self.default_instance == Some(stp_oid) {
+            return Err(RacoonError::Sai(format!(
+                "STP instance 0x{:x} is the switch default and can't be removed",
+                stp_oid
+            )));
+        }
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(remove_fn) = api.remove_stp {
+                remove_fn(stp_oid)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock;
+    use crate::types::SaiObjectType;
+
+    #[test]
+    fn test_default_instance_is_not_removed() {
+        let switch_id = 0x21000000000000;
+        let default_stp = mock::switch_api()
+            .get_default_stp_instance(switch_id)
+            .unwrap();
+        let api = mock::stp_api()
+            .with_switch_id(switch_id)
+            .with_default_instance(default_stp);
+
+        let stp_oid = api.create().unwrap();
+        assert_eq!(SaiObjectType::from_oid(stp_oid), Some(SaiObjectType::Stp));
+        assert_ne!(stp_oid, default_stp);
+        mock::take_calls();
+
+        assert!(api.remove_stp(default_stp).is_err());
+        api.remove_stp(stp_oid).unwrap();
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "remove_stp");
+        assert_eq!(calls[0].oid, stp_oid);
+    }
+}
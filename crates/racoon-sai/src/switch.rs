@@ -1,11 +1,92 @@
 use crate::bindings::*;
 use crate::constants::*;
+use crate::enums::SwitchOperStatus;
+use crate::oid::{NULL_OID, get_object_list, non_null};
 use crate::status::SaiStatus;
-use crate::types::SaiAttribute;
-use racoon_common::{Result, SaiOid};
+use crate::types::{SaiAttribute, SaiObjectType};
+use racoon_common::{RacoonError, Result, SaiOid};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tracing::{debug, instrument, warn};
+
+/// `sai_query_attribute_capability`, exported by the SAI library itself
+/// rather than through an api table
+pub type SaiQueryAttributeCapabilityFn = unsafe extern "C" fn(
+    switch_id: sai_object_id_t,
+    object_type: sai_object_type_t,
+    attr_id: sai_attr_id_t,
+    attr_capability: *mut sai_attr_capability_t,
+) -> sai_status_t;
+
+/// Operations a platform implements for an attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttrCapability {
+    pub create: bool,
+    pub set: bool,
+    pub get: bool,
+}
+
+impl From<sai_attr_capability_t> for AttrCapability {
+    fn from(capability: sai_attr_capability_t) -> Self {
+        Self {
+            create: capability.create_implemented,
+            set: capability.set_implemented,
+            get: capability.get_implemented,
+        }
+    }
+}
+
+/// Attributes [`SwitchApi::warm_capabilities`] queries for an object type
+fn common_attributes(object_type: SaiObjectType) -> &'static [u32] {
+    match object_type {
+        SaiObjectType::Vlan => &[
+            SAI_VLAN_ATTR_VLAN_ID,
+            SAI_VLAN_ATTR_LEARN_DISABLE,
+            SAI_VLAN_ATTR_MAX_LEARNED_ADDRESSES,
+            SAI_VLAN_ATTR_STP_INSTANCE,
+            SAI_VLAN_ATTR_INGRESS_ACL,
+            SAI_VLAN_ATTR_EGRESS_ACL,
+        ],
+        SaiObjectType::VlanMember => &[SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE],
+        SaiObjectType::RouterInterface => &[SAI_ROUTER_INTERFACE_ATTR_MTU],
+        SaiObjectType::Port => &[
+            SAI_PORT_ATTR_ADMIN_STATE,
+            SAI_PORT_ATTR_SPEED,
+            SAI_PORT_ATTR_FEC_MODE,
+            SAI_PORT_ATTR_AUTO_NEG_MODE,
+            SAI_PORT_ATTR_INTERFACE_TYPE,
+        ],
+        _ => &[],
+    }
+}
+
+/// Handler for switch oper status changes
+pub type SwitchStateCallback = Box<dyn Fn(SaiOid, SwitchOperStatus) + Send + Sync>;
+
+/// Handler called by [`on_switch_state_change`]
+///
+/// SAI notifications carry no user context, so the handler is process-wide.
+static SWITCH_STATE_CALLBACK: RwLock<Option<SwitchStateCallback>> = RwLock::new(None);
+
+/// `sai_switch_state_change_notification_fn` passed to the SAI library
+unsafe extern "C" fn on_switch_state_change(
+    switch_id: sai_object_id_t,
+    switch_oper_status: sai_switch_oper_status_t,
+) {
+    let status =
+        SwitchOperStatus::from_sai(switch_oper_status as i32).unwrap_or(SwitchOperStatus::Unknown);
+    if let Ok(callback) = SWITCH_STATE_CALLBACK.read()
+        && let Some(callback) = callback.as_ref()
+    {
+        callback(switch_id, status);
+    }
+}
 
 pub struct SwitchApi {
     api_table: *const sai_switch_api_t,
+    query_capability: Option<SaiQueryAttributeCapabilityFn>,
+    /// Capabilities already queried, by switch, object type and attribute id
+    capabilities: RwLock<HashMap<(SaiOid, SaiObjectType, u32), AttrCapability>>,
 }
 
 unsafe impl Send for SwitchApi {}
@@ -13,12 +94,101 @@ unsafe impl Sync for SwitchApi {}
 
 impl SwitchApi {
     pub fn new(api_table: *const sai_switch_api_t) -> Self {
-        Self { api_table }
+        Self {
+            api_table,
+            query_capability: None,
+            capabilities: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Enable attribute capability queries
+    ///
+    /// Pass the library's `sai_query_attribute_capability` when it exports one
+    /// (see [`SaiAdapter::query_attribute_capability_fn`]).
+    ///
+    /// [`SaiAdapter::query_attribute_capability_fn`]: crate::SaiAdapter::query_attribute_capability_fn
+    pub fn with_capability_query(mut self, query: Option<SaiQueryAttributeCapabilityFn>) -> Self {
+        self.query_capability = query;
+        self
+    }
+
+    /// Query whether an attribute can be created, set and read
+    ///
+    /// Returns `NOT_IMPLEMENTED` when the library has no capability query;
+    /// callers should then assume the attribute is supported. Capabilities
+    /// don't change while the library is loaded, so each attribute is only
+    /// queried once per switch and later calls are answered from a cache.
+    pub fn query_attribute_capability(
+        &self,
+        switch_id: SaiOid,
+        object_type: SaiObjectType,
+        attr_id: u32,
+    ) -> Result<AttrCapability> {
+        let key = (switch_id, object_type, attr_id);
+        if let Ok(cache) = self.capabilities.read()
+            && let Some(capability) = cache.get(&key)
+        {
+            return Ok(*capability);
+        }
+
+        let mut capability = sai_attr_capability_t::default();
+
+        let status = match self.query_capability {
+            Some(query_fn) => unsafe {
+                query_fn(switch_id, object_type.to_sai(), attr_id, &mut capability)
+            },
+            None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+        };
+
+        SaiStatus::from(status).to_result()?;
+        let capability = AttrCapability::from(capability);
+        if let Ok(mut cache) = self.capabilities.write() {
+            cache.insert(key, capability);
+        }
+        Ok(capability)
+    }
+
+    /// Whether `switch_id` can set an attribute, assuming it can when the
+    /// capability can't be queried
+    pub fn is_settable(&self, switch_id: SaiOid, object_type: SaiObjectType, attr_id: u32) -> bool {
+        match self.query_attribute_capability(switch_id, object_type, attr_id) {
+            Ok(capability) if !capability.set => {
+                warn!(
+                    "{} attribute {} is not settable on this platform, skipping",
+                    object_type, attr_id
+                );
+                false
+            }
+            Ok(_) => true,
+            Err(e) => {
+                debug!(
+                    "Capability query for {} attribute {} failed: {}",
+                    object_type, attr_id, e
+                );
+                true
+            }
+        }
+    }
+
+    /// Query the capabilities of an object type's commonly programmed
+    /// attributes ahead of time
+    ///
+    /// Returns how many were cached; attributes whose query fails are left
+    /// to be queried again when first set.
+    pub fn warm_capabilities(&self, switch_id: SaiOid, object_type: SaiObjectType) -> usize {
+        common_attributes(object_type)
+            .iter()
+            .filter(|attr_id| {
+                self.query_attribute_capability(switch_id, object_type, **attr_id)
+                    .is_ok()
+            })
+            .count()
     }
 
     /// Create and initialize a switch
+    #[instrument(level = "debug", skip(self))]
     pub fn create_switch(&self, attributes: &[SaiAttribute]) -> Result<SaiOid> {
-        let mut switch_id: SaiOid = 0;
+        let mut switch_id: SaiOid = NULL_OID;
 
         // Convert Rust attributes to C attributes
         let c_attrs: Vec<sai_attribute_t> = attributes
@@ -36,10 +206,11 @@ impl SwitchApi {
         };
 
         SaiStatus::from(status).to_result()?;
-        Ok(switch_id)
+        non_null(switch_id, "create_switch")
     }
 
     /// Remove a switch
+    #[instrument(level = "debug", skip(self))]
     pub fn remove_switch(&self, switch_id: SaiOid) -> Result<()> {
         let status = unsafe {
             let api = &*self.api_table;
@@ -54,6 +225,7 @@ impl SwitchApi {
     }
 
     /// Set switch attribute
+    #[instrument(level = "debug", skip(self))]
     pub fn set_attribute(&self, switch_id: SaiOid, attribute: &SaiAttribute) -> Result<()> {
         let c_attr = unsafe { attribute.to_c_attribute() };
 
@@ -69,8 +241,123 @@ impl SwitchApi {
         SaiStatus::from(status).to_result()
     }
 
+    /// Prepare the switch for a warm shutdown (`SAI_SWITCH_ATTR_RESTART_WARM`)
+    ///
+    /// The library saves its state when the switch is removed or SAI is
+    /// uninitialized, and the hardware keeps forwarding until a warm boot
+    /// restores it.
+    pub fn set_warm_shutdown(&self, switch_id: SaiOid) -> Result<()> {
+        self.set_attribute(
+            switch_id,
+            &SaiAttribute::new_bool(SAI_SWITCH_ATTR_RESTART_WARM, true),
+        )
+    }
+
+    /// Hash ECMP groups use for IPv4, IPv4-in-IPv4 and IPv6 traffic
+    ///
+    /// `SAI_SWITCH_ATTR_ECMP_HASH` itself is the read-only default hash;
+    /// the per-family attributes override it.
+    pub fn set_ecmp_hash(&self, switch_id: SaiOid, hash_oid: SaiOid) -> Result<()> {
+        self.set_hash_attributes(
+            switch_id,
+            hash_oid,
+            [
+                SAI_SWITCH_ATTR_ECMP_HASH_IPV4,
+                SAI_SWITCH_ATTR_ECMP_HASH_IPV4_IN_IPV4,
+                SAI_SWITCH_ATTR_ECMP_HASH_IPV6,
+            ],
+        )
+    }
+
+    /// Hash LAGs use for IPv4, IPv4-in-IPv4 and IPv6 traffic
+    pub fn set_lag_hash(&self, switch_id: SaiOid, hash_oid: SaiOid) -> Result<()> {
+        self.set_hash_attributes(
+            switch_id,
+            hash_oid,
+            [
+                SAI_SWITCH_ATTR_LAG_HASH_IPV4,
+                SAI_SWITCH_ATTR_LAG_HASH_IPV4_IN_IPV4,
+                SAI_SWITCH_ATTR_LAG_HASH_IPV6,
+            ],
+        )
+    }
+
+    fn set_hash_attributes(
+        &self,
+        switch_id: SaiOid,
+        hash_oid: SaiOid,
+        attr_ids: [u32; 3],
+    ) -> Result<()> {
+        for attr_id in attr_ids {
+            self.set_attribute(switch_id, &SaiAttribute::new_oid(attr_id, hash_oid))?;
+        }
+        Ok(())
+    }
+
+    /// Read the switch oper status (`SAI_SWITCH_ATTR_OPER_STATUS`)
+    pub fn get_oper_status(&self, switch_id: SaiOid) -> Result<SwitchOperStatus> {
+        let c_attr = self.get_c_attribute(switch_id, SAI_SWITCH_ATTR_OPER_STATUS)?;
+        let value = unsafe { c_attr.value.s32 };
+
+        SwitchOperStatus::from_sai(value).ok_or_else(|| {
+            RacoonError::InvalidAttribute(format!("unknown switch oper status {}", value))
+        })
+    }
+
+    /// Read the switch's default STP instance (`SAI_SWITCH_ATTR_DEFAULT_STP_INST_ID`)
+    ///
+    /// VLANs belong to it until assigned another instance.
+    pub fn get_default_stp_instance(&self, switch_id: SaiOid) -> Result<SaiOid> {
+        let c_attr = self.get_c_attribute(switch_id, SAI_SWITCH_ATTR_DEFAULT_STP_INST_ID)?;
+        non_null(unsafe { c_attr.value.oid }, "get_default_stp_instance")
+    }
+
+    /// Read the switch's default .1Q bridge (`SAI_SWITCH_ATTR_DEFAULT_1Q_BRIDGE_ID`)
+    ///
+    /// It holds the bridge ports SAI created with the switch.
+    pub fn get_default_1q_bridge(&self, switch_id: SaiOid) -> Result<SaiOid> {
+        let c_attr = self.get_c_attribute(switch_id, SAI_SWITCH_ATTR_DEFAULT_1Q_BRIDGE_ID)?;
+        non_null(unsafe { c_attr.value.oid }, "get_default_1q_bridge")
+    }
+
+    /// Call `callback` whenever the switch oper status changes
+    ///
+    /// Registers the switch state change notification with SAI. The
+    /// callback runs on the SAI library's notification thread and replaces
+    /// any previously registered one.
+    pub fn register_state_change(
+        &self,
+        switch_id: SaiOid,
+        callback: impl Fn(SaiOid, SwitchOperStatus) + Send + Sync + 'static,
+    ) -> Result<()> {
+        if let Ok(mut registered) = SWITCH_STATE_CALLBACK.write() {
+            *registered = Some(Box::new(callback));
+        }
+
+        let notify: sai_switch_state_change_notification_fn = Some(on_switch_state_change);
+        let attr = SaiAttribute::new_pointer(
+            SAI_SWITCH_ATTR_SWITCH_STATE_CHANGE_NOTIFY,
+            notify.map_or(0, |f| f as usize),
+        );
+        self.set_attribute(switch_id, &attr)
+    }
+
+    /// Ports SAI created for the switch (`SAI_SWITCH_ATTR_PORT_LIST`)
+    pub fn get_port_list(&self, switch_id: SaiOid) -> Result<Vec<SaiOid>> {
+        let get_fn = unsafe { (*self.api_table).get_switch_attribute };
+        get_object_list(get_fn, switch_id, SAI_SWITCH_ATTR_PORT_LIST)
+    }
+
     /// Get switch attribute
     pub fn get_attribute(&self, switch_id: SaiOid, attr_id: u32) -> Result<SaiAttribute> {
+        let c_attr = self.get_c_attribute(switch_id, attr_id)?;
+
+        // Convert C attribute back to Rust (simplified for now)
+        // TODO: Properly convert based on attribute type
+        Ok(SaiAttribute::new_u32(attr_id, unsafe { c_attr.value.u32_ }))
+    }
+
+    fn get_c_attribute(&self, switch_id: SaiOid, attr_id: u32) -> Result<sai_attribute_t> {
         let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
         c_attr.id = attr_id;
 
@@ -84,9 +371,152 @@ impl SwitchApi {
         };
 
         SaiStatus::from(status).to_result()?;
+        Ok(c_attr)
+    }
+}
 
-        // Convert C attribute back to Rust (simplified for now)
-        // TODO: Properly convert based on attribute type
-        Ok(SaiAttribute::new_u32(attr_id, unsafe { c_attr.value.u32_ }))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_query_attribute_capability() {
+        let api = mock::switch_api();
+
+        // The mock reports the VLAN id as create-only
+        let capability = api
+            .query_attribute_capability(0, SaiObjectType::Vlan, SAI_VLAN_ATTR_VLAN_ID)
+            .unwrap();
+        assert_eq!(
+            capability,
+            AttrCapability {
+                create: true,
+                set: false,
+                get: true
+            }
+        );
+
+        let capability = api
+            .query_attribute_capability(0, SaiObjectType::Vlan, SAI_VLAN_ATTR_LEARN_DISABLE)
+            .unwrap();
+        assert!(capability.create && capability.set && capability.get);
+
+        // Sets of create-only attributes are skipped before reaching SAI
+        let vlan_api = mock::vlan_api();
+        let vlan_id = SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 10);
+        let learn = SaiAttribute::new_bool(SAI_VLAN_ATTR_LEARN_DISABLE, true);
+        assert!(
+            !vlan_api
+                .set_attribute_if_supported(&api, 0, 1, &vlan_id)
+                .unwrap()
+        );
+        assert!(
+            vlan_api
+                .set_attribute_if_supported(&api, 0, 1, &learn)
+                .unwrap()
+        );
+        let sets = mock::take_calls()
+            .iter()
+            .filter(|c| c.function == "set_vlan_attribute")
+            .count();
+        assert_eq!(sets, 1);
+    }
+
+    #[test]
+    fn test_capability_cached() {
+        let api = mock::switch_api();
+        mock::take_calls();
+
+        let queries = || {
+            mock::take_calls()
+                .iter()
+                .filter(|c| c.function == "sai_query_attribute_capability")
+                .count()
+        };
+
+        let first = api
+            .query_attribute_capability(0, SaiObjectType::Vlan, SAI_VLAN_ATTR_VLAN_ID)
+            .unwrap();
+        let second = api
+            .query_attribute_capability(0, SaiObjectType::Vlan, SAI_VLAN_ATTR_VLAN_ID)
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(queries(), 1);
+
+        // Warming queries only what isn't cached yet
+        let cached = api.warm_capabilities(0, SaiObjectType::Vlan);
+        assert_eq!(cached, common_attributes(SaiObjectType::Vlan).len());
+        assert_eq!(queries(), cached - 1);
+        api.query_attribute_capability(0, SaiObjectType::Vlan, SAI_VLAN_ATTR_LEARN_DISABLE)
+            .unwrap();
+        assert_eq!(queries(), 0);
+
+        // Each switch is queried for itself
+        api.query_attribute_capability(1, SaiObjectType::Vlan, SAI_VLAN_ATTR_VLAN_ID)
+            .unwrap();
+        assert_eq!(queries(), 1);
+    }
+
+    #[test]
+    fn test_warm_shutdown_sets_restart_warm() {
+        let api = mock::switch_api();
+        mock::take_calls();
+
+        api.set_warm_shutdown(0x21000000000000).unwrap();
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "set_switch_attribute");
+        assert_eq!(calls[0].oid, 0x21000000000000);
+        assert_eq!(calls[0].attrs, [(SAI_SWITCH_ATTR_RESTART_WARM, 1)]);
+    }
+
+    #[test]
+    fn test_oper_status() {
+        let api = mock::switch_api();
+        assert_eq!(
+            api.get_oper_status(0x21000000000000).unwrap(),
+            SwitchOperStatus::Up
+        );
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        api.register_state_change(0x21000000000000, move |switch_id, status| {
+            tx.send((switch_id, status)).unwrap();
+        })
+        .unwrap();
+
+        // Fire the registered notification the way the SAI library would
+        let calls = mock::take_calls();
+        let set = calls
+            .iter()
+            .find(|c| c.function == "set_switch_attribute")
+            .unwrap();
+        let (attr_id, ptr) = set.attrs[0];
+        assert_eq!(attr_id, SAI_SWITCH_ATTR_SWITCH_STATE_CHANGE_NOTIFY);
+        let notify: unsafe extern "C" fn(sai_object_id_t, sai_switch_oper_status_t) =
+            unsafe { std::mem::transmute(ptr as usize) };
+        unsafe { notify(0x21000000000000, SAI_SWITCH_OPER_STATUS_DOWN) };
+
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            (0x21000000000000, SwitchOperStatus::Down)
+        );
+    }
+
+    #[test]
+    fn test_query_without_symbol() {
+        let api = SwitchApi::new(Box::leak(Box::new(sai_switch_api_t::default())));
+        assert!(
+            api.query_attribute_capability(0, SaiObjectType::Vlan, SAI_VLAN_ATTR_VLAN_ID)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_port_list_grows_past_initial_capacity() {
+        let api = mock::switch_api();
+        let ports: Vec<SaiOid> = (0..20).map(|i| mock::add_port(&[i], 25000)).collect();
+
+        assert_eq!(api.get_port_list(0).unwrap(), ports);
     }
 }
@@ -0,0 +1,382 @@
+//! VXLAN tunnels for L2 bridging
+//!
+//! A VXLAN tunnel carries VLAN traffic between VTEPs as VNIs. Two tunnel
+//! maps translate between them: the encap map (VLAN to VNI) picks the VNI
+//! sent for a VLAN and the decap map (VNI to VLAN) picks the VLAN of a
+//! received VNI. Each bridged VNI adds one [`VniVlanMapping`] entry to
+//! both maps. A P2MP tunnel termination entry on the local VTEP address
+//! hands received VXLAN packets to the tunnel.
+
+use crate::bindings::*;
+use crate::constants::*;
+pub use crate::enums::TunnelMapType;
+use crate::oid::{NULL_OID, non_null};
+use crate::status::SaiStatus;
+use crate::types::SaiAttribute;
+use racoon_common::{RacoonError, Result, SaiOid, VlanId};
+use std::net::IpAddr;
+use tracing::instrument;
+
+/// Largest VXLAN network identifier; VNIs are 24 bits
+pub const MAX_VNI: u32 = 0xff_ffff;
+
+/// A VNI bridged to a VLAN
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VniVlanMapping {
+    vni: u32,
+    vlan_id: VlanId,
+}
+
+impl VniVlanMapping {
+    /// Map `vni` to `vlan_id`, rejecting VNIs outside 1-16777215
+    pub fn new(vni: u32, vlan_id: VlanId) -> Result<Self> {
+        if !(1..=MAX_VNI).contains(&vni) {
+            return Err(RacoonError::InvalidAttribute(format!(
+                "VNI {} is outside 1-{}",
+                vni, MAX_VNI
+            )));
+        }
+        Ok(Self { vni, vlan_id })
+    }
+
+    pub fn vni(&self) -> u32 {
+        self.vni
+    }
+
+    pub fn vlan_id(&self) -> VlanId {
+        self.vlan_id
+    }
+}
+
+/// Attributes of the entry of `mapping` in `tunnel_map`
+///
+/// The key and value attributes follow the map's direction: a
+/// [`TunnelMapType::VniToVlan`] entry is keyed by the VNI, a
+/// [`TunnelMapType::VlanToVni`] entry by the VLAN id.
+pub fn map_entry_attributes(
+    map_type: TunnelMapType,
+    tunnel_map: SaiOid,
+    mapping: VniVlanMapping,
+) -> [SaiAttribute; 4] {
+    let vni = mapping.vni();
+    let vlan_id = mapping.vlan_id().get();
+    let (key, value) = match map_type {
+        TunnelMapType::VniToVlan => (
+            SaiAttribute::new_u32(SAI_TUNNEL_MAP_ENTRY_ATTR_VNI_ID_KEY, vni),
+            SaiAttribute::new_u16(SAI_TUNNEL_MAP_ENTRY_ATTR_VLAN_ID_VALUE, vlan_id),
+        ),
+        TunnelMapType::VlanToVni => (
+            SaiAttribute::new_u16(SAI_TUNNEL_MAP_ENTRY_ATTR_VLAN_ID_KEY, vlan_id),
+            SaiAttribute::new_u32(SAI_TUNNEL_MAP_ENTRY_ATTR_VNI_ID_VALUE, vni),
+        ),
+    };
+    [
+        SaiAttribute::new_i32(SAI_TUNNEL_MAP_ENTRY_ATTR_TUNNEL_MAP_TYPE, map_type.to_sai()),
+        SaiAttribute::new_oid(SAI_TUNNEL_MAP_ENTRY_ATTR_TUNNEL_MAP, tunnel_map),
+        key,
+        value,
+    ]
+}
+
+/// Tunnels, tunnel maps and tunnel termination entries
+pub struct TunnelApi {
+    api_table: *const sai_tunnel_api_t,
+}
+
+unsafe impl Send for TunnelApi {}
+unsafe impl Sync for TunnelApi {}
+
+impl TunnelApi {
+    pub fn new(api_table: *const sai_tunnel_api_t) -> Self {
+        Self { api_table }
+    }
+
+    /// Create a tunnel map translating in direction `map_type`
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_tunnel_map(&self, switch_id: SaiOid, map_type: TunnelMapType) -> Result<SaiOid> {
+        let attrs = [SaiAttribute::new_i32(
+            SAI_TUNNEL_MAP_ATTR_TYPE,
+            map_type.to_sai(),
+        )];
+        let create_fn = unsafe { (*self.api_table).create_tunnel_map };
+        create(create_fn, "create_tunnel_map", switch_id, &attrs)
+    }
+
+    /// Add `mapping` to `tunnel_map`, which translates in direction `map_type`
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_tunnel_map_entry(
+        &self,
+        switch_id: SaiOid,
+        tunnel_map: SaiOid,
+        map_type: TunnelMapType,
+        mapping: VniVlanMapping,
+    ) -> Result<SaiOid> {
+        let attrs = map_entry_attributes(map_type, tunnel_map, mapping);
+        let create_fn = unsafe { (*self.api_table).create_tunnel_map_entry };
+        create(create_fn, "create_tunnel_map_entry", switch_id, &attrs)
+    }
+
+    /// Create a P2MP VXLAN tunnel sourced from `src_ip`
+    ///
+    /// `underlay_rif` is the router interface the encapsulated packets are
+    /// routed from, usually a loopback.
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_tunnel(
+        &self,
+        switch_id: SaiOid,
+        underlay_rif: SaiOid,
+        src_ip: IpAddr,
+        encap_map: SaiOid,
+        decap_map: SaiOid,
+    ) -> Result<SaiOid> {
+        let attrs = [
+            SaiAttribute::new_i32(SAI_TUNNEL_ATTR_TYPE, SAI_TUNNEL_TYPE_VXLAN as i32),
+            SaiAttribute::new_oid(SAI_TUNNEL_ATTR_UNDERLAY_INTERFACE, underlay_rif),
+            SaiAttribute::new_i32(SAI_TUNNEL_ATTR_PEER_MODE, SAI_TUNNEL_PEER_MODE_P2MP as i32),
+            SaiAttribute::new_ip(SAI_TUNNEL_ATTR_ENCAP_SRC_IP, src_ip),
+            SaiAttribute::new_oid_list(SAI_TUNNEL_ATTR_ENCAP_MAPPERS, vec![encap_map]),
+            SaiAttribute::new_oid_list(SAI_TUNNEL_ATTR_DECAP_MAPPERS, vec![decap_map]),
+        ];
+        let create_fn = unsafe { (*self.api_table).create_tunnel };
+        create(create_fn, "create_tunnel", switch_id, &attrs)
+    }
+
+    /// Terminate VXLAN packets sent to `dst_ip` in `virtual_router` on `tunnel`
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_tunnel_term_table_entry(
+        &self,
+        switch_id: SaiOid,
+        virtual_router: SaiOid,
+        dst_ip: IpAddr,
+        tunnel: SaiOid,
+    ) -> Result<SaiOid> {
+        let attrs = [
+            SaiAttribute::new_oid(SAI_TUNNEL_TERM_TABLE_ENTRY_ATTR_VR_ID, virtual_router),
+            SaiAttribute::new_i32(
+                SAI_TUNNEL_TERM_TABLE_ENTRY_ATTR_TYPE,
+                SAI_TUNNEL_TERM_TABLE_ENTRY_TYPE_P2MP as i32,
+            ),
+            SaiAttribute::new_ip(SAI_TUNNEL_TERM_TABLE_ENTRY_ATTR_DST_IP, dst_ip),
+            SaiAttribute::new_i32(
+                SAI_TUNNEL_TERM_TABLE_ENTRY_ATTR_TUNNEL_TYPE,
+                SAI_TUNNEL_TYPE_VXLAN as i32,
+            ),
+            SaiAttribute::new_oid(SAI_TUNNEL_TERM_TABLE_ENTRY_ATTR_ACTION_TUNNEL_ID, tunnel),
+        ];
+        let create_fn = unsafe { (*self.api_table).create_tunnel_term_table_entry };
+        create(
+            create_fn,
+            "create_tunnel_term_table_entry",
+            switch_id,
+            &attrs,
+        )
+    }
+
+    /// Remove a tunnel map; its entries must be removed first
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_tunnel_map(&self, tunnel_map: SaiOid) -> Result<()> {
+        remove(unsafe { (*self.api_table).remove_tunnel_map }, tunnel_map)
+    }
+
+    /// Remove a tunnel map entry
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_tunnel_map_entry(&self, entry: SaiOid) -> Result<()> {
+        remove(unsafe { (*self.api_table).remove_tunnel_map_entry }, entry)
+    }
+
+    /// Remove a tunnel; its termination entries must be removed first
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_tunnel(&self, tunnel: SaiOid) -> Result<()> {
+        remove(unsafe { (*self.api_table).remove_tunnel }, tunnel)
+    }
+
+    /// Remove a tunnel termination entry
+    #[instrument(level = "debug", skip(self))]
+    pub fn remove_tunnel_term_table_entry(&self, entry: SaiOid) -> Result<()> {
+        remove(
+            unsafe { (*self.api_table).remove_tunnel_term_table_entry },
+            entry,
+        )
+    }
+}
+
+type CreateFn = Option<
+    unsafe extern "C" fn(
+        *mut sai_object_id_t,
+        sai_object_id_t,
+        u32,
+        *const sai_attribute_t,
+    ) -> sai_status_t,
+>;
+
+type RemoveFn = Option<unsafe extern "C" fn(sai_object_id_t) -> sai_status_t>;
+
+/// Create an object with `attrs` through `create_fn`
+fn create(
+    create_fn: CreateFn,
+    function: &str,
+    switch_id: SaiOid,
+    attrs: &[SaiAttribute],
+) -> Result<SaiOid> {
+    let mut oid: SaiOid = NULL_OID;
+
+    let c_attrs: Vec<sai_attribute_t> = attrs
+        .iter()
+        .map(|attr| unsafe { attr.to_c_attribute() })
+        .collect();
+
+    let status = match create_fn {
+        Some(create_fn) => unsafe {
+            create_fn(&mut oid, switch_id, c_attrs.len() as u32, c_attrs.as_ptr())
+        },
+        None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+    };
+
+    SaiStatus::from(status).to_result()?;
+    non_null(oid, function)
+}
+
+/// Remove `oid` through `remove_fn`
+fn remove(remove_fn: RemoveFn, oid: SaiOid) -> Result<()> {
+    let status = match remove_fn {
+        Some(remove_fn) => unsafe { remove_fn(oid) },
+        None => SAI_STATUS_NOT_IMPLEMENTED as sai_status_t,
+    };
+
+    SaiStatus::from(status).to_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+    use crate::types::SaiObjectType;
+    use std::net::Ipv4Addr;
+
+    fn mapping(vni: u32, vlan: u16) -> VniVlanMapping {
+        VniVlanMapping::new(vni, VlanId::new(vlan).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_vni_range() {
+        let vlan = VlanId::new(100).unwrap();
+        assert!(VniVlanMapping::new(0, vlan).is_err());
+        assert!(VniVlanMapping::new(MAX_VNI + 1, vlan).is_err());
+        assert_eq!(VniVlanMapping::new(MAX_VNI, vlan).unwrap().vni(), MAX_VNI);
+    }
+
+    #[test]
+    fn test_map_entry_attributes_follow_direction() {
+        let map = 0x29000000000001;
+        let decap = map_entry_attributes(TunnelMapType::VniToVlan, map, mapping(10100, 100));
+        assert_eq!(
+            decap,
+            [
+                SaiAttribute::new_i32(
+                    SAI_TUNNEL_MAP_ENTRY_ATTR_TUNNEL_MAP_TYPE,
+                    SAI_TUNNEL_MAP_TYPE_VNI_TO_VLAN_ID as i32
+                ),
+                SaiAttribute::new_oid(SAI_TUNNEL_MAP_ENTRY_ATTR_TUNNEL_MAP, map),
+                SaiAttribute::new_u32(SAI_TUNNEL_MAP_ENTRY_ATTR_VNI_ID_KEY, 10100),
+                SaiAttribute::new_u16(SAI_TUNNEL_MAP_ENTRY_ATTR_VLAN_ID_VALUE, 100),
+            ]
+        );
+
+        let encap = map_entry_attributes(TunnelMapType::VlanToVni, map, mapping(10100, 100));
+        assert_eq!(
+            encap[2],
+            SaiAttribute::new_u16(SAI_TUNNEL_MAP_ENTRY_ATTR_VLAN_ID_KEY, 100)
+        );
+        assert_eq!(
+            encap[3],
+            SaiAttribute::new_u32(SAI_TUNNEL_MAP_ENTRY_ATTR_VNI_ID_VALUE, 10100)
+        );
+    }
+
+    #[test]
+    fn test_vxlan_tunnel() {
+        let api = mock::tunnel_api();
+        let switch_id = 0x21000000000000;
+        let vtep = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        let encap = api
+            .create_tunnel_map(switch_id, TunnelMapType::VlanToVni)
+            .unwrap();
+        let decap = api
+            .create_tunnel_map(switch_id, TunnelMapType::VniToVlan)
+            .unwrap();
+        let entry = api
+            .create_tunnel_map_entry(
+                switch_id,
+                decap,
+                TunnelMapType::VniToVlan,
+                mapping(5000, 50),
+            )
+            .unwrap();
+        let tunnel = api
+            .create_tunnel(switch_id, 0x6000000000001, vtep, encap, decap)
+            .unwrap();
+        let term = api
+            .create_tunnel_term_table_entry(switch_id, 0x3000000000001, vtep, tunnel)
+            .unwrap();
+        assert_eq!(
+            SaiObjectType::from_oid(encap),
+            Some(SaiObjectType::TunnelMap)
+        );
+        assert_eq!(
+            SaiObjectType::from_oid(entry),
+            Some(SaiObjectType::TunnelMapEntry)
+        );
+        assert_eq!(SaiObjectType::from_oid(tunnel), Some(SaiObjectType::Tunnel));
+        assert_eq!(
+            SaiObjectType::from_oid(term),
+            Some(SaiObjectType::TunnelTermTableEntry)
+        );
+
+        let calls = mock::take_calls();
+        assert!(calls.iter().all(|call| call.switch_id == switch_id));
+        assert_eq!(
+            calls[1].attrs,
+            [(
+                SAI_TUNNEL_MAP_ATTR_TYPE,
+                SAI_TUNNEL_MAP_TYPE_VNI_TO_VLAN_ID as u64
+            )]
+        );
+        assert_eq!(
+            calls[2].attrs[2..],
+            [
+                (SAI_TUNNEL_MAP_ENTRY_ATTR_VNI_ID_KEY, 5000),
+                (SAI_TUNNEL_MAP_ENTRY_ATTR_VLAN_ID_VALUE, 50)
+            ]
+        );
+        assert_eq!(calls[3].function, "create_tunnel");
+        assert_eq!(
+            calls[3].attrs[..3],
+            [
+                (SAI_TUNNEL_ATTR_TYPE, SAI_TUNNEL_TYPE_VXLAN as u64),
+                (SAI_TUNNEL_ATTR_UNDERLAY_INTERFACE, 0x6000000000001),
+                (SAI_TUNNEL_ATTR_PEER_MODE, SAI_TUNNEL_PEER_MODE_P2MP as u64)
+            ]
+        );
+        assert_eq!(
+            calls[4].attrs.last(),
+            Some(&(SAI_TUNNEL_TERM_TABLE_ENTRY_ATTR_ACTION_TUNNEL_ID, tunnel))
+        );
+
+        api.remove_tunnel_term_table_entry(term).unwrap();
+        api.remove_tunnel(tunnel).unwrap();
+        api.remove_tunnel_map_entry(entry).unwrap();
+        let removed: Vec<_> = mock::take_calls()
+            .into_iter()
+            .map(|call| (call.function, call.oid))
+            .collect();
+        assert_eq!(
+            removed,
+            [
+                ("remove_tunnel_term_table_entry", term),
+                ("remove_tunnel", tunnel),
+                ("remove_tunnel_map_entry", entry)
+            ]
+        );
+    }
+}
@@ -21,8 +21,19 @@ pub enum SaiObjectType {
     Hostif,
     Queue,
     Scheduler,
+    SchedulerGroup,
+    Hash,
     Buffer,
     Mirror,
+    Bridge,
+    BridgePort,
+    HostifTrapGroup,
+    HostifTrap,
+    Stp,
+    Tunnel,
+    TunnelMap,
+    TunnelMapEntry,
+    TunnelTermTableEntry,
 }
 
 impl SaiObjectType {
@@ -44,15 +55,98 @@ impl SaiObjectType {
             SaiObjectType::Hostif => SAI_OBJECT_TYPE_HOSTIF,
             SaiObjectType::Queue => SAI_OBJECT_TYPE_QUEUE,
             SaiObjectType::Scheduler => SAI_OBJECT_TYPE_SCHEDULER,
+            SaiObjectType::SchedulerGroup => SAI_OBJECT_TYPE_SCHEDULER_GROUP,
+            SaiObjectType::Hash => SAI_OBJECT_TYPE_HASH,
             SaiObjectType::Buffer => SAI_OBJECT_TYPE_BUFFER_POOL,
             SaiObjectType::Mirror => SAI_OBJECT_TYPE_MIRROR_SESSION,
+            SaiObjectType::Bridge => SAI_OBJECT_TYPE_BRIDGE,
+            SaiObjectType::BridgePort => SAI_OBJECT_TYPE_BRIDGE_PORT,
+            SaiObjectType::HostifTrapGroup => SAI_OBJECT_TYPE_HOSTIF_TRAP_GROUP,
+            SaiObjectType::HostifTrap => SAI_OBJECT_TYPE_HOSTIF_TRAP,
+            SaiObjectType::Stp => SAI_OBJECT_TYPE_STP,
+            SaiObjectType::Tunnel => SAI_OBJECT_TYPE_TUNNEL,
+            SaiObjectType::TunnelMap => SAI_OBJECT_TYPE_TUNNEL_MAP,
+            SaiObjectType::TunnelMapEntry => SAI_OBJECT_TYPE_TUNNEL_MAP_ENTRY,
+            SaiObjectType::TunnelTermTableEntry => SAI_OBJECT_TYPE_TUNNEL_TERM_TABLE_ENTRY,
         }
     }
 
-    pub fn from_oid(_oid: SaiOid) -> Option<Self> {
-        // SAI OID encoding includes object type in upper bits
-        // This is a simplified version - actual implementation would decode OID
-        Some(SaiObjectType::Port) // TODO: Implement proper OID decoding
+    pub fn from_sai(object_type: sai_object_type_t) -> Option<Self> {
+        match object_type {
+            SAI_OBJECT_TYPE_SWITCH => Some(SaiObjectType::Switch),
+            SAI_OBJECT_TYPE_PORT => Some(SaiObjectType::Port),
+            SAI_OBJECT_TYPE_VLAN => Some(SaiObjectType::Vlan),
+            SAI_OBJECT_TYPE_VLAN_MEMBER => Some(SaiObjectType::VlanMember),
+            SAI_OBJECT_TYPE_FDB_ENTRY => Some(SaiObjectType::FdbEntry),
+            SAI_OBJECT_TYPE_LAG => Some(SaiObjectType::Lag),
+            SAI_OBJECT_TYPE_LAG_MEMBER => Some(SaiObjectType::LagMember),
+            SAI_OBJECT_TYPE_ROUTER_INTERFACE => Some(SaiObjectType::RouterInterface),
+            SAI_OBJECT_TYPE_ROUTE_ENTRY => Some(SaiObjectType::RouteEntry),
+            SAI_OBJECT_TYPE_NEIGHBOR_ENTRY => Some(SaiObjectType::NeighborEntry),
+            SAI_OBJECT_TYPE_NEXT_HOP => Some(SaiObjectType::NextHop),
+            SAI_OBJECT_TYPE_NEXT_HOP_GROUP => Some(SaiObjectType::NextHopGroup),
+            SAI_OBJECT_TYPE_ACL_TABLE => Some(SaiObjectType::Acl),
+            SAI_OBJECT_TYPE_HOSTIF => Some(SaiObjectType::Hostif),
+            SAI_OBJECT_TYPE_QUEUE => Some(SaiObjectType::Queue),
+            SAI_OBJECT_TYPE_SCHEDULER => Some(SaiObjectType::Scheduler),
+            SAI_OBJECT_TYPE_SCHEDULER_GROUP => Some(SaiObjectType::SchedulerGroup),
+            SAI_OBJECT_TYPE_HASH => Some(SaiObjectType::Hash),
+            SAI_OBJECT_TYPE_BUFFER_POOL => Some(SaiObjectType::Buffer),
+            SAI_OBJECT_TYPE_MIRROR_SESSION => Some(SaiObjectType::Mirror),
+            SAI_OBJECT_TYPE_BRIDGE => Some(SaiObjectType::Bridge),
+            SAI_OBJECT_TYPE_BRIDGE_PORT => Some(SaiObjectType::BridgePort),
+            SAI_OBJECT_TYPE_HOSTIF_TRAP_GROUP => Some(SaiObjectType::HostifTrapGroup),
+            SAI_OBJECT_TYPE_HOSTIF_TRAP => Some(SaiObjectType::HostifTrap),
+            SAI_OBJECT_TYPE_STP => Some(SaiObjectType::Stp),
+            SAI_OBJECT_TYPE_TUNNEL => Some(SaiObjectType::Tunnel),
+            SAI_OBJECT_TYPE_TUNNEL_MAP => Some(SaiObjectType::TunnelMap),
+            SAI_OBJECT_TYPE_TUNNEL_MAP_ENTRY => Some(SaiObjectType::TunnelMapEntry),
+            SAI_OBJECT_TYPE_TUNNEL_TERM_TABLE_ENTRY => Some(SaiObjectType::TunnelTermTableEntry),
+            _ => None,
+        }
+    }
+
+    /// Name of the SAI constant (`SAI_OBJECT_TYPE_VLAN_MEMBER`)
+    pub fn sai_name(&self) -> &'static str {
+        match self {
+            SaiObjectType::Switch => "SAI_OBJECT_TYPE_SWITCH",
+            SaiObjectType::Port => "SAI_OBJECT_TYPE_PORT",
+            SaiObjectType::Vlan => "SAI_OBJECT_TYPE_VLAN",
+            SaiObjectType::VlanMember => "SAI_OBJECT_TYPE_VLAN_MEMBER",
+            SaiObjectType::FdbEntry => "SAI_OBJECT_TYPE_FDB_ENTRY",
+            SaiObjectType::Lag => "SAI_OBJECT_TYPE_LAG",
+            SaiObjectType::LagMember => "SAI_OBJECT_TYPE_LAG_MEMBER",
+            SaiObjectType::RouterInterface => "SAI_OBJECT_TYPE_ROUTER_INTERFACE",
+            SaiObjectType::RouteEntry => "SAI_OBJECT_TYPE_ROUTE_ENTRY",
+            SaiObjectType::NeighborEntry => "SAI_OBJECT_TYPE_NEIGHBOR_ENTRY",
+            SaiObjectType::NextHop => "SAI_OBJECT_TYPE_NEXT_HOP",
+            SaiObjectType::NextHopGroup => "SAI_OBJECT_TYPE_NEXT_HOP_GROUP",
+            SaiObjectType::Acl => "SAI_OBJECT_TYPE_ACL_TABLE",
+            SaiObjectType::Hostif => "SAI_OBJECT_TYPE_HOSTIF",
+            SaiObjectType::Queue => "SAI_OBJECT_TYPE_QUEUE",
+            SaiObjectType::Scheduler => "SAI_OBJECT_TYPE_SCHEDULER",
+            SaiObjectType::SchedulerGroup => "SAI_OBJECT_TYPE_SCHEDULER_GROUP",
+            SaiObjectType::Hash => "SAI_OBJECT_TYPE_HASH",
+            SaiObjectType::Buffer => "SAI_OBJECT_TYPE_BUFFER_POOL",
+            SaiObjectType::Mirror => "SAI_OBJECT_TYPE_MIRROR_SESSION",
+            SaiObjectType::Bridge => "SAI_OBJECT_TYPE_BRIDGE",
+            SaiObjectType::BridgePort => "SAI_OBJECT_TYPE_BRIDGE_PORT",
+            SaiObjectType::HostifTrapGroup => "SAI_OBJECT_TYPE_HOSTIF_TRAP_GROUP",
+            SaiObjectType::HostifTrap => "SAI_OBJECT_TYPE_HOSTIF_TRAP",
+            SaiObjectType::Stp => "SAI_OBJECT_TYPE_STP",
+            SaiObjectType::Tunnel => "SAI_OBJECT_TYPE_TUNNEL",
+            SaiObjectType::TunnelMap => "SAI_OBJECT_TYPE_TUNNEL_MAP",
+            SaiObjectType::TunnelMapEntry => "SAI_OBJECT_TYPE_TUNNEL_MAP_ENTRY",
+            SaiObjectType::TunnelTermTableEntry => "SAI_OBJECT_TYPE_TUNNEL_TERM_TABLE_ENTRY",
+        }
+    }
+
+    /// Decode the object type from an OID
+    ///
+    /// OIDs carry the object type in bits 48-55 (sairedis layout).
+    pub fn from_oid(oid: SaiOid) -> Option<Self> {
+        let object_type = (oid >> crate::oid::OID_OBJECT_TYPE_SHIFT) & 0xff;
+        Self::from_sai(object_type as sai_object_type_t)
     }
 }
 
@@ -75,21 +169,34 @@ impl fmt::Display for SaiObjectType {
             SaiObjectType::Hostif => "HOSTIF",
             SaiObjectType::Queue => "QUEUE",
             SaiObjectType::Scheduler => "SCHEDULER",
+            SaiObjectType::SchedulerGroup => "SCHEDULER_GROUP",
+            SaiObjectType::Hash => "HASH",
             SaiObjectType::Buffer => "BUFFER",
             SaiObjectType::Mirror => "MIRROR",
+            SaiObjectType::Bridge => "BRIDGE",
+            SaiObjectType::BridgePort => "BRIDGE_PORT",
+            SaiObjectType::HostifTrapGroup => "HOSTIF_TRAP_GROUP",
+            SaiObjectType::HostifTrap => "HOSTIF_TRAP",
+            SaiObjectType::Stp => "STP",
+            SaiObjectType::Tunnel => "TUNNEL",
+            SaiObjectType::TunnelMap => "TUNNEL_MAP",
+            SaiObjectType::TunnelMapEntry => "TUNNEL_MAP_ENTRY",
+            SaiObjectType::TunnelTermTableEntry => "TUNNEL_TERM_TABLE_ENTRY",
         };
         write!(f, "{}", s)
     }
 }
 
 /// SAI Attribute wrapper
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SaiAttribute {
     pub id: u32,
     pub value: SaiAttributeValue,
 }
 
-#[derive(Debug, Clone)]
+/// Attribute value; no variant holds a float, so values can key sets and
+/// maps and lists compare element by element
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SaiAttributeValue {
     Bool(bool),
     U8(u8),
@@ -97,14 +204,82 @@ pub enum SaiAttributeValue {
     U32(u32),
     U64(u64),
     I32(i32),
+    U32List(Vec<u32>),
+    /// List of enum values (`s32list`)
+    S32List(Vec<i32>),
     OidList(Vec<SaiOid>),
     Oid(SaiOid),
     MacAddress([u8; 6]),
     IpAddress([u8; 4]),
     Ipv6Address([u8; 16]),
+    /// Address of a callback or buffer (`sai_pointer_t`)
+    Pointer(usize),
+    /// NUL-terminated string of up to 31 bytes (`chardata`)
+    CharData(String),
+}
+
+impl fmt::Display for SaiAttributeValue {
+    /// sairedis string form: OIDs as `oid:0x...`, lists as `count:a,b`,
+    /// MACs as `AA:BB:...`, numbers in decimal
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list<T>(f: &mut fmt::Formatter<'_>, items: &[T], item: fn(&T) -> String) -> fmt::Result {
+            let items: Vec<String> = items.iter().map(item).collect();
+            write!(f, "{}:{}", items.len(), items.join(","))
+        }
+
+        match self {
+            SaiAttributeValue::Bool(v) => write!(f, "{}", v),
+            SaiAttributeValue::U8(v) => write!(f, "{}", v),
+            SaiAttributeValue::U16(v) => write!(f, "{}", v),
+            SaiAttributeValue::U32(v) => write!(f, "{}", v),
+            SaiAttributeValue::U64(v) => write!(f, "{}", v),
+            SaiAttributeValue::I32(v) => write!(f, "{}", v),
+            SaiAttributeValue::U32List(values) => list(f, values, |v| v.to_string()),
+            SaiAttributeValue::S32List(values) => list(f, values, |v| v.to_string()),
+            SaiAttributeValue::OidList(oids) => list(f, oids, |oid| format!("oid:0x{:x}", oid)),
+            SaiAttributeValue::Oid(oid) => write!(f, "oid:0x{:x}", oid),
+            SaiAttributeValue::MacAddress(mac) => {
+                let bytes: Vec<String> = mac.iter().map(|b| format!("{:02X}", b)).collect();
+                write!(f, "{}", bytes.join(":"))
+            }
+            SaiAttributeValue::IpAddress(ip) => write!(f, "{}", std::net::Ipv4Addr::from(*ip)),
+            SaiAttributeValue::Ipv6Address(ip) => write!(f, "{}", std::net::Ipv6Addr::from(*ip)),
+            SaiAttributeValue::Pointer(ptr) => write!(f, "ptr:0x{:x}", ptr),
+            SaiAttributeValue::CharData(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// An attribute rendered with its symbolic name, see [`SaiAttribute::fmt_with_names`]
+pub struct NamedAttribute<'a> {
+    attr: &'a SaiAttribute,
+    object_type: SaiObjectType,
+}
+
+impl fmt::Display for NamedAttribute<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match crate::attr_names::attr_name(self.object_type, self.attr.id) {
+            Some(name) => write!(f, "{}={}", name, self.attr.value),
+            None => write!(
+                f,
+                "{} attribute {}={}",
+                self.object_type, self.attr.id, self.attr.value
+            ),
+        }
+    }
 }
 
 impl SaiAttribute {
+    /// Render as `SAI_VLAN_ATTR_VLAN_ID=100` for logs
+    ///
+    /// Ids the bindings don't name are shown numerically.
+    pub fn fmt_with_names(&self, object_type: SaiObjectType) -> NamedAttribute<'_> {
+        NamedAttribute {
+            attr: self,
+            object_type,
+        }
+    }
+
     pub fn new_bool(id: u32, value: bool) -> Self {
         Self {
             id,
@@ -147,6 +322,51 @@ impl SaiAttribute {
         }
     }
 
+    pub fn new_u32_list(id: u32, value: Vec<u32>) -> Self {
+        Self {
+            id,
+            value: SaiAttributeValue::U32List(value),
+        }
+    }
+
+    pub fn new_s32_list(id: u32, value: Vec<i32>) -> Self {
+        Self {
+            id,
+            value: SaiAttributeValue::S32List(value),
+        }
+    }
+
+    pub fn new_oid_list(id: u32, value: Vec<SaiOid>) -> Self {
+        Self {
+            id,
+            value: SaiAttributeValue::OidList(value),
+        }
+    }
+
+    pub fn new_ip(id: u32, value: std::net::IpAddr) -> Self {
+        Self {
+            id,
+            value: match value {
+                std::net::IpAddr::V4(ip) => SaiAttributeValue::IpAddress(ip.octets()),
+                std::net::IpAddr::V6(ip) => SaiAttributeValue::Ipv6Address(ip.octets()),
+            },
+        }
+    }
+
+    pub fn new_pointer(id: u32, value: usize) -> Self {
+        Self {
+            id,
+            value: SaiAttributeValue::Pointer(value),
+        }
+    }
+
+    pub fn new_chardata(id: u32, value: &str) -> Self {
+        Self {
+            id,
+            value: SaiAttributeValue::CharData(value.to_string()),
+        }
+    }
+
     /// Convert Rust attribute to C SAI attribute
     ///
     /// # Safety
@@ -154,6 +374,8 @@ impl SaiAttribute {
     /// This function creates raw pointers and accesses C unions. The caller must ensure
     /// that the returned `sai_attribute_t` is used correctly with the SAI API and that
     /// the attribute value matches the expected type for the attribute ID.
+    /// List values point into `self`, so the returned attribute must not
+    /// outlive this `SaiAttribute`.
     pub unsafe fn to_c_attribute(&self) -> sai_attribute_t {
         unsafe {
             let mut attr: sai_attribute_t = std::mem::zeroed();
@@ -192,14 +414,206 @@ impl SaiAttribute {
                     attr.value.ipaddr.addr_family = SAI_IP_ADDR_FAMILY_IPV6;
                     attr.value.ipaddr.addr.ip6.copy_from_slice(ip);
                 }
-                SaiAttributeValue::OidList(_) => {
-                    // OID lists require heap allocation and special handling
-                    // This would need to be implemented based on specific use case
-                    todo!("OID list conversion not yet implemented");
+                SaiAttributeValue::Pointer(v) => {
+                    attr.value.ptr = *v as sai_pointer_t;
+                }
+                SaiAttributeValue::CharData(value) => {
+                    // Longer strings are truncated, keeping the terminating NUL
+                    let len = value.len().min(attr.value.chardata.len() - 1);
+                    for (dst, src) in attr.value.chardata.iter_mut().zip(&value.as_bytes()[..len]) {
+                        *dst = *src as _;
+                    }
+                }
+                SaiAttributeValue::U32List(list) => {
+                    attr.value.u32list.count = list.len() as u32;
+                    attr.value.u32list.list = list.as_ptr() as *mut u32;
+                }
+                SaiAttributeValue::S32List(list) => {
+                    attr.value.s32list.count = list.len() as u32;
+                    attr.value.s32list.list = list.as_ptr() as *mut i32;
+                }
+                SaiAttributeValue::OidList(list) => {
+                    attr.value.objlist.count = list.len() as u32;
+                    attr.value.objlist.list = list.as_ptr() as *mut sai_object_id_t;
+                }
+            }
+
+            attr
+        }
+    }
+
+    /// Entries a list value has room for; `None` for scalars
+    pub(crate) fn list_capacity(&self) -> Option<usize> {
+        match &self.value {
+            SaiAttributeValue::U32List(list) => Some(list.len()),
+            SaiAttributeValue::S32List(list) => Some(list.len()),
+            SaiAttributeValue::OidList(list) => Some(list.len()),
+            _ => None,
+        }
+    }
+
+    /// Grow or shrink a list value to `len` entries
+    pub(crate) fn resize_list(&mut self, len: usize) {
+        match &mut self.value {
+            SaiAttributeValue::U32List(list) => list.resize(len, 0),
+            SaiAttributeValue::S32List(list) => list.resize(len, 0),
+            SaiAttributeValue::OidList(list) => list.resize(len, 0),
+            _ => {}
+        }
+    }
+
+    /// C attribute for a `get_*_attribute` call filling in this value
+    ///
+    /// # Safety
+    ///
+    /// List values lend their buffers, so the returned attribute must not
+    /// outlive this `SaiAttribute` or be used after it is modified.
+    pub(crate) unsafe fn c_attribute_for_read(&mut self) -> sai_attribute_t {
+        unsafe {
+            let mut attr: sai_attribute_t = std::mem::zeroed();
+            attr.id = self.id;
+
+            match &mut self.value {
+                SaiAttributeValue::U32List(list) => {
+                    attr.value.u32list.count = list.len() as u32;
+                    attr.value.u32list.list = list.as_mut_ptr();
+                }
+                SaiAttributeValue::S32List(list) => {
+                    attr.value.s32list.count = list.len() as u32;
+                    attr.value.s32list.list = list.as_mut_ptr();
+                }
+                SaiAttributeValue::OidList(list) => {
+                    attr.value.objlist.count = list.len() as u32;
+                    attr.value.objlist.list = list.as_mut_ptr();
                 }
+                _ => {}
             }
 
             attr
         }
     }
+
+    /// Entries SAI reported for a list value of `c_attr`; `None` for scalars
+    ///
+    /// # Safety
+    ///
+    /// `c_attr` must come from [`c_attribute_for_read`](Self::c_attribute_for_read)
+    /// on this attribute.
+    pub(crate) unsafe fn reported_list_len(&self, c_attr: &sai_attribute_t) -> Option<usize> {
+        unsafe {
+            match &self.value {
+                SaiAttributeValue::U32List(_) => Some(c_attr.value.u32list.count as usize),
+                SaiAttributeValue::S32List(_) => Some(c_attr.value.s32list.count as usize),
+                SaiAttributeValue::OidList(_) => Some(c_attr.value.objlist.count as usize),
+                _ => None,
+            }
+        }
+    }
+
+    /// Take the value SAI wrote into `c_attr`, decoded as this value's type
+    ///
+    /// # Safety
+    ///
+    /// `c_attr` must come from [`c_attribute_for_read`](Self::c_attribute_for_read)
+    /// on this attribute, and SAI must have filled it in successfully.
+    pub(crate) unsafe fn read_c_attribute(&mut self, c_attr: &sai_attribute_t) {
+        unsafe {
+            let value = &c_attr.value;
+            match &mut self.value {
+                SaiAttributeValue::Bool(v) => *v = value.booldata,
+                SaiAttributeValue::U8(v) => *v = value.u8_,
+                SaiAttributeValue::U16(v) => *v = value.u16_,
+                SaiAttributeValue::U32(v) => *v = value.u32_,
+                SaiAttributeValue::U64(v) => *v = value.u64_,
+                SaiAttributeValue::I32(v) => *v = value.s32,
+                SaiAttributeValue::Oid(v) => *v = value.oid,
+                SaiAttributeValue::MacAddress(mac) => mac.copy_from_slice(&value.mac),
+                SaiAttributeValue::IpAddress(ip) => *ip = value.ipaddr.addr.ip4.to_be_bytes(),
+                SaiAttributeValue::Ipv6Address(ip) => ip.copy_from_slice(&value.ipaddr.addr.ip6),
+                SaiAttributeValue::Pointer(v) => *v = value.ptr as usize,
+                SaiAttributeValue::CharData(s) => {
+                    let bytes: Vec<u8> = value
+                        .chardata
+                        .iter()
+                        .take_while(|c| **c != 0)
+                        .map(|c| *c as u8)
+                        .collect();
+                    *s = String::from_utf8_lossy(&bytes).into_owned();
+                }
+                SaiAttributeValue::U32List(list) => list.truncate(value.u32list.count as usize),
+                SaiAttributeValue::S32List(list) => list.truncate(value.s32list.count as usize),
+                SaiAttributeValue::OidList(list) => list.truncate(value.objlist.count as usize),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt_with_names() {
+        let attr = SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 100);
+        assert_eq!(
+            attr.fmt_with_names(SaiObjectType::Vlan).to_string(),
+            "SAI_VLAN_ATTR_VLAN_ID=100"
+        );
+
+        let attr = SaiAttribute::new_oid(SAI_VLAN_MEMBER_ATTR_VLAN_ID, 0x26000000000001);
+        assert_eq!(
+            attr.fmt_with_names(SaiObjectType::VlanMember).to_string(),
+            "SAI_VLAN_MEMBER_ATTR_VLAN_ID=oid:0x26000000000001"
+        );
+
+        let attr = SaiAttribute::new_u32(0x7fff, 1);
+        assert_eq!(
+            attr.fmt_with_names(SaiObjectType::Vlan).to_string(),
+            "VLAN attribute 32767=1"
+        );
+    }
+
+    #[test]
+    fn test_attribute_equality() {
+        use std::collections::HashSet;
+
+        let oids = SaiAttribute::new_oid_list(SAI_VLAN_MEMBER_ATTR_VLAN_ID, vec![1, 2]);
+        assert_eq!(
+            oids,
+            SaiAttribute::new_oid_list(SAI_VLAN_MEMBER_ATTR_VLAN_ID, vec![1, 2])
+        );
+        assert_ne!(
+            oids,
+            SaiAttribute::new_oid_list(SAI_VLAN_MEMBER_ATTR_VLAN_ID, vec![2, 1])
+        );
+        assert_ne!(
+            oids,
+            SaiAttribute::new_oid_list(SAI_VLAN_MEMBER_ATTR_VLAN_ID, vec![1])
+        );
+
+        // Same id, same number, different variant
+        assert_ne!(
+            SaiAttribute::new_u32(SAI_VLAN_ATTR_VLAN_ID, 100),
+            SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 100)
+        );
+        // Same value, different id
+        assert_ne!(
+            SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 100),
+            SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID + 1, 100)
+        );
+
+        let set: HashSet<SaiAttribute> = [
+            SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 100),
+            SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 100),
+            SaiAttribute::new_chardata(SAI_VLAN_ATTR_VLAN_ID, "Vlan100"),
+            SaiAttribute::new_s32_list(SAI_VLAN_ATTR_VLAN_ID, vec![-1, 3]),
+            SaiAttribute::new_s32_list(SAI_VLAN_ATTR_VLAN_ID, vec![-1, 3]),
+            SaiAttribute::new_bool(SAI_VLAN_ATTR_VLAN_ID, true),
+            oids.clone(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(&oids));
+    }
 }
@@ -1,11 +1,25 @@
 use crate::bindings::*;
 use crate::constants::*;
+pub use crate::enums::{AclStage, VlanTaggingMode};
+use crate::oid::{NULL_OID, SaiGetObjectKeyFn, get_attributes, get_object_keys, non_null};
 use crate::status::SaiStatus;
-use crate::types::SaiAttribute;
-use racoon_common::{Result, SaiOid, VlanId};
+use crate::switch::SwitchApi;
+use crate::types::{SaiAttribute, SaiAttributeValue, SaiObjectType};
+use racoon_common::{RacoonError, Result, SaiOid, VlanId};
+use tracing::{debug, instrument, warn};
+
+/// A VLAN's state as SAI reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VlanInfo {
+    pub vlan_id: u16,
+    pub members: Vec<SaiOid>,
+    pub stp_instance: SaiOid,
+}
 
 pub struct VlanApi {
     api_table: *const sai_vlan_api_t,
+    switch_id: Option<SaiOid>,
+    get_object_key: Option<SaiGetObjectKeyFn>,
 }
 
 unsafe impl Send for VlanApi {}
@@ -13,12 +27,56 @@ unsafe impl Sync for VlanApi {}
 
 impl VlanApi {
     pub fn new(api_table: *const sai_vlan_api_t) -> Self {
-        Self { api_table }
+        Self {
+            api_table,
+            switch_id: None,
+            get_object_key: None,
+        }
+    }
+
+    /// Enumerate VLANs through the library's `sai_get_object_key`
+    ///
+    /// Pass it when the library exports one (see
+    /// [`SaiAdapter::get_object_key_fn`]); without it
+    /// [`find_vlan`](Self::find_vlan) is not implemented.
+    ///
+    /// [`SaiAdapter::get_object_key_fn`]: crate::SaiAdapter::get_object_key_fn
+    pub fn with_object_key_query(mut self, get_object_key: Option<SaiGetObjectKeyFn>) -> Self {
+        self.get_object_key = get_object_key;
+        self
+    }
+
+    /// Store the switch used by [`create`](Self::create) and
+    /// [`create_member`](Self::create_member)
+    pub fn with_switch_id(mut self, switch_id: SaiOid) -> Self {
+        self.switch_id = Some(switch_id);
+        self
+    }
+
+    /// Create a VLAN on the stored switch
+    pub fn create(&self, vlan_id: VlanId) -> Result<SaiOid> {
+        self.create_vlan(stored_switch_id(self.switch_id)?, vlan_id)
+    }
+
+    /// Create a VLAN member on the stored switch
+    pub fn create_member(
+        &self,
+        vlan_oid: SaiOid,
+        bridge_port_id: SaiOid,
+        tagging_mode: VlanTaggingMode,
+    ) -> Result<SaiOid> {
+        self.create_vlan_member(
+            stored_switch_id(self.switch_id)?,
+            vlan_oid,
+            bridge_port_id,
+            tagging_mode,
+        )
     }
 
     /// Create a VLAN
+    #[instrument(level = "debug", skip(self))]
     pub fn create_vlan(&self, switch_id: SaiOid, vlan_id: VlanId) -> Result<SaiOid> {
-        let mut vlan_oid: SaiOid = 0;
+        let mut vlan_oid: SaiOid = NULL_OID;
 
         let attr = SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, vlan_id.get());
         let c_attr = unsafe { attr.to_c_attribute() };
@@ -33,10 +91,11 @@ impl VlanApi {
         };
 
         SaiStatus::from(status).to_result()?;
-        Ok(vlan_oid)
+        non_null(vlan_oid, "create_vlan")
     }
 
     /// Remove a VLAN
+    #[instrument(level = "debug", skip(self))]
     pub fn remove_vlan(&self, vlan_oid: SaiOid) -> Result<()> {
         let status = unsafe {
             let api = &*self.api_table;
@@ -50,7 +109,24 @@ impl VlanApi {
         SaiStatus::from(status).to_result()
     }
 
+    /// Attributes a VLAN member is created with
+    pub fn member_attributes(
+        vlan_oid: SaiOid,
+        bridge_port_id: SaiOid,
+        tagging_mode: VlanTaggingMode,
+    ) -> Vec<SaiAttribute> {
+        vec![
+            SaiAttribute::new_oid(SAI_VLAN_MEMBER_ATTR_VLAN_ID, vlan_oid),
+            SaiAttribute::new_oid(SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID, bridge_port_id),
+            SaiAttribute::new_i32(
+                SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+                tagging_mode.to_sai(),
+            ),
+        ]
+    }
+
     /// Create a VLAN member (add port to VLAN)
+    #[instrument(level = "debug", skip(self))]
     pub fn create_vlan_member(
         &self,
         switch_id: SaiOid,
@@ -58,13 +134,15 @@ impl VlanApi {
         bridge_port_id: SaiOid,
         tagging_mode: VlanTaggingMode,
     ) -> Result<SaiOid> {
-        let mut member_oid: SaiOid = 0;
+        let mut member_oid: SaiOid = NULL_OID;
 
-        let attrs = [
-            SaiAttribute::new_oid(SAI_VLAN_MEMBER_ATTR_VLAN_ID, vlan_oid),
-            SaiAttribute::new_oid(SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID, bridge_port_id),
-            SaiAttribute::new_i32(SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE, tagging_mode as i32),
-        ];
+        let attrs = Self::member_attributes(vlan_oid, bridge_port_id, tagging_mode);
+        for attr in &attrs {
+            debug!(
+                "create_vlan_member {}",
+                attr.fmt_with_names(SaiObjectType::VlanMember)
+            );
+        }
 
         let c_attrs: Vec<sai_attribute_t> = attrs
             .iter()
@@ -86,10 +164,94 @@ impl VlanApi {
         };
 
         SaiStatus::from(status).to_result()?;
-        Ok(member_oid)
+        non_null(member_oid, "create_vlan_member")
+    }
+
+    /// Create VLAN members in one bulk call
+    ///
+    /// `specs` are `(vlan_oid, bridge_port_id, tagging_mode)`. The result
+    /// has one entry per spec, in order, so a member SAI rejects doesn't
+    /// hide the ones it created. Without a bulk entry point the members
+    /// are created one by one.
+    #[instrument(level = "debug", skip(self))]
+    pub fn create_vlan_members_bulk(
+        &self,
+        switch_id: SaiOid,
+        specs: &[(SaiOid, SaiOid, VlanTaggingMode)],
+    ) -> Result<Vec<Result<SaiOid>>> {
+        if specs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let c_attrs: Vec<Vec<sai_attribute_t>> = specs
+            .iter()
+            .map(|&(vlan_oid, bridge_port_id, tagging_mode)| {
+                Self::member_attributes(vlan_oid, bridge_port_id, tagging_mode)
+                    .iter()
+                    .map(|attr| unsafe { attr.to_c_attribute() })
+                    .collect()
+            })
+            .collect();
+        let attr_counts: Vec<u32> = c_attrs.iter().map(|attrs| attrs.len() as u32).collect();
+        let mut attr_lists: Vec<*const sai_attribute_t> =
+            c_attrs.iter().map(|attrs| attrs.as_ptr()).collect();
+        let mut member_oids = vec![NULL_OID; specs.len()];
+        let mut statuses = vec![SAI_STATUS_SUCCESS as sai_status_t; specs.len()];
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(bulk_fn) = api.create_vlan_members {
+                bulk_fn(
+                    switch_id,
+                    specs.len() as u32,
+                    attr_counts.as_ptr(),
+                    attr_lists.as_mut_ptr(),
+                    SAI_BULK_OP_ERROR_MODE_IGNORE_ERROR,
+                    member_oids.as_mut_ptr(),
+                    statuses.as_mut_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        if status == SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            || status == SAI_STATUS_NOT_SUPPORTED as sai_status_t
+        {
+            debug!(
+                "Bulk VLAN member create unavailable, creating {} members one by one",
+                specs.len()
+            );
+            return Ok(specs
+                .iter()
+                .map(|&(vlan_oid, bridge_port_id, tagging_mode)| {
+                    self.create_vlan_member(switch_id, vlan_oid, bridge_port_id, tagging_mode)
+                })
+                .collect());
+        }
+
+        // The call status only says whether every member succeeded
+        let results: Vec<Result<SaiOid>> = member_oids
+            .into_iter()
+            .zip(statuses)
+            .map(|(member_oid, status)| {
+                SaiStatus::from(status).to_result()?;
+                non_null(member_oid, "create_vlan_members")
+            })
+            .collect();
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        if failed > 0 {
+            warn!(
+                "Bulk VLAN member create: {} of {} members failed",
+                failed,
+                specs.len()
+            );
+        }
+        Ok(results)
     }
 
     /// Remove a VLAN member
+    #[instrument(level = "debug", skip(self))]
     pub fn remove_vlan_member(&self, member_oid: SaiOid) -> Result<()> {
         let status = unsafe {
             let api = &*self.api_table;
@@ -103,7 +265,55 @@ impl VlanApi {
         SaiStatus::from(status).to_result()
     }
 
+    /// Read a member's tagging mode (`SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE`)
+    pub fn get_member_tagging_mode(&self, member_oid: SaiOid) -> Result<VlanTaggingMode> {
+        let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
+        c_attr.id = SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE;
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(get_fn) = api.get_vlan_member_attribute {
+                get_fn(member_oid, 1, &mut c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        let value = unsafe { c_attr.value.s32 };
+
+        VlanTaggingMode::from_sai(value).ok_or_else(|| {
+            RacoonError::InvalidAttribute(format!("unknown VLAN tagging mode {}", value))
+        })
+    }
+
+    /// Change a member's tagging mode in place
+    #[instrument(level = "debug", skip(self))]
+    pub fn set_member_tagging_mode(
+        &self,
+        member_oid: SaiOid,
+        tagging_mode: VlanTaggingMode,
+    ) -> Result<()> {
+        let attr = SaiAttribute::new_i32(
+            SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+            tagging_mode.to_sai(),
+        );
+        let c_attr = unsafe { attr.to_c_attribute() };
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(set_fn) = api.set_vlan_member_attribute {
+                set_fn(member_oid, &c_attr)
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
+
     /// Set VLAN attribute
+    #[instrument(level = "debug", skip(self))]
     pub fn set_attribute(&self, vlan_oid: SaiOid, attribute: &SaiAttribute) -> Result<()> {
         let c_attr = unsafe { attribute.to_c_attribute() };
 
@@ -119,6 +329,73 @@ impl VlanApi {
         SaiStatus::from(status).to_result()
     }
 
+    /// Assign the VLAN to STP instance `stp_oid`
+    ///
+    /// Pass the switch's default instance to move the VLAN back to it.
+    pub fn set_stp_instance(&self, vlan_oid: SaiOid, stp_oid: SaiOid) -> Result<()> {
+        self.set_attribute(
+            vlan_oid,
+            &SaiAttribute::new_oid(SAI_VLAN_ATTR_STP_INSTANCE, stp_oid),
+        )
+    }
+
+    /// Bind an ACL table or group to the VLAN at `stage`
+    ///
+    /// Replaces any ACL already bound at that stage.
+    pub fn bind_acl(&self, vlan_oid: SaiOid, acl_oid: SaiOid, stage: AclStage) -> Result<()> {
+        let attr_id = match stage {
+            AclStage::Ingress => SAI_VLAN_ATTR_INGRESS_ACL,
+            AclStage::Egress => SAI_VLAN_ATTR_EGRESS_ACL,
+        };
+        self.set_attribute(vlan_oid, &SaiAttribute::new_oid(attr_id, acl_oid))
+    }
+
+    /// Remove the ACL bound to the VLAN at `stage`
+    pub fn unbind_acl(&self, vlan_oid: SaiOid, stage: AclStage) -> Result<()> {
+        self.bind_acl(vlan_oid, NULL_OID, stage)
+    }
+
+    /// Set a VLAN attribute unless the platform reports it as not settable
+    ///
+    /// Returns false when the set was skipped. If the capability can't be
+    /// queried the set is attempted anyway.
+    pub fn set_attribute_if_supported(
+        &self,
+        switch_api: &SwitchApi,
+        switch_id: SaiOid,
+        vlan_oid: SaiOid,
+        attribute: &SaiAttribute,
+    ) -> Result<bool> {
+        if !switch_api.is_settable(switch_id, SaiObjectType::Vlan, attribute.id) {
+            return Ok(false);
+        }
+        self.set_attribute(vlan_oid, attribute)?;
+        Ok(true)
+    }
+
+    /// Change a member's tagging mode in place unless the platform reports
+    /// it as not settable
+    ///
+    /// Returns false when the set was skipped, like
+    /// [`set_attribute_if_supported`](Self::set_attribute_if_supported).
+    pub fn set_member_tagging_mode_if_supported(
+        &self,
+        switch_api: &SwitchApi,
+        switch_id: SaiOid,
+        member_oid: SaiOid,
+        tagging_mode: VlanTaggingMode,
+    ) -> Result<bool> {
+        if !switch_api.is_settable(
+            switch_id,
+            SaiObjectType::VlanMember,
+            SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+        ) {
+            return Ok(false);
+        }
+        self.set_member_tagging_mode(member_oid, tagging_mode)?;
+        Ok(true)
+    }
+
     /// Get VLAN attribute
     pub fn get_attribute(&self, vlan_oid: SaiOid, attr_id: u32) -> Result<SaiAttribute> {
         let mut c_attr: sai_attribute_t = unsafe { std::mem::zeroed() };
@@ -138,11 +415,343 @@ impl VlanApi {
         // TODO: Properly convert based on attribute type
         Ok(SaiAttribute::new_u16(attr_id, unsafe { c_attr.value.u16_ }))
     }
+
+    /// Find the VLAN with id `vlan_id` on `switch_id`
+    ///
+    /// For VLANs created without their OID being kept, e.g. by a create
+    /// interrupted by a crash.
+    pub fn find_vlan(&self, switch_id: SaiOid, vlan_id: VlanId) -> Result<Option<SaiOid>> {
+        for vlan_oid in get_object_keys(self.get_object_key, switch_id, SaiObjectType::Vlan)? {
+            let attr = self.get_attribute(vlan_oid, SAI_VLAN_ATTR_VLAN_ID)?;
+            if matches!(attr.value, SaiAttributeValue::U16(id) if id == vlan_id.get()) {
+                return Ok(Some(vlan_oid));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Read several VLAN attributes in one call
+    ///
+    /// The values of `attrs` give the type each attribute is decoded as.
+    pub fn get_attributes(
+        &self,
+        vlan_oid: SaiOid,
+        attrs: &[SaiAttribute],
+    ) -> Result<Vec<SaiAttribute>> {
+        let get_fn = unsafe { (*self.api_table).get_vlan_attribute };
+        get_attributes(get_fn, vlan_oid, attrs)
+    }
+
+    /// Read a VLAN's id, members and STP instance in one call
+    pub fn get_vlan_info(&self, vlan_oid: SaiOid) -> Result<VlanInfo> {
+        let attrs = self.get_attributes(
+            vlan_oid,
+            &[
+                SaiAttribute::new_u16(SAI_VLAN_ATTR_VLAN_ID, 0),
+                SaiAttribute::new_oid_list(SAI_VLAN_ATTR_MEMBER_LIST, Vec::new()),
+                SaiAttribute::new_oid(SAI_VLAN_ATTR_STP_INSTANCE, NULL_OID),
+            ],
+        )?;
+
+        match <[SaiAttribute; 3]>::try_from(attrs).map(|attrs| attrs.map(|attr| attr.value)) {
+            Ok(
+                [
+                    SaiAttributeValue::U16(vlan_id),
+                    SaiAttributeValue::OidList(members),
+                    SaiAttributeValue::Oid(stp_instance),
+                ],
+            ) => Ok(VlanInfo {
+                vlan_id,
+                members,
+                stp_instance,
+            }),
+            _ => Err(RacoonError::Sai(format!(
+                "unexpected attributes read from VLAN 0x{:x}",
+                vlan_oid
+            ))),
+        }
+    }
+
+    /// Get VLAN statistics
+    pub fn get_stats(&self, vlan_oid: SaiOid, counter_ids: &[sai_vlan_stat_t]) -> Result<Vec<u64>> {
+        let mut counters = vec![0u64; counter_ids.len()];
+
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(get_stats_fn) = api.get_vlan_stats {
+                get_stats_fn(
+                    vlan_oid,
+                    counter_ids.len() as u32,
+                    counter_ids.as_ptr(),
+                    counters.as_mut_ptr(),
+                )
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()?;
+        Ok(counters)
+    }
+
+    /// Clear VLAN statistics
+    pub fn clear_stats(&self, vlan_oid: SaiOid, counter_ids: &[sai_vlan_stat_t]) -> Result<()> {
+        let status = unsafe {
+            let api = &*self.api_table;
+            if let Some(clear_stats_fn) = api.clear_vlan_stats {
+                clear_stats_fn(vlan_oid, counter_ids.len() as u32, counter_ids.as_ptr())
+            } else {
+                SAI_STATUS_NOT_IMPLEMENTED as sai_status_t
+            }
+        };
+
+        SaiStatus::from(status).to_result()
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum VlanTaggingMode {
-    Untagged = SAI_VLAN_TAGGING_MODE_UNTAGGED as isize,
-    Tagged = SAI_VLAN_TAGGING_MODE_TAGGED as isize,
-    Priority = SAI_VLAN_TAGGING_MODE_PRIORITY_TAGGED as isize,
+/// Switch id set with `with_switch_id`, or an error for wrappers without one
+pub(crate) fn stored_switch_id(switch_id: Option<SaiOid>) -> Result<SaiOid> {
+    switch_id.ok_or_else(|| {
+        RacoonError::Sai(
+            "no switch id stored; use with_switch_id or pass it explicitly".to_string(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn test_stored_switch_id() {
+        let api = mock::vlan_api().with_switch_id(0x21000000000000);
+
+        let vlan_oid = api.create(VlanId::new(10).unwrap()).unwrap();
+        api.create_member(vlan_oid, 0x1, VlanTaggingMode::Untagged)
+            .unwrap();
+        // Explicit-switch methods still override the stored id
+        api.create_vlan(0x21000000000001, VlanId::new(20).unwrap())
+            .unwrap();
+
+        let switches: Vec<SaiOid> = mock::take_calls().iter().map(|c| c.switch_id).collect();
+        assert_eq!(
+            switches,
+            vec![0x21000000000000, 0x21000000000000, 0x21000000000001]
+        );
+
+        // Without a stored switch the short forms fail instead of using 0
+        assert!(mock::vlan_api().create(VlanId::new(30).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_find_vlan_by_id() {
+        let switch_id = 0x21000000000000;
+        let api = mock::vlan_api();
+        // More VLANs than the first enumeration buffer holds
+        let vlans: Vec<SaiOid> = (1..=20)
+            .map(|id| {
+                api.create_vlan(switch_id, VlanId::new(id).unwrap())
+                    .unwrap()
+            })
+            .collect();
+
+        let found = api.find_vlan(switch_id, VlanId::new(17).unwrap()).unwrap();
+        assert_eq!(found, Some(vlans[16]));
+        assert_eq!(
+            api.find_vlan(switch_id, VlanId::new(100).unwrap()).unwrap(),
+            None
+        );
+
+        // A library without sai_get_object_key can't enumerate
+        let api = VlanApi::new(Box::leak(Box::new(mock::vlan_api_table())));
+        assert!(api.find_vlan(switch_id, VlanId::new(17).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_bind_acl() {
+        let api = mock::vlan_api();
+        let vlan_oid = 0x26000000000001;
+        let acl_oid = 0x7000000000001;
+
+        api.bind_acl(vlan_oid, acl_oid, AclStage::Ingress).unwrap();
+        api.unbind_acl(vlan_oid, AclStage::Egress).unwrap();
+
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 2);
+        assert!(
+            calls
+                .iter()
+                .all(|call| call.function == "set_vlan_attribute")
+        );
+        assert!(calls.iter().all(|call| call.oid == vlan_oid));
+        assert_eq!(calls[0].attrs, vec![(SAI_VLAN_ATTR_INGRESS_ACL, acl_oid)]);
+        assert_eq!(calls[1].attrs, vec![(SAI_VLAN_ATTR_EGRESS_ACL, 0)]);
+    }
+
+    #[test]
+    fn test_set_stp_instance() {
+        let switch_id = 0x21000000000000;
+        let api = mock::vlan_api().with_switch_id(switch_id);
+        let stp = mock::stp_api().with_switch_id(switch_id);
+        let vlan_oid = api.create(VlanId::new(100).unwrap()).unwrap();
+        let stp_oid = stp.create().unwrap();
+        let default_stp = mock::switch_api()
+            .get_default_stp_instance(switch_id)
+            .unwrap();
+        mock::take_calls();
+
+        api.set_stp_instance(vlan_oid, stp_oid).unwrap();
+        api.set_stp_instance(vlan_oid, default_stp).unwrap();
+
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|call| call.oid == vlan_oid));
+        assert_eq!(calls[0].function, "set_vlan_attribute");
+        assert_eq!(calls[0].attrs, vec![(SAI_VLAN_ATTR_STP_INSTANCE, stp_oid)]);
+        assert_eq!(
+            calls[1].attrs,
+            vec![(SAI_VLAN_ATTR_STP_INSTANCE, default_stp)]
+        );
+    }
+
+    #[test]
+    fn test_member_tagging_mode_read_back() {
+        let api = mock::vlan_api().with_switch_id(0x21000000000000);
+        let vlan_oid = api.create(VlanId::new(10).unwrap()).unwrap();
+        let member = api
+            .create_member(vlan_oid, 0x1, VlanTaggingMode::Priority)
+            .unwrap();
+
+        assert_eq!(
+            api.get_member_tagging_mode(member).unwrap(),
+            VlanTaggingMode::Priority
+        );
+        api.set_member_tagging_mode(member, VlanTaggingMode::Tagged)
+            .unwrap();
+        assert_eq!(
+            api.get_member_tagging_mode(member).unwrap(),
+            VlanTaggingMode::Tagged
+        );
+
+        api.remove_vlan_member(member).unwrap();
+        assert!(api.get_member_tagging_mode(member).is_err());
+    }
+
+    #[test]
+    fn test_vlan_info_read_in_one_call() {
+        let switch_id = 0x21000000000000;
+        let api = mock::vlan_api().with_switch_id(switch_id);
+        let vlan_oid = api.create(VlanId::new(100).unwrap()).unwrap();
+        let members: Vec<SaiOid> = (1..=3)
+            .map(|port| {
+                api.create_member(vlan_oid, 0x3a000000000000 + port, VlanTaggingMode::Tagged)
+                    .unwrap()
+            })
+            .collect();
+        mock::take_calls();
+
+        let info = api.get_vlan_info(vlan_oid).unwrap();
+        assert_eq!(info.vlan_id, 100);
+        assert_eq!(info.members, members);
+        assert_eq!(
+            info.stp_instance,
+            mock::attr_value(vlan_oid, SAI_VLAN_ATTR_STP_INSTANCE)
+        );
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "get_vlan_attribute");
+        assert_eq!(calls[0].attrs, [(3, 0)]);
+
+        // A member list larger than the first buffer is read again in full
+        for port in 4..=20 {
+            api.create_member(vlan_oid, 0x3a000000000000 + port, VlanTaggingMode::Tagged)
+                .unwrap();
+        }
+        assert_eq!(api.get_vlan_info(vlan_oid).unwrap().members.len(), 20);
+    }
+
+    #[test]
+    fn test_create_members_bulk() {
+        let switch_id = 0x21000000000000;
+        let api = mock::vlan_api().with_switch_id(switch_id);
+        let vlan_oid = api.create(VlanId::new(100).unwrap()).unwrap();
+        let specs = [
+            (vlan_oid, 0x3a000000000001, VlanTaggingMode::Tagged),
+            (vlan_oid, 0x3a000000000002, VlanTaggingMode::Untagged),
+            (vlan_oid, 0x3a000000000003, VlanTaggingMode::Tagged),
+        ];
+        mock::take_calls();
+
+        let members = api.create_vlan_members_bulk(switch_id, &specs).unwrap();
+        let members: Vec<SaiOid> = members.into_iter().map(|m| m.unwrap()).collect();
+        assert_eq!(members.len(), 3);
+        assert_eq!(
+            api.get_member_tagging_mode(members[1]).unwrap(),
+            VlanTaggingMode::Untagged
+        );
+        let calls = mock::take_calls();
+        assert_eq!(calls[0].function, "create_vlan_members");
+        assert_eq!(calls[0].attrs, vec![(3, 0)]);
+
+        // A rejected member doesn't fail the others
+        let specs = [
+            (vlan_oid, 0x3a000000000004, VlanTaggingMode::Tagged),
+            (vlan_oid, NULL_OID, VlanTaggingMode::Tagged),
+        ];
+        let results = api.create_vlan_members_bulk(switch_id, &specs).unwrap();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+
+    #[test]
+    fn test_create_members_bulk_falls_back_to_loop() {
+        let table = sai_vlan_api_t {
+            create_vlan_members: None,
+            ..mock::vlan_api_table()
+        };
+        let api = VlanApi::new(&table);
+        let specs = [
+            (0x26000000000001, 0x3a000000000001, VlanTaggingMode::Tagged),
+            (0x26000000000001, 0x3a000000000002, VlanTaggingMode::Tagged),
+        ];
+        mock::take_calls();
+
+        let results = api
+            .create_vlan_members_bulk(0x21000000000000, &specs)
+            .unwrap();
+        assert!(results.iter().all(|result| result.is_ok()));
+        let calls = mock::take_calls();
+        assert_eq!(calls.len(), 2);
+        assert!(
+            calls
+                .iter()
+                .all(|call| call.function == "create_vlan_member")
+        );
+    }
+
+    /// A buggy SAI: reports success but never writes the new OID
+    unsafe extern "C" fn create_vlan_without_oid(
+        _vlan_id: *mut sai_object_id_t,
+        _switch_id: sai_object_id_t,
+        _attr_count: u32,
+        _attr_list: *const sai_attribute_t,
+    ) -> sai_status_t {
+        SAI_STATUS_SUCCESS as sai_status_t
+    }
+
+    #[test]
+    fn test_null_oid_on_success_rejected() {
+        let table = sai_vlan_api_t {
+            create_vlan: Some(create_vlan_without_oid),
+            ..mock::vlan_api_table()
+        };
+        let api = VlanApi::new(&table);
+
+        let err = api
+            .create_vlan(0x21000000000000, VlanId::new(100).unwrap())
+            .unwrap_err();
+        assert!(matches!(err, RacoonError::Sai(_)));
+        assert!(err.to_string().contains("null object id"));
+    }
 }
@@ -0,0 +1,69 @@
+//! Programs 50k FDB entries through the FDB agent against the mock SAI and
+//! reports the time taken and the memory used to track them.
+
+use racoon_common::{MacAddress, VlanId};
+use racoon_db_client::DbClient;
+use racoon_sai::mock;
+use racoon_syncd::{FdbEntry, FdbSync, ObjectRegistry, SwitchContext};
+use std::sync::Arc;
+use std::time::Instant;
+
+const ENTRIES: u32 = 50_000;
+const VLANS: u16 = 10;
+
+fn mac(i: u32) -> MacAddress {
+    let b = i.to_be_bytes();
+    MacAddress::new([0x02, 0x00, b[0], b[1], b[2], b[3]])
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    // DbClient connects lazily; programming entries directly never uses it
+    let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+    let registry = Arc::new(ObjectRegistry::new());
+    for vlan in 1..=VLANS {
+        registry.register_vlan(
+            VlanId::new(100 + vlan).unwrap(),
+            0x26000000000000 + u64::from(vlan),
+        );
+    }
+    registry.register_bridge_port("Ethernet0", 0x3a000000000001);
+    let fdb_sync = FdbSync::new(
+        db_client,
+        Arc::new(mock::fdb_api()),
+        SwitchContext::new(0x21000000000000, 0),
+    )
+    .with_object_registry(registry);
+
+    let entry = FdbEntry {
+        port: "Ethernet0".to_string(),
+        entry_type: "dynamic".to_string(),
+        packet_action: None,
+    };
+    let vlan_of = |i: u32| VlanId::new(101 + (i % u32::from(VLANS)) as u16).unwrap();
+
+    let started = Instant::now();
+    for i in 0..ENTRIES {
+        fdb_sync.program_entry(mac(i), vlan_of(i), &entry).unwrap();
+    }
+    let programmed = started.elapsed();
+    let memory = fdb_sync.memory_bytes();
+    assert_eq!(fdb_sync.entry_count(), ENTRIES as usize);
+    mock::take_calls();
+
+    let started = Instant::now();
+    for i in 0..ENTRIES {
+        fdb_sync.remove_entry(mac(i), vlan_of(i)).unwrap();
+    }
+    let removed = started.elapsed();
+    mock::take_calls();
+
+    println!("fdb_sync: {} entries", ENTRIES);
+    println!(
+        "  tracked in {} bytes ({:.1} bytes/entry)",
+        memory,
+        memory as f64 / f64::from(ENTRIES)
+    );
+    println!("  programmed in {:?}", programmed);
+    println!("  removed in {:?}", removed);
+}
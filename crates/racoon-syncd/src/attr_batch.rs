@@ -0,0 +1,180 @@
+//! Batched SAI attribute sets
+//!
+//! Producers often change several attributes of one object in quick
+//! succession, e.g. a port's speed, then MTU, then admin state. Setting
+//! each as it arrives costs a SAI call apiece and can flap the link
+//! between them. [`AttributeBatcher`] collects changes over a window and
+//! applies each object's attributes together once per window.
+
+use crate::clock::{self, Clock, TokioClock};
+use racoon_common::{Result, SaiOid};
+use racoon_sai::SaiAttribute;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tracing::warn;
+
+/// Window used when none is configured
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(50);
+
+/// Attribute changes waiting for the next apply cycle
+type Pending = Vec<(SaiOid, Vec<SaiAttribute>)>;
+
+/// Collects attribute changes and applies them once per window
+#[derive(Debug)]
+pub struct AttributeBatcher {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    /// Objects in order of their first change, each with its latest values
+    pending: Mutex<Pending>,
+}
+
+impl AttributeBatcher {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            clock: Arc::new(TokioClock),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Override the time source, e.g. with a mock clock in tests
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Pending> {
+        // Pending changes stay consistent even if a holder panicked
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queue `attr` for `oid`, replacing a queued value of the same attribute
+    pub fn set(&self, oid: SaiOid, attr: SaiAttribute) {
+        let mut pending = self.lock();
+        let attrs = match pending.iter_mut().position(|(queued, _)| *queued == oid) {
+            Some(index) => &mut pending[index].1,
+            None => {
+                pending.push((oid, Vec::new()));
+                &mut pending.last_mut().expect("just pushed").1
+            }
+        };
+        match attrs.iter_mut().find(|queued| queued.id == attr.id) {
+            Some(queued) => *queued = attr,
+            None => attrs.push(attr),
+        }
+    }
+
+    /// Number of objects with queued changes
+    pub fn pending(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Apply everything queued now, calling `apply` once per object
+    pub fn flush<F>(&self, mut apply: F)
+    where
+        F: FnMut(SaiOid, &[SaiAttribute]) -> Result<()>,
+    {
+        let pending = std::mem::take(&mut *self.lock());
+        for (oid, attrs) in pending {
+            if let Err(e) = apply(oid, &attrs) {
+                warn!(
+                    "Failed to apply {} attributes to 0x{:x}: {}",
+                    attrs.len(),
+                    oid,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Flush once per window, forever
+    pub async fn run<F>(&self, mut apply: F)
+    where
+        F: FnMut(SaiOid, &[SaiAttribute]) -> Result<()>,
+    {
+        clock::every(&*self.clock, self.window, || {
+            self.flush(&mut apply);
+            std::future::ready(())
+        })
+        .await
+    }
+}
+
+impl Default for AttributeBatcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_BATCH_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use racoon_sai::bindings::*;
+
+    const PORT: SaiOid = 0x1000000000001;
+
+    /// Let spawned tasks run until they block again
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rapid_changes_collapse_into_one_apply() {
+        let clock = Arc::new(MockClock::new());
+        let batcher =
+            Arc::new(AttributeBatcher::new(Duration::from_millis(100)).with_clock(clock.clone()));
+        let applied = Arc::new(Mutex::new(Vec::new()));
+
+        let task = tokio::spawn({
+            let batcher = batcher.clone();
+            let applied = applied.clone();
+            async move {
+                batcher
+                    .run(|oid, attrs| {
+                        applied.lock().unwrap().push((oid, attrs.to_vec()));
+                        Ok(())
+                    })
+                    .await
+            }
+        });
+        settle().await;
+
+        batcher.set(PORT, SaiAttribute::new_u32(SAI_PORT_ATTR_SPEED, 25000));
+        batcher.set(PORT, SaiAttribute::new_u32(SAI_PORT_ATTR_MTU, 9100));
+        batcher.set(
+            PORT,
+            SaiAttribute::new_bool(SAI_PORT_ATTR_ADMIN_STATE, true),
+        );
+        batcher.set(PORT, SaiAttribute::new_u32(SAI_PORT_ATTR_SPEED, 100000));
+        assert_eq!(batcher.pending(), 1);
+
+        clock.advance(Duration::from_millis(99));
+        settle().await;
+        assert!(applied.lock().unwrap().is_empty());
+
+        clock.advance(Duration::from_millis(1));
+        settle().await;
+        assert_eq!(
+            *applied.lock().unwrap(),
+            vec![(
+                PORT,
+                vec![
+                    SaiAttribute::new_u32(SAI_PORT_ATTR_SPEED, 100000),
+                    SaiAttribute::new_u32(SAI_PORT_ATTR_MTU, 9100),
+                    SaiAttribute::new_bool(SAI_PORT_ATTR_ADMIN_STATE, true),
+                ]
+            )]
+        );
+        assert_eq!(batcher.pending(), 0);
+
+        // Nothing queued, nothing applied
+        clock.advance(Duration::from_millis(100));
+        settle().await;
+        assert_eq!(applied.lock().unwrap().len(), 1);
+
+        task.abort();
+    }
+}
@@ -0,0 +1,348 @@
+//! Append-only audit file of SAI mutations
+//!
+//! Every object the VLAN, LAG and FDB agents create in or remove from SAI, and
+//! every attribute they set on one (tagging modes, admin states, LAG member
+//! enables), is appended to a file as one JSON line for offline analysis of
+//! what was done to the hardware. Creates and removes use the record format
+//! of the intent log; sets add the attribute and its new value:
+//!
+//! ```json
+//! {"op":"create","object_type":"SAI_OBJECT_TYPE_VLAN","key":"Vlan100","oid":"0x26000000000001","ts":1700000000000}
+//! {"op":"set","object_type":"SAI_OBJECT_TYPE_PORT","key":"Ethernet0","oid":"0x1000000000001","attr":"admin_status","value":"down","ts":1700000000000}
+//! ```
+//!
+//! The file is written directly rather than through tracing, so it keeps
+//! every record whatever the log level or output. Once a record would take
+//! it past `max_bytes` it is rotated: `<path>` becomes `<path>.1`, older
+//! files shift up and the oldest beyond `keep` is deleted.
+//!
+//! Records are written by a dedicated thread, so the agents programming SAI
+//! never wait on the disk. Its queue is bounded; records arriving while it
+//! is full are dropped and counted.
+
+use crate::intent_log::{IntentOp, SaiIntent};
+use racoon_common::config::AuditConfig;
+use racoon_common::metrics::unix_millis;
+use racoon_common::{Result, SaiOid};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use tracing::{info, warn};
+
+/// Records queued for the writer thread before new ones are dropped
+const QUEUE_CAPACITY: usize = 4096;
+
+/// What an audit record reports was done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOp {
+    Create,
+    Remove,
+    Set,
+}
+
+impl From<IntentOp> for AuditOp {
+    fn from(op: IntentOp) -> Self {
+        match op {
+            IntentOp::Create => Self::Create,
+            IntentOp::Remove => Self::Remove,
+        }
+    }
+}
+
+/// One SAI mutation, as written to the audit file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub op: AuditOp,
+    /// SAI object type name, e.g. `SAI_OBJECT_TYPE_VLAN`
+    pub object_type: String,
+    /// Key of the object in its APPL_DB table, e.g. `Vlan100`
+    pub key: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "racoon_common::hex_oid::option"
+    )]
+    pub oid: Option<SaiOid>,
+    /// Attribute a set changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attr: Option<String>,
+    /// Value a set gave the attribute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// When the mutation was made, Unix milliseconds
+    pub ts: u64,
+}
+
+impl AuditRecord {
+    /// Set of `attr` to `value` on an object
+    pub fn set(
+        object_type: &str,
+        key: &str,
+        oid: SaiOid,
+        attr: &str,
+        value: impl ToString,
+    ) -> Self {
+        Self {
+            op: AuditOp::Set,
+            object_type: object_type.to_string(),
+            key: key.to_string(),
+            oid: Some(oid),
+            attr: Some(attr.to_string()),
+            value: Some(value.to_string()),
+            ts: unix_millis(),
+        }
+    }
+}
+
+impl From<&SaiIntent> for AuditRecord {
+    fn from(intent: &SaiIntent) -> Self {
+        Self {
+            op: intent.op.into(),
+            object_type: intent.object_type.clone(),
+            key: intent.key.clone(),
+            oid: intent.oid,
+            attr: None,
+            value: None,
+            ts: intent.ts,
+        }
+    }
+}
+
+impl From<SaiIntent> for AuditRecord {
+    fn from(intent: SaiIntent) -> Self {
+        Self::from(&intent)
+    }
+}
+
+/// Open audit file and the bytes written to it
+struct AuditFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: u32,
+    file: File,
+    size: u64,
+}
+
+impl AuditFile {
+    fn open(path: PathBuf, max_bytes: u64, keep: u32) -> Result<Self> {
+        let (file, size) = open_append(&path)?;
+        Ok(Self {
+            path,
+            max_bytes,
+            keep,
+            file,
+            size,
+        })
+    }
+
+    /// Append a record, rotating the file first if it would grow past
+    /// `max_bytes`
+    fn append(&mut self, record: &AuditRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+            (self.file, self.size) = open_append(&self.path)?;
+        }
+        self.file.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `<path>.N` to `<path>.N+1` and `<path>` to `<path>.1`
+    fn rotate(&self) -> Result<()> {
+        if self.keep == 0 {
+            std::fs::remove_file(&self.path)?;
+            return Ok(());
+        }
+        for n in (1..self.keep).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))?;
+        info!("Rotated audit log {}", self.path.display());
+        Ok(())
+    }
+
+    fn rotated(&self, n: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        path.into()
+    }
+}
+
+fn open_append(path: &Path) -> Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+/// Size-rotated JSON-lines file of SAI mutations
+///
+/// Dropping it waits for the queued records to be written.
+pub struct AuditLog {
+    path: PathBuf,
+    sender: Option<SyncSender<AuditRecord>>,
+    writer: Option<JoinHandle<()>>,
+    /// Records dropped because the queue was full
+    dropped: AtomicU64,
+}
+
+impl AuditLog {
+    /// Open `path` for appending and start its writer thread
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64, keep: u32) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = AuditFile::open(path.clone(), max_bytes, keep)?;
+
+        let (sender, receiver) = mpsc::sync_channel::<AuditRecord>(QUEUE_CAPACITY);
+        let writer = std::thread::Builder::new()
+            .name("audit-log".to_string())
+            .spawn(move || {
+                for record in receiver {
+                    if let Err(e) = file.append(&record) {
+                        // A full disk must not stop the hardware being programmed
+                        warn!(
+                            "Failed to append to audit log {}: {}",
+                            file.path.display(),
+                            e
+                        );
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            path,
+            sender: Some(sender),
+            writer: Some(writer),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Audit log configured in the `[audit]` section, if it has a path
+    pub fn from_config(config: &AuditConfig) -> Result<Option<Self>> {
+        config
+            .path
+            .as_ref()
+            .map(|path| Self::open(path, config.max_bytes, config.keep))
+            .transpose()
+    }
+
+    /// Queue a record for the writer thread
+    ///
+    /// Never blocks: a record that finds the queue full is dropped.
+    pub fn record(&self, record: impl Into<AuditRecord>) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        match sender.try_send(record.into()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(record)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                // Warn on the first drop and then ever more rarely
+                if dropped.is_power_of_two() {
+                    warn!(
+                        "Audit log {} queue full, dropped {} records (latest {})",
+                        self.path.display(),
+                        dropped,
+                        record.key
+                    );
+                }
+            }
+            Err(TrySendError::Disconnected(record)) => warn!(
+                "Audit log {} writer stopped, dropping record of {}",
+                self.path.display(),
+                record.key
+            ),
+        }
+    }
+
+    /// Records dropped so far because the writer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for AuditLog {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer finish the queue and exit
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appends_lines_and_rotates() {
+        let dir = std::env::temp_dir().join(format!("racoon-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(dir.join("audit.jsonl.1"));
+
+        let create =
+            SaiIntent::create("SAI_OBJECT_TYPE_VLAN", "Vlan100").with_oid(0x26000000000064);
+        let remove = SaiIntent::remove("SAI_OBJECT_TYPE_VLAN", "Vlan100", 0x26000000000064);
+        let line_len = serde_json::to_vec(&create).unwrap().len() as u64 + 1;
+
+        // Room for two records but not a third; dropping the log waits for
+        // the writer
+        let max_bytes = line_len * 2 + 1;
+        let audit = AuditLog::open(&path, max_bytes, 1).unwrap();
+        audit.record(&create);
+        audit.record(&remove);
+        let audit_dropped = audit.dropped();
+        drop(audit);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<SaiIntent> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records, [create.clone(), remove]);
+        assert_eq!(audit_dropped, 0);
+
+        // Reopening continues from the size on disk
+        let audit = AuditLog::open(&path, max_bytes, 1).unwrap();
+        audit.record(&create);
+        drop(audit);
+        let rotated = std::fs::read_to_string(dir.join("audit.jsonl.1")).unwrap();
+        assert_eq!(rotated, content);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_set_records_carry_attribute_and_value() {
+        let record = AuditRecord::set(
+            "SAI_OBJECT_TYPE_PORT",
+            "Ethernet0",
+            0x1000000000001,
+            "admin_status",
+            "down",
+        );
+        let json: serde_json::Value = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["op"], "set");
+        assert_eq!(json["attr"], "admin_status");
+        assert_eq!(json["value"], "down");
+
+        // Creates and removes keep the intent log's format
+        let create =
+            SaiIntent::create("SAI_OBJECT_TYPE_LAG", "PortChannel1").with_oid(0x2000000000001);
+        let json = serde_json::to_value(AuditRecord::from(&create)).unwrap();
+        assert_eq!(json, serde_json::to_value(&create).unwrap());
+    }
+}
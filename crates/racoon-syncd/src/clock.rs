@@ -0,0 +1,149 @@
+//! Time source of syncd's timers
+//!
+//! Retry backoff and periodic tasks wait through a [`Clock`] instead of
+//! calling tokio directly. Production code uses [`TokioClock`]; tests use
+//! `MockClock` and advance it by hand, so backoff and periodic behavior is
+//! checked without real sleeps.
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Source of the current time and of delays
+#[async_trait]
+pub trait Clock: Debug + Send + Sync {
+    /// Current time
+    fn now(&self) -> Instant;
+
+    /// Wait until `duration` has passed on this clock
+    async fn sleep(&self, duration: Duration);
+}
+
+/// Wall-clock time through the tokio timer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+#[async_trait]
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Run `tick` every `period` on `clock`, forever
+///
+/// The first tick runs after one period.
+pub async fn every<F, Fut>(clock: &dyn Clock, period: Duration, mut tick: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    loop {
+        clock.sleep(period).await;
+        tick().await;
+    }
+}
+
+#[cfg(test)]
+pub use mock::MockClock;
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use tokio::sync::watch;
+
+    /// Clock that only moves when [`advance`](Self::advance) is called
+    #[derive(Debug)]
+    pub struct MockClock {
+        start: Instant,
+        /// Time advanced since `start`; sleepers wait for it to pass their deadline
+        elapsed: watch::Sender<Duration>,
+    }
+
+    impl MockClock {
+        pub fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                elapsed: watch::Sender::new(Duration::ZERO),
+            }
+        }
+
+        /// Move the clock forward, waking sleepers whose deadline has passed
+        pub fn advance(&self, duration: Duration) {
+            self.elapsed.send_modify(|elapsed| *elapsed += duration);
+        }
+    }
+
+    impl Default for MockClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    #[async_trait]
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.start + *self.elapsed.borrow()
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            let mut elapsed = self.elapsed.subscribe();
+            let deadline = *elapsed.borrow() + duration;
+            // The sender lives in self, so the channel can't close while we wait
+            let _ = elapsed.wait_for(|elapsed| *elapsed >= deadline).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Let spawned tasks run until they block again
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_advancing_mock_clock_triggers_periodic_task() {
+        let clock = Arc::new(MockClock::new());
+        let start = clock.now();
+        let resyncs = Arc::new(AtomicUsize::new(0));
+
+        let task = tokio::spawn({
+            let clock = clock.clone();
+            let resyncs = resyncs.clone();
+            async move {
+                every(&*clock, Duration::from_secs(60), || async {
+                    resyncs.fetch_add(1, Ordering::SeqCst);
+                })
+                .await
+            }
+        });
+        settle().await;
+
+        clock.advance(Duration::from_secs(59));
+        settle().await;
+        assert_eq!(resyncs.load(Ordering::SeqCst), 0);
+
+        clock.advance(Duration::from_secs(1));
+        settle().await;
+        assert_eq!(resyncs.load(Ordering::SeqCst), 1);
+
+        clock.advance(Duration::from_secs(60));
+        settle().await;
+        assert_eq!(resyncs.load(Ordering::SeqCst), 2);
+        assert_eq!(clock.now() - start, Duration::from_secs(120));
+
+        task.abort();
+    }
+}
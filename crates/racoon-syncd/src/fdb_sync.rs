@@ -0,0 +1,581 @@
+//! FDB Synchronization
+//!
+//! Programs the static and dynamic FDB entries of APPL_DB
+//! (`FDB_TABLE:Vlan100:00:11:22:33:44:55`) into SAI.
+//!
+//! SAI keys an FDB entry by its MAC and VLAN rather than by an OID, so that
+//! pair is all the agent has to remember to remove one. Entries are tracked
+//! in an [`FdbTable`], which packs each pair into a single `u64`, keeping
+//! tens of thousands of learned MACs well under a MiB. The VLAN and bridge
+//! port OIDs an entry is programmed with are looked up in the switch's
+//! [`ObjectRegistry`] when needed rather than stored per entry.
+
+use crate::audit_log::{AuditLog, AuditRecord};
+use crate::fdb_table::FdbTable;
+use crate::intent_log::{IntentOp, SaiIntent};
+use crate::object_registry::ObjectRegistry;
+use crate::startup::{HeldNotification, StartupWindow};
+use crate::switch_context::SwitchContext;
+use async_trait::async_trait;
+use racoon_common::config::ResourceLimits;
+use racoon_common::keys::TableKeys;
+use racoon_common::{MacAddress, PacketAction, RacoonError, Result, VlanId};
+use racoon_db_client::{Database, DbClient, DbSubscriber, Operation, home_db, tables};
+use racoon_sai::fdb::{FdbApi, FdbEntryType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{debug, error, info, instrument, warn};
+
+/// SAI object type of FDB entries, as the audit file names it
+const FDB_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_FDB_ENTRY";
+
+/// FDB entry from APPL_DB (`FDB_TABLE:Vlan100:00:11:22:33:44:55`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FdbEntry {
+    pub port: String,
+    /// `static` or `dynamic`; anything else is programmed as dynamic
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    /// `forward`, `drop`, `trap` or `copy`; unset forwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packet_action: Option<PacketAction>,
+}
+
+impl FdbEntry {
+    fn sai_type(&self) -> FdbEntryType {
+        if self.entry_type == "static" {
+            FdbEntryType::Static
+        } else {
+            FdbEntryType::Dynamic
+        }
+    }
+}
+
+/// FDB Synchronization Agent
+pub struct FdbSync {
+    db_client: Arc<DbClient>,
+    fdb_api: Arc<FdbApi>,
+    /// Switch this agent programs
+    switch: SwitchContext,
+    /// OIDs shared with the switch's other agents; VLANs and bridge ports
+    /// are looked up here
+    registry: Arc<ObjectRegistry>,
+    /// Programmed entries
+    entries: FdbTable,
+    /// Notifications held while the startup scan runs
+    startup: StartupWindow,
+    /// File receiving every SAI mutation
+    audit_log: Option<Arc<AuditLog>>,
+    /// APPL_DB key layout
+    table_keys: TableKeys,
+}
+
+impl FdbSync {
+    /// Create new FDB sync agent
+    pub fn new(db_client: Arc<DbClient>, fdb_api: Arc<FdbApi>, switch: SwitchContext) -> Self {
+        Self {
+            db_client,
+            fdb_api,
+            switch,
+            registry: Arc::new(ObjectRegistry::new()),
+            entries: FdbTable::new(),
+            startup: StartupWindow::new(),
+            audit_log: None,
+            table_keys: TableKeys::default(),
+        }
+    }
+
+    /// Switch this agent programs
+    pub fn switch(&self) -> &SwitchContext {
+        &self.switch
+    }
+
+    /// Override the APPL_DB key layout
+    pub fn with_table_keys(mut self, table_keys: TableKeys) -> Self {
+        self.table_keys = table_keys;
+        self
+    }
+
+    /// Share object OIDs with the switch's other agents
+    pub fn with_object_registry(mut self, registry: Arc<ObjectRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Refuse entries beyond the platform's FDB table size
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.entries = self.entries.with_limits(limits);
+        self
+    }
+
+    /// Append every SAI create and remove to `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Record a completed SAI mutation in the audit file
+    fn audit(&self, record: impl Into<AuditRecord>) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(record);
+        }
+    }
+
+    /// Number of programmed entries
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Approximate heap usage of the entry tracking in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.entries.memory_bytes()
+    }
+
+    /// Whether the entry for `mac` on `vlan_id` is programmed
+    pub fn is_programmed(&self, mac: MacAddress, vlan_id: VlanId) -> bool {
+        self.entries.contains(mac, vlan_id)
+    }
+
+    /// Hold notifications until [`start`](Self::start) has scanned APPL_DB
+    ///
+    /// Call before subscribing, so entries published while the scan runs
+    /// are neither missed nor applied twice.
+    pub fn begin_startup(&self) {
+        self.startup.open();
+    }
+
+    /// Start the sync agent
+    pub async fn start(&self) -> Result<()> {
+        info!("Starting FDB synchronization agent");
+
+        // Load existing entries from APPL_DB
+        self.sync_fdb().await?;
+
+        // Handle what was published during the scan and not seen by it
+        self.replay_held_notifications().await;
+
+        info!("FDB synchronization agent started");
+        Ok(())
+    }
+
+    /// Handle notifications held during the startup scan, then stop holding
+    async fn replay_held_notifications(&self) {
+        let entry_of = |held: &HeldNotification| {
+            let channel = self.switch.local_channel(&held.channel);
+            if channel != tables::FDB_TABLE {
+                return None;
+            }
+            let key = serde_json::from_str::<serde_json::Value>(&held.message)
+                .ok()
+                .and_then(|notification| notification["key"].as_str().map(str::to_string));
+            Some((channel.to_string(), key))
+        };
+
+        while let Some(batch) = self.startup.take_uncovered(entry_of) {
+            debug!(
+                "Replaying {} notifications held during startup",
+                batch.len()
+            );
+            for held in batch {
+                self.process_notification(&held.channel, &held.message)
+                    .await;
+            }
+        }
+    }
+
+    /// Sync all FDB entries from APPL_DB to SAI
+    ///
+    /// Programmed entries no longer in APPL_DB are removed, so a resync
+    /// also catches deletions whose notifications were lost.
+    pub async fn sync_fdb(&self) -> Result<()> {
+        info!("Syncing FDB entries from APPL_DB to SAI");
+
+        self.startup.mark_scanned(tables::FDB_TABLE);
+        let keys = self
+            .db_client
+            .scan(
+                home_db(tables::FDB_TABLE),
+                &self.table_keys.pattern(tables::FDB_TABLE),
+            )
+            .await?;
+        for key in &keys {
+            if let Some(entry_key) = self.table_keys.strip(tables::FDB_TABLE, key) {
+                self.startup.mark_read(tables::FDB_TABLE, entry_key);
+            }
+        }
+        let entries: Vec<Option<FdbEntry>> = self
+            .db_client
+            .get_many(home_db(tables::FDB_TABLE), &keys)
+            .await?;
+
+        let mut present = HashSet::new();
+        for (key, entry) in keys.iter().zip(entries) {
+            let (Some(entry_key), Some(entry)) =
+                (self.table_keys.strip(tables::FDB_TABLE, key), entry)
+            else {
+                continue;
+            };
+            let Some((mac, vlan_id)) = parse_fdb_key(entry_key) else {
+                warn!("Malformed FDB key: {}", entry_key);
+                continue;
+            };
+            present.insert((mac, vlan_id));
+            if let Err(e) = self.program_entry(mac, vlan_id, &entry) {
+                warn!("Failed to sync FDB entry {}: {}", entry_key, e);
+            }
+        }
+
+        for (mac, vlan_id) in self.entries.entries() {
+            if present.contains(&(mac, vlan_id)) {
+                continue;
+            }
+            if let Err(e) = self.remove_entry(mac, vlan_id) {
+                warn!(
+                    "Failed to remove stale FDB entry {} on VLAN {}: {}",
+                    mac,
+                    vlan_id.get(),
+                    e
+                );
+            }
+        }
+
+        info!("Synced {} FDB entries", self.entries.len());
+        Ok(())
+    }
+
+    /// Program an FDB entry, replacing the one programmed for its MAC and
+    /// VLAN
+    ///
+    /// Fails when the VLAN or the port's bridge port is not known yet.
+    pub fn program_entry(&self, mac: MacAddress, vlan_id: VlanId, entry: &FdbEntry) -> Result<()> {
+        let bv_id = self
+            .registry
+            .vlan_oid(vlan_id)
+            .ok_or(RacoonError::VlanNotFound(vlan_id.get()))?;
+        let bridge_port = self.registry.bridge_port_oid(&entry.port).ok_or_else(|| {
+            RacoonError::DependencyNotSatisfied(format!("bridge port of {}", entry.port))
+        })?;
+
+        // SAI has no FDB entry update for a move between ports; the old
+        // entry goes first
+        if self.entries.contains(mac, vlan_id) {
+            self.remove_entry(mac, vlan_id)?;
+        }
+        self.entries.check_capacity(mac, vlan_id)?;
+
+        let packet_action = entry.packet_action.unwrap_or(PacketAction::Forward);
+        self.fdb_api.create_fdb_entry(
+            self.switch.switch_id,
+            mac,
+            bv_id,
+            bridge_port,
+            entry.sai_type(),
+            packet_action.into(),
+        )?;
+        self.entries.insert(mac, vlan_id);
+        self.audit(SaiIntent::create(FDB_OBJECT_TYPE, &fdb_key(mac, vlan_id)));
+
+        debug!(
+            "Programmed FDB entry {} on VLAN {} to {}",
+            mac,
+            vlan_id.get(),
+            entry.port
+        );
+        Ok(())
+    }
+
+    /// Remove a programmed FDB entry; entries never programmed are ignored
+    pub fn remove_entry(&self, mac: MacAddress, vlan_id: VlanId) -> Result<()> {
+        if !self.entries.contains(mac, vlan_id) {
+            return Ok(());
+        }
+
+        // Entries go with their VLAN; one already removed took them along
+        if let Some(bv_id) = self.registry.vlan_oid(vlan_id) {
+            self.fdb_api
+                .remove_fdb_entry(self.switch.switch_id, mac, bv_id)?;
+            self.audit(SaiIntent {
+                op: IntentOp::Remove,
+                ..SaiIntent::create(FDB_OBJECT_TYPE, &fdb_key(mac, vlan_id))
+            });
+        }
+        self.entries.remove(mac, vlan_id);
+
+        debug!("Removed FDB entry {} on VLAN {}", mac, vlan_id.get());
+        Ok(())
+    }
+
+    /// Apply an FDB_TABLE change (`Vlan100:00:11:22:33:44:55`)
+    async fn handle_fdb_notification(&self, operation: Operation, key: &str) -> Result<()> {
+        let Some((mac, vlan_id)) = parse_fdb_key(key) else {
+            warn!("Malformed FDB key: {}", key);
+            return Ok(());
+        };
+
+        match operation {
+            Operation::Set | Operation::Hset => {
+                let appl_key = self.table_keys.key(tables::FDB_TABLE, key);
+                let entry: FdbEntry = self
+                    .db_client
+                    .get(home_db(tables::FDB_TABLE), &appl_key)
+                    .await?;
+                self.program_entry(mac, vlan_id, &entry)
+            }
+            Operation::Del => self.remove_entry(mac, vlan_id),
+        }
+    }
+
+    /// Handle database notification
+    #[instrument(skip(self, message))]
+    pub async fn handle_notification(&self, channel: &str, message: &str) {
+        debug!("Received notification on {}: {}", channel, message);
+        if self.startup.hold(channel, message) {
+            debug!(
+                "Holding notification on {} until the startup scan ends",
+                channel
+            );
+            return;
+        }
+        self.process_notification(channel, message).await;
+    }
+
+    async fn process_notification(&self, channel: &str, message: &str) {
+        let channel = self.switch.local_channel(channel);
+        if channel != tables::FDB_TABLE {
+            return;
+        }
+
+        let notification: serde_json::Value = match serde_json::from_str(message) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to parse notification: {}", e);
+                return;
+            }
+        };
+        let Some(operation) = Operation::parse(notification["operation"].as_str().unwrap_or(""))
+        else {
+            return;
+        };
+        let Some(key) = notification["key"].as_str() else {
+            warn!("Notification on {} without key", channel);
+            return;
+        };
+
+        if let Err(e) = self.handle_fdb_notification(operation, key).await {
+            error!("Failed to apply {} change to {}: {}", channel, key, e);
+        }
+    }
+}
+
+/// MAC and VLAN of an FDB_TABLE key (`Vlan100:00:11:22:33:44:55`)
+fn parse_fdb_key(key: &str) -> Option<(MacAddress, VlanId)> {
+    let (vlan_name, mac) = key.split_once(':')?;
+    let vlan_id = VlanId::parse_from_name(vlan_name).ok()?;
+    Some((mac.parse().ok()?, vlan_id))
+}
+
+/// FDB_TABLE key of an entry, as the audit file names it
+fn fdb_key(mac: MacAddress, vlan_id: VlanId) -> String {
+    format!("Vlan{}:{}", vlan_id.get(), mac)
+}
+
+/// Database subscriber implementation for FdbSync
+pub struct FdbSyncSubscriber {
+    fdb_sync: Arc<FdbSync>,
+    /// Channels confirmed so far
+    subscribed: watch::Sender<usize>,
+}
+
+impl FdbSyncSubscriber {
+    pub fn new(fdb_sync: Arc<FdbSync>) -> Self {
+        Self {
+            fdb_sync,
+            subscribed: watch::Sender::new(0),
+        }
+    }
+
+    /// Wait until `channels` subscriptions have been confirmed
+    pub async fn wait_subscribed(&self, channels: usize) {
+        let mut subscribed = self.subscribed.subscribe();
+        // The sender lives in self, so the channel can't close while we wait
+        let _ = subscribed.wait_for(|count| *count >= channels).await;
+    }
+}
+
+#[async_trait]
+impl DbSubscriber for FdbSyncSubscriber {
+    async fn on_message(&self, channel: String, message: String) {
+        self.fdb_sync.handle_notification(&channel, &message).await;
+    }
+
+    async fn on_subscribe(&self, channel: String) {
+        info!("FdbSync subscribed to channel: {}", channel);
+        self.subscribed.send_modify(|count| *count += 1);
+    }
+
+    async fn on_resubscribe(&self) {
+        // Notifications may have been lost while the subscription was wedged
+        if let Err(e) = self.fdb_sync.sync_fdb().await {
+            warn!("FdbSync resync after resubscribe failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_sai::{SAI_FDB_ENTRY_ATTR_BRIDGE_PORT_ID, mock};
+
+    const VLAN_OID: u64 = 0x26000000000001;
+    const BRIDGE_PORT_OID: u64 = 0x3a000000000001;
+
+    async fn test_fdb_sync() -> FdbSync {
+        // DbClient connects lazily, so paths that skip the DB need no server
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        let registry = Arc::new(ObjectRegistry::new());
+        registry.register_vlan(VlanId::new(100).unwrap(), VLAN_OID);
+        registry.register_bridge_port("Ethernet0", BRIDGE_PORT_OID);
+        FdbSync::new(
+            db_client,
+            Arc::new(mock::fdb_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_object_registry(registry)
+    }
+
+    fn mac(i: u32) -> MacAddress {
+        let b = i.to_be_bytes();
+        MacAddress::new([0x02, 0x00, b[0], b[1], b[2], b[3]])
+    }
+
+    fn dynamic_entry(port: &str) -> FdbEntry {
+        FdbEntry {
+            port: port.to_string(),
+            entry_type: "dynamic".to_string(),
+            packet_action: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_fdb_key() {
+        assert_eq!(
+            parse_fdb_key("Vlan100:00:11:22:33:44:55"),
+            Some((
+                "00:11:22:33:44:55".parse().unwrap(),
+                VlanId::new(100).unwrap()
+            ))
+        );
+        assert_eq!(parse_fdb_key("Vlan100"), None);
+        assert_eq!(parse_fdb_key("Ethernet0:00:11:22:33:44:55"), None);
+        assert_eq!(parse_fdb_key("Vlan100:not-a-mac"), None);
+    }
+
+    #[tokio::test]
+    async fn test_10k_entries_tracked_and_deletable() {
+        let fdb_sync = test_fdb_sync().await;
+        let vlan = VlanId::new(100).unwrap();
+        for i in 0..10_000 {
+            fdb_sync
+                .program_entry(mac(i), vlan, &dynamic_entry("Ethernet0"))
+                .unwrap();
+        }
+        assert_eq!(fdb_sync.entry_count(), 10_000);
+        assert!(mock::has_fdb_entry(VLAN_OID, *mac(9_999).as_bytes()));
+        mock::take_calls();
+
+        for i in 0..10_000 {
+            fdb_sync.remove_entry(mac(i), vlan).unwrap();
+        }
+        assert_eq!(fdb_sync.entry_count(), 0);
+        assert!(!mock::has_fdb_entry(VLAN_OID, *mac(0).as_bytes()));
+        let removes = mock::take_calls()
+            .iter()
+            .filter(|call| call.function == "remove_fdb_entry")
+            .count();
+        assert_eq!(removes, 10_000);
+    }
+
+    #[tokio::test]
+    async fn test_entry_moved_between_ports() {
+        let fdb_sync = test_fdb_sync().await;
+        fdb_sync
+            .registry
+            .register_bridge_port("Ethernet4", 0x3a000000000002);
+        let vlan = VlanId::new(100).unwrap();
+
+        fdb_sync
+            .program_entry(mac(1), vlan, &dynamic_entry("Ethernet0"))
+            .unwrap();
+        mock::take_calls();
+        fdb_sync
+            .program_entry(mac(1), vlan, &dynamic_entry("Ethernet4"))
+            .unwrap();
+
+        let calls = mock::take_calls();
+        let functions: Vec<&str> = calls.iter().map(|call| call.function).collect();
+        assert_eq!(functions, ["remove_fdb_entry", "create_fdb_entry"]);
+        assert!(
+            calls[1]
+                .attrs
+                .contains(&(SAI_FDB_ENTRY_ATTR_BRIDGE_PORT_ID, 0x3a000000000002))
+        );
+        assert_eq!(fdb_sync.entry_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_entry_needs_vlan_and_bridge_port() {
+        let fdb_sync = test_fdb_sync().await;
+
+        let err = fdb_sync
+            .program_entry(
+                mac(1),
+                VlanId::new(200).unwrap(),
+                &dynamic_entry("Ethernet0"),
+            )
+            .unwrap_err();
+        assert!(matches!(err, RacoonError::VlanNotFound(200)));
+        let err = fdb_sync
+            .program_entry(
+                mac(1),
+                VlanId::new(100).unwrap(),
+                &dynamic_entry("Ethernet8"),
+            )
+            .unwrap_err();
+        assert!(matches!(err, RacoonError::DependencyNotSatisfied(_)));
+        assert_eq!(fdb_sync.entry_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sync_removes_entries_gone_from_appl_db() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let registry = Arc::new(ObjectRegistry::new());
+        let vlan = VlanId::new(100).unwrap();
+        registry.register_vlan(vlan, VLAN_OID);
+        registry.register_bridge_port("Ethernet0", BRIDGE_PORT_OID);
+        let fdb_sync = FdbSync::new(
+            db_client.clone(),
+            Arc::new(mock::fdb_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_object_registry(registry);
+
+        fdb_sync
+            .program_entry(mac(1), vlan, &dynamic_entry("Ethernet0"))
+            .unwrap();
+        db_client
+            .set(
+                Database::Appl,
+                &format!("FDB_TABLE:{}", fdb_key(mac(2), vlan)),
+                &dynamic_entry("Ethernet0"),
+            )
+            .await
+            .unwrap();
+
+        fdb_sync.sync_fdb().await.unwrap();
+        assert!(!fdb_sync.is_programmed(mac(1), vlan));
+        assert!(fdb_sync.is_programmed(mac(2), vlan));
+    }
+}
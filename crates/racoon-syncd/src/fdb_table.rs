@@ -0,0 +1,181 @@
+//! Compact tracking of programmed FDB entries
+//!
+//! SAI keys FDB entries by `(switch, MAC, bridge/VLAN)` rather than by OID,
+//! so removing an entry only needs its MAC and VLAN. Dynamic FDB can hold
+//! tens of thousands of entries; storing each as a single packed `u64`
+//! keeps 50k entries around half a MiB instead of several MiB of full records.
+
+use dashmap::DashSet;
+use racoon_common::config::ResourceLimits;
+use racoon_common::{MacAddress, Result, VlanId};
+
+/// Set of `(MAC, VLAN)` pairs packed into 60 bits each
+#[derive(Debug, Default)]
+pub struct FdbTable {
+    entries: DashSet<u64>,
+    limits: ResourceLimits,
+}
+
+impl FdbTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enforce the platform's FDB table size in [`check_capacity`](Self::check_capacity)
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Fail if programming the entry would exceed the FDB table size
+    ///
+    /// Called before the SAI create; entries already tracked always pass.
+    pub fn check_capacity(&self, mac: MacAddress, vlan_id: VlanId) -> Result<()> {
+        if self.contains(mac, vlan_id) {
+            return Ok(());
+        }
+        self.limits.check_fdb_entries(self.len())
+    }
+
+    /// Track an entry; returns `false` if it was already tracked
+    pub fn insert(&self, mac: MacAddress, vlan_id: VlanId) -> bool {
+        self.entries.insert(pack(mac, vlan_id))
+    }
+
+    /// Stop tracking an entry; returns `false` if it was not tracked
+    pub fn remove(&self, mac: MacAddress, vlan_id: VlanId) -> bool {
+        self.entries.remove(&pack(mac, vlan_id)).is_some()
+    }
+
+    pub fn contains(&self, mac: MacAddress, vlan_id: VlanId) -> bool {
+        self.entries.contains(&pack(mac, vlan_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Tracked entries, in no particular order
+    pub fn entries(&self) -> Vec<(MacAddress, VlanId)> {
+        self.entries.iter().map(|key| unpack(*key)).collect()
+    }
+
+    /// Remove every entry learned on a VLAN, returning the removed MACs
+    pub fn remove_vlan(&self, vlan_id: VlanId) -> Vec<MacAddress> {
+        let mut removed = Vec::new();
+        self.entries.retain(|key| {
+            let (mac, vlan) = unpack(*key);
+            if vlan == vlan_id {
+                removed.push(mac);
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+
+    /// Approximate heap usage of the table in bytes
+    ///
+    /// Counts one `u64` plus one control byte per allocated bucket.
+    pub fn memory_bytes(&self) -> usize {
+        self.entries.capacity() * (std::mem::size_of::<u64>() + 1)
+    }
+}
+
+/// MAC in bits 12-59, VLAN id in bits 0-11
+fn pack(mac: MacAddress, vlan_id: VlanId) -> u64 {
+    let mac = mac
+        .as_bytes()
+        .iter()
+        .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+    (mac << 12) | u64::from(vlan_id.get())
+}
+
+fn unpack(key: u64) -> (MacAddress, VlanId) {
+    let mac = (key >> 12).to_be_bytes();
+    let mut bytes = [0u8; 6];
+    bytes.copy_from_slice(&mac[2..]);
+    let vlan_id = VlanId::new((key & 0xfff) as u16).expect("packed VLAN id is valid");
+    (MacAddress::new(bytes), vlan_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mac(i: u32) -> MacAddress {
+        let b = i.to_be_bytes();
+        MacAddress::new([0x02, 0x00, b[0], b[1], b[2], b[3]])
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let entry = (
+            "ff:ee:dd:cc:bb:aa".parse().unwrap(),
+            VlanId::new(4094).unwrap(),
+        );
+        assert_eq!(unpack(pack(entry.0, entry.1)), entry);
+    }
+
+    #[test]
+    fn test_track_and_delete_10k() {
+        let table = FdbTable::new();
+        let vlan = VlanId::new(100).unwrap();
+        for i in 0..10_000 {
+            assert!(table.insert(mac(i), vlan));
+        }
+        assert!(!table.insert(mac(0), vlan));
+        assert_eq!(table.len(), 10_000);
+
+        // Same MAC on another VLAN is a distinct entry
+        assert!(table.insert(mac(0), VlanId::new(200).unwrap()));
+        assert!(!table.contains(mac(1), VlanId::new(200).unwrap()));
+
+        for i in 0..5_000 {
+            assert!(table.remove(mac(i), vlan));
+        }
+        assert!(!table.remove(mac(0), vlan));
+        assert_eq!(table.len(), 5_001);
+
+        let removed = table.remove_vlan(vlan);
+        assert_eq!(removed.len(), 5_000);
+        assert_eq!(table.entries(), vec![(mac(0), VlanId::new(200).unwrap())]);
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        let table = FdbTable::new().with_limits(ResourceLimits {
+            max_vlans: None,
+            max_fdb_entries: Some(2),
+        });
+        let vlan = VlanId::new(100).unwrap();
+        table.insert(mac(0), vlan);
+        assert!(table.check_capacity(mac(1), vlan).is_ok());
+        table.insert(mac(1), vlan);
+
+        assert!(table.check_capacity(mac(2), vlan).is_err());
+        assert!(table.check_capacity(mac(0), vlan).is_ok());
+    }
+
+    #[test]
+    fn test_memory_at_50k() {
+        let table = FdbTable::new();
+        for i in 0..50_000 {
+            table.insert(mac(i), VlanId::new((i % 4094 + 1) as u16).unwrap());
+        }
+        assert_eq!(table.len(), 50_000);
+
+        // A full record (MAC, VLAN, port name, type, bridge port OID) per
+        // entry would need several times this
+        assert!(
+            table.memory_bytes() < 2 * 1024 * 1024,
+            "{} bytes for 50k entries",
+            table.memory_bytes()
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! SAI write-ahead intent log
+//!
+//! A SAI create or remove and the ASIC_DB write that records it are not
+//! atomic: a crash in between leaves hardware and ASIC_DB disagreeing. Each
+//! operation is therefore recorded in STATE_DB before it starts and cleared
+//! once ASIC_DB reflects it. Intents still present at startup mark
+//! operations that were interrupted and must be reconciled.
+//!
+//! Records live at `SAI_INTENT:<object type>:<key>`, prefixed with the
+//! switch's namespace on multi-ASIC platforms:
+//!
+//! ```json
+//! {"op":"create","object_type":"SAI_OBJECT_TYPE_VLAN","key":"Vlan100","oid":"0x26000000000001","ts":1700000000000}
+//! ```
+//!
+//! `oid` is absent for a create that has not returned from SAI yet.
+
+use racoon_common::keys::TableKeys;
+use racoon_common::metrics::unix_millis;
+use racoon_common::{Result, SaiOid};
+use racoon_db_client::{Database, DbClient};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// STATE_DB table holding pending intents
+pub const INTENT_TABLE: &str = "SAI_INTENT";
+
+/// Operation an intent announces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntentOp {
+    Create,
+    Remove,
+}
+
+/// A SAI operation that has not been committed to ASIC_DB yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaiIntent {
+    pub op: IntentOp,
+    /// SAI object type name, e.g. `SAI_OBJECT_TYPE_VLAN`
+    pub object_type: String,
+    /// Key of the object in its APPL_DB table, e.g. `Vlan100`
+    pub key: String,
+    /// Object OID, once known
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "racoon_common::hex_oid::option"
+    )]
+    pub oid: Option<SaiOid>,
+    /// When the intent was recorded, Unix milliseconds
+    pub ts: u64,
+}
+
+impl SaiIntent {
+    /// Intent to create an object whose OID is not known yet
+    pub fn create(object_type: &str, key: &str) -> Self {
+        Self {
+            op: IntentOp::Create,
+            object_type: object_type.to_string(),
+            key: key.to_string(),
+            oid: None,
+            ts: unix_millis(),
+        }
+    }
+
+    /// Intent to remove an existing object
+    pub fn remove(object_type: &str, key: &str, oid: SaiOid) -> Self {
+        Self {
+            op: IntentOp::Remove,
+            oid: Some(oid),
+            ..Self::create(object_type, key)
+        }
+    }
+
+    /// Record the OID returned by SAI
+    pub fn with_oid(mut self, oid: SaiOid) -> Self {
+        self.oid = Some(oid);
+        self
+    }
+
+    /// STATE_DB key of the intent
+    pub fn db_key(&self) -> String {
+        format!("{}:{}:{}", INTENT_TABLE, self.object_type, self.key)
+    }
+}
+
+/// Reads and writes intents in STATE_DB
+pub struct IntentLog {
+    db_client: Arc<DbClient>,
+    /// Namespace of the switch's intents
+    keys: TableKeys,
+}
+
+impl IntentLog {
+    pub fn new(db_client: Arc<DbClient>) -> Self {
+        Self {
+            db_client,
+            keys: TableKeys::default(),
+        }
+    }
+
+    /// Keep the intents in the namespace of `keys`
+    pub fn with_table_keys(mut self, keys: TableKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Record an intent, replacing any earlier one for the same object
+    pub async fn record(&self, intent: &SaiIntent) -> Result<()> {
+        self.db_client
+            .set(Database::State, &self.keys.scoped(&intent.db_key()), intent)
+            .await
+    }
+
+    /// Clear an intent once ASIC_DB reflects it
+    pub async fn clear(&self, intent: &SaiIntent) -> Result<()> {
+        self.db_client
+            .del(Database::State, &self.keys.scoped(&intent.db_key()))
+            .await
+    }
+
+    /// Record several intents in one pipeline
+    pub async fn record_many(&self, intents: &[SaiIntent]) -> Result<()> {
+        let records: Vec<(String, &SaiIntent)> = intents
+            .iter()
+            .map(|intent| (self.keys.scoped(&intent.db_key()), intent))
+            .collect();
+        self.db_client.set_many(Database::State, &records).await
+    }
+
+    /// Clear several intents in one round-trip
+    pub async fn clear_many(&self, intents: &[SaiIntent]) -> Result<()> {
+        let keys: Vec<String> = intents
+            .iter()
+            .map(|intent| self.keys.scoped(&intent.db_key()))
+            .collect();
+        self.db_client.del_many(Database::State, &keys).await
+    }
+
+    /// Intents left behind for an object type, sorted by key
+    pub async fn pending(&self, object_type: &str) -> Result<Vec<SaiIntent>> {
+        let keys = self
+            .db_client
+            .scan(
+                Database::State,
+                &self
+                    .keys
+                    .scoped(&format!("{}:{}:*", INTENT_TABLE, object_type)),
+            )
+            .await?;
+        let intents: Vec<Option<SaiIntent>> =
+            self.db_client.get_many(Database::State, &keys).await?;
+
+        let mut intents: Vec<SaiIntent> = intents.into_iter().flatten().collect();
+        intents.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(intents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intent_record_format() {
+        let intent = SaiIntent {
+            ts: 1_700_000_000_000,
+            ..SaiIntent::create("SAI_OBJECT_TYPE_VLAN", "Vlan100")
+        };
+        assert_eq!(intent.db_key(), "SAI_INTENT:SAI_OBJECT_TYPE_VLAN:Vlan100");
+        assert_eq!(
+            serde_json::to_value(&intent).unwrap(),
+            serde_json::json!({
+                "op": "create",
+                "object_type": "SAI_OBJECT_TYPE_VLAN",
+                "key": "Vlan100",
+                "ts": 1_700_000_000_000u64
+            })
+        );
+
+        let intent = intent.with_oid(0x26000000000001);
+        let json = serde_json::to_string(&intent).unwrap();
+        assert!(json.contains(r#""oid":"0x26000000000001""#));
+        assert_eq!(serde_json::from_str::<SaiIntent>(&json).unwrap(), intent);
+
+        let intent = SaiIntent::remove("SAI_OBJECT_TYPE_VLAN", "Vlan200", 0x26000000000002);
+        assert_eq!(intent.op, IntentOp::Remove);
+        assert_eq!(intent.oid, Some(0x26000000000002));
+    }
+}
@@ -0,0 +1,1424 @@
+//! LAG Synchronization
+//!
+//! Creates the LAGs (`LAG_TABLE:PortChannel1`) and LAG members
+//! (`LAG_MEMBER_TABLE:PortChannel1:Ethernet0`) of APPL_DB in SAI, and
+//! enforces their min-links and admin status.
+//!
+//! A PortChannel with `min_links` set only forwards while at least that many
+//! members are oper up. Below the threshold every member is disabled through
+//! SAI, taking the LAG out of forwarding; once enough members are back up
+//! they are enabled again.
+//!
+//! A PortChannel set admin down takes its member ports admin down with it.
+//! Each port's own configured admin status is remembered, so bringing the
+//! LAG back up restores it and leaves independently-down ports down.
+//!
+//! The members SAI reports for a LAG, correlated with their ports' oper
+//! status, are written to STATE_DB (`LAG_STATE:PortChannel1`) for
+//! `show interfaces portchannel`.
+//!
+//! Every tracked LAG's counters, summed over its member ports, are polled
+//! into COUNTERS_DB (`COUNTERS:PortChannel1`).
+//!
+//! With an [`AttributeBatcher`], member port admin changes are queued and
+//! applied once per window, so a LAG flapped down and back up within the
+//! window leaves its ports untouched.
+
+use crate::attr_batch::AttributeBatcher;
+use crate::audit_log::{AuditLog, AuditRecord};
+use crate::intent_log::SaiIntent;
+use crate::object_registry::ObjectRegistry;
+use crate::startup::{HeldNotification, StartupWindow};
+use crate::switch_context::SwitchContext;
+use crate::vlan_state::PORT_STATE_CHANNEL;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use racoon_common::keys::TableKeys;
+use racoon_common::{PortAdminStatus, PortOperStatus, Result, SaiOid};
+use racoon_db_client::{Counters, Database, DbClient, DbSubscriber, Operation, home_db, tables};
+use racoon_sai::lag::LagApi;
+use racoon_sai::port::PortApi;
+use racoon_sai::types::SaiAttributeValue;
+use racoon_sai::{
+    SAI_PORT_ATTR_ADMIN_STATE, SAI_PORT_STAT_IF_IN_OCTETS, SAI_PORT_STAT_IF_IN_UCAST_PKTS,
+    SAI_PORT_STAT_IF_OUT_OCTETS, SAI_PORT_STAT_IF_OUT_UCAST_PKTS, SaiAttribute, sai_port_stat_t,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{debug, error, info, instrument, warn};
+
+/// SAI object type names of the objects the agent programs, as audited
+const LAG_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_LAG";
+const LAG_MEMBER_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_LAG_MEMBER";
+const PORT_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_PORT";
+
+/// Members needed when a LAG does not configure `min_links`
+pub const DEFAULT_MIN_LINKS: u32 = 1;
+
+/// Port counters summed into each LAG's COUNTERS_DB entry
+pub const LAG_COUNTERS: [(sai_port_stat_t, &str); 4] = [
+    (SAI_PORT_STAT_IF_IN_OCTETS, "SAI_PORT_STAT_IF_IN_OCTETS"),
+    (
+        SAI_PORT_STAT_IF_IN_UCAST_PKTS,
+        "SAI_PORT_STAT_IF_IN_UCAST_PKTS",
+    ),
+    (SAI_PORT_STAT_IF_OUT_OCTETS, "SAI_PORT_STAT_IF_OUT_OCTETS"),
+    (
+        SAI_PORT_STAT_IF_OUT_UCAST_PKTS,
+        "SAI_PORT_STAT_IF_OUT_UCAST_PKTS",
+    ),
+];
+
+/// LAG entry from APPL_DB
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LagEntry {
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
+    /// Members that must be oper up for the LAG to forward
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_links: Option<u32>,
+}
+
+/// Admin status of a port's APPL_DB `PORT_TABLE` entry
+#[derive(Debug, Deserialize)]
+struct PortAdminEntry {
+    #[serde(default, with = "racoon_common::admin_status")]
+    admin_status: Option<PortAdminStatus>,
+}
+
+/// A LAG member programmed in SAI
+#[derive(Debug, Clone)]
+struct LagMember {
+    oid: SaiOid,
+    /// The member's port
+    port_oid: SaiOid,
+    oper_up: bool,
+}
+
+#[derive(Debug, Clone)]
+struct TrackedLag {
+    oid: SaiOid,
+    min_links: u32,
+    /// Members by port name
+    members: BTreeMap<String, LagMember>,
+    /// Whether the members are currently enabled
+    forwarding: bool,
+    /// Admin status of the LAG itself
+    admin_status: PortAdminStatus,
+}
+
+impl TrackedLag {
+    fn up_members(&self) -> u32 {
+        self.members
+            .values()
+            .filter(|member| member.oper_up)
+            .count() as u32
+    }
+
+    fn should_forward(&self) -> bool {
+        self.up_members() >= self.min_links
+    }
+}
+
+/// LAG state entry (STATE_DB)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LagState {
+    /// Members SAI reports for the LAG
+    pub member_count: usize,
+    /// Member ports that are oper up and enabled, sorted
+    pub active_members: Vec<String>,
+    pub oper_status: String, // "up" or "down"
+}
+
+impl LagState {
+    /// STATE_DB key of a LAG
+    pub fn key(lag_name: &str) -> String {
+        format!("LAG_STATE:{}", lag_name)
+    }
+}
+
+/// LAG Synchronization Agent
+pub struct LagSync {
+    db_client: Arc<DbClient>,
+    lag_api: Arc<LagApi>,
+    port_api: Arc<PortApi>,
+    /// Switch this agent programs
+    switch: SwitchContext,
+    /// OIDs shared with the switch's other agents; member ports are
+    /// looked up here
+    registry: Arc<ObjectRegistry>,
+    /// LAGs by name
+    lags: DashMap<String, TrackedLag>,
+    /// Admin status each port is configured with, by port name; ports
+    /// without an entry are up
+    port_admin: DashMap<String, PortAdminStatus>,
+    /// Notifications held while the startup scan runs
+    startup: StartupWindow,
+    /// File receiving every SAI mutation
+    audit_log: Option<Arc<AuditLog>>,
+    /// Queue for member port attribute changes; without one they are set
+    /// right away
+    port_batcher: Option<Arc<AttributeBatcher>>,
+    /// APPL_DB key layout
+    table_keys: TableKeys,
+}
+
+impl LagSync {
+    /// Create new LAG sync agent
+    pub fn new(
+        db_client: Arc<DbClient>,
+        lag_api: Arc<LagApi>,
+        port_api: Arc<PortApi>,
+        switch: SwitchContext,
+    ) -> Self {
+        Self {
+            db_client,
+            lag_api,
+            port_api,
+            switch,
+            registry: Arc::new(ObjectRegistry::new()),
+            lags: DashMap::new(),
+            port_admin: DashMap::new(),
+            startup: StartupWindow::new(),
+            audit_log: None,
+            port_batcher: None,
+            table_keys: TableKeys::default(),
+        }
+    }
+
+    /// Switch this agent programs
+    pub fn switch(&self) -> &SwitchContext {
+        &self.switch
+    }
+
+    /// Override the APPL_DB key layout
+    ///
+    /// A namespace in `table_keys` also scopes the agent's LAG_STATE and
+    /// COUNTERS entries.
+    pub fn with_table_keys(mut self, table_keys: TableKeys) -> Self {
+        self.table_keys = table_keys;
+        self
+    }
+
+    /// Share object OIDs with the switch's other agents
+    pub fn with_object_registry(mut self, registry: Arc<ObjectRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Append every SAI create, remove and attribute set to `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Queue member port attribute changes in `batcher`
+    ///
+    /// Nothing is set until [`run_port_batcher`](Self::run_port_batcher)
+    /// flushes the queue.
+    pub fn with_attribute_batcher(mut self, batcher: Arc<AttributeBatcher>) -> Self {
+        self.port_batcher = Some(batcher);
+        self
+    }
+
+    /// Record a completed SAI mutation in the audit file
+    fn audit(&self, record: impl Into<AuditRecord>) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(record);
+        }
+    }
+
+    /// Set a port's admin status in SAI, or queue it with a batcher
+    fn set_port_admin(&self, port: &str, port_oid: SaiOid, status: PortAdminStatus) -> Result<()> {
+        if let Some(batcher) = &self.port_batcher {
+            batcher.set(
+                port_oid,
+                SaiAttribute::new_bool(SAI_PORT_ATTR_ADMIN_STATE, status == PortAdminStatus::Up),
+            );
+            return Ok(());
+        }
+        self.port_api.set_admin_status(port_oid, status)?;
+        self.audit(AuditRecord::set(
+            PORT_OBJECT_TYPE,
+            port,
+            port_oid,
+            "admin_status",
+            status,
+        ));
+        Ok(())
+    }
+
+    /// Set the queued attributes of a port together
+    fn apply_port_attributes(&self, port_oid: SaiOid, attrs: &[SaiAttribute]) -> Result<()> {
+        self.port_api.set_attributes(port_oid, attrs)?;
+        let port = self
+            .registry
+            .port_name(port_oid)
+            .unwrap_or_else(|| format!("0x{:x}", port_oid));
+        for attr in attrs {
+            if attr.id == SAI_PORT_ATTR_ADMIN_STATE {
+                let status = match attr.value {
+                    SaiAttributeValue::Bool(true) => PortAdminStatus::Up,
+                    _ => PortAdminStatus::Down,
+                };
+                self.audit(AuditRecord::set(
+                    PORT_OBJECT_TYPE,
+                    &port,
+                    port_oid,
+                    "admin_status",
+                    status,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply queued port attribute changes once per batch window, forever
+    ///
+    /// Returns at once without a batcher.
+    pub async fn run_port_batcher(&self) {
+        if let Some(batcher) = &self.port_batcher {
+            batcher
+                .run(|port_oid, attrs| self.apply_port_attributes(port_oid, attrs))
+                .await
+        }
+    }
+
+    /// Apply queued port attribute changes now
+    pub fn flush_port_attributes(&self) {
+        if let Some(batcher) = &self.port_batcher {
+            batcher.flush(|port_oid, attrs| self.apply_port_attributes(port_oid, attrs));
+        }
+    }
+
+    /// Enable or disable forwarding through a LAG member in SAI
+    fn set_member_enabled(
+        &self,
+        lag_name: &str,
+        port: &str,
+        member_oid: SaiOid,
+        enabled: bool,
+    ) -> Result<()> {
+        self.lag_api.set_member_enabled(member_oid, enabled)?;
+        self.audit(AuditRecord::set(
+            LAG_MEMBER_OBJECT_TYPE,
+            &format!("{}:{}", lag_name, port),
+            member_oid,
+            "enabled",
+            enabled,
+        ));
+        Ok(())
+    }
+
+    /// Hold notifications until [`start`](Self::start) has scanned APPL_DB
+    ///
+    /// Call before subscribing, so entries published while the scan runs
+    /// are neither missed nor applied twice.
+    pub fn begin_startup(&self) {
+        self.startup.open();
+    }
+
+    /// Start the sync agent
+    pub async fn start(&self) -> Result<()> {
+        info!("Starting LAG synchronization agent");
+
+        // Load existing LAGs from APPL_DB
+        self.sync_lags().await?;
+
+        // Handle what was published during the scan and not seen by it
+        self.replay_held_notifications().await;
+
+        info!("LAG synchronization agent started");
+        Ok(())
+    }
+
+    /// Handle notifications held during the startup scan, then stop holding
+    async fn replay_held_notifications(&self) {
+        let entry_of = |held: &HeldNotification| {
+            let channel = self.switch.local_channel(&held.channel);
+            if channel != tables::LAG_TABLE && channel != tables::LAG_MEMBER_TABLE {
+                return None;
+            }
+            let key = serde_json::from_str::<serde_json::Value>(&held.message)
+                .ok()
+                .and_then(|notification| notification["key"].as_str().map(str::to_string));
+            Some((channel.to_string(), key))
+        };
+
+        while let Some(batch) = self.startup.take_uncovered(entry_of) {
+            debug!(
+                "Replaying {} notifications held during startup",
+                batch.len()
+            );
+            for held in batch {
+                self.process_notification(&held.channel, &held.message)
+                    .await;
+            }
+        }
+    }
+
+    /// Sync all LAGs and their members from APPL_DB to SAI
+    ///
+    /// Members are applied once every LAG is, so each finds its LAG.
+    pub async fn sync_lags(&self) -> Result<()> {
+        info!("Syncing LAGs from APPL_DB to SAI");
+
+        self.startup.mark_scanned(tables::LAG_TABLE);
+        let keys = self
+            .db_client
+            .scan(
+                home_db(tables::LAG_TABLE),
+                &self.table_keys.pattern(tables::LAG_TABLE),
+            )
+            .await?;
+        for key in &keys {
+            if let Some(lag_name) = self.table_keys.strip(tables::LAG_TABLE, key) {
+                self.startup.mark_read(tables::LAG_TABLE, lag_name);
+            }
+        }
+        let entries: Vec<Option<LagEntry>> = self
+            .db_client
+            .get_many(home_db(tables::LAG_TABLE), &keys)
+            .await?;
+        for (key, entry) in keys.iter().zip(entries) {
+            let (Some(lag_name), Some(entry)) =
+                (self.table_keys.strip(tables::LAG_TABLE, key), entry)
+            else {
+                continue;
+            };
+            if let Err(e) = self.apply_lag(lag_name, &entry, false).await {
+                warn!("Failed to sync {}: {}", lag_name, e);
+            }
+        }
+
+        self.startup.mark_scanned(tables::LAG_MEMBER_TABLE);
+        let member_keys = self
+            .db_client
+            .scan(
+                home_db(tables::LAG_MEMBER_TABLE),
+                &self.table_keys.pattern(tables::LAG_MEMBER_TABLE),
+            )
+            .await?;
+        for key in &member_keys {
+            if let Some(member) = self.table_keys.strip(tables::LAG_MEMBER_TABLE, key) {
+                self.startup.mark_read(tables::LAG_MEMBER_TABLE, member);
+            }
+        }
+        self.apply_members(&member_keys).await;
+
+        info!("Synced {} LAGs", self.lags.len());
+        Ok(())
+    }
+
+    /// Create a LAG in SAI, or update the one already created
+    ///
+    /// A LAG created after its members were published picks them up from
+    /// APPL_DB, unless `with_members` is false.
+    async fn apply_lag(&self, lag_name: &str, entry: &LagEntry, with_members: bool) -> Result<()> {
+        if self.lags.contains_key(lag_name) {
+            self.set_min_links(lag_name, entry.min_links)?;
+        } else {
+            let lag_oid = self.lag_api.create(&[])?;
+            self.audit(SaiIntent::create(LAG_OBJECT_TYPE, lag_name).with_oid(lag_oid));
+            info!("Created {} (0x{:x})", lag_name, lag_oid);
+            self.add_lag(lag_name, lag_oid, entry.min_links);
+
+            if with_members {
+                let pattern = self
+                    .table_keys
+                    .key(tables::LAG_MEMBER_TABLE, &format!("{}:*", lag_name));
+                let member_keys = self
+                    .db_client
+                    .scan(home_db(tables::LAG_MEMBER_TABLE), &pattern)
+                    .await?;
+                self.apply_members(&member_keys).await;
+            }
+        }
+        self.set_lag_admin_status(lag_name, entry.admin_status.unwrap_or(PortAdminStatus::Up))?;
+        self.write_state(lag_name).await
+    }
+
+    /// Create the members of APPL_DB keys (`LAG_MEMBER_TABLE:PortChannel1:Ethernet0`)
+    async fn apply_members(&self, keys: &[String]) {
+        for key in keys {
+            let Some((lag_name, port)) = self
+                .table_keys
+                .strip(tables::LAG_MEMBER_TABLE, key)
+                .and_then(|member| member.split_once(':'))
+            else {
+                warn!("Malformed LAG member key: {}", key);
+                continue;
+            };
+            if let Err(e) = self.apply_member(lag_name, port).await {
+                warn!("Failed to add {} to {}: {}", port, lag_name, e);
+            }
+        }
+    }
+
+    /// Remove a LAG and its members from SAI
+    async fn delete_lag(&self, lag_name: &str) -> Result<()> {
+        let ports: Vec<String> = match self.lags.get(lag_name) {
+            Some(lag) => lag.members.keys().cloned().collect(),
+            None => return Ok(()),
+        };
+        for port in ports {
+            self.delete_member(lag_name, &port)?;
+        }
+
+        if let Some((_, lag)) = self.lags.remove(lag_name) {
+            self.lag_api.remove_lag(lag.oid)?;
+            self.audit(SaiIntent::remove(LAG_OBJECT_TYPE, lag_name, lag.oid));
+            info!("Removed {} (0x{:x})", lag_name, lag.oid);
+        }
+        self.write_state(lag_name).await
+    }
+
+    /// Create the member of `port` in a LAG
+    ///
+    /// Members of LAGs not created yet are added when their LAG is.
+    async fn apply_member(&self, lag_name: &str, port: &str) -> Result<()> {
+        let lag_oid = match self.lags.get(lag_name) {
+            Some(lag) if lag.members.contains_key(port) => return Ok(()),
+            Some(lag) => lag.oid,
+            None => {
+                debug!("{} is not created yet, deferring member {}", lag_name, port);
+                return Ok(());
+            }
+        };
+        let Some(port_oid) = self.registry.port_oid(port) else {
+            warn!("Port {} has no OID, not adding it to {}", port, lag_name);
+            return Ok(());
+        };
+
+        self.load_port_admin_status(port).await?;
+        let oper_status = self.port_oper_status(port).await?;
+        let member_oid = self.lag_api.create_member(lag_oid, port_oid)?;
+        let member_key = format!("{}:{}", lag_name, port);
+        self.audit(SaiIntent::create(LAG_MEMBER_OBJECT_TYPE, &member_key).with_oid(member_oid));
+        info!("Added {} to {} (0x{:x})", port, lag_name, member_oid);
+        self.add_member(lag_name, port, port_oid, member_oid, oper_status)?;
+        self.write_state(lag_name).await
+    }
+
+    /// Remove the member of `port` from SAI and stop tracking it
+    fn delete_member(&self, lag_name: &str, port: &str) -> Result<()> {
+        let member_oid = self
+            .lags
+            .get(lag_name)
+            .and_then(|lag| lag.members.get(port).map(|member| member.oid));
+        let Some(member_oid) = member_oid else {
+            return Ok(());
+        };
+
+        self.lag_api.remove_lag_member(member_oid)?;
+        self.audit(SaiIntent::remove(
+            LAG_MEMBER_OBJECT_TYPE,
+            &format!("{}:{}", lag_name, port),
+            member_oid,
+        ));
+        info!("Removed {} from {}", port, lag_name);
+        self.remove_member(lag_name, port)
+    }
+
+    /// Record the admin status `port` is configured with in APPL_DB
+    async fn load_port_admin_status(&self, port: &str) -> Result<()> {
+        let key = self.table_keys.key(tables::PORT_TABLE, port);
+        let entries: Vec<Option<PortAdminEntry>> = self
+            .db_client
+            .get_many(home_db(tables::PORT_TABLE), &[key])
+            .await?;
+        if let Some(status) = entries
+            .into_iter()
+            .flatten()
+            .find_map(|entry| entry.admin_status)
+        {
+            self.port_admin.insert(port.to_string(), status);
+        }
+        Ok(())
+    }
+
+    /// Oper status of a port from STATE_DB `PORT_STATE`; down without an
+    /// entry
+    async fn port_oper_status(&self, port: &str) -> Result<PortOperStatus> {
+        let keys = [self
+            .table_keys
+            .scoped(&format!("{}:{}", tables::PORT_STATE, port))];
+        let states: Vec<Option<serde_json::Value>> =
+            self.db_client.get_many(Database::State, &keys).await?;
+        let up = states
+            .into_iter()
+            .flatten()
+            .any(|state| state["oper_status"] == "up");
+        Ok(if up {
+            PortOperStatus::Up
+        } else {
+            PortOperStatus::Down
+        })
+    }
+
+    /// Apply a LAG change (`PortChannel1`)
+    async fn handle_lag_notification(&self, operation: Operation, lag_name: &str) -> Result<()> {
+        match operation {
+            Operation::Set | Operation::Hset => {
+                let key = self.table_keys.key(tables::LAG_TABLE, lag_name);
+                let entry: LagEntry = self.db_client.get(home_db(tables::LAG_TABLE), &key).await?;
+                self.apply_lag(lag_name, &entry, true).await
+            }
+            Operation::Del => self.delete_lag(lag_name).await,
+        }
+    }
+
+    /// Apply a LAG member change (`PortChannel1:Ethernet0`)
+    async fn handle_member_notification(&self, operation: Operation, key: &str) -> Result<()> {
+        let Some((lag_name, port)) = key.split_once(':') else {
+            warn!("Malformed LAG member key: {}", key);
+            return Ok(());
+        };
+
+        match operation {
+            Operation::Set | Operation::Hset => self.apply_member(lag_name, port).await,
+            Operation::Del => {
+                self.delete_member(lag_name, port)?;
+                self.write_state(lag_name).await
+            }
+        }
+    }
+
+    /// Apply a port's new oper status to its LAG
+    async fn port_oper_status_changed(&self, port: &str) -> Result<()> {
+        let lag_name = self
+            .lags
+            .iter()
+            .find(|lag| lag.members.contains_key(port))
+            .map(|lag| lag.key().clone());
+        let Some(lag_name) = lag_name else {
+            return Ok(());
+        };
+
+        let oper_status = self.port_oper_status(port).await?;
+        self.set_member_oper_status(port, oper_status)?;
+        self.write_state(&lag_name).await
+    }
+
+    /// Handle database notification
+    #[instrument(skip(self, message))]
+    pub async fn handle_notification(&self, channel: &str, message: &str) {
+        debug!("Received notification on {}: {}", channel, message);
+        if self.startup.hold(channel, message) {
+            debug!(
+                "Holding notification on {} until the startup scan ends",
+                channel
+            );
+            return;
+        }
+        self.process_notification(channel, message).await;
+    }
+
+    async fn process_notification(&self, channel: &str, message: &str) {
+        let channel = self.switch.local_channel(channel);
+
+        let notification: serde_json::Value = match serde_json::from_str(message) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to parse notification: {}", e);
+                return;
+            }
+        };
+        let Some(operation) = Operation::parse(notification["operation"].as_str().unwrap_or(""))
+        else {
+            return;
+        };
+        let Some(key) = notification["key"].as_str() else {
+            warn!("Notification on {} without key", channel);
+            return;
+        };
+
+        let result = match channel {
+            tables::LAG_TABLE => self.handle_lag_notification(operation, key).await,
+            tables::LAG_MEMBER_TABLE => self.handle_member_notification(operation, key).await,
+            PORT_STATE_CHANNEL => self.port_oper_status_changed(key).await,
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            error!("Failed to apply {} change to {}: {}", channel, key, e);
+        }
+    }
+
+    /// Start tracking a LAG; `None` uses [`DEFAULT_MIN_LINKS`]
+    pub fn add_lag(&self, lag_name: &str, lag_oid: SaiOid, min_links: Option<u32>) {
+        self.lags.insert(
+            lag_name.to_string(),
+            TrackedLag {
+                oid: lag_oid,
+                min_links: min_links.unwrap_or(DEFAULT_MIN_LINKS),
+                members: BTreeMap::new(),
+                forwarding: true,
+                admin_status: PortAdminStatus::Up,
+            },
+        );
+    }
+
+    pub fn remove_lag(&self, lag_name: &str) {
+        self.lags.remove(lag_name);
+    }
+
+    /// Change the threshold of a tracked LAG
+    pub fn set_min_links(&self, lag_name: &str, min_links: Option<u32>) -> Result<()> {
+        let Some(mut lag) = self.lags.get_mut(lag_name) else {
+            warn!("min_links set on unknown LAG {}", lag_name);
+            return Ok(());
+        };
+        lag.min_links = min_links.unwrap_or(DEFAULT_MIN_LINKS);
+        self.enforce(lag_name, &mut lag)
+    }
+
+    /// Track a member created in SAI
+    ///
+    /// A member joining a LAG that is below `min_links` starts disabled, and
+    /// one joining an admin-down LAG has its port taken admin down.
+    pub fn add_member(
+        &self,
+        lag_name: &str,
+        port: &str,
+        port_oid: SaiOid,
+        member_oid: SaiOid,
+        oper_status: PortOperStatus,
+    ) -> Result<()> {
+        let Some(mut lag) = self.lags.get_mut(lag_name) else {
+            warn!("Member {} added to unknown LAG {}", port, lag_name);
+            return Ok(());
+        };
+        lag.members.insert(
+            port.to_string(),
+            LagMember {
+                oid: member_oid,
+                port_oid,
+                oper_up: oper_status == PortOperStatus::Up,
+            },
+        );
+
+        if !lag.forwarding && !lag.should_forward() {
+            self.set_member_enabled(lag_name, port, member_oid, false)?;
+        }
+        if lag.admin_status == PortAdminStatus::Down
+            && self.port_admin_status(port) == PortAdminStatus::Up
+        {
+            self.set_port_admin(port, port_oid, PortAdminStatus::Down)?;
+        }
+        self.enforce(lag_name, &mut lag)
+    }
+
+    /// Stop tracking a member
+    ///
+    /// A port leaving an admin-down LAG gets its own admin status back.
+    pub fn remove_member(&self, lag_name: &str, port: &str) -> Result<()> {
+        let Some(mut lag) = self.lags.get_mut(lag_name) else {
+            return Ok(());
+        };
+        if let Some(member) = lag.members.remove(port) {
+            let configured = self.port_admin_status(port);
+            if lag.admin_status == PortAdminStatus::Down && configured == PortAdminStatus::Up {
+                self.set_port_admin(port, member.port_oid, configured)?;
+            }
+        }
+        self.enforce(lag_name, &mut lag)
+    }
+
+    /// Apply a LAG's admin status change to its member ports
+    ///
+    /// Going down takes every member port admin down; coming back up
+    /// restores each port's configured status. Ports configured down on
+    /// their own are not touched either way.
+    pub fn set_lag_admin_status(&self, lag_name: &str, status: PortAdminStatus) -> Result<()> {
+        let Some(mut lag) = self.lags.get_mut(lag_name) else {
+            warn!("Admin status set on unknown LAG {}", lag_name);
+            return Ok(());
+        };
+        if lag.admin_status == status {
+            return Ok(());
+        }
+
+        info!("{} admin {}, applying to its members", lag_name, status);
+        for (port, member) in &lag.members {
+            if self.port_admin_status(port) == PortAdminStatus::Up {
+                self.set_port_admin(port, member.port_oid, status)?;
+            } else {
+                debug!("{} is admin down on its own, leaving it", port);
+            }
+        }
+        lag.admin_status = status;
+        Ok(())
+    }
+
+    /// Record and apply a port's own configured admin status
+    ///
+    /// A member of an admin-down LAG stays down; its status is applied once
+    /// the LAG comes back up or the port leaves it.
+    pub fn set_port_admin_status(
+        &self,
+        port: &str,
+        port_oid: SaiOid,
+        status: PortAdminStatus,
+    ) -> Result<()> {
+        self.port_admin.insert(port.to_string(), status);
+
+        let held_down = self
+            .lags
+            .iter()
+            .any(|lag| lag.admin_status == PortAdminStatus::Down && lag.members.contains_key(port));
+        if held_down {
+            debug!(
+                "{} is held down by its LAG, deferring admin {}",
+                port, status
+            );
+            return Ok(());
+        }
+        self.set_port_admin(port, port_oid, status)
+    }
+
+    /// Admin status a port is configured with
+    fn port_admin_status(&self, port: &str) -> PortAdminStatus {
+        self.port_admin
+            .get(port)
+            .map_or(PortAdminStatus::Up, |status| *status)
+    }
+
+    /// React to a member port's oper status change
+    pub fn set_member_oper_status(&self, port: &str, oper_status: PortOperStatus) -> Result<()> {
+        for mut entry in self.lags.iter_mut() {
+            let (lag_name, lag) = entry.pair_mut();
+            if let Some(member) = lag.members.get_mut(port) {
+                member.oper_up = oper_status == PortOperStatus::Up;
+                debug!("{} member {} is {:?}", lag_name, port, oper_status);
+                let lag_name = lag_name.clone();
+                return self.enforce(&lag_name, lag);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a LAG is forwarding, `None` if it is not tracked
+    pub fn is_forwarding(&self, lag_name: &str) -> Option<bool> {
+        self.lags.get(lag_name).map(|lag| lag.forwarding)
+    }
+
+    /// Current state of a LAG, `None` if it is not tracked
+    ///
+    /// Reads the member list from SAI. A member is active when its port is
+    /// oper up and the LAG meets `min_links`; members SAI reports that were
+    /// never tracked count but are not active.
+    pub fn lag_state(&self, lag_name: &str) -> Result<Option<LagState>> {
+        let Some(lag) = self.lags.get(lag_name) else {
+            return Ok(None);
+        };
+        let member_oids = self.lag_api.get_member_list(lag.oid)?;
+
+        let active_members: Vec<String> = lag
+            .members
+            .iter()
+            .filter(|(_, member)| {
+                lag.forwarding && member.oper_up && member_oids.contains(&member.oid)
+            })
+            .map(|(port, _)| port.clone())
+            .collect();
+        for oid in &member_oids {
+            if !lag.members.values().any(|member| member.oid == *oid) {
+                debug!("{} member 0x{:x} is not tracked", lag_name, oid);
+            }
+        }
+
+        Ok(Some(LagState {
+            member_count: member_oids.len(),
+            oper_status: if active_members.is_empty() {
+                "down"
+            } else {
+                "up"
+            }
+            .to_string(),
+            active_members,
+        }))
+    }
+
+    /// Write a LAG's state to STATE_DB, or remove it if the LAG is not tracked
+    pub async fn write_state(&self, lag_name: &str) -> Result<()> {
+        let key = self.table_keys.scoped(&LagState::key(lag_name));
+        match self.lag_state(lag_name)? {
+            Some(state) => {
+                self.db_client.set(Database::State, &key, &state).await?;
+                debug!("Wrote state for {}: {:?}", lag_name, state);
+                Ok(())
+            }
+            None => self.db_client.del(Database::State, &key).await,
+        }
+    }
+
+    /// Read the counters of every tracked LAG, summed over its members
+    ///
+    /// LAGs whose member counters can't be read are skipped.
+    pub fn collect_counters(&self) -> Vec<(String, Counters)> {
+        let counter_ids: Vec<sai_port_stat_t> = LAG_COUNTERS.iter().map(|(id, _)| *id).collect();
+
+        self.lags
+            .iter()
+            .filter_map(|lag| {
+                let member_ports: Vec<SaiOid> =
+                    lag.members.values().map(|member| member.port_oid).collect();
+                let values =
+                    match self
+                        .lag_api
+                        .get_stats(&self.port_api, &member_ports, &counter_ids)
+                    {
+                        Ok(values) => values,
+                        Err(e) => {
+                            debug!("No counters for {}: {}", lag.key(), e);
+                            return None;
+                        }
+                    };
+
+                let values = LAG_COUNTERS
+                    .iter()
+                    .zip(values)
+                    .map(|((_, name), value)| (name.to_string(), value))
+                    .collect();
+                Some((lag.key().clone(), Counters { values }))
+            })
+            .collect()
+    }
+
+    /// Write the counters of every tracked LAG to COUNTERS_DB
+    pub async fn poll_counters(&self) -> Result<()> {
+        for (lag_name, counters) in self.collect_counters() {
+            let fields = counters
+                .values
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_string()))
+                .collect();
+            self.db_client
+                .hset_multiple(
+                    home_db(tables::COUNTERS),
+                    &self
+                        .table_keys
+                        .scoped(&format!("{}:{}", tables::COUNTERS, lag_name)),
+                    &fields,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Enable or disable all members when the LAG crosses `min_links`
+    fn enforce(&self, lag_name: &str, lag: &mut TrackedLag) -> Result<()> {
+        let forward = lag.should_forward();
+        if forward == lag.forwarding {
+            return Ok(());
+        }
+
+        if forward {
+            info!(
+                "{} has {} of {} required members up, bringing it up",
+                lag_name,
+                lag.up_members(),
+                lag.min_links
+            );
+        } else {
+            warn!(
+                "{} has {} of {} required members up, bringing it down",
+                lag_name,
+                lag.up_members(),
+                lag.min_links
+            );
+        }
+
+        for (port, member) in &lag.members {
+            self.set_member_enabled(lag_name, port, member.oid, forward)?;
+        }
+        lag.forwarding = forward;
+        Ok(())
+    }
+}
+
+/// Database subscriber implementation for LagSync
+pub struct LagSyncSubscriber {
+    lag_sync: Arc<LagSync>,
+    /// Channels confirmed so far
+    subscribed: watch::Sender<usize>,
+}
+
+impl LagSyncSubscriber {
+    pub fn new(lag_sync: Arc<LagSync>) -> Self {
+        Self {
+            lag_sync,
+            subscribed: watch::Sender::new(0),
+        }
+    }
+
+    /// Wait until `channels` subscriptions have been confirmed
+    pub async fn wait_subscribed(&self, channels: usize) {
+        let mut subscribed = self.subscribed.subscribe();
+        // The sender lives in self, so the channel can't close while we wait
+        let _ = subscribed.wait_for(|count| *count >= channels).await;
+    }
+}
+
+#[async_trait]
+impl DbSubscriber for LagSyncSubscriber {
+    async fn on_message(&self, channel: String, message: String) {
+        self.lag_sync.handle_notification(&channel, &message).await;
+    }
+
+    async fn on_subscribe(&self, channel: String) {
+        info!("LagSync subscribed to channel: {}", channel);
+        self.subscribed.send_modify(|count| *count += 1);
+    }
+
+    async fn on_resubscribe(&self) {
+        // Notifications may have been lost while the subscription was wedged
+        if let Err(e) = self.lag_sync.sync_lags().await {
+            warn!("LagSync resync after resubscribe failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_sai::{SAI_LAG_MEMBER_ATTR_EGRESS_DISABLE, mock};
+
+    async fn test_lag_sync(lag_api: Arc<LagApi>) -> LagSync {
+        // DbClient connects lazily, so paths that skip the DB need no server
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        LagSync::new(
+            db_client,
+            lag_api,
+            Arc::new(mock::port_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+    }
+
+    /// Egress-disable values set per member, in call order
+    fn disables() -> Vec<(SaiOid, u64)> {
+        mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "set_lag_member_attribute")
+            .flat_map(|call| {
+                call.attrs
+                    .into_iter()
+                    .filter(|(id, _)| *id == SAI_LAG_MEMBER_ATTR_EGRESS_DISABLE)
+                    .map(move |(_, value)| (call.oid, value))
+            })
+            .collect()
+    }
+
+    /// Admin state values set per port, in call order
+    fn admin_states() -> Vec<(SaiOid, u64)> {
+        mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "set_port_attribute")
+            .flat_map(|call| {
+                call.attrs
+                    .into_iter()
+                    .filter(|(id, _)| *id == SAI_PORT_ATTR_ADMIN_STATE)
+                    .map(move |(_, value)| (call.oid, value))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_lag_admin_status_propagates_to_members() {
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_oid = lag_api.create(&[]).unwrap();
+        let ports: [(&str, SaiOid); 2] = [
+            ("Ethernet0", 0x1000000000001),
+            ("Ethernet4", 0x1000000000002),
+        ];
+
+        let lag_sync = test_lag_sync(lag_api.clone()).await;
+        lag_sync.add_lag("PortChannel1", lag_oid, None);
+        for (port, port_oid) in ports {
+            let member = lag_api.create_member(lag_oid, port_oid).unwrap();
+            lag_sync
+                .add_member("PortChannel1", port, port_oid, member, PortOperStatus::Up)
+                .unwrap();
+        }
+        // Ethernet4 is shut on its own
+        lag_sync
+            .set_port_admin_status("Ethernet4", ports[1].1, PortAdminStatus::Down)
+            .unwrap();
+        mock::take_calls();
+
+        lag_sync
+            .set_lag_admin_status("PortChannel1", PortAdminStatus::Down)
+            .unwrap();
+        assert_eq!(admin_states(), vec![(ports[0].1, 0)]);
+
+        // Configuring Ethernet0 while the LAG holds it down is deferred
+        lag_sync
+            .set_port_admin_status("Ethernet0", ports[0].1, PortAdminStatus::Up)
+            .unwrap();
+        assert!(admin_states().is_empty());
+
+        // LAG up restores Ethernet0 and leaves the shut Ethernet4 down
+        lag_sync
+            .set_lag_admin_status("PortChannel1", PortAdminStatus::Up)
+            .unwrap();
+        assert_eq!(admin_states(), vec![(ports[0].1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_batched_lag_flap_leaves_ports_alone() {
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_oid = lag_api.create(&[]).unwrap();
+        let port_oid = 0x1000000000001;
+
+        let lag_sync = test_lag_sync(lag_api.clone())
+            .await
+            .with_attribute_batcher(Arc::new(AttributeBatcher::default()));
+        lag_sync.add_lag("PortChannel1", lag_oid, None);
+        let member = lag_api.create_member(lag_oid, port_oid).unwrap();
+        lag_sync
+            .add_member(
+                "PortChannel1",
+                "Ethernet0",
+                port_oid,
+                member,
+                PortOperStatus::Up,
+            )
+            .unwrap();
+        mock::take_calls();
+
+        lag_sync
+            .set_lag_admin_status("PortChannel1", PortAdminStatus::Down)
+            .unwrap();
+        lag_sync
+            .set_lag_admin_status("PortChannel1", PortAdminStatus::Up)
+            .unwrap();
+        assert!(admin_states().is_empty());
+
+        // Down then up collapses into a single set of the final state
+        lag_sync.flush_port_attributes();
+        assert_eq!(admin_states(), vec![(port_oid, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_below_min_links_disables_members() {
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_oid = lag_api.create(&[]).unwrap();
+        let members: Vec<SaiOid> = (1..=3)
+            .map(|port| lag_api.create_member(lag_oid, port).unwrap())
+            .collect();
+
+        let lag_sync = test_lag_sync(lag_api).await;
+        lag_sync.add_lag("PortChannel1", lag_oid, Some(2));
+        for (i, oid) in members.iter().enumerate() {
+            lag_sync
+                .add_member(
+                    "PortChannel1",
+                    &format!("Ethernet{}", i * 4),
+                    i as SaiOid + 1,
+                    *oid,
+                    PortOperStatus::Up,
+                )
+                .unwrap();
+        }
+        mock::take_calls();
+
+        // 2 of 3 up still meets min_links
+        lag_sync
+            .set_member_oper_status("Ethernet0", PortOperStatus::Down)
+            .unwrap();
+        assert_eq!(lag_sync.is_forwarding("PortChannel1"), Some(true));
+        assert!(disables().is_empty());
+
+        // 1 of 3 up takes the whole LAG down
+        lag_sync
+            .set_member_oper_status("Ethernet4", PortOperStatus::Down)
+            .unwrap();
+        assert_eq!(lag_sync.is_forwarding("PortChannel1"), Some(false));
+        assert_eq!(
+            disables(),
+            members.iter().map(|oid| (*oid, 1)).collect::<Vec<_>>()
+        );
+
+        // Recovering one member re-enables all of them
+        lag_sync
+            .set_member_oper_status("Ethernet0", PortOperStatus::Up)
+            .unwrap();
+        assert_eq!(lag_sync.is_forwarding("PortChannel1"), Some(true));
+        assert_eq!(
+            disables(),
+            members.iter().map(|oid| (*oid, 0)).collect::<Vec<_>>()
+        );
+
+        // Ports outside any LAG are ignored
+        lag_sync
+            .set_member_oper_status("Ethernet64", PortOperStatus::Down)
+            .unwrap();
+        assert!(disables().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_state_from_member_list() {
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_sync = test_lag_sync(lag_api.clone()).await;
+
+        // An empty LAG is down
+        let lag_oid = lag_api.create(&[]).unwrap();
+        lag_sync.add_lag("PortChannel1", lag_oid, None);
+        let state = lag_sync.lag_state("PortChannel1").unwrap().unwrap();
+        assert_eq!(state.member_count, 0);
+        assert!(state.active_members.is_empty());
+        assert_eq!(state.oper_status, "down");
+
+        for (port, port_oid, oper_status) in [
+            ("Ethernet0", 0x1000000000001, PortOperStatus::Up),
+            ("Ethernet4", 0x1000000000002, PortOperStatus::Down),
+        ] {
+            let member = lag_api.create_member(lag_oid, port_oid).unwrap();
+            lag_sync
+                .add_member("PortChannel1", port, port_oid, member, oper_status)
+                .unwrap();
+        }
+        mock::take_calls();
+
+        let state = lag_sync.lag_state("PortChannel1").unwrap().unwrap();
+        assert_eq!(
+            state,
+            LagState {
+                member_count: 2,
+                active_members: vec!["Ethernet0".to_string()],
+                oper_status: "up".to_string(),
+            }
+        );
+        let functions: Vec<&str> = mock::take_calls().iter().map(|c| c.function).collect();
+        assert_eq!(functions, vec!["get_lag_attribute"]);
+
+        assert_eq!(lag_sync.lag_state("PortChannel2").unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_attribute_sets_audited() {
+        let dir = std::env::temp_dir().join(format!("racoon-lag-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        let _ = std::fs::remove_file(&path);
+        let audit_log = Arc::new(AuditLog::open(&path, 1 << 20, 1).unwrap());
+
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_sync = test_lag_sync(lag_api.clone())
+            .await
+            .with_audit_log(audit_log.clone());
+        let lag_oid = lag_api.create(&[]).unwrap();
+        lag_sync.add_lag("PortChannel1", lag_oid, Some(2));
+        let member = lag_api.create_member(lag_oid, 0x1000000000001).unwrap();
+        // One member of two required disables it
+        lag_sync
+            .add_member(
+                "PortChannel1",
+                "Ethernet0",
+                0x1000000000001,
+                member,
+                PortOperStatus::Up,
+            )
+            .unwrap();
+        lag_sync
+            .set_lag_admin_status("PortChannel1", PortAdminStatus::Down)
+            .unwrap();
+        drop(lag_sync);
+        drop(Arc::into_inner(audit_log));
+
+        let records: Vec<AuditRecord> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let sets: Vec<(&str, &str, &str)> = records
+            .iter()
+            .map(|record| {
+                (
+                    record.key.as_str(),
+                    record.attr.as_deref().unwrap(),
+                    record.value.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sets,
+            [
+                ("PortChannel1:Ethernet0", "enabled", "false"),
+                ("Ethernet0", "admin_status", "down"),
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_state_written_to_state_db() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_sync = LagSync::new(
+            db_client.clone(),
+            lag_api.clone(),
+            Arc::new(mock::port_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        let lag_oid = lag_api.create(&[]).unwrap();
+        lag_sync.add_lag("PortChannel1", lag_oid, None);
+        for (port, port_oid) in [
+            ("Ethernet0", 0x1000000000001),
+            ("Ethernet4", 0x1000000000002),
+        ] {
+            let member = lag_api.create_member(lag_oid, port_oid).unwrap();
+            lag_sync
+                .add_member("PortChannel1", port, port_oid, member, PortOperStatus::Up)
+                .unwrap();
+        }
+
+        lag_sync.write_state("PortChannel1").await.unwrap();
+        let state: LagState = db_client
+            .get(Database::State, &LagState::key("PortChannel1"))
+            .await
+            .unwrap();
+        assert_eq!(state.member_count, 2);
+        assert_eq!(state.active_members, vec!["Ethernet0", "Ethernet4"]);
+        assert_eq!(state.oper_status, "up");
+
+        // A LAG no longer tracked has its state removed
+        lag_sync.remove_lag("PortChannel1");
+        lag_sync.write_state("PortChannel1").await.unwrap();
+        assert!(
+            !db_client
+                .exists(Database::State, &LagState::key("PortChannel1"))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_counters_summed_over_members() {
+        let lag_api = Arc::new(mock::lag_api().with_switch_id(0x21000000000000));
+        let lag_sync = test_lag_sync(lag_api.clone()).await;
+        let lag_oid = lag_api.create(&[]).unwrap();
+        lag_sync.add_lag("PortChannel1", lag_oid, None);
+        let ports = [mock::add_port(&[1], 25_000), mock::add_port(&[2], 25_000)];
+        for (i, port_oid) in ports.iter().enumerate() {
+            let member = lag_api.create_member(lag_oid, *port_oid).unwrap();
+            lag_sync
+                .add_member(
+                    "PortChannel1",
+                    &format!("Ethernet{}", i * 4),
+                    *port_oid,
+                    member,
+                    PortOperStatus::Up,
+                )
+                .unwrap();
+        }
+
+        let counters = lag_sync.collect_counters();
+        assert_eq!(counters.len(), 1);
+        let (lag_name, fields) = &counters[0];
+        assert_eq!(lag_name, "PortChannel1");
+        assert_eq!(fields.values.len(), LAG_COUNTERS.len());
+        assert_eq!(
+            fields.values["SAI_PORT_STAT_IF_IN_OCTETS"],
+            ports
+                .iter()
+                .map(|oid| mock::stat_value(*oid, SAI_PORT_STAT_IF_IN_OCTETS))
+                .sum::<u64>()
+        );
+
+        // A removed LAG is no longer polled
+        lag_sync.remove_lag("PortChannel1");
+        assert!(lag_sync.collect_counters().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lag_config_applied_from_appl_db() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let registry = Arc::new(ObjectRegistry::new());
+        let ethernet0 = mock::add_port(&[0, 1, 2, 3], 100000);
+        registry.register_port("Ethernet0", ethernet0);
+        let lag_sync = LagSync::new(
+            db_client.clone(),
+            Arc::new(mock::lag_api().with_switch_id(0x21000000000000)),
+            Arc::new(mock::port_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_object_registry(registry);
+
+        let down = LagEntry {
+            admin_status: Some(PortAdminStatus::Down),
+            min_links: None,
+        };
+        db_client
+            .set(Database::Appl, "LAG_TABLE:PortChannel1", &down)
+            .await
+            .unwrap();
+        db_client
+            .set(
+                Database::Appl,
+                "LAG_MEMBER_TABLE:PortChannel1:Ethernet0",
+                &serde_json::json!({ "status": "enabled" }),
+            )
+            .await
+            .unwrap();
+        mock::take_calls();
+
+        // The startup scan creates the LAG and its member, and the admin
+        // down LAG takes the port down
+        lag_sync.start().await.unwrap();
+        let functions: Vec<&str> = mock::take_calls()
+            .iter()
+            .map(|call| call.function)
+            .filter(|function| function.starts_with("create_") || *function == "set_port_attribute")
+            .collect();
+        assert_eq!(
+            functions,
+            vec!["create_lag", "create_lag_member", "set_port_attribute"]
+        );
+        assert_eq!(
+            lag_sync
+                .lag_state("PortChannel1")
+                .unwrap()
+                .unwrap()
+                .member_count,
+            1
+        );
+
+        // Bringing the LAG up restores the port
+        db_client
+            .set(
+                Database::Appl,
+                "LAG_TABLE:PortChannel1",
+                &LagEntry::default(),
+            )
+            .await
+            .unwrap();
+        let message = serde_json::json!({ "operation": "SET", "key": "PortChannel1" });
+        lag_sync
+            .handle_notification("LAG_TABLE", &message.to_string())
+            .await;
+        assert_eq!(admin_states(), vec![(ethernet0, 1)]);
+
+        // Deleting the LAG removes its member first
+        let message = serde_json::json!({ "operation": "DEL", "key": "PortChannel1" });
+        lag_sync
+            .handle_notification("LAG_TABLE", &message.to_string())
+            .await;
+        let functions: Vec<&str> = mock::take_calls()
+            .iter()
+            .map(|call| call.function)
+            .filter(|function| function.starts_with("remove_"))
+            .collect();
+        assert_eq!(functions, vec!["remove_lag_member", "remove_lag"]);
+        assert_eq!(lag_sync.is_forwarding("PortChannel1"), None);
+    }
+}
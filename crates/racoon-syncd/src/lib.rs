@@ -2,6 +2,87 @@
 //!
 //! Synchronizes database state to hardware via SAI
 
+pub mod attr_batch;
+pub mod audit_log;
+pub mod clock;
+pub mod fdb_sync;
+pub mod fdb_table;
+pub mod intent_log;
+pub mod lag_sync;
+pub mod object_registry;
+pub mod port_map;
+pub mod port_serdes;
+pub mod retry;
+pub mod startup;
+pub mod switch_context;
+pub mod switch_state;
+pub mod vlan_state;
 pub mod vlan_sync;
 
-pub use vlan_sync::{VlanSync, VlanSyncSubscriber};
+pub use attr_batch::{AttributeBatcher, DEFAULT_BATCH_WINDOW};
+pub use audit_log::{AuditLog, AuditOp, AuditRecord};
+pub use clock::{Clock, TokioClock};
+pub use fdb_sync::{FdbEntry, FdbSync, FdbSyncSubscriber};
+pub use fdb_table::FdbTable;
+pub use intent_log::{INTENT_TABLE, IntentLog, IntentOp, SaiIntent};
+pub use lag_sync::{DEFAULT_MIN_LINKS, LagEntry, LagState, LagSync, LagSyncSubscriber};
+pub use object_registry::ObjectRegistry;
+pub use port_map::{PortEntry, register_bridge_ports, register_ports};
+pub use port_serdes::{PortSerdesState, publish_port_serdes_state};
+pub use retry::RetryPolicy;
+pub use startup::StartupWindow;
+pub use switch_context::SwitchContext;
+pub use switch_state::{
+    SWITCH_STATE_KEY, SwitchState, mark_warm_shutdown, publish_switch_state, take_warm_shutdown,
+};
+pub use vlan_state::{PORT_STATE_CHANNEL, VlanState, VlanStateWriter};
+pub use vlan_sync::{AsicVlanRecord, VlanMemberEntry, VlanSync, VlanSyncSubscriber};
+
+use racoon_common::config::SyncAgent;
+
+/// APPL_DB channels consumed by each agent
+pub fn agent_channels(agent: SyncAgent) -> &'static [&'static str] {
+    match agent {
+        SyncAgent::Vlan => &["VLAN_TABLE", "VLAN_MEMBER_TABLE", PORT_STATE_CHANNEL],
+        SyncAgent::Lag => &["LAG_TABLE", "LAG_MEMBER_TABLE", PORT_STATE_CHANNEL],
+        SyncAgent::Fdb => &["FDB_TABLE"],
+    }
+}
+
+/// Channels to subscribe to for the enabled agents, each once
+pub fn subscription_channels(agents: &[SyncAgent]) -> Vec<String> {
+    let mut channels: Vec<String> = Vec::new();
+    for channel in agents.iter().flat_map(|agent| agent_channels(*agent)) {
+        if !channels.iter().any(|c| c == channel) {
+            channels.push(channel.to_string());
+        }
+    }
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_fdb_subscription_without_fdb_agent() {
+        let channels = subscription_channels(&[SyncAgent::Vlan, SyncAgent::Lag]);
+        assert_eq!(
+            channels,
+            vec![
+                "VLAN_TABLE",
+                "VLAN_MEMBER_TABLE",
+                "PORT_STATE",
+                "LAG_TABLE",
+                "LAG_MEMBER_TABLE"
+            ]
+        );
+        assert!(!channels.contains(&"FDB_TABLE".to_string()));
+    }
+
+    #[test]
+    fn test_fdb_agent_subscribes_to_fdb_table() {
+        let channels = subscription_channels(&[SyncAgent::Fdb]);
+        assert_eq!(channels, vec!["FDB_TABLE"]);
+    }
+}
@@ -3,40 +3,120 @@
 //! Synchronizes database state to hardware via SAI
 
 use anyhow::Result;
-use racoon_db_client::{DbClient, DbSubscriberClient};
-use racoon_sai::{SaiAdapter, VlanApi};
-use racoon_syncd::{VlanSync, VlanSyncSubscriber};
+use arc_swap::ArcSwap;
+use racoon_common::Config;
+use racoon_common::config::SyncAgent;
+use racoon_common::logging::{LogLevelHandle, init_logging, shutdown_tracing};
+use racoon_common::reload::{SharedConfig, reload_config};
+use racoon_common::{RacoonError, VlanId};
+use racoon_db_client::{DbClient, DbSubscriber};
+use racoon_sai::fdb::FdbApi;
+use racoon_sai::lag::LagApi;
+use racoon_sai::port::PortApi;
+use racoon_sai::{
+    BridgeApi, BridgePortTable, RouterInterfaceApi, SaiAdapter, SaiBootType, SaiObjectType,
+    SwitchApi, SwitchOperStatus, VlanApi,
+};
+use racoon_syncd::clock;
+use racoon_syncd::{
+    AttributeBatcher, AuditLog, FdbSync, FdbSyncSubscriber, LagSync, LagSyncSubscriber,
+    ObjectRegistry, SwitchContext, TokioClock, VlanSync, VlanSyncSubscriber, agent_channels,
+    mark_warm_shutdown, publish_switch_state, register_bridge_ports, register_ports,
+    take_warm_shutdown,
+};
+use std::collections::HashSet;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::task::JoinSet;
 use tracing::{error, info, warn};
 
+/// Settings applied on SIGHUP besides the log level; all of them renew
+/// the subscriptions and counter pollers
+const HOT_SETTINGS: [&str; 3] = [
+    "services.agents",
+    "database.pubsub_watchdog_secs",
+    "counters.poll_interval_secs",
+];
+
+/// How long the startup scan waits for the subscriptions to be confirmed
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Switch ID of the first ASIC; further ASICs follow it
+const SWITCH_ID_BASE: u64 = 0x21000000000000;
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .with_thread_ids(true)
-        .with_level(true)
-        .init();
+    // Load configuration; built-in defaults apply when the file is unavailable
+    let config_path =
+        std::env::var("RACOON_CONFIG").unwrap_or_else(|_| "/etc/racoon/racoon.toml".to_string());
+    let (config, load_error) = match Config::load(&config_path) {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+
+    // Initialize tracing as the [logging] section asks
+    let log_level = init_logging(&config.logging)?;
 
     info!("Starting Racoon SAI Synchronization Daemon (syncd)");
+    if let Some(e) = load_error {
+        warn!("Failed to load config from {}: {}", config_path, e);
+    }
+    // SIGHUP applies the log level and HOT_SETTINGS; other settings keep
+    // their startup value until a restart
+    let config: SharedConfig = Arc::new(ArcSwap::from_pointee(config));
+    let mut hangup = signal(SignalKind::hangup())?;
+    let startup_config = config.load_full();
 
-    // Get database URL from environment or use default
+    // Database URL from the environment, then the config
     let db_url =
-        std::env::var("RACOON_DB_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        std::env::var("RACOON_DB_URL").unwrap_or_else(|_| startup_config.database.to_url());
     info!("Connecting to database: {}", db_url);
 
     // Create database client
-    let db_client = Arc::new(DbClient::new(&db_url).await?);
+    // Channel namespace shared by publisher and subscribers
+    let channel_prefix = startup_config.database.channel_prefix.clone();
+    let mut db_client = DbClient::new(&db_url).await?;
+    if let Some(prefix) = &channel_prefix {
+        info!("Using pub/sub channel prefix: {}", prefix);
+        db_client = db_client.with_channel_prefix(prefix);
+    }
+    let db_client = Arc::new(db_client);
     info!("Database client connected");
 
+    // Select sync agents from configuration; all of them run without one
+    for agent in startup_config.services.sync_agents() {
+        if agent_channels(agent).is_empty() {
+            warn!("{} agent is not available in syncd, skipping", agent.name());
+        }
+    }
+
     // Get SAI library path from environment
     let sai_lib_path =
         std::env::var("SAI_LIBRARY_PATH").unwrap_or_else(|_| "/usr/lib/libsai.so".to_string());
 
     info!("Loading SAI library from: {}", sai_lib_path);
 
-    // Initialize SAI adapter
-    let sai_adapter = match SaiAdapter::load(&sai_lib_path) {
+    // Initialize SAI adapter; a warm boot restores the state saved on the
+    // last warm shutdown, so it is only attempted when one was recorded
+    let warm_boot = startup_config.features.warm_boot;
+    let table_keys = startup_config.database.table_keys();
+    let warm_shutdown = take_warm_shutdown(&db_client, &table_keys)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to read warm shutdown marker, booting cold: {}", e);
+            false
+        });
+    let boot_type = if warm_boot && warm_shutdown {
+        SaiBootType::Warm
+    } else {
+        if warm_boot {
+            info!("No warm shutdown recorded, booting cold");
+        }
+        SaiBootType::Cold
+    };
+    let sai_adapter = match SaiAdapter::load_with_boot_type(&sai_lib_path, boot_type) {
         Ok(adapter) => {
             info!("SAI adapter initialized successfully");
             adapter
@@ -51,37 +131,496 @@ async fn main() -> Result<()> {
         }
     };
 
-    // Get switch ID (for real hardware, this would come from SAI initialization)
-    // For now, use a dummy switch ID
-    let switch_id: u64 = 0x21000000000000;
-    info!("Using switch ID: 0x{:x}", switch_id);
+    // One switch per ASIC (for real hardware, these would come from SAI
+    // initialization). For now, use dummy switch IDs
+    let asic_count = startup_config.platform.asic_count.max(1);
+    let default_vlan = startup_config.vlan.default_vlan.and_then(VlanId::new);
+    let switches: Vec<SwitchContext> = (0..asic_count)
+        .map(|index| {
+            let context = SwitchContext::new(SWITCH_ID_BASE + index as u64, index);
+            match default_vlan {
+                Some(vlan) => context.with_default_vlan(vlan),
+                None => context,
+            }
+        })
+        .collect();
+    for switch in &switches {
+        info!(
+            "Using switch ID 0x{:x} for ASIC {}",
+            switch.switch_id, switch.index
+        );
+    }
+
+    // Switch state is reported for the first ASIC
+    let switch_id = switches[0].switch_id;
+
+    // Report whether the switch came up, and keep STATE_DB current
+    let switch_api = Arc::new(
+        SwitchApi::new(sai_adapter.get_switch_api() as *const _)
+            .with_capability_query(sai_adapter.query_attribute_capability_fn()),
+    );
+    match switch_api.get_oper_status(switch_id) {
+        Ok(status) => {
+            if status != SwitchOperStatus::Up {
+                warn!("Switch is not operationally up: {}", status.as_str());
+            }
+            if let Err(e) = publish_switch_state(&db_client, switch_id, status).await {
+                warn!("Failed to publish switch state: {}", e);
+            }
+        }
+        Err(e) => warn!("Failed to read switch oper status: {}", e),
+    }
+
+    let (state_tx, mut state_rx) = tokio::sync::mpsc::unbounded_channel();
+    if let Err(e) = switch_api.register_state_change(switch_id, move |switch_id, status| {
+        let _ = state_tx.send((switch_id, status));
+    }) {
+        warn!("Failed to register switch state change notification: {}", e);
+    }
+    let state_db = db_client.clone();
+    tokio::spawn(async move {
+        while let Some((switch_id, status)) = state_rx.recv().await {
+            if let Err(e) = publish_switch_state(&state_db, switch_id, status).await {
+                warn!("Failed to publish switch state: {}", e);
+            }
+        }
+    });
 
     // Create VLAN API from the adapter's VLAN API table
     let vlan_api_table = sai_adapter.get_vlan_api() as *const _;
-    let vlan_api = Arc::new(VlanApi::new(vlan_api_table));
+    let vlan_api = Arc::new(
+        VlanApi::new(vlan_api_table).with_object_key_query(sai_adapter.get_object_key_fn()),
+    );
+    // VLAN router interfaces need it; libraries without one only bridge
+    let rif_api = sai_adapter
+        .get_router_interface_api()
+        .map(|table| Arc::new(RouterInterfaceApi::new(table as *const _)));
+    // Ports are named by their lanes and report serdes state through it
+    let port_api = Arc::new(PortApi::new(sai_adapter.get_port_api() as *const _));
+    let bridge_api = Arc::new(BridgeApi::new(sai_adapter.get_bridge_api() as *const _));
+    let lag_api_table = sai_adapter.get_lag_api() as *const _;
+    let fdb_api = Arc::new(FdbApi::new(sai_adapter.get_fdb_api() as *const _));
 
-    // Create VLAN synchronization agent
-    let vlan_sync = Arc::new(VlanSync::new(db_client.clone(), vlan_api, switch_id));
+    // Create the synchronization agents of each switch
+    let limits = startup_config.resource_limits().unwrap_or_else(|e| {
+        warn!(
+            "Failed to load platform capabilities, not enforcing limits: {}",
+            e
+        );
+        Default::default()
+    });
+    info!("Resource limits: {:?}", limits);
+    let cleanup_on_shutdown = startup_config.features.cleanup_on_shutdown;
+    if warm_boot && cleanup_on_shutdown {
+        warn!("cleanup_on_shutdown removes all programmed objects, defeating warm boot");
+    }
+    let audit_log = AuditLog::from_config(&startup_config.audit)
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to open audit log, not auditing SAI mutations: {}",
+                e
+            );
+            None
+        })
+        .map(Arc::new);
+    let mut switch_agents = Vec::new();
+    for &switch in &switches {
+        // Single-ASIC platforms keep the plain key and channel names
+        let switch_keys = if asic_count > 1 {
+            switch.table_keys(&table_keys)
+        } else {
+            table_keys.clone()
+        };
+
+        // Agents of the same switch look up each other's objects here
+        let registry = Arc::new(ObjectRegistry::new());
+        if let Err(e) = register_ports(
+            &db_client,
+            &switch_keys,
+            &switch_api,
+            &port_api,
+            switch.switch_id,
+            &registry,
+        )
+        .await
+        {
+            warn!("Failed to name the ports of ASIC {}: {}", switch.index, e);
+        }
+        if let Err(e) = register_bridge_ports(&switch_api, &bridge_api, switch.switch_id, &registry)
+        {
+            warn!(
+                "Failed to register the bridge ports of ASIC {}: {}",
+                switch.index, e
+            );
+        }
+        // The VLAN agent checks these before setting attributes
+        for object_type in [
+            SaiObjectType::Vlan,
+            SaiObjectType::VlanMember,
+            SaiObjectType::RouterInterface,
+        ] {
+            let warmed = switch_api.warm_capabilities(switch.switch_id, object_type);
+            info!(
+                "Cached capabilities of {} {} attributes for ASIC {}",
+                warmed, object_type, switch.index
+            );
+        }
+        let mut vlan_sync = VlanSync::new(db_client.clone(), vlan_api.clone(), switch)
+            .with_switch_api(switch_api.clone())
+            .with_object_registry(registry.clone())
+            .with_port_api(port_api.clone())
+            .with_bridge_ports(bridge_api.clone(), Arc::new(BridgePortTable::new()))
+            .with_table_keys(switch_keys.clone())
+            .with_resource_limits(limits)
+            .with_cleanup_on_shutdown(cleanup_on_shutdown)
+            .with_warm_boot(boot_type == SaiBootType::Warm);
+        if let Some(rif_api) = &rif_api {
+            vlan_sync = vlan_sync.with_router_interface_api(rif_api.clone());
+        }
+        if let Some(audit_log) = &audit_log {
+            vlan_sync = vlan_sync.with_audit_log(audit_log.clone());
+        }
 
-    // Start VLAN synchronization (load existing VLANs from APPL_DB)
-    vlan_sync.start().await?;
-    info!("VLAN synchronization agent started");
+        // All agents are created up front, so a reload can enable any
+        let lag_api = LagApi::new(lag_api_table).with_switch_id(switch.switch_id);
+        let mut lag_sync = LagSync::new(
+            db_client.clone(),
+            Arc::new(lag_api),
+            port_api.clone(),
+            switch,
+        )
+        .with_object_registry(registry.clone())
+        .with_table_keys(switch_keys.clone());
+        if let Some(audit_log) = &audit_log {
+            lag_sync = lag_sync.with_audit_log(audit_log.clone());
+        }
+        if let Some(window) = startup_config.features.port_batch_window() {
+            lag_sync = lag_sync.with_attribute_batcher(Arc::new(AttributeBatcher::new(window)));
+        }
 
-    // Create subscriber for APPL_DB changes
-    let subscriber_client = DbSubscriberClient::new(&db_url)?;
-    let vlan_subscriber = Arc::new(VlanSyncSubscriber::new(vlan_sync.clone()));
+        let mut fdb_sync = FdbSync::new(db_client.clone(), fdb_api.clone(), switch)
+            .with_object_registry(registry)
+            .with_table_keys(switch_keys)
+            .with_resource_limits(limits);
+        if let Some(audit_log) = &audit_log {
+            fdb_sync = fdb_sync.with_audit_log(audit_log.clone());
+        }
 
-    info!("Subscribing to APPL_DB VLAN_TABLE channel");
+        switch_agents.push(SwitchAgents {
+            switch,
+            multi_asic: asic_count > 1,
+            vlan_sync: Arc::new(vlan_sync),
+            lag_sync: Arc::new(lag_sync),
+            fdb_sync: Arc::new(fdb_sync),
+            started: HashSet::new(),
+        });
+    }
 
-    // Subscribe to VLAN table changes
-    // This will block and process messages
-    if let Err(e) = subscriber_client
-        .subscribe(vec!["VLAN_TABLE".to_string()], vlan_subscriber)
+    // Process table changes of the enabled agents until ctrl-c or until
+    // any switch's subscription ends; a SIGHUP changing HOT_SETTINGS
+    // renews the subscriptions and pollers
+    let mut subscriptions = JoinSet::new();
+    // Counter pollers, stopped on shutdown
+    let mut pollers = JoinSet::new();
+    let result = 'run: loop {
+        if let Err(e) = run_agents(
+            &mut switch_agents,
+            &config.load_full(),
+            &db_client,
+            &mut subscriptions,
+            &mut pollers,
+        )
         .await
-    {
+        {
+            break 'run Err(e);
+        }
+
+        {
+            let subscription = async {
+                match subscriptions.join_next().await {
+                    Some(Ok(result)) => result,
+                    Some(Err(e)) => Err(RacoonError::Internal(format!(
+                        "subscription task failed: {}",
+                        e
+                    ))),
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::pin!(subscription);
+            loop {
+                tokio::select! {
+                    result = &mut subscription => break 'run result,
+                    _ = tokio::signal::ctrl_c() => {
+                        info!("Received shutdown signal");
+                        break 'run Ok(());
+                    }
+                    _ = hangup.recv() => {
+                        info!("Received SIGHUP, reloading {}", config_path);
+                        if reload(&config_path, &config, &log_level) {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        info!("Renewing subscriptions and counter pollers");
+        subscriptions.shutdown().await;
+        pollers.shutdown().await;
+    };
+
+    // Release programmed objects while SAI is still initialized; the
+    // adapter uninitializes SAI when it is dropped at the end of main
+    subscriptions.abort_all();
+    pollers.abort_all();
+    for agents in &switch_agents {
+        agents.lag_sync.flush_port_attributes();
+        agents.vlan_sync.shutdown().await;
+    }
+
+    // Keep the hardware forwarding for the next warm boot; pointless once
+    // cleanup removed everything
+    if warm_boot && !cleanup_on_shutdown {
+        let mut prepared = true;
+        for switch in &switches {
+            match switch_api.set_warm_shutdown(switch.switch_id) {
+                Ok(()) => info!("Switch 0x{:x} prepared for warm boot", switch.switch_id),
+                Err(e) => {
+                    warn!(
+                        "Failed to prepare switch 0x{:x} for warm boot: {}",
+                        switch.switch_id, e
+                    );
+                    prepared = false;
+                }
+            }
+        }
+        // The next start boots warm only if every switch was prepared
+        if prepared && let Err(e) = mark_warm_shutdown(&db_client, &table_keys).await {
+            warn!("Failed to record warm shutdown, next boot is cold: {}", e);
+        }
+    }
+
+    db_client.shutdown().await;
+    shutdown_tracing();
+
+    if let Err(e) = result {
         error!("Subscription error: {}", e);
         return Err(e.into());
     }
 
+    info!("syncd stopped");
+    Ok(())
+}
+
+/// Sync agents of one switch
+struct SwitchAgents {
+    switch: SwitchContext,
+    /// Channels are named for the switch, as its keys are
+    multi_asic: bool,
+    vlan_sync: Arc<VlanSync>,
+    lag_sync: Arc<LagSync>,
+    fdb_sync: Arc<FdbSync>,
+    /// Agents whose startup scan has run
+    started: HashSet<SyncAgent>,
+}
+
+impl SwitchAgents {
+    /// APPL_DB channels of `agent` on this switch
+    fn channels(&self, agent: SyncAgent) -> Vec<String> {
+        agent_channels(agent)
+            .iter()
+            .map(|channel| {
+                if self.multi_asic {
+                    self.switch.channel(channel)
+                } else {
+                    channel.to_string()
+                }
+            })
+            .collect()
+    }
+}
+
+/// Subscribe the agents `config` enables and poll their counters
+///
+/// An agent subscribes before its first scan of APPL_DB, so entries
+/// published during the scan are held rather than missed. Renewed
+/// subscriptions resync the agent instead, as notifications published
+/// between the old and new subscription are lost. Disabled agents keep
+/// what they programmed but stop following APPL_DB.
+async fn run_agents(
+    switch_agents: &mut [SwitchAgents],
+    config: &Config,
+    db_client: &DbClient,
+    subscriptions: &mut JoinSet<racoon_common::Result<()>>,
+    pollers: &mut JoinSet<()>,
+) -> racoon_common::Result<()> {
+    let agents = config.services.sync_agents();
+    let watchdog = config.database.pubsub_watchdog();
+    let poll_interval = config.counters.poll_interval();
+    for switch_agent in switch_agents {
+        let switch = switch_agent.switch;
+
+        if agents.contains(&SyncAgent::Vlan) {
+            let vlan_sync = switch_agent.vlan_sync.clone();
+            let first = switch_agent.started.insert(SyncAgent::Vlan);
+            if first {
+                vlan_sync.begin_startup();
+            }
+            let vlan_subscriber = Arc::new(VlanSyncSubscriber::new(vlan_sync.clone()));
+            let channels = switch_agent.channels(SyncAgent::Vlan);
+            let channel_count = channels.len();
+            spawn_subscription(
+                subscriptions,
+                db_client,
+                watchdog,
+                channels,
+                vlan_subscriber.clone(),
+            );
+            wait_subscribed(vlan_subscriber.wait_subscribed(channel_count), &switch).await;
+
+            if first {
+                // Start VLAN synchronization (load existing VLANs from APPL_DB)
+                vlan_sync.start().await?;
+                info!(
+                    "VLAN synchronization agent started for ASIC {}",
+                    switch.index
+                );
+            } else if let Err(e) = vlan_sync.sync_vlans().await {
+                warn!("VLAN resync of ASIC {} failed: {}", switch.index, e);
+            }
+
+            // Poll VLAN counters into COUNTERS_DB
+            if let Some(interval) = poll_interval {
+                pollers.spawn(async move {
+                    clock::every(&TokioClock, interval, || async {
+                        if let Err(e) = vlan_sync.poll_counters().await {
+                            warn!("Failed to poll VLAN counters: {}", e);
+                        }
+                    })
+                    .await
+                });
+            }
+        }
+
+        if agents.contains(&SyncAgent::Lag) {
+            let lag_sync = switch_agent.lag_sync.clone();
+            let first = switch_agent.started.insert(SyncAgent::Lag);
+            if first {
+                lag_sync.begin_startup();
+            }
+            let lag_subscriber = Arc::new(LagSyncSubscriber::new(lag_sync.clone()));
+            let channels = switch_agent.channels(SyncAgent::Lag);
+            let channel_count = channels.len();
+            spawn_subscription(
+                subscriptions,
+                db_client,
+                watchdog,
+                channels,
+                lag_subscriber.clone(),
+            );
+            wait_subscribed(lag_subscriber.wait_subscribed(channel_count), &switch).await;
+
+            if first {
+                // Start LAG synchronization (load existing LAGs from APPL_DB)
+                lag_sync.start().await?;
+                info!(
+                    "LAG synchronization agent started for ASIC {}",
+                    switch.index
+                );
+            } else if let Err(e) = lag_sync.sync_lags().await {
+                warn!("LAG resync of ASIC {} failed: {}", switch.index, e);
+            }
+
+            // Apply batched member port changes; returns at once unbatched
+            pollers.spawn({
+                let lag_sync = lag_sync.clone();
+                async move { lag_sync.run_port_batcher().await }
+            });
+
+            // Poll LAG counters, summed over their members, into COUNTERS_DB
+            if let Some(interval) = poll_interval {
+                pollers.spawn(async move {
+                    clock::every(&TokioClock, interval, || async {
+                        if let Err(e) = lag_sync.poll_counters().await {
+                            warn!("Failed to poll LAG counters: {}", e);
+                        }
+                    })
+                    .await
+                });
+            }
+        }
+
+        if agents.contains(&SyncAgent::Fdb) {
+            let fdb_sync = switch_agent.fdb_sync.clone();
+            let first = switch_agent.started.insert(SyncAgent::Fdb);
+            if first {
+                fdb_sync.begin_startup();
+            }
+            let fdb_subscriber = Arc::new(FdbSyncSubscriber::new(fdb_sync.clone()));
+            let channels = switch_agent.channels(SyncAgent::Fdb);
+            let channel_count = channels.len();
+            spawn_subscription(
+                subscriptions,
+                db_client,
+                watchdog,
+                channels,
+                fdb_subscriber.clone(),
+            );
+            wait_subscribed(fdb_subscriber.wait_subscribed(channel_count), &switch).await;
+
+            if first {
+                // Start FDB synchronization (load existing entries from APPL_DB)
+                fdb_sync.start().await?;
+                info!(
+                    "FDB synchronization agent started for ASIC {}",
+                    switch.index
+                );
+            } else if let Err(e) = fdb_sync.sync_fdb().await {
+                warn!("FDB resync of ASIC {} failed: {}", switch.index, e);
+            }
+        }
+    }
     Ok(())
 }
+
+/// Re-read the config, returning whether the subscriptions and pollers
+/// must be renewed
+fn reload(path: &str, config: &SharedConfig, log_level: &LogLevelHandle) -> bool {
+    match reload_config(path, config, log_level, &HOT_SETTINGS) {
+        Ok(reload) => HOT_SETTINGS.iter().any(|setting| reload.applied(setting)),
+        Err(e) => {
+            error!("Failed to reload config, keeping the running one: {}", e);
+            false
+        }
+    }
+}
+
+/// Subscribe `subscriber` to `channels` on a connection of its own
+fn spawn_subscription<S: DbSubscriber + 'static>(
+    subscriptions: &mut JoinSet<racoon_common::Result<()>>,
+    db_client: &DbClient,
+    watchdog: Option<Duration>,
+    channels: Vec<String>,
+    subscriber: Arc<S>,
+) {
+    let mut subscriber_client = db_client.subscriber();
+    if let Some(window) = watchdog {
+        subscriber_client = subscriber_client.with_watchdog(window);
+    }
+    info!("Subscribing to APPL_DB channels: {:?}", channels);
+    subscriptions.spawn(async move { subscriber_client.subscribe(channels, subscriber).await });
+}
+
+/// Wait up to [`SUBSCRIBE_TIMEOUT`] for a switch's subscriptions to be
+/// confirmed
+async fn wait_subscribed(subscribed: impl Future<Output = ()>, switch: &SwitchContext) {
+    if tokio::time::timeout(SUBSCRIBE_TIMEOUT, subscribed)
+        .await
+        .is_err()
+    {
+        warn!(
+            "Subscriptions for ASIC {} not confirmed within {:?}, scanning anyway",
+            switch.index, SUBSCRIBE_TIMEOUT
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! SAI objects shared between agents
+//!
+//! Each agent owns the objects it programs, but others need their OIDs: a
+//! VLAN member needs its VLAN and bridge port, an FDB entry its bridge port.
+//! The owning agent registers an object once it exists in SAI and
+//! unregisters it once removed; other agents only look objects up.
+//!
+//! A registry describes one switch, so each ASIC's agents share their own.
+//! It also counts the VLAN memberships of each front-panel port, so a
+//! breakout through it refuses to remove a port that still carries traffic.
+
+use dashmap::DashMap;
+use racoon_common::{PortName, Result, SaiOid, VlanId};
+use racoon_sai::port::{PortApi, PortMap};
+use std::sync::Mutex;
+
+/// OIDs of VLANs, ports and bridge ports programmed on a switch
+#[derive(Debug, Default)]
+pub struct ObjectRegistry {
+    vlans: DashMap<VlanId, SaiOid>,
+    /// Port OID by port name
+    ports: DashMap<String, SaiOid>,
+    /// Bridge port OID by port name
+    bridge_ports: DashMap<String, SaiOid>,
+    /// Front-panel ports and their VLAN memberships, guarding breakout
+    port_map: Mutex<PortMap>,
+}
+
+impl ObjectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_vlan(&self, vlan_id: VlanId, vlan_oid: SaiOid) {
+        self.vlans.insert(vlan_id, vlan_oid);
+    }
+
+    pub fn unregister_vlan(&self, vlan_id: VlanId) {
+        self.vlans.remove(&vlan_id);
+    }
+
+    /// OID of a VLAN, if it is programmed
+    pub fn vlan_oid(&self, vlan_id: VlanId) -> Option<SaiOid> {
+        self.vlans.get(&vlan_id).map(|oid| *oid)
+    }
+
+    pub fn register_port(&self, port: &str, port_oid: SaiOid) {
+        self.ports.insert(port.to_string(), port_oid);
+        if let Ok(name) = port.parse() {
+            self.port_map().insert(name, port_oid);
+        }
+    }
+
+    pub fn unregister_port(&self, port: &str) {
+        self.ports.remove(port);
+    }
+
+    /// OID of a port by name
+    pub fn port_oid(&self, port: &str) -> Option<SaiOid> {
+        self.ports.get(port).map(|oid| *oid)
+    }
+
+    /// Name of a port by OID, if it is registered
+    pub fn port_name(&self, port_oid: SaiOid) -> Option<String> {
+        self.ports
+            .iter()
+            .find(|entry| *entry.value() == port_oid)
+            .map(|entry| entry.key().clone())
+    }
+
+    pub fn register_bridge_port(&self, port: &str, bridge_port_oid: SaiOid) {
+        self.bridge_ports.insert(port.to_string(), bridge_port_oid);
+    }
+
+    pub fn unregister_bridge_port(&self, port: &str) {
+        self.bridge_ports.remove(port);
+    }
+
+    /// OID of the bridge port of a port, by port name
+    pub fn bridge_port_oid(&self, port: &str) -> Option<SaiOid> {
+        self.bridge_ports.get(port).map(|oid| *oid)
+    }
+
+    /// Record that a port joined a VLAN; other interfaces are not counted
+    pub fn add_vlan_member(&self, port: &str) {
+        if let Ok(name) = port.parse() {
+            self.port_map().add_vlan_member(name);
+        }
+    }
+
+    /// Record that a port left a VLAN
+    pub fn remove_vlan_member(&self, port: &str) {
+        if let Ok(name) = port.parse() {
+            self.port_map().remove_vlan_member(name);
+        }
+    }
+
+    /// Break out a port and register its sub-ports, returning their names
+    ///
+    /// Refused while the port is a member of any VLAN.
+    pub fn breakout_port(
+        &self,
+        api: &PortApi,
+        switch_id: SaiOid,
+        port: &str,
+        lanes_per_subport: usize,
+    ) -> Result<Vec<String>> {
+        let name = parse_port(port)?;
+        let mut port_map = self.port_map();
+        let subports = port_map.breakout(api, switch_id, name, lanes_per_subport)?;
+
+        self.ports.remove(port);
+        Ok(subports
+            .into_iter()
+            .filter_map(|subport| {
+                let port_oid = port_map.get(subport)?;
+                let subport = subport.to_string();
+                self.ports.insert(subport.clone(), port_oid);
+                Some(subport)
+            })
+            .collect())
+    }
+
+    /// Merge sub-ports back into one port registered under the first name
+    ///
+    /// Refused while any of the sub-ports is a member of a VLAN.
+    pub fn unbreakout_ports(
+        &self,
+        api: &PortApi,
+        switch_id: SaiOid,
+        ports: &[&str],
+        speed: u32,
+    ) -> Result<SaiOid> {
+        let names = ports
+            .iter()
+            .map(|port| parse_port(port))
+            .collect::<Result<Vec<PortName>>>()?;
+        let port_oid = self.port_map().unbreakout(api, switch_id, &names, speed)?;
+
+        for port in ports {
+            self.ports.remove(*port);
+        }
+        if let Some(first) = names.first() {
+            self.ports.insert(first.to_string(), port_oid);
+        }
+        Ok(port_oid)
+    }
+
+    fn port_map(&self) -> std::sync::MutexGuard<'_, PortMap> {
+        // The map is consistent after every call, so a panic elsewhere
+        // does not poison it for the other agents
+        self.port_map
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Front-panel name of `port`
+fn parse_port(port: &str) -> Result<PortName> {
+    port.parse()
+        .map_err(|_| racoon_common::RacoonError::PortNotFound(port.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_and_unregister() {
+        let registry = ObjectRegistry::new();
+        let vlan_id = VlanId::new(100).unwrap();
+
+        registry.register_vlan(vlan_id, 0x26000000000064);
+        registry.register_port("Ethernet0", 0x1000000000001);
+        registry.register_bridge_port("Ethernet0", 0x3a000000000001);
+        assert_eq!(registry.vlan_oid(vlan_id), Some(0x26000000000064));
+        assert_eq!(registry.port_oid("Ethernet0"), Some(0x1000000000001));
+        assert_eq!(
+            registry.port_name(0x1000000000001).as_deref(),
+            Some("Ethernet0")
+        );
+        assert_eq!(
+            registry.bridge_port_oid("Ethernet0"),
+            Some(0x3a000000000001)
+        );
+        assert_eq!(registry.bridge_port_oid("Ethernet4"), None);
+
+        registry.unregister_vlan(vlan_id);
+        registry.unregister_port("Ethernet0");
+        registry.unregister_bridge_port("Ethernet0");
+        assert_eq!(registry.vlan_oid(vlan_id), None);
+        assert_eq!(registry.port_oid("Ethernet0"), None);
+        assert_eq!(registry.bridge_port_oid("Ethernet0"), None);
+    }
+
+    #[test]
+    fn test_breakout_refused_while_port_is_a_vlan_member() {
+        let api = racoon_sai::mock::port_api();
+        let parent = racoon_sai::mock::add_port(&[1, 2, 3, 4], 100_000);
+        let registry = ObjectRegistry::new();
+        registry.register_port("Ethernet0", parent);
+
+        registry.add_vlan_member("Ethernet0");
+        let err = registry.breakout_port(&api, 0, "Ethernet0", 2).unwrap_err();
+        assert!(matches!(
+            err,
+            racoon_common::RacoonError::DependencyNotSatisfied(_)
+        ));
+        assert_eq!(registry.port_oid("Ethernet0"), Some(parent));
+
+        registry.remove_vlan_member("Ethernet0");
+        let subports = registry.breakout_port(&api, 0, "Ethernet0", 2).unwrap();
+        assert_eq!(subports, ["Ethernet0", "Ethernet2"]);
+        assert_ne!(registry.port_oid("Ethernet0"), Some(parent));
+        assert!(registry.port_oid("Ethernet2").is_some());
+    }
+}
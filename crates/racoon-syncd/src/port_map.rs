@@ -0,0 +1,218 @@
+//! Port names of a switch's SAI ports
+//!
+//! SAI creates the front-panel ports itself when the switch is initialized,
+//! so only their OIDs are known. Each is matched to the APPL_DB
+//! `PORT_TABLE` entry with the same hardware lanes and registered under that
+//! entry's name, letting agents resolve port names to OIDs.
+//!
+//! SAI also puts each port in the switch's default .1Q bridge. Those bridge
+//! ports are registered under the port's name too, so VLAN members are
+//! created on them rather than on a second bridge port of the same port.
+
+use crate::object_registry::ObjectRegistry;
+use racoon_common::keys::TableKeys;
+use racoon_common::{Result, SaiOid};
+use racoon_db_client::{DbClient, home_db, tables};
+use racoon_sai::port::PortApi;
+use racoon_sai::{BridgeApi, SwitchApi};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+/// Port entry from APPL_DB
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortEntry {
+    pub lanes: String, // "0,1,2,3"
+}
+
+impl PortEntry {
+    /// Hardware lanes, sorted; `None` if any is not a number
+    pub fn lane_list(&self) -> Option<Vec<u32>> {
+        let mut lanes = self
+            .lanes
+            .split(',')
+            .map(|lane| lane.trim().parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        lanes.sort();
+        Some(lanes)
+    }
+}
+
+/// Register the name of every SAI port of `switch_id` found in APPL_DB
+///
+/// Ports without a `PORT_TABLE` entry on the same lanes stay unnamed.
+/// Returns the number of ports registered.
+pub async fn register_ports(
+    db_client: &DbClient,
+    table_keys: &TableKeys,
+    switch_api: &SwitchApi,
+    port_api: &PortApi,
+    switch_id: SaiOid,
+    registry: &ObjectRegistry,
+) -> Result<usize> {
+    let keys = db_client
+        .scan(
+            home_db(tables::PORT_TABLE),
+            &table_keys.pattern(tables::PORT_TABLE),
+        )
+        .await?;
+    let entries: Vec<Option<PortEntry>> = db_client
+        .get_many(home_db(tables::PORT_TABLE), &keys)
+        .await?;
+
+    let mut names: HashMap<Vec<u32>, &str> = HashMap::new();
+    for (key, entry) in keys.iter().zip(&entries) {
+        let (Some(port), Some(entry)) = (table_keys.strip(tables::PORT_TABLE, key), entry) else {
+            continue;
+        };
+        match entry.lane_list() {
+            Some(lanes) => {
+                names.insert(lanes, port);
+            }
+            None => warn!("Port {} has invalid lanes {:?}", port, entry.lanes),
+        }
+    }
+
+    let mut registered = 0;
+    for port_oid in switch_api.get_port_list(switch_id)? {
+        let mut lanes = match port_api.get_hw_lanes(port_oid) {
+            Ok(lanes) => lanes,
+            Err(e) => {
+                warn!("Failed to read lanes of port 0x{:x}: {}", port_oid, e);
+                continue;
+            }
+        };
+        lanes.sort();
+        match names.get(&lanes) {
+            Some(port) => {
+                registry.register_port(port, port_oid);
+                registered += 1;
+            }
+            None => debug!("No port on lanes {:?} (0x{:x})", lanes, port_oid),
+        }
+    }
+
+    info!(
+        "Registered {} of {} ports for switch 0x{:x}",
+        registered,
+        names.len(),
+        switch_id
+    );
+    Ok(registered)
+}
+
+/// Register the bridge ports SAI created in the default .1Q bridge of
+/// `switch_id`, by the name of the port they bridge
+///
+/// Bridge ports of unnamed ports (or of LAGs) are skipped, so ports must be
+/// registered first. Returns the number of bridge ports registered.
+pub fn register_bridge_ports(
+    switch_api: &SwitchApi,
+    bridge_api: &BridgeApi,
+    switch_id: SaiOid,
+    registry: &ObjectRegistry,
+) -> Result<usize> {
+    let bridge = switch_api.get_default_1q_bridge(switch_id)?;
+
+    let mut registered = 0;
+    for bridge_port_oid in bridge_api.get_bridge_ports(bridge)? {
+        let port_oid = match bridge_api.get_bridge_port_port(bridge_port_oid) {
+            Ok(port_oid) => port_oid,
+            Err(e) => {
+                warn!(
+                    "Failed to read the port of bridge port 0x{:x}: {}",
+                    bridge_port_oid, e
+                );
+                continue;
+            }
+        };
+        match registry.port_name(port_oid) {
+            Some(port) => {
+                registry.register_bridge_port(&port, bridge_port_oid);
+                registered += 1;
+            }
+            None => debug!(
+                "Bridge port 0x{:x} is not on a named port (0x{:x})",
+                bridge_port_oid, port_oid
+            ),
+        }
+    }
+
+    info!(
+        "Registered {} default bridge ports for switch 0x{:x}",
+        registered, switch_id
+    );
+    Ok(registered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_db_client::Database;
+    use racoon_sai::mock;
+
+    #[test]
+    fn test_lane_list() {
+        let entry = PortEntry {
+            lanes: "3, 1,2,0".to_string(),
+        };
+        assert_eq!(entry.lane_list(), Some(vec![0, 1, 2, 3]));
+
+        let entry = PortEntry {
+            lanes: "0,x".to_string(),
+        };
+        assert_eq!(entry.lane_list(), None);
+    }
+
+    #[tokio::test]
+    async fn test_register_ports_by_lanes() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = server.client().await;
+        let registry = ObjectRegistry::new();
+
+        let ethernet0 = mock::add_port(&[0, 1, 2, 3], 100000);
+        let ethernet4 = mock::add_port(&[4, 5, 6, 7], 100000);
+        // No APPL_DB entry: stays unnamed
+        mock::add_port(&[8, 9, 10, 11], 100000);
+        for (key, lanes) in [
+            ("PORT_TABLE:Ethernet0", "0,1,2,3"),
+            ("PORT_TABLE:Ethernet4", "7,6,5,4"),
+        ] {
+            let entry = PortEntry {
+                lanes: lanes.to_string(),
+            };
+            db_client.set(Database::Appl, key, &entry).await.unwrap();
+        }
+
+        let registered = register_ports(
+            &db_client,
+            &TableKeys::default(),
+            &mock::switch_api(),
+            &mock::port_api(),
+            0,
+            &registry,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(registered, 2);
+        assert_eq!(registry.port_oid("Ethernet0"), Some(ethernet0));
+        assert_eq!(registry.port_oid("Ethernet4"), Some(ethernet4));
+    }
+
+    #[test]
+    fn test_register_default_bridge_ports_by_port_name() {
+        let registry = ObjectRegistry::new();
+        let ethernet0 = mock::add_port(&[0, 1, 2, 3], 100000);
+        let unnamed = mock::add_port(&[4, 5, 6, 7], 100000);
+        let bridge_port = mock::add_bridge_port(ethernet0);
+        mock::add_bridge_port(unnamed);
+        registry.register_port("Ethernet0", ethernet0);
+
+        let registered =
+            register_bridge_ports(&mock::switch_api(), &mock::bridge_api(), 0, &registry).unwrap();
+
+        assert_eq!(registered, 1);
+        assert_eq!(registry.bridge_port_oid("Ethernet0"), Some(bridge_port));
+    }
+}
@@ -0,0 +1,109 @@
+//! Port link bring-up state
+//!
+//! Publishes what SAI reports about a port's link training and serdes
+//! lanes to STATE_DB (`PORT_SERDES_STATE:<port>`), so operators can see
+//! why a port won't link up.
+
+use racoon_common::keys::TableKeys;
+use racoon_common::{Result, SaiOid};
+use racoon_db_client::{DbClient, home_db, tables};
+use racoon_sai::port::{LaneStatus, LinkTrainingStatus, PortApi, SerdesStatus};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Port serdes state entry (STATE_DB)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortSerdesState {
+    pub link_training: String, // "trained", "not_trained", "failed" or "unknown"
+    /// Why link training failed, e.g. "frame_lock_error"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_training_failure: Option<String>,
+    pub signal_detect: String, // "up", "down" or "unknown"
+    pub rx_lock: String,       // "up", "down" or "unknown"
+    /// Lanes without signal or CDR lock, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub down_lanes: Vec<u32>,
+}
+
+impl PortSerdesState {
+    pub fn new(link_training: LinkTrainingStatus, serdes: &SerdesStatus) -> Self {
+        let mut down_lanes: Vec<u32> = [&serdes.signal_detect, &serdes.rx_lock]
+            .into_iter()
+            .flat_map(|status| match status {
+                LaneStatus::Down(lanes) => lanes.clone(),
+                _ => Vec::new(),
+            })
+            .collect();
+        down_lanes.sort();
+        down_lanes.dedup();
+
+        Self {
+            link_training: link_training.as_str().to_string(),
+            link_training_failure: link_training.failure().map(str::to_string),
+            signal_detect: serdes.signal_detect.as_str().to_string(),
+            rx_lock: serdes.rx_lock.as_str().to_string(),
+            down_lanes,
+        }
+    }
+
+    /// STATE_DB key of a port in the namespace of `keys`
+    pub fn key(keys: &TableKeys, port: &str) -> String {
+        keys.scoped(&format!("{}:{}", tables::PORT_SERDES_STATE, port))
+    }
+}
+
+/// Read a port's link training and serdes state and write it to STATE_DB
+pub async fn publish_port_serdes_state(
+    db_client: &DbClient,
+    keys: &TableKeys,
+    port_api: &PortApi,
+    port: &str,
+    port_oid: SaiOid,
+) -> Result<PortSerdesState> {
+    let state = PortSerdesState::new(
+        port_api.get_link_training_status(port_oid)?,
+        &port_api.get_serdes_status(port_oid)?,
+    );
+    db_client
+        .set(
+            home_db(tables::PORT_SERDES_STATE),
+            &PortSerdesState::key(keys, port),
+            &state,
+        )
+        .await?;
+
+    debug!("Wrote serdes state for {}: {:?}", port, state);
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use racoon_sai::PortLinkTrainingFailureStatus;
+
+    #[test]
+    fn test_state_of_failed_port() {
+        let serdes = SerdesStatus {
+            signal_detect: LaneStatus::Down(vec![3]),
+            rx_lock: LaneStatus::Down(vec![3, 1]),
+        };
+        let state = PortSerdesState::new(
+            LinkTrainingStatus::Failed(PortLinkTrainingFailureStatus::FrameLockError),
+            &serdes,
+        );
+        assert_eq!(state.link_training, "failed");
+        assert_eq!(
+            state.link_training_failure.as_deref(),
+            Some("frame_lock_error")
+        );
+        assert_eq!(state.down_lanes, [1, 3]);
+        assert_eq!(
+            PortSerdesState::key(&TableKeys::default(), "Ethernet0"),
+            "PORT_SERDES_STATE:Ethernet0"
+        );
+        assert_eq!(
+            PortSerdesState::key(&TableKeys::default().with_namespace("asic1"), "Ethernet0"),
+            "asic1:PORT_SERDES_STATE:Ethernet0"
+        );
+    }
+}
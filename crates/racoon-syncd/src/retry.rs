@@ -0,0 +1,160 @@
+//! Retrying of SAI calls
+//!
+//! Transient SAI failures (see [`racoon_sai::classify`]) are retried with
+//! exponential backoff; permanent ones are returned immediately.
+
+use crate::clock::{Clock, TokioClock};
+use racoon_common::Result;
+use racoon_sai::{Retryability, classify_error};
+use std::time::Duration;
+use tracing::warn;
+
+/// Backoff settings for transient SAI failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after each attempt
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `call`, retrying while it fails with a transient SAI error
+    pub async fn run<T>(&self, what: &str, call: impl FnMut() -> Result<T>) -> Result<T> {
+        self.run_with_clock(&TokioClock, what, call).await
+    }
+
+    /// [`run`](Self::run), waiting out the backoff on `clock`
+    pub async fn run_with_clock<T>(
+        &self,
+        clock: &dyn Clock,
+        what: &str,
+        mut call: impl FnMut() -> Result<T>,
+    ) -> Result<T> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+
+        loop {
+            match call() {
+                Ok(value) => return Ok(value),
+                Err(e)
+                    if attempt < self.max_attempts
+                        && classify_error(&e) == Retryability::Transient =>
+                {
+                    warn!(
+                        "{} failed (attempt {}/{}): {}, retrying in {:?}",
+                        what, attempt, self.max_attempts, e, backoff
+                    );
+                    clock.sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use racoon_sai::{SAI_STATUS_INVALID_PARAMETER, SAI_STATUS_TABLE_FULL, SaiStatus};
+    use std::cell::Cell;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_retried() {
+        let mut calls = 0;
+        let result = policy()
+            .run("create VLAN", || {
+                calls += 1;
+                if calls < 3 {
+                    SaiStatus(SAI_STATUS_TABLE_FULL).to_result()?;
+                }
+                Ok(calls)
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        // Attempts are bounded
+        let mut calls = 0;
+        let result: Result<()> = policy()
+            .run("create VLAN", || {
+                calls += 1;
+                SaiStatus(SAI_STATUS_TABLE_FULL).to_result()
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_backoff_waits_on_clock() {
+        let clock = MockClock::new();
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+        let calls = Cell::new(0);
+        let run = policy.run_with_clock(&clock, "create VLAN", || {
+            calls.set(calls.get() + 1);
+            SaiStatus(SAI_STATUS_TABLE_FULL).to_result()
+        });
+        let mut run = std::pin::pin!(run);
+
+        // Each backoff only ends once the clock has moved past it
+        assert!(poll_once(&mut run).await.is_none());
+        assert_eq!(calls.get(), 1);
+        clock.advance(Duration::from_millis(999));
+        assert!(poll_once(&mut run).await.is_none());
+        assert_eq!(calls.get(), 1);
+        clock.advance(Duration::from_millis(1));
+        assert!(poll_once(&mut run).await.is_none());
+        clock.advance(Duration::from_secs(2));
+        assert!(poll_once(&mut run).await.unwrap().is_err());
+        assert_eq!(calls.get(), 3);
+    }
+
+    /// Poll `future` once, returning its output if it completed
+    async fn poll_once<F: Future + Unpin>(future: &mut F) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            output = future => Some(output),
+            _ = std::future::ready(()) => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_not_retried() {
+        let mut calls = 0;
+        let result: Result<()> = policy()
+            .run("create VLAN", || {
+                calls += 1;
+                SaiStatus(SAI_STATUS_INVALID_PARAMETER).to_result()
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,215 @@
+//! Notifications received while the startup scan runs
+//!
+//! syncd subscribes before it scans APPL_DB, so an entry published while
+//! the scan runs is not lost. Notifications received in that window are
+//! held in a [`StartupWindow`] instead of being handled. The scan marks
+//! each entry it reads; once it finishes, a held notification is dropped
+//! if it was received before the scan read its entry, since producers
+//! write the entry before publishing. The others are handled in the order
+//! they were received.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A held notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeldNotification {
+    pub channel: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    /// Held notifications with their receive sequence number
+    held: Vec<(u64, HeldNotification)>,
+    /// Sequence number of the next received notification
+    next_seq: u64,
+    /// Sequence number at which each `(table, key)` was read by the scan
+    read: HashMap<(String, String), u64>,
+    /// Sequence number at which each table's key list was scanned
+    scanned: HashMap<String, u64>,
+}
+
+impl Window {
+    /// Whether the scan read a state at least as new as `seq`'s notification
+    fn covers(&self, seq: u64, table: &str, key: Option<&str>) -> bool {
+        let read_at = key
+            .and_then(|key| self.read.get(&(table.to_string(), key.to_string())))
+            .or_else(|| self.scanned.get(table));
+        read_at.is_some_and(|read_at| seq < *read_at)
+    }
+}
+
+/// Holds notifications received during the startup scan
+///
+/// Closed by default: notifications pass through until
+/// [`open`](Self::open) is called.
+#[derive(Debug, Default)]
+pub struct StartupWindow {
+    window: Mutex<Option<Window>>,
+}
+
+impl StartupWindow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Window>> {
+        // The window holds no invariant a panicking holder could break
+        self.window.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start holding notifications
+    pub fn open(&self) {
+        self.lock().get_or_insert_with(Window::default);
+    }
+
+    /// Whether notifications are being held
+    pub fn is_open(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Hold a notification while the window is open
+    ///
+    /// Returns `false` when the window is closed, in which case the caller
+    /// handles the notification itself.
+    pub fn hold(&self, channel: &str, message: &str) -> bool {
+        let mut guard = self.lock();
+        let Some(window) = guard.as_mut() else {
+            return false;
+        };
+        let seq = window.next_seq;
+        window.next_seq += 1;
+        window.held.push((
+            seq,
+            HeldNotification {
+                channel: channel.to_string(),
+                message: message.to_string(),
+            },
+        ));
+        true
+    }
+
+    /// The scan is about to list `table`'s keys
+    ///
+    /// Notifications held so far for keys absent from the listing are
+    /// covered by it.
+    pub fn mark_scanned(&self, table: &str) {
+        if let Some(window) = self.lock().as_mut() {
+            window.scanned.insert(table.to_string(), window.next_seq);
+        }
+    }
+
+    /// The scan is about to read `key` of `table`
+    ///
+    /// Notifications held so far for the key are covered by the read.
+    pub fn mark_read(&self, table: &str, key: &str) {
+        if let Some(window) = self.lock().as_mut() {
+            window
+                .read
+                .insert((table.to_string(), key.to_string()), window.next_seq);
+        }
+    }
+
+    /// Take held notifications the scan did not cover, or close the window
+    ///
+    /// `entry_of` maps a notification to the `(table, key)` it changes, or
+    /// `None` for notifications the scan never covers. Returns `None` and
+    /// closes the window once nothing is held, so notifications received
+    /// while the returned batch is handled are held for the next call and
+    /// stay in order.
+    pub fn take_uncovered<F>(&self, entry_of: F) -> Option<Vec<HeldNotification>>
+    where
+        F: Fn(&HeldNotification) -> Option<(String, Option<String>)>,
+    {
+        let mut guard = self.lock();
+        let window = guard.as_mut()?;
+        if window.held.is_empty() {
+            *guard = None;
+            return None;
+        }
+
+        let held = std::mem::take(&mut window.held);
+        Some(
+            held.into_iter()
+                .filter(|(seq, notification)| match entry_of(notification) {
+                    Some((table, key)) => !window.covers(*seq, &table, key.as_deref()),
+                    None => true,
+                })
+                .map(|(_, notification)| notification)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Channel is the table; the message is the key
+    fn entry_of(notification: &HeldNotification) -> Option<(String, Option<String>)> {
+        Some((
+            notification.channel.clone(),
+            Some(notification.message.clone()),
+        ))
+    }
+
+    fn messages(batch: Option<Vec<HeldNotification>>) -> Vec<String> {
+        batch
+            .unwrap()
+            .into_iter()
+            .map(|notification| notification.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_closed_window_passes_through() {
+        let window = StartupWindow::new();
+        assert!(!window.hold("VLAN_TABLE", "Vlan100"));
+        assert!(window.take_uncovered(entry_of).is_none());
+    }
+
+    #[test]
+    fn test_event_during_scan_is_neither_lost_nor_duplicated() {
+        let window = StartupWindow::new();
+        window.open();
+
+        // Published before the scan read Vlan100: the scan saw it
+        assert!(window.hold("VLAN_TABLE", "Vlan100"));
+        window.mark_scanned("VLAN_TABLE");
+        window.mark_read("VLAN_TABLE", "Vlan100");
+        // Published after the scan read Vlan100 and listed the table
+        assert!(window.hold("VLAN_TABLE", "Vlan100"));
+        assert!(window.hold("VLAN_TABLE", "Vlan200"));
+
+        assert_eq!(
+            messages(window.take_uncovered(entry_of)),
+            vec!["Vlan100", "Vlan200"]
+        );
+        assert!(window.is_open());
+
+        // Received while the batch was handled; held until the next call
+        assert!(window.hold("VLAN_MEMBER_TABLE", "Vlan200:Ethernet0"));
+        assert_eq!(
+            messages(window.take_uncovered(entry_of)),
+            vec!["Vlan200:Ethernet0"]
+        );
+
+        assert!(window.take_uncovered(entry_of).is_none());
+        assert!(!window.is_open());
+        assert!(!window.hold("VLAN_TABLE", "Vlan300"));
+    }
+
+    #[test]
+    fn test_absent_key_covered_by_listing() {
+        let window = StartupWindow::new();
+        window.open();
+
+        // A deletion published before the listing: the key is already gone
+        window.hold("VLAN_TABLE", "Vlan100");
+        window.mark_scanned("VLAN_TABLE");
+
+        assert!(messages(window.take_uncovered(entry_of)).is_empty());
+        assert!(window.take_uncovered(entry_of).is_none());
+    }
+}
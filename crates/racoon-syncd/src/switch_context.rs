@@ -0,0 +1,88 @@
+//! Per-switch context of sync agents
+//!
+//! Multi-ASIC platforms have one SAI switch per ASIC, each with its own
+//! agents. Agents of ASIC `N` listen on channels in the `asicN` namespace
+//! (`asic1:VLAN_TABLE`) and keep their database keys in it
+//! (`asic1:VLAN_STATE:Vlan100`); single-ASIC platforms keep the plain
+//! channel and key names.
+
+use racoon_common::constants::DEFAULT_VLAN_ID;
+use racoon_common::keys::TableKeys;
+use racoon_common::{SaiOid, VlanId};
+
+/// Separator between a namespace and a channel name
+const NAMESPACE_SEPARATOR: char = ':';
+
+/// The switch an agent programs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwitchContext {
+    /// SAI switch object
+    pub switch_id: SaiOid,
+    /// ASIC index on the platform, from 0
+    pub index: u32,
+    /// VLAN untagged ports fall into on this switch
+    pub default_vlan: VlanId,
+}
+
+impl SwitchContext {
+    pub fn new(switch_id: SaiOid, index: u32) -> Self {
+        Self {
+            switch_id,
+            index,
+            default_vlan: VlanId::new(DEFAULT_VLAN_ID).expect("default VLAN id is valid"),
+        }
+    }
+
+    /// Override the default VLAN
+    pub fn with_default_vlan(mut self, default_vlan: VlanId) -> Self {
+        self.default_vlan = default_vlan;
+        self
+    }
+
+    /// Channel namespace of the switch, e.g. `asic0`
+    pub fn namespace(&self) -> String {
+        format!("asic{}", self.index)
+    }
+
+    /// `channel` scoped to the switch's namespace
+    pub fn channel(&self, channel: &str) -> String {
+        format!("{}{}{}", self.namespace(), NAMESPACE_SEPARATOR, channel)
+    }
+
+    /// `keys` scoped to the switch's namespace
+    pub fn table_keys(&self, keys: &TableKeys) -> TableKeys {
+        keys.clone().with_namespace(self.namespace())
+    }
+
+    /// Channel name with the switch's namespace removed, if present
+    pub fn local_channel<'a>(&self, channel: &'a str) -> &'a str {
+        channel
+            .strip_prefix(self.namespace().as_str())
+            .and_then(|rest| rest.strip_prefix(NAMESPACE_SEPARATOR))
+            .unwrap_or(channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaced_channels() {
+        let context = SwitchContext::new(0x21000000000001, 1);
+        assert_eq!(context.default_vlan.get(), DEFAULT_VLAN_ID);
+        assert_eq!(context.channel("VLAN_TABLE"), "asic1:VLAN_TABLE");
+        assert_eq!(context.local_channel("asic1:VLAN_TABLE"), "VLAN_TABLE");
+        assert_eq!(context.local_channel("VLAN_TABLE"), "VLAN_TABLE");
+        assert_eq!(
+            context.local_channel("asic10:VLAN_TABLE"),
+            "asic10:VLAN_TABLE"
+        );
+        assert_eq!(
+            context
+                .table_keys(&TableKeys::default())
+                .key("VLAN_TABLE", "Vlan100"),
+            "asic1:VLAN_TABLE:Vlan100"
+        );
+    }
+}
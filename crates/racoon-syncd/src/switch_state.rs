@@ -0,0 +1,103 @@
+//! Switch operational state
+//!
+//! Publishes the SAI switch oper status to STATE_DB (`RACOON_STATE:switch`),
+//! and records a successful warm shutdown (`BOOT_STATE:warm_shutdown`) so
+//! the next start knows whether a warm boot can restore the switch.
+
+use racoon_common::keys::TableKeys;
+use racoon_common::metrics::unix_millis;
+use racoon_common::{Result, SaiOid};
+use racoon_db_client::{Database, DbClient, home_db, tables};
+use racoon_sai::SwitchOperStatus;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// BOOT_STATE key of the warm-shutdown marker
+const WARM_SHUTDOWN_KEY: &str = "warm_shutdown";
+
+/// STATE_DB key of the switch state
+pub const SWITCH_STATE_KEY: &str = "RACOON_STATE:switch";
+
+/// Switch state entry (STATE_DB)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwitchState {
+    #[serde(with = "racoon_common::hex_oid")]
+    pub switch_id: SaiOid,
+    pub oper_status: String, // "up", "down", "failed" or "unknown"
+}
+
+impl SwitchState {
+    pub fn new(switch_id: SaiOid, oper_status: SwitchOperStatus) -> Self {
+        Self {
+            switch_id,
+            oper_status: oper_status.as_str().to_string(),
+        }
+    }
+}
+
+/// Write the switch oper status to STATE_DB
+pub async fn publish_switch_state(
+    db_client: &DbClient,
+    switch_id: SaiOid,
+    oper_status: SwitchOperStatus,
+) -> Result<()> {
+    let state = SwitchState::new(switch_id, oper_status);
+    db_client
+        .set(Database::State, SWITCH_STATE_KEY, &state)
+        .await?;
+
+    info!(
+        "Switch 0x{:x} oper status: {}",
+        switch_id,
+        oper_status.as_str()
+    );
+    Ok(())
+}
+
+/// Record that the switch was prepared for a warm boot
+pub async fn mark_warm_shutdown(db_client: &DbClient, keys: &TableKeys) -> Result<()> {
+    db_client
+        .set(
+            home_db(tables::BOOT_STATE),
+            &keys.key(tables::BOOT_STATE, WARM_SHUTDOWN_KEY),
+            &serde_json::json!({ "ts": unix_millis() }),
+        )
+        .await
+}
+
+/// Consume the warm-shutdown marker, returning whether it was set
+///
+/// A warm boot is only possible right after a warm shutdown; the marker is
+/// removed so a crash after this start comes back cold.
+pub async fn take_warm_shutdown(db_client: &DbClient, keys: &TableKeys) -> Result<bool> {
+    let state_db = home_db(tables::BOOT_STATE);
+    let key = keys.key(tables::BOOT_STATE, WARM_SHUTDOWN_KEY);
+    if !db_client.exists(state_db, &key).await? {
+        return Ok(false);
+    }
+    db_client.del(state_db, &key).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_warm_shutdown_marker_consumed() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = server.client().await;
+        let keys = TableKeys::default();
+
+        assert!(!take_warm_shutdown(&db_client, &keys).await.unwrap());
+        mark_warm_shutdown(&db_client, &keys).await.unwrap();
+        assert!(
+            db_client
+                .exists(Database::State, "BOOT_STATE:warm_shutdown")
+                .await
+                .unwrap()
+        );
+        assert!(take_warm_shutdown(&db_client, &keys).await.unwrap());
+        assert!(!take_warm_shutdown(&db_client, &keys).await.unwrap());
+    }
+}
@@ -0,0 +1,323 @@
+//! VLAN operational state
+//!
+//! Records per-VLAN description, member count, oper-up members and oper
+//! status in STATE_DB (`VLAN_STATE:Vlan100`) for `show vlan` style tools.
+//! Entries are rewritten when the description or membership changes and when
+//! a member port's oper status changes (`PORT_STATE` notifications).
+
+use dashmap::DashMap;
+use racoon_common::Result;
+use racoon_common::keys::TableKeys;
+use racoon_db_client::{Database, DbClient};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Channel carrying port oper-status changes (`PORT_STATE:<port>` keys)
+pub const PORT_STATE_CHANNEL: &str = "PORT_STATE";
+
+/// VLAN state entry (STATE_DB)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VlanState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub member_count: usize,
+    /// Member ports that are oper up, sorted
+    #[serde(default)]
+    pub active_members: Vec<String>,
+    pub oper_status: String, // "up" or "down"
+}
+
+impl VlanState {
+    /// State of a VLAN given its member ports and their `PORT_STATE` entries
+    ///
+    /// Ports without state count as down. The VLAN is oper up when at least
+    /// one member is.
+    pub fn from_ports(ports: &[String], port_states: &[Option<serde_json::Value>]) -> Self {
+        let active_members: Vec<String> = ports
+            .iter()
+            .zip(port_states)
+            .filter(|(_, state)| {
+                state
+                    .as_ref()
+                    .is_some_and(|state| state["oper_status"] == "up")
+            })
+            .map(|(port, _)| port.clone())
+            .collect();
+
+        Self {
+            description: None,
+            member_count: ports.len(),
+            oper_status: if active_members.is_empty() {
+                "down"
+            } else {
+                "up"
+            }
+            .to_string(),
+            active_members,
+        }
+    }
+}
+
+/// Writes `VLAN_STATE` entries as VLANs and members are programmed
+pub struct VlanStateWriter {
+    db_client: Arc<DbClient>,
+    /// Member ports per VLAN name
+    members: DashMap<String, BTreeSet<String>>,
+    /// Namespace of the switch's keys
+    keys: TableKeys,
+    /// Descriptions of the VLANs that have one
+    descriptions: DashMap<String, String>,
+}
+
+impl VlanStateWriter {
+    pub fn new(db_client: Arc<DbClient>) -> Self {
+        Self {
+            db_client,
+            members: DashMap::new(),
+            keys: TableKeys::default(),
+            descriptions: DashMap::new(),
+        }
+    }
+
+    /// Write the keys in the namespace of `keys`
+    pub fn with_table_keys(mut self, keys: TableKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// STATE_DB key of a VLAN
+    pub fn key(vlan_name: &str) -> String {
+        format!("VLAN_STATE:{}", vlan_name)
+    }
+
+    /// STATE_DB key of a VLAN in the switch's namespace
+    fn scoped_key(&self, vlan_name: &str) -> String {
+        self.keys.scoped(&Self::key(vlan_name))
+    }
+
+    /// Record a newly programmed VLAN
+    pub async fn add_vlan(&self, vlan_name: &str, description: Option<&str>) -> Result<()> {
+        self.members.entry(vlan_name.to_string()).or_default();
+        self.set_description(vlan_name, description).await
+    }
+
+    /// Record a VLAN's description; `None` clears it
+    pub async fn set_description(&self, vlan_name: &str, description: Option<&str>) -> Result<()> {
+        match description {
+            Some(description) => {
+                self.descriptions
+                    .insert(vlan_name.to_string(), description.to_string());
+            }
+            None => {
+                self.descriptions.remove(vlan_name);
+            }
+        }
+        self.write(vlan_name).await
+    }
+
+    /// Drop the state of a removed VLAN
+    pub async fn remove_vlan(&self, vlan_name: &str) -> Result<()> {
+        self.members.remove(vlan_name);
+        self.descriptions.remove(vlan_name);
+        self.db_client
+            .del(Database::State, &self.scoped_key(vlan_name))
+            .await
+    }
+
+    /// Record a member port joining a VLAN
+    pub async fn add_member(&self, vlan_name: &str, port: &str) -> Result<()> {
+        self.members
+            .entry(vlan_name.to_string())
+            .or_default()
+            .insert(port.to_string());
+        self.write(vlan_name).await
+    }
+
+    /// Record a member port leaving a VLAN
+    pub async fn remove_member(&self, vlan_name: &str, port: &str) -> Result<()> {
+        if let Some(mut ports) = self.members.get_mut(vlan_name) {
+            ports.remove(port);
+        }
+        self.write(vlan_name).await
+    }
+
+    /// Rewrite every VLAN a port is a member of after its oper status changed
+    ///
+    /// Returns the names of the VLANs that were rewritten.
+    pub async fn port_oper_status_changed(&self, port: &str) -> Result<Vec<String>> {
+        let mut vlans: Vec<String> = self
+            .members
+            .iter()
+            .filter(|entry| entry.value().contains(port))
+            .map(|entry| entry.key().clone())
+            .collect();
+        vlans.sort();
+
+        for vlan_name in &vlans {
+            self.write(vlan_name).await?;
+        }
+        debug!("Oper status of {} changed, updated {:?}", port, vlans);
+        Ok(vlans)
+    }
+
+    /// Current member count of a VLAN
+    pub fn member_count(&self, vlan_name: &str) -> usize {
+        self.members.get(vlan_name).map_or(0, |ports| ports.len())
+    }
+
+    /// Recompute and write the state of a VLAN from STATE_DB `PORT_STATE`
+    async fn write(&self, vlan_name: &str) -> Result<()> {
+        let ports: Vec<String> = self
+            .members
+            .get(vlan_name)
+            .map(|ports| ports.iter().cloned().collect())
+            .unwrap_or_default();
+
+        let keys: Vec<String> = ports
+            .iter()
+            .map(|port| self.keys.scoped(&format!("PORT_STATE:{}", port)))
+            .collect();
+        let port_states: Vec<Option<serde_json::Value>> =
+            self.db_client.get_many(Database::State, &keys).await?;
+        let state = VlanState {
+            description: self
+                .descriptions
+                .get(vlan_name)
+                .map(|description| description.clone()),
+            ..VlanState::from_ports(&ports, &port_states)
+        };
+        self.db_client
+            .set(Database::State, &self.scoped_key(vlan_name), &state)
+            .await?;
+
+        debug!("Wrote state for {}: {:?}", vlan_name, state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_from_ports() {
+        let ports: Vec<String> = ["Ethernet0", "Ethernet4", "Ethernet8"]
+            .iter()
+            .map(|port| port.to_string())
+            .collect();
+        let up = Some(serde_json::json!({"oper_status": "up"}));
+        let down = Some(serde_json::json!({"oper_status": "down"}));
+
+        let state = VlanState::from_ports(&ports, &[up.clone(), down.clone(), up]);
+        assert_eq!(state.member_count, 3);
+        assert_eq!(state.active_members, vec!["Ethernet0", "Ethernet8"]);
+        assert_eq!(state.oper_status, "up");
+
+        // A port without state counts as down
+        let state = VlanState::from_ports(&ports, &[down, None, None]);
+        assert!(state.active_members.is_empty());
+        assert_eq!(state.oper_status, "down");
+    }
+
+    #[test]
+    fn test_state_without_description() {
+        let state = VlanState::from_ports(&[], &[]);
+        let json = serde_json::to_value(&state).unwrap();
+        assert!(json.get("description").is_none());
+
+        // Entries written before descriptions were recorded still parse
+        let state: VlanState =
+            serde_json::from_str(r#"{"member_count": 0, "oper_status": "down"}"#).unwrap();
+        assert_eq!(state.description, None);
+    }
+
+    #[tokio::test]
+    async fn test_description_written_and_updated() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let writer = VlanStateWriter::new(db_client.clone());
+        let state = || async {
+            db_client
+                .get::<VlanState>(Database::State, &VlanStateWriter::key("Vlan100"))
+                .await
+                .unwrap()
+        };
+
+        writer.add_vlan("Vlan100", Some("uplink")).await.unwrap();
+        assert_eq!(state().await.description.as_deref(), Some("uplink"));
+
+        writer
+            .set_description("Vlan100", Some("core"))
+            .await
+            .unwrap();
+        assert_eq!(state().await.description.as_deref(), Some("core"));
+
+        // Membership changes keep the description
+        writer.add_member("Vlan100", "Ethernet0").await.unwrap();
+        let vlan100 = state().await;
+        assert_eq!(vlan100.description.as_deref(), Some("core"));
+        assert_eq!(vlan100.member_count, 1);
+
+        writer.set_description("Vlan100", None).await.unwrap();
+        assert_eq!(state().await.description, None);
+    }
+
+    #[tokio::test]
+    async fn test_port_down_updates_all_its_vlans() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        // Port states are read in the writer's namespace
+        let keys = TableKeys::default().with_namespace("asic0");
+        let writer = VlanStateWriter::new(db_client.clone()).with_table_keys(keys.clone());
+
+        let set_oper = |port: &'static str, status: &'static str| {
+            let db_client = db_client.clone();
+            let key = keys.scoped(&format!("PORT_STATE:{}", port));
+            async move {
+                db_client
+                    .set(
+                        Database::State,
+                        &key,
+                        &serde_json::json!({"oper_status": status}),
+                    )
+                    .await
+                    .unwrap();
+            }
+        };
+        set_oper("Ethernet0", "up").await;
+        set_oper("Ethernet4", "up").await;
+
+        // Ethernet0 is a member of both VLANs
+        for (vlan, port) in [
+            ("Vlan100", "Ethernet0"),
+            ("Vlan100", "Ethernet4"),
+            ("Vlan200", "Ethernet0"),
+        ] {
+            writer.add_member(vlan, port).await.unwrap();
+        }
+        writer.add_vlan("Vlan300", None).await.unwrap();
+
+        set_oper("Ethernet0", "down").await;
+        let updated = writer.port_oper_status_changed("Ethernet0").await.unwrap();
+        assert_eq!(updated, vec!["Vlan100", "Vlan200"]);
+
+        let state = |vlan: &'static str| {
+            let db_client = db_client.clone();
+            let key = keys.scoped(&VlanStateWriter::key(vlan));
+            async move {
+                db_client
+                    .get::<VlanState>(Database::State, &key)
+                    .await
+                    .unwrap()
+            }
+        };
+        let vlan100 = state("Vlan100").await;
+        assert_eq!(vlan100.active_members, vec!["Ethernet4"]);
+        assert_eq!(vlan100.oper_status, "up");
+        let vlan200 = state("Vlan200").await;
+        assert!(vlan200.active_members.is_empty());
+        assert_eq!(vlan200.oper_status, "down");
+    }
+}
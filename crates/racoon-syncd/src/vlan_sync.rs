@@ -2,14 +2,40 @@
 //!
 //! Synchronizes VLAN entries from APPL_DB to hardware via SAI
 
+use crate::audit_log::{AuditLog, AuditRecord};
+use crate::clock::{Clock, TokioClock};
+use crate::intent_log::{IntentLog, IntentOp, SaiIntent};
+use crate::object_registry::ObjectRegistry;
+use crate::port_serdes::publish_port_serdes_state;
+use crate::retry::RetryPolicy;
+use crate::startup::{HeldNotification, StartupWindow};
+use crate::switch_context::SwitchContext;
+use crate::vlan_state::{PORT_STATE_CHANNEL, VlanStateWriter};
 use async_trait::async_trait;
 use dashmap::DashMap;
-use racoon_common::{Result, SaiOid, VlanId};
-use racoon_db_client::{Database, DbClient, DbSubscriber};
-use racoon_sai::VlanApi;
+use racoon_common::config::ResourceLimits;
+use racoon_common::deps::DependencyResolver;
+use racoon_common::keys::TableKeys;
+use racoon_common::metrics::{
+    EventLog, IgnoredNotifications, IgnoredSnapshot, LatencyHistogram, LatencySnapshot,
+    ProcessedEvent, unix_millis,
+};
+use racoon_common::{PortAdminStatus, Result, SaiOid, VlanId, VlanTaggingMode};
+use racoon_db_client::{
+    Counters, DbClient, DbSubscriber, Notification, Operation, home_db, tables,
+};
+use racoon_sai::port::PortApi;
+use racoon_sai::{
+    AsicAttributeSerializer, BridgeApi, BridgePortTable, NULL_OID, RouterInterfaceApi,
+    SAI_VLAN_STAT_IN_OCTETS, SAI_VLAN_STAT_IN_PACKETS, SAI_VLAN_STAT_OUT_OCTETS,
+    SAI_VLAN_STAT_OUT_PACKETS, SaiObjectType, SwitchApi, VlanApi, sai_vlan_stat_t,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{debug, error, info, warn};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, watch};
+use tracing::{debug, error, info, instrument, warn};
 
 /// VLAN entry from APPL_DB
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,59 +43,572 @@ pub struct VlanEntry {
     pub vlanid: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Admin status of the VLAN's L3 interface; `None` leaves it up
+    #[serde(
+        default,
+        with = "racoon_common::admin_status",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub admin_status: Option<PortAdminStatus>,
+    /// MTU of the VLAN's L3 interface; `None` keeps the platform default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
 }
 
+/// VLAN member entry from APPL_DB (`VLAN_MEMBER_TABLE:Vlan100:Ethernet0`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VlanMemberEntry {
+    pub tagging_mode: VlanTaggingMode,
+}
+
+/// VLAN record in ASIC_DB (`ASIC_STATE:SAI_OBJECT_TYPE_VLAN:<oid>`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsicVlanRecord {
+    pub vlanid: u16,
+    #[serde(with = "racoon_common::hex_oid")]
+    pub oid: SaiOid,
+}
+
+impl AsicVlanRecord {
+    /// ASIC_DB key of the record
+    pub fn key(&self) -> String {
+        format!("ASIC_STATE:SAI_OBJECT_TYPE_VLAN:0x{:x}", self.oid)
+    }
+}
+
+/// SAI object type of VLAN intents
+const VLAN_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_VLAN";
+
+/// SAI object type of VLAN member audit records
+const VLAN_MEMBER_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_VLAN_MEMBER";
+
+/// SAI object type of router interface audit records
+const RIF_OBJECT_TYPE: &str = "SAI_OBJECT_TYPE_ROUTER_INTERFACE";
+
+/// VLAN counters polled into COUNTERS_DB, with their field names
+pub const VLAN_COUNTERS: [(sai_vlan_stat_t, &str); 4] = [
+    (SAI_VLAN_STAT_IN_OCTETS, "SAI_VLAN_STAT_IN_OCTETS"),
+    (SAI_VLAN_STAT_IN_PACKETS, "SAI_VLAN_STAT_IN_PACKETS"),
+    (SAI_VLAN_STAT_OUT_OCTETS, "SAI_VLAN_STAT_OUT_OCTETS"),
+    (SAI_VLAN_STAT_OUT_PACKETS, "SAI_VLAN_STAT_OUT_PACKETS"),
+];
+
 /// VLAN synchronization state
 #[derive(Debug, Clone)]
-struct VlanState {
+struct ProgrammedVlan {
     _vlan_id: VlanId,
     /// SAI object ID for the VLAN
     sai_oid: SaiOid,
+    /// Last APPL_DB entry programmed for this VLAN
+    entry: VlanEntry,
+}
+
+/// VLAN member created in SAI
+#[derive(Debug, Clone, Copy)]
+struct ProgrammedMember {
+    oid: SaiOid,
+    vlan_oid: SaiOid,
+    bridge_port: SaiOid,
+    tagging_mode: VlanTaggingMode,
+    /// Port whose bridge port was acquired from the bridge port table
+    shared_port: Option<SaiOid>,
+}
+
+/// What programming a VLAN entry takes, decided before SAI is called
+enum VlanPlan {
+    /// Already programmed; changes were applied in place
+    Updated,
+    /// Restored by warm boot and tracked, with its ASIC_DB record and intent
+    Adopted(AsicVlanRecord, SaiIntent),
+    /// Must be created in SAI under the OID-less intent
+    Create(VlanId, SaiIntent),
 }
 
 /// VLAN Synchronization Agent
 pub struct VlanSync {
     db_client: Arc<DbClient>,
     vlan_api: Arc<VlanApi>,
-    switch_id: SaiOid,
+    /// Router interface api; without one VLAN admin status is only tracked
+    rif_api: Option<Arc<RouterInterfaceApi>>,
+    /// Port api; without one the serdes state of ports is not published
+    port_api: Option<Arc<PortApi>>,
+    /// Bridge ports created for ports without a registered one
+    bridge_ports: Option<(Arc<BridgeApi>, Arc<BridgePortTable>)>,
+    /// Switch api whose cached capabilities gate attribute sets; without
+    /// one every set is attempted
+    switch_api: Option<Arc<SwitchApi>>,
+    /// Switch this agent programs
+    switch: SwitchContext,
     /// Track VLANs we've programmed
-    vlans: DashMap<VlanId, VlanState>,
+    vlans: DashMap<VlanId, ProgrammedVlan>,
+    /// Locks serializing creates and removes of the same VLAN
+    in_flight: DashMap<VlanId, Arc<Mutex<()>>>,
+    /// OIDs shared with the switch's other agents; VLANs are published
+    /// here and bridge ports looked up
+    registry: Arc<ObjectRegistry>,
+    /// Router interface (SVI) of each VLAN with L3 enabled
+    router_interfaces: DashMap<VlanId, SaiOid>,
+    /// VLAN members by member key (`Vlan100:Ethernet0`), as last
+    /// programmed in SAI
+    members: DashMap<String, ProgrammedMember>,
+    /// Member keys whose VLAN is not programmed yet, by VLAN; programmed
+    /// once their VLAN is
+    deferred_members: DashMap<VlanId, HashSet<String>>,
+    /// Counters of each VLAN at the last poll, the base of its RATES
+    last_counters: DashMap<VlanId, (Instant, Counters)>,
+    /// Notification latency (producer timestamp or receipt -> programmed)
+    latency: LatencyHistogram,
+    /// Notifications dropped before handling
+    ignored: IgnoredNotifications,
+    /// Outcome of the last processed notifications
+    events: EventLog,
+    /// Operational state written to STATE_DB
+    state_writer: VlanStateWriter,
+    /// Write-ahead log of SAI creates and removes
+    intent_log: IntentLog,
+    /// File receiving every completed SAI create and remove
+    audit_log: Option<Arc<AuditLog>>,
+    /// Backoff for transient SAI failures
+    retry: RetryPolicy,
+    /// Time source of the retry backoff
+    clock: Arc<dyn Clock>,
+    /// Notifications held while the startup scan runs
+    startup: StartupWindow,
+    /// Hardware table sizes checked before creating VLANs
+    limits: ResourceLimits,
+    /// Remove programmed objects in [`shutdown`](Self::shutdown)
+    cleanup_on_shutdown: bool,
+    /// SAI restored the switch from a warm shutdown, so VLANs already in
+    /// SAI are adopted rather than created
+    warm_boot: bool,
+    /// OIDs of the VLANs recorded in ASIC_DB before a warm boot, until
+    /// they are adopted
+    restored_vlans: DashMap<VlanId, SaiOid>,
+    /// APPL_DB key layout
+    table_keys: TableKeys,
 }
 
 impl VlanSync {
     /// Create new VLAN sync agent
-    pub fn new(db_client: Arc<DbClient>, vlan_api: Arc<VlanApi>, switch_id: SaiOid) -> Self {
+    pub fn new(db_client: Arc<DbClient>, vlan_api: Arc<VlanApi>, switch: SwitchContext) -> Self {
         Self {
+            state_writer: VlanStateWriter::new(db_client.clone()),
+            intent_log: IntentLog::new(db_client.clone()),
+            audit_log: None,
             db_client,
             vlan_api,
-            switch_id,
+            rif_api: None,
+            port_api: None,
+            bridge_ports: None,
+            switch_api: None,
+            switch,
             vlans: DashMap::new(),
+            in_flight: DashMap::new(),
+            registry: Arc::new(ObjectRegistry::new()),
+            router_interfaces: DashMap::new(),
+            members: DashMap::new(),
+            deferred_members: DashMap::new(),
+            last_counters: DashMap::new(),
+            latency: LatencyHistogram::new(),
+            ignored: IgnoredNotifications::new(),
+            events: EventLog::default(),
+            retry: RetryPolicy::default(),
+            clock: Arc::new(TokioClock),
+            startup: StartupWindow::new(),
+            limits: ResourceLimits::default(),
+            cleanup_on_shutdown: false,
+            warm_boot: false,
+            restored_vlans: DashMap::new(),
+            table_keys: TableKeys::default(),
+        }
+    }
+
+    /// Switch this agent programs
+    pub fn switch(&self) -> &SwitchContext {
+        &self.switch
+    }
+
+    /// Override the backoff used for transient SAI failures
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Override the time source, e.g. with a mock clock in tests
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Enforce the platform's hardware table sizes
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Remove programmed VLANs on shutdown instead of leaving them for a
+    /// warm boot
+    pub fn with_cleanup_on_shutdown(mut self, cleanup: bool) -> Self {
+        self.cleanup_on_shutdown = cleanup;
+        self
+    }
+
+    /// Adopt the VLANs SAI restored on a warm boot instead of creating
+    /// them again
+    pub fn with_warm_boot(mut self, warm_boot: bool) -> Self {
+        self.warm_boot = warm_boot;
+        self
+    }
+
+    /// Apply VLAN admin status to the VLANs' router interfaces, and remove
+    /// them before their VLAN
+    pub fn with_router_interface_api(mut self, rif_api: Arc<RouterInterfaceApi>) -> Self {
+        self.rif_api = Some(rif_api);
+        self
+    }
+
+    /// Publish a port's link training and serdes state when its oper
+    /// status changes
+    pub fn with_port_api(mut self, port_api: Arc<PortApi>) -> Self {
+        self.port_api = Some(port_api);
+        self
+    }
+
+    /// Create the bridge port of a member's port in `table` when no agent
+    /// registered one, and remove it with the port's last member
+    pub fn with_bridge_ports(
+        mut self,
+        bridge_api: Arc<BridgeApi>,
+        table: Arc<BridgePortTable>,
+    ) -> Self {
+        self.bridge_ports = Some((bridge_api, table));
+        self
+    }
+
+    /// Skip attribute sets the platform reports as not settable
+    pub fn with_switch_api(mut self, switch_api: Arc<SwitchApi>) -> Self {
+        self.switch_api = Some(switch_api);
+        self
+    }
+
+    /// Override the APPL_DB key layout
+    ///
+    /// A namespace in `table_keys` also scopes the agent's ASIC_DB records,
+    /// intents and VLAN_STATE entries, so switches sharing a database keep
+    /// apart.
+    pub fn with_table_keys(mut self, table_keys: TableKeys) -> Self {
+        self.state_writer = self.state_writer.with_table_keys(table_keys.clone());
+        self.intent_log = self.intent_log.with_table_keys(table_keys.clone());
+        self.table_keys = table_keys;
+        self
+    }
+
+    /// Share object OIDs with the switch's other agents
+    pub fn with_object_registry(mut self, registry: Arc<ObjectRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Append SAI creates and removes to an audit file
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Keep the last `capacity` processed notifications for
+    /// [`recent_events`](Self::recent_events)
+    pub fn with_event_capacity(mut self, capacity: usize) -> Self {
+        self.events = EventLog::new(capacity);
+        self
+    }
+
+    /// Record the bridge port VLAN members of `port` are created on, and
+    /// program the members deferred until it existed
+    pub async fn set_bridge_port(&self, port: &str, bridge_port_oid: SaiOid) {
+        self.registry.register_bridge_port(port, bridge_port_oid);
+        self.replay_port_members(port).await;
+    }
+
+    /// Record the router interface of a VLAN and apply its admin status
+    /// and MTU
+    pub fn set_router_interface(&self, vlan_id: VlanId, rif_oid: SaiOid) -> Result<()> {
+        self.router_interfaces.insert(vlan_id, rif_oid);
+        self.apply_rif_admin_state(vlan_id)?;
+        self.apply_rif_mtu(vlan_id)
+    }
+
+    /// Record a completed SAI create, remove or attribute set in the
+    /// audit file
+    fn audit(&self, record: impl Into<AuditRecord>) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(record);
+        }
+    }
+
+    /// Lock serializing operations on `vlan_id`
+    ///
+    /// Locks are kept once created; dropping one while another task holds
+    /// it would let a third task start alongside it.
+    fn vlan_lock(&self, vlan_id: VlanId) -> Arc<Mutex<()>> {
+        self.in_flight.entry(vlan_id).or_default().clone()
+    }
+
+    /// Enable or disable routing on a VLAN's router interface
+    ///
+    /// Does nothing for an untracked VLAN or one without a router interface.
+    fn apply_rif_admin_state(&self, vlan_id: VlanId) -> Result<()> {
+        let (Some(rif_api), Some(rif_oid)) = (
+            &self.rif_api,
+            self.router_interfaces.get(&vlan_id).map(|rif| *rif),
+        ) else {
+            return Ok(());
+        };
+        let Some(admin_status) = self.vlans.get(&vlan_id).map(|vlan| vlan.entry.admin_status)
+        else {
+            return Ok(());
+        };
+
+        let up = admin_status != Some(PortAdminStatus::Down);
+        rif_api.set_admin_state(rif_oid, up)?;
+        self.audit(AuditRecord::set(
+            RIF_OBJECT_TYPE,
+            &format!("Vlan{}", vlan_id.get()),
+            rif_oid,
+            "admin_state",
+            if up { "up" } else { "down" },
+        ));
+        info!(
+            "Router interface 0x{:x} of VLAN {} admin {}",
+            rif_oid,
+            vlan_id.get(),
+            if up { "up" } else { "down" }
+        );
+        Ok(())
+    }
+
+    /// Set the MTU of a VLAN's router interface
+    ///
+    /// Skipped when the VLAN has no MTU or the switch reports the router
+    /// interface MTU as not settable. An MTU dropped from the entry leaves the
+    /// last one set.
+    fn apply_rif_mtu(&self, vlan_id: VlanId) -> Result<()> {
+        let (Some(rif_api), Some(rif_oid)) = (
+            &self.rif_api,
+            self.router_interfaces.get(&vlan_id).map(|rif| *rif),
+        ) else {
+            return Ok(());
+        };
+        let Some(mtu) = self.vlans.get(&vlan_id).and_then(|vlan| vlan.entry.mtu) else {
+            return Ok(());
+        };
+
+        let set = match &self.switch_api {
+            Some(switch_api) => {
+                rif_api.set_mtu_if_supported(switch_api, self.switch.switch_id, rif_oid, mtu)?
+            }
+            None => {
+                rif_api.set_mtu(rif_oid, mtu)?;
+                true
+            }
+        };
+        if !set {
+            return Ok(());
         }
+        self.audit(AuditRecord::set(
+            RIF_OBJECT_TYPE,
+            &format!("Vlan{}", vlan_id.get()),
+            rif_oid,
+            "mtu",
+            mtu,
+        ));
+        info!(
+            "Router interface 0x{:x} of VLAN {} MTU {}",
+            rif_oid,
+            vlan_id.get(),
+            mtu
+        );
+        Ok(())
+    }
+
+    /// Hold notifications until [`start`](Self::start) has scanned APPL_DB
+    ///
+    /// Call before subscribing, so entries published while the scan runs
+    /// are neither missed nor programmed twice.
+    pub fn begin_startup(&self) {
+        self.startup.open();
     }
 
     /// Start the sync agent
     pub async fn start(&self) -> Result<()> {
         info!("Starting VLAN synchronization agent");
 
+        // Undo operations interrupted by a crash before loading APPL_DB
+        self.reconcile_intents().await?;
+
+        // VLANs restored by a warm boot are adopted by their ASIC_DB records
+        if self.warm_boot {
+            for record in self.asic_records().await? {
+                if let Some(vlan_id) = VlanId::new(record.vlanid) {
+                    self.restored_vlans.insert(vlan_id, record.oid);
+                }
+            }
+            info!(
+                "Adopting {} VLANs restored by warm boot",
+                self.restored_vlans.len()
+            );
+        }
+
         // Load existing VLANs from APPL_DB
         self.sync_vlans().await?;
 
+        // Handle what was published during the scan and not seen by it
+        self.replay_held_notifications().await;
+
         info!("VLAN synchronization agent started");
         Ok(())
     }
 
-    /// Sync all VLANs from APPL_DB to SAI
-    async fn sync_vlans(&self) -> Result<()> {
+    /// Handle notifications held during the startup scan, then stop holding
+    async fn replay_held_notifications(&self) {
+        let entry_of = |held: &HeldNotification| {
+            let channel = self.switch.local_channel(&held.channel);
+            if channel != tables::VLAN_TABLE && channel != tables::VLAN_MEMBER_TABLE {
+                return None;
+            }
+            let key = serde_json::from_str::<serde_json::Value>(&held.message)
+                .ok()
+                .and_then(|notification| notification["key"].as_str().map(str::to_string));
+            Some((channel.to_string(), key))
+        };
+
+        while let Some(batch) = self.startup.take_uncovered(entry_of) {
+            debug!(
+                "Replaying {} notifications held during startup",
+                batch.len()
+            );
+            for held in batch {
+                self.process_notification(&held.channel, &held.message)
+                    .await;
+            }
+        }
+    }
+
+    /// Sync all VLANs and their members from APPL_DB to SAI
+    ///
+    /// Entries are applied in dependency order (a member after its VLAN);
+    /// members of VLANs missing from APPL_DB are left for their VLAN's
+    /// notification.
+    pub async fn sync_vlans(&self) -> Result<()> {
         info!("Syncing VLANs from APPL_DB to SAI");
 
-        let keys = self.db_client.keys(Database::Appl, "VLAN_TABLE:*").await?;
+        self.startup.mark_scanned(tables::VLAN_TABLE);
+        let keys = self
+            .db_client
+            .scan(
+                home_db(tables::VLAN_TABLE),
+                &self.table_keys.pattern(tables::VLAN_TABLE),
+            )
+            .await?;
+
+        // Fetch all entries in a single round-trip
+        for key in &keys {
+            if let Some(vlan_name) = self.table_keys.strip(tables::VLAN_TABLE, key) {
+                self.startup.mark_read(tables::VLAN_TABLE, vlan_name);
+            }
+        }
+        let entries: Vec<Option<VlanEntry>> = self
+            .db_client
+            .get_many(home_db(tables::VLAN_TABLE), &keys)
+            .await?;
+        let mut entries: HashMap<String, Option<VlanEntry>> =
+            keys.iter().cloned().zip(entries).collect();
+
+        self.startup.mark_scanned(tables::VLAN_MEMBER_TABLE);
+        let member_keys = self
+            .db_client
+            .scan(
+                home_db(tables::VLAN_MEMBER_TABLE),
+                &self.table_keys.pattern(tables::VLAN_MEMBER_TABLE),
+            )
+            .await?;
+
+        let mut resolver = DependencyResolver::new();
+        for key in &keys {
+            resolver.add(key.clone(), Vec::new());
+        }
+        for key in &member_keys {
+            let member = self
+                .table_keys
+                .strip(tables::VLAN_MEMBER_TABLE, key)
+                .unwrap_or(key);
+            let vlan_name = member.split_once(':').map_or(member, |(vlan, _)| vlan);
+            resolver.add(
+                key.clone(),
+                vec![self.table_keys.key(tables::VLAN_TABLE, vlan_name)],
+            );
+        }
+        let plan = resolver.resolve(&HashSet::new());
+
+        // Members only depend on VLANs, so all VLANs are programmed first,
+        // together
+        let mut vlans = Vec::new();
+        for key in &plan.ordered {
+            let Some(vlan_name) = self.table_keys.strip(tables::VLAN_TABLE, key) else {
+                continue;
+            };
+            let Some(entry) = entries.remove(key).flatten() else {
+                debug!("VLAN {} disappeared before it could be read", vlan_name);
+                continue;
+            };
+
+            if check_vlan_key(vlan_name, &entry).is_ok() {
+                vlans.push(entry);
+            }
+        }
+        for (vlanid, result) in self.program_vlans(vlans).await {
+            match result {
+                Ok(_) => debug!("Synced VLAN: Vlan{}", vlanid),
+                Err(e) => warn!("Failed to sync VLAN Vlan{}: {}", vlanid, e),
+            }
+        }
 
-        for key in keys {
-            if let Some(vlan_name) = key.strip_prefix("VLAN_TABLE:") {
-                match self.create_vlan(vlan_name).await {
-                    Ok(_) => debug!("Synced VLAN: {}", vlan_name),
-                    Err(e) => warn!("Failed to sync VLAN {}: {}", vlan_name, e),
+        // New members are created in one bulk call
+        let mut member_keys = Vec::new();
+        let mut members = Vec::new();
+        for key in &plan.ordered {
+            if let Some(member) = self.table_keys.strip(tables::VLAN_MEMBER_TABLE, key) {
+                self.startup.mark_read(tables::VLAN_MEMBER_TABLE, member);
+                member_keys.push(key.clone());
+                members.push(member.to_string());
+            }
+        }
+        let member_entries: Vec<Option<VlanMemberEntry>> = self
+            .db_client
+            .get_many(home_db(tables::VLAN_MEMBER_TABLE), &member_keys)
+            .await?;
+        let members = members
+            .into_iter()
+            .zip(member_entries)
+            .filter_map(|(member, entry)| match entry {
+                Some(entry) => Some((member, entry)),
+                None => {
+                    debug!("VLAN member {} disappeared before it could be read", member);
+                    None
                 }
+            })
+            .collect();
+        for (member, result) in self.program_members(members).await {
+            match result {
+                Ok(()) => {}
+                // The VLAN failed to program; retried with it
+                Err(racoon_common::RacoonError::VlanNotFound(_)) => self.defer_member(&member),
+                Err(e) => warn!("Failed to load VLAN member {}: {}", member, e),
+            }
+        }
+        for key in plan.deferred.iter().chain(&plan.cyclic) {
+            if let Some(member) = self.table_keys.strip(tables::VLAN_MEMBER_TABLE, key) {
+                debug!("Deferring {}: its VLAN is not in APPL_DB", member);
+                self.defer_member(member);
             }
         }
 
@@ -79,27 +618,294 @@ impl VlanSync {
 
     /// Create VLAN in hardware via SAI
     async fn create_vlan(&self, vlan_name: &str) -> Result<()> {
-        let appl_key = format!("VLAN_TABLE:{}", vlan_name);
+        let appl_key = self.table_keys.key(tables::VLAN_TABLE, vlan_name);
 
         // Get VLAN entry from APPL_DB
-        let entry: VlanEntry = self.db_client.get(Database::Appl, &appl_key).await?;
+        let entry: VlanEntry = self
+            .db_client
+            .get(home_db(tables::VLAN_TABLE), &appl_key)
+            .await?;
+        check_vlan_key(vlan_name, &entry)?;
+
+        self.program_vlan(entry).await
+    }
 
+    /// Program a VLAN entry to hardware and record it in ASIC_DB
+    async fn program_vlan(&self, entry: VlanEntry) -> Result<()> {
         let vlan_id = VlanId::new(entry.vlanid)
             .ok_or(racoon_common::RacoonError::InvalidVlanId(entry.vlanid))?;
 
-        // Check if already created
-        if self.vlans.contains_key(&vlan_id) {
-            debug!("VLAN {} already exists in SAI", vlan_id.get());
+        // Held until the VLAN is complete, so a delete can't remove it
+        // between the SAI create and its ASIC_DB record
+        let lock = self.vlan_lock(vlan_id);
+        let _guard = lock.lock().await;
+
+        let Some((record, intent)) = self.create_or_update_vlan(&entry).await? else {
             return Ok(());
+        };
+        self.db_client
+            .set(
+                home_db(tables::ASIC_STATE),
+                &self.table_keys.scoped(&record.key()),
+                &record,
+            )
+            .await?;
+        self.complete_vlan(&entry, &record, &intent).await
+    }
+
+    /// Program VLAN entries, recording the created VLANs in ASIC_DB in one
+    /// pipeline
+    ///
+    /// The intents of the batch are recorded, given their OIDs and cleared
+    /// in one pipeline each rather than per VLAN. Returns the outcome of
+    /// each entry by VLAN id. VLANs SAI failed to create get no ASIC_DB
+    /// record. Entries are distinct VLANs, as read from `VLAN_TABLE`.
+    async fn program_vlans(&self, entries: Vec<VlanEntry>) -> Vec<(u16, Result<()>)> {
+        // Every VLAN of the batch stays locked until it is complete, like in
+        // `program_vlan`; locks are taken in VLAN id order so concurrent
+        // batches can't deadlock
+        let mut vlan_ids: Vec<VlanId> = entries
+            .iter()
+            .filter_map(|entry| VlanId::new(entry.vlanid))
+            .collect();
+        vlan_ids.sort_by_key(|vlan_id| vlan_id.get());
+        vlan_ids.dedup();
+        let mut _guards = Vec::with_capacity(vlan_ids.len());
+        for vlan_id in vlan_ids {
+            _guards.push(self.vlan_lock(vlan_id).lock_owned().await);
+        }
+
+        let mut results = Vec::with_capacity(entries.len());
+        let mut created = Vec::new();
+        let mut pending = Vec::new();
+        for entry in entries {
+            match self.plan_vlan(&entry, pending.len()).await {
+                Ok(VlanPlan::Updated) => results.push((entry.vlanid, Ok(()))),
+                Ok(VlanPlan::Adopted(record, intent)) => created.push((entry, record, intent)),
+                Ok(VlanPlan::Create(vlan_id, intent)) => pending.push((entry, vlan_id, intent)),
+                Err(e) => results.push((entry.vlanid, Err(e))),
+            }
+        }
+
+        // The create intents of the whole batch go in one pipeline
+        let intents: Vec<SaiIntent> = pending
+            .iter()
+            .map(|(_, _, intent)| intent.clone())
+            .collect();
+        if let Err(e) = self.intent_log.record_many(&intents).await {
+            for (entry, _, _) in pending {
+                results.push((
+                    entry.vlanid,
+                    Err(racoon_common::RacoonError::Database(e.to_string())),
+                ));
+            }
+            pending = Vec::new();
+        }
+
+        // SAI has no bulk VLAN create, so the VLANs are created one by one
+        let mut failed = Vec::new();
+        let mut with_oids = Vec::new();
+        for (entry, vlan_id, intent) in pending {
+            match self.create_sai_vlan(vlan_id, &entry, &intent).await {
+                Ok(vlan_oid) => {
+                    let intent = intent.with_oid(vlan_oid);
+                    let record = AsicVlanRecord {
+                        vlanid: entry.vlanid,
+                        oid: vlan_oid,
+                    };
+                    with_oids.push(intent.clone());
+                    created.push((entry, record, intent));
+                }
+                Err(e) => {
+                    failed.push(intent);
+                    results.push((entry.vlanid, Err(e)));
+                }
+            }
+        }
+        if let Err(e) = self.intent_log.clear_many(&failed).await {
+            warn!(
+                "Failed to clear the intents of VLANs SAI didn't create: {}",
+                e
+            );
+        }
+        if let Err(e) = self.intent_log.record_many(&with_oids).await {
+            // The OID-less intents stay; reconcile finds the VLANs by their ID
+            warn!(
+                "Failed to record the OIDs of created VLANs in the intent log: {}",
+                e
+            );
+        }
+
+        let records: Vec<(String, &AsicVlanRecord)> = created
+            .iter()
+            .map(|(_, record, _)| (self.table_keys.scoped(&record.key()), record))
+            .collect();
+        if let Err(e) = self
+            .db_client
+            .set_many(home_db(tables::ASIC_STATE), &records)
+            .await
+        {
+            // The VLANs stay programmed and tracked, like a failed single
+            // write leaves them
+            for (entry, _, _) in created {
+                results.push((
+                    entry.vlanid,
+                    Err(racoon_common::RacoonError::Database(e.to_string())),
+                ));
+            }
+            return results;
+        }
+
+        let mut written = Vec::with_capacity(created.len());
+        for (entry, record, intent) in created {
+            match self.write_vlan_state(&entry).await {
+                Ok(()) => written.push((entry, record, intent)),
+                Err(e) => results.push((entry.vlanid, Err(e))),
+            }
+        }
+        let intents: Vec<SaiIntent> = written
+            .iter()
+            .map(|(_, _, intent)| intent.clone())
+            .collect();
+        if let Err(e) = self.intent_log.clear_many(&intents).await {
+            for (entry, _, _) in written {
+                results.push((
+                    entry.vlanid,
+                    Err(racoon_common::RacoonError::Database(e.to_string())),
+                ));
+            }
+            return results;
+        }
+        for (entry, record, _) in written {
+            self.vlan_programmed(&entry, &record).await;
+            results.push((entry.vlanid, Ok(())));
+        }
+        results
+    }
+
+    /// Create a VLAN in SAI, or update the one already programmed
+    ///
+    /// A created VLAN is tracked and returned with its ASIC_DB record and
+    /// the intent [`complete_vlan`](Self::complete_vlan) clears once the
+    /// record is written. The caller holds the VLAN's lock until then, so a
+    /// concurrent create of the same VLAN sees it as programmed.
+    async fn create_or_update_vlan(
+        &self,
+        entry: &VlanEntry,
+    ) -> Result<Option<(AsicVlanRecord, SaiIntent)>> {
+        let (vlan_id, intent) = match self.plan_vlan(entry, 0).await? {
+            VlanPlan::Updated => return Ok(None),
+            VlanPlan::Adopted(record, intent) => return Ok(Some((record, intent))),
+            VlanPlan::Create(vlan_id, intent) => (vlan_id, intent),
+        };
+
+        self.intent_log.record(&intent).await?;
+        let vlan_oid = match self.create_sai_vlan(vlan_id, entry, &intent).await {
+            Ok(oid) => oid,
+            Err(e) => {
+                self.intent_log.clear(&intent).await?;
+                return Err(e);
+            }
+        };
+        let intent = intent.with_oid(vlan_oid);
+        if let Err(e) = self.intent_log.record(&intent).await {
+            // The OID-less intent stays; reconcile finds the VLAN by its ID
+            warn!(
+                "Failed to record the OID of VLAN {} in the intent log: {}",
+                vlan_id.get(),
+                e
+            );
+        }
+
+        let record = AsicVlanRecord {
+            vlanid: entry.vlanid,
+            oid: vlan_oid,
+        };
+        Ok(Some((record, intent)))
+    }
+
+    /// Apply an entry to its programmed VLAN, adopt a warm-boot restored
+    /// one, or plan its SAI create
+    ///
+    /// Nothing is written to the intent log; a planned create's intent must
+    /// be recorded before [`create_sai_vlan`](Self::create_sai_vlan).
+    /// `planned` VLANs already planned but not created yet count against the
+    /// VLAN limit.
+    async fn plan_vlan(&self, entry: &VlanEntry, planned: usize) -> Result<VlanPlan> {
+        let vlan_id = VlanId::new(entry.vlanid)
+            .ok_or(racoon_common::RacoonError::InvalidVlanId(entry.vlanid))?;
+
+        // Check if already created; the description, admin status and MTU
+        // change in place
+        let changed = self.vlans.get_mut(&vlan_id).map(|mut vlan| {
+            let description_changed = vlan.entry.description != entry.description;
+            let admin_changed = vlan.entry.admin_status != entry.admin_status;
+            let mtu_changed = vlan.entry.mtu != entry.mtu;
+            vlan.entry.description = entry.description.clone();
+            vlan.entry.admin_status = entry.admin_status;
+            vlan.entry.mtu = entry.mtu;
+            (description_changed, admin_changed, mtu_changed)
+        });
+        if let Some((description_changed, admin_changed, mtu_changed)) = changed {
+            debug!("VLAN {} already exists in SAI", vlan_id.get());
+            if admin_changed {
+                self.apply_rif_admin_state(vlan_id)?;
+            }
+            if mtu_changed {
+                self.apply_rif_mtu(vlan_id)?;
+            }
+            if description_changed {
+                self.state_writer
+                    .set_description(
+                        &format!("Vlan{}", vlan_id.get()),
+                        entry.description.as_deref(),
+                    )
+                    .await?;
+            }
+            return Ok(VlanPlan::Updated);
+        }
+        self.limits.check_vlans(self.vlans.len() + planned)?;
+
+        if let Some(vlan_oid) = self.restored_vlan(vlan_id) {
+            info!(
+                "Adopted VLAN {} restored by warm boot (OID: 0x{:x})",
+                vlan_id.get(),
+                vlan_oid
+            );
+            self.track_vlan(vlan_id, vlan_oid, entry);
+            let intent = SaiIntent::create(VLAN_OBJECT_TYPE, &format!("Vlan{}", vlan_id.get()))
+                .with_oid(vlan_oid);
+            let record = AsicVlanRecord {
+                vlanid: entry.vlanid,
+                oid: vlan_oid,
+            };
+            return Ok(VlanPlan::Adopted(record, intent));
         }
 
-        // Create VLAN via SAI
+        let intent = SaiIntent::create(VLAN_OBJECT_TYPE, &format!("Vlan{}", vlan_id.get()));
+        Ok(VlanPlan::Create(vlan_id, intent))
+    }
+
+    /// Create a planned VLAN in SAI and track it
+    async fn create_sai_vlan(
+        &self,
+        vlan_id: VlanId,
+        entry: &VlanEntry,
+        intent: &SaiIntent,
+    ) -> Result<SaiOid> {
         info!(
-            "Creating VLAN {} in hardware (switch_id: 0x{:x})",
+            "Creating VLAN {} in hardware (switch {}, switch_id: 0x{:x})",
             vlan_id.get(),
-            self.switch_id
+            self.switch.index,
+            self.switch.switch_id
         );
-        let vlan_oid = self.vlan_api.create_vlan(self.switch_id, vlan_id)?;
+        let vlan_oid = self
+            .retry
+            .run_with_clock(&*self.clock, "create VLAN", || {
+                self.vlan_api.create_vlan(self.switch.switch_id, vlan_id)
+            })
+            .await?;
+        self.audit(&intent.clone().with_oid(vlan_oid));
 
         info!(
             "Created VLAN {} in SAI with OID: 0x{:x}",
@@ -107,42 +913,169 @@ impl VlanSync {
             vlan_oid
         );
 
-        // Store state
-        let state = VlanState {
+        // Tracked before anything else can fail, so the VLAN in SAI is
+        // never forgotten
+        self.track_vlan(vlan_id, vlan_oid, entry);
+        Ok(vlan_oid)
+    }
+
+    /// Track a VLAN present in SAI and share its OID
+    fn track_vlan(&self, vlan_id: VlanId, vlan_oid: SaiOid, entry: &VlanEntry) {
+        let state = ProgrammedVlan {
             _vlan_id: vlan_id,
             sai_oid: vlan_oid,
+            entry: entry.clone(),
         };
-        self.vlans.insert(vlan_id, state.clone());
+        self.vlans.insert(vlan_id, state);
+        self.registry.register_vlan(vlan_id, vlan_oid);
+    }
 
-        // Write to ASIC_DB
-        let asic_key = format!("ASIC_STATE:SAI_OBJECT_TYPE_VLAN:0x{:x}", vlan_oid);
-        let asic_value = serde_json::json!({
-            "vlanid": entry.vlanid,
-            "oid": format!("0x{:x}", vlan_oid)
-        });
+    /// OID of a VLAN SAI restored on a warm boot, from its ASIC_DB record or
+    /// else by looking it up in SAI
+    fn restored_vlan(&self, vlan_id: VlanId) -> Option<SaiOid> {
+        if !self.warm_boot {
+            return None;
+        }
+        if let Some((_, vlan_oid)) = self.restored_vlans.remove(&vlan_id) {
+            return Some(vlan_oid);
+        }
+        match self.vlan_api.find_vlan(self.switch.switch_id, vlan_id) {
+            Ok(vlan_oid) => vlan_oid,
+            Err(e) => {
+                warn!(
+                    "Failed to look up VLAN {} in SAI, creating it: {}",
+                    vlan_id.get(),
+                    e
+                );
+                None
+            }
+        }
+    }
 
-        self.db_client
-            .set(Database::Asic, &asic_key, &asic_value)
-            .await?;
+    /// Finish programming a VLAN whose ASIC_DB record is written
+    async fn complete_vlan(
+        &self,
+        entry: &VlanEntry,
+        record: &AsicVlanRecord,
+        intent: &SaiIntent,
+    ) -> Result<()> {
+        self.write_vlan_state(entry).await?;
+        self.intent_log.clear(intent).await?;
+        self.vlan_programmed(entry, record).await;
+        Ok(())
+    }
+
+    /// Publish a VLAN whose ASIC_DB record is written to STATE_DB
+    async fn write_vlan_state(&self, entry: &VlanEntry) -> Result<()> {
+        self.state_writer
+            .add_vlan(
+                &format!("Vlan{}", entry.vlanid),
+                entry.description.as_deref(),
+            )
+            .await
+    }
 
+    /// Program the members that waited for a now complete VLAN
+    async fn vlan_programmed(&self, entry: &VlanEntry, record: &AsicVlanRecord) {
         info!(
             "Programmed VLAN {} to hardware (OID: 0x{:x})",
-            vlan_id.get(),
-            vlan_oid
+            entry.vlanid, record.oid
         );
 
-        Ok(())
+        if let Some(vlan_id) = VlanId::new(entry.vlanid) {
+            self.replay_deferred_members(vlan_id).await;
+        }
+    }
+
+    /// Hold a member (`Vlan100:Ethernet0`) until its VLAN is programmed
+    fn defer_member(&self, key: &str) {
+        let vlan_id = key
+            .split_once(':')
+            .and_then(|(vlan_name, _)| VlanId::parse_from_name(vlan_name).ok());
+        match vlan_id {
+            Some(vlan_id) => {
+                self.deferred_members
+                    .entry(vlan_id)
+                    .or_default()
+                    .insert(key.to_string());
+            }
+            None => warn!("Malformed VLAN member key: {}", key),
+        }
+    }
+
+    /// Program the members of `port` deferred because it had no bridge port
+    ///
+    /// Only VLANs already programmed are replayed; the members of the others
+    /// wait for their VLAN.
+    async fn replay_port_members(&self, port: &str) {
+        let vlan_ids: Vec<VlanId> = self
+            .deferred_members
+            .iter()
+            .filter(|deferred| self.vlans.contains_key(deferred.key()))
+            .filter(|deferred| {
+                deferred
+                    .value()
+                    .iter()
+                    .any(|key| key.split_once(':').map(|(_, p)| p) == Some(port))
+            })
+            .map(|deferred| *deferred.key())
+            .collect();
+        for vlan_id in vlan_ids {
+            self.replay_deferred_members(vlan_id).await;
+        }
+    }
+
+    /// Program the members deferred until `vlan_id` was programmed
+    ///
+    /// Their entries are read again from APPL_DB, so members deleted in
+    /// the meantime are dropped.
+    async fn replay_deferred_members(&self, vlan_id: VlanId) {
+        let Some((_, keys)) = self.deferred_members.remove(&vlan_id) else {
+            return;
+        };
+        let keys: Vec<String> = keys.into_iter().collect();
+        let appl_keys: Vec<String> = keys
+            .iter()
+            .map(|key| self.table_keys.key(tables::VLAN_MEMBER_TABLE, key))
+            .collect();
+        let entries: Vec<Option<VlanMemberEntry>> = match self
+            .db_client
+            .get_many(home_db(tables::VLAN_MEMBER_TABLE), &appl_keys)
+            .await
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(
+                    "Failed to read the deferred members of VLAN {}: {}",
+                    vlan_id.get(),
+                    e
+                );
+                self.deferred_members
+                    .entry(vlan_id)
+                    .or_default()
+                    .extend(keys);
+                return;
+            }
+        };
+
+        let members = keys
+            .into_iter()
+            .zip(entries)
+            .filter_map(|(key, entry)| entry.map(|entry| (key, entry)))
+            .collect();
+        for (key, result) in self.program_members(members).await {
+            match result {
+                Ok(()) => debug!("Programmed deferred VLAN member {}", key),
+                Err(e) => warn!("Failed to program deferred VLAN member {}: {}", key, e),
+            }
+        }
     }
 
     /// Delete VLAN from hardware
     async fn delete_vlan(&self, vlan_name: &str) -> Result<()> {
-        // Parse VLAN ID from name (Vlan100 -> 100)
-        let vlan_id_str = vlan_name.strip_prefix("Vlan").unwrap_or(vlan_name);
-        let vlan_id_num = vlan_id_str
-            .parse::<u16>()
-            .map_err(|_| racoon_common::RacoonError::InvalidVlanId(0))?;
-        let vlan_id = VlanId::new(vlan_id_num)
-            .ok_or(racoon_common::RacoonError::InvalidVlanId(vlan_id_num))?;
+        let vlan_id = VlanId::parse_from_name(vlan_name)?;
+        let lock = self.vlan_lock(vlan_id);
+        let _guard = lock.lock().await;
 
         // Get state
         let state = match self.vlans.get(&vlan_id) {
@@ -153,77 +1086,909 @@ impl VlanSync {
             }
         };
 
+        // Members and the router interface reference the VLAN, so they go
+        // first. The members wait for the VLAN to come back, and are only
+        // programmed again if their APPL_DB entries are still there then
+        let prefix = format!("Vlan{}:", vlan_id.get());
+        let member_keys: Vec<String> = self
+            .members
+            .iter()
+            .filter(|member| member.key().starts_with(&prefix))
+            .map(|member| member.key().clone())
+            .collect();
+        for key in member_keys {
+            self.delete_member(&key).await?;
+            self.defer_member(&key);
+        }
+        self.remove_router_interface(vlan_id).await?;
+
         // Delete from SAI
         info!("Deleting VLAN {} from hardware", vlan_id.get());
-        self.vlan_api.remove_vlan(state.sai_oid)?;
+        let intent = SaiIntent::remove(
+            VLAN_OBJECT_TYPE,
+            &format!("Vlan{}", vlan_id.get()),
+            state.sai_oid,
+        );
+        self.intent_log.record(&intent).await?;
+        self.retry
+            .run_with_clock(&*self.clock, "remove VLAN", || {
+                self.vlan_api.remove_vlan(state.sai_oid)
+            })
+            .await?;
+        self.audit(&intent);
 
         // Remove from tracking
         self.vlans.remove(&vlan_id);
+        self.registry.unregister_vlan(vlan_id);
+        self.last_counters.remove(&vlan_id);
 
         // Remove from ASIC_DB
-        let asic_key = format!("ASIC_STATE:SAI_OBJECT_TYPE_VLAN:0x{:x}", state.sai_oid);
-        self.db_client.del(Database::Asic, &asic_key).await?;
+        let asic_key = self.table_keys.scoped(&format!(
+            "ASIC_STATE:SAI_OBJECT_TYPE_VLAN:0x{:x}",
+            state.sai_oid
+        ));
+        self.db_client
+            .del(home_db(tables::ASIC_STATE), &asic_key)
+            .await?;
+        self.state_writer
+            .remove_vlan(&format!("Vlan{}", vlan_id.get()))
+            .await?;
+        self.intent_log.clear(&intent).await?;
 
         info!("Deleted VLAN {} from hardware", vlan_id.get());
 
         Ok(())
     }
 
-    /// Handle database notification
-    pub async fn handle_notification(&self, channel: &str, message: &str) {
-        debug!("Received notification on {}: {}", channel, message);
+    /// Remove the router interface of a VLAN from SAI, if it has one
+    async fn remove_router_interface(&self, vlan_id: VlanId) -> Result<()> {
+        let Some(rif_oid) = self.router_interfaces.get(&vlan_id).map(|rif| *rif) else {
+            return Ok(());
+        };
+        let Some(rif_api) = &self.rif_api else {
+            return Err(racoon_common::RacoonError::Internal(format!(
+                "VLAN {} has router interface 0x{:x} but no router interface API",
+                vlan_id.get(),
+                rif_oid
+            )));
+        };
 
-        // Parse notification
-        let notification: serde_json::Value = match serde_json::from_str(message) {
-            Ok(v) => v,
-            Err(e) => {
-                error!("Failed to parse notification: {}", e);
-                return;
+        info!(
+            "Removing router interface 0x{:x} of VLAN {}",
+            rif_oid,
+            vlan_id.get()
+        );
+        self.retry
+            .run_with_clock(&*self.clock, "remove router interface", || {
+                rif_api.remove_router_interface(rif_oid)
+            })
+            .await?;
+        self.audit(&SaiIntent::remove(
+            RIF_OBJECT_TYPE,
+            &format!("Vlan{}", vlan_id.get()),
+            rif_oid,
+        ));
+        self.router_interfaces.remove(&vlan_id);
+        Ok(())
+    }
+
+    /// Release programmed objects before SAI is uninitialized
+    ///
+    /// With cleanup on shutdown every tracked member and VLAN is removed
+    /// from SAI, ASIC_DB and VLAN_STATE; otherwise they stay programmed for
+    /// a warm boot. `Drop` can't do this since removal is async, so main
+    /// calls it explicitly. Failures are logged and the rest are still
+    /// removed. Returns the number of VLANs removed.
+    pub async fn shutdown(&self) -> usize {
+        if !self.cleanup_on_shutdown {
+            info!(
+                "Leaving {} VLANs programmed for warm boot",
+                self.vlans.len()
+            );
+            return 0;
+        }
+
+        let members: Vec<String> = self.members.iter().map(|m| m.key().clone()).collect();
+        for key in members {
+            if let Err(e) = self.handle_member_notification(Operation::Del, &key).await {
+                warn!("Failed to remove VLAN member {} on shutdown: {}", key, e);
             }
-        };
+        }
 
-        let operation = notification["operation"].as_str().unwrap_or("");
-        let key = notification["key"].as_str().unwrap_or("");
+        let mut vlan_ids: Vec<VlanId> = self.vlans.iter().map(|v| *v.key()).collect();
+        vlan_ids.sort_by_key(|vlan_id| vlan_id.get());
+        let mut removed = 0;
+        for vlan_id in vlan_ids {
+            let vlan_name = format!("Vlan{}", vlan_id.get());
+            match self.delete_vlan(&vlan_name).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Failed to remove {} on shutdown: {}", vlan_name, e),
+            }
+        }
 
-        match operation {
-            "SET" | "CREATE" => {
-                if let Err(e) = self.create_vlan(key).await {
-                    error!("Failed to create VLAN {}: {}", key, e);
+        info!("Removed {} VLANs from hardware on shutdown", removed);
+        removed
+    }
+
+    /// Reconcile VLAN intents left behind by a crash
+    ///
+    /// An interrupted create is rolled back and an interrupted remove is
+    /// completed: either way the object is removed from SAI, ASIC_DB and
+    /// VLAN_STATE, and `sync_vlans` programs it again if APPL_DB still has
+    /// it. A create recorded before SAI returned an OID may still have
+    /// created the VLAN, so SAI is searched for it by VLAN id; an intent
+    /// whose VLAN can't be searched for is kept for the next start. Returns
+    /// the number of intents reconciled.
+    pub async fn reconcile_intents(&self) -> Result<usize> {
+        let intents = self.intent_log.pending(VLAN_OBJECT_TYPE).await?;
+
+        let mut reconciled = 0;
+        for intent in &intents {
+            let oid = match (intent.op, intent.oid) {
+                (_, Some(oid)) => Some(oid),
+                (IntentOp::Create, None) => match self.find_created_vlan(&intent.key) {
+                    Ok(oid) => oid,
+                    Err(e) => {
+                        warn!(
+                            "Keeping interrupted create of {}, can't search SAI for it: {}",
+                            intent.key, e
+                        );
+                        continue;
+                    }
+                },
+                (IntentOp::Remove, None) => None,
+            };
+
+            match oid {
+                Some(oid) => {
+                    let action = match intent.op {
+                        IntentOp::Create => "Rolling back",
+                        IntentOp::Remove => "Completing",
+                    };
+                    warn!(
+                        "{} interrupted {:?} of {} (OID: 0x{:x})",
+                        action, intent.op, intent.key, oid
+                    );
+                    // The object may already be gone, e.g. after a SAI reset
+                    match self.vlan_api.remove_vlan(oid) {
+                        Ok(()) => {
+                            self.audit(&SaiIntent::remove(VLAN_OBJECT_TYPE, &intent.key, oid))
+                        }
+                        Err(e) => warn!("Failed to remove {} from SAI: {}", intent.key, e),
+                    }
+                    let asic_key = self
+                        .table_keys
+                        .scoped(&format!("ASIC_STATE:SAI_OBJECT_TYPE_VLAN:0x{:x}", oid));
+                    self.db_client
+                        .del(home_db(tables::ASIC_STATE), &asic_key)
+                        .await?;
+                    self.state_writer.remove_vlan(&intent.key).await?;
                 }
-            }
-            "DEL" | "DELETE" => {
-                if let Err(e) = self.delete_vlan(key).await {
-                    error!("Failed to delete VLAN {}: {}", key, e);
+                None => {
+                    warn!(
+                        "Discarding {:?} intent of {}, SAI has no such VLAN",
+                        intent.op, intent.key
+                    );
                 }
             }
-            _ => {
-                warn!("Unknown operation: {}", operation);
-            }
+            self.intent_log.clear(intent).await?;
+            reconciled += 1;
         }
+
+        if reconciled > 0 {
+            info!("Reconciled {} interrupted VLAN operations", reconciled);
+        }
+        Ok(reconciled)
     }
 
-    /// Get statistics
-    pub fn stats(&self) -> VlanSyncStats {
+    /// OID of a VLAN an interrupted create may have left in SAI
+    fn find_created_vlan(&self, vlan_name: &str) -> Result<Option<SaiOid>> {
+        let vlan_id = VlanId::parse_from_name(vlan_name)?;
+        self.vlan_api.find_vlan(self.switch.switch_id, vlan_id)
+    }
+
+    /// Read back the VLAN records written to ASIC_DB
+    pub async fn asic_records(&self) -> Result<Vec<AsicVlanRecord>> {
+        let keys = self
+            .db_client
+            .keys(
+                home_db(tables::ASIC_STATE),
+                &self.table_keys.scoped("ASIC_STATE:SAI_OBJECT_TYPE_VLAN:*"),
+            )
+            .await?;
+        let records: Vec<Option<AsicVlanRecord>> = self
+            .db_client
+            .get_many(home_db(tables::ASIC_STATE), &keys)
+            .await?;
+
+        Ok(records.into_iter().flatten().collect())
+    }
+
+    /// Apply a single-field delta to the cached entry of a VLAN
+    ///
+    /// Returns the updated entry when the change affects hardware and the VLAN
+    /// must be reprogrammed. Fields such as the description only update the cache.
+    fn apply_field_delta(
+        &self,
+        vlan_name: &str,
+        field: &str,
+        value: serde_json::Value,
+    ) -> Result<Option<VlanEntry>> {
+        let vlan_id = VlanId::parse_from_name(vlan_name)?;
+
+        let mut state = self
+            .vlans
+            .get_mut(&vlan_id)
+            .ok_or(racoon_common::RacoonError::VlanNotFound(vlan_id.get()))?;
+
+        let mut view = serde_json::to_value(&state.entry)?;
+        view[field] = value;
+        let updated: VlanEntry = serde_json::from_value(view)?;
+
+        let reprogram = updated.vlanid != state.entry.vlanid;
+        state.entry = updated.clone();
+
+        debug!("Applied {} delta to cached VLAN {}", field, vlan_name);
+        Ok(reprogram.then_some(updated))
+    }
+
+    /// Handle a field-level delta, falling back to a full read when uncached
+    async fn handle_field_delta(&self, notification: &Notification) -> Result<()> {
+        let (Some(field), Some(value)) = (&notification.field, &notification.value) else {
+            warn!("Field delta for {} without field/value", notification.key);
+            return Ok(());
+        };
+
+        match self.apply_field_delta(&notification.key, field, value.clone()) {
+            Ok(None) if field == "description" => {
+                let vlan_id = VlanId::parse_from_name(&notification.key)?;
+                let description = self
+                    .vlans
+                    .get(&vlan_id)
+                    .and_then(|vlan| vlan.entry.description.clone());
+                self.state_writer
+                    .set_description(&notification.key, description.as_deref())
+                    .await
+            }
+            Ok(None) if field == "admin_status" => {
+                self.apply_rif_admin_state(VlanId::parse_from_name(&notification.key)?)
+            }
+            Ok(None) => Ok(()),
+            Ok(Some(entry)) => {
+                info!(
+                    "Field {} of {} changed in hardware, reprogramming",
+                    field, notification.key
+                );
+                self.delete_vlan(&notification.key).await?;
+                self.program_vlan(entry).await
+            }
+            Err(racoon_common::RacoonError::VlanNotFound(_)) => {
+                self.create_vlan(&notification.key).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Create a VLAN member in SAI with the entry's tagging mode
+    ///
+    /// An existing member whose last programmed tagging mode differs from
+    /// the entry is changed in place; an unchanged one makes no SAI call.
+    /// Returns the OID of a newly created member, or `None` if the member
+    /// already existed or the port has no bridge port yet.
+    fn program_member(
+        &self,
+        vlan_name: &str,
+        port: &str,
+        entry: &VlanMemberEntry,
+    ) -> Result<Option<SaiOid>> {
+        let key = format!("{}:{}", vlan_name, port);
+        if let Some(mut member) = self.members.get_mut(&key) {
+            if member.tagging_mode == entry.tagging_mode {
+                debug!("VLAN member {} unchanged, skipping SAI", key);
+                return Ok(None);
+            }
+            info!(
+                "Changing tagging mode of VLAN member {} from {} to {}",
+                key, member.tagging_mode, entry.tagging_mode
+            );
+            if self.set_member_tagging_mode(member.oid, entry.tagging_mode)? {
+                member.tagging_mode = entry.tagging_mode;
+                self.audit(AuditRecord::set(
+                    VLAN_MEMBER_OBJECT_TYPE,
+                    &key,
+                    member.oid,
+                    "tagging_mode",
+                    entry.tagging_mode,
+                ));
+            }
+            return Ok(None);
+        }
+
+        let Some((vlan_oid, bridge_port, shared_port)) = self.member_targets(vlan_name, port)?
+        else {
+            debug!("No bridge port for {}, not programming {}", port, key);
+            return Ok(None);
+        };
+
+        let member_oid = match self.vlan_api.create_vlan_member(
+            self.switch.switch_id,
+            vlan_oid,
+            bridge_port,
+            entry.tagging_mode.into(),
+        ) {
+            Ok(member_oid) => member_oid,
+            Err(e) => {
+                self.release_bridge_port(shared_port);
+                return Err(e);
+            }
+        };
+        self.track_member(
+            &key,
+            ProgrammedMember {
+                oid: member_oid,
+                vlan_oid,
+                bridge_port,
+                tagging_mode: entry.tagging_mode,
+                shared_port,
+            },
+        );
+        Ok(Some(member_oid))
+    }
+
+    /// Program VLAN member entries (`Vlan100:Ethernet0`), creating the new
+    /// members in one bulk SAI call
+    ///
+    /// Members already programmed are updated like in
+    /// [`program_member`](Self::program_member). Every handled member is
+    /// recorded in ASIC_DB and VLAN_STATE; a member whose port has no bridge
+    /// port yet is deferred until it has one and left out of the outcomes.
+    /// Returns the outcome of each other entry by member key.
+    async fn program_members(
+        &self,
+        members: Vec<(String, VlanMemberEntry)>,
+    ) -> Vec<(String, Result<()>)> {
+        let mut results = Vec::with_capacity(members.len());
+        let mut handled = Vec::new();
+        let mut pending = Vec::new();
+        for (key, entry) in members {
+            let Some((vlan_name, port)) = key.split_once(':') else {
+                warn!("Malformed VLAN member key: {}", key);
+                continue;
+            };
+            if self.members.contains_key(&key) {
+                match self.program_member(vlan_name, port, &entry) {
+                    Ok(_) => handled.push(key),
+                    Err(e) => results.push((key, Err(e))),
+                }
+                continue;
+            }
+            match self.member_targets(vlan_name, port) {
+                Ok(Some((vlan_oid, bridge_port, shared_port))) => pending.push((
+                    key,
+                    ProgrammedMember {
+                        oid: NULL_OID,
+                        vlan_oid,
+                        bridge_port,
+                        tagging_mode: entry.tagging_mode,
+                        shared_port,
+                    },
+                )),
+                Ok(None) => {
+                    debug!("Deferring {}: {} has no bridge port", key, port);
+                    self.defer_member(&key);
+                }
+                Err(e) => results.push((key, Err(e))),
+            }
+        }
+
+        let specs: Vec<(SaiOid, SaiOid, racoon_sai::vlan::VlanTaggingMode)> = pending
+            .iter()
+            .map(|(_, member)| {
+                (
+                    member.vlan_oid,
+                    member.bridge_port,
+                    member.tagging_mode.into(),
+                )
+            })
+            .collect();
+        let created = match self
+            .vlan_api
+            .create_vlan_members_bulk(self.switch.switch_id, &specs)
+        {
+            Ok(created) => created,
+            Err(e) => {
+                for (key, member) in pending {
+                    self.release_bridge_port(member.shared_port);
+                    results.push((key, Err(racoon_common::RacoonError::Sai(e.to_string()))));
+                }
+                return results;
+            }
+        };
+        for ((key, mut member), result) in pending.into_iter().zip(created) {
+            match result {
+                Ok(member_oid) => {
+                    member.oid = member_oid;
+                    self.track_member(&key, member);
+                    handled.push(key);
+                }
+                Err(e) => {
+                    self.release_bridge_port(member.shared_port);
+                    results.push((key, Err(e)));
+                }
+            }
+        }
+
+        for key in handled {
+            let result = self.record_member(&key).await;
+            results.push((key, result));
+        }
+        results
+    }
+
+    /// VLAN and bridge port a member of `port` in `vlan_name` is created on
+    ///
+    /// `None` if the port has no bridge port yet. A bridge port acquired
+    /// from the bridge port table comes with the port's OID, for
+    /// [`release_bridge_port`](Self::release_bridge_port).
+    fn member_targets(
+        &self,
+        vlan_name: &str,
+        port: &str,
+    ) -> Result<Option<(SaiOid, SaiOid, Option<SaiOid>)>> {
+        let vlan_id = VlanId::parse_from_name(vlan_name)?;
+        let vlan_oid = self
+            .vlans
+            .get(&vlan_id)
+            .map(|vlan| vlan.sai_oid)
+            .ok_or(racoon_common::RacoonError::VlanNotFound(vlan_id.get()))?;
+        Ok(self
+            .acquire_bridge_port(port)?
+            .map(|(bridge_port, shared_port)| (vlan_oid, bridge_port, shared_port)))
+    }
+
+    /// Audit and track a member SAI created
+    fn track_member(&self, key: &str, member: ProgrammedMember) {
+        self.audit(&SaiIntent::create(VLAN_MEMBER_OBJECT_TYPE, key).with_oid(member.oid));
+        self.members.insert(key.to_string(), member);
+        if let Some((_, port)) = key.split_once(':') {
+            self.registry.add_vlan_member(port);
+        }
+        info!(
+            "Created VLAN member {} ({}) in SAI with OID: 0x{:x}",
+            key, member.tagging_mode, member.oid
+        );
+    }
+
+    /// Change a member's tagging mode in SAI
+    ///
+    /// Returns false when the switch reports the mode as not settable; the
+    /// member then keeps its programmed mode.
+    fn set_member_tagging_mode(
+        &self,
+        member_oid: SaiOid,
+        tagging_mode: VlanTaggingMode,
+    ) -> Result<bool> {
+        match &self.switch_api {
+            Some(switch_api) => self.vlan_api.set_member_tagging_mode_if_supported(
+                switch_api,
+                self.switch.switch_id,
+                member_oid,
+                tagging_mode.into(),
+            ),
+            None => {
+                self.vlan_api
+                    .set_member_tagging_mode(member_oid, tagging_mode.into())?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Bridge port for a member of `port`
+    ///
+    /// A bridge port registered by another agent is used as is. Otherwise
+    /// one is acquired from the bridge port table, if the agent has one,
+    /// and the port's OID is returned with it for the release.
+    fn acquire_bridge_port(&self, port: &str) -> Result<Option<(SaiOid, Option<SaiOid>)>> {
+        if let Some(bridge_port) = self.registry.bridge_port_oid(port) {
+            return Ok(Some((bridge_port, None)));
+        }
+        let (Some((bridge_api, table)), Some(port_oid)) =
+            (&self.bridge_ports, self.registry.port_oid(port))
+        else {
+            return Ok(None);
+        };
+        let bridge_port = table.acquire(bridge_api, self.switch.switch_id, port_oid)?;
+        Ok(Some((bridge_port, Some(port_oid))))
+    }
+
+    /// Give back a bridge port taken by [`acquire_bridge_port`](Self::acquire_bridge_port)
+    fn release_bridge_port(&self, shared_port: Option<SaiOid>) {
+        if let (Some((bridge_api, table)), Some(port_oid)) = (&self.bridge_ports, shared_port)
+            && let Err(e) = table.release(bridge_api, port_oid)
+        {
+            // The bridge port is left in place for the port's next member
+            warn!("Failed to remove bridge port of 0x{:x}: {}", port_oid, e);
+        }
+    }
+
+    /// Remove a VLAN member from SAI, returning its OID if it was programmed
+    fn remove_member(&self, key: &str) -> Result<Option<SaiOid>> {
+        let Some(member) = self.members.get(key).map(|member| *member) else {
+            return Ok(None);
+        };
+        let member_oid = member.oid;
+        self.vlan_api.remove_vlan_member(member_oid)?;
+        self.audit(&SaiIntent::remove(VLAN_MEMBER_OBJECT_TYPE, key, member_oid));
+        self.members.remove(key);
+        if let Some((_, port)) = key.split_once(':') {
+            self.registry.remove_vlan_member(port);
+        }
+        self.release_bridge_port(member.shared_port);
+
+        info!("Removed VLAN member {} from SAI", key);
+        Ok(Some(member_oid))
+    }
+
+    /// ASIC_DB key and fields of a programmed VLAN member, in sairedis form
+    fn asic_member_record(&self, key: &str) -> Result<Option<(String, HashMap<String, String>)>> {
+        let Some(member) = self.members.get(key).map(|member| *member) else {
+            return Ok(None);
+        };
+        let attrs = VlanApi::member_attributes(
+            member.vlan_oid,
+            member.bridge_port,
+            member.tagging_mode.into(),
+        );
+        let fields = AsicAttributeSerializer::serialize(SaiObjectType::VlanMember, &attrs)?;
+        Ok(Some((
+            self.table_keys.scoped(&AsicAttributeSerializer::key(
+                SaiObjectType::VlanMember,
+                member.oid,
+            )),
+            fields,
+        )))
+    }
+
+    /// Write a member's ASIC_DB record, if it is programmed, and add it to
+    /// its VLAN's VLAN_STATE
+    async fn record_member(&self, key: &str) -> Result<()> {
+        let Some((vlan_name, port)) = key.split_once(':') else {
+            return Ok(());
+        };
+        if let Some((asic_key, fields)) = self.asic_member_record(key)? {
+            self.db_client
+                .hset_multiple(home_db(tables::ASIC_STATE), &asic_key, &fields)
+                .await?;
+        }
+        self.state_writer.add_member(vlan_name, port).await
+    }
+
+    /// Apply a VLAN member change (`Vlan100:Ethernet0`) to SAI and VLAN_STATE
+    ///
+    /// A field delta re-reads the entry, so a `tagging_mode` change from
+    /// [`DbClient::reassign_vlan_member`] is applied to the existing member.
+    async fn handle_member_notification(&self, operation: Operation, key: &str) -> Result<()> {
+        let Some((vlan_name, port)) = key.split_once(':') else {
+            warn!("Malformed VLAN member key: {}", key);
+            return Ok(());
+        };
+
+        match operation {
+            Operation::Set | Operation::Hset => {
+                let vlan_id = VlanId::parse_from_name(vlan_name)?;
+                if !self.vlans.contains_key(&vlan_id) {
+                    debug!("Deferring {}: its VLAN is not programmed", key);
+                    self.defer_member(key);
+                    return Ok(());
+                }
+                let appl_key = self.table_keys.key(tables::VLAN_MEMBER_TABLE, key);
+                let entry: VlanMemberEntry = self
+                    .db_client
+                    .get(home_db(tables::VLAN_MEMBER_TABLE), &appl_key)
+                    .await?;
+                self.program_member(vlan_name, port, &entry)?;
+                if !self.members.contains_key(key) {
+                    debug!("Deferring {}: {} has no bridge port", key, port);
+                    self.defer_member(key);
+                    return Ok(());
+                }
+                self.record_member(key).await
+            }
+            Operation::Del => {
+                if let Ok(vlan_id) = VlanId::parse_from_name(vlan_name)
+                    && let Some(mut deferred) = self.deferred_members.get_mut(&vlan_id)
+                {
+                    deferred.remove(key);
+                }
+                self.delete_member(key).await
+            }
+        }
+    }
+
+    /// Remove a member (`Vlan100:Ethernet0`) from SAI, ASIC_DB and
+    /// VLAN_STATE
+    async fn delete_member(&self, key: &str) -> Result<()> {
+        let Some((vlan_name, port)) = key.split_once(':') else {
+            return Ok(());
+        };
+        if let Some(member_oid) = self.remove_member(key)? {
+            let asic_key = self.table_keys.scoped(&AsicAttributeSerializer::key(
+                SaiObjectType::VlanMember,
+                member_oid,
+            ));
+            self.db_client
+                .del(home_db(tables::ASIC_STATE), &asic_key)
+                .await?;
+        }
+        self.state_writer.remove_member(vlan_name, port).await
+    }
+
+    /// Publish the link training and serdes state of a port whose oper
+    /// status changed
+    ///
+    /// The state only explains why a port is down, so failing to read it
+    /// does not fail the notification.
+    async fn publish_serdes_state(&self, port: &str) {
+        let Some(port_api) = &self.port_api else {
+            return;
+        };
+        let Some(port_oid) = self.registry.port_oid(port) else {
+            debug!("Port {} has no OID, not publishing its serdes state", port);
+            return;
+        };
+        if let Err(e) =
+            publish_port_serdes_state(&self.db_client, &self.table_keys, port_api, port, port_oid)
+                .await
+        {
+            warn!("Failed to publish serdes state of {}: {}", port, e);
+        }
+    }
+
+    /// Handle database notification
+    #[instrument(skip(self, message))]
+    pub async fn handle_notification(&self, channel: &str, message: &str) {
+        debug!("Received notification on {}: {}", channel, message);
+        if self.startup.hold(channel, message) {
+            debug!(
+                "Holding notification on {} until the startup scan ends",
+                channel
+            );
+            return;
+        }
+        self.process_notification(channel, message).await;
+    }
+
+    async fn process_notification(&self, channel: &str, message: &str) {
+        let channel = self.switch.local_channel(channel);
+
+        // Parse notification
+        let notification: serde_json::Value = match serde_json::from_str(message) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to parse notification: {}", e);
+                self.ignored.record(message);
+                return;
+            }
+        };
+
+        let operation_name = notification["operation"].as_str().unwrap_or("");
+        let Some(operation) = Operation::parse(operation_name) else {
+            self.ignored.record(message);
+            return;
+        };
+        let Some(key) = notification["key"].as_str() else {
+            warn!("Notification on {} without key", channel);
+            self.ignored.record(message);
+            return;
+        };
+        let received = Instant::now();
+
+        if channel == PORT_STATE_CHANNEL {
+            let result = self.state_writer.port_oper_status_changed(key).await;
+            if let Err(e) = &result {
+                error!("Failed to update VLAN state for port {}: {}", key, e);
+            }
+            self.publish_serdes_state(key).await;
+            // Another agent may have created the port's bridge port meanwhile
+            self.replay_port_members(key).await;
+            self.events.record(operation_name, key, &result);
+            return;
+        }
+
+        if channel == tables::VLAN_MEMBER_TABLE {
+            let result = self.handle_member_notification(operation, key).await;
+            if let Err(e) = &result {
+                error!("Failed to update VLAN member {}: {}", key, e);
+            }
+            self.events.record(operation_name, key, &result);
+            return;
+        }
+
+        let result = match operation {
+            Operation::Set => {
+                let result = self.create_vlan(key).await;
+                if let Err(e) = &result {
+                    error!("Failed to create VLAN {}: {}", key, e);
+                }
+                result
+            }
+            Operation::Del => {
+                let result = self.delete_vlan(key).await;
+                if let Err(e) = &result {
+                    error!("Failed to delete VLAN {}: {}", key, e);
+                }
+                result
+            }
+            Operation::Hset => match serde_json::from_value::<Notification>(notification.clone()) {
+                Ok(delta) => {
+                    let result = self.handle_field_delta(&delta).await;
+                    if let Err(e) = &result {
+                        error!("Failed to apply delta to VLAN {}: {}", key, e);
+                    }
+                    result
+                }
+                Err(e) => {
+                    error!("Malformed field delta for {}: {}", key, e);
+                    Err(racoon_common::RacoonError::from(e))
+                }
+            },
+        };
+        self.events.record(operation_name, key, &result);
+
+        self.record_latency(key, received, notification["ts"].as_u64());
+    }
+
+    /// Record how long a notification took to reach hardware
+    ///
+    /// With a producer timestamp (`ts`, Unix milliseconds) the latency is
+    /// end-to-end; without one only local processing time is measured.
+    fn record_latency(&self, key: &str, received: Instant, produced_ms: Option<u64>) {
+        let local = received.elapsed();
+        let latency = match produced_ms {
+            Some(ts) => Duration::from_millis(unix_millis().saturating_sub(ts)).max(local),
+            None => local,
+        };
+
+        self.latency.observe(latency);
+        debug!(
+            "Notification for {} processed: local {:?}, end-to-end {:?}",
+            key,
+            local,
+            produced_ms.map(|_| latency)
+        );
+    }
+
+    /// Read counters of every programmed VLAN
+    ///
+    /// VLANs whose counters can't be read (e.g. the SAI has no VLAN stats)
+    /// are skipped.
+    pub fn collect_counters(&self) -> Vec<(VlanId, Counters)> {
+        let counter_ids: Vec<sai_vlan_stat_t> = VLAN_COUNTERS.iter().map(|(id, _)| *id).collect();
+
+        self.vlans
+            .iter()
+            .filter_map(|vlan| {
+                let values = match self.vlan_api.get_stats(vlan.sai_oid, &counter_ids) {
+                    Ok(values) => values,
+                    Err(e) => {
+                        debug!("No counters for VLAN {}: {}", vlan.key(), e);
+                        return None;
+                    }
+                };
+
+                let values = VLAN_COUNTERS
+                    .iter()
+                    .zip(values)
+                    .map(|((_, name), value)| (name.to_string(), value))
+                    .collect();
+                Some((*vlan.key(), Counters { values }))
+            })
+            .collect()
+    }
+
+    /// Write VLAN counters to COUNTERS_DB, and their per-second rates since
+    /// the previous poll to RATES
+    pub async fn poll_counters(&self) -> Result<()> {
+        for (vlan_id, counters) in self.collect_counters() {
+            let now = Instant::now();
+            let fields = counters
+                .values
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_string()))
+                .collect();
+            self.db_client
+                .hset_multiple(
+                    home_db(tables::COUNTERS),
+                    &self
+                        .table_keys
+                        .scoped(&format!("{}:Vlan{}", tables::COUNTERS, vlan_id)),
+                    &fields,
+                )
+                .await?;
+
+            let prev = self.last_counters.insert(vlan_id, (now, counters.clone()));
+            if let Some((polled, prev)) = prev {
+                let rates = counters
+                    .diff(&prev, now - polled)
+                    .into_iter()
+                    .map(|(name, rate)| (name, rate.to_string()))
+                    .collect();
+                self.db_client
+                    .hset_multiple(
+                        home_db(tables::RATES),
+                        &self
+                            .table_keys
+                            .scoped(&format!("{}:Vlan{}", tables::RATES, vlan_id)),
+                        &rates,
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Outcome of the last processed notifications, oldest first
+    pub fn recent_events(&self) -> Vec<ProcessedEvent> {
+        self.events.recent()
+    }
+
+    /// Get statistics
+    pub fn stats(&self) -> VlanSyncStats {
         VlanSyncStats {
             vlan_count: self.vlans.len(),
+            latency: self.latency.snapshot(),
+            ignored: self.ignored.snapshot(),
         }
     }
 }
 
+/// Reject an entry whose `vlanid` is not the VLAN its key names
+///
+/// A producer writing `Vlan100` with `vlanid: 200` would otherwise program
+/// the wrong VLAN.
+fn check_vlan_key(vlan_name: &str, entry: &VlanEntry) -> Result<()> {
+    let vlan_id = VlanId::parse_from_name(vlan_name)?;
+    if vlan_id.get() != entry.vlanid {
+        error!(
+            "APPL_DB entry {} has vlanid {}, skipping it",
+            vlan_name, entry.vlanid
+        );
+        return Err(racoon_common::RacoonError::VlanIdMismatch {
+            name: vlan_name.to_string(),
+            vlanid: entry.vlanid,
+        });
+    }
+    Ok(())
+}
+
 /// VLAN sync statistics
 #[derive(Debug, Clone, Serialize)]
 pub struct VlanSyncStats {
     pub vlan_count: usize,
+    pub latency: LatencySnapshot,
+    pub ignored: IgnoredSnapshot,
 }
 
 /// Database subscriber implementation for VlanSync
 pub struct VlanSyncSubscriber {
     vlan_sync: Arc<VlanSync>,
+    /// Channels confirmed so far
+    subscribed: watch::Sender<usize>,
 }
 
 impl VlanSyncSubscriber {
     pub fn new(vlan_sync: Arc<VlanSync>) -> Self {
-        Self { vlan_sync }
+        Self {
+            vlan_sync,
+            subscribed: watch::Sender::new(0),
+        }
+    }
+
+    /// Wait until `channels` subscriptions have been confirmed
+    pub async fn wait_subscribed(&self, channels: usize) {
+        let mut subscribed = self.subscribed.subscribe();
+        // The sender lives in self, so the channel can't close while we wait
+        let _ = subscribed.wait_for(|count| *count >= channels).await;
     }
 }
 
@@ -235,5 +2000,1555 @@ impl DbSubscriber for VlanSyncSubscriber {
 
     async fn on_subscribe(&self, channel: String) {
         info!("VlanSync subscribed to channel: {}", channel);
+        self.subscribed.send_modify(|count| *count += 1);
+    }
+
+    async fn on_resubscribe(&self) {
+        // Notifications may have been lost while the subscription was wedged
+        if let Err(e) = self.vlan_sync.sync_vlans().await {
+            warn!("VlanSync resync after resubscribe failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port_serdes::PortSerdesState;
+    use crate::vlan_state::VlanState;
+    use racoon_db_client::Database;
+
+    async fn test_vlan_sync() -> VlanSync {
+        // DbClient connects lazily, so paths that skip the DB need no server
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        VlanSync::new(
+            db_client,
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_notification_latency_recorded() {
+        let vlan_sync = test_vlan_sync().await;
+
+        // Deleting an untracked VLAN completes without touching the DB
+        let message = serde_json::json!({
+            "operation": "DEL",
+            "key": "Vlan100",
+            "ts": unix_millis()
+        });
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &message.to_string())
+            .await;
+
+        // Missing timestamp still records local processing time
+        let message = serde_json::json!({ "operation": "DEL", "key": "Vlan200" });
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &message.to_string())
+            .await;
+
+        assert_eq!(vlan_sync.stats().latency.count, 2);
+    }
+
+    #[test]
+    fn test_vlan_key_must_match_vlanid() {
+        let entry = |vlanid| VlanEntry {
+            vlanid,
+            description: None,
+            admin_status: None,
+            mtu: None,
+        };
+        assert!(check_vlan_key("Vlan100", &entry(100)).is_ok());
+        assert!(matches!(
+            check_vlan_key("Vlan100", &entry(200)),
+            Err(racoon_common::RacoonError::VlanIdMismatch { vlanid: 200, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_entry_not_programmed() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 200}),
+            )
+            .await
+            .unwrap();
+        racoon_sai::mock::take_calls();
+
+        let message = serde_json::json!({ "operation": "SET", "key": "Vlan100" });
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &message.to_string())
+            .await;
+        vlan_sync.sync_vlans().await.unwrap();
+
+        assert!(racoon_sai::mock::take_calls().is_empty());
+        assert_eq!(vlan_sync.stats().vlan_count, 0);
+        assert!(vlan_sync.recent_events()[0].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_recent_events_capped() {
+        let vlan_sync = test_vlan_sync().await.with_event_capacity(3);
+        let delete =
+            |vlan: &str| serde_json::json!({ "operation": "DEL", "key": vlan }).to_string();
+
+        // Deleting untracked VLANs completes without touching the DB
+        for vlan in ["Vlan100", "Vlan200", "Vlan300"] {
+            vlan_sync
+                .handle_notification("VLAN_TABLE", &delete(vlan))
+                .await;
+        }
+        let events = vlan_sync.recent_events();
+        assert_eq!(events.len(), 3);
+        assert!(
+            events
+                .iter()
+                .all(|event| event.operation == "DEL" && event.error.is_none())
+        );
+
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &delete("Vlan400"))
+            .await;
+        let keys: Vec<String> = vlan_sync
+            .recent_events()
+            .into_iter()
+            .map(|event| event.key)
+            .collect();
+        assert_eq!(keys, vec!["Vlan200", "Vlan300", "Vlan400"]);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_notifications_counted() {
+        let vlan_sync = test_vlan_sync().await;
+
+        for message in [
+            "not json",
+            r#"{"operation": "FLUSH", "key": "Vlan100"}"#,
+            r#"{"operation": "SET"}"#,
+        ] {
+            vlan_sync.handle_notification("VLAN_TABLE", message).await;
+        }
+
+        let stats = vlan_sync.stats();
+        assert_eq!(stats.ignored.count, 3);
+        assert_eq!(
+            stats.ignored.last_payload.as_deref(),
+            Some(r#"{"operation": "SET"}"#)
+        );
+        // Nothing reached the handlers
+        assert_eq!(stats.latency.count, 0);
+    }
+
+    #[test]
+    fn test_asic_vlan_record_round_trip() {
+        let record = AsicVlanRecord {
+            vlanid: 100,
+            oid: 0x26000000000001,
+        };
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json, r#"{"vlanid":100,"oid":"0x26000000000001"}"#);
+        assert_eq!(
+            serde_json::from_str::<AsicVlanRecord>(&json).unwrap(),
+            record
+        );
+        assert_eq!(
+            record.key(),
+            "ASIC_STATE:SAI_OBJECT_TYPE_VLAN:0x26000000000001"
+        );
+
+        // OIDs must be 0x-prefixed hex strings
+        assert!(serde_json::from_str::<AsicVlanRecord>(r#"{"vlanid":1,"oid":"26"}"#).is_err());
+        assert!(serde_json::from_str::<AsicVlanRecord>(r#"{"vlanid":1,"oid":38}"#).is_err());
+        assert!(serde_json::from_str::<AsicVlanRecord>(r#"{"vlanid":1,"oid":"0xzz"}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_collect_counters() {
+        let sync = test_vlan_sync().await;
+        let entry = VlanEntry {
+            vlanid: 300,
+            description: None,
+            admin_status: None,
+            mtu: None,
+        };
+        let vlan_oid = sync
+            .vlan_api
+            .create_vlan(0, VlanId::new(300).unwrap())
+            .unwrap();
+        sync.vlans.insert(
+            VlanId::new(300).unwrap(),
+            ProgrammedVlan {
+                _vlan_id: VlanId::new(300).unwrap(),
+                sai_oid: vlan_oid,
+                entry,
+            },
+        );
+
+        let counters = sync.collect_counters();
+        assert_eq!(counters.len(), 1);
+        let (vlan_id, fields) = &counters[0];
+        assert_eq!(vlan_id.get(), 300);
+        assert_eq!(fields.values.len(), VLAN_COUNTERS.len());
+        assert_eq!(
+            fields.values["SAI_VLAN_STAT_OUT_OCTETS"],
+            racoon_sai::mock::stat_value(vlan_oid, SAI_VLAN_STAT_OUT_OCTETS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_field_delta_updates_cache() {
+        let vlan_sync = test_vlan_sync().await;
+        let vlan_id = VlanId::new(100).unwrap();
+        vlan_sync.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: VlanEntry {
+                    vlanid: 100,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                },
+            },
+        );
+
+        // No server; the VLAN_STATE write fails fast instead of reconnecting
+        vlan_sync.db_client.shutdown().await;
+
+        let delta = Notification::field_delta("VLAN_TABLE", "Vlan100", "description", "uplink");
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &serde_json::to_string(&delta).unwrap())
+            .await;
+
+        let state = vlan_sync.vlans.get(&vlan_id).unwrap();
+        assert_eq!(state.entry.description.as_deref(), Some("uplink"));
+        assert_eq!(state.sai_oid, 0x26000000000001);
+        // Description is not programmed in hardware
+        assert!(racoon_sai::mock::take_calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_router_interface_removed_before_vlan() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_router_interface_api(Arc::new(racoon_sai::mock::router_interface_api()));
+        let vlan_id = VlanId::new(100).unwrap();
+        vlan_sync
+            .program_vlan(VlanEntry {
+                vlanid: 100,
+                description: None,
+                admin_status: None,
+                mtu: None,
+            })
+            .await
+            .unwrap();
+        let rif = 0x6000000000001;
+        vlan_sync.set_router_interface(vlan_id, rif).unwrap();
+        racoon_sai::mock::take_calls();
+
+        vlan_sync.delete_vlan("Vlan100").await.unwrap();
+
+        let removes: Vec<&str> = racoon_sai::mock::take_calls()
+            .iter()
+            .map(|call| call.function)
+            .filter(|function| function.starts_with("remove_"))
+            .collect();
+        assert_eq!(removes, ["remove_router_interface", "remove_vlan"]);
+        assert!(vlan_sync.router_interfaces.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_admin_status_sets_rif_state() {
+        let vlan_sync = test_vlan_sync()
+            .await
+            .with_router_interface_api(Arc::new(racoon_sai::mock::router_interface_api()));
+        let vlan_id = VlanId::new(100).unwrap();
+        vlan_sync.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: VlanEntry {
+                    vlanid: 100,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                },
+            },
+        );
+        let rif = 0x6000000000001;
+        vlan_sync.set_router_interface(vlan_id, rif).unwrap();
+        racoon_sai::mock::take_calls();
+
+        let toggle = |status: &str| {
+            let delta = Notification::field_delta("VLAN_TABLE", "Vlan100", "admin_status", status);
+            serde_json::to_string(&delta).unwrap()
+        };
+        let rif_states = || {
+            racoon_sai::mock::take_calls()
+                .into_iter()
+                .filter(|call| call.function == "set_router_interface_attribute")
+                .inspect(|call| assert_eq!(call.oid, rif))
+                .flat_map(|call| call.attrs)
+                .collect::<Vec<_>>()
+        };
+
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &toggle("down"))
+            .await;
+        assert_eq!(
+            vlan_sync.vlans.get(&vlan_id).unwrap().entry.admin_status,
+            Some(PortAdminStatus::Down)
+        );
+        assert_eq!(
+            rif_states(),
+            [
+                (racoon_sai::SAI_ROUTER_INTERFACE_ATTR_ADMIN_V4_STATE, 0),
+                (racoon_sai::SAI_ROUTER_INTERFACE_ATTR_ADMIN_V6_STATE, 0),
+            ]
+        );
+
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &toggle("up"))
+            .await;
+        assert_eq!(
+            rif_states(),
+            [
+                (racoon_sai::SAI_ROUTER_INTERFACE_ATTR_ADMIN_V4_STATE, 1),
+                (racoon_sai::SAI_ROUTER_INTERFACE_ATTR_ADMIN_V6_STATE, 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mtu_set_on_rif_only_when_settable() {
+        let vlan_sync = test_vlan_sync()
+            .await
+            .with_router_interface_api(Arc::new(racoon_sai::mock::router_interface_api()))
+            .with_switch_api(Arc::new(racoon_sai::mock::switch_api()));
+        let vlan_id = VlanId::new(100).unwrap();
+        let entry = |mtu| VlanEntry {
+            vlanid: 100,
+            description: None,
+            admin_status: None,
+            mtu,
+        };
+        vlan_sync.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: entry(None),
+            },
+        );
+        let rif = 0x6000000000001;
+        vlan_sync.set_router_interface(vlan_id, rif).unwrap();
+        let mtu_sets = || {
+            racoon_sai::mock::take_calls()
+                .into_iter()
+                .filter(|call| call.function == "set_router_interface_attribute")
+                .flat_map(|call| call.attrs)
+                .filter(|(attr_id, _)| *attr_id == racoon_sai::SAI_ROUTER_INTERFACE_ATTR_MTU)
+                .map(|(_, mtu)| mtu)
+                .collect::<Vec<_>>()
+        };
+        assert!(mtu_sets().is_empty());
+
+        vlan_sync
+            .create_or_update_vlan(&entry(Some(9100)))
+            .await
+            .unwrap();
+        assert_eq!(mtu_sets(), [9100]);
+
+        // A platform with a create-only MTU never gets the set
+        let switch_api = SwitchApi::new(Box::leak(Box::new(racoon_sai::mock::switch_api_table())))
+            .with_capability_query(Some(query_create_only));
+        let vlan_sync = vlan_sync.with_switch_api(Arc::new(switch_api));
+        vlan_sync
+            .create_or_update_vlan(&entry(Some(1500)))
+            .await
+            .unwrap();
+        assert!(mtu_sets().is_empty());
+        assert_eq!(vlan_sync.vlans.get(&vlan_id).unwrap().entry.mtu, Some(1500));
+    }
+
+    #[tokio::test]
+    async fn test_priority_tagged_member() {
+        let vlan_sync = test_vlan_sync().await;
+        let vlan_id = VlanId::new(100).unwrap();
+        vlan_sync.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: VlanEntry {
+                    vlanid: 100,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                },
+            },
+        );
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+        racoon_sai::mock::take_calls();
+
+        let entry: VlanMemberEntry =
+            serde_json::from_str(r#"{"tagging_mode": "priority_tagged"}"#).unwrap();
+        let member = vlan_sync
+            .program_member("Vlan100", "Ethernet0", &entry)
+            .unwrap()
+            .unwrap();
+
+        let calls = racoon_sai::mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "create_vlan_member");
+        assert_eq!(calls[0].oid, member);
+        assert!(calls[0].attrs.contains(&(
+            racoon_sai::SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+            racoon_sai::SAI_VLAN_TAGGING_MODE_PRIORITY_TAGGED as u64
+        )));
+
+        // Programming the same mode again is skipped
+        assert_eq!(
+            vlan_sync
+                .program_member("Vlan100", "Ethernet0", &entry)
+                .unwrap(),
+            None
+        );
+        assert!(racoon_sai::mock::take_calls().is_empty());
+
+        // A changed mode is applied to the existing member
+        let tagged = VlanMemberEntry {
+            tagging_mode: VlanTaggingMode::Tagged,
+        };
+        assert_eq!(
+            vlan_sync
+                .program_member("Vlan100", "Ethernet0", &tagged)
+                .unwrap(),
+            None
+        );
+        let calls = racoon_sai::mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "set_vlan_member_attribute");
+        assert_eq!(
+            calls[0].attrs,
+            vec![(
+                racoon_sai::SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+                racoon_sai::SAI_VLAN_TAGGING_MODE_TAGGED as u64
+            )]
+        );
+
+        // ASIC_DB carries the full attribute set of the current state
+        let (asic_key, fields) = vlan_sync
+            .asic_member_record("Vlan100:Ethernet0")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            asic_key,
+            format!("ASIC_STATE:SAI_OBJECT_TYPE_VLAN_MEMBER:oid:0x{:x}", member)
+        );
+        assert_eq!(
+            fields["SAI_VLAN_MEMBER_ATTR_VLAN_ID"],
+            "oid:0x26000000000001"
+        );
+        assert_eq!(
+            fields["SAI_VLAN_MEMBER_ATTR_BRIDGE_PORT_ID"],
+            "oid:0x3a000000000001"
+        );
+        assert_eq!(
+            fields["SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE"],
+            "SAI_VLAN_TAGGING_MODE_TAGGED"
+        );
+
+        // Removal releases the SAI member
+        assert_eq!(
+            vlan_sync.remove_member("Vlan100:Ethernet0").unwrap(),
+            Some(member)
+        );
+        let calls = racoon_sai::mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "remove_vlan_member");
+        assert_eq!(calls[0].oid, member);
+        assert!(vlan_sync.members.is_empty());
+    }
+
+    /// Mock capability query reporting every attribute as create-only
+    unsafe extern "C" fn query_create_only(
+        _switch_id: racoon_sai::sai_object_id_t,
+        _object_type: racoon_sai::sai_object_type_t,
+        _attr_id: racoon_sai::sai_attr_id_t,
+        attr_capability: *mut racoon_sai::sai_attr_capability_t,
+    ) -> racoon_sai::sai_status_t {
+        unsafe {
+            *attr_capability = racoon_sai::sai_attr_capability_t {
+                create_implemented: true,
+                set_implemented: false,
+                get_implemented: true,
+            };
+        }
+        racoon_sai::SAI_STATUS_SUCCESS as racoon_sai::sai_status_t
+    }
+
+    #[tokio::test]
+    async fn test_unsettable_tagging_mode_kept() {
+        let switch_api = SwitchApi::new(Box::leak(Box::new(racoon_sai::mock::switch_api_table())))
+            .with_capability_query(Some(query_create_only));
+        let vlan_sync = test_vlan_sync().await.with_switch_api(Arc::new(switch_api));
+        let vlan_id = VlanId::new(100).unwrap();
+        vlan_sync.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: VlanEntry {
+                    vlanid: 100,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                },
+            },
+        );
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+        let untagged = VlanMemberEntry {
+            tagging_mode: VlanTaggingMode::Untagged,
+        };
+        vlan_sync
+            .program_member("Vlan100", "Ethernet0", &untagged)
+            .unwrap()
+            .unwrap();
+        racoon_sai::mock::take_calls();
+
+        let tagged = VlanMemberEntry {
+            tagging_mode: VlanTaggingMode::Tagged,
+        };
+        vlan_sync
+            .program_member("Vlan100", "Ethernet0", &tagged)
+            .unwrap();
+
+        // The set never reaches SAI and the programmed mode is kept
+        let functions: Vec<&str> = racoon_sai::mock::take_calls()
+            .iter()
+            .map(|call| call.function)
+            .collect();
+        assert!(!functions.contains(&"set_vlan_member_attribute"));
+        let (_, fields) = vlan_sync
+            .asic_member_record("Vlan100:Ethernet0")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            fields["SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE"],
+            "SAI_VLAN_TAGGING_MODE_UNTAGGED"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vlan_past_limit_rejected() {
+        let vlan_sync = test_vlan_sync().await.with_resource_limits(ResourceLimits {
+            max_vlans: Some(1),
+            max_fdb_entries: None,
+        });
+        let vlan_id = VlanId::new(100).unwrap();
+        vlan_sync.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: VlanEntry {
+                    vlanid: 100,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                },
+            },
+        );
+        racoon_sai::mock::take_calls();
+
+        // Re-programming the tracked VLAN is not an addition
+        let entry = |vlanid| VlanEntry {
+            vlanid,
+            description: None,
+            admin_status: None,
+            mtu: None,
+        };
+        vlan_sync.program_vlan(entry(100)).await.unwrap();
+
+        let err = vlan_sync.program_vlan(entry(200)).await.unwrap_err();
+        assert!(matches!(
+            err,
+            racoon_common::RacoonError::ResourceExhausted { limit: 1, .. }
+        ));
+        assert!(racoon_sai::mock::take_calls().is_empty());
+        assert_eq!(vlan_sync.stats().vlan_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_switch_contexts_track_vlans_independently() {
+        let db_client = Arc::new(DbClient::new("redis://127.0.0.1:6379").await.unwrap());
+        let vlan_api = Arc::new(racoon_sai::mock::vlan_api());
+        let asic0 = VlanSync::new(
+            db_client.clone(),
+            vlan_api.clone(),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        let asic1 = VlanSync::new(db_client, vlan_api, SwitchContext::new(0x21000000000001, 1));
+
+        let vlan_id = VlanId::new(100).unwrap();
+        asic0.vlans.insert(
+            vlan_id,
+            ProgrammedVlan {
+                _vlan_id: vlan_id,
+                sai_oid: 0x26000000000001,
+                entry: VlanEntry {
+                    vlanid: 100,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                },
+            },
+        );
+        asic0.set_bridge_port("Ethernet0", 0x3a000000000001).await;
+        asic1.set_bridge_port("Ethernet0", 0x3a000000000002).await;
+        racoon_sai::mock::take_calls();
+
+        let entry = VlanMemberEntry {
+            tagging_mode: VlanTaggingMode::Untagged,
+        };
+        asic0
+            .program_member("Vlan100", "Ethernet0", &entry)
+            .unwrap()
+            .unwrap();
+        let calls = racoon_sai::mock::take_calls();
+        assert_eq!(calls[0].switch_id, 0x21000000000000);
+
+        // The VLAN programmed on asic0 does not exist on asic1
+        assert!(matches!(
+            asic1.program_member("Vlan100", "Ethernet0", &entry),
+            Err(racoon_common::RacoonError::VlanNotFound(100))
+        ));
+        assert!(racoon_sai::mock::take_calls().is_empty());
+        assert_eq!(asic0.stats().vlan_count, 1);
+        assert_eq!(asic1.stats().vlan_count, 0);
+        assert_eq!(asic1.members.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_switch_namespaces_keep_records_apart() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_api = Arc::new(racoon_sai::mock::vlan_api());
+        let asic = |index| {
+            let switch = SwitchContext::new(0x21000000000000 + index as u64, index);
+            VlanSync::new(db_client.clone(), vlan_api.clone(), switch)
+                .with_table_keys(switch.table_keys(&TableKeys::default()))
+        };
+        let (asic0, asic1) = (asic(0), asic(1));
+        for namespace in ["asic0", "asic1"] {
+            db_client
+                .set(
+                    Database::Appl,
+                    &format!("{}:VLAN_TABLE:Vlan100", namespace),
+                    &serde_json::json!({"vlanid": 100}),
+                )
+                .await
+                .unwrap();
+        }
+
+        asic0.sync_vlans().await.unwrap();
+        asic1.sync_vlans().await.unwrap();
+        assert_eq!(asic0.asic_records().await.unwrap().len(), 1);
+        assert_eq!(asic1.asic_records().await.unwrap().len(), 1);
+
+        // Removing the VLAN from asic0 leaves asic1's records alone
+        asic0.delete_vlan("Vlan100").await.unwrap();
+        assert!(asic0.asic_records().await.unwrap().is_empty());
+        assert_eq!(asic1.asic_records().await.unwrap().len(), 1);
+        let state = |namespace: &str| {
+            let key = format!("{}:{}", namespace, VlanStateWriter::key("Vlan100"));
+            let db_client = db_client.clone();
+            async move {
+                db_client
+                    .get::<VlanState>(Database::State, &key)
+                    .await
+                    .is_ok()
+            }
+        };
+        assert!(!state("asic0").await);
+        assert!(state("asic1").await);
+
+        // Nothing of asic1's is mistaken for an interrupted operation
+        assert_eq!(asic1.reconcile_intents().await.unwrap(), 0);
+        assert_eq!(asic1.stats().vlan_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cleanup_removes_tracked_vlans() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        for vlanid in [100, 200] {
+            vlan_sync
+                .program_vlan(VlanEntry {
+                    vlanid,
+                    description: None,
+                    admin_status: None,
+                    mtu: None,
+                })
+                .await
+                .unwrap();
+        }
+        let programmed: Vec<SaiOid> = racoon_sai::mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "create_vlan")
+            .map(|call| call.oid)
+            .collect();
+
+        // Warm boot policy leaves everything programmed
+        assert_eq!(vlan_sync.shutdown().await, 0);
+        assert!(racoon_sai::mock::take_calls().is_empty());
+        assert_eq!(vlan_sync.asic_records().await.unwrap().len(), 2);
+
+        let vlan_sync = vlan_sync.with_cleanup_on_shutdown(true);
+        assert_eq!(vlan_sync.shutdown().await, 2);
+        let removed: Vec<SaiOid> = racoon_sai::mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "remove_vlan")
+            .map(|call| call.oid)
+            .collect();
+        assert_eq!(removed, programmed);
+        assert_eq!(vlan_sync.stats().vlan_count, 0);
+        assert!(vlan_sync.asic_records().await.unwrap().is_empty());
+    }
+
+    /// Mock `create_vlan` that rejects VLAN 200
+    unsafe extern "C" fn create_vlan_rejecting_200(
+        vlan_oid: *mut racoon_sai::sai_object_id_t,
+        switch_id: racoon_sai::sai_object_id_t,
+        attr_count: u32,
+        attr_list: *const racoon_sai::sai_attribute_t,
+    ) -> racoon_sai::sai_status_t {
+        let attrs = unsafe { std::slice::from_raw_parts(attr_list, attr_count as usize) };
+        let rejected = attrs.iter().any(|attr| {
+            attr.id == racoon_sai::SAI_VLAN_ATTR_VLAN_ID && unsafe { attr.value.u16_ } == 200
+        });
+        if rejected {
+            return racoon_sai::SAI_STATUS_INVALID_PARAMETER;
+        }
+        let create = racoon_sai::mock::vlan_api_table().create_vlan.unwrap();
+        unsafe { create(vlan_oid, switch_id, attr_count, attr_list) }
+    }
+
+    #[tokio::test]
+    async fn test_batch_records_only_created_vlans() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let table = Box::leak(Box::new(racoon_sai::sai_vlan_api_t {
+            create_vlan: Some(create_vlan_rejecting_200),
+            ..racoon_sai::mock::vlan_api_table()
+        }));
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(VlanApi::new(table)),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+
+        let entries = [100, 200, 300].map(|vlanid| VlanEntry {
+            vlanid,
+            description: None,
+            admin_status: None,
+            mtu: None,
+        });
+        let results = vlan_sync.program_vlans(entries.to_vec()).await;
+        let failed: Vec<u16> = results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(vlanid, _)| *vlanid)
+            .collect();
+        assert_eq!(failed, [200]);
+        assert_eq!(results.len(), 3);
+
+        let mut recorded: Vec<u16> = vlan_sync
+            .asic_records()
+            .await
+            .unwrap()
+            .iter()
+            .map(|record| record.vlanid)
+            .collect();
+        recorded.sort();
+        assert_eq!(recorded, [100, 300]);
+        assert_eq!(vlan_sync.stats().vlan_count, 2);
+        // Intents of created and failed VLANs alike are cleared
+        assert!(
+            vlan_sync
+                .intent_log
+                .pending(VLAN_OBJECT_TYPE)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_creates_deduplicated() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        racoon_sai::mock::take_calls();
+
+        // Both creates yield on the intent log write, where they would race
+        let (first, second) = tokio::join!(
+            vlan_sync.create_vlan("Vlan100"),
+            vlan_sync.create_vlan("Vlan100")
+        );
+        first.unwrap();
+        second.unwrap();
+
+        let creates = racoon_sai::mock::take_calls()
+            .iter()
+            .filter(|call| call.function == "create_vlan")
+            .count();
+        assert_eq!(creates, 1);
+        assert_eq!(vlan_sync.stats().vlan_count, 1);
+        assert_eq!(vlan_sync.asic_records().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_waits_for_create_to_complete() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        let entry = VlanEntry {
+            vlanid: 100,
+            description: None,
+            admin_status: None,
+            mtu: None,
+        };
+
+        // The delete arrives while the create is writing its records; it
+        // must not run until they are written, or it leaves them behind
+        let (created, deleted) = tokio::join!(
+            vlan_sync.program_vlan(entry),
+            vlan_sync.delete_vlan("Vlan100")
+        );
+        created.unwrap();
+        deleted.unwrap();
+
+        assert_eq!(vlan_sync.stats().vlan_count, 0);
+        assert!(vlan_sync.asic_records().await.unwrap().is_empty());
+        assert!(
+            !db_client
+                .exists(Database::State, "VLAN_STATE:Vlan100")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_member_agent_resolves_registered_vlan() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let registry = Arc::new(ObjectRegistry::new());
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_object_registry(registry.clone());
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+
+        // The member agent only holds the registry
+        let member_agent = registry.clone();
+        let vlan_id = VlanId::new(100).unwrap();
+        assert_eq!(member_agent.vlan_oid(vlan_id), None);
+
+        vlan_sync.create_vlan("Vlan100").await.unwrap();
+        let vlan_oid = vlan_sync.asic_records().await.unwrap()[0].oid;
+        assert_eq!(member_agent.vlan_oid(vlan_id), Some(vlan_oid));
+
+        vlan_sync.delete_vlan("Vlan100").await.unwrap();
+        assert_eq!(member_agent.vlan_oid(vlan_id), None);
+    }
+
+    #[tokio::test]
+    async fn test_vlan_state_member_count() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+        vlan_sync
+            .set_bridge_port("Ethernet4", 0x3a000000000002)
+            .await;
+
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        for member in ["Vlan100:Ethernet0", "Vlan100:Ethernet4"] {
+            db_client
+                .set(
+                    Database::Appl,
+                    &format!("VLAN_MEMBER_TABLE:{}", member),
+                    &serde_json::json!({"tagging_mode": "untagged"}),
+                )
+                .await
+                .unwrap();
+        }
+        let notify = |table: &str, key: &str| {
+            serde_json::json!({"operation": "SET", "table": table, "key": key}).to_string()
+        };
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &notify("VLAN_TABLE", "Vlan100"))
+            .await;
+        for member in ["Vlan100:Ethernet0", "Vlan100:Ethernet4"] {
+            vlan_sync
+                .handle_notification("VLAN_MEMBER_TABLE", &notify("VLAN_MEMBER_TABLE", member))
+                .await;
+        }
+
+        let state: VlanState = db_client
+            .get(Database::State, "VLAN_STATE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(state.member_count, 2);
+        // No member port has reported oper status yet
+        assert_eq!(state.oper_status, "down");
+    }
+
+    #[tokio::test]
+    async fn test_startup_members_created_in_one_bulk_call() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        for (port, bridge_port) in [
+            ("Ethernet0", 0x3a000000000001),
+            ("Ethernet4", 0x3a000000000002),
+        ] {
+            db_client
+                .set(
+                    Database::Appl,
+                    &format!("VLAN_MEMBER_TABLE:Vlan100:{}", port),
+                    &serde_json::json!({"tagging_mode": "tagged"}),
+                )
+                .await
+                .unwrap();
+            vlan_sync.set_bridge_port(port, bridge_port).await;
+        }
+        racoon_sai::mock::take_calls();
+
+        vlan_sync.sync_vlans().await.unwrap();
+
+        let member_calls: Vec<_> = racoon_sai::mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function.contains("vlan_member"))
+            .collect();
+        assert_eq!(member_calls.len(), 1);
+        assert_eq!(member_calls[0].function, "create_vlan_members");
+        assert_eq!(member_calls[0].attrs, vec![(2, 0)]);
+        for member in ["Vlan100:Ethernet0", "Vlan100:Ethernet4"] {
+            let (asic_key, _) = vlan_sync.asic_member_record(member).unwrap().unwrap();
+            assert!(db_client.exists(Database::Asic, &asic_key).await.unwrap());
+        }
+        let state: VlanState = db_client
+            .get(Database::State, "VLAN_STATE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(state.member_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_member_deferred_until_its_vlan_is_programmed() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_MEMBER_TABLE:Vlan100:Ethernet0",
+                &serde_json::json!({"tagging_mode": "tagged"}),
+            )
+            .await
+            .unwrap();
+        let notify = |key: &str| serde_json::json!({"operation": "SET", "key": key}).to_string();
+        racoon_sai::mock::take_calls();
+
+        // The member arrives first and waits for its VLAN
+        vlan_sync
+            .handle_notification("VLAN_MEMBER_TABLE", &notify("Vlan100:Ethernet0"))
+            .await;
+        assert!(vlan_sync.members.is_empty());
+        assert!(racoon_sai::mock::take_calls().is_empty());
+
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &notify("Vlan100"))
+            .await;
+
+        let member = vlan_sync.members.get("Vlan100:Ethernet0").unwrap();
+        assert_eq!(member.tagging_mode, VlanTaggingMode::Tagged);
+        assert!(vlan_sync.deferred_members.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_member_deferred_until_its_port_has_a_bridge_port() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_MEMBER_TABLE:Vlan100:Ethernet0",
+                &serde_json::json!({"tagging_mode": "tagged"}),
+            )
+            .await
+            .unwrap();
+        let notify = |key: &str| serde_json::json!({"operation": "SET", "key": key}).to_string();
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &notify("Vlan100"))
+            .await;
+
+        // Ethernet0 has no bridge port; the member is neither created nor
+        // reported in VLAN_STATE
+        vlan_sync
+            .handle_notification("VLAN_MEMBER_TABLE", &notify("Vlan100:Ethernet0"))
+            .await;
+        assert!(vlan_sync.members.is_empty());
+        let state: VlanState = db_client
+            .get(Database::State, "VLAN_STATE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(state.member_count, 0);
+
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+
+        let member = vlan_sync.members.get("Vlan100:Ethernet0").unwrap();
+        assert_eq!(member.bridge_port, 0x3a000000000001);
+        drop(member);
+        let state: VlanState = db_client
+            .get(Database::State, "VLAN_STATE:Vlan100")
+            .await
+            .unwrap();
+        assert_eq!(state.member_count, 1);
+        assert!(vlan_sync.deferred_members.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oper_status_change_publishes_serdes_state() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let registry = Arc::new(ObjectRegistry::new());
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_object_registry(registry.clone())
+        .with_port_api(Arc::new(racoon_sai::mock::port_api()));
+        let port_oid = racoon_sai::mock::add_port(&[0, 1, 2, 3], 100000);
+        registry.register_port("Ethernet0", port_oid);
+
+        let message = serde_json::json!({"operation": "SET", "key": "Ethernet0"});
+        vlan_sync
+            .handle_notification(PORT_STATE_CHANNEL, &message.to_string())
+            .await;
+
+        let state: PortSerdesState = db_client
+            .get(Database::State, "PORT_SERDES_STATE:Ethernet0")
+            .await
+            .unwrap();
+        assert_eq!(state.link_training, "trained");
+        assert_eq!(state.signal_detect, "up");
+    }
+
+    #[tokio::test]
+    async fn test_bridge_port_created_for_unregistered_port() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let registry = Arc::new(ObjectRegistry::new());
+        let table = Arc::new(BridgePortTable::new());
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        )
+        .with_object_registry(registry.clone())
+        .with_bridge_ports(Arc::new(racoon_sai::mock::bridge_api()), table.clone());
+        let port_oid = racoon_sai::mock::add_port(&[0, 1, 2, 3], 100000);
+        registry.register_port("Ethernet0", port_oid);
+
+        for vlan in ["Vlan100", "Vlan200"] {
+            let vlanid: u16 = vlan[4..].parse().unwrap();
+            db_client
+                .set(
+                    Database::Appl,
+                    &format!("VLAN_TABLE:{}", vlan),
+                    &serde_json::json!({ "vlanid": vlanid }),
+                )
+                .await
+                .unwrap();
+            db_client
+                .set(
+                    Database::Appl,
+                    &format!("VLAN_MEMBER_TABLE:{}:Ethernet0", vlan),
+                    &serde_json::json!({"tagging_mode": "tagged"}),
+                )
+                .await
+                .unwrap();
+            for (channel, key) in [
+                ("VLAN_TABLE", vlan.to_string()),
+                ("VLAN_MEMBER_TABLE", format!("{}:Ethernet0", vlan)),
+            ] {
+                let message = serde_json::json!({"operation": "SET", "key": key});
+                vlan_sync
+                    .handle_notification(channel, &message.to_string())
+                    .await;
+            }
+        }
+
+        // Both memberships share one bridge port, removed with the last
+        let bridge_port = table.get(port_oid).unwrap();
+        let creates = racoon_sai::mock::take_calls()
+            .iter()
+            .filter(|call| call.function == "create_bridge_port")
+            .count();
+        assert_eq!(creates, 1);
+
+        for vlan in ["Vlan100", "Vlan200"] {
+            assert_eq!(table.get(port_oid), Some(bridge_port));
+            let key = format!("{}:Ethernet0", vlan);
+            let message = serde_json::json!({"operation": "DEL", "key": key});
+            vlan_sync
+                .handle_notification("VLAN_MEMBER_TABLE", &message.to_string())
+                .await;
+        }
+        assert_eq!(table.get(port_oid), None);
+    }
+
+    #[tokio::test]
+    async fn test_reassigned_member_changed_in_place() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_MEMBER_TABLE:Vlan100:Ethernet0",
+                &serde_json::json!({"tagging_mode": "untagged"}),
+            )
+            .await
+            .unwrap();
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+        for (table, key) in [
+            ("VLAN_TABLE", "Vlan100"),
+            ("VLAN_MEMBER_TABLE", "Vlan100:Ethernet0"),
+        ] {
+            let message = serde_json::json!({"operation": "SET", "key": key});
+            vlan_sync
+                .handle_notification(table, &message.to_string())
+                .await;
+        }
+        racoon_sai::mock::take_calls();
+
+        db_client
+            .reassign_vlan_member(
+                Database::Appl,
+                &TableKeys::default(),
+                "Vlan100",
+                "Ethernet0",
+                VlanTaggingMode::Tagged,
+            )
+            .await
+            .unwrap();
+        let delta = Notification::field_delta(
+            tables::VLAN_MEMBER_TABLE,
+            "Vlan100:Ethernet0",
+            "tagging_mode",
+            "tagged",
+        );
+        vlan_sync
+            .handle_notification(
+                tables::VLAN_MEMBER_TABLE,
+                &serde_json::to_string(&delta).unwrap(),
+            )
+            .await;
+
+        // The member is updated, not removed and created again
+        let calls = racoon_sai::mock::take_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function, "set_vlan_member_attribute");
+        assert_eq!(
+            calls[0].attrs,
+            vec![(
+                racoon_sai::SAI_VLAN_MEMBER_ATTR_VLAN_TAGGING_MODE,
+                racoon_sai::SAI_VLAN_TAGGING_MODE_TAGGED as u64
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_uncleared_intents_reconciled_on_start() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_api = Arc::new(racoon_sai::mock::vlan_api());
+
+        // A create that reached SAI and ASIC_DB but crashed before clearing
+        // its intent, and a remove that crashed right after being recorded
+        let created = vlan_api
+            .create_vlan(0x21000000000000, VlanId::new(100).unwrap())
+            .unwrap();
+        let record = AsicVlanRecord {
+            vlanid: 100,
+            oid: created,
+        };
+        db_client
+            .set(Database::Asic, &record.key(), &record)
+            .await
+            .unwrap();
+        let intent_log = IntentLog::new(db_client.clone());
+        intent_log
+            .record(&SaiIntent::create(VLAN_OBJECT_TYPE, "Vlan100").with_oid(created))
+            .await
+            .unwrap();
+        intent_log
+            .record(&SaiIntent::remove(
+                VLAN_OBJECT_TYPE,
+                "Vlan200",
+                0x26000000000200,
+            ))
+            .await
+            .unwrap();
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        racoon_sai::mock::take_calls();
+
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            vlan_api,
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        vlan_sync.start().await.unwrap();
+
+        let removed: Vec<SaiOid> = racoon_sai::mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "remove_vlan")
+            .map(|call| call.oid)
+            .collect();
+        assert_eq!(removed, vec![created, 0x26000000000200]);
+        assert!(
+            intent_log
+                .pending(VLAN_OBJECT_TYPE)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Vlan100 is still in APPL_DB, so it was programmed again
+        let records = vlan_sync.asic_records().await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].vlanid, 100);
+        assert_ne!(records[0].oid, created);
+    }
+
+    #[tokio::test]
+    async fn test_create_intent_without_oid_found_in_sai() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_api = Arc::new(racoon_sai::mock::vlan_api());
+
+        // Both creates crashed before SAI returned; only Vlan300's reached it
+        let leaked = vlan_api
+            .create_vlan(0x21000000000000, VlanId::new(300).unwrap())
+            .unwrap();
+        let intent_log = IntentLog::new(db_client.clone());
+        for vlan_name in ["Vlan300", "Vlan400"] {
+            intent_log
+                .record(&SaiIntent::create(VLAN_OBJECT_TYPE, vlan_name))
+                .await
+                .unwrap();
+        }
+        racoon_sai::mock::take_calls();
+
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            vlan_api.clone(),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        assert_eq!(vlan_sync.reconcile_intents().await.unwrap(), 2);
+
+        let removed: Vec<SaiOid> = racoon_sai::mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "remove_vlan")
+            .map(|call| call.oid)
+            .collect();
+        assert_eq!(removed, vec![leaked]);
+        assert_eq!(
+            vlan_api
+                .find_vlan(0x21000000000000, VlanId::new(300).unwrap())
+                .unwrap(),
+            None
+        );
+        assert!(
+            intent_log
+                .pending(VLAN_OBJECT_TYPE)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_vlan_removes_its_members_first() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        vlan_sync
+            .set_bridge_port("Ethernet0", 0x3a000000000001)
+            .await;
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_MEMBER_TABLE:Vlan100:Ethernet0",
+                &serde_json::json!({"tagging_mode": "tagged"}),
+            )
+            .await
+            .unwrap();
+        vlan_sync.sync_vlans().await.unwrap();
+        racoon_sai::mock::take_calls();
+
+        vlan_sync.delete_vlan("Vlan100").await.unwrap();
+        let functions: Vec<&str> = racoon_sai::mock::take_calls()
+            .iter()
+            .map(|call| call.function)
+            .filter(|function| function.starts_with("remove_"))
+            .collect();
+        assert_eq!(functions, vec!["remove_vlan_member", "remove_vlan"]);
+        assert!(vlan_sync.members.is_empty());
+
+        // Recreating the VLAN programs the member still in APPL_DB again
+        let message = serde_json::json!({"operation": "SET", "key": "Vlan100"});
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &message.to_string())
+            .await;
+        assert!(vlan_sync.members.contains_key("Vlan100:Ethernet0"));
+    }
+
+    #[tokio::test]
+    async fn test_warm_boot_adopts_restored_vlan() {
+        let vlan_api = Arc::new(racoon_sai::mock::vlan_api());
+        let restored = vlan_api
+            .create_vlan(0x21000000000000, VlanId::new(100).unwrap())
+            .unwrap();
+        let vlan_sync = test_vlan_sync().await.with_warm_boot(true);
+        racoon_sai::mock::take_calls();
+
+        let entry = VlanEntry {
+            vlanid: 100,
+            description: None,
+            admin_status: None,
+            mtu: None,
+        };
+        let (record, _) = vlan_sync
+            .create_or_update_vlan(&entry)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(record.oid, restored);
+        assert_eq!(
+            vlan_sync.registry.vlan_oid(VlanId::new(100).unwrap()),
+            Some(restored)
+        );
+        assert!(
+            racoon_sai::mock::take_calls()
+                .iter()
+                .all(|call| call.function != "create_vlan")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notifications_held_until_startup_scan() {
+        let vlan_sync = test_vlan_sync().await;
+        vlan_sync.begin_startup();
+
+        let notify = serde_json::json!({"operation": "SET", "key": "Vlan100"}).to_string();
+        vlan_sync.handle_notification("VLAN_TABLE", &notify).await;
+        assert_eq!(vlan_sync.stats().vlan_count, 0);
+        assert!(racoon_sai::mock::take_calls().is_empty());
+        assert!(vlan_sync.startup.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_event_during_startup_scan_programmed_once() {
+        let server = racoon_db_client::test_server_or_skip!();
+        let db_client = Arc::new(server.client().await);
+        let vlan_sync = VlanSync::new(
+            db_client.clone(),
+            Arc::new(racoon_sai::mock::vlan_api()),
+            SwitchContext::new(0x21000000000000, 0),
+        );
+        db_client
+            .set(
+                Database::Appl,
+                "VLAN_TABLE:Vlan100",
+                &serde_json::json!({"vlanid": 100}),
+            )
+            .await
+            .unwrap();
+
+        // Subscribed, then orchd publishes Vlan100 before the scan reads it
+        vlan_sync.begin_startup();
+        let notify = |operation: &str, key: &str| {
+            serde_json::json!({"operation": operation, "key": key}).to_string()
+        };
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &notify("SET", "Vlan100"))
+            .await;
+        vlan_sync.start().await.unwrap();
+
+        let creates = racoon_sai::mock::take_calls()
+            .into_iter()
+            .filter(|call| call.function == "create_vlan")
+            .count();
+        assert_eq!(creates, 1);
+        assert_eq!(vlan_sync.stats().vlan_count, 1);
+
+        // The window closed with the scan; notifications are handled directly
+        db_client
+            .del(Database::Appl, "VLAN_TABLE:Vlan100")
+            .await
+            .unwrap();
+        vlan_sync
+            .handle_notification("VLAN_TABLE", &notify("DEL", "Vlan100"))
+            .await;
+        assert_eq!(vlan_sync.stats().vlan_count, 0);
     }
 }